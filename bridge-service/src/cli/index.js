@@ -57,19 +57,66 @@ program
             console.log(`Deposit Address: ${depositAddress}\n`);
             console.log('To deposit ZEC and receive wZEC on Solana:');
             console.log('1. Send ZEC to the address above');
-            console.log('2. Include your Solana address in the memo field');
+            console.log('2. Put the deposit memo for your Solana address in the memo field');
+            console.log('   (run `solz deposit-memo <solana-address>` to get it, or');
+            console.log('   `solz deposit-uri <solana-address> --qr` to scan it into your wallet)');
             console.log('3. Wait for confirmations (6 blocks)');
             console.log('4. wZEC will be minted to your Solana address\n');
             console.log(`Minimum deposit: ${config.bridge.minDepositZEC} ZEC`);
             console.log(`Maximum deposit: ${config.bridge.maxDepositZEC} ZEC`);
             console.log(`Fee: ${config.bridge.feePercentage}%\n`);
-            console.log('Example memo: 9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin\n');
         } catch (error) {
             console.error('Failed to show deposit address:', error.message);
             process.exit(1);
         }
     });
 
+/**
+ * Encode a deposit memo
+ */
+program
+    .command('deposit-memo <solana-address>')
+    .description('Show the memo that credits a deposit to a Solana address')
+    .action(async (solanaAddress) => {
+        try {
+            const { encodeDepositMemo } = await import('../utils/deposit-memo.js');
+            console.log(encodeDepositMemo(solanaAddress));
+        } catch (error) {
+            console.error('Failed to encode deposit memo:', error.message);
+            process.exit(1);
+        }
+    });
+
+/**
+ * Build a ZIP-321 deposit URI
+ */
+program
+    .command('deposit-uri <solana-address>')
+    .description('Show a ZIP-321 payment URI that deposits to a Solana address')
+    .option('-a, --amount <zec>', 'Amount of ZEC to request')
+    .option('--qr', 'Also print the URI as a QR code')
+    .option('-c, --config <path>', 'Path to configuration file')
+    .action(async (solanaAddress, options) => {
+        try {
+            const { buildDepositUri, renderQrCode } = await import('../utils/payment-uri.js');
+            const config = loadConfig(options.config);
+            const uri = buildDepositUri({
+                depositAddress: config.zcash.depositAddress,
+                recipient: solanaAddress,
+                amount: options.amount,
+                label: 'SolZ Bridge deposit'
+            });
+
+            console.log(uri);
+            if (options.qr) {
+                console.log('\n' + await renderQrCode(uri));
+            }
+        } catch (error) {
+            console.error('Failed to build deposit URI:', error.message);
+            process.exit(1);
+        }
+    });
+
 /**
  * Check bridge balance and reserves
  */
@@ -240,6 +287,42 @@ program
     });
 
 // Admin commands
+/**
+ * Show a relayer's on-chain activity
+ */
+program
+    .command('relayer-stats <relayer>')
+    .description('Show the mints and finalized withdrawals a relayer has submitted on-chain')
+    .option('-c, --config <path>', 'Path to configuration file')
+    .action(async (relayer, options) => {
+        try {
+            const { Keypair } = await import('@solana/web3.js');
+            const { default: SolanaTokenManager } = await import('../solana/token-manager.js');
+            const config = loadConfig(options.config);
+            const manager = new SolanaTokenManager(config, null);
+
+            // Read-only, so no local key is needed
+            await manager.initialize(Keypair.generate());
+
+            const stats = await manager.getRelayerStats(relayer);
+            if (!stats) {
+                console.log('No relayer stats account for this key.');
+                return;
+            }
+
+            const currentSlot = await manager.connection.getSlot();
+            console.log(`Relayer:                 ${stats.relayer}`);
+            console.log(`  Mints Submitted:       ${stats.mintsSubmitted}`);
+            console.log(`  Minted Volume:         ${stats.mintedVolume.toFixed(8)} ZEC`);
+            console.log(`  Withdrawals Finalized: ${stats.withdrawalsFinalized}`);
+            console.log(`  Finalized Volume:      ${stats.finalizedVolume.toFixed(8)} ZEC`);
+            console.log(`  Last Activity Slot:    ${stats.lastActivitySlot} (${currentSlot - stats.lastActivitySlot} slots ago)`);
+        } catch (error) {
+            console.error('Failed to fetch relayer stats:', error.message);
+            process.exit(1);
+        }
+    });
+
 const admin = program.command('admin').description('Admin commands (requires authority key)');
 
 /**
@@ -321,6 +404,32 @@ admin
         }
     });
 
+/**
+ * Build an admin instruction for a Realms governance proposal
+ */
+admin
+    .command('governance-ix <instruction> [args...]')
+    .description('Print an admin instruction as a base64 Realms custom instruction')
+    .requiredOption('-g, --governance <pubkey>', 'Governance account holding the bridge authority (usually its native treasury)')
+    .option('-c, --config <path>', 'Path to configuration file')
+    .action(async (instruction, args, options) => {
+        try {
+            const { Keypair } = await import('@solana/web3.js');
+            const { default: SolanaTokenManager } = await import('../solana/token-manager.js');
+            const config = loadConfig(options.config);
+            const manager = new SolanaTokenManager(config, null);
+
+            // The governance signs when the proposal executes, so no local key is needed
+            await manager.initialize(Keypair.generate());
+
+            const ix = await manager.buildGovernanceInstruction(instruction, args, options.governance);
+            console.log(manager.serializeGovernanceInstruction(ix));
+        } catch (error) {
+            console.error('Failed to build governance instruction:', error.message);
+            process.exit(1);
+        }
+    });
+
 /**
  * Load configuration
  */
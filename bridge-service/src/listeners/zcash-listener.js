@@ -2,6 +2,7 @@ import axios from 'axios';
 import EventEmitter from 'events';
 import { getLogger } from '../utils/logger.js';
 import KeyManager from '../keymanager/wallet.js';
+import { parseDepositMemo } from '../utils/deposit-memo.js';
 
 const logger = getLogger('zcash-listener');
 
@@ -238,24 +239,24 @@ class ZcashListener extends EventEmitter {
                 memoText = Buffer.from(memoText.slice(2), 'hex').toString('utf8');
             }
 
-            // Trim whitespace
-            memoText = memoText.trim();
+            // Zcash pads text memos with zero bytes
+            memoText = memoText.replace(/\0+$/, '');
 
-            // Extract Solana address (base58, 32-44 characters)
-            // Look for base58 pattern
-            const base58Regex = /[1-9A-HJ-NP-Za-km-z]{32,44}/;
-            const match = memoText.match(base58Regex);
-
-            if (match) {
-                return match[0];
+            try {
+                const { recipient, legacy } = parseDepositMemo(memoText);
+                if (legacy) {
+                    logger.debug('Deposit uses a legacy memo without checksum', { txid: tx.txid });
+                }
+                return recipient;
+            } catch (error) {
+                logger.debug('No Solana recipient in memo', {
+                    txid: tx.txid,
+                    memo: memoText,
+                    reason: error.message
+                });
+                return null;
             }
 
-            logger.debug('No Solana address found in memo', {
-                txid: tx.txid,
-                memo: memoText
-            });
-            return null;
-
         } catch (error) {
             logger.error('Failed to parse memo', error, { txid: tx.txid });
             return null;
@@ -225,6 +225,30 @@ class BridgeOrchestrator extends EventEmitter {
                 return;
             }
 
+            // Large withdrawals stay timelocked on-chain until their unlock slot
+            if (burn.unlockSlot) {
+                const currentSlot = await this.solanaManager.connection.getSlot();
+                if (currentSlot < burn.unlockSlot) {
+                    logger.info('Withdrawal is timelocked, deferring burn processing', {
+                        signature,
+                        unlockSlot: burn.unlockSlot,
+                        currentSlot
+                    });
+                    this.database.updateBurnStatus(signature, 'CONFIRMED');
+                    return;
+                }
+            }
+
+            // Don't pay out a withdrawal the bridge won't let us finalize yet
+            if (!(await this.solanaManager.isWithinFinalizeWindow(burn.nonce))) {
+                logger.info('Withdrawal is past the finalize window, deferring burn processing', {
+                    signature,
+                    nonce: burn.nonce
+                });
+                this.database.updateBurnStatus(signature, 'CONFIRMED');
+                return;
+            }
+
             // Check reserves
             const currentReserve = bridgeState.total_locked_zec - bridgeState.total_withdrawn_zec;
             
@@ -252,7 +276,9 @@ class BridgeOrchestrator extends EventEmitter {
             const txid = await this.zcashListener.sendShieldedTransaction(
                 burn.zecAddress,
                 amountAfterFee,
-                `Withdrawal from Solana: ${signature.substring(0, 20)}`
+                burn.memo && burn.memo.length > 0
+                    ? burn.memo
+                    : `Withdrawal from Solana: ${signature.substring(0, 20)}`
             );
 
             // Record withdrawal in database
@@ -268,6 +294,15 @@ class BridgeOrchestrator extends EventEmitter {
             this.database.updateWithdrawalStatus(txid, 'SENT');
             this.database.updateBurnStatus(signature, 'COMPLETED');
 
+            // Link the burn to its payout on-chain; the ZEC is already sent,
+            // so a failure here must not fail the burn
+            try {
+                const payoutHeight = await this.zcashListener.rpcCall('getblockcount');
+                await this.solanaManager.finalizeWithdrawal(burn.nonce, txid, payoutHeight);
+            } catch (error) {
+                logger.error('Failed to record payout on-chain', error, { signature, txid });
+            }
+
             // Update bridge reserves
             await this.updateReserves();
 
@@ -341,7 +376,8 @@ class BridgeOrchestrator extends EventEmitter {
                             signature: burn.signature,
                             amount: burn.amount,
                             sender: burn.sender,
-                            zecAddress: burn.zec_destination
+                            zecAddress: burn.zec_destination,
+                            memo: Buffer.from(burn.memo || '', 'hex')
                         });
                     }
                 }
@@ -1,25 +1,64 @@
 import { 
     Connection, 
+    Ed25519Program,
     PublicKey, 
     Transaction,
     SystemProgram,
+    SYSVAR_INSTRUCTIONS_PUBKEY,
+    SYSVAR_RENT_PUBKEY,
     LAMPORTS_PER_SOL
 } from '@solana/web3.js';
 import { 
     TOKEN_PROGRAM_ID,
+    ASSOCIATED_TOKEN_PROGRAM_ID,
     createMint,
+    getAssociatedTokenAddressSync,
     getOrCreateAssociatedTokenAccount,
-    mintTo,
     getAccount
 } from '@solana/spl-token';
-import { Program, AnchorProvider, web3, BN } from '@coral-xyz/anchor';
+import { Program, AnchorProvider, BorshCoder, EventParser, web3, BN } from '@coral-xyz/anchor';
 import EventEmitter from 'events';
 import { getLogger } from '../utils/logger.js';
 import KeyManager from '../keymanager/wallet.js';
 import fs from 'fs';
+import crypto from 'crypto';
 
 const logger = getLogger('solana-manager');
 
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
+const MEMO_PROGRAM_ID = new PublicKey('MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr');
+
+// Admin instructions whose only accounts are the bridge state and its authority,
+// so a governance proposal can carry them with the governance as the signer
+const GOVERNANCE_INSTRUCTIONS = [
+    'pauseBridge',
+    'resumeBridge',
+    'setPauseFlags',
+    'updateMintFee',
+    'updateBurnFee',
+    'updateCancellationFee',
+    'updateInsuranceShare',
+    'updateTransactionLimits',
+    'updateDailyMintLimit',
+    'updateMinBurnAmount',
+    'updateWithdrawalTimelock',
+    'updateUsdLimits',
+    'updateCircuitBreaker',
+    'updateInsuranceUnstakeSlots',
+    'updateRetentionSlots',
+    'updateFinalizeWindow',
+    'updateTimelockSlots',
+    'updateCompliance',
+    'updateTreasury',
+    'updateDepositAddress',
+    'updateHeartbeatGap',
+    'heartbeat',
+    'setRole',
+    'proposeAuthority',
+    'cancelAuthorityProposal',
+    'decommissionBridge'
+];
+
 /**
  * Solana Token Manager - Handles wZEC minting and burn monitoring
  */
@@ -32,7 +71,12 @@ class SolanaTokenManager extends EventEmitter {
         this.provider = null;
         this.program = null;
         this.mintAddress = null;
+        this.tokenProgramId = TOKEN_PROGRAM_ID;
         this.bridgeStateAddress = null;
+        this.bridgeStatsAddress = null;
+        this.guardianSetAddress = null;
+        this.mintAuthorityAddress = null;
+        this.singletonReceipts = false;
         this.isListening = false;
         this.subscriptionId = null;
     }
@@ -69,17 +113,25 @@ class SolanaTokenManager extends EventEmitter {
                 { commitment: 'confirmed' }
             );
 
-            // Load program IDL if available
-            await this.loadProgram();
-
             // Set mint address
             if (this.config.solana.mintAddress) {
                 this.mintAddress = new PublicKey(this.config.solana.mintAddress);
+
+                // The mint may live under either SPL Token or Token-2022
+                const mintInfo = await this.connection.getAccountInfo(this.mintAddress);
+                if (mintInfo) {
+                    this.tokenProgramId = mintInfo.owner;
+                }
+
                 logger.info('Mint address configured', {
-                    mint: this.mintAddress.toBase58()
+                    mint: this.mintAddress.toBase58(),
+                    tokenProgram: this.tokenProgramId.toBase58()
                 });
             }
 
+            // Load program IDL if available; bridge PDAs are keyed by the mint
+            await this.loadProgram();
+
             // Check connection
             const version = await this.connection.getVersion();
             logger.info('Connected to Solana', {
@@ -114,16 +166,47 @@ class SolanaTokenManager extends EventEmitter {
                 const idl = JSON.parse(fs.readFileSync(idlPath, 'utf8'));
                 this.program = new Program(idl, programId, this.provider);
                 
-                // Derive bridge state PDA
+                if (!this.mintAddress) {
+                    logger.warn('Mint address not configured, bridge accounts unavailable');
+                    return;
+                }
+
+                // Derive bridge state PDA for this mint's bridge instance
                 const [bridgeState] = PublicKey.findProgramAddressSync(
-                    [Buffer.from('bridge_state')],
+                    [Buffer.from('bridge_state'), this.mintAddress.toBuffer()],
                     programId
                 );
                 this.bridgeStateAddress = bridgeState;
 
+                // Derive bridge stats PDA
+                const [bridgeStats] = PublicKey.findProgramAddressSync(
+                    [Buffer.from('bridge_stats'), bridgeState.toBuffer()],
+                    programId
+                );
+                this.bridgeStatsAddress = bridgeStats;
+
+                // Derive guardian set PDA
+                const [guardianSet] = PublicKey.findProgramAddressSync(
+                    [Buffer.from('guardian_set'), bridgeState.toBuffer()],
+                    programId
+                );
+                this.guardianSetAddress = guardianSet;
+
+                // Derive the PDA that holds the wZEC mint authority
+                const [mintAuthority] = PublicKey.findProgramAddressSync(
+                    [Buffer.from('mint_authority'), this.mintAddress.toBuffer()],
+                    programId
+                );
+                this.mintAuthorityAddress = mintAuthority;
+
+                // Bridges moved off the singleton PDAs keep older receipts under its seeds
+                const state = await this.program.account.bridgeState.fetchNullable(bridgeState);
+                this.singletonReceipts = Boolean(state && state.singletonReceipts);
+
                 logger.info('Anchor program loaded', {
                     programId: programId.toBase58(),
-                    bridgeState: bridgeState.toBase58()
+                    bridgeState: bridgeState.toBase58(),
+                    guardianSet: guardianSet.toBase58()
                 });
             } else {
                 logger.warn('IDL file not found, program features unavailable');
@@ -136,7 +219,7 @@ class SolanaTokenManager extends EventEmitter {
     /**
      * Initialize bridge (first-time setup)
      */
-    async initializeBridge(feePercentage = 10) {
+    async initializeBridge(mintFeeBps = 0, burnFeeBps = 10) {
         try {
             if (!this.program) {
                 throw new Error('Program not loaded');
@@ -148,14 +231,57 @@ class SolanaTokenManager extends EventEmitter {
 
             logger.info('Initializing bridge on-chain...');
 
+            // Create the wZEC metadata in the same transaction so wallets display it
+            const postInstructions = [];
+            const metadataConfig = this.config.solana.metadata;
+            if (metadataConfig) {
+                const [metadata] = PublicKey.findProgramAddressSync(
+                    [
+                        Buffer.from('metadata'),
+                        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+                        this.mintAddress.toBuffer()
+                    ],
+                    TOKEN_METADATA_PROGRAM_ID
+                );
+
+                postInstructions.push(await this.program.methods
+                    .createMetadata(metadataConfig.name, metadataConfig.symbol, metadataConfig.uri)
+                    .accounts({
+                        bridgeState: this.bridgeStateAddress,
+                        mint: this.mintAddress,
+                        metadata,
+                        mintAuthority: this.mintAuthorityAddress,
+                        authority: this.provider.wallet.publicKey,
+                        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                        systemProgram: SystemProgram.programId,
+                        rent: SYSVAR_RENT_PUBKEY
+                    })
+                    .instruction());
+            }
+
             const tx = await this.program.methods
-                .initialize(feePercentage)
+                .initialize(
+                    mintFeeBps,
+                    burnFeeBps,
+                    { [this.config.zcash.network]: {} }
+                )
                 .accounts({
                     bridgeState: this.bridgeStateAddress,
+                    bridgeStats: this.bridgeStatsAddress,
                     mint: this.mintAddress,
+                    mintAuthority: this.mintAuthorityAddress,
+                    feeVault: getAssociatedTokenAddressSync(
+                        this.mintAddress,
+                        this.bridgeStateAddress,
+                        true,
+                        this.tokenProgramId
+                    ),
                     authority: this.provider.wallet.publicKey,
+                    tokenProgram: this.tokenProgramId,
+                    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
                     systemProgram: SystemProgram.programId
                 })
+                .postInstructions(postInstructions)
                 .rpc();
 
             logger.info('Bridge initialized on-chain', { signature: tx });
@@ -169,8 +295,11 @@ class SolanaTokenManager extends EventEmitter {
 
     /**
      * Mint wZEC tokens to recipient
+     *
+     * The mint transaction carries a memo with the Zcash txid, and the block
+     * height when one is known, so explorers show where the deposit came from
      */
-    async mintWZEC(recipient, amount, zcashTxid) {
+    async mintWZEC(recipient, amount, zcashTxid, blockHeight = null) {
         const startTime = Date.now();
         
         try {
@@ -191,38 +320,54 @@ class SolanaTokenManager extends EventEmitter {
                 this.connection,
                 this.provider.wallet.payer || this.provider.wallet,
                 this.mintAddress,
-                recipientPubkey
+                recipientPubkey,
+                false,
+                'confirmed',
+                undefined,
+                this.tokenProgramId
             );
 
             // Convert amount to token units (assuming 8 decimals like ZEC)
             const amountInTokenUnits = Math.floor(amount * 100000000);
 
-            let signature;
-
-            // Use program if available, otherwise use direct SPL token mint
-            if (this.program) {
-                signature = await this.program.methods
-                    .mintWzec(new BN(amountInTokenUnits), zcashTxid)
-                    .accounts({
-                        bridgeState: this.bridgeStateAddress,
-                        mint: this.mintAddress,
-                        recipientTokenAccount: recipientTokenAccount.address,
-                        authority: this.provider.wallet.publicKey,
-                        tokenProgram: TOKEN_PROGRAM_ID
-                    })
-                    .rpc();
-            } else {
-                // Fallback to direct minting
-                signature = await mintTo(
-                    this.connection,
-                    this.provider.wallet.payer || this.provider.wallet,
-                    this.mintAddress,
-                    recipientTokenAccount.address,
-                    this.provider.wallet.publicKey,
-                    amountInTokenUnits
-                );
+            // Only the program can mint: the mint authority is its PDA
+            if (!this.program) {
+                throw new Error('Program not loaded');
             }
 
+            const signature = await this.program.methods
+                .mintWzec(new BN(amountInTokenUnits), [...this.txidBytes(zcashTxid)], blockHeight)
+                .accounts({
+                    bridgeState: this.bridgeStateAddress,
+                    bridgeStats: this.bridgeStatsAddress,
+                    guardianSet: this.guardianSetAddress,
+                    depositReceipt: this.getDepositReceiptAddress(zcashTxid),
+                    legacyDepositReceipt: this.getLegacyDepositReceiptAddress(zcashTxid),
+                    mint: this.mintAddress,
+                    recipientTokenAccount: recipientTokenAccount.address,
+                    userLimits: this.getUserLimitsAddress(recipientPubkey),
+                    denyListEntry: this.getDenyListEntryAddress(recipientPubkey),
+                    feeVault: getAssociatedTokenAddressSync(
+                        this.mintAddress,
+                        this.bridgeStateAddress,
+                        true,
+                        this.tokenProgramId
+                    ),
+                    mintAuthority: this.mintAuthorityAddress,
+                    authority: this.provider.wallet.publicKey,
+                    tokenProgram: this.tokenProgramId,
+                    systemProgram: SystemProgram.programId,
+                    instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+                    memoProgram: MEMO_PROGRAM_ID
+                })
+                // The bridge wallet attests as a guardian of the set
+                .remainingAccounts([{
+                    pubkey: this.provider.wallet.publicKey,
+                    isSigner: true,
+                    isWritable: false
+                }])
+                .rpc();
+
             logger.logMint(signature, amount, recipient, zcashTxid);
 
             const duration = Date.now() - startTime;
@@ -245,6 +390,392 @@ class SolanaTokenManager extends EventEmitter {
         }
     }
 
+    /**
+     * Mint wZEC for several confirmed deposits in a single transaction
+     */
+    async mintWZECBatch(deposits) {
+        const startTime = Date.now();
+
+        try {
+            if (!this.program) {
+                throw new Error('Program not loaded');
+            }
+
+            logger.info('Minting wZEC batch', { count: deposits.length });
+
+            const entries = [];
+            const remainingAccounts = [];
+            for (const { recipient, amount, zcashTxid } of deposits) {
+                const recipientPubkey = new PublicKey(recipient);
+                const recipientTokenAccount = await getOrCreateAssociatedTokenAccount(
+                    this.connection,
+                    this.provider.wallet.payer || this.provider.wallet,
+                    this.mintAddress,
+                    recipientPubkey,
+                    false,
+                    'confirmed',
+                    undefined,
+                    this.tokenProgramId
+                );
+
+                entries.push({
+                    amount: new BN(Math.floor(amount * 100000000)),
+                    zcashTxid: [...this.txidBytes(zcashTxid)]
+                });
+                remainingAccounts.push(
+                    { pubkey: recipientTokenAccount.address, isSigner: false, isWritable: true },
+                    { pubkey: this.getDepositReceiptAddress(zcashTxid), isSigner: false, isWritable: true },
+                    { pubkey: this.getLegacyDepositReceiptAddress(zcashTxid), isSigner: false, isWritable: false },
+                    { pubkey: this.getUserLimitsAddress(recipientPubkey), isSigner: false, isWritable: true },
+                    { pubkey: this.getDenyListEntryAddress(recipientPubkey), isSigner: false, isWritable: false }
+                );
+            }
+
+            // The bridge wallet attests as a guardian of the set
+            remainingAccounts.push({
+                pubkey: this.provider.wallet.publicKey,
+                isSigner: true,
+                isWritable: false
+            });
+
+            const signature = await this.program.methods
+                .mintWzecBatch(entries)
+                .accounts({
+                    bridgeState: this.bridgeStateAddress,
+                    bridgeStats: this.bridgeStatsAddress,
+                    guardianSet: this.guardianSetAddress,
+                    mint: this.mintAddress,
+                    feeVault: getAssociatedTokenAddressSync(
+                        this.mintAddress,
+                        this.bridgeStateAddress,
+                        true,
+                        this.tokenProgramId
+                    ),
+                    mintAuthority: this.mintAuthorityAddress,
+                    authority: this.provider.wallet.publicKey,
+                    tokenProgram: this.tokenProgramId,
+                    systemProgram: SystemProgram.programId,
+                    instructions: SYSVAR_INSTRUCTIONS_PUBKEY
+                })
+                .remainingAccounts(remainingAccounts)
+                .rpc();
+
+            for (const { recipient, amount, zcashTxid } of deposits) {
+                logger.logMint(signature, amount, recipient, zcashTxid);
+            }
+
+            const duration = Date.now() - startTime;
+            logger.logPerformance('mintWZECBatch', duration, true, {
+                count: deposits.length,
+                signature
+            });
+
+            return signature;
+
+        } catch (error) {
+            const duration = Date.now() - startTime;
+            logger.logPerformance('mintWZECBatch', duration, false);
+            logger.error('Failed to mint wZEC batch', error, {
+                count: deposits.length
+            });
+            throw error;
+        }
+    }
+
+    /**
+     * Whether withdrawal `nonce` may be settled yet: with a finalize window set,
+     * the bridge refuses nonces a full window or more past the oldest unsettled one
+     */
+    async isWithinFinalizeWindow(nonce) {
+        if (!this.program) {
+            throw new Error('Program not loaded');
+        }
+
+        const state = await this.program.account.bridgeState.fetch(this.bridgeStateAddress);
+        if (state.finalizeWindow === 0) {
+            return true;
+        }
+        return new BN(nonce).lt(state.settledFloor.addn(state.finalizeWindow));
+    }
+
+    /**
+     * Mark an on-chain withdrawal request as paid with its Zcash payout txid,
+     * and its output index when the payout is shared with other withdrawals
+     */
+    async finalizeWithdrawal(nonce, payoutTxid, payoutHeight, payoutOutput = null) {
+        try {
+            if (!this.program) {
+                throw new Error('Program not loaded');
+            }
+
+            const [withdrawalRequest] = PublicKey.findProgramAddressSync(
+                [
+                    Buffer.from('withdrawal'),
+                    this.bridgeStateAddress.toBuffer(),
+                    new BN(nonce).toArrayLike(Buffer, 'le', 8)
+                ],
+                this.program.programId
+            );
+
+            const tx = await this.program.methods
+                .finalizeWithdrawal(Array.from(Buffer.from(payoutTxid, 'hex')), payoutHeight, payoutOutput)
+                .accounts({
+                    bridgeState: this.bridgeStateAddress,
+                    withdrawalRequest,
+                    authority: this.provider.wallet.publicKey
+                })
+                .rpc();
+
+            logger.info('Withdrawal finalized on-chain', {
+                nonce,
+                payoutTxid,
+                signature: tx
+            });
+            return tx;
+
+        } catch (error) {
+            logger.error('Failed to finalize withdrawal', error, { nonce, payoutTxid });
+            throw error;
+        }
+    }
+
+    /**
+     * Submit a burn permit signed off-chain by a token owner without SOL, paying
+     * the Solana fees from the bridge wallet in exchange for the permit's wZEC
+     * relayer fee. `permit` holds the owner, zecAddress, amount, relayerFee, nonce
+     * and expiry it was signed over (amounts in token units) and the hex signature.
+     */
+    async submitBurnPermit(permit) {
+        try {
+            if (!this.program) {
+                throw new Error('Program not loaded');
+            }
+
+            const minRelayerFee = new BN(this.config.solana?.minPermitRelayerFee || 0);
+            if (new BN(permit.relayerFee).lt(minRelayerFee)) {
+                throw new Error(`Relayer fee below the ${minRelayerFee.toString()} minimum`);
+            }
+
+            const owner = new PublicKey(permit.owner);
+            const relayer = this.provider.wallet.publicKey;
+            const state = await this.program.account.bridgeState.fetch(this.bridgeStateAddress);
+
+            // Check the permit is next in line, so a stale one doesn't cost us a failed transaction
+            const permitNonce = await this.program.account.permitNonce.fetchNullable(
+                this.getPermitNonceAddress(owner)
+            );
+            const nextNonce = permitNonce ? permitNonce.nextNonce : new BN(0);
+            if (!nextNonce.eq(new BN(permit.nonce))) {
+                throw new Error(`Permit nonce ${permit.nonce} is not the owner's next nonce ${nextNonce.toString()}`);
+            }
+
+            const relayerTokenAccount = await getOrCreateAssociatedTokenAccount(
+                this.connection,
+                this.provider.wallet.payer || this.provider.wallet,
+                this.mintAddress,
+                relayer,
+                false,
+                'confirmed',
+                undefined,
+                this.tokenProgramId
+            );
+
+            const [withdrawalRequest] = PublicKey.findProgramAddressSync(
+                [
+                    Buffer.from('withdrawal'),
+                    this.bridgeStateAddress.toBuffer(),
+                    state.withdrawalNonce.toArrayLike(Buffer, 'le', 8)
+                ],
+                this.program.programId
+            );
+
+            const signatureInstruction = Ed25519Program.createInstructionWithPublicKey({
+                publicKey: owner.toBytes(),
+                message: this.burnPermitMessage(permit, relayer),
+                signature: Buffer.from(permit.signature, 'hex')
+            });
+
+            const tx = await this.program.methods
+                .burnWzecWithPermit(
+                    new BN(permit.amount),
+                    permit.zecAddress,
+                    new BN(permit.relayerFee),
+                    new BN(permit.nonce),
+                    new BN(permit.expiry)
+                )
+                .accounts({
+                    bridgeState: this.bridgeStateAddress,
+                    bridgeStats: this.bridgeStatsAddress,
+                    withdrawalRequest,
+                    mint: this.mintAddress,
+                    userTokenAccount: getAssociatedTokenAddressSync(
+                        this.mintAddress,
+                        owner,
+                        true,
+                        this.tokenProgramId
+                    ),
+                    feeVault: getAssociatedTokenAddressSync(
+                        this.mintAddress,
+                        this.bridgeStateAddress,
+                        true,
+                        this.tokenProgramId
+                    ),
+                    insuranceVault: state.insuranceShareBps > 0 ? state.insuranceVault : null,
+                    userLimits: this.getUserLimitsAddress(owner),
+                    denyListEntry: this.getDenyListEntryAddress(owner),
+                    permitNonce: this.getPermitNonceAddress(owner),
+                    owner,
+                    relayerTokenAccount: relayerTokenAccount.address,
+                    relayer,
+                    instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+                    tokenProgram: this.tokenProgramId,
+                    systemProgram: SystemProgram.programId
+                })
+                .preInstructions([signatureInstruction])
+                .rpc();
+
+            logger.info('Burn permit submitted', {
+                owner: permit.owner,
+                nonce: permit.nonce,
+                relayerFee: permit.relayerFee,
+                signature: tx
+            });
+            return tx;
+
+        } catch (error) {
+            logger.error('Failed to submit burn permit', error, {
+                owner: permit.owner,
+                nonce: permit.nonce
+            });
+            throw error;
+        }
+    }
+
+    /**
+     * Build the message an owner signs to let `relayer` burn for them, matching
+     * `PermitNonce::message` in the program
+     */
+    burnPermitMessage(permit, relayer) {
+        const network = this.config.zcash?.network || 'testnet';
+        return Buffer.concat([
+            Buffer.from(`solz-burn-permit-${network}`),
+            this.bridgeStateAddress.toBuffer(),
+            new PublicKey(permit.owner).toBuffer(),
+            new PublicKey(relayer).toBuffer(),
+            new BN(permit.amount).toArrayLike(Buffer, 'le', 8),
+            new BN(permit.relayerFee).toArrayLike(Buffer, 'le', 8),
+            new BN(permit.nonce).toArrayLike(Buffer, 'le', 8),
+            new BN(permit.expiry).toTwos(64).toArrayLike(Buffer, 'le', 8),
+            Buffer.from(permit.zecAddress)
+        ]);
+    }
+
+    /**
+     * Convert a txid from the hex form zcashd and block explorers show to the
+     * internal byte order the program expects
+     */
+    txidBytes(zcashTxid) {
+        if (!/^[0-9a-fA-F]{64}$/.test(zcashTxid)) {
+            throw new Error(`Invalid Zcash txid: ${zcashTxid}`);
+        }
+        return Buffer.from(zcashTxid, 'hex').reverse();
+    }
+
+    /**
+     * Derive the deposit receipt PDA that guards a Zcash txid against double minting
+     */
+    getDepositReceiptAddress(zcashTxid) {
+        const [depositReceipt] = PublicKey.findProgramAddressSync(
+            [Buffer.from('deposit'), this.bridgeStateAddress.toBuffer(), this.txidBytes(zcashTxid)],
+            this.program.programId
+        );
+        return depositReceipt;
+    }
+
+    /**
+     * Derive the receipt PDA a txid had before receipts were seeded by its raw bytes,
+     * which the program checks is still empty. Bridges moved off the singleton PDAs
+     * left the state key out of those seeds.
+     */
+    getLegacyDepositReceiptAddress(zcashTxid) {
+        const displayHex = this.txidBytes(zcashTxid).reverse().toString('hex');
+        const txidHash = crypto.createHash('sha256').update(displayHex).digest();
+        const seeds = this.singletonReceipts
+            ? [Buffer.from('deposit'), txidHash]
+            : [Buffer.from('deposit'), this.bridgeStateAddress.toBuffer(), txidHash];
+        const [depositReceipt] = PublicKey.findProgramAddressSync(seeds, this.program.programId);
+        return depositReceipt;
+    }
+
+    /**
+     * Derive the optional per-user limits PDA for a wallet
+     */
+    getUserLimitsAddress(owner) {
+        const [userLimits] = PublicKey.findProgramAddressSync(
+            [Buffer.from('user_limits'), this.bridgeStateAddress.toBuffer(), owner.toBuffer()],
+            this.program.programId
+        );
+        return userLimits;
+    }
+
+    /**
+     * Derive the deny list entry PDA for a wallet
+     */
+    getDenyListEntryAddress(owner) {
+        const [denyListEntry] = PublicKey.findProgramAddressSync(
+            [Buffer.from('deny_list'), this.bridgeStateAddress.toBuffer(), owner.toBuffer()],
+            this.program.programId
+        );
+        return denyListEntry;
+    }
+
+    /**
+     * Derive the PDA tracking a wallet's next burn permit nonce
+     */
+    getPermitNonceAddress(owner) {
+        const [permitNonce] = PublicKey.findProgramAddressSync(
+            [Buffer.from('permit_nonce'), this.bridgeStateAddress.toBuffer(), owner.toBuffer()],
+            this.program.programId
+        );
+        return permitNonce;
+    }
+
+    /**
+     * Derive the PDA counting a relayer's mints and finalized withdrawals
+     */
+    getRelayerStatsAddress(relayer) {
+        const [relayerStats] = PublicKey.findProgramAddressSync(
+            [Buffer.from('relayer_stats'), this.bridgeStateAddress.toBuffer(), relayer.toBuffer()],
+            this.program.programId
+        );
+        return relayerStats;
+    }
+
+    /**
+     * Fetch a relayer's on-chain activity counters, or null if it has none
+     */
+    async getRelayerStats(relayer) {
+        if (!this.program) {
+            throw new Error('Program not loaded');
+        }
+
+        const stats = await this.program.account.relayerStats.fetchNullable(
+            this.getRelayerStatsAddress(new PublicKey(relayer))
+        );
+        if (!stats) {
+            return null;
+        }
+
+        return {
+            relayer: stats.relayer.toBase58(),
+            mintsSubmitted: stats.mintsSubmitted.toNumber(),
+            mintedVolume: stats.mintedVolume.toNumber() / 100000000,
+            withdrawalsFinalized: stats.withdrawalsFinalized.toNumber(),
+            finalizedVolume: stats.finalizedVolume.toNumber() / 100000000,
+            lastActivitySlot: stats.lastActivitySlot.toNumber()
+        };
+    }
+
     /**
      * Start listening for burn transactions
      */
@@ -270,6 +801,18 @@ class SolanaTokenManager extends EventEmitter {
 
         logger.info(`Listening for burns (polling every ${pollInterval}ms)`);
 
+        // Keep the on-chain dead-man's switch from firing while we are alive
+        const heartbeatInterval = this.config.solana.heartbeatIntervalMs;
+        if (this.program && heartbeatInterval) {
+            this.heartbeatInterval = setInterval(async () => {
+                try {
+                    await this.sendHeartbeat();
+                } catch (error) {
+                    logger.error('Error sending heartbeat', error);
+                }
+            }, heartbeatInterval);
+        }
+
         // Initial check
         await this.checkForBurns();
     }
@@ -289,6 +832,11 @@ class SolanaTokenManager extends EventEmitter {
             this.burnPollInterval = null;
         }
 
+        if (this.heartbeatInterval) {
+            clearInterval(this.heartbeatInterval);
+            this.heartbeatInterval = null;
+        }
+
         if (this.subscriptionId !== null) {
             this.connection.removeAccountChangeListener(this.subscriptionId);
             this.subscriptionId = null;
@@ -343,23 +891,10 @@ class SolanaTokenManager extends EventEmitter {
                 return;
             }
 
-            // Parse logs to find burn event
-            const logs = tx.meta.logMessages || [];
-            
-            // Look for burn-related logs
-            const burnLog = logs.find(log => 
-                log.includes('Burned') && log.includes('wZEC')
-            );
-
-            if (!burnLog) {
-                return;
-            }
-
-            // Parse burn details from logs
+            // Parse burn details from the emitted BurnEvent
             const burnDetails = await this.parseBurnMemo(signature, tx);
             
             if (!burnDetails) {
-                logger.warn('Could not parse burn details', { signature });
                 return;
             }
 
@@ -381,7 +916,7 @@ class SolanaTokenManager extends EventEmitter {
                 burnDetails.amount,
                 burnDetails.sender,
                 burnDetails.zecAddress,
-                burnDetails.memo || ''
+                burnDetails.memo.toString('hex')
             );
 
             this.database.updateBurnStatus(signature, 'CONFIRMED');
@@ -430,37 +965,36 @@ class SolanaTokenManager extends EventEmitter {
             }
 
             const logs = tx.meta.logMessages || [];
-            
-            // Extract amount, sender, and ZEC address from logs
-            let amount = 0;
-            let sender = null;
-            let zecAddress = null;
-
-            // Parse program logs
-            for (const log of logs) {
-                // Look for "Burned X wZEC from Y"
-                const burnMatch = log.match(/Burned (\d+) wZEC from (\w+)/);
-                if (burnMatch) {
-                    amount = parseInt(burnMatch[1]) / 100000000; // Convert from token units
-                    sender = burnMatch[2];
-                }
 
-                // Look for "ZEC destination: ztestsapling1..."
-                const destMatch = log.match(/ZEC destination: (ztestsapling1\w+)/);
-                if (destMatch) {
-                    zecAddress = destMatch[1];
+            // Decode Anchor events emitted by the program
+            const eventParser = new EventParser(
+                this.program.programId,
+                new BorshCoder(this.program.idl)
+            );
+
+            let burnEvent = null;
+            for (const event of eventParser.parseLogs(logs)) {
+                if (event.name === 'BurnEvent') {
+                    burnEvent = event.data;
+                    break;
                 }
             }
 
-            if (!amount || !sender || !zecAddress) {
+            if (!burnEvent) {
                 return null;
             }
 
+            const amount = burnEvent.amount.toNumber() / 100000000; // Convert from token units
+            const sender = burnEvent.user.toBase58();
+            const zecAddress = burnEvent.zecAddress;
+
             return {
                 amount,
                 sender,
                 zecAddress,
-                memo: zecAddress
+                nonce: burnEvent.nonce.toNumber(),
+                unlockSlot: burnEvent.unlockSlot.toNumber(),
+                memo: Buffer.from(burnEvent.memo)
             };
 
         } catch (error) {
@@ -515,15 +1049,25 @@ class SolanaTokenManager extends EventEmitter {
             const state = await this.program.account.bridgeState.fetch(
                 this.bridgeStateAddress
             );
+            const stats = await this.program.account.bridgeStats.fetch(
+                this.bridgeStatsAddress
+            );
 
             return {
+                version: state.version,
                 authority: state.authority.toBase58(),
                 mint: state.mint.toBase58(),
-                feePercentage: state.feePercentage,
-                paused: state.paused,
-                totalMinted: state.totalMinted.toNumber() / 100000000,
-                totalBurned: state.totalBurned.toNumber() / 100000000,
-                feeCollected: state.feeCollected.toNumber() / 100000000
+                mintFeeBps: state.mintFeeBps,
+                burnFeeBps: state.burnFeeBps,
+                paused: state.mintsPaused && state.burnsPaused,
+                mintsPaused: state.mintsPaused,
+                burnsPaused: state.burnsPaused,
+                guardianPausedBy: state.guardianPausedBy.equals(PublicKey.default)
+                    ? null
+                    : state.guardianPausedBy.toBase58(),
+                totalMinted: stats.totalMinted.toNumber() / 100000000,
+                totalBurned: stats.totalBurned.toNumber() / 100000000,
+                feeCollected: stats.feeCollected.toNumber() / 100000000
             };
 
         } catch (error) {
@@ -532,6 +1076,101 @@ class SolanaTokenManager extends EventEmitter {
         }
     }
 
+    /**
+     * Send an authority heartbeat to the bridge program
+     */
+    async sendHeartbeat() {
+        const tx = await this.program.methods
+            .heartbeat()
+            .accounts({
+                bridgeState: this.bridgeStateAddress,
+                authority: this.provider.wallet.publicKey
+            })
+            .rpc();
+
+        logger.debug('Heartbeat sent', { signature: tx });
+        return tx;
+    }
+
+    /**
+     * Build an admin instruction with `authority` as its signer, for a Realms
+     * proposal whose governance (usually its native treasury) holds the bridge
+     * authority. String `args` are converted using the instruction's IDL types.
+     */
+    async buildGovernanceInstruction(name, args, authority) {
+        if (!this.program) {
+            throw new Error('Program not loaded');
+        }
+        if (!GOVERNANCE_INSTRUCTIONS.includes(name)) {
+            throw new Error(`Unsupported governance instruction: ${name}`);
+        }
+
+        const idlInstruction = this.program.idl.instructions.find((ix) => ix.name === name);
+        if (args.length !== idlInstruction.args.length) {
+            throw new Error(
+                `${name} takes ${idlInstruction.args.length} arguments: ` +
+                idlInstruction.args.map((arg) => arg.name).join(', ')
+            );
+        }
+        const values = idlInstruction.args.map((arg, i) => this.parseInstructionArg(arg.type, args[i]));
+
+        return this.program.methods[name](...values)
+            .accounts({
+                bridgeState: this.bridgeStateAddress,
+                authority: new PublicKey(authority)
+            })
+            .instruction();
+    }
+
+    /**
+     * Convert a command-line string to the value an IDL argument type expects
+     */
+    parseInstructionArg(type, value) {
+        switch (type) {
+            case 'u8':
+            case 'u16':
+            case 'u32':
+                return Number(value);
+            case 'u64':
+                return new BN(value);
+            case 'bool':
+                return value === 'true';
+            case 'publicKey':
+                return new PublicKey(value);
+            default:
+                if (type.array) {
+                    return [...Buffer.from(value, 'hex')];
+                }
+                if (type.defined) {
+                    // Enum variants are given by name, e.g. `pauser`
+                    return { [value]: {} };
+                }
+                throw new Error(`Unsupported argument type: ${JSON.stringify(type)}`);
+        }
+    }
+
+    /**
+     * Serialize an instruction as the base64 `InstructionData` that Realms takes
+     * for a custom instruction: program id, account metas, then the data
+     */
+    serializeGovernanceInstruction(instruction) {
+        const accounts = instruction.keys.map(({ pubkey, isSigner, isWritable }) =>
+            Buffer.concat([pubkey.toBuffer(), Buffer.from([isSigner ? 1 : 0, isWritable ? 1 : 0])])
+        );
+        const accountCount = Buffer.alloc(4);
+        accountCount.writeUInt32LE(accounts.length);
+        const dataLength = Buffer.alloc(4);
+        dataLength.writeUInt32LE(instruction.data.length);
+
+        return Buffer.concat([
+            instruction.programId.toBuffer(),
+            accountCount,
+            ...accounts,
+            dataLength,
+            instruction.data
+        ]).toString('base64');
+    }
+
     /**
      * Pause bridge (admin only)
      */
@@ -0,0 +1,78 @@
+import { PublicKey } from '@solana/web3.js';
+import { utils } from '@coral-xyz/anchor';
+import crypto from 'crypto';
+
+const { bs58 } = utils.bytes;
+
+/**
+ * Deposit memo protocol
+ *
+ * A deposit memo is the base58 encoding of
+ *
+ *     version (1 byte) || recipient (32 bytes) || checksum (4 bytes)
+ *
+ * where the checksum is the first 4 bytes of SHA-256(version || recipient).
+ * The relayer parses the same format (relayer/src/memo.rs). A bare base58
+ * public key is still accepted as the legacy, unchecksummed form.
+ */
+export const MEMO_VERSION = 1;
+const CHECKSUM_LENGTH = 4;
+const ENCODED_LENGTH = 1 + 32 + CHECKSUM_LENGTH;
+
+export class DepositMemoError extends Error {
+    constructor(message) {
+        super(message);
+        this.name = 'DepositMemoError';
+    }
+}
+
+function checksum(payload) {
+    return crypto.createHash('sha256').update(payload).digest().subarray(0, CHECKSUM_LENGTH);
+}
+
+/**
+ * Memo text a depositor includes to have wZEC minted to `recipient`
+ */
+export function encodeDepositMemo(recipient) {
+    const payload = Buffer.concat([
+        Buffer.from([MEMO_VERSION]),
+        new PublicKey(recipient).toBuffer()
+    ]);
+    return bs58.encode(Buffer.concat([payload, checksum(payload)]));
+}
+
+/**
+ * Recipient named by a deposit memo's text
+ *
+ * Returns { recipient, legacy }, where `legacy` marks a bare public key.
+ * Throws DepositMemoError for anything else.
+ */
+export function parseDepositMemo(text) {
+    const trimmed = (text || '').trim();
+    if (!trimmed) {
+        throw new DepositMemoError('memo is empty');
+    }
+
+    let bytes;
+    try {
+        bytes = Buffer.from(bs58.decode(trimmed));
+    } catch (error) {
+        throw new DepositMemoError(`memo ${JSON.stringify(trimmed)} is not base58`);
+    }
+
+    if (bytes.length === 32) {
+        return { recipient: new PublicKey(bytes).toBase58(), legacy: true };
+    }
+    if (bytes[0] !== MEMO_VERSION) {
+        throw new DepositMemoError(`unknown memo version ${bytes[0]}`);
+    }
+    if (bytes.length !== ENCODED_LENGTH) {
+        throw new DepositMemoError(`memo is ${bytes.length} bytes, expected ${ENCODED_LENGTH}`);
+    }
+
+    const payload = bytes.subarray(0, ENCODED_LENGTH - CHECKSUM_LENGTH);
+    if (!checksum(payload).equals(bytes.subarray(payload.length))) {
+        throw new DepositMemoError('memo checksum mismatch');
+    }
+    return { recipient: new PublicKey(payload.subarray(1)).toBase58(), legacy: false };
+}
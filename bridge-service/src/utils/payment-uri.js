@@ -0,0 +1,60 @@
+import { encodeDepositMemo } from './deposit-memo.js';
+
+const ZATOSHIS_PER_ZEC = 100000000n;
+
+/**
+ * Format a ZEC amount for a ZIP-321 `amount` parameter
+ *
+ * Accepts a decimal string or number with at most 8 decimal places and
+ * returns it without trailing zeros.
+ */
+export function formatZecAmount(amount) {
+    const text = String(amount).trim();
+    const match = /^(\d+)(?:\.(\d{1,8}))?$/.exec(text);
+    if (!match) {
+        throw new Error(`invalid ZEC amount ${JSON.stringify(text)}`);
+    }
+    const zatoshis = BigInt(match[1]) * ZATOSHIS_PER_ZEC + BigInt((match[2] || '').padEnd(8, '0'));
+    if (zatoshis === 0n || zatoshis > 21000000n * ZATOSHIS_PER_ZEC) {
+        throw new Error(`ZEC amount ${text} is out of range`);
+    }
+
+    const whole = zatoshis / ZATOSHIS_PER_ZEC;
+    const fraction = (zatoshis % ZATOSHIS_PER_ZEC).toString().padStart(8, '0').replace(/0+$/, '');
+    return fraction ? `${whole}.${fraction}` : whole.toString();
+}
+
+/**
+ * ZIP-321 payment URI for a deposit credited to `recipient`
+ *
+ * The memo parameter carries the deposit memo for the recipient, so a wallet
+ * scanning the URI sends a correctly attributed deposit without the user
+ * typing anything. `amount` is optional; without it the wallet asks.
+ */
+export function buildDepositUri({ depositAddress, recipient, amount, label }) {
+    // ZIP-321 forbids memos for transparent addresses
+    if (!depositAddress || depositAddress.startsWith('t')) {
+        throw new Error('deposit address must be a shielded or unified address');
+    }
+
+    const params = [];
+    if (amount !== undefined && amount !== null) {
+        params.push(`amount=${formatZecAmount(amount)}`);
+    }
+    params.push(`memo=${Buffer.from(encodeDepositMemo(recipient), 'utf8').toString('base64url')}`);
+    if (label) {
+        params.push(`label=${encodeURIComponent(label)}`);
+    }
+    return `zcash:${depositAddress}?${params.join('&')}`;
+}
+
+/**
+ * QR code for a payment URI, drawn with terminal characters
+ *
+ * The URI is encoded as-is: base64url memos are case-sensitive, so the
+ * compact alphanumeric QR mode can't be used.
+ */
+export async function renderQrCode(uri) {
+    const { default: QRCode } = await import('qrcode');
+    return QRCode.toString(uri, { type: 'terminal', small: true, errorCorrectionLevel: 'M' });
+}
@@ -0,0 +1,35 @@
+import { describe, it } from 'node:test';
+import assert from 'node:assert';
+import { utils } from '@coral-xyz/anchor';
+import { encodeDepositMemo, parseDepositMemo, DepositMemoError } from '../src/utils/deposit-memo.js';
+
+describe('Deposit memo', () => {
+    const recipient = '9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin';
+
+    it('should round-trip a recipient through the versioned format', () => {
+        const memo = encodeDepositMemo(recipient);
+
+        assert.notStrictEqual(memo, recipient);
+        assert.deepStrictEqual(parseDepositMemo(memo), { recipient, legacy: false });
+    });
+
+    it('should accept a bare public key as a legacy memo', () => {
+        assert.deepStrictEqual(parseDepositMemo(`  ${recipient}\n`), { recipient, legacy: true });
+    });
+
+    it('should reject a memo with a corrupted checksum', () => {
+        const bytes = Buffer.from(utils.bytes.bs58.decode(encodeDepositMemo(recipient)));
+        bytes[bytes.length - 1] ^= 0x01;
+
+        assert.throws(() => parseDepositMemo(utils.bytes.bs58.encode(bytes)), /checksum/);
+    });
+
+    it('should reject unknown versions and free text', () => {
+        const bytes = Buffer.from(utils.bytes.bs58.decode(encodeDepositMemo(recipient)));
+        bytes[0] = 2;
+
+        assert.throws(() => parseDepositMemo(utils.bytes.bs58.encode(bytes)), /unknown memo version 2/);
+        assert.throws(() => parseDepositMemo('thanks for the bridge!'), DepositMemoError);
+        assert.throws(() => parseDepositMemo(''), DepositMemoError);
+    });
+});
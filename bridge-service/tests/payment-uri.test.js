@@ -0,0 +1,44 @@
+import { describe, it } from 'node:test';
+import assert from 'node:assert';
+import { buildDepositUri, formatZecAmount } from '../src/utils/payment-uri.js';
+import { encodeDepositMemo, parseDepositMemo } from '../src/utils/deposit-memo.js';
+
+describe('Deposit payment URI', () => {
+    const depositAddress = 'ztestsapling1test';
+    const recipient = '9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin';
+
+    it('should format ZEC amounts without trailing zeros', () => {
+        assert.strictEqual(formatZecAmount('1.50000000'), '1.5');
+        assert.strictEqual(formatZecAmount(2), '2');
+        assert.strictEqual(formatZecAmount('0.00000001'), '0.00000001');
+        assert.throws(() => formatZecAmount('0.000000001'), /invalid ZEC amount/);
+        assert.throws(() => formatZecAmount('0'), /out of range/);
+    });
+
+    it('should carry the deposit memo as base64url', () => {
+        const uri = buildDepositUri({ depositAddress, recipient, amount: '1.25', label: 'SolZ deposit' });
+        const url = new URL(uri);
+
+        assert.strictEqual(url.protocol, 'zcash:');
+        assert.strictEqual(url.pathname, depositAddress);
+        assert.strictEqual(url.searchParams.get('amount'), '1.25');
+        assert.strictEqual(url.searchParams.get('label'), 'SolZ deposit');
+
+        const memo = Buffer.from(url.searchParams.get('memo'), 'base64url').toString('utf8');
+        assert.strictEqual(memo, encodeDepositMemo(recipient));
+        assert.strictEqual(parseDepositMemo(memo).recipient, recipient);
+    });
+
+    it('should leave the amount to the wallet when none is given', () => {
+        const uri = buildDepositUri({ depositAddress, recipient });
+
+        assert.ok(!uri.includes('amount='));
+    });
+
+    it('should refuse transparent deposit addresses', () => {
+        assert.throws(
+            () => buildDepositUri({ depositAddress: 'tmXYZ', recipient }),
+            /shielded or unified/
+        );
+    });
+});
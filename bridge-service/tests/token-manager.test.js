@@ -0,0 +1,118 @@
+import { describe, it } from 'node:test';
+import assert from 'node:assert';
+import { PublicKey, TransactionInstruction } from '@solana/web3.js';
+import { BN } from '@coral-xyz/anchor';
+import SolanaTokenManager from '../src/solana/token-manager.js';
+
+describe('SolanaTokenManager', () => {
+    const manager = new SolanaTokenManager({}, null);
+
+    describe('txidBytes', () => {
+        // Zcash genesis coinbase txid, as zcashd and block explorers display it
+        const displayTxid = 'c4eaa58879081de3c24a7b117ed2b28300e7ec4c4c1dff1d3f1268b7857a4cf2';
+
+        it('should reverse a display-order txid into internal byte order', () => {
+            assert.strictEqual(
+                manager.txidBytes(displayTxid).toString('hex'),
+                'f24c7a85b768123f1dff1d4c4cece70083b2d27e117b4ac2e31d087988a5eac4'
+            );
+        });
+
+        it('should accept uppercase hex', () => {
+            assert.deepStrictEqual(
+                manager.txidBytes(displayTxid.toUpperCase()),
+                manager.txidBytes(displayTxid)
+            );
+        });
+
+        it('should reject txids that are not 32 bytes of hex', () => {
+            assert.throws(() => manager.txidBytes(displayTxid.slice(2)), /Invalid Zcash txid/);
+            assert.throws(() => manager.txidBytes(`${displayTxid.slice(2)}zz`), /Invalid Zcash txid/);
+        });
+    });
+
+    describe('burnPermitMessage', () => {
+        it('should lay out the permit fields as the program verifies them', () => {
+            const permitManager = new SolanaTokenManager({ zcash: { network: 'testnet' } }, null);
+            permitManager.bridgeStateAddress = new PublicKey(Buffer.alloc(32, 1));
+            const owner = new PublicKey(Buffer.alloc(32, 2));
+            const relayer = new PublicKey(Buffer.alloc(32, 3));
+
+            const message = permitManager.burnPermitMessage({
+                owner: owner.toBase58(),
+                zecAddress: 'tmAddr',
+                amount: '500000',
+                relayerFee: 10000,
+                nonce: 1,
+                expiry: 1700000000
+            }, relayer);
+
+            const domain = Buffer.from('solz-burn-permit-testnet');
+            assert.deepStrictEqual(message.subarray(0, domain.length), domain);
+            let offset = domain.length;
+            assert.deepStrictEqual(message.subarray(offset, offset + 32), Buffer.alloc(32, 1));
+            assert.deepStrictEqual(message.subarray(offset + 32, offset + 64), owner.toBuffer());
+            assert.deepStrictEqual(message.subarray(offset + 64, offset + 96), relayer.toBuffer());
+            offset += 96;
+            assert.strictEqual(message.readBigUInt64LE(offset), 500000n);
+            assert.strictEqual(message.readBigUInt64LE(offset + 8), 10000n);
+            assert.strictEqual(message.readBigUInt64LE(offset + 16), 1n);
+            assert.strictEqual(message.readBigInt64LE(offset + 24), 1700000000n);
+            assert.strictEqual(message.subarray(offset + 32).toString(), 'tmAddr');
+        });
+    });
+
+    describe('serializeGovernanceInstruction', () => {
+        it('should lay out the program id, account metas and data as Realms expects', () => {
+            const programId = new PublicKey(Buffer.alloc(32, 1));
+            const bridgeState = new PublicKey(Buffer.alloc(32, 2));
+            const governance = new PublicKey(Buffer.alloc(32, 3));
+            const instruction = new TransactionInstruction({
+                programId,
+                keys: [
+                    { pubkey: bridgeState, isSigner: false, isWritable: true },
+                    { pubkey: governance, isSigner: true, isWritable: false }
+                ],
+                data: Buffer.from([9, 8, 7])
+            });
+
+            const serialized = Buffer.from(manager.serializeGovernanceInstruction(instruction), 'base64');
+            assert.strictEqual(serialized.length, 32 + 4 + 2 * 34 + 4 + 3);
+            assert.deepStrictEqual(serialized.subarray(0, 32), programId.toBuffer());
+            assert.strictEqual(serialized.readUInt32LE(32), 2);
+            assert.deepStrictEqual(serialized.subarray(36, 68), bridgeState.toBuffer());
+            assert.deepStrictEqual([...serialized.subarray(68, 70)], [0, 1]);
+            assert.deepStrictEqual([...serialized.subarray(102, 104)], [1, 0]);
+            assert.strictEqual(serialized.readUInt32LE(104), 3);
+            assert.deepStrictEqual([...serialized.subarray(108)], [9, 8, 7]);
+        });
+    });
+
+    describe('parseInstructionArg', () => {
+        it('should convert command-line strings to IDL argument values', () => {
+            assert.ok(manager.parseInstructionArg('u64', '18446744073709551615').eq(new BN('18446744073709551615')));
+            assert.strictEqual(manager.parseInstructionArg('u16', '25'), 25);
+            assert.strictEqual(manager.parseInstructionArg('bool', 'true'), true);
+            assert.deepStrictEqual(manager.parseInstructionArg({ defined: 'Role' }, 'pauser'), { pauser: {} });
+            assert.deepStrictEqual(manager.parseInstructionArg({ array: ['u8', 2] }, 'ab01'), [0xab, 0x01]);
+        });
+    });
+
+    describe('isWithinFinalizeWindow', () => {
+        const windowManager = new SolanaTokenManager({}, null);
+        const withState = (state) => {
+            windowManager.program = { account: { bridgeState: { fetch: async () => state } } };
+        };
+
+        it('should allow nonces below the floor plus the window', async () => {
+            withState({ finalizeWindow: 2, settledFloor: new BN(5) });
+            assert.strictEqual(await windowManager.isWithinFinalizeWindow(6), true);
+            assert.strictEqual(await windowManager.isWithinFinalizeWindow(7), false);
+        });
+
+        it('should allow any nonce when no window is set', async () => {
+            withState({ finalizeWindow: 0, settledFloor: new BN(0) });
+            assert.strictEqual(await windowManager.isWithinFinalizeWindow(1000), true);
+        });
+    });
+});
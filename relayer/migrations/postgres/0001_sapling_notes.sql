@@ -0,0 +1,20 @@
+-- Unix time as SQLite's unixepoch() gives it, so queries and column defaults
+-- read the same on either database
+CREATE FUNCTION unixepoch() RETURNS BIGINT LANGUAGE SQL STABLE
+    AS $$ SELECT EXTRACT(EPOCH FROM now())::BIGINT $$;
+
+-- Sapling notes received by the deposit address
+CREATE TABLE sapling_notes (
+    txid TEXT NOT NULL,  -- Display order
+    output_index BIGINT NOT NULL,
+    height BIGINT NOT NULL,
+    position BIGINT NOT NULL,  -- Leaf position in the note commitment tree
+    value BIGINT NOT NULL,  -- Zatoshis
+    cmu BYTEA NOT NULL,
+    nullifier BYTEA NOT NULL UNIQUE,
+    spent_txid TEXT,  -- Transaction revealing the nullifier, once seen
+    spent_height BIGINT,
+    PRIMARY KEY (txid, output_index)
+);
+
+CREATE INDEX sapling_notes_unspent ON sapling_notes (spent_txid) WHERE spent_txid IS NULL;
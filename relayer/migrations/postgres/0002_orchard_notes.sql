@@ -0,0 +1,15 @@
+-- Orchard notes received by the deposit address
+CREATE TABLE orchard_notes (
+    txid TEXT NOT NULL,  -- Display order
+    action_index BIGINT NOT NULL,
+    height BIGINT NOT NULL,
+    position BIGINT NOT NULL,  -- Leaf position in the note commitment tree
+    value BIGINT NOT NULL,  -- Zatoshis
+    cmx BYTEA NOT NULL,
+    nullifier BYTEA NOT NULL UNIQUE,
+    spent_txid TEXT,  -- Transaction revealing the nullifier, once seen
+    spent_height BIGINT,
+    PRIMARY KEY (txid, action_index)
+);
+
+CREATE INDEX orchard_notes_unspent ON orchard_notes (spent_txid) WHERE spent_txid IS NULL;
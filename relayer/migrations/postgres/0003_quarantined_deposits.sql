@@ -0,0 +1,10 @@
+-- Deposits whose memo names no recipient, held for manual review
+CREATE TABLE quarantined_deposits (
+    txid TEXT PRIMARY KEY,  -- Display order
+    pool TEXT NOT NULL,
+    amount BIGINT NOT NULL,  -- Zatoshis
+    height BIGINT NOT NULL,
+    memo BYTEA NOT NULL,
+    reason TEXT NOT NULL,
+    quarantined_at BIGINT NOT NULL DEFAULT (unixepoch())
+);
@@ -0,0 +1,44 @@
+-- Deposits the relayer has attributed, keyed by idempotency ID
+CREATE TABLE deposits (
+    id TEXT PRIMARY KEY,  -- "mint:<txid>"
+    txid TEXT NOT NULL UNIQUE,  -- Display order
+    recipient TEXT NOT NULL,
+    amount BIGINT NOT NULL,  -- Zatoshis
+    height BIGINT NOT NULL,
+    status TEXT NOT NULL,  -- pending or minted
+    signature TEXT,  -- Solana signature of the confirmed mint
+    created_at BIGINT NOT NULL DEFAULT (unixepoch()),
+    updated_at BIGINT NOT NULL DEFAULT (unixepoch())
+);
+
+-- Guardian attestations the relayer has given
+CREATE TABLE attestations (
+    idempotency_id TEXT NOT NULL,
+    guardian TEXT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT (unixepoch()),
+    PRIMARY KEY (idempotency_id, guardian)
+);
+
+-- Every Solana transaction the relayer has submitted
+CREATE TABLE submissions (
+    signature TEXT PRIMARY KEY,
+    idempotency_id TEXT NOT NULL,
+    status TEXT NOT NULL,  -- submitted, confirmed, failed or expired
+    error TEXT,
+    submitted_at BIGINT NOT NULL DEFAULT (unixepoch()),
+    updated_at BIGINT NOT NULL DEFAULT (unixepoch())
+);
+
+CREATE INDEX submissions_by_idempotency_id ON submissions (idempotency_id);
+
+-- Zcash payouts for burn withdrawal requests, keyed by idempotency ID
+CREATE TABLE payouts (
+    id TEXT PRIMARY KEY,  -- "payout:<withdrawal nonce>"
+    withdrawal_request TEXT NOT NULL UNIQUE,
+    zec_address TEXT NOT NULL,
+    amount BIGINT NOT NULL,  -- Zatoshis
+    status TEXT NOT NULL,
+    zcash_txid TEXT,  -- Display order
+    created_at BIGINT NOT NULL DEFAULT (unixepoch()),
+    updated_at BIGINT NOT NULL DEFAULT (unixepoch())
+);
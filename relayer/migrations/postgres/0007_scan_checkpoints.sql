@@ -0,0 +1,9 @@
+-- Where scanning got to: the last block of each poll and the note commitment
+-- tree frontiers after it, hex encoded as the node serves them
+CREATE TABLE scan_checkpoints (
+    height BIGINT PRIMARY KEY,
+    hash TEXT NOT NULL,  -- Display order
+    sapling_tree TEXT NOT NULL,
+    orchard_tree TEXT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT (unixepoch())
+);
@@ -0,0 +1,6 @@
+-- Progress of each payout: status moves from pending through sending, sent
+-- and confirmed to finalized, or is invalid when the withdrawal can't be paid
+ALTER TABLE payouts ADD COLUMN fee BIGINT NOT NULL DEFAULT 0;  -- Zatoshis, deducted from the amount owed
+ALTER TABLE payouts ADD COLUMN operation_id TEXT;  -- Wallet operation that built the payout
+ALTER TABLE payouts ADD COLUMN height BIGINT;  -- Block in which the payout confirmed
+ALTER TABLE payouts ADD COLUMN signature TEXT;  -- Solana signature of finalize_withdrawal
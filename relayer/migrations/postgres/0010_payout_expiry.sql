@@ -0,0 +1,13 @@
+-- Zcash transactions are dropped once the chain passes their expiry height, so
+-- a payout that expired unmined is sent again as a new transaction
+ALTER TABLE payouts ADD COLUMN expiry_height BIGINT;  -- Of the current payout transaction, 0 when it never expires
+ALTER TABLE payouts ADD COLUMN rebroadcasts BIGINT NOT NULL DEFAULT 0;
+
+-- Payout transactions that expired without being mined
+CREATE TABLE expired_payouts (
+    zcash_txid TEXT NOT NULL,  -- Display order
+    payout_id TEXT NOT NULL,
+    expiry_height BIGINT NOT NULL,
+    expired_at BIGINT NOT NULL DEFAULT (unixepoch()),
+    PRIMARY KEY (zcash_txid, payout_id)
+);
@@ -0,0 +1,7 @@
+-- Last bridge transaction the burn listener processed, where a backfill after
+-- a dropped WebSocket subscription resumes
+CREATE TABLE event_cursor (
+    id BIGINT PRIMARY KEY CHECK (id = 1),
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL
+);
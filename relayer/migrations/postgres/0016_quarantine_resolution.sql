@@ -0,0 +1,13 @@
+-- Operators resolve a quarantined deposit by attributing it to a Solana
+-- recipient, who is then minted to, or by refunding it in ZEC
+ALTER TABLE quarantined_deposits ADD COLUMN status TEXT NOT NULL DEFAULT 'quarantined';  -- quarantined, attributed, refunding or refunded
+ALTER TABLE quarantined_deposits ADD COLUMN recipient TEXT;  -- Solana wallet it was attributed to
+ALTER TABLE quarantined_deposits ADD COLUMN refund_address TEXT;  -- Zcash address the sender gave
+ALTER TABLE quarantined_deposits ADD COLUMN refund_operation_id TEXT;  -- Wallet operation sending the refund
+ALTER TABLE quarantined_deposits ADD COLUMN refund_txid TEXT;  -- Display order
+ALTER TABLE quarantined_deposits ADD COLUMN resolved_by TEXT;  -- Operator
+ALTER TABLE quarantined_deposits ADD COLUMN resolved_at BIGINT;
+
+-- A retired deposit address is no longer credited to its recipient; what it
+-- receives afterwards is quarantined
+ALTER TABLE deposit_addresses ADD COLUMN retired_at BIGINT;
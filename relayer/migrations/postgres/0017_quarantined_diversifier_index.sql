@@ -0,0 +1,3 @@
+-- The address a quarantined deposit paid, so it can be attributed once a
+-- peer relayer names the recipient it issued that address to
+ALTER TABLE quarantined_deposits ADD COLUMN diversifier_index BYTEA;  -- When every note paid the same address
@@ -0,0 +1,15 @@
+-- Sapling notes received by the deposit address
+CREATE TABLE sapling_notes (
+    txid TEXT NOT NULL,  -- Display order
+    output_index INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    position INTEGER NOT NULL,  -- Leaf position in the note commitment tree
+    value INTEGER NOT NULL,  -- Zatoshis
+    cmu BLOB NOT NULL,
+    nullifier BLOB NOT NULL UNIQUE,
+    spent_txid TEXT,  -- Transaction revealing the nullifier, once seen
+    spent_height INTEGER,
+    PRIMARY KEY (txid, output_index)
+);
+
+CREATE INDEX sapling_notes_unspent ON sapling_notes (spent_txid) WHERE spent_txid IS NULL;
@@ -0,0 +1,15 @@
+-- Orchard notes received by the deposit address
+CREATE TABLE orchard_notes (
+    txid TEXT NOT NULL,  -- Display order
+    action_index INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    position INTEGER NOT NULL,  -- Leaf position in the note commitment tree
+    value INTEGER NOT NULL,  -- Zatoshis
+    cmx BLOB NOT NULL,
+    nullifier BLOB NOT NULL UNIQUE,
+    spent_txid TEXT,  -- Transaction revealing the nullifier, once seen
+    spent_height INTEGER,
+    PRIMARY KEY (txid, action_index)
+);
+
+CREATE INDEX orchard_notes_unspent ON orchard_notes (spent_txid) WHERE spent_txid IS NULL;
@@ -0,0 +1,10 @@
+-- Deposits whose memo names no recipient, held for manual review
+CREATE TABLE quarantined_deposits (
+    txid TEXT PRIMARY KEY,  -- Display order
+    pool TEXT NOT NULL,
+    amount INTEGER NOT NULL,  -- Zatoshis
+    height INTEGER NOT NULL,
+    memo BLOB NOT NULL,
+    reason TEXT NOT NULL,
+    quarantined_at INTEGER NOT NULL DEFAULT (unixepoch())
+);
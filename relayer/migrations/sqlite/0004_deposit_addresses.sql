@@ -0,0 +1,7 @@
+-- Diversified deposit addresses issued to Solana recipients
+CREATE TABLE deposit_addresses (
+    recipient TEXT PRIMARY KEY,  -- Base58 Solana public key
+    diversifier_index BLOB NOT NULL UNIQUE,
+    address TEXT NOT NULL,
+    issued_at INTEGER NOT NULL DEFAULT (unixepoch())
+);
@@ -0,0 +1,5 @@
+-- Hashes of scanned blocks, compared against the node's chain to detect reorgs
+CREATE TABLE scanned_blocks (
+    height INTEGER PRIMARY KEY,
+    hash TEXT NOT NULL  -- Display order
+);
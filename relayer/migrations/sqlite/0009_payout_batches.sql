@@ -0,0 +1,4 @@
+-- Payouts batched into one transaction share its txid; each withdrawal is
+-- paid by its own output
+ALTER TABLE payouts ADD COLUMN output_index INTEGER;  -- Sapling output, Orchard action or transparent output
+CREATE INDEX payouts_by_zcash_txid ON payouts (zcash_txid);
@@ -0,0 +1,8 @@
+-- Lease held by the one relayer instance allowed to scan, mint and pay out
+-- when several share this database
+CREATE TABLE leader_lease (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    holder TEXT NOT NULL,  -- Instance ID
+    expires_at INTEGER NOT NULL,  -- Unix time
+    renewed_at INTEGER NOT NULL
+);
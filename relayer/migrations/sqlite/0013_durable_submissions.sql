@@ -0,0 +1,3 @@
+-- Signed transactions kept with their submission, so one built on a durable
+-- nonce can be sent again after an outage instead of being given up
+ALTER TABLE submissions ADD COLUMN transaction BLOB;
@@ -0,0 +1,10 @@
+-- Operators' decisions on payouts above the approval threshold, which wait in
+-- status needs_approval until enough distinct operators approve
+CREATE TABLE payout_approvals (
+    idempotency_id TEXT NOT NULL,  -- "payout:<withdrawal nonce>"
+    operator TEXT NOT NULL,
+    decision TEXT NOT NULL,  -- approved or rejected
+    reason TEXT,
+    decided_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    PRIMARY KEY (idempotency_id, operator)
+);
@@ -0,0 +1,13 @@
+-- Hash-chained log of the relayer's consequential actions, each entry signed
+-- by the operator key. An entry's hash covers the previous entry's hash, so
+-- no entry can be changed, dropped or reordered without breaking the chain.
+CREATE TABLE audit_log (
+    seq INTEGER PRIMARY KEY,  -- 1 for the first entry, then consecutive
+    at INTEGER NOT NULL,  -- Unix time
+    action TEXT NOT NULL,  -- e.g. mint_submitted, payout_sent, config_changed
+    detail TEXT NOT NULL,  -- JSON
+    prev_hash TEXT NOT NULL,  -- Hex; 64 zeros for the first entry
+    hash TEXT NOT NULL,  -- Hex SHA-256
+    signer TEXT NOT NULL,  -- Base58 Ed25519 public key of the operator key
+    signature TEXT NOT NULL  -- Base58 signature of the hash's 32 bytes
+);
@@ -0,0 +1,477 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use tracing::info;
+
+use crate::audit::AuditLog;
+use crate::config::ApprovalConfig;
+use crate::payout::Payout;
+use crate::quarantine::{self, Quarantined, Refund, Refunds};
+use crate::store::Store;
+
+/// Outcome of one operator's approval
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Approval {
+    pub id: String,
+    pub approvals: usize,  // Distinct operators who have approved so far
+    pub required: usize,
+    pub released: bool,  // Went back to `pending`, to be sent with the next batch
+}
+
+/// A payout waiting for approval, as `GET /approvals` lists it
+#[derive(Serialize)]
+struct AwaitingApproval {
+    #[serde(flatten)]
+    payout: Payout,
+    approved_by: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RejectRequest {
+    reason: String,
+}
+
+#[derive(Deserialize)]
+struct AttributeRequest {
+    recipient: String,  // Base58 Solana wallet
+}
+
+#[derive(Deserialize)]
+struct RefundRequest {
+    address: String,  // Zcash address the sender gave
+}
+
+#[derive(Clone)]
+struct Admin {
+    store: Store,
+    audit: Option<AuditLog>,
+    refunds: Option<Refunds>,  // Without `[payouts]`, deposits can be attributed but not refunded
+    required: usize,
+    operators: Arc<HashMap<String, String>>,  // Bearer token to operator name
+}
+
+/// Approve the payout of withdrawal `nonce` as `operator`
+///
+/// The payout is released once `required` distinct operators have approved
+/// it; approving twice as the same operator counts once.
+pub async fn approve(
+    store: &Store,
+    audit: Option<&AuditLog>,
+    nonce: u64,
+    operator: &str,
+    required: usize,
+) -> Result<Approval> {
+    let id = format!("payout:{nonce}");
+    let count = store.approve_payout(&id, operator, required).await?;
+    let released = count >= required;
+    info!(nonce, operator, approvals = count, required, released, "payout approved");
+    if let Some(audit) = audit {
+        audit
+            .record(
+                "payout_approved",
+                json!({ "nonce": nonce, "operator": operator, "approvals": count, "released": released }),
+            )
+            .await?;
+    }
+    Ok(Approval {
+        id,
+        approvals: count,
+        required,
+        released,
+    })
+}
+
+/// Reject the payout of withdrawal `nonce` as `operator`, so it is never sent
+///
+/// One rejection is final: the burned wZEC stays burned and the withdrawal
+/// has to be settled by hand.
+pub async fn reject(store: &Store, audit: Option<&AuditLog>, nonce: u64, operator: &str, reason: &str) -> Result<()> {
+    store.reject_payout(&format!("payout:{nonce}"), operator, reason).await?;
+    info!(nonce, operator, reason, "payout rejected");
+    if let Some(audit) = audit {
+        audit
+            .record("payout_rejected", json!({ "nonce": nonce, "operator": operator, "reason": reason }))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Each operator's admin API token, read from the environment variable
+/// `approvals.operators` names for them, mapped to the operator
+pub fn operator_tokens(approvals: &ApprovalConfig) -> Result<HashMap<String, String>> {
+    let operators = approvals
+        .operators
+        .iter()
+        .map(|(name, env)| {
+            let token =
+                std::env::var(env).with_context(|| format!("reading operator {name}'s admin API token from ${env}"))?;
+            Ok((token, name.clone()))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+    anyhow::ensure!(operators.len() == approvals.operators.len(), "operators must not share an admin API token");
+    Ok(operators)
+}
+
+/// Decides payouts through the running relayer's admin API, for the CLI
+///
+/// The API acts as the operator whose token the request carries, so the CLI
+/// proves who is deciding the same way any other client does; naming an
+/// operator isn't enough.
+pub struct AdminClient {
+    http: reqwest::Client,
+    url: String,
+    token: String,
+}
+
+impl AdminClient {
+    /// Client of the admin API on `listen`, with the operator's own token from
+    /// the environment variable `token_env`
+    pub fn new(listen: SocketAddr, token_env: &str) -> Result<Self> {
+        let token = std::env::var(token_env)
+            .with_context(|| format!("reading your admin API token from ${token_env}"))?;
+        Ok(Self {
+            http: reqwest::Client::new(),
+            url: format!("http://{listen}"),
+            token,
+        })
+    }
+
+    pub async fn approve(&self, nonce: u64) -> Result<Approval> {
+        let response = self.post(nonce, "approve", None).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn reject(&self, nonce: u64, reason: &str) -> Result<()> {
+        let request = RejectRequest {
+            reason: reason.to_string(),
+        };
+        self.post(nonce, "reject", Some(&request)).await?;
+        Ok(())
+    }
+
+    async fn post(&self, nonce: u64, decision: &str, body: Option<&RejectRequest>) -> Result<reqwest::Response> {
+        let mut request = self
+            .http
+            .post(format!("{}/payouts/{nonce}/{decision}", self.url))
+            .bearer_auth(&self.token);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("reaching the admin API at {}", self.url))?;
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            StatusCode::UNAUTHORIZED => bail!("the token is no operator's admin API token"),
+            StatusCode::NOT_FOUND => bail!("no payout:{nonce}"),
+            StatusCode::CONFLICT => bail!("payout:{nonce} is not awaiting approval"),
+            status => bail!("admin API answered {status}"),
+        }
+    }
+}
+
+/// Serve the admin API on `listen` until the relayer stops
+///
+/// Every request carries an operator's bearer token from `operators`, and
+/// acts as that operator.
+///
+/// * `GET /approvals`: payouts waiting for approval, with who has approved
+/// * `POST /payouts/:nonce/approve`: approve a payout as the calling operator
+/// * `POST /payouts/:nonce/reject`: reject a payout, with a JSON `reason`
+/// * `GET /quarantine`: quarantined deposits waiting for an operator
+/// * `POST /quarantine/:txid/attribute`: mint a quarantined deposit to a
+///   JSON `recipient`
+/// * `POST /quarantine/:txid/refund`: refund a quarantined deposit to the
+///   JSON `address` its sender gave
+pub async fn serve(
+    listen: SocketAddr,
+    store: Store,
+    audit: Option<AuditLog>,
+    refunds: Option<Refunds>,
+    required: usize,
+    operators: HashMap<String, String>,
+) -> Result<()> {
+    let app = Router::new()
+        .route("/approvals", get(awaiting))
+        .route("/payouts/:nonce/approve", post(approve_payout))
+        .route("/payouts/:nonce/reject", post(reject_payout))
+        .route("/quarantine", get(quarantined))
+        .route("/quarantine/:txid/attribute", post(attribute_deposit))
+        .route("/quarantine/:txid/refund", post(refund_deposit))
+        .with_state(Admin {
+            store,
+            audit,
+            refunds,
+            required,
+            operators: Arc::new(operators),
+        });
+    axum::Server::try_bind(&listen)
+        .with_context(|| format!("binding admin API to {listen}"))?
+        .serve(app.into_make_service())
+        .await
+        .context("serving admin API")
+}
+
+impl Admin {
+    /// Operator whose token authorizes the request
+    fn operator(&self, headers: &HeaderMap) -> Result<&str, StatusCode> {
+        headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| self.operators.get(token))
+            .map(String::as_str)
+            .ok_or(StatusCode::UNAUTHORIZED)
+    }
+
+    /// Fail unless the payout of `nonce` exists and is waiting for approval
+    async fn awaiting(&self, nonce: u64) -> Result<(), StatusCode> {
+        let payout = self
+            .store
+            .payout(&format!("payout:{nonce}"))
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        if payout.status != "needs_approval" {
+            return Err(StatusCode::CONFLICT);
+        }
+        Ok(())
+    }
+
+    /// Fail unless deposit `txid` exists and is still quarantined
+    async fn quarantined(&self, txid: &str) -> Result<(), StatusCode> {
+        let quarantined = self
+            .store
+            .quarantined(txid)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        if quarantined.status != "quarantined" {
+            return Err(StatusCode::CONFLICT);
+        }
+        Ok(())
+    }
+}
+
+async fn awaiting(State(admin): State<Admin>, headers: HeaderMap) -> Result<Json<Vec<AwaitingApproval>>, StatusCode> {
+    admin.operator(&headers)?;
+    let payouts = admin
+        .store
+        .payouts_awaiting_approval()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(
+        payouts
+            .into_iter()
+            .map(|(payout, approved_by)| AwaitingApproval { payout, approved_by })
+            .collect(),
+    ))
+}
+
+async fn approve_payout(
+    State(admin): State<Admin>,
+    Path(nonce): Path<u64>,
+    headers: HeaderMap,
+) -> Result<Json<Approval>, StatusCode> {
+    let operator = admin.operator(&headers)?;
+    admin.awaiting(nonce).await?;
+    approve(&admin.store, admin.audit.as_ref(), nonce, operator, admin.required)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn reject_payout(
+    State(admin): State<Admin>,
+    Path(nonce): Path<u64>,
+    headers: HeaderMap,
+    Json(request): Json<RejectRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let operator = admin.operator(&headers)?;
+    admin.awaiting(nonce).await?;
+    reject(&admin.store, admin.audit.as_ref(), nonce, operator, &request.reason)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn quarantined(State(admin): State<Admin>, headers: HeaderMap) -> Result<Json<Vec<Quarantined>>, StatusCode> {
+    admin.operator(&headers)?;
+    admin
+        .store
+        .quarantined_deposits()
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn attribute_deposit(
+    State(admin): State<Admin>,
+    Path(txid): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<AttributeRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let operator = admin.operator(&headers)?;
+    let recipient: Pubkey = request.recipient.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    admin.quarantined(&txid).await?;
+    quarantine::attribute(&admin.store, admin.audit.as_ref(), &txid, recipient, operator)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn refund_deposit(
+    State(admin): State<Admin>,
+    Path(txid): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<RefundRequest>,
+) -> Result<Json<Refund>, StatusCode> {
+    let operator = admin.operator(&headers)?;
+    let refunds = admin.refunds.as_ref().ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    admin.quarantined(&txid).await?;
+    refunds
+        .refund(&admin.store, admin.audit.as_ref(), &txid, &request.address, operator)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use axum::http::HeaderValue;
+
+    use super::*;
+    use crate::payout::Withdrawal;
+    use crate::store::tests::open_temp;
+
+    /// A store holding the payout of withdrawal `nonce`, waiting for approval
+    async fn awaiting_approval(name: &str, nonce: u64) -> Store {
+        let store = open_temp(name).await;
+        let withdrawal = Withdrawal {
+            request: Pubkey::new_unique(),
+            nonce,
+            net_amount: 500_000_000_000,
+            zec_address: "t1SmxPaFcF5kYFn5ctgCt7QiaXzpTZyF4kG".to_string(),
+            memo: Vec::new(),
+            unlock_slot: 0,
+        };
+        store.record_payout(&withdrawal, "needs_approval").await.unwrap();
+        store
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_str(&format!("Bearer {token}")).unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn payout_is_released_by_the_second_operator() {
+        let store = awaiting_approval("approve-two", 7).await;
+
+        let first = approve(&store, None, 7, "alice", 2).await.unwrap();
+        assert_eq!((first.approvals, first.released), (1, false));
+        assert_eq!(store.payout("payout:7").await.unwrap().unwrap().status, "needs_approval");
+
+        let second = approve(&store, None, 7, "bob", 2).await.unwrap();
+        assert_eq!((second.approvals, second.released), (2, true));
+        assert_eq!(store.payout("payout:7").await.unwrap().unwrap().status, "pending");
+
+        // Released payouts take no more decisions
+        assert!(approve(&store, None, 7, "carol", 2).await.is_err());
+        assert!(reject(&store, None, 7, "carol", "too late").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn an_operator_approving_twice_counts_once() {
+        let store = awaiting_approval("approve-twice", 8).await;
+
+        approve(&store, None, 8, "alice", 2).await.unwrap();
+        let again = approve(&store, None, 8, "alice", 2).await.unwrap();
+        assert_eq!((again.approvals, again.released), (1, false));
+        let payout = store.payout("payout:8").await.unwrap().unwrap();
+        assert_eq!(payout.status, "needs_approval");
+        let awaiting = store.payouts_awaiting_approval().await.unwrap();
+        assert_eq!(awaiting.len(), 1);
+        assert_eq!(awaiting[0].1, ["alice"]);
+    }
+
+    #[tokio::test]
+    async fn one_rejection_is_final() {
+        let store = awaiting_approval("reject", 9).await;
+
+        approve(&store, None, 9, "alice", 2).await.unwrap();
+        reject(&store, None, 9, "bob", "address on a sanctions list").await.unwrap();
+        assert_eq!(store.payout("payout:9").await.unwrap().unwrap().status, "rejected");
+        assert!(approve(&store, None, 9, "carol", 2).await.is_err());
+        assert!(store.open_payouts().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn requests_act_as_the_operator_their_token_belongs_to() {
+        std::env::set_var("SOLZ_TEST_ADMIN_TOKEN_ALICE", "alice-token");
+        std::env::set_var("SOLZ_TEST_ADMIN_TOKEN_BOB", "bob-token");
+        let approvals = ApprovalConfig {
+            threshold: 0,
+            required: 2,
+            operators: BTreeMap::from([
+                ("alice".to_string(), "SOLZ_TEST_ADMIN_TOKEN_ALICE".to_string()),
+                ("bob".to_string(), "SOLZ_TEST_ADMIN_TOKEN_BOB".to_string()),
+            ]),
+            listen: "127.0.0.1:0".parse().unwrap(),
+        };
+        let operators = operator_tokens(&approvals).unwrap();
+        assert_eq!(operators.len(), 2);
+        assert_eq!(operators["alice-token"], "alice");
+        assert_eq!(operators["bob-token"], "bob");
+
+        let admin = Admin {
+            store: open_temp("admin-tokens").await,
+            audit: None,
+            refunds: None,
+            required: approvals.required,
+            operators: Arc::new(operators),
+        };
+        assert_eq!(admin.operator(&bearer("alice-token")), Ok("alice"));
+        assert_eq!(admin.operator(&bearer("bob-token")), Ok("bob"));
+        // Operator names are not tokens, and a token needs the Bearer scheme
+        assert_eq!(admin.operator(&bearer("alice")), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(admin.operator(&HeaderMap::new()), Err(StatusCode::UNAUTHORIZED));
+        let mut basic = HeaderMap::new();
+        basic.insert("authorization", HeaderValue::from_static("alice-token"));
+        assert_eq!(admin.operator(&basic), Err(StatusCode::UNAUTHORIZED));
+    }
+
+    #[test]
+    fn operators_must_not_share_a_token() {
+        std::env::set_var("SOLZ_TEST_ADMIN_TOKEN_SHARED", "shared-token");
+        let approvals = ApprovalConfig {
+            threshold: 0,
+            required: 2,
+            operators: BTreeMap::from([
+                ("alice".to_string(), "SOLZ_TEST_ADMIN_TOKEN_SHARED".to_string()),
+                ("bob".to_string(), "SOLZ_TEST_ADMIN_TOKEN_SHARED".to_string()),
+            ]),
+            listen: "127.0.0.1:0".parse().unwrap(),
+        };
+        assert!(operator_tokens(&approvals).is_err());
+
+        let missing = ApprovalConfig {
+            operators: BTreeMap::from([("carol".to_string(), "SOLZ_TEST_ADMIN_TOKEN_UNSET".to_string())]),
+            ..approvals
+        };
+        assert!(operator_tokens(&missing).is_err());
+    }
+}
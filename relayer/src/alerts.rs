@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::config::{AlertConfig, AlertSink};
+
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// How urgent an alert is
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+/// Where alerts go, with any secret already read from the environment
+enum Sink {
+    Webhook(String),
+    Slack(String),
+    PagerDuty(String),
+}
+
+/// Sends alerts to webhooks, Slack and PagerDuty
+///
+/// Alerts are keyed, e.g. `deposit_stuck:<txid>`. A key that keeps firing is
+/// sent again only every `repeat_mins`, and resolving it tells the sinks once
+/// the condition clears, so PagerDuty incidents close themselves.
+pub struct Alerter {
+    http: reqwest::Client,
+    sinks: Vec<Sink>,
+    source: String,  // Identifies this relayer in every alert
+    repeat: Duration,
+    firing: Mutex<HashMap<String, Instant>>,  // Key and when it was last sent
+}
+
+impl Alerter {
+    pub fn new(config: &AlertConfig, source: String) -> Result<Self> {
+        let secret = |name: &str| std::env::var(name).with_context(|| format!("{name} is not set"));
+        let sinks = config
+            .sinks
+            .iter()
+            .map(|sink| {
+                Ok(match sink {
+                    AlertSink::Webhook { url } => Sink::Webhook(url.clone()),
+                    AlertSink::Slack { url_env } => Sink::Slack(secret(url_env)?),
+                    AlertSink::Pagerduty { routing_key_env } => Sink::PagerDuty(secret(routing_key_env)?),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            http: reqwest::Client::new(),
+            sinks,
+            source,
+            repeat: Duration::from_secs(config.repeat_mins * 60),
+            firing: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Raise alert `key`, unless it was already sent within the repeat interval
+    pub async fn fire(&self, key: &str, severity: Severity, summary: &str) {
+        {
+            let Ok(mut firing) = self.firing.lock() else {
+                return;
+            };
+            if firing.get(key).is_some_and(|sent| sent.elapsed() < self.repeat) {
+                return;
+            }
+            firing.insert(key.to_string(), Instant::now());
+        }
+        warn!(key, ?severity, summary, "alert");
+        self.deliver(key, Some(severity), summary).await;
+    }
+
+    /// Clear alert `key` if it is firing
+    pub async fn resolve(&self, key: &str, summary: &str) {
+        let was_firing = self
+            .firing
+            .lock()
+            .map(|mut firing| firing.remove(key).is_some())
+            .unwrap_or(false);
+        if was_firing {
+            self.deliver(key, None, summary).await;
+        }
+    }
+
+    /// Send to every sink; `severity` is `None` for a resolution
+    async fn deliver(&self, key: &str, severity: Option<Severity>, summary: &str) {
+        for sink in &self.sinks {
+            let (url, body) = match sink {
+                Sink::Webhook(url) => (
+                    url.as_str(),
+                    json!({
+                        "key": key,
+                        "status": if severity.is_some() { "firing" } else { "resolved" },
+                        "severity": severity,
+                        "summary": summary,
+                        "source": self.source,
+                    }),
+                ),
+                Sink::Slack(url) => (url.as_str(), self.slack_message(severity, summary)),
+                Sink::PagerDuty(routing_key) => (PAGERDUTY_EVENTS_URL, self.pagerduty_event(routing_key, key, severity, summary)),
+            };
+            let sent = self
+                .http
+                .post(url)
+                .json(&body)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(error) = sent {
+                warn!(key, %error, "delivering alert failed");
+            }
+        }
+    }
+
+    fn slack_message(&self, severity: Option<Severity>, summary: &str) -> Value {
+        let text = match severity {
+            Some(Severity::Critical) => format!(":rotating_light: *solz relayer {}*: {summary}", self.source),
+            Some(Severity::Warning) => format!(":warning: *solz relayer {}*: {summary}", self.source),
+            None => format!(":white_check_mark: *solz relayer {}* resolved: {summary}", self.source),
+        };
+        json!({ "text": text })
+    }
+
+    /// PagerDuty Events API v2 event, deduplicated on the alert key
+    fn pagerduty_event(&self, routing_key: &str, key: &str, severity: Option<Severity>, summary: &str) -> Value {
+        match severity {
+            Some(severity) => json!({
+                "routing_key": routing_key,
+                "event_action": "trigger",
+                "dedup_key": key,
+                "payload": {
+                    "summary": summary,
+                    "source": self.source,
+                    "severity": severity,
+                },
+            }),
+            None => json!({
+                "routing_key": routing_key,
+                "event_action": "resolve",
+                "dedup_key": key,
+            }),
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use tracing::{info, instrument, warn};
+use wzec_bridge::{DepositReceipt, GuardianSet, ZcashNetwork};
+
+use crate::audit::AuditLog;
+use crate::deposit::Deposit;
+use crate::keys::ViewingKey;
+use crate::quarantine;
+use crate::signer::{self, BridgeSigner};
+use crate::store::Store;
+
+/// Who quarantined deposits attributed by `adopt` are recorded as resolved by
+const PEER_ATTRIBUTION: &str = "peer attestation";
+
+/// A deposit another relayer asks this one to attest
+#[derive(Serialize, Deserialize)]
+struct AttestRequest {
+    txid: String,  // Display order
+    recipient: String,  // Solana wallet
+    amount: u64,  // Zatoshis
+}
+
+#[derive(Serialize, Deserialize)]
+struct AttestResponse {
+    guardian: String,
+    signature: String,  // Base58 Ed25519 signature over the deposit's attestation message
+}
+
+/// Asks peer relayers to attest deposits this relayer mints
+pub struct Aggregator {
+    http: reqwest::Client,
+    peers: Vec<String>,
+    token: String,
+}
+
+impl Aggregator {
+    pub fn new(peers: Vec<String>, token: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            peers,
+            token,
+        }
+    }
+
+    /// Signatures over `message` from enough peers, with this relayer's own
+    /// key, to reach the quorum of `guardians`
+    ///
+    /// Each signature is checked against `message` and the guardian set, so a
+    /// peer answering with another key or a bad signature is skipped.
+    #[instrument(name = "attest", skip_all, fields(peers = self.peers.len()))]
+    pub async fn gather(
+        &self,
+        deposit: &Deposit,
+        message: &[u8],
+        guardians: &GuardianSet,
+        relayer: &Pubkey,
+    ) -> Result<Vec<(Pubkey, [u8; 64])>> {
+        let mut needed = guardians.threshold as usize;
+        if guardians.guardians.contains(relayer) {
+            needed = needed.saturating_sub(1);
+        }
+        let request = AttestRequest {
+            txid: deposit.txid.clone(),
+            recipient: deposit.recipient.to_string(),
+            amount: deposit.amount,
+        };
+
+        let mut signers = HashSet::from([*relayer]);
+        let mut attestations = Vec::new();
+        for peer in &self.peers {
+            if attestations.len() >= needed {
+                break;
+            }
+            let attestation = match self.ask(peer, &request).await {
+                Ok(attestation) => attestation,
+                Err(error) => {
+                    warn!(%peer, txid = %deposit.txid, error = %format!("{error:#}"), "peer did not attest");
+                    continue;
+                }
+            };
+            let (guardian, signature) = attestation;
+            if !guardians.guardians.contains(&guardian) || !signature.verify(guardian.as_ref(), message) {
+                warn!(%peer, %guardian, txid = %deposit.txid, "peer attestation is not a valid guardian signature");
+                continue;
+            }
+            let bytes: [u8; 64] = signature.as_ref().try_into()?;
+            if signers.insert(guardian) {
+                attestations.push((guardian, bytes));
+            }
+        }
+        if attestations.len() < needed {
+            bail!(
+                "only {} of the {needed} peer attestations needed for deposit {}",
+                attestations.len(),
+                deposit.txid
+            );
+        }
+        Ok(attestations)
+    }
+
+    #[instrument(skip(self, request))]
+    async fn ask(&self, peer: &str, request: &AttestRequest) -> Result<(Pubkey, Signature)> {
+        let response: AttestResponse = self
+            .http
+            .post(format!("{}/attest", peer.trim_end_matches('/')))
+            .bearer_auth(&self.token)
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok((
+            response.guardian.parse().context("peer guardian is not a public key")?,
+            response.signature.parse().context("peer signature is not base58")?,
+        ))
+    }
+}
+
+/// What the attestation server signs with and checks deposits against
+#[derive(Clone)]
+pub struct Attestor {
+    pub store: Store,
+    pub signer: Arc<dyn BridgeSigner>,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub network: ZcashNetwork,
+    pub token: Arc<String>,
+    pub keys: Arc<ViewingKey>,  // Derives the deposit addresses peers issued
+    pub min_deposit: u64,  // Zatoshis
+    pub audit: Option<AuditLog>,
+}
+
+/// Attest deposits this relayer has seen to the relayer that mints them,
+/// until stopped
+///
+/// * `POST /attest`: sign the attestation message of a deposit, if this
+///   relayer's own scan found it with the same recipient and amount
+pub async fn serve(listen: SocketAddr, attestor: Attestor) -> Result<()> {
+    let app = Router::new().route("/attest", post(attest)).with_state(attestor);
+    axum::Server::try_bind(&listen)
+        .with_context(|| format!("binding attestation server to {listen}"))?
+        .serve(app.into_make_service())
+        .await
+        .context("serving attestations")
+}
+
+/// Quarantined deposit `txid`, attributed to `recipient` if every note paid
+/// the deposit address this relayer's own viewing key derives for them
+///
+/// Deposit addresses are issued on one relayer, so the others quarantine
+/// memo-less deposits to them until the minting relayer asks for an
+/// attestation and names the recipient. The address is recorded as issued,
+/// so later deposits to it are attributed by the scan. Addresses this relayer
+/// already knows were quarantined for some other reason, which an attestation
+/// request doesn't override.
+pub(crate) async fn adopt(
+    store: &Store,
+    keys: &ViewingKey,
+    audit: Option<&AuditLog>,
+    min_deposit: u64,
+    txid: &str,
+    recipient: &Pubkey,
+) -> Result<Option<Deposit>> {
+    let Some(diversifier_index) = store.quarantined_diversifier_index(txid).await? else {
+        return Ok(None);
+    };
+    if store.deposit_recipient(&diversifier_index).await?.is_some()
+        || store.retired_recipient(&diversifier_index).await?.is_some()
+    {
+        return Ok(None);
+    }
+    let address = keys.deposit_address(recipient)?;
+    if address.diversifier_index != diversifier_index {
+        warn!(txid, %recipient, "quarantined deposit didn't pay the recipient's deposit address");
+        return Ok(None);
+    }
+    let quarantined = store.quarantined(txid).await?.context("quarantined deposit vanished")?;
+    if (quarantined.amount as u64) < min_deposit {
+        return Ok(None);
+    }
+    store.insert_deposit_address(recipient, &address).await?;
+    quarantine::attribute(store, audit, txid, *recipient, PEER_ATTRIBUTION).await.map(Some)
+}
+
+#[instrument(name = "attest_for_peer", skip_all, fields(transfer = %format_args!("mint:{}", request.txid)))]
+async fn attest(
+    State(attestor): State<Attestor>,
+    headers: HeaderMap,
+    Json(request): Json<AttestRequest>,
+) -> Result<Json<AttestResponse>, StatusCode> {
+    let expected = format!("Bearer {}", attestor.token);
+    if headers.get("authorization").and_then(|value| value.to_str().ok()) != Some(expected.as_str()) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // Only what this relayer's own scanner found and attributed is attested
+    let deposit = match attestor.store.deposit(&request.txid).await {
+        Ok(Some(deposit)) => deposit,
+        Ok(None) => {
+            let recipient: Pubkey = request.recipient.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+            adopt(
+                &attestor.store,
+                &attestor.keys,
+                attestor.audit.as_ref(),
+                attestor.min_deposit,
+                &request.txid,
+                &recipient,
+            )
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?
+        }
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    if deposit.recipient.to_string() != request.recipient || deposit.amount != request.amount {
+        warn!(
+            txid = %request.txid,
+            requested_recipient = %request.recipient,
+            requested_amount = request.amount,
+            "refusing to attest a deposit that doesn't match the scanned one"
+        );
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let recipient_token_account =
+        get_associated_token_address_with_program_id(&deposit.recipient, &attestor.mint, &attestor.token_program);
+    let message =
+        DepositReceipt::attestation_message(attestor.network, &recipient_token_account, deposit.amount, &deposit.zcash_txid);
+    let signature = attestor
+        .signer
+        .sign(&message)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let signature = Signature::try_from(signature.as_slice()).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let guardian = signer::solana_pubkey(attestor.signer.as_ref()).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    attestor
+        .store
+        .record_attestation(&deposit.idempotency_id(), &guardian)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    info!(txid = %deposit.txid, amount = deposit.amount, recipient = %deposit.recipient, "attested deposit");
+    Ok(Json(AttestResponse {
+        guardian: guardian.to_string(),
+        signature: signature.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use zcash_keys::keys::UnifiedSpendingKey;
+    use zcash_primitives::consensus;
+    use zcash_primitives::zip32::AccountId;
+
+    use super::*;
+    use crate::config::Network;
+    use crate::deposit::tests::note;
+    use crate::store::tests::open_temp;
+
+    /// The viewing key every relayer of a test bridge holds
+    fn viewing_key() -> ViewingKey {
+        let network = consensus::Network::TestNetwork;
+        let usk = UnifiedSpendingKey::from_seed(&network, &[7; 32], AccountId::ZERO).unwrap();
+        ViewingKey::decode(Network::Testnet, &usk.to_unified_full_viewing_key().encode(&network)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn peers_attribute_deposits_to_addresses_issued_elsewhere() {
+        let keys = viewing_key();
+        let recipient = Pubkey::new_unique();
+        let address = keys.deposit_address(&recipient).unwrap();
+
+        // The minting relayer issued the address; its peer never heard of it
+        let minting = open_temp("attest-minting").await;
+        let peer = open_temp("attest-peer").await;
+        minting.insert_deposit_address(&recipient, &address).await.unwrap();
+
+        // Both scan the same memo-less deposit to it
+        let mut paid = note(1, 0, 500_000);
+        paid.diversifier_index = address.diversifier_index;
+        let notes = [paid];
+        let assigned = [minting.deposit_recipient(&address.diversifier_index).await.unwrap()];
+        let deposit = Deposit::from_notes(&notes, &assigned).unwrap();
+        assert_eq!(deposit.recipient, recipient);
+        assert_eq!(peer.deposit_recipient(&address.diversifier_index).await.unwrap(), None);
+        assert!(Deposit::from_notes(&notes, &[None]).is_err());
+        peer.quarantine(&notes, "no recipient").await.unwrap();
+        assert!(peer.deposit(&deposit.txid).await.unwrap().is_none());
+
+        // Naming someone else doesn't release it
+        let other = Pubkey::new_unique();
+        assert!(adopt(&peer, &keys, None, 0, &deposit.txid, &other).await.unwrap().is_none());
+        assert_eq!(peer.quarantined(&deposit.txid).await.unwrap().unwrap().status, "quarantined");
+
+        // The attestation request names the recipient, whose address the peer derives itself
+        let adopted = adopt(&peer, &keys, None, 0, &deposit.txid, &recipient).await.unwrap().unwrap();
+        assert_eq!(adopted.recipient, recipient);
+        assert_eq!(adopted.amount, deposit.amount);
+        assert_eq!(adopted.zcash_txid, deposit.zcash_txid);
+        let stored = peer.deposit(&deposit.txid).await.unwrap().unwrap();
+        assert_eq!((stored.recipient, stored.amount), (recipient, deposit.amount));
+        let quarantined = peer.quarantined(&deposit.txid).await.unwrap().unwrap();
+        assert_eq!(quarantined.status, "attributed");
+        assert_eq!(quarantined.resolved_by.as_deref(), Some(PEER_ATTRIBUTION));
+
+        // Later deposits to the address are credited by the peer's own scan
+        assert_eq!(peer.deposit_recipient(&address.diversifier_index).await.unwrap(), Some(recipient));
+        assert!(adopt(&peer, &keys, None, 0, &deposit.txid, &recipient).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn peers_keep_deposits_they_would_not_credit_quarantined() {
+        let keys = viewing_key();
+        let store = open_temp("attest-refused").await;
+
+        // Below the minimum
+        let small = Pubkey::new_unique();
+        let mut paid = note(1, 0, 1_000);
+        paid.diversifier_index = keys.deposit_address(&small).unwrap().diversifier_index;
+        store.quarantine(&[paid.clone()], "no recipient").await.unwrap();
+        assert!(adopt(&store, &keys, None, 10_000, &paid.txid, &small).await.unwrap().is_none());
+
+        // Paid to an address this relayer retired
+        let retired = Pubkey::new_unique();
+        let address = keys.deposit_address(&retired).unwrap();
+        store.insert_deposit_address(&retired, &address).await.unwrap();
+        assert!(store.retire_deposit_address(&retired).await.unwrap());
+        let mut paid = note(2, 0, 500_000);
+        paid.diversifier_index = address.diversifier_index;
+        store.quarantine(&[paid.clone()], "retired address").await.unwrap();
+        assert!(adopt(&store, &keys, None, 0, &paid.txid, &retired).await.unwrap().is_none());
+
+        // Split across two recipients' addresses
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut notes = [note(3, 0, 500_000), note(3, 1, 500_000)];
+        notes[0].diversifier_index = keys.deposit_address(&first).unwrap().diversifier_index;
+        notes[1].diversifier_index = keys.deposit_address(&second).unwrap().diversifier_index;
+        store.quarantine(&notes, "several recipients").await.unwrap();
+        assert_eq!(store.quarantined_diversifier_index(&notes[0].txid).await.unwrap(), None);
+        assert!(adopt(&store, &keys, None, 0, &notes[0].txid, &first).await.unwrap().is_none());
+
+        for txid in [&notes[0].txid, &note(1, 0, 0).txid, &note(2, 0, 0).txid] {
+            assert_eq!(store.quarantined(txid).await.unwrap().unwrap().status, "quarantined");
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_sdk::hash::{hashv, Hash};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::config::AuditConfig;
+use crate::signer::{self, BridgeSigner};
+use crate::store::Store;
+
+/// Times an append is retried after another process took the same `seq`
+const APPEND_ATTEMPTS: u32 = 5;
+
+/// One entry of the audit log, as stored and as exported
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub seq: i64,
+    pub at: i64,  // Unix time
+    pub action: String,
+    pub detail: String,  // JSON, hashed as the exact bytes stored
+    pub prev_hash: String,  // Hex
+    pub hash: String,  // Hex
+    pub signer: String,  // Base58 operator key
+    pub signature: String,  // Base58
+}
+
+/// SHA-256 an entry is chained and signed by:
+/// `prev_hash ‖ seq ‖ at ‖ len(action) ‖ action ‖ len(detail) ‖ detail`, with
+/// `seq` and `at` as 8-byte and the lengths as 4-byte big-endian integers
+pub fn entry_hash(prev_hash: &Hash, seq: i64, at: i64, action: &str, detail: &str) -> Hash {
+    hashv(&[
+        prev_hash.as_ref(),
+        &seq.to_be_bytes(),
+        &at.to_be_bytes(),
+        &(action.len() as u32).to_be_bytes(),
+        action.as_bytes(),
+        &(detail.len() as u32).to_be_bytes(),
+        detail.as_bytes(),
+    ])
+}
+
+/// Appends the relayer's consequential actions to the `audit_log` table,
+/// each entry chained to the one before by its hash and signed by the
+/// operator key
+#[derive(Clone)]
+pub struct AuditLog {
+    store: Store,
+    signer: Arc<dyn BridgeSigner>,
+    operator: Pubkey,
+    append: Arc<Mutex<()>>,  // Keeps this process's appends in order
+}
+
+impl AuditLog {
+    pub async fn open(config: &AuditConfig, store: Store) -> Result<Self> {
+        let signer: Arc<dyn BridgeSigner> = signer::open(&config.signer).await?.into();
+        let operator = signer::solana_pubkey(signer.as_ref())?;
+        Ok(Self {
+            store,
+            signer,
+            operator,
+            append: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Append `action` with its `detail`
+    ///
+    /// Another process appending at the same time (a CLI command, or a
+    /// second instance) takes the next `seq` first; the entry is then
+    /// chained again to the new tip.
+    pub async fn record(&self, action: &str, detail: Value) -> Result<AuditEntry> {
+        let _append = self.append.lock().await;
+        let detail = detail.to_string();
+        for _ in 0..APPEND_ATTEMPTS {
+            let (seq, prev_hash) = match self.store.last_audit_entry().await? {
+                Some(last) => (last.seq + 1, parse_hash(&last.hash)?),
+                None => (1, Hash::default()),
+            };
+            let at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+            let hash = entry_hash(&prev_hash, seq, at, action, &detail);
+            let signature = self.signer.sign(hash.as_ref()).await.context("signing audit entry")?;
+            let signature = Signature::try_from(signature.as_slice()).context("operator key returned a malformed signature")?;
+            let entry = AuditEntry {
+                seq,
+                at,
+                action: action.to_string(),
+                detail: detail.clone(),
+                prev_hash: hex::encode(prev_hash),
+                hash: hex::encode(hash),
+                signer: self.operator.to_string(),
+                signature: signature.to_string(),
+            };
+            if self.store.append_audit_entry(&entry).await? {
+                debug!(seq, action, "audit entry recorded");
+                return Ok(entry);
+            }
+        }
+        anyhow::bail!("audit log kept moving while appending {action}")
+    }
+
+    /// Record `config_changed` if the config's digest differs from the one
+    /// last recorded
+    pub async fn record_config(&self, digest: &str) -> Result<()> {
+        let last = self.store.last_audit_detail("config_changed").await?;
+        let recorded = last
+            .and_then(|detail| serde_json::from_str::<Value>(&detail).ok())
+            .and_then(|detail| detail["digest"].as_str().map(str::to_string));
+        if recorded.as_deref() != Some(digest) {
+            self.record("config_changed", serde_json::json!({ "digest": digest })).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Check an exported log: consecutive from `seq` 1, each entry chained to
+/// the one before, hashed as [`entry_hash`] says and signed by its `signer`
+///
+/// With `operator`, every entry must be signed by that key. Returns the
+/// distinct keys that signed.
+pub fn verify(entries: &[AuditEntry], operator: Option<&Pubkey>) -> Result<BTreeSet<Pubkey>> {
+    let mut prev_hash = Hash::default();
+    let mut signers = BTreeSet::new();
+    for (entry, seq) in entries.iter().zip(1..) {
+        anyhow::ensure!(entry.seq == seq, "entry {seq} is missing; found {} in its place", entry.seq);
+        anyhow::ensure!(
+            parse_hash(&entry.prev_hash)? == prev_hash,
+            "entry {seq} does not chain to entry {}",
+            seq - 1
+        );
+        let hash = entry_hash(&prev_hash, entry.seq, entry.at, &entry.action, &entry.detail);
+        anyhow::ensure!(parse_hash(&entry.hash)? == hash, "entry {seq} does not match its hash");
+        let signer: Pubkey = entry.signer.parse().with_context(|| format!("entry {seq} signer"))?;
+        if let Some(operator) = operator {
+            anyhow::ensure!(&signer == operator, "entry {seq} is signed by {signer}, not {operator}");
+        }
+        let signature: Signature = entry.signature.parse().with_context(|| format!("entry {seq} signature"))?;
+        anyhow::ensure!(
+            signature.verify(signer.as_ref(), hash.as_ref()),
+            "entry {seq} signature does not verify"
+        );
+        signers.insert(signer);
+        prev_hash = hash;
+    }
+    Ok(signers)
+}
+
+fn parse_hash(hex: &str) -> Result<Hash> {
+    let bytes: [u8; 32] = hex::decode(hex)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .with_context(|| format!("{hex:?} is not a hex-encoded 32-byte hash"))?;
+    Ok(Hash::new_from_array(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use solana_sdk::signature::{write_keypair_file, Keypair, Signer};
+
+    use super::*;
+    use crate::config::SignerConfig;
+    use crate::store::tests::open_temp;
+
+    /// An audit log signed by a fresh operator key, with three entries
+    async fn three_entries(name: &str) -> (Vec<AuditEntry>, Pubkey) {
+        let keypair = Keypair::new();
+        let path = std::env::temp_dir().join(format!("solz-relayer-{name}-{}.json", std::process::id()));
+        write_keypair_file(&keypair, &path).unwrap();
+        let config = AuditConfig {
+            signer: SignerConfig::Local { keypair: path },
+        };
+        let store = open_temp(name).await;
+        let log = AuditLog::open(&config, store.clone()).await.unwrap();
+        log.record("config_changed", json!({ "digest": "aa" })).await.unwrap();
+        log.record("mint", json!({ "txid": "01", "amount": 500_000 })).await.unwrap();
+        log.record("payout_approved", json!({ "nonce": 7, "operator": "alice" })).await.unwrap();
+        (store.audit_entries().await.unwrap(), keypair.pubkey())
+    }
+
+    fn rejection(entries: &[AuditEntry], operator: Option<&Pubkey>) -> String {
+        format!("{:#}", verify(entries, operator).unwrap_err())
+    }
+
+    #[tokio::test]
+    async fn intact_log_verifies() {
+        let (entries, operator) = three_entries("audit-intact").await;
+        assert_eq!(entries.iter().map(|entry| entry.seq).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(entries[0].prev_hash, hex::encode(Hash::default()));
+        assert_eq!(entries[1].prev_hash, entries[0].hash);
+        assert_eq!(verify(&entries, None).unwrap(), BTreeSet::from([operator]));
+        assert_eq!(verify(&entries, Some(&operator)).unwrap(), BTreeSet::from([operator]));
+
+        // As exported and read back by `audit-verify`
+        let exported: Vec<AuditEntry> = entries
+            .iter()
+            .map(|entry| serde_json::from_str(&serde_json::to_string(entry).unwrap()).unwrap())
+            .collect();
+        assert!(verify(&exported, Some(&operator)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn edited_entries_are_detected() {
+        let (entries, _) = three_entries("audit-edited").await;
+
+        let mut edited = entries.clone();
+        edited[1].detail = json!({ "txid": "01", "amount": 5_000_000 }).to_string();
+        assert!(rejection(&edited, None).contains("entry 2 does not match its hash"));
+
+        let mut edited = entries.clone();
+        edited[1].at += 1;
+        assert!(rejection(&edited, None).contains("entry 2 does not match its hash"));
+
+        // Hashing the edit again leaves the signature over the old hash
+        let mut edited = entries.clone();
+        edited[1].action = "burn".to_string();
+        let rehashed = entry_hash(&parse_hash(&edited[0].hash).unwrap(), 2, edited[1].at, "burn", &edited[1].detail);
+        edited[1].hash = hex::encode(rehashed);
+        assert!(rejection(&edited, None).contains("entry 2 signature does not verify"));
+    }
+
+    #[tokio::test]
+    async fn reordered_and_truncated_logs_are_detected() {
+        let (entries, _) = three_entries("audit-reordered").await;
+
+        let mut reordered = entries.clone();
+        reordered.swap(1, 2);
+        assert!(rejection(&reordered, None).contains("entry 2 is missing; found 3 in its place"));
+
+        // Renumbered to hide the swap, it no longer chains
+        reordered[1].seq = 2;
+        reordered[2].seq = 3;
+        assert!(rejection(&reordered, None).contains("entry 2 does not chain to entry 1"));
+
+        let without_first = &entries[1..];
+        assert!(rejection(without_first, None).contains("entry 1 is missing"));
+        let without_middle = [entries[0].clone(), entries[2].clone()];
+        assert!(rejection(&without_middle, None).contains("entry 2 is missing"));
+
+        let mut cut_short = entries.clone();
+        cut_short[2].detail.truncate(cut_short[2].detail.len() / 2);
+        assert!(rejection(&cut_short, None).contains("entry 3 does not match its hash"));
+    }
+
+    #[tokio::test]
+    async fn bad_signatures_are_detected() {
+        let (entries, operator) = three_entries("audit-signatures").await;
+        let intruder = Keypair::new();
+
+        // A signature over the right hash, by a key other than the entry's signer
+        let mut forged = entries.clone();
+        forged[2].signature = intruder.sign_message(&hex::decode(&entries[2].hash).unwrap()).to_string();
+        assert!(rejection(&forged, None).contains("entry 3 signature does not verify"));
+
+        // A signature over another entry's hash
+        let mut swapped = entries.clone();
+        swapped[2].signature = entries[1].signature.clone();
+        assert!(rejection(&swapped, None).contains("entry 3 signature does not verify"));
+
+        // Re-signed by another key, it verifies only until the operator is named
+        let mut resigned = entries.clone();
+        resigned[2].signer = intruder.pubkey().to_string();
+        resigned[2].signature = forged[2].signature.clone();
+        assert_eq!(verify(&resigned, None).unwrap(), BTreeSet::from([operator, intruder.pubkey()]));
+        assert!(rejection(&resigned, Some(&operator)).contains(&format!("entry 3 is signed by {}", intruder.pubkey())));
+
+        let mut garbled = entries.clone();
+        garbled[0].signature = "not base58!".to_string();
+        assert!(rejection(&garbled, None).contains("entry 1 signature"));
+    }
+}
@@ -0,0 +1,133 @@
+//! Fault injection for integration tests, compiled in with the `chaos` Cargo
+//! feature
+//!
+//! `SOLZ_CHAOS` sets how likely each fault is, e.g.
+//! `rpc_failure=0.05,lost_response=0.02,delayed_confirmation=0.2,reorg=0.01,duplicate_event=0.1`,
+//! and `SOLZ_CHAOS_SEED` seeds the random choices so a failing run can be
+//! repeated. Without the feature every check is a constant `false`.
+
+/// A fault the relayer can be made to suffer
+#[derive(Debug, Clone, Copy)]
+pub enum Fault {
+    /// A JSON-RPC or Solana request fails before it is sent
+    RpcFailure,
+    /// A request is carried out but its response is lost
+    LostResponse,
+    /// A sent Solana transaction's status check is skipped, so it confirms a
+    /// round later, or expires first
+    DelayedConfirmation,
+    /// The latest scanned blocks are reported orphaned, so they are rolled
+    /// back and scanned again
+    Reorg,
+    /// A note or withdrawal is delivered twice
+    DuplicateEvent,
+}
+
+/// Whether to inject `fault` here
+#[cfg(not(feature = "chaos"))]
+#[inline(always)]
+pub fn strikes(_fault: Fault) -> bool {
+    false
+}
+
+/// Whether to inject `fault` here
+#[cfg(feature = "chaos")]
+pub fn strikes(fault: Fault) -> bool {
+    let struck = enabled::strikes(fault);
+    if struck {
+        tracing::warn!(?fault, "chaos: injecting fault");
+    }
+    struck
+}
+
+/// Fail with `what` when an [`Fault::RpcFailure`] or, once the request has
+/// been made, a [`Fault::LostResponse`] strikes
+pub fn fail(fault: Fault, what: &str) -> anyhow::Result<()> {
+    if strikes(fault) {
+        anyhow::bail!("chaos: injected {fault:?} in {what}");
+    }
+    Ok(())
+}
+
+/// How many of the latest scanned blocks a [`Fault::Reorg`] orphans, if one
+/// strikes
+pub fn reorg_depth() -> Option<u32> {
+    if strikes(Fault::Reorg) {
+        Some(depth())
+    } else {
+        None
+    }
+}
+
+#[cfg(not(feature = "chaos"))]
+fn depth() -> u32 {
+    0
+}
+
+#[cfg(feature = "chaos")]
+fn depth() -> u32 {
+    enabled::depth()
+}
+
+#[cfg(feature = "chaos")]
+mod enabled {
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::Fault;
+
+    /// Deepest injected reorg, in blocks
+    const MAX_REORG_DEPTH: u32 = 3;
+
+    struct Chaos {
+        rates: HashMap<String, f64>,  // Fault name to its probability at each check
+        rng: Mutex<StdRng>,
+    }
+
+    fn chaos() -> &'static Chaos {
+        static CHAOS: OnceLock<Chaos> = OnceLock::new();
+        CHAOS.get_or_init(|| {
+            let rates = std::env::var("SOLZ_CHAOS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|rate| rate.split_once('='))
+                .filter_map(|(name, rate)| Some((name.trim().to_string(), rate.trim().parse().ok()?)))
+                .collect();
+            let rng = match std::env::var("SOLZ_CHAOS_SEED").ok().and_then(|seed| seed.parse().ok()) {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            Chaos {
+                rates,
+                rng: Mutex::new(rng),
+            }
+        })
+    }
+
+    fn key(fault: Fault) -> &'static str {
+        match fault {
+            Fault::RpcFailure => "rpc_failure",
+            Fault::LostResponse => "lost_response",
+            Fault::DelayedConfirmation => "delayed_confirmation",
+            Fault::Reorg => "reorg",
+            Fault::DuplicateEvent => "duplicate_event",
+        }
+    }
+
+    pub fn strikes(fault: Fault) -> bool {
+        let chaos = chaos();
+        let rate = chaos.rates.get(key(fault)).copied().unwrap_or(0.0).clamp(0.0, 1.0);
+        rate > 0.0 && chaos.rng.lock().map(|mut rng| rng.gen_bool(rate)).unwrap_or(false)
+    }
+
+    pub fn depth() -> u32 {
+        chaos()
+            .rng
+            .lock()
+            .map(|mut rng| rng.gen_range(1..=MAX_REORG_DEPTH))
+            .unwrap_or(1)
+    }
+}
@@ -0,0 +1,905 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+
+/// Prefix of the environment variables that override config settings
+const ENV_PREFIX: &str = "SOLZ__";
+
+/// Settings a running relayer takes from its config file again on SIGHUP; a
+/// section's name covers everything in it
+const RELOADABLE: &[&str] = &[
+    "zcash.confirmations",
+    "zcash.poll_interval_secs",
+    "zcash.watch_mempool",
+    "zcash.min_deposit",
+    "payouts.batch_window_secs",
+    "payouts.max_batch_size",
+    "payouts.extra_fee_actions",
+    "payouts.operation_timeout_secs",
+    "alerts",
+    "retry",
+];
+
+/// Relayer configuration, read from a TOML file
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub zcash: ZcashConfig,
+    pub lightwalletd: Option<LightwalletdConfig>,
+    pub solana: SolanaConfig,
+    pub payouts: Option<PayoutConfig>,  // Withdrawal payouts are off without it
+    pub approvals: Option<ApprovalConfig>,  // Every payout goes out unapproved without it
+    pub status: Option<StatusConfig>,  // The status API is off without it
+    pub consolidation: Option<ConsolidationConfig>,  // Note consolidation is off without it
+    pub reserve: Option<ReserveConfig>,  // Every deposit stays hot without it
+    pub frost: Option<FrostConfig>,  // Threshold signing of reserve spends
+    pub attestation: Option<AttestationConfig>,  // Mints carry only this relayer's attestation without it
+    pub ha: Option<HaConfig>,  // Only one instance may use the database without it
+    pub telemetry: Option<TelemetryConfig>,  // Spans are only logged without it
+    pub alerts: Option<AlertConfig>,  // Problems are only logged without it
+    pub audit: Option<AuditConfig>,  // Nothing is written to the audit log without it
+    #[serde(default)]
+    pub dry_run: bool,  // Simulate Solana transactions and never ask the wallet to spend; also set by --dry-run
+    #[serde(default)]
+    pub database: DatabaseConfig,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
+    #[serde(skip)]
+    pub digest: String,  // Hex SHA-256 of the settings in effect, overrides included
+    #[serde(skip)]
+    pub path: PathBuf,  // File the config was read from, for reloading
+    #[serde(skip)]
+    table: toml::Table,  // Settings as read at startup, overrides included
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ZcashConfig {
+    #[serde(default)]
+    pub backend: Backend,
+    pub network: Network,
+    #[serde(default)]
+    pub rpc_url: String,
+    #[serde(default)]
+    pub rpc_user: String,
+    #[serde(default)]
+    pub rpc_password: String,
+    pub rpc_cookie_file: Option<PathBuf>,  // Zebra's RPC cookie, when cookie authentication is on
+    pub deposit_address: String,
+    pub viewing_key: String,  // Unified or Sapling extended full viewing key of the deposit address
+    pub birthday_height: u32,  // First block that can hold a deposit
+    pub confirmations: u32,
+    #[serde(default = "default_true")]
+    pub pause_on_deep_reorg: bool,  // Guardian-pause mints when a reorg reaches scanned blocks
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    #[serde(default)]
+    pub watch_mempool: bool,  // Report deposits from the mempool each poll, before they confirm
+    #[serde(default)]
+    pub min_deposit: u64,  // Zatoshis; smaller deposits are quarantined
+}
+
+/// Where blocks to scan for deposits come from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// Full blocks from `zcashd` RPC
+    #[default]
+    Zcashd,
+    /// Full blocks from Zebra RPC
+    Zebra,
+    /// CompactBlocks from lightwalletd
+    Lightwalletd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    pub fn consensus(self) -> zcash_primitives::consensus::Network {
+        match self {
+            Network::Mainnet => zcash_primitives::consensus::Network::MainNetwork,
+            Network::Testnet => zcash_primitives::consensus::Network::TestNetwork,
+        }
+    }
+
+    pub fn address(self) -> zcash_addr::Network {
+        match self {
+            Network::Mainnet => zcash_addr::Network::Main,
+            Network::Testnet => zcash_addr::Network::Test,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LightwalletdConfig {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SolanaConfig {
+    pub rpc_url: String,
+    #[serde(default)]
+    pub keypair: Option<PathBuf>,  // Relayer key: pays fees, holds the minter role and attests as a guardian
+    pub signer: Option<SignerConfig>,  // Where the relayer key is kept instead of `keypair`
+    pub mint: String,
+    pub ws_url: Option<String>,  // WebSocket endpoint for following burns instead of scanning for them every poll
+    pub geyser: Option<GeyserConfig>,  // Yellowstone gRPC stream to follow burns from instead of `ws_url`
+    #[serde(default)]
+    pub fees: FeeConfig,
+    pub nonce_account: Option<String>,  // Durable nonce account the relayer key is authority of, so transactions outlive their blockhash
+}
+
+/// Yellowstone gRPC (Geyser plugin) stream of the bridge's transactions and
+/// withdrawal accounts
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeyserConfig {
+    pub endpoint: String,
+    #[serde(default = "default_geyser_token_env")]
+    pub token_env: String,  // Environment variable holding the x-token, when the endpoint wants one
+}
+
+impl SolanaConfig {
+    /// Backend holding the relayer key: `signer`, or else the `keypair` file
+    pub fn signer(&self) -> Result<SignerConfig> {
+        match (&self.signer, &self.keypair) {
+            (Some(signer), None) => Ok(signer.clone()),
+            (None, Some(keypair)) => Ok(SignerConfig::Local {
+                keypair: keypair.clone(),
+            }),
+            (Some(_), Some(_)) => anyhow::bail!("set either solana.keypair or [solana.signer], not both"),
+            (None, None) => anyhow::bail!("solana.keypair or a [solana.signer] section is required"),
+        }
+    }
+
+    pub fn nonce_account(&self) -> Result<Option<Pubkey>> {
+        self.nonce_account
+            .as_ref()
+            .map(|account| {
+                account
+                    .parse()
+                    .with_context(|| format!("solana.nonce_account {account:?} is not a valid public key"))
+            })
+            .transpose()
+    }
+}
+
+/// Priority fees on the relayer's Solana transactions, raised on every retry
+/// of the same submission
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeeConfig {
+    pub compute_unit_limit: u32,  // Requested per transaction; 0 leaves the runtime default
+    pub priority_fee: u64,  // Micro-lamports per compute unit on the first attempt; 0 for none
+    pub escalation_percent: u64,  // Added to the price on each retry
+    pub max_priority_fee: u64,
+}
+
+impl FeeConfig {
+    /// Compute unit price for the `attempt`th try, counting from 1
+    pub fn priority_fee(&self, attempt: u32) -> u64 {
+        let mut price = self.priority_fee;
+        for _ in 1..attempt {
+            price = price.saturating_add(price.saturating_mul(self.escalation_percent) / 100);
+            if price >= self.max_priority_fee {
+                break;
+            }
+        }
+        price.min(self.max_priority_fee)
+    }
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            compute_unit_limit: 200_000,
+            priority_fee: 1_000,
+            escalation_percent: 100,
+            max_priority_fee: 1_000_000,
+        }
+    }
+}
+
+/// Backend holding a key the relayer signs with
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum SignerConfig {
+    /// Solana keypair file on the relayer host
+    Local { keypair: PathBuf },
+    /// PKCS#11 token, such as a YubiHSM 2
+    Pkcs11 {
+        module: PathBuf,  // PKCS#11 library, e.g. yubihsm_pkcs11.so
+        slot: u64,
+        key_label: String,  // CKA_LABEL of the Ed25519 key pair
+        #[serde(default = "default_pin_env")]
+        pin_env: String,  // Environment variable holding the user PIN
+    },
+    /// gRPC signing service, see proto/signer.proto
+    Remote {
+        url: String,
+        key_id: String,
+        tls_ca: Option<PathBuf>,  // PEM roots to trust instead of the web PKI
+        tls_cert: Option<PathBuf>,  // PEM client certificate, for mutual TLS
+        tls_key: Option<PathBuf>,
+    },
+}
+
+/// Zcash wallet that pays out burn withdrawals
+#[derive(Debug, Deserialize)]
+pub struct PayoutConfig {
+    pub rpc_url: String,  // zcashd or Zallet wallet holding the deposit address's spending key
+    #[serde(default)]
+    pub rpc_user: String,
+    #[serde(default)]
+    pub rpc_password: String,
+    #[serde(default)]
+    pub extra_fee_actions: u64,  // Logical actions paid for beyond the ZIP-317 estimate, in case the wallet picks more notes
+    #[serde(default)]
+    pub batch_window_secs: u64,  // How long a withdrawal may wait for others to share its payout
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,  // Withdrawals paid by one transaction at most
+    #[serde(default = "default_operation_timeout_secs")]
+    pub operation_timeout_secs: u64,  // How long to wait for the wallet to build a payout before the next poll
+}
+
+/// Four-eyes approval of large payouts before the wallet is asked to send them
+#[derive(Debug, Deserialize)]
+pub struct ApprovalConfig {
+    pub threshold: u64,  // Payouts of more zatoshis than this wait for approval
+    #[serde(default = "default_approvals_required")]
+    pub required: usize,  // Distinct operators who must approve
+    pub operators: BTreeMap<String, String>,  // Operator name to the environment variable holding their admin API token
+    pub listen: SocketAddr,  // Admin API operators decide through, with their own tokens
+}
+
+/// Merging the deposit address's small Sapling notes while the bridge is quiet
+#[derive(Debug, Deserialize)]
+pub struct ConsolidationConfig {
+    #[serde(default = "default_consolidation_min_notes")]
+    pub min_notes: usize,  // Unspent Sapling notes that trigger a merge
+    #[serde(default = "default_consolidation_max_notes")]
+    pub max_notes: usize,  // Notes merged by one transaction at most
+    #[serde(default = "default_quiet_blocks")]
+    pub quiet_blocks: u32,  // Scanned blocks without a deposit before the bridge counts as quiet
+}
+
+/// Split of the bridge's ZEC between the hot deposit address, which the payout
+/// wallet can spend, and a cold reserve address whose key stays offline
+#[derive(Debug, Deserialize)]
+pub struct ReserveConfig {
+    pub cold_address: String,
+    pub hot_max: u64,  // Zatoshis; a hot balance above this is swept to `cold_address`
+    pub hot_min: u64,  // Zatoshis; a hot balance below this raises an alert
+    #[serde(default)]
+    pub pause_withdrawals: bool,  // Also pause burns below `hot_min`; needs the authority key
+}
+
+/// Several instances sharing one database, only the holder of the leader
+/// lease acting at a time
+#[derive(Debug, Deserialize)]
+pub struct HaConfig {
+    #[serde(default = "default_instance_id")]
+    pub instance_id: String,  // Unique per instance; random by default
+    #[serde(default = "default_lease_secs")]
+    pub lease_secs: u64,  // How long a leader that stops renewing keeps the lease
+}
+
+/// Guardian attestations exchanged between independent relayers, so a mint
+/// carries a quorum of observers rather than one
+#[derive(Debug, Deserialize)]
+pub struct AttestationConfig {
+    #[serde(default)]
+    pub peers: Vec<String>,  // Other relayers asked to attest each deposit this one mints
+    pub listen: Option<SocketAddr>,  // Where this relayer attests deposits it has seen for others
+    #[serde(default)]
+    pub observe_only: bool,  // Record and attest deposits, but leave minting to another relayer
+    #[serde(default = "default_attestation_token_env")]
+    pub token_env: String,  // Environment variable holding the bearer token shared with peers
+}
+
+/// FROST threshold signing of Orchard spends from the reserve, split between
+/// operators
+#[derive(Debug, Deserialize)]
+pub struct FrostConfig {
+    pub public_key_package: PathBuf,  // Hex-encoded group public key package from key generation
+    pub min_signers: u16,
+    #[serde(default)]
+    pub participants: Vec<String>,  // Participant URLs the coordinator asks to sign
+    pub key_package: Option<PathBuf>,  // This operator's hex-encoded key share, to run a participant
+    pub listen: Option<SocketAddr>,  // Where this operator's participant listens
+    #[serde(default = "default_frost_token_env")]
+    pub token_env: String,  // Environment variable holding the bearer token shared with participants
+}
+
+/// Conditions worth waking an operator for, and where to send them
+#[derive(Debug, Deserialize)]
+pub struct AlertConfig {
+    #[serde(default)]
+    pub sinks: Vec<AlertSink>,
+    #[serde(default = "default_stuck_deposit_mins")]
+    pub stuck_deposit_mins: u64,  // Age at which a pending deposit counts as stuck
+    pub reserve_ratio_min: Option<f64>,  // Lowest attested reserve per circulating wZEC; unchecked without it
+    #[serde(default = "default_repeat_mins")]
+    pub repeat_mins: u64,  // How often an alert that keeps firing is sent again
+}
+
+/// Destination for alerts
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum AlertSink {
+    /// JSON POST of the alert
+    Webhook { url: String },
+    /// Slack incoming webhook
+    Slack {
+        #[serde(default = "default_slack_url_env")]
+        url_env: String,  // Environment variable holding the webhook URL
+    },
+    /// PagerDuty Events API v2
+    Pagerduty {
+        #[serde(default = "default_pagerduty_key_env")]
+        routing_key_env: String,  // Environment variable holding the integration's routing key
+    },
+}
+
+/// Signed, hash-chained log of the relayer's consequential actions
+#[derive(Debug, Deserialize)]
+pub struct AuditConfig {
+    pub signer: SignerConfig,  // Operator key that signs every entry, kept apart from the relayer key
+}
+
+/// OpenTelemetry export of the relayer's tracing spans
+#[derive(Debug, Deserialize)]
+pub struct TelemetryConfig {
+    pub otlp_endpoint: String,  // OTLP/gRPC collector, e.g. http://127.0.0.1:4317
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+/// Read-only HTTP status API
+#[derive(Debug, Deserialize)]
+pub struct StatusConfig {
+    pub listen: SocketAddr,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DatabaseConfig {
+    #[serde(default = "default_database_path")]
+    pub path: PathBuf,  // SQLite file, created on first run
+    pub url_env: Option<String>,  // Environment variable holding a postgres:// URL to use instead, as [ha] needs
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            path: default_database_path(),
+            url_env: None,
+        }
+    }
+}
+
+/// Exponential backoff for failed mint submissions
+#[derive(Debug, Deserialize)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+/// Bounds on the deposit pipeline, which checks, attests and builds mints
+/// concurrently, then records and sends them one at a time in scan order
+#[derive(Debug, Deserialize)]
+pub struct PipelineConfig {
+    pub queue_depth: usize,  // Built mints waiting to be committed
+    pub attest_concurrency: usize,  // Deposits checked, attested and built at once
+    pub submit_concurrency: usize,  // Sent mints waiting to confirm at once
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            queue_depth: 16,
+            attest_concurrency: 4,
+            submit_concurrency: 1,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_poll_interval_secs() -> u64 {
+    30
+}
+
+fn default_max_batch_size() -> usize {
+    50
+}
+
+fn default_database_path() -> PathBuf {
+    PathBuf::from("relayer.sqlite")
+}
+
+fn default_pin_env() -> String {
+    "SOLZ_PKCS11_PIN".to_string()
+}
+
+fn default_instance_id() -> String {
+    format!("{}-{:08x}", std::process::id(), rand::random::<u32>())
+}
+
+fn default_lease_secs() -> u64 {
+    30
+}
+
+fn default_approvals_required() -> usize {
+    2
+}
+
+fn default_stuck_deposit_mins() -> u64 {
+    30
+}
+
+fn default_repeat_mins() -> u64 {
+    60
+}
+
+fn default_slack_url_env() -> String {
+    "SOLZ_SLACK_WEBHOOK".to_string()
+}
+
+fn default_pagerduty_key_env() -> String {
+    "SOLZ_PAGERDUTY_KEY".to_string()
+}
+
+fn default_service_name() -> String {
+    "solz-relayer".to_string()
+}
+
+fn default_geyser_token_env() -> String {
+    "SOLZ_GEYSER_TOKEN".to_string()
+}
+
+fn default_attestation_token_env() -> String {
+    "SOLZ_ATTESTATION_TOKEN".to_string()
+}
+
+fn default_frost_token_env() -> String {
+    "SOLZ_FROST_TOKEN".to_string()
+}
+
+fn default_consolidation_min_notes() -> usize {
+    20
+}
+
+fn default_consolidation_max_notes() -> usize {
+    50
+}
+
+fn default_quiet_blocks() -> u32 {
+    10
+}
+
+fn default_operation_timeout_secs() -> u64 {
+    60
+}
+
+/// Set the `__`-separated `key`, e.g. `PAYOUTS__BATCH_WINDOW_SECS` for
+/// `payouts.batch_window_secs`, to `value`
+///
+/// The value is read as TOML, so numbers, booleans and inline tables and
+/// arrays keep their type; anything that doesn't parse is taken as a string.
+fn override_setting(table: &mut toml::Table, key: &str, value: &str) -> Result<()> {
+    let path: Vec<String> = key.split("__").map(str::to_lowercase).collect();
+    let (name, parents) = path.split_last().context("empty setting name")?;
+    let mut table = table;
+    for parent in parents {
+        table = table
+            .entry(parent.clone())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .with_context(|| format!("{parent} is not a section"))?;
+    }
+    let value = toml::from_str::<toml::Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut parsed| parsed.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()));
+    table.insert(name.clone(), value);
+    Ok(())
+}
+
+/// Append to `changed` the dotted names of the settings that differ between
+/// `old` and `new`; a section present on one side only counts as one
+fn changed_settings(prefix: &str, old: &toml::Table, new: &toml::Table, changed: &mut Vec<String>) {
+    let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for name in names {
+        let setting = if prefix.is_empty() { name.clone() } else { format!("{prefix}.{name}") };
+        match (old.get(name), new.get(name)) {
+            (Some(toml::Value::Table(old)), Some(toml::Value::Table(new))) => {
+                changed_settings(&setting, old, new, changed)
+            }
+            (old, new) if old != new => changed.push(setting),
+            _ => {}
+        }
+    }
+}
+
+impl Config {
+    /// Read `path`, override it from `SOLZ__` environment variables, and
+    /// check the result
+    ///
+    /// A setting the relayer doesn't know is an error rather than ignored, so
+    /// a misspelt key can't silently leave its default in place.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::load_with(path, std::env::vars())
+    }
+
+    /// `load`, with `vars` standing in for the environment
+    fn load_with(path: &Path, vars: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading config {}", path.display()))?;
+        let mut table: toml::Table = toml::from_str(&text)
+            .with_context(|| format!("parsing config {}", path.display()))?;
+        for (name, value) in vars {
+            if let Some(key) = name.strip_prefix(ENV_PREFIX) {
+                override_setting(&mut table, key, &value).with_context(|| format!("applying ${name}"))?;
+            }
+        }
+        let mut unknown = Vec::new();
+        let mut config: Config = serde_ignored::deserialize(toml::Value::Table(table.clone()), |setting| {
+            unknown.push(setting.to_string())
+        })
+        .with_context(|| format!("parsing config {}", path.display()))?;
+        anyhow::ensure!(unknown.is_empty(), "unknown settings in config {}: {}", path.display(), unknown.join(", "));
+        config.validate()?;
+        config.digest = hex::encode(solana_sdk::hash::hash(toml::to_string(&table)?.as_bytes()).to_bytes());
+        config.path = path.to_path_buf();
+        config.table = table;
+        Ok(config)
+    }
+
+    /// Take the settings in [`RELOADABLE`] from `fresh`, returning the other
+    /// settings that differ from startup and so need a restart
+    pub fn reload(&mut self, fresh: Config) -> Vec<String> {
+        let mut restart = Vec::new();
+        changed_settings("", &self.table, &fresh.table, &mut restart);
+        restart.retain(|setting| {
+            !RELOADABLE
+                .iter()
+                .any(|reloadable| setting == reloadable || setting.starts_with(&format!("{reloadable}.")))
+        });
+
+        self.zcash.confirmations = fresh.zcash.confirmations;
+        self.zcash.poll_interval_secs = fresh.zcash.poll_interval_secs;
+        self.zcash.watch_mempool = fresh.zcash.watch_mempool;
+        self.zcash.min_deposit = fresh.zcash.min_deposit;
+        if let (Some(payouts), Some(fresh_payouts)) = (&mut self.payouts, &fresh.payouts) {
+            payouts.batch_window_secs = fresh_payouts.batch_window_secs;
+            payouts.max_batch_size = fresh_payouts.max_batch_size;
+            payouts.extra_fee_actions = fresh_payouts.extra_fee_actions;
+            payouts.operation_timeout_secs = fresh_payouts.operation_timeout_secs;
+        }
+        self.alerts = fresh.alerts;
+        self.retry = fresh.retry;
+        self.digest = fresh.digest;
+        restart
+    }
+
+    fn validate(&self) -> Result<()> {
+        let deposit_address = zcash_addr::Address::parse_for(&self.zcash.deposit_address, self.zcash.network.address())
+            .with_context(|| format!("zcash.deposit_address {:?}", self.zcash.deposit_address))?;
+        anyhow::ensure!(
+            deposit_address.can_receive_memo(),
+            "zcash.deposit_address has no shielded receiver to carry deposit memos"
+        );
+        anyhow::ensure!(self.zcash.confirmations > 0, "zcash.confirmations must be at least 1");
+        anyhow::ensure!(self.retry.max_attempts > 0, "retry.max_attempts must be at least 1");
+        anyhow::ensure!(self.pipeline.queue_depth > 0, "pipeline.queue_depth must be at least 1");
+        anyhow::ensure!(self.pipeline.attest_concurrency > 0, "pipeline.attest_concurrency must be at least 1");
+        anyhow::ensure!(self.pipeline.submit_concurrency > 0, "pipeline.submit_concurrency must be at least 1");
+        anyhow::ensure!(
+            self.pipeline.submit_concurrency == 1 || self.solana.nonce_account.is_none(),
+            "transactions on solana.nonce_account can't be in flight together; leave pipeline.submit_concurrency at 1"
+        );
+        if let SignerConfig::Remote { tls_cert, tls_key, .. } = self.solana.signer()? {
+            anyhow::ensure!(
+                tls_cert.is_some() == tls_key.is_some(),
+                "solana.signer.tls_cert and solana.signer.tls_key go together"
+            );
+        }
+        anyhow::ensure!(
+            self.solana.fees.priority_fee <= self.solana.fees.max_priority_fee,
+            "solana.fees.priority_fee must not exceed solana.fees.max_priority_fee"
+        );
+        self.solana.nonce_account()?;
+        if let Some(ws_url) = &self.solana.ws_url {
+            anyhow::ensure!(
+                ws_url.starts_with("ws://") || ws_url.starts_with("wss://"),
+                "solana.ws_url must be a ws:// or wss:// URL"
+            );
+        }
+        anyhow::ensure!(
+            self.solana.ws_url.is_none() || self.solana.geyser.is_none(),
+            "set either solana.ws_url or [solana.geyser], not both"
+        );
+        match self.zcash.backend {
+            Backend::Zcashd | Backend::Zebra => anyhow::ensure!(
+                !self.zcash.rpc_url.is_empty(),
+                "zcash.rpc_url is required by the {:?} backend",
+                self.zcash.backend
+            ),
+            Backend::Lightwalletd => anyhow::ensure!(
+                self.lightwalletd.is_some(),
+                "a [lightwalletd] section is required by the lightwalletd backend"
+            ),
+        }
+        if let Some(payouts) = &self.payouts {
+            anyhow::ensure!(!payouts.rpc_url.is_empty(), "payouts.rpc_url is required");
+            anyhow::ensure!(payouts.max_batch_size > 0, "payouts.max_batch_size must be at least 1");
+        }
+        if let Some(approvals) = &self.approvals {
+            anyhow::ensure!(self.payouts.is_some(), "approvals needs the [payouts] wallet");
+            anyhow::ensure!(
+                approvals.required >= 2,
+                "approvals.required must be at least 2, or one operator could release payouts alone"
+            );
+            anyhow::ensure!(
+                approvals.operators.len() >= approvals.required,
+                "approvals.operators lists fewer operators than approvals.required"
+            );
+        }
+        if let Some(consolidation) = &self.consolidation {
+            anyhow::ensure!(self.payouts.is_some(), "consolidation needs the [payouts] wallet");
+            anyhow::ensure!(
+                consolidation.min_notes >= 2 && consolidation.max_notes >= 2,
+                "consolidation.min_notes and consolidation.max_notes must be at least 2"
+            );
+        }
+        if let Some(reserve) = &self.reserve {
+            anyhow::ensure!(self.payouts.is_some(), "reserve sweeps need the [payouts] wallet");
+            zcash_addr::Address::parse_for(&reserve.cold_address, self.zcash.network.address())
+                .with_context(|| format!("reserve.cold_address {:?}", reserve.cold_address))?;
+            anyhow::ensure!(
+                reserve.cold_address != self.zcash.deposit_address,
+                "reserve.cold_address must differ from zcash.deposit_address"
+            );
+            anyhow::ensure!(reserve.hot_min < reserve.hot_max, "reserve.hot_min must be below reserve.hot_max");
+        }
+        if let Some(attestation) = &self.attestation {
+            anyhow::ensure!(
+                !(attestation.observe_only && !attestation.peers.is_empty()),
+                "an observe_only relayer doesn't mint, so it has no use for attestation.peers"
+            );
+            anyhow::ensure!(
+                !attestation.observe_only || attestation.listen.is_some(),
+                "an observe_only relayer needs attestation.listen to attest for the minting relayer"
+            );
+        }
+        if let Some(SignerConfig::Remote { tls_cert, tls_key, .. }) = self.audit.as_ref().map(|audit| &audit.signer) {
+            anyhow::ensure!(
+                tls_cert.is_some() == tls_key.is_some(),
+                "audit.signer.tls_cert and audit.signer.tls_key go together"
+            );
+        }
+        if let Some(alerts) = &self.alerts {
+            anyhow::ensure!(!alerts.sinks.is_empty(), "alerts needs at least one [[alerts.sinks]] entry");
+            anyhow::ensure!(alerts.repeat_mins > 0, "alerts.repeat_mins must be at least 1");
+        }
+        if let Some(ha) = &self.ha {
+            anyhow::ensure!(ha.lease_secs >= 3, "ha.lease_secs must be at least 3");
+            anyhow::ensure!(!ha.instance_id.is_empty(), "ha.instance_id must not be empty");
+            anyhow::ensure!(
+                self.database.url_env.is_some(),
+                "ha needs a database every instance reaches over the network: set database.url_env to a Postgres URL, \
+                 as a SQLite file can't be shared between hosts"
+            );
+        }
+        if let Some(frost) = &self.frost {
+            anyhow::ensure!(frost.min_signers >= 2, "frost.min_signers must be at least 2");
+            anyhow::ensure!(
+                frost.participants.is_empty() || frost.participants.len() >= frost.min_signers as usize,
+                "frost.participants lists fewer participants than frost.min_signers"
+            );
+        }
+        self.mint()?;
+        Ok(())
+    }
+
+    pub fn mint(&self) -> Result<Pubkey> {
+        self.solana
+            .mint
+            .parse()
+            .with_context(|| format!("solana.mint {:?} is not a valid public key", self.solana.mint))
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.zcash.poll_interval_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Smallest config the relayer accepts
+    const MINIMAL: &str = r#"
+        [zcash]
+        network = "testnet"
+        rpc_url = "http://127.0.0.1:18232"
+        deposit_address = "ztestsapling1gh7w9vhta6eaq23c0qrv53z0xamgpg099q4cs68epka5gp6evvwj6ugkg2mjdvzyq938c6lwyvu"
+        viewing_key = "zxviewtestsapling1..."
+        birthday_height = 2800000
+        confirmations = 6
+
+        [solana]
+        rpc_url = "http://127.0.0.1:8899"
+        keypair = "relayer.json"
+        mint = "So11111111111111111111111111111111111111112"
+    "#;
+
+    /// `text` written to a config file in the temp directory, one per test
+    fn write(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("solz-relayer-{name}-{}.toml", std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn load(name: &str, text: &str) -> Result<Config> {
+        Config::load_with(&write(name, text), [])
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn minimal_config_loads() {
+        let config = load("minimal", MINIMAL).unwrap();
+        assert_eq!(config.zcash.confirmations, 6);
+        assert!(config.approvals.is_none());
+    }
+
+    #[test]
+    fn approvals_need_two_operators() {
+        let approvals = |required: usize| {
+            format!(
+                r#"{MINIMAL}
+                [payouts]
+                rpc_url = "http://127.0.0.1:18232"
+
+                [approvals]
+                threshold = 100000000000
+                required = {required}
+                listen = "127.0.0.1:8091"
+
+                [approvals.operators]
+                alice = "SOLZ_ADMIN_TOKEN_ALICE"
+                bob = "SOLZ_ADMIN_TOKEN_BOB"
+                "#
+            )
+        };
+        let config = load("approvals-two", &approvals(2)).unwrap();
+        assert_eq!(config.approvals.unwrap().required, 2);
+        for required in [0, 1] {
+            let error = load("approvals-one", &approvals(required)).unwrap_err();
+            assert!(format!("{error:#}").contains("approvals.required must be at least 2"), "{error:#}");
+        }
+        // More approvals than operators
+        assert!(load("approvals-three", &approvals(3)).is_err());
+    }
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let path = write("overrides", &format!("{MINIMAL}\n[retry]\nmax_attempts = 5\n"));
+        let overrides = vars(&[
+            ("SOLZ__ZCASH__CONFIRMATIONS", "12"),
+            ("SOLZ__ZCASH__RPC_URL", "http://10.0.0.5:18232"),
+            ("SOLZ__RETRY__MAX_ATTEMPTS", "9"),
+            ("SOLZ__PAYOUTS__RPC_URL", "http://10.0.0.6:18232"),
+            ("SOLZ__PAYOUTS__MAX_BATCH_SIZE", "7"),
+            ("SOLZ__DRY_RUN", "true"),
+            ("ZCASH__CONFIRMATIONS", "1"),  // Not ours, without the prefix
+        ]);
+        let file = Config::load_with(&path, []).unwrap();
+        let config = Config::load_with(&path, overrides).unwrap();
+
+        // Overrides win over the file, keep their TOML type, and can add a section
+        assert_eq!((file.zcash.confirmations, config.zcash.confirmations), (6, 12));
+        assert_eq!(config.zcash.rpc_url, "http://10.0.0.5:18232");
+        assert_eq!((file.retry.max_attempts, config.retry.max_attempts), (5, 9));
+        let payouts = config.payouts.as_ref().unwrap();
+        assert_eq!((payouts.rpc_url.as_str(), payouts.max_batch_size), ("http://10.0.0.6:18232", 7));
+        assert!(config.dry_run && !file.dry_run);
+        // Settings nothing overrides keep the file's value
+        assert_eq!(config.zcash.birthday_height, 2800000);
+        // The digest covers the overrides
+        assert_ne!(config.digest, file.digest);
+        assert_eq!(Config::load_with(&path, []).unwrap().digest, file.digest);
+    }
+
+    #[test]
+    fn overrides_are_checked_like_the_file() {
+        let path = write("bad-overrides", MINIMAL);
+        let rejected = |name: &str, value: &str| {
+            format!("{:#}", Config::load_with(&path, vars(&[(name, value)])).unwrap_err())
+        };
+        assert!(rejected("SOLZ__ZCASH__CONFIRMATINS", "12").contains("unknown settings"));
+        assert!(rejected("SOLZ__ZCASH__CONFIRMATIONS", "0").contains("zcash.confirmations must be at least 1"));
+        assert!(rejected("SOLZ__ZCASH__CONFIRMATIONS", "twelve").contains("parsing config"));
+        assert!(rejected("SOLZ__ZCASH__NETWORK__NAME", "testnet").contains("network is not a section"));
+    }
+
+    #[test]
+    fn reload_takes_reloadable_settings_only() {
+        let mut config = load("reload", MINIMAL).unwrap();
+        let changed = MINIMAL
+            .replace("confirmations = 6", "confirmations = 10\nmin_deposit = 100000")
+            .replace("http://127.0.0.1:8899", "http://10.0.0.7:8899");
+        let fresh = Config::load_with(&write("reload", &changed), []).unwrap();
+        let digest = fresh.digest.clone();
+
+        let restart = config.reload(fresh);
+        assert_eq!(restart, ["solana.rpc_url"]);
+        assert_eq!((config.zcash.confirmations, config.zcash.min_deposit), (10, 100000));
+        assert_eq!(config.solana.rpc_url, "http://127.0.0.1:8899");
+        assert_eq!(config.digest, digest);
+    }
+
+    #[test]
+    fn invalid_file_leaves_the_running_config() {
+        let mut config = load("reload-invalid", MINIMAL).unwrap();
+        let digest = config.digest.clone();
+
+        // What SIGHUP does: read the file again, and take it only if it loads
+        for invalid in [
+            MINIMAL.replace("confirmations = 6", "confirmations = 0"),
+            MINIMAL.replace("confirmations = 6", "confirmations = 10\nconfirmation = 10"),
+            MINIMAL.replace("confirmations = 6", "confirmations = 10\n[zcash"),
+            String::new(),
+        ] {
+            write("reload-invalid", &invalid);
+            assert!(Config::load_with(&config.path, []).is_err(), "{invalid}");
+        }
+        assert_eq!(config.zcash.confirmations, 6);
+        assert_eq!(config.digest, digest);
+
+        // Fixed, the next reload takes it
+        write("reload-invalid", &MINIMAL.replace("confirmations = 6", "confirmations = 10"));
+        let fresh = Config::load_with(&config.path, []).unwrap();
+        assert!(config.reload(fresh).is_empty());
+        assert_eq!(config.zcash.confirmations, 10);
+        assert_ne!(config.digest, digest);
+    }
+
+    #[test]
+    fn ha_needs_a_shared_database() {
+        let error = load("ha-sqlite", &format!("{MINIMAL}\n[ha]\ninstance_id = \"relayer-a\"\n")).unwrap_err();
+        assert!(format!("{error:#}").contains("set database.url_env to a Postgres URL"), "{error:#}");
+
+        let shared = format!("{MINIMAL}\n[ha]\ninstance_id = \"relayer-a\"\n\n[database]\nurl_env = \"SOLZ_DATABASE_URL\"\n");
+        let config = load("ha-postgres", &shared).unwrap();
+        assert_eq!(config.ha.unwrap().instance_id, "relayer-a");
+        assert_eq!(config.database.url_env.as_deref(), Some("SOLZ_DATABASE_URL"));
+
+        assert!(load("ha-short", &shared.replace("[ha]\n", "[ha]\nlease_secs = 2\n")).is_err());
+    }
+}
+
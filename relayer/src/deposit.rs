@@ -0,0 +1,232 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, bail, Context, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::memo;
+use crate::scan::ScannedTransaction;
+
+/// Shielded pool a note belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pool {
+    Sapling,
+    Orchard,
+}
+
+impl Pool {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Pool::Sapling => "sapling",
+            Pool::Orchard => "orchard",
+        }
+    }
+}
+
+/// An output the deposit address received, as found by scanning
+#[derive(Debug, Clone)]
+pub struct ReceivedNote {
+    pub pool: Pool,
+    pub txid: String,  // Display order
+    pub output_index: u32,  // Sapling output or Orchard action index
+    pub amount_zat: u64,
+    pub memo: [u8; 512],
+    pub confirmations: u32,
+    pub height: u32,
+    pub position: u64,  // Leaf position in the pool's note commitment tree
+    pub commitment: [u8; 32],  // Sapling cmu or Orchard cmx
+    pub nullifier: [u8; 32],
+    pub diversifier_index: [u8; 11],  // Of the address the note paid
+}
+
+/// A nullifier revealed by a transaction, possibly spending one of our notes
+#[derive(Debug, Clone)]
+pub struct Spend {
+    pub pool: Pool,
+    pub nullifier: [u8; 32],
+    pub txid: String,  // Display order
+    pub height: u32,
+}
+
+/// What a scan of newly confirmed blocks found
+#[derive(Debug, Default)]
+pub struct Scan {
+    pub blocks: Vec<(u32, String)>,  // Height and display-order hash of each scanned block
+    pub notes: Vec<ReceivedNote>,
+    pub spends: Vec<Spend>,
+}
+
+/// What a look at the mempool found
+#[derive(Debug, Default)]
+pub struct MempoolScan {
+    pub txids: HashSet<String>,  // Display order, every transaction in the mempool
+    pub transactions: Vec<(String, ScannedTransaction)>,  // Newly seen ones paying the deposit address
+}
+
+/// A shielded deposit to the bridge address, attributed to a Solana recipient
+#[derive(Debug, Clone)]
+pub struct Deposit {
+    pub txid: String,  // Display order, as zcashd and explorers show it
+    pub zcash_txid: [u8; 32],  // Internal byte order, as `mint_wzec` takes it
+    pub amount: u64,  // Zatoshis
+    pub recipient: Pubkey,
+    pub block_height: u32,
+    pub confirmations: u32,
+}
+
+impl Deposit {
+    /// Attribute the notes one transaction paid the bridge, `assigned` holding
+    /// for each the recipient its address was issued to, or otherwise taking
+    /// the Solana wallet named in its memo
+    ///
+    /// A transaction is minted once, for the sum of its notes, so they must
+    /// all name the same recipient; one paying several is refused.
+    pub fn from_notes(notes: &[ReceivedNote], assigned: &[Option<Pubkey>]) -> Result<Self> {
+        let first = notes.first().context("deposit without notes")?;
+        let mut recipient = None;
+        let mut amount = 0u64;
+        for (note, assigned) in notes.iter().zip(assigned) {
+            let named = match assigned {
+                Some(recipient) => *recipient,
+                None => memo::recipient(&note.memo)
+                    .with_context(|| format!("deposit {} output {}", note.txid, note.output_index))?,
+            };
+            match recipient {
+                Some(earlier) if earlier != named => {
+                    bail!("deposit {} pays both {earlier} and {named}", note.txid)
+                }
+                _ => recipient = Some(named),
+            }
+            amount = amount
+                .checked_add(note.amount_zat)
+                .with_context(|| format!("deposit {} amount overflows", note.txid))?;
+        }
+        Ok(Self {
+            txid: first.txid.clone(),
+            zcash_txid: txid_bytes(&first.txid)?,
+            amount,
+            recipient: recipient.context("deposit without notes")?,
+            block_height: first.height,
+            confirmations: first.confirmations,
+        })
+    }
+
+    /// Key of everything the relayer does to mint this deposit
+    pub fn idempotency_id(&self) -> String {
+        format!("mint:{}", self.txid)
+    }
+}
+
+/// Group `notes` by the transaction that paid them, in the order each
+/// transaction first appears, dropping notes listed more than once
+pub fn by_transaction(notes: Vec<ReceivedNote>) -> Vec<Vec<ReceivedNote>> {
+    let mut transactions: Vec<Vec<ReceivedNote>> = Vec::new();
+    let mut seen = HashSet::new();
+    for note in notes {
+        if !seen.insert((note.txid.clone(), note.pool.as_str(), note.output_index)) {
+            continue;
+        }
+        match transactions.iter_mut().find(|notes| notes[0].txid == note.txid) {
+            Some(notes) => notes.push(note),
+            None => transactions.push(vec![note]),
+        }
+    }
+    transactions
+}
+
+/// Txid bytes in internal order from the display-order hex nodes report
+pub fn txid_bytes(txid: &str) -> Result<[u8; 32]> {
+    let mut bytes: [u8; 32] = hex::decode(txid)
+        .with_context(|| format!("txid {txid} is not hex"))?
+        .try_into()
+        .map_err(|_| anyhow!("txid {txid} is not 32 bytes"))?;
+    bytes.reverse();
+    Ok(bytes)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Display-order txid made of `byte` repeated
+    pub(crate) fn txid(byte: u8) -> String {
+        hex::encode([byte; 32])
+    }
+
+    /// A Sapling note of `amount_zat` paid by output `output_index` of the
+    /// transaction `txid(tx)`, with no memo
+    pub(crate) fn note(tx: u8, output_index: u32, amount_zat: u64) -> ReceivedNote {
+        let mut memo = [0; 512];
+        memo[0] = 0xF6;
+        let mut nullifier = [0xAA; 32];
+        nullifier[0] = tx;
+        nullifier[1] = output_index as u8;
+        ReceivedNote {
+            pool: Pool::Sapling,
+            txid: txid(tx),
+            output_index,
+            amount_zat,
+            memo,
+            confirmations: 10,
+            height: 100,
+            position: output_index.into(),
+            commitment: [tx; 32],
+            nullifier,
+            diversifier_index: [0; 11],
+        }
+    }
+
+    /// `note` with a memo naming `recipient` in the legacy format
+    pub(crate) fn naming(mut note: ReceivedNote, recipient: &Pubkey) -> ReceivedNote {
+        let text = recipient.to_string();
+        note.memo = [0; 512];
+        note.memo[..text.len()].copy_from_slice(text.as_bytes());
+        note
+    }
+
+    #[test]
+    fn notes_of_one_transaction_are_summed() {
+        let recipient = Pubkey::new_unique();
+        let notes = [naming(note(1, 0, 30_000), &recipient), naming(note(1, 1, 20_000), &recipient)];
+        let deposit = Deposit::from_notes(&notes, &[None, None]).unwrap();
+        assert_eq!(deposit.amount, 50_000);
+        assert_eq!(deposit.recipient, recipient);
+        assert_eq!(deposit.txid, txid(1));
+    }
+
+    #[test]
+    fn issued_address_and_memo_naming_the_same_recipient_agree() {
+        let recipient = Pubkey::new_unique();
+        let notes = [note(1, 0, 30_000), naming(note(1, 1, 20_000), &recipient)];
+        let deposit = Deposit::from_notes(&notes, &[Some(recipient), None]).unwrap();
+        assert_eq!(deposit.amount, 50_000);
+    }
+
+    #[test]
+    fn transaction_paying_two_recipients_is_refused() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let notes = [note(1, 0, 30_000), note(1, 1, 20_000)];
+        assert!(Deposit::from_notes(&notes, &[Some(alice), Some(bob)]).is_err());
+        let notes = [naming(note(1, 0, 30_000), &alice), naming(note(1, 1, 20_000), &bob)];
+        assert!(Deposit::from_notes(&notes, &[None, None]).is_err());
+    }
+
+    #[test]
+    fn any_note_without_a_recipient_refuses_the_transaction() {
+        let recipient = Pubkey::new_unique();
+        let notes = [naming(note(1, 0, 30_000), &recipient), note(1, 1, 20_000)];
+        assert!(Deposit::from_notes(&notes, &[None, None]).is_err());
+    }
+
+    #[test]
+    fn notes_are_grouped_by_transaction_in_scan_order() {
+        let notes = vec![note(2, 0, 1), note(1, 0, 2), note(2, 1, 3), note(2, 0, 1), note(1, 1, 4)];
+        let grouped: Vec<Vec<(String, u32)>> = by_transaction(notes)
+            .into_iter()
+            .map(|notes| notes.into_iter().map(|note| (note.txid, note.output_index)).collect())
+            .collect();
+        assert_eq!(
+            grouped,
+            [vec![(txid(2), 0), (txid(2), 1)], vec![(txid(1), 0), (txid(1), 1)]]
+        );
+    }
+}
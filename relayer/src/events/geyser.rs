@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anyhow::{bail, Context, Result};
+use futures::StreamExt;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use tracing::{debug, info};
+use wzec_bridge::{interface, WithdrawalRequest, WithdrawalStatus};
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::prelude::{
+    subscribe_request_filter_accounts_filter, subscribe_request_filter_accounts_filter_memcmp,
+    subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
+    SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp,
+    SubscribeRequestFilterTransactions, SubscribeUpdateAccount, SubscribeUpdateTransaction,
+};
+
+use super::WithdrawalListener;
+use crate::config::GeyserConfig;
+
+/// Follow the bridge's transactions and withdrawal accounts on a Yellowstone
+/// gRPC stream until it drops
+///
+/// Transactions drive the cursor and carry the burn events, as on the
+/// WebSocket feed. Account updates catch a request turning pending even
+/// without its logs, which some providers truncate.
+pub(super) async fn listen(listener: &WithdrawalListener, geyser: &GeyserConfig) -> Result<()> {
+    let token = std::env::var(&geyser.token_env).ok();
+    let mut client = GeyserGrpcClient::connect(geyser.endpoint.clone(), token, None)
+        .with_context(|| format!("connecting to {}", geyser.endpoint))?;
+    // The request sink stays open for the life of the stream
+    let (_requests, mut stream) = client
+        .subscribe_with_request(Some(subscribe_request(&listener.bridge_state)))
+        .await
+        .context("subscribing to the Geyser stream")?;
+
+    let mut backfilled = listener.backfill().await?;
+    info!(backfilled = backfilled.len(), endpoint = %geyser.endpoint, "withdrawal listener subscribed");
+    while let Some(update) = stream.next().await {
+        match update.context("reading the Geyser stream")?.update_oneof {
+            Some(UpdateOneof::Transaction(update)) => {
+                let Some((signature, logs)) = transaction_logs(&update) else {
+                    continue;
+                };
+                if backfilled.remove(&signature) {
+                    continue;
+                }
+                listener.handle(&signature, update.slot, &logs).await?;
+            }
+            Some(UpdateOneof::Account(update)) => {
+                if let Some(nonce) = pending_nonce(&listener.bridge_state, &update) {
+                    debug!(nonce, slot = update.slot, "pending withdrawal account updated");
+                    listener.send(nonce)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    bail!("Geyser stream closed by the server")
+}
+
+/// Successful transactions mentioning the bridge state, and every
+/// `WithdrawalRequest` account of the program, at confirmed commitment
+fn subscribe_request(bridge_state: &Pubkey) -> SubscribeRequest {
+    let transactions = SubscribeRequestFilterTransactions {
+        vote: Some(false),
+        failed: Some(false),
+        account_include: vec![bridge_state.to_string()],
+        ..SubscribeRequestFilterTransactions::default()
+    };
+    let accounts = SubscribeRequestFilterAccounts {
+        owner: vec![wzec_bridge::ID.to_string()],
+        filters: vec![SubscribeRequestFilterAccountsFilter {
+            filter: Some(subscribe_request_filter_accounts_filter::Filter::Memcmp(
+                SubscribeRequestFilterAccountsFilterMemcmp {
+                    offset: 0,
+                    data: Some(subscribe_request_filter_accounts_filter_memcmp::Data::Bytes(
+                        WithdrawalRequest::DISCRIMINATOR.to_vec(),
+                    )),
+                },
+            )),
+        }],
+        ..SubscribeRequestFilterAccounts::default()
+    };
+    SubscribeRequest {
+        transactions: HashMap::from([("bridge".to_string(), transactions)]),
+        accounts: HashMap::from([("withdrawals".to_string(), accounts)]),
+        commitment: Some(CommitmentLevel::Confirmed as i32),
+        ..SubscribeRequest::default()
+    }
+}
+
+/// Signature and log messages of a streamed transaction
+fn transaction_logs(update: &SubscribeUpdateTransaction) -> Option<(String, Vec<String>)> {
+    let transaction = update.transaction.as_ref()?;
+    let meta = transaction.meta.as_ref()?;
+    if meta.err.is_some() {
+        return None;
+    }
+    let signature = Signature::try_from(transaction.signature.as_slice()).ok()?;
+    Some((signature.to_string(), meta.log_messages.clone()))
+}
+
+/// Nonce of a streamed withdrawal account of this bridge, if it is pending
+fn pending_nonce(bridge_state: &Pubkey, update: &SubscribeUpdateAccount) -> Option<u64> {
+    let account = update.account.as_ref()?;
+    let address = Pubkey::try_from(account.pubkey.as_slice()).ok()?;
+    let request = WithdrawalRequest::try_deserialize(&mut account.data.as_slice()).ok()?;
+    // Requests of other bridges share the program; only this bridge's PDA matches
+    (request.status == WithdrawalStatus::Pending
+        && interface::withdrawal_request_address(bridge_state, request.nonce) == address)
+        .then_some(request.nonce)
+}
@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{Context, Result};
+use base64::Engine;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::UiTransactionEncoding;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, instrument, warn};
+use wzec_bridge::{BurnEvent, WithdrawalUnvetoedEvent};
+
+use crate::chaos::{self, Fault};
+use crate::config::GeyserConfig;
+use crate::store::Store;
+
+mod geyser;
+mod websocket;
+
+/// Longest wait between reconnection attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Where the listener streams the bridge's transactions from
+pub enum Feed {
+    /// RPC node's `logsSubscribe`
+    WebSocket(String),
+    /// Yellowstone gRPC, which also streams the withdrawal accounts themselves
+    Geyser(GeyserConfig),
+}
+
+/// Watches the bridge's transactions on a [`Feed`] and sends the nonce of
+/// every withdrawal that becomes pending: each burn, and each withdrawal a
+/// guardian veto releases
+///
+/// The last transaction handled is saved as a cursor. Whenever the
+/// subscription is (re)established, everything since the cursor is
+/// backfilled with `getSignaturesForAddress`, so a dropped connection loses
+/// no burns.
+pub struct WithdrawalListener {
+    feed: Feed,
+    rpc: RpcClient,
+    bridge_state: Pubkey,
+    store: Store,
+    withdrawals: UnboundedSender<u64>,
+}
+
+impl WithdrawalListener {
+    /// Listener for `bridge_state`'s transactions, starting from the saved
+    /// cursor
+    ///
+    /// A fresh database has no cursor, so one is set at the bridge's latest
+    /// transaction: nothing after this returns is missed, and whatever came
+    /// before is for the caller to load from the program accounts.
+    pub async fn open(
+        feed: Feed,
+        rpc_url: &str,
+        bridge_state: Pubkey,
+        store: Store,
+        withdrawals: UnboundedSender<u64>,
+    ) -> Result<Self> {
+        let rpc = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+        if store.event_cursor().await?.is_none() {
+            let latest = rpc
+                .get_signatures_for_address_with_config(
+                    &bridge_state,
+                    GetConfirmedSignaturesForAddress2Config {
+                        limit: Some(1),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        ..GetConfirmedSignaturesForAddress2Config::default()
+                    },
+                )
+                .await
+                .context("fetching the bridge's latest transaction")?;
+            if let Some(latest) = latest.first() {
+                store.save_event_cursor(&latest.signature, latest.slot).await?;
+            }
+        }
+        Ok(Self {
+            feed,
+            rpc,
+            bridge_state,
+            store,
+            withdrawals,
+        })
+    }
+
+    /// Listen until the relayer stops, reconnecting with backoff
+    pub async fn run(self) {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            let started = tokio::time::Instant::now();
+            if let Err(error) = self.listen().await {
+                warn!(error = %format!("{error:#}"), "withdrawal listener disconnected; reconnecting");
+            }
+            if started.elapsed() > MAX_BACKOFF {
+                backoff = Duration::from_secs(1);
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    async fn listen(&self) -> Result<()> {
+        match &self.feed {
+            Feed::WebSocket(url) => websocket::listen(self, url).await,
+            Feed::Geyser(geyser) => geyser::listen(self, geyser).await,
+        }
+    }
+
+    /// Handle every bridge transaction since the cursor, oldest first,
+    /// returning their signatures
+    async fn backfill(&self) -> Result<HashSet<String>> {
+        let Some(cursor) = self.store.event_cursor().await? else {
+            return Ok(HashSet::new());
+        };
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    &self.bridge_state,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: Some(cursor),
+                        limit: None,
+                        commitment: Some(CommitmentConfig::confirmed()),
+                    },
+                )
+                .await
+                .context("backfilling bridge transactions")?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(last.signature.parse()?);
+            signatures.extend(page);
+        }
+
+        let mut handled = HashSet::new();
+        for status in signatures.into_iter().rev() {
+            if status.err.is_none() {
+                let signature: Signature = status.signature.parse()?;
+                let transaction = self
+                    .rpc
+                    .get_transaction_with_config(
+                        &signature,
+                        RpcTransactionConfig {
+                            encoding: Some(UiTransactionEncoding::Json),
+                            commitment: Some(CommitmentConfig::confirmed()),
+                            max_supported_transaction_version: Some(0),
+                        },
+                    )
+                    .await
+                    .with_context(|| format!("fetching bridge transaction {signature}"))?;
+                let logs = match transaction.transaction.meta.map(|meta| meta.log_messages) {
+                    Some(OptionSerializer::Some(logs)) => logs,
+                    _ => Vec::new(),
+                };
+                self.handle(&status.signature, status.slot, &logs).await?;
+            }
+            handled.insert(status.signature);
+        }
+        Ok(handled)
+    }
+
+    #[instrument(name = "bridge_transaction", skip(self, logs))]
+    async fn handle(&self, signature: &str, slot: u64, logs: &[String]) -> Result<()> {
+        for nonce in withdrawal_nonces(logs) {
+            debug!(nonce, %signature, "withdrawal seen");
+            self.send(nonce)?;
+        }
+        self.store.save_event_cursor(signature, slot).await
+    }
+
+    fn send(&self, nonce: u64) -> Result<()> {
+        self.withdrawals.send(nonce).context("relayer stopped taking withdrawals")?;
+        if chaos::strikes(Fault::DuplicateEvent) {
+            self.withdrawals.send(nonce).context("relayer stopped taking withdrawals")?;
+        }
+        Ok(())
+    }
+}
+
+/// Nonces of the `BurnEvent`s and `WithdrawalUnvetoedEvent`s the bridge
+/// program itself emitted in a transaction's `logs`
+///
+/// Logs are attributed to programs by following the invoke and
+/// success/failed lines, so events logged by other programs are ignored.
+fn withdrawal_nonces(logs: &[String]) -> Vec<u64> {
+    let program = wzec_bridge::ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut nonces = Vec::new();
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() != Some(&program.as_str()) {
+                continue;
+            }
+            let Ok(data) = base64::engine::general_purpose::STANDARD.decode(data) else {
+                continue;
+            };
+            if let Some(mut body) = data.strip_prefix(&BurnEvent::DISCRIMINATOR[..]) {
+                if let Ok(event) = BurnEvent::deserialize(&mut body) {
+                    nonces.push(event.nonce);
+                }
+            } else if let Some(mut body) = data.strip_prefix(&WithdrawalUnvetoedEvent::DISCRIMINATOR[..]) {
+                if let Ok(event) = WithdrawalUnvetoedEvent::deserialize(&mut body) {
+                    nonces.push(event.nonce);
+                }
+            }
+        } else if let Some((id, outcome)) = rest.split_once(' ') {
+            if outcome.starts_with("invoke [") {
+                stack.push(id);
+            } else if outcome == "success" || outcome.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    nonces
+}
@@ -0,0 +1,39 @@
+use anyhow::{bail, Context, Result};
+use futures::StreamExt;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use tracing::info;
+
+use super::WithdrawalListener;
+
+/// Follow the bridge's transaction logs with `logsSubscribe` until the
+/// subscription drops
+pub(super) async fn listen(listener: &WithdrawalListener, url: &str) -> Result<()> {
+    let pubsub = PubsubClient::new(url)
+        .await
+        .with_context(|| format!("connecting to {url}"))?;
+    let (mut stream, unsubscribe) = pubsub
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![listener.bridge_state.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )
+        .await
+        .context("subscribing to bridge logs")?;
+
+    // Subscribed first, so nothing landing during the backfill is missed;
+    // whatever both deliver is handled once
+    let mut backfilled = listener.backfill().await?;
+    info!(backfilled = backfilled.len(), "withdrawal listener subscribed");
+    while let Some(response) = stream.next().await {
+        let logs = response.value;
+        if logs.err.is_some() || backfilled.remove(&logs.signature) {
+            continue;
+        }
+        listener.handle(&logs.signature, response.context.slot, &logs.logs).await?;
+    }
+    unsubscribe().await;
+    bail!("log subscription closed by the server")
+}
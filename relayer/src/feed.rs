@@ -0,0 +1,143 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Events held for a slow event stream client before it starts missing them
+const EVENT_BUFFER: usize = 256;
+
+/// A deposit seen in the mempool, before it has any confirmations
+#[derive(Debug, Clone, Serialize)]
+pub struct SeenDeposit {
+    pub txid: String,  // Display order
+    pub amount: u64,  // Zatoshis
+    pub recipient: Option<String>,  // Solana wallet to mint to; none when the deposit will be quarantined
+    pub first_seen: i64,  // Unix time
+    pub in_mempool: bool,  // Cleared once it leaves the mempool, mined or evicted
+    #[serde(skip)]
+    pub forget_height: u32,  // Dropped once the tip passes this without scanning finding it
+}
+
+/// A step in a deposit's progress, streamed to front-ends
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DepositEvent {
+    /// In the mempool, with 0 confirmations
+    Seen {
+        txid: String,
+        amount: u64,
+        recipient: Option<String>,
+    },
+    /// Reached the confirmation depth and recorded, to be minted
+    Confirmed {
+        txid: String,
+        amount: u64,
+        recipient: String,
+        confirmations: u32,
+    },
+    /// wZEC minted to the recipient
+    Minted {
+        txid: String,
+        amount: u64,
+        recipient: String,
+        signature: String,
+    },
+    /// Held for an operator instead of minted
+    Quarantined { txid: String, amount: u64, reason: String },
+}
+
+impl DepositEvent {
+    /// Name the event goes by on the stream
+    pub fn name(&self) -> &'static str {
+        match self {
+            DepositEvent::Seen { .. } => "seen",
+            DepositEvent::Confirmed { .. } => "confirmed",
+            DepositEvent::Minted { .. } => "minted",
+            DepositEvent::Quarantined { .. } => "quarantined",
+        }
+    }
+
+    fn txid(&self) -> &str {
+        match self {
+            DepositEvent::Seen { txid, .. }
+            | DepositEvent::Confirmed { txid, .. }
+            | DepositEvent::Minted { txid, .. }
+            | DepositEvent::Quarantined { txid, .. } => txid,
+        }
+    }
+}
+
+/// Deposits seen in the mempool, and the stream of every deposit's
+/// progress, shared between the relayer and the status API
+#[derive(Clone)]
+pub struct DepositFeed {
+    seen: Arc<RwLock<BTreeMap<String, SeenDeposit>>>,  // By txid
+    events: broadcast::Sender<DepositEvent>,
+}
+
+impl Default for DepositFeed {
+    fn default() -> Self {
+        Self {
+            seen: Arc::default(),
+            events: broadcast::channel(EVENT_BUFFER).0,
+        }
+    }
+}
+
+impl DepositFeed {
+    /// Every deposit seen in the mempool that scanning hasn't found yet
+    pub fn seen(&self) -> Vec<SeenDeposit> {
+        self.seen
+            .read()
+            .map(|seen| seen.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// The deposit in `txid`, if it was seen in the mempool and scanning
+    /// hasn't found it yet
+    pub fn get(&self, txid: &str) -> Option<SeenDeposit> {
+        self.seen.read().ok()?.get(txid).cloned()
+    }
+
+    /// Add a deposit newly seen in the mempool
+    pub fn see(&self, deposit: SeenDeposit) {
+        let event = DepositEvent::Seen {
+            txid: deposit.txid.clone(),
+            amount: deposit.amount,
+            recipient: deposit.recipient.clone(),
+        };
+        if let Ok(mut seen) = self.seen.write() {
+            seen.insert(deposit.txid.clone(), deposit);
+        }
+        self.publish(event);
+    }
+
+    /// Mark which seen deposits are still in `mempool`, and drop those the
+    /// chain at `tip` shows will never be found
+    pub fn refresh(&self, mempool: &HashSet<String>, tip: u32) {
+        if let Ok(mut seen) = self.seen.write() {
+            seen.retain(|txid, deposit| {
+                deposit.in_mempool = mempool.contains(txid);
+                deposit.in_mempool || tip <= deposit.forget_height
+            });
+        }
+    }
+
+    /// Stream `event` to subscribers, forgetting the deposit from the
+    /// mempool once scanning has found it
+    pub fn publish(&self, event: DepositEvent) {
+        if !matches!(event, DepositEvent::Seen { .. }) {
+            if let Ok(mut seen) = self.seen.write() {
+                seen.remove(event.txid());
+            }
+        }
+        // No subscribers is fine
+        let _ = self.events.send(event);
+    }
+
+    /// Every event from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<DepositEvent> {
+        self.events.subscribe()
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use rand::RngCore;
+use reddsa::frost::redpallas as frost;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use super::{bytes32, public_key_package, CommitRequest, CommitResponse, SignRequest, SignResponse};
+use crate::config::FrostConfig;
+
+/// Runs signing sessions across the operators' participants
+pub struct Coordinator {
+    http: reqwest::Client,
+    participants: Vec<String>,
+    min_signers: usize,
+    public_key_package: frost::keys::PublicKeyPackage,
+    token: String,
+}
+
+impl Coordinator {
+    pub fn new(config: &FrostConfig) -> Result<Self> {
+        Ok(Self {
+            http: reqwest::Client::new(),
+            participants: config.participants.clone(),
+            min_signers: config.min_signers as usize,
+            public_key_package: public_key_package(&config.public_key_package)?,
+            token: std::env::var(&config.token_env)
+                .with_context(|| format!("reading the FROST bearer token from ${}", config.token_env))?,
+        })
+    }
+
+    /// Spend authorization signature over `sighash` for an Orchard action
+    /// whose spend was randomized by `alpha`
+    ///
+    /// Asks every participant to commit, signs with the first `min_signers`
+    /// that answer, and checks the aggregate against the randomized group key.
+    pub async fn sign(&self, sighash: &[u8], alpha: &[u8; 32]) -> Result<[u8; 64]> {
+        let mut session = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut session);
+        let session = hex::encode(session);
+
+        let mut signers = Vec::new();
+        let mut commitments = BTreeMap::new();
+        for url in &self.participants {
+            if commitments.len() == self.min_signers {
+                break;
+            }
+            let request = CommitRequest {
+                session: session.clone(),
+            };
+            let response: CommitResponse = match self.post(url, "commit", &request).await {
+                Ok(response) => response,
+                Err(error) => {
+                    warn!(participant = %url, error = %format!("{error:#}"), "FROST participant did not commit");
+                    continue;
+                }
+            };
+            let identifier = frost::Identifier::deserialize(&bytes32(&response.identifier, "identifier")?)
+                .with_context(|| format!("identifier from {url}"))?;
+            let commitment = frost::round1::SigningCommitments::deserialize(&hex::decode(&response.commitments)?)
+                .with_context(|| format!("commitments from {url}"))?;
+            commitments.insert(identifier, commitment);
+            signers.push((identifier, url));
+        }
+        if commitments.len() < self.min_signers {
+            bail!(
+                "only {} of the {} FROST participants needed committed",
+                commitments.len(),
+                self.min_signers
+            );
+        }
+
+        let signing_package = frost::SigningPackage::new(commitments, sighash);
+        let randomizer = frost::Randomizer::deserialize(alpha).context("alpha is not a Pallas scalar")?;
+        let request = SignRequest {
+            session,
+            signing_package: hex::encode(signing_package.serialize()?),
+            randomizer: hex::encode(alpha),
+        };
+        let mut shares = BTreeMap::new();
+        for (identifier, url) in signers {
+            let response: SignResponse = self
+                .post(url, "sign", &request)
+                .await
+                .with_context(|| format!("signature share from {url}"))?;
+            let share = frost::round2::SignatureShare::deserialize(bytes32(&response.share, "signature share")?)
+                .with_context(|| format!("signature share from {url}"))?;
+            shares.insert(identifier, share);
+        }
+
+        let params = frost::RandomizedParams::from_randomizer(self.public_key_package.verifying_key(), randomizer);
+        let signature = frost::aggregate(&signing_package, &shares, &self.public_key_package, &params)
+            .context("aggregating signature shares")?;
+        info!(signers = shares.len(), "FROST spend authorization signed");
+        Ok(signature.serialize())
+    }
+
+    async fn post<Request: Serialize, Response: DeserializeOwned>(
+        &self,
+        url: &str,
+        round: &str,
+        request: &Request,
+    ) -> Result<Response> {
+        let response = self
+            .http
+            .post(format!("{}/frost/{round}", url.trim_end_matches('/')))
+            .bearer_auth(&self.token)
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.json().await?)
+    }
+}
@@ -0,0 +1,63 @@
+//! FROST threshold signing of Orchard spend authorizations
+//!
+//! The reserve's Orchard spend authorizing key is split between operators
+//! with rerandomized FROST over RedPallas, so no single machine can move
+//! reserve funds. Each operator runs a participant holding one key share; a
+//! coordinator collects a threshold of signature shares for each spend of a
+//! transaction built elsewhere and aggregates them.
+
+mod coordinator;
+mod participant;
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use reddsa::frost::redpallas as frost;
+use serde::{Deserialize, Serialize};
+
+pub use self::coordinator::Coordinator;
+pub use self::participant::serve;
+
+/// Round one request: commit to fresh nonces for `session`
+#[derive(Serialize, Deserialize)]
+struct CommitRequest {
+    session: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CommitResponse {
+    identifier: String,  // Hex
+    commitments: String,  // Hex-encoded `SigningCommitments`
+}
+
+/// Round two request: sign `signing_package` with the nonces committed for
+/// `session`, rerandomized by `randomizer`
+#[derive(Serialize, Deserialize)]
+struct SignRequest {
+    session: String,
+    signing_package: String,  // Hex-encoded `SigningPackage`
+    randomizer: String,  // Hex-encoded 32-byte Orchard alpha
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignResponse {
+    share: String,  // Hex-encoded `SignatureShare`
+}
+
+/// Hex-encoded key material written by key generation
+fn read_hex(path: &Path) -> Result<Vec<u8>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    hex::decode(text.trim()).with_context(|| format!("{} is not hex", path.display()))
+}
+
+fn public_key_package(path: &Path) -> Result<frost::keys::PublicKeyPackage> {
+    frost::keys::PublicKeyPackage::deserialize(&read_hex(path)?)
+        .with_context(|| format!("decoding public key package {}", path.display()))
+}
+
+fn bytes32(encoded: &str, what: &str) -> Result<[u8; 32]> {
+    hex::decode(encoded)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .with_context(|| format!("{what} is not 32 hex-encoded bytes"))
+}
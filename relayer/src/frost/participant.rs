@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use reddsa::frost::redpallas as frost;
+use tracing::{info, warn};
+
+use super::{bytes32, read_hex, CommitRequest, CommitResponse, SignRequest, SignResponse};
+use crate::config::FrostConfig;
+
+/// How long committed nonces wait for round two before they are dropped
+const SESSION_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Clone)]
+struct Participant {
+    key_package: Arc<frost::keys::KeyPackage>,
+    token: Arc<String>,
+    sessions: Arc<Mutex<HashMap<String, (Instant, frost::round1::SigningNonces)>>>,
+}
+
+/// Serve this operator's key share to the coordinator until stopped
+///
+/// * `POST /frost/commit`: commit to fresh nonces for a session
+/// * `POST /frost/sign`: sign that session's package; the nonces are used
+///   once and forgotten
+pub async fn serve(config: &FrostConfig) -> Result<()> {
+    let key_path = config.key_package.as_ref().context("frost.key_package is required to run a participant")?;
+    let listen: SocketAddr = config.listen.context("frost.listen is required to run a participant")?;
+    let key_package = frost::keys::KeyPackage::deserialize(&read_hex(key_path)?)
+        .with_context(|| format!("decoding key package {}", key_path.display()))?;
+    let token = std::env::var(&config.token_env)
+        .with_context(|| format!("reading the FROST bearer token from ${}", config.token_env))?;
+    info!(%listen, identifier = %hex::encode(key_package.identifier().serialize()), "FROST participant listening");
+
+    let app = Router::new()
+        .route("/frost/commit", post(commit))
+        .route("/frost/sign", post(sign))
+        .with_state(Participant {
+            key_package: Arc::new(key_package),
+            token: Arc::new(token),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        });
+    axum::Server::try_bind(&listen)
+        .with_context(|| format!("binding FROST participant to {listen}"))?
+        .serve(app.into_make_service())
+        .await
+        .context("serving FROST participant")
+}
+
+fn authorize(participant: &Participant, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let expected = format!("Bearer {}", participant.token);
+    match headers.get("authorization").and_then(|value| value.to_str().ok()) {
+        Some(value) if value == expected => Ok(()),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+async fn commit(
+    State(participant): State<Participant>,
+    headers: HeaderMap,
+    Json(request): Json<CommitRequest>,
+) -> Result<Json<CommitResponse>, StatusCode> {
+    authorize(&participant, &headers)?;
+    let (nonces, commitments) =
+        frost::round1::commit(participant.key_package.signing_share(), &mut rand::thread_rng());
+    let commitments = commitments.serialize().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    {
+        let mut sessions = participant.sessions.lock().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        sessions.retain(|_, (started, _)| started.elapsed() < SESSION_TIMEOUT);
+        if sessions.contains_key(&request.session) {
+            return Err(StatusCode::CONFLICT);
+        }
+        sessions.insert(request.session.clone(), (Instant::now(), nonces));
+    }
+    info!(session = %request.session, "FROST nonces committed");
+    Ok(Json(CommitResponse {
+        identifier: hex::encode(participant.key_package.identifier().serialize()),
+        commitments: hex::encode(commitments),
+    }))
+}
+
+async fn sign(
+    State(participant): State<Participant>,
+    headers: HeaderMap,
+    Json(request): Json<SignRequest>,
+) -> Result<Json<SignResponse>, StatusCode> {
+    authorize(&participant, &headers)?;
+    // Nonces are taken out before anything else, so a failed round two can't be retried with them
+    let (_, nonces) = participant
+        .sessions
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .remove(&request.session)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let signing_package = hex::decode(&request.signing_package)
+        .ok()
+        .and_then(|bytes| frost::SigningPackage::deserialize(&bytes).ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let randomizer = bytes32(&request.randomizer, "randomizer")
+        .ok()
+        .and_then(|alpha| frost::Randomizer::deserialize(&alpha).ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let share = frost::round2::sign(&signing_package, &nonces, &participant.key_package, randomizer).map_err(|error| {
+        warn!(session = %request.session, %error, "FROST signing failed");
+        StatusCode::BAD_REQUEST
+    })?;
+    info!(
+        session = %request.session,
+        sighash = %hex::encode(signing_package.message()),
+        "FROST signature share issued"
+    );
+    Ok(Json(SignResponse {
+        share: hex::encode(share.serialize()),
+    }))
+}
@@ -0,0 +1,102 @@
+use anyhow::{anyhow, Context, Result};
+use orchard::keys::FullViewingKey;
+use sapling_crypto::zip32::DiversifiableFullViewingKey;
+use solana_sdk::hash::hashv;
+use solana_sdk::pubkey::Pubkey;
+use zcash_keys::encoding::{decode_extended_full_viewing_key, encode_payment_address};
+use zcash_keys::keys::{UnifiedAddressRequest, UnifiedFullViewingKey};
+use zcash_primitives::consensus::{self, Parameters};
+use zcash_primitives::zip32::DiversifierIndex;
+
+use crate::config::Network;
+
+/// The deposit address's full viewing key, decoded for the configured network
+pub struct ViewingKey {
+    network: consensus::Network,
+    unified: Option<UnifiedFullViewingKey>,
+    pub sapling: Option<DiversifiableFullViewingKey>,
+    pub orchard: Option<FullViewingKey>,
+}
+
+/// A deposit address issued to one Solana recipient
+#[derive(Debug, Clone)]
+pub struct DepositAddress {
+    pub address: String,
+    pub diversifier_index: [u8; 11],
+}
+
+impl ViewingKey {
+    /// Decode a unified full viewing key, or a Sapling extended full viewing
+    /// key for a Sapling-only deposit address
+    pub fn decode(network: Network, encoded: &str) -> Result<Self> {
+        let network = network.consensus();
+        let key = if encoded.starts_with(network.hrp_sapling_extended_full_viewing_key()) {
+            let efvk = decode_extended_full_viewing_key(network.hrp_sapling_extended_full_viewing_key(), encoded)
+                .map_err(|error| anyhow!("invalid Sapling viewing key: {error}"))?;
+            Self {
+                network,
+                unified: None,
+                sapling: Some(efvk.to_diversifiable_full_viewing_key()),
+                orchard: None,
+            }
+        } else {
+            let ufvk = UnifiedFullViewingKey::decode(&network, encoded)
+                .map_err(|error| anyhow!("invalid unified viewing key: {error}"))?;
+            Self {
+                network,
+                sapling: ufvk.sapling().cloned(),
+                orchard: ufvk.orchard().cloned(),
+                unified: Some(ufvk),
+            }
+        };
+        anyhow::ensure!(
+            key.sapling.is_some() || key.orchard.is_some(),
+            "viewing key has neither a Sapling nor an Orchard component"
+        );
+        Ok(key)
+    }
+
+    pub fn network(&self) -> consensus::Network {
+        self.network
+    }
+
+    /// The deposit address assigned to `recipient`
+    ///
+    /// ZIP 32 derives it at a diversifier index fixed by the recipient, so
+    /// every relayer holding the viewing key issues the same address. Funds
+    /// sent to it are credited to the recipient without a memo. Sapling leaves
+    /// about half of all indices without an address, so the index actually
+    /// used is the first valid one at or after the recipient's.
+    pub fn deposit_address(&self, recipient: &Pubkey) -> Result<DepositAddress> {
+        let start = recipient_diversifier_index(recipient);
+        let (address, index) = match &self.unified {
+            Some(ufvk) => {
+                let request = UnifiedAddressRequest::new(self.orchard.is_some(), self.sapling.is_some(), false)
+                    .context("viewing key has no shielded component")?;
+                let (address, index) = ufvk
+                    .find_address(start, request)
+                    .map_err(|error| anyhow!("deriving deposit address: {error:?}"))?;
+                (address.encode(&self.network), index)
+            }
+            None => {
+                let dfvk = self.sapling.as_ref().expect("decoded with a Sapling key");
+                let (index, address) = dfvk
+                    .find_address(start)
+                    .context("no Sapling address at or after the recipient's diversifier index")?;
+                (encode_payment_address(self.network.hrp_sapling_payment_address(), &address), index)
+            }
+        };
+        Ok(DepositAddress {
+            address,
+            diversifier_index: *index.as_bytes(),
+        })
+    }
+}
+
+/// Diversifier index a recipient's deposit address search starts from
+fn recipient_diversifier_index(recipient: &Pubkey) -> DiversifierIndex {
+    let digest = hashv(&[b"solz-deposit-address", recipient.as_ref()]).to_bytes();
+    let mut index = [0u8; 11];
+    index.copy_from_slice(&digest[..11]);
+    DiversifierIndex::from(index)
+}
@@ -0,0 +1,238 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Context, Result};
+use tonic::transport::{Channel, ClientTlsConfig};
+use tracing::debug;
+use zcash_client_backend::proto::compact_formats::{CompactBlock, CompactTx};
+use zcash_client_backend::proto::service::compact_tx_streamer_client::CompactTxStreamerClient;
+use zcash_client_backend::proto::service::{BlockId, BlockRange, ChainSpec, Exclude, TxFilter};
+
+use crate::config::LightwalletdConfig;
+use crate::deposit::{MempoolScan, Pool, Scan, Spend};
+use crate::scan::{Scanner, TreeSizes, TreeState};
+
+/// gRPC client for a lightwalletd server
+pub struct LightwalletdClient {
+    client: CompactTxStreamerClient<Channel>,
+}
+
+impl LightwalletdClient {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let mut endpoint = Channel::from_shared(url.to_string()).context("invalid lightwalletd url")?;
+        if url.starts_with("https://") {
+            endpoint = endpoint.tls_config(ClientTlsConfig::new())?;
+        }
+        let channel = endpoint
+            .connect()
+            .await
+            .with_context(|| format!("connecting to lightwalletd at {url}"))?;
+        Ok(Self {
+            client: CompactTxStreamerClient::new(channel),
+        })
+    }
+
+    pub async fn latest_height(&mut self) -> Result<u32> {
+        let block = self.client.get_latest_block(ChainSpec {}).await?.into_inner();
+        Ok(block.height as u32)
+    }
+
+    /// Compact blocks `start..=end`, in order
+    pub async fn blocks(&mut self, start: u32, end: u32) -> Result<Vec<CompactBlock>> {
+        let range = BlockRange {
+            start: Some(BlockId {
+                height: start.into(),
+                hash: Vec::new(),
+            }),
+            end: Some(BlockId {
+                height: end.into(),
+                hash: Vec::new(),
+            }),
+        };
+        let mut stream = self.client.get_block_range(range).await?.into_inner();
+        let mut blocks = Vec::new();
+        while let Some(block) = stream.message().await? {
+            blocks.push(block);
+        }
+        Ok(blocks)
+    }
+
+    /// Display-order hash of the block at `height`
+    pub async fn block_hash(&mut self, height: u32) -> Result<String> {
+        let block = self
+            .client
+            .get_block(BlockId {
+                height: height.into(),
+                hash: Vec::new(),
+            })
+            .await?
+            .into_inner();
+        Ok(display_hash(&block.hash))
+    }
+
+    /// Note commitment tree frontiers after the block at `height`
+    pub async fn tree_state(&mut self, height: u32) -> Result<TreeState> {
+        let state = self
+            .client
+            .get_tree_state(BlockId {
+                height: height.into(),
+                hash: Vec::new(),
+            })
+            .await?
+            .into_inner();
+        Ok(TreeState {
+            sapling: state.sapling_tree,
+            orchard: state.orchard_tree,
+        })
+    }
+
+    /// Raw transaction `txid`, given in internal byte order
+    pub async fn transaction(&mut self, txid: &[u8]) -> Result<Vec<u8>> {
+        let filter = TxFilter {
+            block: None,
+            index: 0,
+            hash: txid.to_vec(),
+        };
+        Ok(self.client.get_transaction(filter).await?.into_inner().data)
+    }
+
+    /// Compact transactions in the mempool
+    pub async fn mempool(&mut self) -> Result<Vec<CompactTx>> {
+        let mut stream = self.client.get_mempool_tx(Exclude::default()).await?.into_inner();
+        let mut transactions = Vec::new();
+        while let Some(tx) = stream.message().await? {
+            transactions.push(tx);
+        }
+        Ok(transactions)
+    }
+}
+
+/// Finds deposits by trial-decrypting CompactBlocks from lightwalletd
+///
+/// Blocks are scanned from the birthday height up to the deepest block with the
+/// required confirmations. Transactions the compact scan matches are fetched in
+/// full to recover their memos; spent nullifiers come from the compact blocks.
+pub struct LightwalletdSource {
+    client: LightwalletdClient,
+    scanner: Scanner,
+    next_height: u32,
+}
+
+impl LightwalletdSource {
+    /// Scan from `start_height`: the birthday on first run, or just past the
+    /// last checkpoint
+    pub async fn new(config: &LightwalletdConfig, scanner: Scanner, start_height: u32) -> Result<Self> {
+        Ok(Self {
+            client: LightwalletdClient::connect(&config.url).await?,
+            scanner,
+            next_height: start_height,
+        })
+    }
+
+    pub async fn tip(&mut self) -> Result<u32> {
+        self.client.latest_height().await
+    }
+
+    pub async fn block_hash(&mut self, height: u32) -> Result<String> {
+        self.client.block_hash(height).await
+    }
+
+    pub async fn tree_state(&mut self, height: u32) -> Result<TreeState> {
+        self.client.tree_state(height).await
+    }
+
+    /// Scan again from `height`, after a reorg
+    pub fn rewind(&mut self, height: u32) {
+        self.next_height = self.next_height.min(height);
+    }
+
+    /// Scan next from `height`, wherever scanning had got to
+    pub fn seek(&mut self, height: u32) {
+        self.next_height = height;
+    }
+
+    /// Every transaction in the mempool, fetching in full those not in
+    /// `known` that the compact scan matches, decrypted as if mined at `height`
+    pub async fn mempool(&mut self, known: &HashSet<String>, height: u32) -> Result<MempoolScan> {
+        let mut scan = MempoolScan::default();
+        for tx in self.client.mempool().await? {
+            let txid = display_hash(&tx.hash);
+            if !known.contains(&txid) && self.scanner.compact_match(&tx, height) {
+                // Mined or evicted since the mempool was listed; the scan will tell
+                let Ok(raw) = self.client.transaction(&tx.hash).await else {
+                    continue;
+                };
+                scan.transactions.push((txid.clone(), self.scanner.scan_transaction(&raw, height)?));
+            }
+            scan.txids.insert(txid);
+        }
+        Ok(scan)
+    }
+
+    /// Deposits and spends in blocks that reached `confirmations` since the last call
+    pub async fn confirmed_notes(&mut self, confirmations: u32) -> Result<Scan> {
+        let tip = self.client.latest_height().await?;
+        let mut scan = Scan::default();
+        let Some(end) = (tip + 1).checked_sub(confirmations) else {
+            return Ok(scan);
+        };
+        if end < self.next_height {
+            return Ok(scan);
+        }
+
+        debug!(start = self.next_height, end, "scanning compact blocks");
+        for block in self.client.blocks(self.next_height, end).await? {
+            let height = block.height as u32;
+            scan.blocks.push((height, display_hash(&block.hash)));
+            let tree_sizes = block
+                .chain_metadata
+                .as_ref()
+                .map(|metadata| TreeSizes {
+                    sapling: metadata.sapling_commitment_tree_size.into(),
+                    orchard: metadata.orchard_commitment_tree_size.into(),
+                })
+                .ok_or_else(|| anyhow!("compact block {height} has no chain metadata"))?;
+            let added = TreeSizes {
+                sapling: block.vtx.iter().map(|tx| tx.outputs.len() as u64).sum(),
+                orchard: block.vtx.iter().map(|tx| tx.actions.len() as u64).sum(),
+            };
+            let mut next = tree_sizes
+                .before(&added)
+                .with_context(|| format!("block {height} has more note commitments than its trees"))?;
+
+            for tx in &block.vtx {
+                let txid = display_hash(&tx.hash);
+                let nullifiers = tx
+                    .spends
+                    .iter()
+                    .map(|spend| (Pool::Sapling, &spend.nf))
+                    .chain(tx.actions.iter().map(|action| (Pool::Orchard, &action.nullifier)));
+                for (pool, nullifier) in nullifiers {
+                    scan.spends.push(Spend {
+                        pool,
+                        nullifier: nullifier.as_slice().try_into().context("malformed compact nullifier")?,
+                        txid: txid.clone(),
+                        height,
+                    });
+                }
+
+                if self.scanner.compact_match(tx, height) {
+                    let raw = self.client.transaction(&tx.hash).await?;
+                    for output in self.scanner.scan_transaction(&raw, height)?.outputs {
+                        let position = next.get(output.pool()) + output.index as u64;
+                        let note = self.scanner.received_note(&txid, output, height, tip + 1 - height, position);
+                        scan.notes.push(note);
+                    }
+                }
+                next.sapling += tx.outputs.len() as u64;
+                next.orchard += tx.actions.len() as u64;
+            }
+        }
+        self.next_height = end + 1;
+        Ok(scan)
+    }
+}
+
+/// Display-order hex of a block or transaction hash lightwalletd gives in internal order
+fn display_hash(hash: &[u8]) -> String {
+    hex::encode(hash.iter().rev().copied().collect::<Vec<u8>>())
+}
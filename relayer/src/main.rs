@@ -0,0 +1,300 @@
+mod admin;
+mod alerts;
+mod attestation;
+mod audit;
+mod chaos;
+mod config;
+mod deposit;
+mod events;
+mod feed;
+mod frost;
+mod keys;
+mod lightwalletd;
+mod memo;
+mod metrics;
+mod minter;
+mod node;
+mod payout;
+mod quarantine;
+mod relayer;
+mod retry;
+mod rpc;
+mod scan;
+mod signer;
+mod status;
+mod store;
+mod telemetry;
+mod wallet;
+mod zip317;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::audit::{AuditEntry, AuditLog};
+use crate::admin::AdminClient;
+use crate::config::Config;
+use crate::frost::Coordinator;
+use crate::keys::ViewingKey;
+use crate::quarantine::Refunds;
+use crate::relayer::Relayer;
+use crate::store::Store;
+
+/// Watch for deposits to the bridge address and mint wZEC on Solana
+#[derive(Parser)]
+#[command(name = "solz-relayer", version)]
+struct Args {
+    /// Path to the relayer's TOML configuration
+    #[arg(short, long, default_value = "relayer.toml")]
+    config: PathBuf,
+
+    /// Simulate Solana transactions and log the wallet spends instead of
+    /// making them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Watch for deposits and mint wZEC (the default)
+    Run,
+    /// Issue the deposit address that credits a Solana recipient without a memo
+    DepositAddress {
+        /// Solana wallet deposits to the address are minted to
+        recipient: Pubkey,
+    },
+    /// Stop crediting a recipient's deposit address, so later deposits to it
+    /// are quarantined instead of minted
+    RetireDepositAddress {
+        /// Solana wallet the address credits
+        recipient: Pubkey,
+    },
+    /// Scan again from a height, minting any deposit that was missed, then exit
+    Rescan {
+        /// First block to scan again
+        #[arg(long)]
+        from_height: u32,
+    },
+    /// Serve this operator's FROST key share to the coordinator
+    FrostParticipant,
+    /// Sign the Orchard spends of a reserve transaction with FROST, printing
+    /// one spend authorization signature per alpha
+    FrostSign {
+        /// Hex-encoded transaction sighash
+        #[arg(long)]
+        sighash: String,
+        /// Hex-encoded spend randomizer of each Orchard action, in order
+        #[arg(long = "alpha", required = true)]
+        alphas: Vec<String>,
+    },
+    /// Approve a payout waiting for operators' approval
+    ///
+    /// Goes through the running relayer's admin API, which approves as the
+    /// operator whose token is given.
+    ApprovePayout {
+        /// Nonce of the withdrawal the payout settles
+        nonce: u64,
+        /// Environment variable holding your own admin API token
+        #[arg(long, default_value = "SOLZ_ADMIN_TOKEN")]
+        token_env: String,
+    },
+    /// Reject a payout waiting for operators' approval, so it is never sent
+    ///
+    /// Goes through the running relayer's admin API, like approving.
+    RejectPayout {
+        /// Nonce of the withdrawal the payout settles
+        nonce: u64,
+        /// Why the payout is rejected
+        #[arg(long)]
+        reason: String,
+        /// Environment variable holding your own admin API token
+        #[arg(long, default_value = "SOLZ_ADMIN_TOKEN")]
+        token_env: String,
+    },
+    /// List quarantined deposits waiting for an operator
+    Quarantined,
+    /// Mint a quarantined deposit to the recipient its sender names
+    ///
+    /// Each operator attributes the deposit on their own relayer, as peers
+    /// only attest deposits in their own database.
+    AttributeDeposit {
+        /// Zcash txid of the deposit
+        txid: String,
+        /// Solana wallet to mint to
+        recipient: Pubkey,
+        /// Attributing operator
+        #[arg(long)]
+        operator: String,
+    },
+    /// Send a quarantined deposit back to its sender, less the fee
+    RefundDeposit {
+        /// Zcash txid of the deposit
+        txid: String,
+        /// Zcash address the sender gave for the refund
+        address: String,
+        /// Refunding operator
+        #[arg(long)]
+        operator: String,
+    },
+    /// Write the audit log as JSON lines, one entry per line
+    AuditExport {
+        /// File to write instead of standard output
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Check an exported audit log's hash chain and signatures
+    ///
+    /// Needs no config or database, so an auditor can run it on the export
+    /// alone.
+    AuditVerify {
+        /// Exported audit log
+        path: PathBuf,
+        /// Operator key every entry must be signed by
+        #[arg(long)]
+        operator: Option<Pubkey>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    if let Some(Command::AuditVerify { path, operator }) = &args.command {
+        return verify_audit_log(path, operator.as_ref());
+    }
+    let mut config = Config::load(&args.config)?;
+    config.dry_run |= args.dry_run;
+    telemetry::init(config.telemetry.as_ref())?;
+    let result = run(args.command.unwrap_or(Command::Run), config).await;
+    telemetry::shutdown();
+    result
+}
+
+async fn run(command: Command, config: Config) -> Result<()> {
+    match command {
+        Command::Run => Relayer::new(config).await?.run().await,
+        Command::DepositAddress { recipient } => {
+            let key = ViewingKey::decode(config.zcash.network, &config.zcash.viewing_key)?;
+            let address = key.deposit_address(&recipient)?;
+            Store::open(&config.database)
+                .await?
+                .insert_deposit_address(&recipient, &address)
+                .await?;
+            println!("{}", address.address);
+            Ok(())
+        }
+        Command::RetireDepositAddress { recipient } => {
+            let retired = Store::open(&config.database)
+                .await?
+                .retire_deposit_address(&recipient)
+                .await?;
+            anyhow::ensure!(retired, "{recipient} has no deposit address in use");
+            println!("deposit address of {recipient}: retired");
+            Ok(())
+        }
+        Command::Rescan { from_height } => Relayer::new(config).await?.rescan(from_height).await,
+        Command::FrostParticipant => frost::serve(config.frost.as_ref().context("missing [frost] section")?).await,
+        Command::FrostSign { sighash, alphas } => {
+            let coordinator = Coordinator::new(config.frost.as_ref().context("missing [frost] section")?)?;
+            let sighash = hex::decode(&sighash).context("sighash is not hex")?;
+            for alpha in alphas {
+                let alpha: [u8; 32] = hex::decode(&alpha)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .with_context(|| format!("alpha {alpha} is not 32 hex-encoded bytes"))?;
+                println!("{}", hex::encode(coordinator.sign(&sighash, &alpha).await?));
+            }
+            Ok(())
+        }
+        Command::ApprovePayout { nonce, token_env } => {
+            let approval = admin_client(&config, &token_env)?.approve(nonce).await?;
+            if approval.released {
+                println!("{}: approved by {} of {}, released", approval.id, approval.approvals, approval.required);
+            } else {
+                println!("{}: approved by {} of {}", approval.id, approval.approvals, approval.required);
+            }
+            Ok(())
+        }
+        Command::RejectPayout { nonce, reason, token_env } => {
+            admin_client(&config, &token_env)?.reject(nonce, &reason).await?;
+            println!("payout:{nonce}: rejected");
+            Ok(())
+        }
+        Command::Quarantined => {
+            for quarantined in Store::open(&config.database).await?.quarantined_deposits().await? {
+                println!("{}", serde_json::to_string(&quarantined)?);
+            }
+            Ok(())
+        }
+        Command::AttributeDeposit { txid, recipient, operator } => {
+            let store = Store::open(&config.database).await?;
+            let audit = audit_log(&config, &store).await?;
+            let deposit = quarantine::attribute(&store, audit.as_ref(), &txid, recipient, &operator).await?;
+            println!("{txid}: attributed to {}, minting {} zatoshis", deposit.recipient, deposit.amount);
+            Ok(())
+        }
+        Command::RefundDeposit { txid, address, operator } => {
+            let refunds = Refunds::new(&config).context("missing [payouts] section")?;
+            let store = Store::open(&config.database).await?;
+            let audit = audit_log(&config, &store).await?;
+            let refund = refunds.refund(&store, audit.as_ref(), &txid, &address, &operator).await?;
+            match refund.refund_txid {
+                Some(refund_txid) => println!("{txid}: refunded {} zatoshis in {refund_txid}", refund.amount),
+                None => println!("{txid}: refunding {} zatoshis, wallet operation {}", refund.amount, refund.operation_id),
+            }
+            Ok(())
+        }
+        Command::AuditExport { output } => {
+            let entries = Store::open(&config.database).await?.audit_entries().await?;
+            let mut lines = String::new();
+            for entry in &entries {
+                lines.push_str(&serde_json::to_string(entry)?);
+                lines.push('\n');
+            }
+            match output {
+                Some(path) => std::fs::write(&path, lines).with_context(|| format!("writing {}", path.display()))?,
+                None => print!("{lines}"),
+            }
+            Ok(())
+        }
+        Command::AuditVerify { path, operator } => verify_audit_log(&path, operator.as_ref()),
+    }
+}
+
+/// The audit log, when `[audit]` is configured
+async fn audit_log(config: &Config, store: &Store) -> Result<Option<AuditLog>> {
+    match &config.audit {
+        Some(audit) => Ok(Some(AuditLog::open(audit, store.clone()).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Check the audit log exported to `path`, printing who signed it
+fn verify_audit_log(path: &Path, operator: Option<&Pubkey>) -> Result<()> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let entries = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str::<AuditEntry>(line).with_context(|| format!("parsing line {}", index + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let signers = audit::verify(&entries, operator)?;
+    println!("{} entries verified", entries.len());
+    for signer in signers {
+        println!("signed by {signer}");
+    }
+    Ok(())
+}
+
+/// Client of the relayer's admin API, with the calling operator's token from
+/// the environment variable `token_env`
+fn admin_client(config: &Config, token_env: &str) -> Result<AdminClient> {
+    let approvals = config.approvals.as_ref().context("missing [approvals] section")?;
+    AdminClient::new(approvals.listen, token_env)
+}
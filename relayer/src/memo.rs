@@ -0,0 +1,137 @@
+use std::fmt;
+
+use solana_sdk::hash::hashv;
+use solana_sdk::pubkey::Pubkey;
+
+/// Version byte of the current deposit memo format
+pub const MEMO_VERSION: u8 = 1;
+const CHECKSUM_LEN: usize = 4;
+const ENCODED_LEN: usize = 1 + 32 + CHECKSUM_LEN;
+
+/// Why a deposit memo names no recipient
+#[derive(Debug)]
+pub enum MemoError {
+    Empty,
+    NotText,
+    NotBase58,
+    UnknownVersion(u8),
+    WrongLength(usize),
+    BadChecksum,
+}
+
+impl fmt::Display for MemoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoError::Empty => write!(f, "memo is empty"),
+            MemoError::NotText => write!(f, "memo is not text"),
+            MemoError::NotBase58 => write!(f, "memo is not base58"),
+            MemoError::UnknownVersion(version) => write!(f, "unknown memo version {version}"),
+            MemoError::WrongLength(len) => write!(f, "memo is {len} bytes, expected {ENCODED_LEN}"),
+            MemoError::BadChecksum => write!(f, "memo checksum mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for MemoError {}
+
+/// Solana recipient named by a deposit memo
+///
+/// The memo text is the base58 encoding of `version || recipient || checksum`,
+/// the checksum being the first four bytes of SHA-256 over the version and
+/// recipient. A bare base58 public key is accepted as the legacy format. The
+/// bridge service's `deposit-memo.js` implements the same format.
+pub fn recipient(memo: &[u8; 512]) -> Result<Pubkey, MemoError> {
+    // ZIP 302: 0xF6 followed by zeros is "no memo"; any other first byte
+    // above 0xF4 marks a memo that isn't UTF-8 text
+    if memo[0] == 0xF6 && memo[1..].iter().all(|&byte| byte == 0) {
+        return Err(MemoError::Empty);
+    }
+    if memo[0] > 0xF4 {
+        return Err(MemoError::NotText);
+    }
+
+    let end = memo.iter().rposition(|&byte| byte != 0).map_or(0, |i| i + 1);
+    let text = std::str::from_utf8(&memo[..end]).map_err(|_| MemoError::NotText)?.trim();
+    if text.is_empty() {
+        return Err(MemoError::Empty);
+    }
+    let bytes = bs58::decode(text).into_vec().map_err(|_| MemoError::NotBase58)?;
+
+    if bytes.len() == 32 {
+        return Ok(Pubkey::try_from(bytes.as_slice()).expect("32 bytes"));
+    }
+    if bytes[0] != MEMO_VERSION {
+        return Err(MemoError::UnknownVersion(bytes[0]));
+    }
+    if bytes.len() != ENCODED_LEN {
+        return Err(MemoError::WrongLength(bytes.len()));
+    }
+    let (payload, checksum) = bytes.split_at(ENCODED_LEN - CHECKSUM_LEN);
+    if hashv(&[payload]).to_bytes()[..CHECKSUM_LEN] != *checksum {
+        return Err(MemoError::BadChecksum);
+    }
+    Ok(Pubkey::try_from(&payload[1..]).expect("32 bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memo_text(text: &str) -> [u8; 512] {
+        let mut memo = [0; 512];
+        memo[..text.len()].copy_from_slice(text.as_bytes());
+        memo
+    }
+
+    /// `version || recipient || checksum` in base58, as `deposit-memo.js` writes it
+    fn encode(version: u8, recipient: &Pubkey) -> String {
+        let mut bytes = vec![version];
+        bytes.extend_from_slice(recipient.as_ref());
+        let checksum = hashv(&[bytes.as_slice()]).to_bytes();
+        bytes.extend_from_slice(&checksum[..CHECKSUM_LEN]);
+        bs58::encode(bytes).into_string()
+    }
+
+    #[test]
+    fn versioned_memo_round_trips() {
+        let wallet = Pubkey::new_unique();
+        let memo = memo_text(&encode(MEMO_VERSION, &wallet));
+        assert_eq!(recipient(&memo).unwrap(), wallet);
+    }
+
+    #[test]
+    fn bare_public_key_is_accepted() {
+        let wallet = Pubkey::new_unique();
+        let memo = memo_text(&format!(" {wallet}\n"));
+        assert_eq!(recipient(&memo).unwrap(), wallet);
+    }
+
+    #[test]
+    fn corrupted_checksum_is_refused() {
+        let mut text = encode(MEMO_VERSION, &Pubkey::new_unique()).into_bytes();
+        let last = text.last_mut().unwrap();
+        *last = if *last == b'2' { b'3' } else { b'2' };
+        let memo = memo_text(std::str::from_utf8(&text).unwrap());
+        assert!(matches!(recipient(&memo), Err(MemoError::BadChecksum)));
+    }
+
+    #[test]
+    fn unknown_version_and_wrong_length_are_refused() {
+        let memo = memo_text(&encode(2, &Pubkey::new_unique()));
+        assert!(matches!(recipient(&memo), Err(MemoError::UnknownVersion(2))));
+        let memo = memo_text(&bs58::encode([MEMO_VERSION; 20]).into_string());
+        assert!(matches!(recipient(&memo), Err(MemoError::WrongLength(20))));
+    }
+
+    #[test]
+    fn memos_naming_no_one_are_told_apart() {
+        let mut no_memo = [0; 512];
+        no_memo[0] = 0xF6;
+        assert!(matches!(recipient(&no_memo), Err(MemoError::Empty)));
+        assert!(matches!(recipient(&memo_text("  ")), Err(MemoError::Empty)));
+        let mut binary = [0; 512];
+        binary[0] = 0xFF;
+        assert!(matches!(recipient(&binary), Err(MemoError::NotText)));
+        assert!(matches!(recipient(&memo_text("not base58: 0OIl")), Err(MemoError::NotBase58)));
+    }
+}
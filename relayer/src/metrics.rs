@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use prometheus::{Encoder, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+
+/// Prometheus metrics, served in the text format on the status API's
+/// `GET /metrics`
+///
+/// Counters are bumped where the relayer acts; gauges are set after every
+/// poll from what it saw.
+pub struct Metrics {
+    registry: Registry,
+    pub deposits_seen: IntCounter,
+    pub deposits_minted: IntCounter,
+    pub deposits_quarantined: IntCounter,
+    pub payouts_sent: IntCounter,
+    pub withdrawals_finalized: IntCounter,
+    pub deep_reorgs: IntCounter,
+    pub errors: IntCounterVec,  // Labelled by `operation`: poll, mint, payout, refund, consolidate or sweep
+    pub zcash_tip_height: IntGauge,
+    pub zcash_scanned_height: IntGauge,
+    pub solana_slot: IntGauge,
+    pub hot_balance: IntGauge,
+    pub pending_deposits: IntGauge,
+    pub pending_withdrawals: IntGauge,
+    pub open_payouts: IntGauge,
+    pub leader: IntGauge,
+    pub halted: IntGauge,
+}
+
+pub type SharedMetrics = Arc<Metrics>;
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new_custom(Some("solz_relayer".to_string()), None)?;
+        let counter = |name: &str, help: &str| -> Result<IntCounter> {
+            let counter = IntCounter::new(name, help)?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok(counter)
+        };
+        let gauge = |name: &str, help: &str| -> Result<IntGauge> {
+            let gauge = IntGauge::new(name, help)?;
+            registry.register(Box::new(gauge.clone()))?;
+            Ok(gauge)
+        };
+        let errors = IntCounterVec::new(Opts::new("errors_total", "Failed operations"), &["operation"])?;
+        registry.register(Box::new(errors.clone()))?;
+
+        Ok(Self {
+            deposits_seen: counter("deposits_seen_total", "Deposits attributed to a recipient")?,
+            deposits_minted: counter("deposits_minted_total", "Deposits this relayer minted")?,
+            deposits_quarantined: counter("deposits_quarantined_total", "Deposits quarantined instead of minted")?,
+            payouts_sent: counter("payouts_sent_total", "Withdrawal payouts broadcast on Zcash")?,
+            withdrawals_finalized: counter("withdrawals_finalized_total", "Withdrawals finalized on Solana")?,
+            deep_reorgs: counter("deep_reorgs_total", "Reorgs deeper than the confirmation depth")?,
+            errors,
+            zcash_tip_height: gauge("zcash_tip_height", "Zcash chain tip")?,
+            zcash_scanned_height: gauge("zcash_scanned_height", "Highest Zcash block scanned")?,
+            solana_slot: gauge("solana_slot", "Solana slot at the last poll")?,
+            hot_balance: gauge("hot_balance_zatoshis", "Unspent zatoshis at the deposit address")?,
+            pending_deposits: gauge("pending_deposits", "Deposits waiting to be minted")?,
+            pending_withdrawals: gauge("pending_withdrawals", "Withdrawals waiting to be paid out")?,
+            open_payouts: gauge("open_payouts", "Payouts not yet finalized")?,
+            leader: gauge("leader", "1 while this instance acts on the database")?,
+            halted: gauge("halted", "1 after a deep reorg stopped minting")?,
+            registry,
+        })
+    }
+
+    /// Every metric in the Prometheus text exposition format
+    pub fn encode(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
@@ -0,0 +1,489 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anyhow::{anyhow, bail, Context, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::nonce_utils::nonblocking as nonce_utils;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use tracing::{debug, info, instrument};
+use wzec_bridge::{
+    interface, BridgeState, DepositReceipt, GuardianSet, ReserveAttestation, WithdrawalRequest, WithdrawalStatus,
+};
+
+use crate::chaos::{self, Fault};
+use crate::config::{FeeConfig, SolanaConfig};
+use crate::deposit::{txid_bytes, Deposit};
+use crate::payout::Withdrawal;
+use crate::signer::{self, BridgeSigner};
+
+/// How often a sent transaction's status is checked, and the transaction sent
+/// again, while waiting for it to confirm
+const CONFIRM_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What the signer is asked to sign to check that it answers; not a
+/// transaction message, so the signature can't authorize anything
+const SIGNER_PROBE: &[u8] = b"solz-relayer signer probe";
+
+/// Submits `mint_wzec` for confirmed deposits and `finalize_withdrawal` for
+/// paid-out burns
+///
+/// The relayer key signs as the minter and attests as a guardian; bridges whose
+/// guardian threshold is above one need the other guardians' signatures as well.
+/// Finalizing takes the bridge authority or a bonded relayer.
+pub struct Minter {
+    rpc: RpcClient,
+    signer: Arc<dyn BridgeSigner>,  // Holds the relayer key
+    relayer: Pubkey,
+    mint: Pubkey,
+    bridge_state: Pubkey,
+    fees: FeeConfig,
+    nonce_account: Option<Pubkey>,  // Durable nonce the transactions are built on instead of a recent blockhash
+    dry_run: bool,  // Simulate transactions instead of sending them
+}
+
+/// A signed transaction, ready to send
+pub struct Submission {
+    pub transaction: Transaction,
+    lifetime: Lifetime,
+}
+
+/// How long a transaction can still land
+enum Lifetime {
+    /// Until the chain passes this block height, ~150 blocks past its blockhash
+    BlockHeight(u64),
+    /// Until this durable nonce account moves off the transaction's nonce
+    Nonce(Pubkey),
+}
+
+impl Minter {
+    pub async fn new(config: &SolanaConfig, mint: Pubkey, dry_run: bool) -> Result<Self> {
+        let signer: Arc<dyn BridgeSigner> = signer::open(&config.signer()?).await?.into();
+        let relayer = signer::solana_pubkey(signer.as_ref())?;
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed()),
+            signer,
+            relayer,
+            mint,
+            bridge_state: interface::bridge_state_address(&mint),
+            fees: config.fees.clone(),
+            nonce_account: config.nonce_account()?,
+            dry_run,
+        })
+    }
+
+    pub fn relayer(&self) -> Pubkey {
+        self.relayer
+    }
+
+    /// Backend holding the relayer key, shared with the attestation server
+    pub fn signer(&self) -> Arc<dyn BridgeSigner> {
+        self.signer.clone()
+    }
+
+    pub fn mint(&self) -> Pubkey {
+        self.mint
+    }
+
+    /// Token program that owns the wZEC mint
+    pub async fn token_program(&self) -> Result<Pubkey> {
+        Ok(self
+            .rpc
+            .get_account(&self.mint)
+            .await
+            .with_context(|| format!("fetching mint {}", self.mint))?
+            .owner)
+    }
+
+    /// Message guardians sign to attest `deposit`, naming its recipient's
+    /// associated token account
+    pub async fn attestation_message(&self, deposit: &Deposit) -> Result<Vec<u8>> {
+        let state = self.bridge_state().await?;
+        let recipient_token_account =
+            get_associated_token_address_with_program_id(&deposit.recipient, &self.mint, &self.token_program().await?);
+        Ok(DepositReceipt::attestation_message(
+            state.network,
+            &recipient_token_account,
+            deposit.amount,
+            &deposit.zcash_txid,
+        ))
+    }
+
+    pub async fn guardian_set(&self) -> Result<GuardianSet> {
+        let address = interface::guardian_set_address(&self.bridge_state);
+        let data = self
+            .rpc
+            .get_account_data(&address)
+            .await
+            .with_context(|| format!("fetching guardian set {address}"))?;
+        GuardianSet::try_deserialize(&mut data.as_slice()).context("decoding guardian set")
+    }
+
+    /// Whether the signer backend answers, by having it sign a probe
+    pub async fn check_signer(&self) -> Result<()> {
+        self.signer.sign(SIGNER_PROBE).await.map(|_| ())
+    }
+
+    /// The guardians' most recent reserve snapshot, if any was posted
+    pub async fn latest_reserve_attestation(&self) -> Result<Option<ReserveAttestation>> {
+        let state = self.bridge_state().await?;
+        let Some(sequence) = state.reserve_attestation_count.checked_sub(1) else {
+            return Ok(None);
+        };
+        let address = interface::reserve_attestation_address(&self.bridge_state, sequence);
+        let data = self
+            .rpc
+            .get_account_data(&address)
+            .await
+            .with_context(|| format!("fetching reserve attestation {address}"))?;
+        Ok(Some(
+            ReserveAttestation::try_deserialize(&mut data.as_slice()).context("decoding reserve attestation")?,
+        ))
+    }
+
+    pub async fn bridge_state(&self) -> Result<BridgeState> {
+        let data = self
+            .rpc
+            .get_account_data(&self.bridge_state)
+            .await
+            .with_context(|| format!("fetching bridge state {}", self.bridge_state))?;
+        BridgeState::try_deserialize(&mut data.as_slice()).context("decoding bridge state")
+    }
+
+    /// Whether a receipt already records `deposit` as minted
+    pub async fn is_minted(&self, deposit: &Deposit) -> Result<bool> {
+        let receipt = interface::deposit_receipt_address(&self.bridge_state, &deposit.zcash_txid);
+        Ok(self
+            .rpc
+            .get_account_with_commitment(&receipt, self.rpc.commitment())
+            .await?
+            .value
+            .is_some())
+    }
+
+    /// Pause mints with the relayer's guardian key
+    pub async fn guardian_pause(&self, attempt: u32) -> Result<Signature> {
+        let submission = self
+            .prepare(&[interface::guardian_pause_instruction(&self.mint, &self.relayer)], attempt)
+            .await?;
+        self.send(&submission).await.context("pausing mints")
+    }
+
+    /// Pause burns, leaving mints as they are, with the relayer's key as the
+    /// bridge authority
+    ///
+    /// Returns `None` when burns are already paused.
+    pub async fn pause_burns(&self, attempt: u32) -> Result<Option<Signature>> {
+        let state = self.bridge_state().await?;
+        if state.burns_paused {
+            return Ok(None);
+        }
+        let relayer = self.relayer;
+        anyhow::ensure!(
+            relayer == state.authority,
+            "only the bridge authority can pause burns, and the relayer key {relayer} is not it"
+        );
+        let submission = self
+            .prepare(
+                &[interface::set_pause_flags_instruction(&self.mint, &relayer, state.mints_paused, true)],
+                attempt,
+            )
+            .await?;
+        self.send(&submission).await.context("pausing burns").map(Some)
+    }
+
+    /// Signed transaction minting `deposit` to its recipient's associated token
+    /// account, creating the account if needed
+    ///
+    /// `attestations` are other guardians' signatures over the deposit's
+    /// attestation message; the relayer key attests by signing the transaction.
+    pub async fn mint_transaction(
+        &self,
+        deposit: &Deposit,
+        attestations: &[(Pubkey, [u8; 64])],
+        attempt: u32,
+    ) -> Result<Submission> {
+        let state = self.bridge_state().await?;
+        let token_program = self.token_program().await?;
+        let relayer = self.relayer;
+        let recipient_token_account =
+            get_associated_token_address_with_program_id(&deposit.recipient, &self.mint, &token_program);
+
+        let create_token_account = create_associated_token_account_idempotent(
+            &relayer,
+            &deposit.recipient,
+            &self.mint,
+            &token_program,
+        );
+        let mint = interface::mint_wzec_instruction(
+            interface::mint_accounts(
+                &state,
+                &deposit.recipient,
+                &recipient_token_account,
+                &relayer,
+                &token_program,
+                &deposit.zcash_txid,
+            ),
+            vec![AccountMeta::new_readonly(relayer, true)],
+            deposit.amount,
+            deposit.zcash_txid,
+            Some(deposit.block_height),
+        );
+
+        let mut instructions = vec![create_token_account];
+        if !attestations.is_empty() {
+            let message =
+                DepositReceipt::attestation_message(state.network, &recipient_token_account, deposit.amount, &deposit.zcash_txid);
+            instructions.push(interface::guardian_attestation_instruction(attestations, &message));
+        }
+        instructions.push(mint);
+        self.prepare(&instructions, attempt).await
+    }
+
+    pub async fn slot(&self) -> Result<u64> {
+        Ok(self.rpc.get_slot().await?)
+    }
+
+    /// Every pending withdrawal of this bridge, oldest first
+    pub async fn pending_withdrawals(&self) -> Result<Vec<Withdrawal>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                &WithdrawalRequest::DISCRIMINATOR,
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(self.rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(&wzec_bridge::ID, config)
+            .await
+            .context("fetching withdrawal requests")?;
+
+        let mut withdrawals = Vec::new();
+        for (address, account) in accounts {
+            let Ok(request) = WithdrawalRequest::try_deserialize(&mut account.data.as_slice()) else {
+                continue;
+            };
+            // Requests of other bridges share the program; only this bridge's PDA matches
+            if request.status != WithdrawalStatus::Pending
+                || interface::withdrawal_request_address(&self.bridge_state, request.nonce) != address
+            {
+                continue;
+            }
+            withdrawals.push(Withdrawal::from_request(address, &request));
+        }
+        withdrawals.sort_by_key(|withdrawal| withdrawal.nonce);
+        Ok(withdrawals)
+    }
+
+    /// This bridge's withdrawal `nonce` and its status, or `None` once the
+    /// request account is closed
+    pub async fn withdrawal(&self, nonce: u64) -> Result<Option<(WithdrawalStatus, Withdrawal)>> {
+        let address = interface::withdrawal_request_address(&self.bridge_state, nonce);
+        let Some(account) = self
+            .rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())
+            .await
+            .with_context(|| format!("fetching withdrawal request {address}"))?
+            .value
+        else {
+            return Ok(None);
+        };
+        let request = WithdrawalRequest::try_deserialize(&mut account.data.as_slice())
+            .with_context(|| format!("decoding withdrawal request {address}"))?;
+        Ok(Some((request.status, Withdrawal::from_request(address, &request))))
+    }
+
+    /// Signed transaction finalizing withdrawal `nonce` with its Zcash payout,
+    /// and the payout's output to it when known
+    pub async fn finalize_transaction(
+        &self,
+        nonce: u64,
+        payout_txid: &str,
+        payout_height: u32,
+        payout_output: Option<u16>,
+        attempt: u32,
+    ) -> Result<Submission> {
+        let state = self.bridge_state().await?;
+        let relayer = self.relayer;
+        let finalize = interface::finalize_withdrawal_instruction(
+            &self.mint,
+            nonce,
+            &relayer,
+            relayer != state.authority,
+            txid_bytes(payout_txid)?,
+            payout_height,
+            payout_output,
+        );
+        self.prepare(&[finalize], attempt).await
+    }
+
+    /// Sign `instructions` for the `attempt`th try at a submission, counting
+    /// from 1, with its priority fee and on the durable nonce if one is set
+    async fn prepare(&self, instructions: &[Instruction], attempt: u32) -> Result<Submission> {
+        let mut all = Vec::new();
+        let (blockhash, lifetime) = match self.nonce_account {
+            Some(account) => {
+                // The runtime only honours a nonce advanced by the first instruction
+                all.push(system_instruction::advance_nonce_account(&account, &self.relayer));
+                (self.durable_nonce(&account).await?, Lifetime::Nonce(account))
+            }
+            None => {
+                let (blockhash, last_valid_block_height) =
+                    self.rpc.get_latest_blockhash_with_commitment(self.rpc.commitment()).await?;
+                (blockhash, Lifetime::BlockHeight(last_valid_block_height))
+            }
+        };
+        if self.fees.compute_unit_limit > 0 {
+            all.push(ComputeBudgetInstruction::set_compute_unit_limit(self.fees.compute_unit_limit));
+        }
+        let price = self.fees.priority_fee(attempt);
+        if price > 0 {
+            all.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        all.extend_from_slice(instructions);
+        let transaction = signer::sign_transaction(self.signer.as_ref(), &all, blockhash).await?;
+        Ok(Submission { transaction, lifetime })
+    }
+
+    async fn durable_nonce(&self, account: &Pubkey) -> Result<Hash> {
+        let account_data = nonce_utils::get_account_with_commitment(&self.rpc, account, self.rpc.commitment())
+            .await
+            .with_context(|| format!("fetching nonce account {account}"))?;
+        Ok(nonce_utils::data_from_account(&account_data)
+            .with_context(|| format!("decoding nonce account {account}"))?
+            .blockhash())
+    }
+
+    /// A transaction a previous run sent on the durable nonce, if it can
+    /// still land
+    pub async fn revive(&self, transaction: Transaction) -> Result<Option<Submission>> {
+        let Some(account) = self.nonce_account else {
+            return Ok(None);
+        };
+        if self.durable_nonce(&account).await? != transaction.message.recent_blockhash {
+            return Ok(None);
+        }
+        Ok(Some(Submission {
+            transaction,
+            lifetime: Lifetime::Nonce(account),
+        }))
+    }
+
+    /// Send `submission` and wait for it to confirm, sending it again every
+    /// few seconds in case it was dropped
+    ///
+    /// Fails once the transaction can no longer land, so that a retry
+    /// prepares it afresh with a newer blockhash and a higher priority fee.
+    #[instrument(skip_all, fields(signature = %submission.transaction.signatures[0]))]
+    pub async fn send(&self, submission: &Submission) -> Result<Signature> {
+        let signature = self.broadcast(submission).await?;
+        self.confirm(submission, signature).await
+    }
+
+    /// Send `submission` once, without waiting for it to confirm
+    pub async fn broadcast(&self, submission: &Submission) -> Result<Signature> {
+        let transaction = &submission.transaction;
+        if self.dry_run {
+            return self.simulate(transaction).await;
+        }
+        chaos::fail(Fault::RpcFailure, "sendTransaction")?;
+        let signature = self.rpc.send_transaction(transaction).await?;
+        chaos::fail(Fault::LostResponse, "sendTransaction")?;
+        Ok(signature)
+    }
+
+    /// Wait for a broadcast `submission` to confirm, sending it again every
+    /// few seconds in case it was dropped, and failing once it can no longer
+    /// land
+    pub async fn confirm(&self, submission: &Submission, signature: Signature) -> Result<Signature> {
+        if self.dry_run {
+            return Ok(signature);
+        }
+        let transaction = &submission.transaction;
+        let resend = RpcSendTransactionConfig {
+            skip_preflight: true,
+            ..RpcSendTransactionConfig::default()
+        };
+        loop {
+            tokio::time::sleep(CONFIRM_INTERVAL).await;
+            if chaos::strikes(Fault::DelayedConfirmation) {
+                continue;
+            }
+            // Checked before the status, so a transaction landing in between isn't taken for expired
+            let expired = !self.can_land(submission).await?;
+            if let Some(result) = self
+                .rpc
+                .get_signature_status_with_commitment(&signature, self.rpc.commitment())
+                .await?
+            {
+                return result
+                    .map(|()| signature)
+                    .map_err(|error| anyhow!("transaction {signature} failed: {error}"));
+            }
+            if expired {
+                bail!("transaction {signature} expired before it confirmed");
+            }
+            // Best effort; the status check decides the outcome
+            let _ = self.rpc.send_transaction_with_config(transaction, resend).await;
+        }
+    }
+
+    /// Simulate `transaction` in place of sending it, for a dry run,
+    /// returning its signature as if it had confirmed
+    async fn simulate(&self, transaction: &Transaction) -> Result<Signature> {
+        let signature = transaction.signatures[0];
+        let result = self.rpc.simulate_transaction(transaction).await?.value;
+        let logs = result.logs.unwrap_or_default();
+        if let Some(error) = result.err {
+            bail!("dry run: transaction {signature} would fail: {error}; logs: {}", logs.join(" | "));
+        }
+        info!(%signature, units = ?result.units_consumed, "dry run: simulated transaction instead of sending it");
+        debug!(%signature, ?logs, "simulation logs");
+        Ok(signature)
+    }
+
+    /// Whether `submission` can still land: its blockhash hasn't expired, or
+    /// its durable nonce hasn't moved on
+    pub async fn can_land(&self, submission: &Submission) -> Result<bool> {
+        Ok(match submission.lifetime {
+            Lifetime::BlockHeight(last_valid_block_height) => {
+                self.rpc.get_block_height().await? <= last_valid_block_height
+            }
+            Lifetime::Nonce(account) => {
+                self.durable_nonce(&account).await? == submission.transaction.message.recent_blockhash
+            }
+        })
+    }
+
+    /// Whether a submitted transaction succeeded, failed, or is unknown to the
+    /// cluster (never landed, or too old for the status cache)
+    pub async fn signature_status(&self, signature: &Signature) -> Result<Option<Result<(), String>>> {
+        let statuses = self.rpc.get_signature_statuses(&[*signature]).await?.value;
+        Ok(statuses
+            .into_iter()
+            .next()
+            .flatten()
+            .map(|status| match status.err {
+                Some(error) => Err(error.to_string()),
+                None => Ok(()),
+            }))
+    }
+}
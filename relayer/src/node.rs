@@ -0,0 +1,325 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::debug;
+
+use crate::config::ZcashConfig;
+use crate::deposit::{MempoolScan, Scan, Spend};
+use crate::rpc::JsonRpcClient;
+use crate::scan::{Scanner, TreeSizes, TreeState};
+
+/// Chain access the relayer needs from a Zcash full node
+///
+/// Deposits are found by trial-decrypting the transactions this returns, so
+/// no node-side wallet is involved and any implementation can stand in for another.
+#[async_trait]
+pub trait ZcashNode: Send + Sync {
+    /// Height of the best chain tip
+    async fn tip_height(&self) -> Result<u32>;
+
+    /// The block at `height` on the best chain
+    async fn block(&self, height: u32) -> Result<Block>;
+
+    /// Display-order hash of the block at `height` on the best chain
+    async fn block_hash(&self, height: u32) -> Result<String>;
+
+    /// Note commitment tree frontiers after the block at `height`
+    async fn tree_state(&self, height: u32) -> Result<TreeState>;
+
+    /// Raw bytes of a transaction, by display-order txid
+    async fn raw_transaction(&self, txid: &str) -> Result<Vec<u8>>;
+
+    /// Display-order txids of the transactions in the mempool
+    async fn mempool_txids(&self) -> Result<Vec<String>>;
+}
+
+/// What scanning needs of a block
+pub struct Block {
+    pub hash: String,  // Display order
+    pub txids: Vec<String>,  // Display order
+    pub tree_sizes: TreeSizes,  // Note commitments in each tree after this block
+}
+
+#[derive(Deserialize)]
+struct VerboseBlock {
+    hash: String,
+    tx: Vec<String>,
+    trees: BlockTrees,
+}
+
+#[derive(Deserialize)]
+struct BlockTrees {
+    #[serde(default)]
+    sapling: Option<TreeSize>,  // Omitted before Sapling activation
+    #[serde(default)]
+    orchard: Option<TreeSize>,  // Omitted before NU5 activation
+}
+
+#[derive(Deserialize)]
+struct TreeSize {
+    size: u64,
+}
+
+async fn block(rpc: &JsonRpcClient, height: u32) -> Result<Block> {
+    let block: VerboseBlock = rpc.call("getblock", json!([height.to_string(), 1])).await?;
+    Ok(Block {
+        hash: block.hash,
+        txids: block.tx,
+        tree_sizes: TreeSizes {
+            sapling: block.trees.sapling.map_or(0, |tree| tree.size),
+            orchard: block.trees.orchard.map_or(0, |tree| tree.size),
+        },
+    })
+}
+
+async fn block_hash(rpc: &JsonRpcClient, height: u32) -> Result<String> {
+    rpc.call("getblockhash", json!([height])).await
+}
+
+#[derive(Deserialize)]
+struct RpcTreeState {
+    #[serde(default)]
+    sapling: Option<RpcTree>,
+    #[serde(default)]
+    orchard: Option<RpcTree>,
+}
+
+#[derive(Deserialize)]
+struct RpcTree {
+    commitments: RpcCommitments,
+}
+
+#[derive(Deserialize)]
+struct RpcCommitments {
+    #[serde(rename = "finalState", default)]
+    final_state: String,  // Empty before the pool activates
+}
+
+async fn tree_state(rpc: &JsonRpcClient, height: u32) -> Result<TreeState> {
+    let state: RpcTreeState = rpc.call("z_gettreestate", json!([height.to_string()])).await?;
+    Ok(TreeState {
+        sapling: state.sapling.map(|tree| tree.commitments.final_state).unwrap_or_default(),
+        orchard: state.orchard.map(|tree| tree.commitments.final_state).unwrap_or_default(),
+    })
+}
+
+async fn raw_transaction(rpc: &JsonRpcClient, txid: &str) -> Result<Vec<u8>> {
+    let raw: String = rpc.call("getrawtransaction", json!([txid, 0])).await?;
+    hex::decode(raw).with_context(|| format!("transaction {txid} is not hex"))
+}
+
+async fn mempool_txids(rpc: &JsonRpcClient) -> Result<Vec<String>> {
+    rpc.call("getrawmempool", json!([])).await
+}
+
+/// `zcashd`, authenticated with its RPC user and password
+pub struct Zcashd {
+    rpc: JsonRpcClient,
+}
+
+impl Zcashd {
+    pub fn new(config: &ZcashConfig) -> Self {
+        Self {
+            rpc: JsonRpcClient::new(
+                &config.rpc_url,
+                Some((config.rpc_user.clone(), config.rpc_password.clone())),
+                "1.0",
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl ZcashNode for Zcashd {
+    async fn tip_height(&self) -> Result<u32> {
+        self.rpc.call("getblockcount", json!([])).await
+    }
+
+    async fn block(&self, height: u32) -> Result<Block> {
+        block(&self.rpc, height).await
+    }
+
+    async fn block_hash(&self, height: u32) -> Result<String> {
+        block_hash(&self.rpc, height).await
+    }
+
+    async fn tree_state(&self, height: u32) -> Result<TreeState> {
+        tree_state(&self.rpc, height).await
+    }
+
+    async fn raw_transaction(&self, txid: &str) -> Result<Vec<u8>> {
+        raw_transaction(&self.rpc, txid).await
+    }
+
+    async fn mempool_txids(&self) -> Result<Vec<String>> {
+        mempool_txids(&self.rpc).await
+    }
+}
+
+/// Zebra (`zebrad`), whose RPC server is unauthenticated unless cookie
+/// authentication is enabled
+pub struct Zebra {
+    rpc: JsonRpcClient,
+}
+
+impl Zebra {
+    pub fn new(config: &ZcashConfig) -> Result<Self> {
+        let auth = match &config.rpc_cookie_file {
+            Some(path) => {
+                let cookie = std::fs::read_to_string(path)
+                    .with_context(|| format!("reading Zebra RPC cookie {}", path.display()))?;
+                let (user, password) = cookie
+                    .trim()
+                    .split_once(':')
+                    .context("Zebra RPC cookie is not user:password")?;
+                Some((user.to_string(), password.to_string()))
+            }
+            None => None,
+        };
+        Ok(Self {
+            rpc: JsonRpcClient::new(&config.rpc_url, auth, "2.0"),
+        })
+    }
+}
+
+#[async_trait]
+impl ZcashNode for Zebra {
+    async fn tip_height(&self) -> Result<u32> {
+        self.rpc.call("getblockcount", json!([])).await
+    }
+
+    async fn block(&self, height: u32) -> Result<Block> {
+        block(&self.rpc, height).await
+    }
+
+    async fn block_hash(&self, height: u32) -> Result<String> {
+        block_hash(&self.rpc, height).await
+    }
+
+    async fn tree_state(&self, height: u32) -> Result<TreeState> {
+        tree_state(&self.rpc, height).await
+    }
+
+    async fn raw_transaction(&self, txid: &str) -> Result<Vec<u8>> {
+        raw_transaction(&self.rpc, txid).await
+    }
+
+    async fn mempool_txids(&self) -> Result<Vec<String>> {
+        mempool_txids(&self.rpc).await
+    }
+}
+
+/// Finds deposits by trial-decrypting every transaction in newly confirmed blocks
+pub struct NodeSource {
+    node: Box<dyn ZcashNode>,
+    scanner: Scanner,
+    next_height: u32,
+}
+
+impl NodeSource {
+    /// Scan from `start_height`: the birthday on first run, or just past the
+    /// last checkpoint
+    pub fn new(node: Box<dyn ZcashNode>, scanner: Scanner, start_height: u32) -> Self {
+        Self {
+            node,
+            scanner,
+            next_height: start_height,
+        }
+    }
+
+    pub async fn tip(&self) -> Result<u32> {
+        self.node.tip_height().await
+    }
+
+    pub async fn block_hash(&self, height: u32) -> Result<String> {
+        self.node.block_hash(height).await
+    }
+
+    pub async fn tree_state(&self, height: u32) -> Result<TreeState> {
+        self.node.tree_state(height).await
+    }
+
+    /// Scan again from `height`, after a reorg
+    pub fn rewind(&mut self, height: u32) {
+        self.next_height = self.next_height.min(height);
+    }
+
+    /// Scan next from `height`, wherever scanning had got to
+    pub fn seek(&mut self, height: u32) {
+        self.next_height = height;
+    }
+
+    /// Every transaction in the mempool, decrypting those not in `known` as
+    /// if mined at `height`
+    pub async fn mempool(&self, known: &HashSet<String>, height: u32) -> Result<MempoolScan> {
+        let mut scan = MempoolScan::default();
+        for txid in self.node.mempool_txids().await? {
+            if !known.contains(&txid) {
+                // Mined or evicted since the mempool was listed; the scan will tell
+                let Ok(raw) = self.node.raw_transaction(&txid).await else {
+                    continue;
+                };
+                let scanned = self.scanner.scan_transaction(&raw, height)?;
+                if !scanned.outputs.is_empty() {
+                    scan.transactions.push((txid.clone(), scanned));
+                }
+            }
+            scan.txids.insert(txid);
+        }
+        Ok(scan)
+    }
+
+    /// Deposits and spends in blocks that reached `confirmations` since the last call
+    pub async fn confirmed_notes(&mut self, confirmations: u32) -> Result<Scan> {
+        let tip = self.node.tip_height().await?;
+        let mut scan = Scan::default();
+        let Some(end) = (tip + 1).checked_sub(confirmations) else {
+            return Ok(scan);
+        };
+        if end < self.next_height {
+            return Ok(scan);
+        }
+
+        debug!(start = self.next_height, end, "scanning blocks");
+        for height in self.next_height..=end {
+            let block = self.node.block(height).await?;
+            scan.blocks.push((height, block.hash.clone()));
+
+            // Output positions count back from the tree size after the block,
+            // so the whole block is decrypted before any nullifier is derived
+            let mut outputs = Vec::new();
+            let mut added = TreeSizes::default();
+            for txid in block.txids {
+                let raw = self.node.raw_transaction(&txid).await?;
+                let scanned = self.scanner.scan_transaction(&raw, height)?;
+                for output in scanned.outputs {
+                    let offset = added.get(output.pool()) + output.index as u64;
+                    outputs.push((txid.clone(), offset, output));
+                }
+                scan.spends.extend(scanned.nullifiers.into_iter().map(|(pool, nullifier)| Spend {
+                    pool,
+                    nullifier,
+                    txid: txid.clone(),
+                    height,
+                }));
+                added.sapling += scanned.sapling_outputs;
+                added.orchard += scanned.orchard_actions;
+            }
+
+            let start = block
+                .tree_sizes
+                .before(&added)
+                .with_context(|| format!("block {height} has more note commitments than its trees"))?;
+            for (txid, offset, output) in outputs {
+                let position = start.get(output.pool()) + offset;
+                let note = self.scanner.received_note(&txid, output, height, tip + 1 - height, position);
+                scan.notes.push(note);
+            }
+        }
+        self.next_height = end + 1;
+        Ok(scan)
+    }
+}
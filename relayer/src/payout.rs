@@ -0,0 +1,56 @@
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use wzec_bridge::WithdrawalRequest;
+
+/// A pending burn owed a Zcash payout
+#[derive(Debug, Clone)]
+pub struct Withdrawal {
+    pub request: Pubkey,  // `WithdrawalRequest` account
+    pub nonce: u64,
+    pub net_amount: u64,  // Zatoshis owed, before the network fee
+    pub zec_address: String,
+    pub memo: Vec<u8>,  // Empty when the burn set none
+    pub unlock_slot: u64,  // Earliest slot the payout may be finalized, and so sent
+}
+
+impl Withdrawal {
+    pub fn from_request(request: Pubkey, state: &WithdrawalRequest) -> Self {
+        Self {
+            request,
+            nonce: state.nonce,
+            net_amount: state.net_amount,
+            zec_address: state.zec_address.clone(),
+            memo: state.memo.clone(),
+            unlock_slot: state.unlock_slot,
+        }
+    }
+
+    /// Key of the withdrawal's payout in the database
+    pub fn idempotency_id(&self) -> String {
+        format!("payout:{}", self.nonce)
+    }
+}
+
+/// How far a withdrawal's payout has got, as recorded in the database
+///
+/// `status` moves from `pending` through `sending` (handed to the wallet),
+/// `sent` (broadcast as `zcash_txid`) and `confirmed` (seen spending bridge
+/// notes at `height`) to `finalized`. Withdrawals that can't be paid are
+/// `invalid` and left for an operator. A batch of payouts shares its wallet
+/// operation and txid, each paid by its own output. A payout whose transaction
+/// expired unmined goes back to `pending` and is sent again.
+#[derive(Debug, Clone, Serialize)]
+pub struct Payout {
+    pub id: String,
+    pub zec_address: String,
+    pub status: String,
+    pub amount: u64,  // Zatoshis sent to the withdrawal address
+    pub fee: u64,  // This withdrawal's share of the network fee
+    pub operation_id: Option<String>,
+    pub zcash_txid: Option<String>,  // Display order
+    pub output_index: Option<u32>,  // Sapling output, Orchard action or transparent output paying the withdrawal
+    pub height: Option<u32>,
+    pub expiry_height: Option<u32>,  // Of the current transaction, 0 when it never expires
+    pub rebroadcasts: u32,  // Transactions sent for it that expired unmined
+    pub created_at: i64,  // Unix time the payout was recorded
+}
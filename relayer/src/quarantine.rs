@@ -0,0 +1,325 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Serialize, Serializer};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use tracing::{info, warn};
+
+use crate::audit::AuditLog;
+use crate::config::{Config, Network};
+use crate::deposit::{txid_bytes, Deposit};
+use crate::store::Store;
+use crate::wallet::{Operation, Recipient, Wallet};
+use crate::zip317;
+
+/// A deposit held back from minting, as operators review it
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Quarantined {
+    pub txid: String,  // Display order
+    pub pool: String,
+    pub amount: i64,  // Zatoshis
+    #[sqlx(try_from = "i64")]
+    pub height: u32,
+    #[serde(serialize_with = "memo_text")]
+    pub memo: Vec<u8>,
+    pub reason: String,
+    pub status: String,  // quarantined, attributed, refunding or refunded
+    pub recipient: Option<String>,  // Solana wallet it was attributed to
+    pub refund_address: Option<String>,
+    pub refund_operation_id: Option<String>,
+    pub refund_txid: Option<String>,
+    pub resolved_by: Option<String>,  // Operator
+    pub quarantined_at: i64,  // Unix time
+}
+
+/// A refund handed to the wallet
+#[derive(Debug, Serialize)]
+pub struct Refund {
+    pub txid: String,  // Of the quarantined deposit
+    pub address: String,
+    pub amount: u64,  // Zatoshis sent, after the fee
+    pub fee: u64,
+    pub operation_id: String,
+    pub refund_txid: Option<String>,  // Once the wallet has broadcast it
+}
+
+/// Memos are shown as text, without their zero padding
+fn memo_text<S: Serializer>(memo: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(String::from_utf8_lossy(memo).trim_end_matches('\0'))
+}
+
+/// Quarantined deposit `txid`, if it is still waiting for an operator
+async fn waiting(store: &Store, txid: &str) -> Result<Quarantined> {
+    let quarantined = store
+        .quarantined(txid)
+        .await?
+        .with_context(|| format!("deposit {txid} is not quarantined"))?;
+    anyhow::ensure!(
+        quarantined.status == "quarantined",
+        "deposit {txid} is already {}",
+        quarantined.status
+    );
+    Ok(quarantined)
+}
+
+/// Attribute quarantined deposit `txid` to `recipient` as `operator`, so it
+/// is minted to them like any other deposit
+///
+/// Peer relayers only attest deposits in their own database, so each
+/// operator attributes the deposit on their own relayer.
+pub async fn attribute(
+    store: &Store,
+    audit: Option<&AuditLog>,
+    txid: &str,
+    recipient: Pubkey,
+    operator: &str,
+) -> Result<Deposit> {
+    let quarantined = waiting(store, txid).await?;
+    let deposit = Deposit {
+        txid: quarantined.txid.clone(),
+        zcash_txid: txid_bytes(&quarantined.txid)?,
+        amount: quarantined.amount as u64,
+        recipient,
+        block_height: quarantined.height,
+        confirmations: 0,  // Not tracked once stored
+    };
+    anyhow::ensure!(
+        store.attribute_quarantined(&deposit, operator).await?,
+        "deposit {txid} was resolved meanwhile"
+    );
+    info!(txid, %recipient, operator, "quarantined deposit attributed");
+    if let Some(audit) = audit {
+        audit
+            .record(
+                "deposit_attributed",
+                json!({ "txid": txid, "recipient": recipient.to_string(), "amount": deposit.amount, "operator": operator }),
+            )
+            .await?;
+    }
+    Ok(deposit)
+}
+
+/// Sends refunds of quarantined deposits through the payout wallet
+#[derive(Clone)]
+pub struct Refunds {
+    wallet: Arc<Wallet>,
+    deposit_address: String,
+    network: Network,
+    extra_fee_actions: u64,
+    timeout: Duration,  // How long to wait for the wallet to broadcast a refund
+    dry_run: bool,
+}
+
+impl Refunds {
+    /// Refunds through the `[payouts]` wallet, if one is configured
+    pub fn new(config: &Config) -> Option<Self> {
+        let payouts = config.payouts.as_ref()?;
+        Some(Self {
+            wallet: Arc::new(Wallet::new(payouts)),
+            deposit_address: config.zcash.deposit_address.clone(),
+            network: config.zcash.network,
+            extra_fee_actions: payouts.extra_fee_actions,
+            timeout: Duration::from_secs(payouts.operation_timeout_secs),
+            dry_run: config.dry_run,
+        })
+    }
+
+    /// Refund quarantined deposit `txid` to `address`, given by its sender,
+    /// as `operator`
+    ///
+    /// The deposit's amount is sent back less the ZIP-317 fee. The deposit is
+    /// claimed before the wallet is asked, so it can't be refunded twice; if
+    /// the wallet then fails to broadcast, it goes back to quarantine. A
+    /// refund the wallet is still building when this returns is settled by
+    /// the relayer's next poll.
+    pub async fn refund(
+        &self,
+        store: &Store,
+        audit: Option<&AuditLog>,
+        txid: &str,
+        address: &str,
+        operator: &str,
+    ) -> Result<Refund> {
+        let quarantined = waiting(store, txid).await?;
+        let parsed = zcash_addr::Address::parse_for(address, self.network.address())
+            .with_context(|| format!("refund address {address:?}"))?;
+        let receiver = parsed
+            .preferred_receiver()
+            .cloned()
+            .with_context(|| format!("refund address {address} has no receiver"))?;
+        let amount = quarantined.amount as u64;
+        let notes = store.unspent_notes().await?;
+        let fee = zip317::payout_shape(&notes, amount, &[receiver]).conventional_fee()
+            + self.extra_fee_actions * zip317::MARGINAL_FEE;
+        anyhow::ensure!(amount > fee, "deposit {txid} of {amount} zatoshis doesn't cover the {fee} zatoshi fee");
+        let memo = format!("Refund of deposit {txid}");
+        let recipient = Recipient {
+            address,
+            amount: amount - fee,
+            memo: parsed.can_receive_memo().then_some(memo.as_bytes()),
+        };
+        if self.dry_run {
+            info!(txid, address, amount = recipient.amount, fee, "dry run: would refund quarantined deposit");
+            return Ok(Refund {
+                txid: txid.to_string(),
+                address: address.to_string(),
+                amount: recipient.amount,
+                fee,
+                operation_id: String::new(),
+                refund_txid: None,
+            });
+        }
+
+        anyhow::ensure!(
+            store.start_refund(txid, address, operator).await?,
+            "deposit {txid} was resolved meanwhile"
+        );
+        let operation_id = match self
+            .wallet
+            .send(&self.deposit_address, &[recipient], fee, !parsed.can_receive_memo())
+            .await
+        {
+            Ok(operation_id) => operation_id,
+            Err(error) => {
+                store.cancel_refund(txid).await?;
+                return Err(error.context(format!("refunding deposit {txid}")));
+            }
+        };
+        store.record_refund_operation(txid, &operation_id).await?;
+        info!(txid, address, amount = amount - fee, fee, %operation_id, operator, "refunding quarantined deposit");
+        if let Some(audit) = audit {
+            audit
+                .record(
+                    "refund_sent",
+                    json!({
+                        "txid": txid,
+                        "address": address,
+                        "amount": amount - fee,
+                        "fee": fee,
+                        "operation_id": operation_id,
+                        "operator": operator,
+                    }),
+                )
+                .await?;
+        }
+        let refund_txid = self.settle(store, txid, &operation_id, self.timeout).await?;
+        Ok(Refund {
+            txid: txid.to_string(),
+            address: address.to_string(),
+            amount: amount - fee,
+            fee,
+            operation_id,
+            refund_txid,
+        })
+    }
+
+    /// Settle every refund the wallet was still building; a dry run leaves
+    /// them for the next real run
+    pub async fn settle_all(&self, store: &Store) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        for (txid, operation_id) in store.refunds_in_flight().await? {
+            if let Err(error) = self.settle(store, &txid, &operation_id, Duration::ZERO).await {
+                warn!(%txid, %operation_id, error = %format!("{error:#}"), "refund failed; deposit back in quarantine");
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for the refund of `txid` to be broadcast,
+    /// returning its txid once it is, or nothing while the wallet is still
+    /// building it
+    async fn settle(&self, store: &Store, txid: &str, operation_id: &str, timeout: Duration) -> Result<Option<String>> {
+        match self.wallet.wait(operation_id, timeout).await? {
+            Operation::Succeeded(refund_txid) => {
+                store.finish_refund(txid, &refund_txid).await?;
+                info!(txid, %refund_txid, "quarantined deposit refunded");
+                Ok(Some(refund_txid))
+            }
+            Operation::Failed(error) => {
+                store.cancel_refund(txid).await?;
+                anyhow::bail!("wallet failed to send the refund of deposit {txid}: {error}")
+            }
+            // Still building, or lost with a wallet restart and left for an operator
+            Operation::Running | Operation::Unknown => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deposit::tests::{note, txid};
+    use crate::store::tests::open_temp;
+
+    const SENDER: &str = "t1SmxPaFcF5kYFn5ctgCt7QiaXzpTZyF4kG";
+
+    #[tokio::test]
+    async fn quarantined_deposit_is_refunded_once() {
+        let store = open_temp("refund-once").await;
+        store.quarantine(&[note(1, 0, 50_000)], "no recipient").await.unwrap();
+
+        assert!(store.start_refund(&txid(1), SENDER, "alice").await.unwrap());
+        // Claimed, so another operator can't refund it while the wallet builds the first refund
+        assert!(!store.start_refund(&txid(1), SENDER, "bob").await.unwrap());
+        assert!(waiting(&store, &txid(1)).await.is_err());
+        store.record_refund_operation(&txid(1), "opid-1").await.unwrap();
+        assert_eq!(store.refunds_in_flight().await.unwrap(), [(txid(1), "opid-1".to_string())]);
+
+        store.finish_refund(&txid(1), &txid(9)).await.unwrap();
+        assert!(store.refunds_in_flight().await.unwrap().is_empty());
+        assert!(store.quarantined_deposits().await.unwrap().is_empty());
+        let refunded = store.quarantined(&txid(1)).await.unwrap().unwrap();
+        assert_eq!(refunded.status, "refunded");
+        assert_eq!(refunded.refund_txid, Some(txid(9)));
+        assert_eq!(refunded.resolved_by.as_deref(), Some("alice"));
+
+        // Nor refunded again, even once a late wallet failure is reported for it
+        assert!(!store.start_refund(&txid(1), SENDER, "bob").await.unwrap());
+        store.cancel_refund(&txid(1)).await.unwrap();
+        assert_eq!(store.quarantined(&txid(1)).await.unwrap().unwrap().status, "refunded");
+    }
+
+    #[tokio::test]
+    async fn refund_the_wallet_failed_goes_back_to_quarantine() {
+        let store = open_temp("refund-failed").await;
+        store.quarantine(&[note(1, 0, 50_000)], "no recipient").await.unwrap();
+        assert!(store.start_refund(&txid(1), SENDER, "alice").await.unwrap());
+        store.record_refund_operation(&txid(1), "opid-1").await.unwrap();
+
+        store.cancel_refund(&txid(1)).await.unwrap();
+        let quarantined = waiting(&store, &txid(1)).await.unwrap();
+        assert_eq!((quarantined.refund_address, quarantined.refund_operation_id), (None, None));
+        assert!(store.refunds_in_flight().await.unwrap().is_empty());
+        // Free to be refunded, once
+        assert!(store.start_refund(&txid(1), SENDER, "bob").await.unwrap());
+        assert!(!store.start_refund(&txid(1), SENDER, "bob").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn refunded_deposit_is_not_minted() {
+        let store = open_temp("refund-not-minted").await;
+        store.quarantine(&[note(1, 0, 50_000)], "no recipient").await.unwrap();
+        let recipient = Pubkey::new_unique();
+
+        // Neither while the refund is out nor after it
+        assert!(store.start_refund(&txid(1), SENDER, "alice").await.unwrap());
+        assert!(attribute(&store, None, &txid(1), recipient, "bob").await.is_err());
+        store.finish_refund(&txid(1), &txid(9)).await.unwrap();
+        assert!(attribute(&store, None, &txid(1), recipient, "bob").await.is_err());
+        assert!(store.deposit(&txid(1)).await.unwrap().is_none());
+        assert!(store.pending_deposits().await.unwrap().is_empty());
+        assert!(store.attributed_deposits().await.unwrap().is_empty());
+
+        // And an attributed deposit, minted instead, can't be refunded
+        store.quarantine(&[note(2, 0, 50_000)], "no recipient").await.unwrap();
+        let deposit = attribute(&store, None, &txid(2), recipient, "alice").await.unwrap();
+        assert!(!store.start_refund(&txid(2), SENDER, "bob").await.unwrap());
+        let attributed = store.attributed_deposits().await.unwrap();
+        assert_eq!(attributed.len(), 1);
+        assert_eq!((attributed[0].txid.as_str(), attributed[0].amount), (deposit.txid.as_str(), 50_000));
+    }
+}
@@ -0,0 +1,2303 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures::stream::{self, FuturesOrdered, StreamExt};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+use tracing::{error, info, instrument, warn};
+use wzec_bridge::{interface, WithdrawalStatus};
+
+use crate::admin;
+use crate::alerts::{Alerter, Severity};
+use crate::audit::AuditLog;
+use crate::chaos::{self, Fault};
+use crate::attestation::{self, Aggregator, Attestor};
+use crate::config::{ApprovalConfig, Backend, Config, PayoutConfig, PipelineConfig};
+use crate::deposit::{self, Deposit, MempoolScan, Pool, ReceivedNote, Scan};
+use crate::events::{Feed, WithdrawalListener};
+use crate::feed::{DepositEvent, DepositFeed, SeenDeposit};
+use crate::keys::ViewingKey;
+use crate::lightwalletd::LightwalletdSource;
+use crate::memo;
+use crate::metrics::{Metrics, SharedMetrics};
+use crate::minter::{Minter, Submission};
+use crate::node::{NodeSource, Zcashd, Zebra};
+use crate::payout::{Payout, Withdrawal};
+use crate::quarantine::Refunds;
+use crate::retry;
+use crate::scan::{DecryptedOutput, Scanner, TreeState};
+use crate::status::{self, SharedSnapshot, Snapshot};
+use crate::store::Store;
+use crate::wallet::{Operation, Recipient, Wallet};
+use crate::zip317;
+
+/// Blocks a mempool transaction that never expires is given to confirm,
+/// as for the default expiry delta, before it is no longer reported seen
+const MEMPOOL_EXPIRY_DELTA: u32 = 40;
+
+/// Where the relayer learns about deposits
+enum Source {
+    Node(NodeSource),
+    Lightwalletd(LightwalletdSource),
+}
+
+impl Source {
+    async fn tip(&mut self) -> Result<u32> {
+        match self {
+            Source::Node(node) => node.tip().await,
+            Source::Lightwalletd(lightwalletd) => lightwalletd.tip().await,
+        }
+    }
+
+    async fn block_hash(&mut self, height: u32) -> Result<String> {
+        match self {
+            Source::Node(node) => node.block_hash(height).await,
+            Source::Lightwalletd(lightwalletd) => lightwalletd.block_hash(height).await,
+        }
+    }
+
+    async fn tree_state(&mut self, height: u32) -> Result<TreeState> {
+        match self {
+            Source::Node(node) => node.tree_state(height).await,
+            Source::Lightwalletd(lightwalletd) => lightwalletd.tree_state(height).await,
+        }
+    }
+
+    fn rewind(&mut self, height: u32) {
+        match self {
+            Source::Node(node) => node.rewind(height),
+            Source::Lightwalletd(lightwalletd) => lightwalletd.rewind(height),
+        }
+    }
+
+    fn seek(&mut self, height: u32) {
+        match self {
+            Source::Node(node) => node.seek(height),
+            Source::Lightwalletd(lightwalletd) => lightwalletd.seek(height),
+        }
+    }
+
+    /// Every transaction in the mempool, decrypting those not in `known`
+    async fn mempool(&mut self, known: &HashSet<String>, height: u32) -> Result<MempoolScan> {
+        match self {
+            Source::Node(node) => node.mempool(known, height).await,
+            Source::Lightwalletd(lightwalletd) => lightwalletd.mempool(known, height).await,
+        }
+    }
+
+    /// Deposits and spends in blocks that reached `confirmations` since the last call
+    async fn confirmed_notes(&mut self, confirmations: u32) -> Result<Scan> {
+        match self {
+            Source::Node(node) => node.confirmed_notes(confirmations).await,
+            Source::Lightwalletd(lightwalletd) => lightwalletd.confirmed_notes(confirmations).await,
+        }
+    }
+}
+
+/// Bearer token shared with peer relayers, from the environment variable `name`
+fn attestation_token(name: &str) -> Result<String> {
+    std::env::var(name).with_context(|| format!("reading the attestation bearer token from ${name}"))
+}
+
+/// Run `deposits` through the stages `Relayer::mint_all` describes, returning
+/// each with what `finish` made of it, in the order given
+///
+/// Apart from the relayer, so the ordering can be tested with stand-in stages.
+async fn mint_pipeline<M, Attest, Commit, Confirm, Finish>(
+    pipeline: &PipelineConfig,
+    deposits: Vec<Deposit>,
+    attest: impl Fn(Deposit) -> Attest,
+    commit: impl Fn(Deposit, Result<M>) -> Commit,
+    confirm: impl Fn(Deposit, Result<M>) -> Confirm,
+    finish: impl Fn(Deposit, Result<M>) -> Finish,
+) -> Vec<(Deposit, Result<bool>)>
+where
+    Attest: Future<Output = (Deposit, Result<M>)>,
+    Commit: Future<Output = (Deposit, Result<M>)>,
+    Confirm: Future<Output = (Deposit, Result<M>)>,
+    Finish: Future<Output = (Deposit, Result<bool>)>,
+{
+    let (queue, mut queued) = mpsc::channel(pipeline.queue_depth);
+    let attesting = async move {
+        let mut attested = stream::iter(deposits).map(attest).buffered(pipeline.attest_concurrency);
+        while let Some(deposit) = attested.next().await {
+            if queue.send(deposit).await.is_err() {
+                break;
+            }
+        }
+    };
+    let committing = async {
+        let mut confirming = FuturesOrdered::new();
+        let mut finished = Vec::new();
+        let mut open = true;
+        loop {
+            tokio::select! {
+                Some((deposit, minting)) = confirming.next(), if !confirming.is_empty() => {
+                    finished.push(finish(deposit, minting).await);
+                }
+                next = queued.recv(), if open && confirming.len() < pipeline.submit_concurrency => {
+                    let Some((deposit, minting)) = next else {
+                        open = false;
+                        continue;
+                    };
+                    let (deposit, minting) = commit(deposit, minting).await;
+                    confirming.push_back(confirm(deposit, minting));
+                }
+                else => break,
+            }
+        }
+        finished
+    };
+    let ((), finished) = tokio::join!(attesting, committing);
+    finished
+}
+
+/// Attribute the `notes` one transaction paid the bridge to the recipient
+/// of the deposit address each paid, or otherwise the one its memo names,
+/// or give the reason the transaction is quarantined instead: no single
+/// recipient, a retired address or too small an amount
+async fn attribute(store: &Store, min_deposit: u64, notes: &[ReceivedNote]) -> Result<Result<Deposit, String>> {
+    let mut assigned = Vec::with_capacity(notes.len());
+    for note in notes {
+        let recipient = store.deposit_recipient(&note.diversifier_index).await?;
+        if recipient.is_none() {
+            if let Some(retired) = store.retired_recipient(&note.diversifier_index).await? {
+                return Ok(Err(format!("deposit {} paid the retired deposit address of {retired}", note.txid)));
+            }
+        }
+        assigned.push(recipient);
+    }
+    let deposit = match Deposit::from_notes(notes, &assigned) {
+        Ok(deposit) => deposit,
+        Err(error) => return Ok(Err(format!("{error:#}"))),
+    };
+    if deposit.amount < min_deposit {
+        return Ok(Err(format!(
+            "deposit {} of {} zatoshis is below the minimum of {min_deposit}",
+            deposit.txid, deposit.amount
+        )));
+    }
+    Ok(Ok(deposit))
+}
+
+/// The payouts of `batch` to send now: none until it is full or its oldest
+/// payout has waited `window_secs`, and then the first `max_size`, the rest
+/// waiting for the next batch
+fn due_batch(
+    mut batch: Vec<(Withdrawal, Payout)>,
+    max_size: usize,
+    window_secs: u64,
+    now: i64,
+) -> Vec<(Withdrawal, Payout)> {
+    let Some(oldest) = batch.iter().map(|(_, payout)| payout.created_at).min() else {
+        return batch;
+    };
+    if batch.len() < max_size && now < oldest + window_secs as i64 {
+        return Vec::new();
+    }
+    batch.truncate(max_size);
+    batch
+}
+
+/// The payouts of `batch` that cover an equal share of the fee `fee_for`
+/// charges for sending them together, with that share, or `None` if none do
+///
+/// Leaving out a withdrawal too small for its share changes the transaction,
+/// so the fee is worked out again until every one left covers it. Those left
+/// out wait for a smaller batch.
+fn share_fee(
+    mut batch: Vec<(Withdrawal, Payout)>,
+    fee_for: impl Fn(&[(Withdrawal, Payout)]) -> Result<u64>,
+) -> Result<Option<(Vec<(Withdrawal, Payout)>, u64)>> {
+    while !batch.is_empty() {
+        let fee_share = fee_for(&batch)?.div_ceil(batch.len() as u64);
+        let (payable, short): (Vec<_>, Vec<_>) =
+            batch.into_iter().partition(|(withdrawal, _)| withdrawal.net_amount > fee_share);
+        batch = payable;
+        for (withdrawal, _) in &short {
+            warn!(
+                nonce = withdrawal.nonce,
+                net_amount = withdrawal.net_amount,
+                fee_share,
+                "withdrawal does not cover its share of the payout fee; waiting for a smaller batch"
+            );
+        }
+        if short.is_empty() {
+            return Ok(Some((batch, fee_share)));
+        }
+    }
+    Ok(None)
+}
+
+/// Status a new payout of `withdrawal` starts in: `pending`, or
+/// `needs_approval` above the `approvals` threshold, or `invalid` when it
+/// can't be paid at all on `network` for `fee`
+fn initial_payout_status(
+    withdrawal: &Withdrawal,
+    fee: u64,
+    network: zcash_addr::Network,
+    approvals: Option<&ApprovalConfig>,
+) -> &'static str {
+    match zcash_addr::Address::parse_for(&withdrawal.zec_address, network) {
+        Err(error) => {
+            warn!(nonce = withdrawal.nonce, zec_address = %withdrawal.zec_address, %error, "withdrawal address is not payable");
+            "invalid"
+        }
+        Ok(_) if withdrawal.net_amount <= fee => {
+            warn!(nonce = withdrawal.nonce, net_amount = withdrawal.net_amount, fee, "withdrawal does not cover the network fee");
+            "invalid"
+        }
+        Ok(_) => match approvals {
+            Some(approvals) if withdrawal.net_amount > approvals.threshold => {
+                info!(
+                    nonce = withdrawal.nonce,
+                    net_amount = withdrawal.net_amount,
+                    threshold = approvals.threshold,
+                    "payout needs operators' approval"
+                );
+                "needs_approval"
+            }
+            _ => "pending",
+        },
+    }
+}
+
+/// Expiry height of a sent payout transaction not seen spending bridge notes,
+/// if it can no longer be mined: it expires, and the scan has passed that
+/// height without finding it
+///
+/// Only then is the payout sent again. Before, the old transaction could
+/// still be mined and the withdrawal paid twice.
+fn expired_unmined(expiry_height: Option<u32>, scanned_height: Option<u32>) -> Option<u32> {
+    let expiry_height = expiry_height.filter(|&height| height > 0)?;
+    (scanned_height? >= expiry_height).then_some(expiry_height)
+}
+
+/// Keep taking or renewing the leader lease for `instance` in the background,
+/// returning a flag that is set while this instance holds it
+///
+/// Renewal runs apart from polling, so a slow poll doesn't lose the lease;
+/// only a dead process or an unreachable database does.
+fn hold_lease(store: Store, instance: String, lease_secs: u64) -> Arc<AtomicBool> {
+    let leader = Arc::new(AtomicBool::new(false));
+    let flag = leader.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs((lease_secs / 3).max(1)));
+        loop {
+            interval.tick().await;
+            let held = match store.acquire_lease(&instance, lease_secs).await {
+                Ok(held) => held,
+                Err(error) => {
+                    warn!(%instance, error = %format!("{error:#}"), "renewing the leader lease failed");
+                    false
+                }
+            };
+            flag.store(held, Ordering::SeqCst);
+        }
+    });
+    leader
+}
+
+/// Polls for confirmed deposits and mints wZEC for each
+pub struct Relayer {
+    config: Config,
+    source: Source,
+    minter: Minter,
+    wallet: Option<Wallet>,  // Pays out withdrawals when `[payouts]` is configured
+    refunds: Option<Refunds>,  // Refunds quarantined deposits through the same wallet
+    aggregator: Option<Aggregator>,  // Gathers peer attestations when `attestation.peers` is set
+    store: Store,
+    handled: HashSet<String>,  // Txids minted or refused since startup
+    failed: Vec<ReceivedNote>,  // Notes that couldn't be attributed, retried on the next poll
+    pending: Vec<Deposit>,  // Deposits whose mint failed, retried on the next poll
+    start_height: u32,  // Where scanning resumed at startup
+    tip: Option<(u32, String)>,  // Chain tip at the last poll
+    halted: bool,  // Set by a deep reorg; deposits are recorded but not minted until restart
+    snapshot: SharedSnapshot,  // Published to the status API after every poll
+    feed: DepositFeed,  // Deposits seen in the mempool and deposit events, for the status API
+    mempool: HashSet<String>,  // Txids in the mempool at the last look, already decrypted
+    metrics: SharedMetrics,
+    alerter: Option<Alerter>,  // Sends alerts when `[alerts]` is configured
+    audit: Option<AuditLog>,  // Records consequential actions when `[audit]` is configured
+    consolidating: Option<WalletTransaction>,  // Note merge in flight, at most one at a time
+    sweeping: Option<WalletTransaction>,  // Sweep to the cold reserve in flight, at most one at a time
+    hot_low: bool,  // Hot balance below `reserve.hot_min` at the last poll
+    leader: bool,  // Acting on the database: always, unless `[ha]` is set and another instance holds the lease
+    withdrawals: Option<mpsc::UnboundedReceiver<u64>>,  // Nonces from the withdrawal listener, when `solana.ws_url` or `[solana.geyser]` is set
+    watched: BTreeSet<u64>,  // Withdrawals the listener has seen that may still need paying out
+    rehearsed: Mutex<HashSet<String>>,  // Wallet spends a dry run has already logged
+    rehearsed_payouts: Mutex<HashMap<String, Payout>>,  // Payouts a dry run would have recorded, kept in memory only
+}
+
+/// How far a deposit has come through the minting pipeline
+enum Minting {
+    /// Already minted, or left to the minting relayer when `false`
+    Settled(bool),
+    /// Its receipt is on chain, though the database doesn't know yet
+    Receipt,
+    /// Mint built with these other guardians' attestations
+    Built(Vec<(Pubkey, [u8; 64])>, Submission),
+    /// First mint attempt recorded and sent, or failed to send
+    Sent(Vec<(Pubkey, [u8; 64])>, Submission, Result<Signature>),
+    /// Mint confirmed on chain
+    Confirmed(Signature),
+}
+
+/// A maintenance transaction handed to the wallet, a note merge or a sweep
+struct WalletTransaction {
+    operation_id: String,
+    txid: Option<String>,  // Once broadcast
+    expiry_height: u32,  // 0 when it never expires
+}
+
+impl Relayer {
+    pub async fn new(config: Config) -> Result<Self> {
+        let mint = config.mint()?;
+        let zcash = &config.zcash;
+        let scanner = Scanner::new(&ViewingKey::decode(zcash.network, &zcash.viewing_key)?);
+        let store = Store::open(&config.database).await?;
+
+        // Resume past the last checkpoint; only a fresh database starts at the birthday
+        let start_height = match store.checkpoint_height().await? {
+            Some(height) => height + 1,
+            None => zcash.birthday_height,
+        };
+        let source = match zcash.backend {
+            Backend::Zcashd => Source::Node(NodeSource::new(
+                Box::new(Zcashd::new(zcash)),
+                scanner,
+                start_height,
+            )),
+            Backend::Zebra => Source::Node(NodeSource::new(
+                Box::new(Zebra::new(zcash)?),
+                scanner,
+                start_height,
+            )),
+            Backend::Lightwalletd => {
+                let lightwalletd = config
+                    .lightwalletd
+                    .as_ref()
+                    .context("missing [lightwalletd] section")?;
+                Source::Lightwalletd(LightwalletdSource::new(lightwalletd, scanner, start_height).await?)
+            }
+        };
+        let aggregator = match &config.attestation {
+            Some(attestation) if !attestation.peers.is_empty() => {
+                Some(Aggregator::new(attestation.peers.clone(), attestation_token(&attestation.token_env)?))
+            }
+            _ => None,
+        };
+        // Without `[ha]`, this is the only instance using the database
+        let leader = config.ha.is_none();
+        let minter = Minter::new(&config.solana, mint, config.dry_run).await?;
+        let alerter = match &config.alerts {
+            Some(alerts) => Some(Alerter::new(alerts, minter.relayer().to_string())?),
+            None => None,
+        };
+        // A dry run's actions never happen, so they aren't audited
+        let audit = match &config.audit {
+            Some(audit) if !config.dry_run => Some(AuditLog::open(audit, store.clone()).await?),
+            _ => None,
+        };
+        Ok(Self {
+            source,
+            minter,
+            wallet: config.payouts.as_ref().map(Wallet::new),
+            refunds: Refunds::new(&config),
+            aggregator,
+            store,
+            config,
+            start_height,
+            handled: HashSet::new(),
+            failed: Vec::new(),
+            pending: Vec::new(),
+            tip: None,
+            halted: false,
+            snapshot: Arc::new(RwLock::new(Snapshot::default())),
+            feed: DepositFeed::default(),
+            mempool: HashSet::new(),
+            metrics: Arc::new(Metrics::new()?),
+            alerter,
+            audit,
+            consolidating: None,
+            sweeping: None,
+            hot_low: false,
+            leader,
+            withdrawals: None,
+            watched: BTreeSet::new(),
+            rehearsed: Mutex::new(HashSet::new()),
+            rehearsed_payouts: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Poll until interrupted
+    pub async fn run(&mut self) -> Result<()> {
+        let tip = self.source.tip().await?;
+        let unspent_zat = self.store.unspent_balance().await?;
+        info!(
+            relayer = %self.minter.relayer(),
+            backend = ?self.config.zcash.backend,
+            deposit_address = %self.config.zcash.deposit_address,
+            confirmations = self.config.zcash.confirmations,
+            tip,
+            start_height = self.start_height,
+            unspent_zat,
+            "relayer started"
+        );
+        if self.config.dry_run {
+            warn!("dry run: Solana transactions are only simulated and the wallet is never asked to spend");
+        }
+        if let Some(audit) = &self.audit {
+            audit.record_config(&self.config.digest).await?;
+        }
+        if self.leader {
+            self.reconcile_submissions().await?;
+            self.pending = self.store.pending_deposits().await?;
+        }
+        let lease = self.config.ha.as_ref().map(|ha| hold_lease(self.store.clone(), ha.instance_id.clone(), ha.lease_secs));
+
+        let feed = match (&self.config.solana.ws_url, &self.config.solana.geyser) {
+            (Some(url), _) => Some(Feed::WebSocket(url.clone())),
+            (None, Some(geyser)) => Some(Feed::Geyser(geyser.clone())),
+            (None, None) => None,
+        };
+        // Withdrawals only matter to a relayer that pays them out
+        if let (Some(feed), Some(_)) = (feed, &self.wallet) {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            let listener = WithdrawalListener::open(
+                feed,
+                &self.config.solana.rpc_url,
+                interface::bridge_state_address(&self.minter.mint()),
+                self.store.clone(),
+                sender,
+            )
+            .await?;
+            tokio::spawn(listener.run());
+            self.withdrawals = Some(receiver);
+            if self.leader {
+                self.watch_pending().await?;
+            }
+            info!("withdrawal listener started");
+        }
+
+        if let Some(status) = &self.config.status {
+            let server = status::serve(
+                status.listen,
+                self.store.clone(),
+                self.snapshot.clone(),
+                self.metrics.clone(),
+                self.feed.clone(),
+            );
+            tokio::spawn(async move {
+                if let Err(error) = server.await {
+                    error!(error = %format!("{error:#}"), "status API stopped");
+                }
+            });
+            info!(listen = %status.listen, "status API listening");
+        }
+
+        if let Some(approvals) = &self.config.approvals {
+            let listen = approvals.listen;
+            let server = admin::serve(
+                listen,
+                self.store.clone(),
+                self.audit.clone(),
+                self.refunds.clone(),
+                approvals.required,
+                admin::operator_tokens(approvals)?,
+            );
+            tokio::spawn(async move {
+                if let Err(error) = server.await {
+                    error!(error = %format!("{error:#}"), "admin API stopped");
+                }
+            });
+            info!(%listen, "admin API listening");
+        }
+
+        let attestation_listen = self.config.attestation.as_ref().and_then(|attestation| attestation.listen);
+        if let (Some(listen), true) = (attestation_listen, self.config.dry_run) {
+            warn!(%listen, "dry run: not serving attestations to peers");
+        } else if let Some(listen) = attestation_listen {
+            let attestation = self.config.attestation.as_ref().context("missing [attestation] section")?;
+            let zcash = &self.config.zcash;
+            let server = attestation::serve(
+                listen,
+                Attestor {
+                    store: self.store.clone(),
+                    signer: self.minter.signer(),
+                    mint: self.minter.mint(),
+                    token_program: self.minter.token_program().await?,
+                    network: self.minter.bridge_state().await?.network,
+                    token: Arc::new(attestation_token(&attestation.token_env)?),
+                    keys: Arc::new(ViewingKey::decode(zcash.network, &zcash.viewing_key)?),
+                    min_deposit: zcash.min_deposit,
+                    audit: self.audit.clone(),
+                },
+            );
+            tokio::spawn(async move {
+                if let Err(error) = server.await {
+                    error!(error = %format!("{error:#}"), "attestation server stopped");
+                }
+            });
+            info!(%listen, "attestation server listening");
+        }
+
+        let mut interval = tokio::time::interval(self.config.poll_interval());
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut hangup = signal(SignalKind::hangup()).context("listening for SIGHUP")?;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Some(lease) = &lease {
+                        if !self.follow_lease(lease.load(Ordering::SeqCst)).await {
+                            self.publish(None).await;
+                            continue;
+                        }
+                    }
+                    let error = self.poll().await.err().map(|error| format!("{error:#}"));
+                    if let Some(error) = &error {
+                        error!(%error, "poll failed");
+                        self.metrics.errors.with_label_values(&["poll"]).inc();
+                    }
+                    self.publish(error).await;
+                }
+                _ = hangup.recv() => {
+                    let poll_interval = self.config.poll_interval();
+                    if let Err(error) = self.reload_config().await {
+                        error!(error = %format!("{error:#}"), "config reload failed; keeping the running config");
+                    }
+                    if self.config.poll_interval() != poll_interval {
+                        interval = tokio::time::interval(self.config.poll_interval());
+                        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                    }
+                }
+                _ = tokio::signal::ctrl_c() => {
+                    info!("relayer stopping");
+                    if let Some(ha) = &self.config.ha {
+                        self.store.release_lease(&ha.instance_id).await?;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Take the reloadable settings from the config file as it is now
+    ///
+    /// Nothing changes unless the whole file is valid. Other settings that
+    /// changed are reported and left as they were until a restart.
+    async fn reload_config(&mut self) -> Result<()> {
+        let fresh = Config::load(&self.config.path)?;
+        if fresh.digest == self.config.digest {
+            info!("config unchanged");
+            return Ok(());
+        }
+        let alerter = match &fresh.alerts {
+            Some(alerts) => Some(Alerter::new(alerts, self.minter.relayer().to_string())?),
+            None => None,
+        };
+        let restart = self.config.reload(fresh);
+        self.alerter = alerter;
+        if !restart.is_empty() {
+            warn!(settings = ?restart, "config settings that need a restart were left as they were");
+        }
+        info!(
+            confirmations = self.config.zcash.confirmations,
+            poll_interval_secs = self.config.zcash.poll_interval_secs,
+            digest = %self.config.digest,
+            "config reloaded"
+        );
+        if let Some(audit) = &self.audit {
+            audit.record_config(&self.config.digest).await?;
+        }
+        Ok(())
+    }
+
+    /// Act on a change of leadership, returning whether this instance should poll
+    ///
+    /// A new leader picks up from the database rather than from whatever this
+    /// instance saw before it stood by: it resumes scanning above the latest
+    /// checkpoint and reloads pending deposits and unsettled submissions.
+    async fn follow_lease(&mut self, leader: bool) -> bool {
+        if leader == self.leader {
+            return leader;
+        }
+        if !leader {
+            warn!("lost the leader lease; standing by");
+            self.leader = false;
+            return false;
+        }
+        match self.promote().await {
+            Ok(()) => {
+                info!(resume_height = self.start_height, "took the leader lease");
+                self.leader = true;
+                true
+            }
+            Err(error) => {
+                error!(error = %format!("{error:#}"), "taking over as leader failed");
+                false
+            }
+        }
+    }
+
+    async fn promote(&mut self) -> Result<()> {
+        self.start_height = match self.store.checkpoint_height().await? {
+            Some(height) => height + 1,
+            None => self.config.zcash.birthday_height,
+        };
+        self.source.seek(self.start_height);
+        self.tip = None;
+        self.handled.clear();
+        self.failed.clear();
+        self.consolidating = None;
+        self.sweeping = None;
+        self.reconcile_submissions().await?;
+        self.pending = self.store.pending_deposits().await?;
+        if self.withdrawals.is_some() {
+            self.watch_pending().await?;
+        }
+        Ok(())
+    }
+
+    /// Publish what the last poll saw to the status API and its metrics
+    async fn publish(&self, last_error: Option<String>) {
+        let scanned_height = match self.store.latest_block().await {
+            Ok(block) => block.map(|(height, _)| height),
+            Err(error) => {
+                warn!(error = %format!("{error:#}"), "reading scan progress for the status API");
+                None
+            }
+        };
+        let snapshot = Snapshot {
+            relayer: self.minter.relayer().to_string(),
+            tip: self.tip.as_ref().map(|(height, _)| *height),
+            scanned_height,
+            halted: self.halted,
+            leader: self.leader,
+            hot_low: self.hot_low,
+            last_poll: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|elapsed| elapsed.as_secs() as i64),
+            last_error,
+        };
+        self.metrics.zcash_tip_height.set(snapshot.tip.unwrap_or_default().into());
+        self.metrics.zcash_scanned_height.set(snapshot.scanned_height.unwrap_or_default().into());
+        self.metrics.leader.set(snapshot.leader.into());
+        self.metrics.halted.set(snapshot.halted.into());
+        self.metrics.pending_deposits.set(self.pending.len() as i64);
+        if let Err(error) = self.record_gauges().await {
+            warn!(error = %format!("{error:#}"), "reading metrics");
+        }
+        if let Ok(mut published) = self.snapshot.write() {
+            *published = snapshot;
+        }
+    }
+
+    async fn record_gauges(&self) -> Result<()> {
+        self.metrics.hot_balance.set(self.store.unspent_balance().await? as i64);
+        self.metrics.open_payouts.set(self.store.open_payouts().await?.len() as i64);
+        self.metrics.solana_slot.set(self.minter.slot().await? as i64);
+        Ok(())
+    }
+
+    /// Scan again from `from_height` up to the confirmed tip, then exit
+    ///
+    /// Notes, spends and quarantined deposits already in the database are left
+    /// as they are and anything missing is added. Every deposit found is
+    /// checked against the database and its on-chain receipt, so only deposits
+    /// that were never minted are minted now.
+    pub async fn rescan(&mut self, from_height: u32) -> Result<()> {
+        if from_height > self.start_height {
+            bail!("nothing scanned at or above height {from_height}; scanning resumes at {}", self.start_height);
+        }
+        info!(
+            relayer = %self.minter.relayer(),
+            from_height,
+            scanned_to = self.start_height.saturating_sub(1),
+            "rescanning"
+        );
+        self.record("rescan", json!({ "from_height": from_height })).await?;
+        self.reconcile_submissions().await?;
+        self.pending = self.store.pending_deposits().await?;
+
+        self.source.rewind(from_height);
+        let mut blocks = 0;
+        loop {
+            match self.poll().await? {
+                0 => break,
+                scanned => blocks += scanned,
+            }
+        }
+        if self.halted {
+            bail!("rescan stopped by a deep reorg; mints are paused");
+        }
+        let unminted = self.failed.len() + self.pending.len();
+        if unminted > 0 {
+            bail!("{unminted} deposits could not be minted; run the relayer to retry them");
+        }
+        info!(from_height, blocks, "rescan complete");
+        Ok(())
+    }
+
+    /// Append `action` to the audit log, if there is one
+    async fn record(&self, action: &str, detail: Value) -> Result<()> {
+        if let Some(audit) = &self.audit {
+            audit.record(action, detail).await?;
+        }
+        Ok(())
+    }
+
+    /// In a dry run, whether the wallet spend `key` is new, so what the
+    /// relayer would do is logged once rather than on every poll
+    fn first_rehearsal(&self, key: String) -> bool {
+        self.rehearsed.lock().map(|mut rehearsed| rehearsed.insert(key)).unwrap_or(true)
+    }
+
+    async fn alert(&self, key: &str, severity: Severity, summary: &str) {
+        if let Some(alerter) = &self.alerter {
+            alerter.fire(key, severity, summary).await;
+        }
+    }
+
+    async fn resolve_alert(&self, key: &str, summary: &str) {
+        if let Some(alerter) = &self.alerter {
+            alerter.resolve(key, summary).await;
+        }
+    }
+
+    /// Raise or clear the alerts that are checked every poll: deposits stuck
+    /// pending, the signer not answering, and the attested reserve falling
+    /// below `reserve_ratio_min`
+    async fn check_alerts(&self) -> Result<()> {
+        let Some(alerts) = &self.config.alerts else {
+            return Ok(());
+        };
+        for deposit in self.store.stuck_deposits(alerts.stuck_deposit_mins * 60).await? {
+            self.alert(
+                &format!("deposit_stuck:{}", deposit.txid),
+                Severity::Warning,
+                &format!(
+                    "deposit {} of {} zatoshis to {} pending for over {} minutes",
+                    deposit.txid, deposit.amount, deposit.recipient, alerts.stuck_deposit_mins
+                ),
+            )
+            .await;
+        }
+
+        match self.minter.check_signer().await {
+            Ok(()) => self.resolve_alert("signer_unreachable", "signer answering again").await,
+            Err(error) => {
+                self.alert(
+                    "signer_unreachable",
+                    Severity::Critical,
+                    &format!("relayer signer not answering: {error:#}"),
+                )
+                .await
+            }
+        }
+
+        if let Some(minimum) = alerts.reserve_ratio_min {
+            if let Some(attestation) = self.minter.latest_reserve_attestation().await? {
+                if attestation.circulating_wzec > 0 {
+                    let ratio = attestation.reserve_zatoshis as f64 / attestation.circulating_wzec as f64;
+                    if ratio < minimum {
+                        self.alert(
+                            "reserve_ratio",
+                            Severity::Critical,
+                            &format!(
+                                "attested reserve ratio {ratio:.4} below {minimum} at Zcash height {}",
+                                attestation.zcash_height
+                            ),
+                        )
+                        .await;
+                    } else {
+                        self.resolve_alert("reserve_ratio", &format!("attested reserve ratio back at {ratio:.4}"))
+                            .await;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Record newly confirmed notes and spends, then mint every deposit that
+    /// has reached the confirmation depth, returning how many blocks were scanned
+    #[instrument(skip_all)]
+    async fn poll(&mut self) -> Result<usize> {
+        self.track_tip().await?;
+        if let Some(fork_height) = self.find_fork().await? {
+            self.roll_back(fork_height).await?;
+        }
+        if self.config.zcash.watch_mempool {
+            if let Err(error) = self.watch_mempool().await {
+                warn!(error = %format!("{error:#}"), "watching the mempool failed");
+            }
+        }
+
+        let Scan { blocks, mut notes, spends } = self.source.confirmed_notes(self.config.zcash.confirmations).await?;
+        if let Some(note) = notes.first().filter(|_| chaos::strikes(Fault::DuplicateEvent)).cloned() {
+            notes.push(note);
+        }
+        self.store.insert_blocks(&blocks).await?;
+        for note in &notes {
+            self.store.insert_note(note).await?;
+        }
+        for spend in &spends {
+            if self.store.mark_spent(spend).await? {
+                info!(pool = ?spend.pool, txid = %spend.txid, height = spend.height, "bridge note spent");
+            }
+        }
+        // Checkpoint only once the scan is stored, so a crash rescans rather than skips
+        if let Some((height, hash)) = blocks.last() {
+            let trees = self.source.tree_state(*height).await?;
+            self.store.save_checkpoint(*height, hash, &trees).await?;
+        }
+        // Change and merged notes come back from the bridge's own transactions; they aren't deposits
+        let own = self.store.own_transactions(&spends).await?;
+        notes.retain(|note| !own.contains(&note.txid));
+        notes.append(&mut self.failed);
+        if self.halted {
+            // Keep the deposits for after the reorg has been investigated
+            self.failed = notes;
+            return Ok(blocks.len());
+        }
+
+        let mut deposits = std::mem::take(&mut self.pending);
+        for notes in deposit::by_transaction(notes) {
+            let txid = notes[0].txid.clone();
+            if self.handled.contains(&txid) {
+                continue;
+            }
+
+            let attributed = match attribute(&self.store, self.config.zcash.min_deposit, &notes).await {
+                Ok(attributed) => attributed,
+                Err(error) => {
+                    error!(%txid, error = %format!("{error:#}"), "deposit address lookup failed");
+                    self.failed.extend(notes);
+                    continue;
+                }
+            };
+            match attributed {
+                Ok(deposit) => {
+                    self.metrics.deposits_seen.inc();
+                    self.feed.publish(DepositEvent::Confirmed {
+                        txid: deposit.txid.clone(),
+                        amount: deposit.amount,
+                        recipient: deposit.recipient.to_string(),
+                        confirmations: deposit.confirmations,
+                    });
+                    deposits.push(deposit);
+                }
+                Err(reason) => {
+                    let amount: u64 = notes.iter().map(|note| note.amount_zat).sum();
+                    warn!(%txid, amount, outputs = notes.len(), %reason, "quarantining deposit");
+                    match self.store.quarantine(&notes, &reason).await {
+                        Ok(()) => {
+                            self.metrics.deposits_quarantined.inc();
+                            self.feed.publish(DepositEvent::Quarantined {
+                                txid: txid.clone(),
+                                amount,
+                                reason,
+                            });
+                            self.handled.insert(txid);
+                        }
+                        Err(error) => {
+                            error!(%txid, error = %format!("{error:#}"), "quarantine failed");
+                            self.failed.extend(notes);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Released by an operator since, or left unminted by an earlier run
+        for deposit in self.store.attributed_deposits().await? {
+            self.handled.remove(&deposit.txid);
+            deposits.push(deposit);
+        }
+        let mut attempted = HashSet::new();
+        deposits.retain(|deposit| !self.handled.contains(&deposit.txid) && attempted.insert(deposit.txid.clone()));
+        for (deposit, minted) in self.mint_all(deposits).await {
+            match minted {
+                Ok(true) => {
+                    self.resolve_alert(&format!("deposit_stuck:{}", deposit.txid), "deposit minted").await;
+                    self.handled.insert(deposit.txid);
+                }
+                // Left for the minting relayer; checked again next poll
+                Ok(false) => self.pending.push(deposit),
+                Err(error) => {
+                    error!(txid = %deposit.txid, error = %format!("{error:#}"), "mint failed");
+                    self.metrics.errors.with_label_values(&["mint"]).inc();
+                    self.pending.push(deposit);
+                }
+            }
+        }
+        if self.wallet.is_some() {
+            let withdrawals = self.pending_withdrawals().await?;
+            self.metrics.pending_withdrawals.set(withdrawals.len() as i64);
+            self.pay_out(withdrawals).await?;
+        }
+        if let Some(refunds) = &self.refunds {
+            if let Err(error) = refunds.settle_all(&self.store).await {
+                error!(error = %format!("{error:#}"), "settling refunds failed");
+                self.metrics.errors.with_label_values(&["refund"]).inc();
+            }
+        }
+        if let Err(error) = self.consolidate().await {
+            error!(error = %format!("{error:#}"), "note consolidation failed");
+            self.metrics.errors.with_label_values(&["consolidate"]).inc();
+        }
+        if let Err(error) = self.sweep().await {
+            error!(error = %format!("{error:#}"), "reserve sweep failed");
+            self.metrics.errors.with_label_values(&["sweep"]).inc();
+        }
+        if let Err(error) = self.check_alerts().await {
+            warn!(error = %format!("{error:#}"), "checking alert conditions failed");
+        }
+        Ok(blocks.len())
+    }
+
+    /// Look for deposits in the mempool, so front-ends can show them as seen
+    /// with 0 confirmations long before they are minted
+    ///
+    /// Only transactions new since the last look are decrypted. A seen
+    /// deposit is reported until scanning records it at the confirmation
+    /// depth, or until it expired without confirming.
+    async fn watch_mempool(&mut self) -> Result<()> {
+        let Some((tip, _)) = self.tip.clone() else {
+            return Ok(());
+        };
+        let scan = self.source.mempool(&self.mempool, tip + 1).await?;
+        for (txid, scanned) in scan.transactions {
+            if self.handled.contains(&txid) || self.feed.get(&txid).is_some() {
+                continue;
+            }
+            // Change and merged notes come back from the bridge's own transactions; they aren't deposits
+            if self.store.spends_ours(&scanned.nullifiers).await? {
+                continue;
+            }
+            // Attributed like the scan does: all its outputs, when they name one recipient
+            if scanned.outputs.is_empty() {
+                continue;
+            }
+            let mut recipients = HashSet::new();
+            for output in &scanned.outputs {
+                recipients.insert(match self.store.deposit_recipient(&output.diversifier_index).await? {
+                    Some(assigned) => Some(assigned),
+                    None => memo::recipient(&output.memo).ok(),
+                });
+            }
+            let recipient = match recipients.len() {
+                1 => recipients.into_iter().next().flatten(),
+                _ => None,
+            };
+            let amount: u64 = scanned.outputs.iter().map(DecryptedOutput::value).sum();
+            let expiry_height = match scanned.expiry_height {
+                0 => tip + MEMPOOL_EXPIRY_DELTA,
+                expiry_height => expiry_height,
+            };
+            info!(%txid, amount, recipient = ?recipient, "deposit seen in the mempool");
+            self.feed.see(SeenDeposit {
+                txid,
+                amount,
+                recipient: recipient.map(|recipient| recipient.to_string()),
+                first_seen: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+                in_mempool: true,
+                forget_height: expiry_height + self.config.zcash.confirmations,
+            });
+        }
+        self.feed.refresh(&scan.txids, tip);
+        self.mempool = scan.txids;
+        Ok(())
+    }
+
+    /// Note the chain tip, logging reorgs that stay above the confirmation depth
+    ///
+    /// Those only orphan blocks the relayer hasn't scanned yet, so deposits in
+    /// them are simply picked up from the new chain once confirmed.
+    async fn track_tip(&mut self) -> Result<()> {
+        let height = self.source.tip().await?;
+        if let Some((previous, hash)) = &self.tip {
+            if *previous <= height && self.source.block_hash(*previous).await? != *hash {
+                warn!(height = previous, "chain tip reorganized; unconfirmed deposits will be rescanned");
+            }
+        }
+        let hash = self.source.block_hash(height).await?;
+        self.tip = Some((height, hash));
+        Ok(())
+    }
+
+    /// Highest scanned block still on the best chain, if a reorg has orphaned
+    /// any block above it
+    async fn find_fork(&mut self) -> Result<Option<u32>> {
+        let Some((mut height, hash)) = self.store.latest_block().await? else {
+            return Ok(None);
+        };
+        if let Some(depth) = chaos::reorg_depth() {
+            return Ok(Some(height.saturating_sub(depth)));
+        }
+        if self.source.block_hash(height).await? == hash {
+            return Ok(None);
+        }
+
+        // Below the first scanned block there is nothing left to compare
+        while height > 0 {
+            height -= 1;
+            let Some(hash) = self.store.block_hash(height).await? else {
+                break;
+            };
+            if self.source.block_hash(height).await? == hash {
+                break;
+            }
+        }
+        Ok(Some(height))
+    }
+
+    /// Recover from a reorg that orphaned scanned blocks above `fork_height`
+    ///
+    /// Scanned blocks already had the required confirmations, so this reorg is
+    /// deeper than the confirmation depth and deposits minted from orphaned
+    /// blocks may no longer exist on Zcash. The relayer forgets what it scanned
+    /// above the fork, rescans from there and, unless disabled, pauses mints
+    /// with its guardian key until an operator has reviewed the reorg.
+    async fn roll_back(&mut self, fork_height: u32) -> Result<()> {
+        error!(
+            fork_height,
+            confirmations = self.config.zcash.confirmations,
+            "reorg deeper than the confirmation depth orphaned scanned blocks"
+        );
+        self.metrics.deep_reorgs.inc();
+        self.alert(
+            &format!("deep_reorg:{fork_height}"),
+            Severity::Critical,
+            &format!(
+                "Zcash reorg deeper than {} confirmations orphaned scanned blocks above {fork_height}",
+                self.config.zcash.confirmations
+            ),
+        )
+        .await;
+
+        self.store.roll_back(fork_height).await?;
+        self.source.rewind(fork_height + 1);
+        self.failed.retain(|note| note.height <= fork_height);
+        self.pending.retain(|deposit| deposit.block_height <= fork_height);
+        // Orphaned deposits may come back in other blocks; receipts still stop double mints
+        self.handled.clear();
+
+        if self.config.zcash.pause_on_deep_reorg && !self.halted {
+            self.halted = true;
+            match retry::with_backoff(&self.config.retry, "guardian_pause", |attempt| self.minter.guardian_pause(attempt)).await {
+                Ok(signature) => {
+                    error!(%signature, "paused mints after deep reorg");
+                    self.record("guardian_pause", json!({ "signature": signature.to_string(), "fork_height": fork_height }))
+                        .await?;
+                }
+                Err(error) => error!(
+                    error = %format!("{error:#}"),
+                    "failed to pause mints after deep reorg; pause the bridge manually"
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Settle the submissions a previous run sent but never saw confirmed
+    ///
+    /// A dry run leaves them for the next real run.
+    async fn reconcile_submissions(&self) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+        for (id, signature, transaction) in self.store.unsettled_submissions().await? {
+            let mut status = self.minter.signature_status(&signature).await?;
+            // A transaction on the durable nonce outlives the run that sent it
+            if let (None, Some(transaction)) = (&status, transaction) {
+                if let Some(submission) = self.minter.revive(transaction).await? {
+                    info!(id, %signature, "sending a submission from a previous run again");
+                    status = Some(self.minter.send(&submission).await.map(|_| ()).map_err(|error| format!("{error:#}")));
+                }
+            }
+            match status {
+                Some(Ok(())) => {
+                    self.store.finish_submission(&signature, "confirmed", None).await?;
+                    if id.starts_with("mint:") {
+                        self.store.mark_minted(&id, Some(&signature)).await?;
+                    } else if id.starts_with("payout:") {
+                        self.store.mark_finalized(&id, Some(&signature)).await?;
+                    }
+                    info!(id, %signature, "submission from a previous run confirmed");
+                }
+                Some(Err(error)) => {
+                    self.store.finish_submission(&signature, "failed", Some(&error)).await?;
+                }
+                None => {
+                    self.store.finish_submission(&signature, "expired", None).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Mint `deposits` through the attest, commit and confirm stages,
+    /// returning each with whether it is minted, in the order given
+    ///
+    /// Up to `attest_concurrency` deposits are checked, attested and built at
+    /// once, without writing anything. The commit stage then takes them one
+    /// at a time in the order given: it records each deposit and its signed
+    /// mint, and sends it. Up to `submit_concurrency` sent mints wait to
+    /// confirm at once, and the database learns they are minted in the same
+    /// order. Between attesting and committing sits a queue of at most
+    /// `queue_depth` deposits: when Solana slows down, attesting waits for
+    /// room rather than piling up deposits.
+    async fn mint_all(&self, deposits: Vec<Deposit>) -> Vec<(Deposit, Result<bool>)> {
+        mint_pipeline(
+            &self.config.pipeline,
+            deposits,
+            |deposit| async move {
+                let minting = self.attest_deposit(&deposit).await;
+                (deposit, minting)
+            },
+            |deposit, minting| async move {
+                let minting = match minting {
+                    Ok(minting) => self.commit_deposit(&deposit, minting).await,
+                    Err(error) => Err(error),
+                };
+                (deposit, minting)
+            },
+            |deposit, minting| async move {
+                let minting = match minting {
+                    Ok(minting) => self.confirm_deposit(&deposit, minting).await,
+                    Err(error) => Err(error),
+                };
+                (deposit, minting)
+            },
+            |deposit, minting| async move {
+                let outcome = self.finish_deposit(&deposit, minting).await;
+                (deposit, outcome)
+            },
+        )
+        .await
+    }
+
+    /// Settle `deposit` if the database or a receipt shows it is already
+    /// minted, or if an `observe_only` relayer leaves it to the minting relayer;
+    /// otherwise gather the other guardians' attestations for it and build
+    /// its mint
+    ///
+    /// Writes nothing, so deposits can be attested in any order.
+    #[instrument(
+        name = "deposit",
+        skip_all,
+        fields(transfer = %deposit.idempotency_id(), amount = deposit.amount, recipient = %deposit.recipient)
+    )]
+    async fn attest_deposit(&self, deposit: &Deposit) -> Result<Minting> {
+        if self.store.is_minted(deposit).await? {
+            return Ok(Minting::Settled(true));
+        }
+        if self.minter.is_minted(deposit).await? {
+            return Ok(Minting::Receipt);
+        }
+        if self.config.attestation.as_ref().is_some_and(|attestation| attestation.observe_only) {
+            return Ok(Minting::Settled(false));
+        }
+        let attestations = match &self.aggregator {
+            Some(aggregator) => {
+                retry::with_backoff(&self.config.retry, "attest", |_| async move {
+                    let message = self.minter.attestation_message(deposit).await?;
+                    let guardians = self.minter.guardian_set().await?;
+                    aggregator
+                        .gather(deposit, &message, &guardians, &self.minter.relayer())
+                        .await
+                })
+                .await?
+            }
+            None => Vec::new(),
+        };
+        let submission = self.minter.mint_transaction(deposit, &attestations, 1).await?;
+        Ok(Minting::Built(attestations, submission))
+    }
+
+    /// Record `deposit` and, if it was built, record and send its first mint
+    /// attempt
+    ///
+    /// Deposits come through here one at a time in scan order, so the
+    /// database and the cluster see them in the same order on every run. A
+    /// mint built on a blockhash that has since expired, or on a durable
+    /// nonce an earlier mint advanced, is built again first. A dry run records
+    /// the deposit as pending but nothing of its simulated mint, so a later
+    /// real run still mints it.
+    #[instrument(name = "mint", skip_all, fields(transfer = %deposit.idempotency_id()))]
+    async fn commit_deposit(&self, deposit: &Deposit, minting: Minting) -> Result<Minting> {
+        self.store.record_deposit(deposit).await?;
+        match minting {
+            Minting::Receipt => {
+                info!(txid = %deposit.txid, "deposit receipt already on chain");
+                if !self.config.dry_run {
+                    self.store.mark_minted(&deposit.idempotency_id(), None).await?;
+                }
+                Ok(Minting::Settled(true))
+            }
+            Minting::Built(attestations, submission) => {
+                let submission = if self.minter.can_land(&submission).await? {
+                    submission
+                } else {
+                    self.minter.mint_transaction(deposit, &attestations, 1).await?
+                };
+                self.record_mint(deposit, &submission, 1).await?;
+                let sent = self.minter.broadcast(&submission).await;
+                Ok(Minting::Sent(attestations, submission, sent))
+            }
+            minting => Ok(minting),
+        }
+    }
+
+    /// Wait for the mint `commit_deposit` sent to confirm, retrying with
+    /// fresh attempts if it failed to send or expired
+    #[instrument(name = "mint", skip_all, fields(transfer = %deposit.idempotency_id()))]
+    async fn confirm_deposit(&self, deposit: &Deposit, minting: Minting) -> Result<Minting> {
+        let (attestations, submission, sent) = match minting {
+            Minting::Sent(attestations, submission, sent) => (attestations, submission, sent),
+            minting => return Ok(minting),
+        };
+        let first = submission.transaction.signatures[0];
+        let confirmed = match sent {
+            Ok(signature) => self.minter.confirm(&submission, signature).await,
+            Err(error) => Err(error),
+        };
+        let signature = match confirmed {
+            Ok(signature) => {
+                self.finish_submission(&signature, "confirmed", None).await?;
+                signature
+            }
+            Err(error) => {
+                self.finish_submission(&first, "failed", Some(&format!("{error:#}"))).await?;
+                warn!(txid = %deposit.txid, error = %format!("{error:#}"), "first mint attempt failed; retrying");
+                // The first attempt was the one committed in order
+                retry::with_backoff(&self.config.retry, "mint_wzec", |attempt| {
+                    self.submit_mint(deposit, &attestations, attempt + 1)
+                })
+                .await?
+            }
+        };
+        Ok(Minting::Confirmed(signature))
+    }
+
+    /// Mark a confirmed `deposit` minted, returning whether it is minted
+    ///
+    /// Deposits come through here in scan order, however their mints raced
+    /// to confirm.
+    async fn finish_deposit(&self, deposit: &Deposit, minting: Result<Minting>) -> Result<bool> {
+        match minting? {
+            Minting::Settled(minted) => Ok(minted),
+            // Only this run remembers it, so a real run on the same database mints it
+            Minting::Confirmed(signature) if self.config.dry_run => {
+                info!(
+                    txid = %deposit.txid,
+                    amount = deposit.amount,
+                    recipient = %deposit.recipient,
+                    %signature,
+                    "dry run: mint simulated"
+                );
+                Ok(true)
+            }
+            Minting::Confirmed(signature) => {
+                self.store.mark_minted(&deposit.idempotency_id(), Some(&signature)).await?;
+                self.metrics.deposits_minted.inc();
+                info!(
+                    txid = %deposit.txid,
+                    amount = deposit.amount,
+                    recipient = %deposit.recipient,
+                    confirmations = deposit.confirmations,
+                    %signature,
+                    "minted deposit"
+                );
+                self.feed.publish(DepositEvent::Minted {
+                    txid: deposit.txid.clone(),
+                    amount: deposit.amount,
+                    recipient: deposit.recipient.to_string(),
+                    signature: signature.to_string(),
+                });
+                Ok(true)
+            }
+            _ => bail!("deposit {} left the pipeline before its mint confirmed", deposit.txid),
+        }
+    }
+
+    /// Record one signed mint attempt before it is sent, so a crash while
+    /// the transaction is in flight leaves something to reconcile on restart
+    async fn record_mint(&self, deposit: &Deposit, submission: &Submission, attempt: u32) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+        let id = deposit.idempotency_id();
+        self.store.record_submission(&id, &submission.transaction).await?;
+        self.store.record_attestation(&id, &self.minter.relayer()).await?;
+        self.record(
+            "mint_submitted",
+            json!({
+                "txid": deposit.txid,
+                "recipient": deposit.recipient.to_string(),
+                "amount": deposit.amount,
+                "signature": submission.transaction.signatures[0].to_string(),
+                "attempt": attempt,
+            }),
+        )
+        .await
+    }
+
+    /// Settle a recorded submission; a dry run records none
+    async fn finish_submission(&self, signature: &Signature, status: &str, error: Option<&str>) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+        self.store.finish_submission(signature, status, error).await
+    }
+
+    /// Sign, record and send one retried mint attempt
+    #[instrument(name = "mint_wzec", skip(self, deposit, attestations))]
+    async fn submit_mint(&self, deposit: &Deposit, attestations: &[(Pubkey, [u8; 64])], attempt: u32) -> Result<Signature> {
+        let submission = self.minter.mint_transaction(deposit, attestations, attempt).await?;
+        let signature = submission.transaction.signatures[0];
+        self.record_mint(deposit, &submission, attempt).await?;
+
+        match self.minter.send(&submission).await {
+            Ok(signature) => {
+                self.finish_submission(&signature, "confirmed", None).await?;
+                Ok(signature)
+            }
+            Err(error) => {
+                self.finish_submission(&signature, "failed", Some(&format!("{error:#}"))).await?;
+                Err(error.context(format!("minting deposit {}", deposit.txid)))
+            }
+        }
+    }
+
+    /// Watch every pending withdrawal, as a starting point for the listener
+    /// to add to
+    async fn watch_pending(&mut self) -> Result<()> {
+        self.watched = self
+            .minter
+            .pending_withdrawals()
+            .await?
+            .iter()
+            .map(|withdrawal| withdrawal.nonce)
+            .collect();
+        Ok(())
+    }
+
+    /// Pending withdrawals of this bridge, oldest first
+    ///
+    /// With the withdrawal listener running, only the watched requests are
+    /// fetched rather than every program account. A request stops being
+    /// watched once it's no longer pending; an unveto brings it back through
+    /// the listener.
+    async fn pending_withdrawals(&mut self) -> Result<Vec<Withdrawal>> {
+        let Some(receiver) = &mut self.withdrawals else {
+            return self.minter.pending_withdrawals().await;
+        };
+        while let Ok(nonce) = receiver.try_recv() {
+            self.watched.insert(nonce);
+        }
+        let mut withdrawals = Vec::new();
+        for nonce in self.watched.clone() {
+            match self.minter.withdrawal(nonce).await? {
+                Some((WithdrawalStatus::Pending, withdrawal)) => withdrawals.push(withdrawal),
+                _ => {
+                    self.watched.remove(&nonce);
+                }
+            }
+        }
+        Ok(withdrawals)
+    }
+
+    /// Pay out every pending withdrawal whose timelock has passed, and finalize
+    /// the payouts that have confirmed
+    async fn pay_out(&self, withdrawals: Vec<Withdrawal>) -> Result<()> {
+        let (Some(wallet), Some(payouts)) = (&self.wallet, &self.config.payouts) else {
+            return Ok(());
+        };
+        let slot = self.minter.slot().await?;
+        let mut batch = Vec::new();
+        let mut operations = HashMap::new();
+        for withdrawal in withdrawals {
+            // Paying before the timelock ends would pre-empt a guardian veto
+            if slot < withdrawal.unlock_slot {
+                continue;
+            }
+            let id = withdrawal.idempotency_id();
+            let payout = match self.store.payout(&id).await? {
+                Some(payout) => payout,
+                None if self.config.dry_run => self.rehearsed_payout(&withdrawal, zip317::MINIMUM_FEE)?,
+                None => {
+                    self.record_payout(&withdrawal, zip317::MINIMUM_FEE).await?;
+                    self.store.payout(&id).await?.context("payout was not recorded")?
+                }
+            };
+            if payout.status == "pending" {
+                batch.push((withdrawal, payout));
+            } else if self.config.dry_run {
+                // Payouts a real run started are left for it to finish
+                if self.first_rehearsal(format!("payout:{id}:{}", payout.status)) {
+                    info!(nonce = withdrawal.nonce, status = %payout.status, "dry run: leaving payout as it is");
+                }
+            } else if let Err(error) = self.advance_payout(wallet, payouts, &withdrawal, payout, &mut operations).await {
+                error!(nonce = withdrawal.nonce, error = %format!("{error:#}"), "payout failed");
+                self.metrics.errors.with_label_values(&["payout"]).inc();
+                self.alert(
+                    &format!("payout_failed:{}", withdrawal.nonce),
+                    Severity::Warning,
+                    &format!("payout of withdrawal {} failed: {error:#}", withdrawal.nonce),
+                )
+                .await;
+            }
+        }
+        if let Err(error) = self.send_batch(wallet, payouts, batch).await {
+            error!(error = %format!("{error:#}"), "sending payouts failed");
+            self.metrics.errors.with_label_values(&["payout"]).inc();
+            self.alert("payout_batch_failed", Severity::Warning, &format!("sending payouts failed: {error:#}"))
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Merge the deposit address's small Sapling notes into one while the
+    /// bridge is quiet, so that busy periods don't leave payouts spending
+    /// dozens of notes
+    ///
+    /// The bridge is quiet when no payout is open and no note has arrived in
+    /// the last `quiet_blocks` scanned blocks, so a merge never competes with
+    /// a payout for notes. Only one merge is in flight at a time; it is done
+    /// once the scanner sees it spend bridge notes, or once scanning passes
+    /// its expiry height without seeing it.
+    #[instrument(skip_all)]
+    async fn consolidate(&mut self) -> Result<()> {
+        let (Some(wallet), Some(consolidation)) = (&self.wallet, &self.config.consolidation) else {
+            return Ok(());
+        };
+        let Some((scanned_height, _)) = self.store.latest_block().await? else {
+            return Ok(());
+        };
+
+        if let Some(merge) = self.consolidating.take() {
+            self.consolidating = self.settle(wallet, merge, "note merge", scanned_height).await?;
+            return Ok(());
+        }
+        if self.sweeping.is_some() || !self.store.open_payouts().await?.is_empty() {
+            return Ok(());
+        }
+        if let Some(height) = self.store.latest_note_height().await? {
+            if scanned_height < height + consolidation.quiet_blocks {
+                return Ok(());
+            }
+        }
+        let notes = self
+            .store
+            .unspent_notes()
+            .await?
+            .into_iter()
+            .filter(|&(pool, _)| pool == Pool::Sapling)
+            .count();
+        if notes < consolidation.min_notes {
+            return Ok(());
+        }
+
+        // zcashd merges transparent and Sapling funds only, so merge into the Sapling receiver
+        let network = self.config.zcash.network.address();
+        let address = zcash_addr::Address::parse_for(&self.config.zcash.deposit_address, network)?
+            .receivers()
+            .iter()
+            .find(|receiver| matches!(receiver, zcash_addr::Receiver::Sapling(_)))
+            .context("deposit address has no Sapling receiver to merge")?
+            .to_address(network)
+            .encode();
+        let limit = notes.min(consolidation.max_notes);
+        let shape = zip317::TxShape {
+            sapling_spends: limit as u64,
+            sapling_outputs: 2,
+            ..Default::default()
+        };
+        let payouts = self.config.payouts.as_ref().context("missing [payouts] section")?;
+        let fee = shape.conventional_fee() + payouts.extra_fee_actions * zip317::MARGINAL_FEE;
+        if self.config.dry_run {
+            if self.first_rehearsal(format!("merge:{limit}")) {
+                info!(notes, limit, fee, "dry run: would merge notes");
+            }
+            return Ok(());
+        }
+        let (operation_id, merging) = wallet.merge(&address, limit, fee).await?;
+        info!(notes, merging, fee, %operation_id, "merging notes");
+        self.consolidating = Some(WalletTransaction {
+            operation_id,
+            txid: None,
+            expiry_height: 0,
+        });
+        Ok(())
+    }
+
+    /// Keep the hot deposit address between `hot_min` and `hot_max`: sweep
+    /// the excess over `hot_max` to the cold reserve, and alert when the
+    /// balance drops below `hot_min`
+    ///
+    /// A sweep leaves the hot balance halfway between the two, so the next
+    /// few deposits don't each trigger another. Like a merge, it waits until
+    /// no payout is open and only one is in flight at a time.
+    #[instrument(skip_all)]
+    async fn sweep(&mut self) -> Result<()> {
+        let (Some(wallet), Some(reserve)) = (&self.wallet, &self.config.reserve) else {
+            return Ok(());
+        };
+        let balance = self.store.unspent_balance().await?;
+        let hot_low = balance < reserve.hot_min;
+        if hot_low && !self.hot_low {
+            error!(
+                hot_balance = balance,
+                hot_min = reserve.hot_min,
+                "hot balance below hot_min; top it up from the cold reserve"
+            );
+            if reserve.pause_withdrawals {
+                match retry::with_backoff(&self.config.retry, "pause_burns", |attempt| self.minter.pause_burns(attempt)).await {
+                    Ok(Some(signature)) => {
+                        warn!(%signature, "burns paused until the hot balance is topped up");
+                        self.record("pause_burns", json!({ "signature": signature.to_string(), "hot_balance": balance }))
+                            .await?;
+                    }
+                    Ok(None) => info!("burns were already paused"),
+                    Err(error) => error!(error = %format!("{error:#}"), "pausing burns failed"),
+                }
+            }
+        } else if !hot_low && self.hot_low {
+            info!(hot_balance = balance, hot_min = reserve.hot_min, "hot balance back above hot_min");
+        }
+        self.hot_low = hot_low;
+
+        let Some((scanned_height, _)) = self.store.latest_block().await? else {
+            return Ok(());
+        };
+        if let Some(sweep) = self.sweeping.take() {
+            self.sweeping = self.settle(wallet, sweep, "reserve sweep", scanned_height).await?;
+            return Ok(());
+        }
+        if balance <= reserve.hot_max || self.consolidating.is_some() || !self.store.open_payouts().await?.is_empty() {
+            return Ok(());
+        }
+
+        let payouts = self.config.payouts.as_ref().context("missing [payouts] section")?;
+        let receiver = zcash_addr::Address::parse(&reserve.cold_address)?
+            .preferred_receiver()
+            .cloned()
+            .context("reserve.cold_address has no receiver")?;
+        let excess = balance - (reserve.hot_min + reserve.hot_max) / 2;
+        let notes = self.store.unspent_notes().await?;
+        let fee = zip317::payout_shape(&notes, excess, &[receiver]).conventional_fee()
+            + payouts.extra_fee_actions * zip317::MARGINAL_FEE;
+        if excess <= fee {
+            return Ok(());
+        }
+        let amount = excess - fee;
+        let recipient = Recipient {
+            address: &reserve.cold_address,
+            amount,
+            memo: None,
+        };
+        let reveal_recipients = !zcash_addr::Address::parse(&reserve.cold_address)?.can_receive_memo();
+        if self.config.dry_run {
+            if self.first_rehearsal(format!("sweep:{amount}")) {
+                info!(hot_balance = balance, amount, fee, "dry run: would sweep to the cold reserve");
+            }
+            return Ok(());
+        }
+        let operation_id = wallet
+            .send(&self.config.zcash.deposit_address, &[recipient], fee, reveal_recipients)
+            .await?;
+        info!(hot_balance = balance, amount, fee, %operation_id, "sweeping to the cold reserve");
+        self.record(
+            "sweep_sent",
+            json!({
+                "operation_id": operation_id,
+                "cold_address": reserve.cold_address,
+                "amount": amount,
+                "fee": fee,
+            }),
+        )
+        .await?;
+        self.sweeping = Some(WalletTransaction {
+            operation_id,
+            txid: None,
+            expiry_height: 0,
+        });
+        Ok(())
+    }
+
+    /// Follow a maintenance transaction the wallet is sending, returning it
+    /// while it is still in flight
+    ///
+    /// It is done once the scanner sees it spend bridge notes, or once
+    /// scanning passes its expiry height without seeing it.
+    async fn settle(
+        &self,
+        wallet: &Wallet,
+        mut transaction: WalletTransaction,
+        what: &str,
+        scanned_height: u32,
+    ) -> Result<Option<WalletTransaction>> {
+        let txid = match transaction.txid.clone() {
+            Some(txid) => txid,
+            None => match wallet.operation(&transaction.operation_id).await? {
+                Operation::Running => return Ok(Some(transaction)),
+                Operation::Succeeded(txid) => {
+                    transaction.expiry_height = wallet.expiry_height(&txid).await?;
+                    info!(zcash_txid = %txid, expiry_height = transaction.expiry_height, "{what} sent");
+                    transaction.txid = Some(txid.clone());
+                    txid
+                }
+                Operation::Failed(reason) => {
+                    warn!(operation_id = %transaction.operation_id, %reason, "{what} failed");
+                    return Ok(None);
+                }
+                Operation::Unknown => {
+                    warn!(operation_id = %transaction.operation_id, "wallet has no record of the {what}");
+                    return Ok(None);
+                }
+            },
+        };
+        if let Some(height) = self.store.spend_height(&txid).await? {
+            info!(zcash_txid = %txid, height, "{what} confirmed");
+            Ok(None)
+        } else if transaction.expiry_height > 0 && scanned_height >= transaction.expiry_height {
+            warn!(zcash_txid = %txid, expiry_height = transaction.expiry_height, "{what} expired unmined");
+            Ok(None)
+        } else {
+            Ok(Some(transaction))
+        }
+    }
+
+    /// Hand pending payouts to the wallet as one transaction, once the oldest
+    /// has waited out the batching window or the batch is full
+    ///
+    /// The batch pays the ZIP-317 conventional fee of the transaction the
+    /// wallet is expected to build, split evenly between its withdrawals.
+    /// Withdrawals too small to cover their share wait for a smaller batch.
+    /// Payouts are marked `sending` before the wallet is called, so if the
+    /// relayer dies before the wallet answers, they are left for an operator
+    /// rather than sent twice.
+    #[instrument(name = "payout_batch", skip_all, fields(withdrawals = batch.len()))]
+    async fn send_batch(
+        &self,
+        wallet: &Wallet,
+        payouts: &PayoutConfig,
+        batch: Vec<(Withdrawal, Payout)>,
+    ) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let batch = due_batch(batch, payouts.max_batch_size, payouts.batch_window_secs, now);
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let notes = self.store.unspent_notes().await?;
+        let Some((batch, fee_share)) = share_fee(batch, |batch| self.payout_fee(payouts, &notes, batch))? else {
+            return Ok(());
+        };
+        let mut recipients = Vec::with_capacity(batch.len());
+        let mut reveal_recipients = false;
+        for (withdrawal, _) in &batch {
+            let shielded = zcash_addr::Address::parse(&withdrawal.zec_address)?.can_receive_memo();
+            reveal_recipients |= !shielded;
+            recipients.push(Recipient {
+                address: &withdrawal.zec_address,
+                amount: withdrawal.net_amount - fee_share,
+                memo: (shielded && !withdrawal.memo.is_empty()).then_some(withdrawal.memo.as_slice()),
+            });
+        }
+        let fee = fee_share * batch.len() as u64;
+        if self.config.dry_run {
+            let nonces: Vec<u64> = batch.iter().map(|(withdrawal, _)| withdrawal.nonce).collect();
+            if self.first_rehearsal(format!("payouts:{nonces:?}")) {
+                for ((withdrawal, _), recipient) in batch.iter().zip(&recipients) {
+                    info!(
+                        nonce = withdrawal.nonce,
+                        zec_address = %withdrawal.zec_address,
+                        amount = recipient.amount,
+                        fee_share,
+                        "dry run: would pay out withdrawal"
+                    );
+                }
+                info!(withdrawals = batch.len(), ?nonces, fee, "dry run: would hand payouts to the wallet");
+            }
+            return Ok(());
+        }
+        for ((withdrawal, _), recipient) in batch.iter().zip(&recipients) {
+            self.store
+                .start_payout(&withdrawal.idempotency_id(), recipient.amount, fee_share)
+                .await?;
+        }
+
+        let operation_id = match wallet
+            .send(&self.config.zcash.deposit_address, &recipients, fee, reveal_recipients)
+            .await
+        {
+            Ok(operation_id) => operation_id,
+            Err(error) => {
+                for (withdrawal, _) in &batch {
+                    self.store
+                        .update_payout(&withdrawal.idempotency_id(), "pending", None, None, None)
+                        .await?;
+                }
+                return Err(error.context(format!("sending {} payouts", batch.len())));
+            }
+        };
+        for (withdrawal, _) in &batch {
+            self.store
+                .update_payout(&withdrawal.idempotency_id(), "sending", Some(&operation_id), None, None)
+                .await?;
+        }
+        info!(
+            withdrawals = batch.len(),
+            nonces = ?batch.iter().map(|(withdrawal, _)| withdrawal.nonce).collect::<Vec<_>>(),
+            fee,
+            %operation_id,
+            "payouts handed to the wallet"
+        );
+        let payouts: Vec<Value> = batch
+            .iter()
+            .zip(&recipients)
+            .map(|((withdrawal, _), recipient)| {
+                json!({
+                    "nonce": withdrawal.nonce,
+                    "zec_address": withdrawal.zec_address,
+                    "amount": recipient.amount,
+                })
+            })
+            .collect();
+        self.record("payout_sent", json!({ "operation_id": operation_id, "fee": fee, "payouts": payouts }))
+            .await?;
+        Ok(())
+    }
+
+    /// Take a payout already handed to the wallet as far as it can get this
+    /// poll: wait for the wallet to broadcast it, wait for it to confirm, then
+    /// finalize the withdrawal
+    ///
+    /// `operations` remembers what the wallet said about each operation this
+    /// poll, since a batch shares one.
+    #[instrument(
+        name = "withdrawal",
+        skip_all,
+        fields(transfer = %withdrawal.idempotency_id(), status = %payout.status)
+    )]
+    async fn advance_payout(
+        &self,
+        wallet: &Wallet,
+        payouts: &PayoutConfig,
+        withdrawal: &Withdrawal,
+        mut payout: Payout,
+        operations: &mut HashMap<String, Operation>,
+    ) -> Result<()> {
+        let id = withdrawal.idempotency_id();
+        if payout.status == "sending" {
+            let Some(operation_id) = payout.operation_id.clone() else {
+                bail!("payout {id} may have reached the wallet unrecorded; check the wallet and update it by hand");
+            };
+            let operation = match operations.get(&operation_id) {
+                Some(operation) => operation.clone(),
+                None => {
+                    let timeout = Duration::from_secs(payouts.operation_timeout_secs);
+                    let operation = wallet.wait(&operation_id, timeout).await?;
+                    operations.insert(operation_id.clone(), operation.clone());
+                    operation
+                }
+            };
+            match operation {
+                Operation::Running => return Ok(()),
+                Operation::Succeeded(txid) => {
+                    self.store.update_payout(&id, "sent", None, Some(&txid), None).await?;
+                    self.metrics.payouts_sent.inc();
+                    info!(
+                        nonce = withdrawal.nonce,
+                        zcash_txid = %txid,
+                        amount = payout.amount,
+                        zec_address = %withdrawal.zec_address,
+                        "payout sent"
+                    );
+                    payout.status = "sent".to_string();
+                    payout.zcash_txid = Some(txid);
+                }
+                Operation::Failed(reason) => {
+                    self.store.update_payout(&id, "pending", None, None, None).await?;
+                    bail!("wallet operation {operation_id} failed: {reason}");
+                }
+                Operation::Unknown => bail!(
+                    "wallet has no record of operation {operation_id}; check whether withdrawal {} was paid and update payout {id} by hand",
+                    withdrawal.nonce
+                ),
+            }
+        }
+
+        if payout.status == "sent" {
+            let txid = payout.zcash_txid.clone().context("sent payout has no txid")?;
+            if payout.expiry_height.is_none() {
+                let expiry_height = wallet.expiry_height(&txid).await?;
+                self.store.set_expiry_height(&id, expiry_height).await?;
+                payout.expiry_height = Some(expiry_height);
+            }
+            if payout.output_index.is_none() {
+                payout.output_index = self.find_output(wallet, withdrawal, &payout, &txid).await?;
+            }
+            // The payout spends bridge notes, so the scanner sees it once it is as deep as deposits must be
+            let Some(height) = self.store.spend_height(&txid).await? else {
+                return self.expire_payout(withdrawal, &payout, &txid).await;
+            };
+            self.store.update_payout(&id, "confirmed", None, None, Some(height)).await?;
+            payout.status = "confirmed".to_string();
+            payout.height = Some(height);
+        }
+
+        if payout.status == "confirmed" {
+            let signature = retry::with_backoff(&self.config.retry, "finalize_withdrawal", |attempt| {
+                self.submit_finalize(withdrawal, &payout, attempt)
+            })
+            .await?;
+            self.store.mark_finalized(&id, Some(&signature)).await?;
+            self.metrics.withdrawals_finalized.inc();
+            self.resolve_alert(&format!("payout_failed:{}", withdrawal.nonce), "withdrawal finalized")
+                .await;
+            info!(
+                nonce = withdrawal.nonce,
+                zcash_txid = payout.zcash_txid.as_deref().unwrap_or_default(),
+                output_index = payout.output_index,
+                %signature,
+                "finalized withdrawal"
+            );
+        }
+        Ok(())
+    }
+
+    /// Send a payout again if its transaction `txid` expired without being mined
+    ///
+    /// A transaction can't be mined above its expiry height, so once scanning
+    /// has passed that height without seeing it, it is gone for good and the
+    /// withdrawal can be paid again without paying it twice.
+    #[instrument(skip(self, payout), fields(transfer = %withdrawal.idempotency_id()))]
+    async fn expire_payout(&self, withdrawal: &Withdrawal, payout: &Payout, txid: &str) -> Result<()> {
+        let scanned_height = self.store.latest_block().await?.map(|(height, _)| height);
+        let Some(expiry_height) = expired_unmined(payout.expiry_height, scanned_height) else {
+            return Ok(());
+        };
+        self.store.expire_payout(&payout.id, txid, expiry_height).await?;
+        warn!(
+            nonce = withdrawal.nonce,
+            zcash_txid = %txid,
+            expiry_height,
+            rebroadcasts = payout.rebroadcasts + 1,
+            "payout expired unmined; sending it again"
+        );
+        Ok(())
+    }
+
+    /// Find and record which output of the payout transaction `txid` pays
+    /// `withdrawal`, skipping outputs already matched to another withdrawal of
+    /// the same batch
+    async fn find_output(
+        &self,
+        wallet: &Wallet,
+        withdrawal: &Withdrawal,
+        payout: &Payout,
+        txid: &str,
+    ) -> Result<Option<u32>> {
+        let taken = self.store.payout_outputs(txid).await?;
+        let found = wallet.outputs(txid).await?.into_iter().find(|output| {
+            output.address == withdrawal.zec_address && output.amount == payout.amount && !taken.contains(&output.index)
+        });
+        match found {
+            Some(output) => {
+                self.store.set_output_index(&withdrawal.idempotency_id(), output.index).await?;
+                Ok(Some(output.index))
+            }
+            None => {
+                warn!(nonce = withdrawal.nonce, zcash_txid = %txid, "payout output not found; finalizing without it");
+                Ok(None)
+            }
+        }
+    }
+
+    /// ZIP-317 fee of a transaction paying `batch` from the bridge's unspent
+    /// `notes`, plus the configured margin
+    fn payout_fee(&self, payouts: &PayoutConfig, notes: &[(Pool, u64)], batch: &[(Withdrawal, Payout)]) -> Result<u64> {
+        let total = batch.iter().map(|(withdrawal, _)| withdrawal.net_amount).sum();
+        let receivers = batch
+            .iter()
+            .map(|(withdrawal, _)| {
+                zcash_addr::Address::parse(&withdrawal.zec_address)?
+                    .preferred_receiver()
+                    .cloned()
+                    .with_context(|| format!("withdrawal address {} has no receiver", withdrawal.zec_address))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let shape = zip317::payout_shape(notes, total, &receivers);
+        Ok(shape.conventional_fee() + payouts.extra_fee_actions * zip317::MARGINAL_FEE)
+    }
+
+    /// Record a withdrawal the relayer will pay, or that it can't be paid at all
+    async fn record_payout(&self, withdrawal: &Withdrawal, fee: u64) -> Result<()> {
+        self.store.record_payout(withdrawal, self.payout_status(withdrawal, fee)).await
+    }
+
+    /// In a dry run, the payout `record_payout` would have recorded, kept in
+    /// memory from the first poll that saw the withdrawal
+    fn rehearsed_payout(&self, withdrawal: &Withdrawal, fee: u64) -> Result<Payout> {
+        let mut payouts = self.rehearsed_payouts.lock().map_err(|_| anyhow!("dry-run payouts poisoned"))?;
+        if let Some(payout) = payouts.get(&withdrawal.idempotency_id()) {
+            return Ok(payout.clone());
+        }
+        let payout = Payout {
+            id: withdrawal.idempotency_id(),
+            zec_address: withdrawal.zec_address.clone(),
+            status: self.payout_status(withdrawal, fee).to_string(),
+            amount: withdrawal.net_amount,
+            fee: 0,
+            operation_id: None,
+            zcash_txid: None,
+            output_index: None,
+            height: None,
+            expiry_height: None,
+            rebroadcasts: 0,
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+        };
+        payouts.insert(payout.id.clone(), payout.clone());
+        Ok(payout)
+    }
+
+    /// Status a new payout of `withdrawal` starts in, as `initial_payout_status` decides
+    fn payout_status(&self, withdrawal: &Withdrawal, fee: u64) -> &'static str {
+        initial_payout_status(withdrawal, fee, self.config.zcash.network.address(), self.config.approvals.as_ref())
+    }
+
+    /// Sign, record and send one `finalize_withdrawal` attempt
+    #[instrument(name = "finalize_withdrawal", skip(self, withdrawal, payout))]
+    async fn submit_finalize(&self, withdrawal: &Withdrawal, payout: &Payout, attempt: u32) -> Result<Signature> {
+        let id = withdrawal.idempotency_id();
+        let txid = payout.zcash_txid.as_deref().context("confirmed payout has no txid")?;
+        let height = payout.height.context("confirmed payout has no height")?;
+        let output = payout.output_index.and_then(|index| u16::try_from(index).ok());
+        let submission = self
+            .minter
+            .finalize_transaction(withdrawal.nonce, txid, height, output, attempt)
+            .await?;
+        let signature = submission.transaction.signatures[0];
+        if !self.config.dry_run {
+            self.store.record_submission(&id, &submission.transaction).await?;
+        }
+        self.record(
+            "finalize_submitted",
+            json!({
+                "nonce": withdrawal.nonce,
+                "zcash_txid": txid,
+                "height": height,
+                "signature": signature.to_string(),
+                "attempt": attempt,
+            }),
+        )
+        .await?;
+
+        match self.minter.send(&submission).await {
+            Ok(signature) => {
+                self.finish_submission(&signature, "confirmed", None).await?;
+                Ok(signature)
+            }
+            Err(error) => {
+                self.finish_submission(&signature, "failed", Some(&format!("{error:#}"))).await?;
+                Err(error.context(format!("finalizing withdrawal {}", withdrawal.nonce)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::UnboundedReceiver;
+    use tokio::sync::Notify;
+
+    use super::*;
+    use crate::deposit::tests::{naming, note, txid};
+    use crate::store::tests::open_temp;
+
+    type Event = (&'static str, u8);
+
+    fn deposit(tx: u8) -> Deposit {
+        Deposit {
+            txid: txid(tx),
+            zcash_txid: [tx; 32],
+            amount: 100_000,
+            recipient: Pubkey::new_unique(),
+            block_height: 100,
+            confirmations: 10,
+        }
+    }
+
+    /// A stand-in stage: report that deposit `tx` reached `stage`, wait for
+    /// the test to open its gate, then report it passed
+    async fn gated(events: &mpsc::UnboundedSender<Event>, gates: &[Notify], stage: &'static str, tx: u8) {
+        events.send((stage, tx)).unwrap();
+        gates[usize::from(tx)].notified().await;
+        events.send((stage, tx + 100)).unwrap();
+    }
+
+    /// Wait until `event` has happened, whatever else happens meanwhile
+    async fn happened(events: &mut UnboundedReceiver<Event>, seen: &mut HashSet<Event>, event: Event) {
+        while !seen.contains(&event) {
+            seen.insert(events.recv().await.expect("pipeline stopped"));
+        }
+    }
+
+    #[tokio::test]
+    async fn deposits_are_committed_and_finished_in_scan_order() {
+        let pipeline = PipelineConfig {
+            queue_depth: 2,
+            attest_concurrency: 4,
+            submit_concurrency: 3,
+        };
+        let deposits: Vec<_> = (1..=5).map(deposit).collect();
+        let attest_gates: Vec<_> = (0..=5).map(|_| Notify::new()).collect();
+        let confirm_gates: Vec<_> = (0..=5).map(|_| Notify::new()).collect();
+        let (sender, mut events) = mpsc::unbounded_channel();
+        let committed = Mutex::new(Vec::new());
+        let finished = Mutex::new(Vec::new());
+
+        let minting = mint_pipeline(
+            &pipeline,
+            deposits.clone(),
+            |deposit| {
+                let (sender, gates) = (sender.clone(), &attest_gates);
+                async move {
+                    let tx = deposit.zcash_txid[0];
+                    gated(&sender, gates, "attest", tx).await;
+                    // An attestation failure is carried through to the end, in its place
+                    let minting = if tx == 3 { Err(anyhow!("no quorum")) } else { Ok(tx) };
+                    (deposit, minting)
+                }
+            },
+            |deposit, minting| {
+                committed.lock().unwrap().push(deposit.zcash_txid[0]);
+                async move { (deposit, minting) }
+            },
+            |deposit, minting| {
+                let (sender, gates) = (sender.clone(), &confirm_gates);
+                async move {
+                    gated(&sender, gates, "confirm", deposit.zcash_txid[0]).await;
+                    (deposit, minting)
+                }
+            },
+            |deposit, minting| {
+                finished.lock().unwrap().push(deposit.zcash_txid[0]);
+                async move { (deposit, minting.map(|tx| tx != 0)) }
+            },
+        );
+
+        // Open the gates so that every stage finishes its deposits last to first
+        let driving = async {
+            let mut seen = HashSet::new();
+            for tx in 1..=4 {
+                happened(&mut events, &mut seen, ("attest", tx)).await;
+            }
+            for tx in [4, 3, 2, 1, 5] {
+                attest_gates[usize::from(tx)].notify_one();
+                happened(&mut events, &mut seen, ("attest", tx + 100)).await;
+            }
+            for tx in 1..=3 {
+                happened(&mut events, &mut seen, ("confirm", tx)).await;
+            }
+            // Only three mints wait to confirm at once
+            assert!(!seen.contains(&("confirm", 4)));
+            for tx in [3, 2, 1, 5, 4] {
+                happened(&mut events, &mut seen, ("confirm", tx)).await;
+                confirm_gates[usize::from(tx)].notify_one();
+                happened(&mut events, &mut seen, ("confirm", tx + 100)).await;
+            }
+        };
+        let (outcomes, ()) = tokio::join!(minting, driving);
+
+        assert_eq!(*committed.lock().unwrap(), [1, 2, 3, 4, 5]);
+        assert_eq!(*finished.lock().unwrap(), [1, 2, 3, 4, 5]);
+        let outcomes: Vec<_> = outcomes
+            .iter()
+            .map(|(deposit, minted)| (deposit.txid.clone(), minted.is_ok()))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [(txid(1), true), (txid(2), true), (txid(3), false), (txid(4), true), (txid(5), true)]
+        );
+    }
+
+    #[tokio::test]
+    async fn deposit_without_a_recipient_is_quarantined_not_minted() {
+        let store = open_temp("unattributable").await;
+        let notes = [note(1, 0, 50_000)];
+        let reason = attribute(&store, 10_000, &notes).await.unwrap().unwrap_err();
+        store.quarantine(&notes, &reason).await.unwrap();
+        // Scanned again after a restart, it is still the one deposit
+        store.quarantine(&notes, &reason).await.unwrap();
+
+        let quarantined = store.quarantined_deposits().await.unwrap();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!((quarantined[0].txid.as_str(), quarantined[0].amount), (txid(1).as_str(), 50_000));
+        assert_eq!(quarantined[0].status, "quarantined");
+        assert!(store.deposit(&txid(1)).await.unwrap().is_none());
+        assert!(store.pending_deposits().await.unwrap().is_empty());
+        assert!(store.attributed_deposits().await.unwrap().is_empty());
+
+        // Naming a recipient, it is minted unless too small
+        let recipient = Pubkey::new_unique();
+        let named = [naming(note(2, 0, 50_000), &recipient)];
+        assert_eq!(attribute(&store, 10_000, &named).await.unwrap().unwrap().recipient, recipient);
+        let small = [naming(note(3, 0, 9_999), &recipient)];
+        assert!(attribute(&store, 10_000, &small).await.unwrap().is_err());
+    }
+
+    /// Withdrawal `nonce` owed `net_amount`, with its payout recorded at `created_at`
+    fn payout(nonce: u64, net_amount: u64, created_at: i64) -> (Withdrawal, Payout) {
+        let withdrawal = Withdrawal {
+            request: Pubkey::new_unique(),
+            nonce,
+            net_amount,
+            zec_address: "t1SmxPaFcF5kYFn5ctgCt7QiaXzpTZyF4kG".to_string(),
+            memo: Vec::new(),
+            unlock_slot: 0,
+        };
+        let payout = Payout {
+            id: withdrawal.idempotency_id(),
+            zec_address: withdrawal.zec_address.clone(),
+            status: "pending".to_string(),
+            amount: net_amount,
+            fee: zip317::MINIMUM_FEE,
+            operation_id: None,
+            zcash_txid: None,
+            output_index: None,
+            height: None,
+            expiry_height: None,
+            rebroadcasts: 0,
+            created_at,
+        };
+        (withdrawal, payout)
+    }
+
+    fn nonces(batch: &[(Withdrawal, Payout)]) -> Vec<u64> {
+        batch.iter().map(|(withdrawal, _)| withdrawal.nonce).collect()
+    }
+
+    #[test]
+    fn batch_waits_for_its_window_or_to_fill() {
+        let batch = || vec![payout(1, 100_000, 1_000), payout(2, 100_000, 1_030)];
+
+        // Within the window of the oldest, with room for more
+        assert!(due_batch(batch(), 3, 60, 1_059).is_empty());
+        // The oldest has waited the window out
+        assert_eq!(nonces(&due_batch(batch(), 3, 60, 1_060)), [1, 2]);
+        // Full, however young
+        assert_eq!(nonces(&due_batch(batch(), 2, 60, 1_030)), [1, 2]);
+        // Without a window, every payout goes out as soon as it is pending
+        assert_eq!(nonces(&due_batch(vec![payout(3, 100_000, 1_000)], 50, 0, 1_000)), [3]);
+        assert!(due_batch(Vec::new(), 50, 0, 1_000).is_empty());
+    }
+
+    #[test]
+    fn batch_over_the_size_limit_is_split() {
+        let mut pending: Vec<_> = (1..=5).map(|nonce| payout(nonce, 100_000, 1_000)).collect();
+
+        // Each poll sends the first `max_batch_size`; the rest wait for the next
+        let first = due_batch(pending.clone(), 2, 0, 1_000);
+        assert_eq!(nonces(&first), [1, 2]);
+        pending.retain(|(withdrawal, _)| !nonces(&first).contains(&withdrawal.nonce));
+        let second = due_batch(pending.clone(), 2, 0, 1_000);
+        assert_eq!(nonces(&second), [3, 4]);
+        pending.retain(|(withdrawal, _)| !nonces(&second).contains(&withdrawal.nonce));
+        assert_eq!(nonces(&due_batch(pending, 2, 0, 1_000)), [5]);
+    }
+
+    /// ZIP-317 fee of a payout spending one note to `batch`'s transparent
+    /// addresses, with change
+    fn fee_for(batch: &[(Withdrawal, Payout)]) -> Result<u64> {
+        let receivers: Vec<_> = batch.iter().map(|_| zcash_addr::Receiver::P2pkh([0; 20])).collect();
+        let total = batch.iter().map(|(withdrawal, _)| withdrawal.net_amount).sum();
+        Ok(zip317::payout_shape(&[(Pool::Sapling, 10_000_000)], total, &receivers).conventional_fee())
+    }
+
+    #[test]
+    fn batch_shares_its_fee_equally() {
+        let batch: Vec<_> = (1..=4).map(|nonce| payout(nonce, 100_000, 1_000)).collect();
+        let fee = fee_for(&batch).unwrap();
+        let (payable, fee_share) = share_fee(batch, fee_for).unwrap().unwrap();
+        assert_eq!(nonces(&payable), [1, 2, 3, 4]);
+        assert_eq!(fee_share, fee.div_ceil(4));
+        assert!(fee_share * 4 >= fee);
+    }
+
+    #[test]
+    fn withdrawals_too_small_for_their_share_wait_for_a_smaller_batch() {
+        // Four transparent outputs and a Sapling spend and change: 6 actions, 30_000 zatoshis,
+        // 7_500 each. Without nonce 4, 5 actions or 8_334 each, which nonce 3 doesn't cover
+        // either. Alone, nonces 1 and 2 pay 20_000, 10_000 each.
+        let batch = vec![
+            payout(1, 100_000, 1_000),
+            payout(2, 100_000, 1_000),
+            payout(3, 8_000, 1_000),
+            payout(4, 7_000, 1_000),
+        ];
+        assert_eq!(fee_for(&batch).unwrap(), 30_000);
+        let (payable, fee_share) = share_fee(batch, fee_for).unwrap().unwrap();
+        assert_eq!(nonces(&payable), [1, 2]);
+        assert_eq!(fee_share, 10_000);
+
+        // None covers its share
+        assert!(share_fee(vec![payout(5, 9_000, 1_000), payout(6, 4_000, 1_000)], fee_for).unwrap().is_none());
+    }
+
+    #[test]
+    fn new_payouts_start_pending_unless_unpayable_or_over_the_threshold() {
+        let (withdrawal, _) = payout(1, 100_000, 1_000);
+        let network = zcash_addr::Network::Main;
+        assert_eq!(initial_payout_status(&withdrawal, zip317::MINIMUM_FEE, network, None), "pending");
+        // A testnet bridge can't pay a mainnet address
+        assert_eq!(
+            initial_payout_status(&withdrawal, zip317::MINIMUM_FEE, zcash_addr::Network::Test, None),
+            "invalid"
+        );
+        let garbled = Withdrawal { zec_address: "t1notanaddress".to_string(), ..withdrawal.clone() };
+        assert_eq!(initial_payout_status(&garbled, zip317::MINIMUM_FEE, network, None), "invalid");
+        // Nothing would be left for the recipient after the fee
+        assert_eq!(initial_payout_status(&withdrawal, 100_000, network, None), "invalid");
+        assert_eq!(initial_payout_status(&withdrawal, 99_999, network, None), "pending");
+
+        let approvals = ApprovalConfig {
+            threshold: 100_000,
+            required: 2,
+            operators: Default::default(),
+            listen: "127.0.0.1:0".parse().unwrap(),
+        };
+        assert_eq!(initial_payout_status(&withdrawal, zip317::MINIMUM_FEE, network, Some(&approvals)), "pending");
+        let large = Withdrawal { net_amount: 100_001, ..withdrawal.clone() };
+        assert_eq!(initial_payout_status(&large, zip317::MINIMUM_FEE, network, Some(&approvals)), "needs_approval");
+        // An unpayable withdrawal isn't put to the operators
+        assert_eq!(initial_payout_status(&large, 100_001, network, Some(&approvals)), "invalid");
+    }
+
+    #[test]
+    fn payout_is_sent_again_only_once_its_transaction_cannot_be_mined() {
+        // Not yet at its expiry height, the transaction may still be mined
+        assert_eq!(expired_unmined(Some(2_000_040), Some(2_000_039)), None);
+        assert_eq!(expired_unmined(Some(2_000_040), Some(2_000_040)), Some(2_000_040));
+        assert_eq!(expired_unmined(Some(2_000_040), Some(2_000_100)), Some(2_000_040));
+        // Transactions that never expire, or whose expiry isn't known yet, are waited for
+        assert_eq!(expired_unmined(Some(0), Some(2_000_100)), None);
+        assert_eq!(expired_unmined(None, Some(2_000_100)), None);
+        // Nothing scanned yet
+        assert_eq!(expired_unmined(Some(2_000_040), None), None);
+    }
+}
+
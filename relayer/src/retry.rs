@@ -0,0 +1,33 @@
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use tracing::warn;
+
+use crate::config::RetryConfig;
+
+/// Run `operation` until it succeeds or `config.max_attempts` is reached,
+/// doubling the wait between attempts up to `config.max_backoff_ms`
+///
+/// `operation` is passed the attempt number, counting from 1.
+pub async fn with_backoff<T, F, Fut>(config: &RetryConfig, label: &str, mut operation: F) -> Result<T>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut backoff = Duration::from_millis(config.initial_backoff_ms);
+    let max_backoff = Duration::from_millis(config.max_backoff_ms);
+    let mut attempt = 1;
+    loop {
+        match operation(attempt).await {
+            Ok(value) => return Ok(value),
+            Err(error) if attempt < config.max_attempts => {
+                warn!(%label, attempt, ?backoff, error = %format!("{error:#}"), "retrying");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(max_backoff);
+                attempt += 1;
+            }
+            Err(error) => return Err(error.context(format!("{label} failed after {attempt} attempts"))),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::chaos::{self, Fault};
+
+/// Minimal JSON-RPC client shared by the zcashd and Zebra backends
+pub struct JsonRpcClient {
+    http: reqwest::Client,
+    url: String,
+    auth: Option<(String, String)>,
+    version: &'static str,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl JsonRpcClient {
+    pub fn new(url: &str, auth: Option<(String, String)>, version: &'static str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.to_string(),
+            auth,
+            version,
+        }
+    }
+
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        chaos::fail(Fault::RpcFailure, method)?;
+        let mut request = self.http.post(&self.url).json(&json!({
+            "jsonrpc": self.version,
+            "id": "solz-relayer",
+            "method": method,
+            "params": params,
+        }));
+        if let Some((user, password)) = &self.auth {
+            request = request.basic_auth(user, Some(password));
+        }
+
+        let response: RpcResponse<T> = request
+            .send()
+            .await
+            .with_context(|| format!("{method} request"))?
+            .json()
+            .await
+            .with_context(|| format!("decoding {method} response"))?;
+        if let Some(error) = response.error {
+            return Err(anyhow!("{method} failed ({}): {}", error.code, error.message));
+        }
+        chaos::fail(Fault::LostResponse, method)?;
+        response
+            .result
+            .ok_or_else(|| anyhow!("{method} returned no result"))
+    }
+}
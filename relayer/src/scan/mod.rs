@@ -0,0 +1,185 @@
+mod orchard;
+mod sapling;
+
+use anyhow::{Context, Result};
+use zcash_client_backend::proto::compact_formats::CompactTx;
+use zcash_primitives::consensus::{self, BlockHeight, BranchId};
+use zcash_primitives::transaction::Transaction;
+
+use crate::deposit::{Pool, ReceivedNote};
+use crate::keys::ViewingKey;
+
+use self::orchard::OrchardScanner;
+use self::sapling::SaplingScanner;
+
+/// A note of ours, decrypted from a Sapling output or an Orchard action
+#[derive(Debug, Clone)]
+pub struct DecryptedOutput {
+    pub index: usize,  // Output or action index within the pool's bundle
+    pub note: DecryptedNote,
+    pub memo: [u8; 512],
+    pub commitment: [u8; 32],  // Sapling cmu or Orchard cmx
+    pub diversifier_index: [u8; 11],  // Of the address the output paid
+}
+
+#[derive(Debug, Clone)]
+pub enum DecryptedNote {
+    Sapling(sapling_crypto::Note),
+    Orchard(::orchard::Note),
+}
+
+impl DecryptedOutput {
+    pub fn pool(&self) -> Pool {
+        match self.note {
+            DecryptedNote::Sapling(_) => Pool::Sapling,
+            DecryptedNote::Orchard(_) => Pool::Orchard,
+        }
+    }
+
+    /// Zatoshis
+    pub fn value(&self) -> u64 {
+        match &self.note {
+            DecryptedNote::Sapling(note) => note.value().inner(),
+            DecryptedNote::Orchard(note) => note.value().inner(),
+        }
+    }
+}
+
+/// Note commitment counts of the Sapling and Orchard trees
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TreeSizes {
+    pub sapling: u64,
+    pub orchard: u64,
+}
+
+impl TreeSizes {
+    pub fn get(&self, pool: Pool) -> u64 {
+        match pool {
+            Pool::Sapling => self.sapling,
+            Pool::Orchard => self.orchard,
+        }
+    }
+
+    /// Tree sizes before a block, from the sizes after it and the commitments it added
+    pub fn before(&self, added: &TreeSizes) -> Option<TreeSizes> {
+        Some(TreeSizes {
+            sapling: self.sapling.checked_sub(added.sapling)?,
+            orchard: self.orchard.checked_sub(added.orchard)?,
+        })
+    }
+}
+
+/// Sapling and Orchard note commitment tree frontiers after a block, hex
+/// encoded as nodes and lightwalletd serve them
+#[derive(Debug, Clone, Default)]
+pub struct TreeState {
+    pub sapling: String,
+    pub orchard: String,
+}
+
+/// What scanning one transaction found
+#[derive(Debug, Default)]
+pub struct ScannedTransaction {
+    pub outputs: Vec<DecryptedOutput>,
+    pub nullifiers: Vec<(Pool, [u8; 32])>,  // Every nullifier revealed, ours or not
+    pub sapling_outputs: u64,  // Sapling outputs, ours or not
+    pub orchard_actions: u64,  // Orchard actions, ours or not
+    pub expiry_height: u32,  // 0 when it never expires
+}
+
+/// Trial-decrypts transactions with the deposit address's viewing key, in
+/// every pool the key covers
+pub struct Scanner {
+    network: consensus::Network,
+    sapling: Option<SaplingScanner>,
+    orchard: Option<OrchardScanner>,
+}
+
+impl Scanner {
+    /// Scan with every component of the deposit address's viewing key
+    pub fn new(key: &ViewingKey) -> Self {
+        Self {
+            network: key.network(),
+            sapling: key.sapling.as_ref().map(|dfvk| SaplingScanner::new(key.network(), dfvk)),
+            orchard: key.orchard.as_ref().map(OrchardScanner::new),
+        }
+    }
+
+    /// Whether any output or action of a compact transaction at `height` is ours
+    pub fn compact_match(&self, tx: &CompactTx, height: u32) -> bool {
+        self.sapling.as_ref().is_some_and(|sapling| sapling.compact_match(tx, height))
+            || self.orchard.as_ref().is_some_and(|orchard| orchard.compact_match(tx))
+    }
+
+    /// Decrypt the outputs of a full transaction mined at `height` and collect
+    /// its revealed nullifiers
+    pub fn scan_transaction(&self, raw: &[u8], height: u32) -> Result<ScannedTransaction> {
+        let height = BlockHeight::from_u32(height);
+        let tx = Transaction::read(raw, BranchId::for_height(&self.network, height))
+            .context("parsing transaction")?;
+
+        let mut scanned = ScannedTransaction {
+            expiry_height: tx.expiry_height().into(),
+            ..ScannedTransaction::default()
+        };
+        if let Some(bundle) = tx.sapling_bundle() {
+            scanned.sapling_outputs = bundle.shielded_outputs().len() as u64;
+            scanned
+                .nullifiers
+                .extend(bundle.shielded_spends().iter().map(|spend| (Pool::Sapling, spend.nullifier().0)));
+        }
+        if let Some(bundle) = tx.orchard_bundle() {
+            scanned.orchard_actions = bundle.actions().len() as u64;
+            scanned
+                .nullifiers
+                .extend(bundle.actions().iter().map(|action| (Pool::Orchard, action.nullifier().to_bytes())));
+        }
+        if let Some(sapling) = &self.sapling {
+            scanned.outputs.extend(sapling.decrypt(&tx, height));
+        }
+        if let Some(orchard) = &self.orchard {
+            scanned.outputs.extend(orchard.decrypt(&tx));
+        }
+        Ok(scanned)
+    }
+
+    /// The received note for one of our outputs, at `position` in its pool's
+    /// note commitment tree
+    pub fn received_note(
+        &self,
+        txid: &str,
+        output: DecryptedOutput,
+        height: u32,
+        confirmations: u32,
+        position: u64,
+    ) -> ReceivedNote {
+        ReceivedNote {
+            pool: output.pool(),
+            txid: txid.to_string(),
+            output_index: output.index as u32,
+            amount_zat: output.value(),
+            memo: output.memo,
+            confirmations,
+            height,
+            position,
+            commitment: output.commitment,
+            nullifier: self.nullifier(&output, position),
+            diversifier_index: output.diversifier_index,
+        }
+    }
+
+    fn nullifier(&self, output: &DecryptedOutput, position: u64) -> [u8; 32] {
+        match &output.note {
+            DecryptedNote::Sapling(note) => self
+                .sapling
+                .as_ref()
+                .expect("Sapling notes are only decrypted with a Sapling key")
+                .nullifier(note, position),
+            DecryptedNote::Orchard(note) => self
+                .orchard
+                .as_ref()
+                .expect("Orchard notes are only decrypted with an Orchard key")
+                .nullifier(note),
+        }
+    }
+}
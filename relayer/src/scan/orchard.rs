@@ -0,0 +1,68 @@
+use orchard::keys::{FullViewingKey, IncomingViewingKey, PreparedIncomingViewingKey, Scope};
+use orchard::note_encryption::{CompactAction, OrchardDomain};
+use orchard::Note;
+use zcash_client_backend::proto::compact_formats::CompactTx;
+use zcash_note_encryption::{try_compact_note_decryption, try_note_decryption};
+use zcash_primitives::transaction::Transaction;
+
+use super::{DecryptedNote, DecryptedOutput};
+
+/// Trial-decrypts Orchard actions with the Orchard key of the deposit address
+pub struct OrchardScanner {
+    fvk: FullViewingKey,
+    ivk: IncomingViewingKey,
+    prepared_ivk: PreparedIncomingViewingKey,
+}
+
+impl OrchardScanner {
+    pub fn new(fvk: &FullViewingKey) -> Self {
+        let ivk = fvk.to_ivk(Scope::External);
+        Self {
+            fvk: fvk.clone(),
+            prepared_ivk: PreparedIncomingViewingKey::new(&ivk),
+            ivk,
+        }
+    }
+
+    /// Whether any Orchard action of a compact transaction is ours
+    pub fn compact_match(&self, tx: &CompactTx) -> bool {
+        tx.actions.iter().any(|action| {
+            CompactAction::try_from(action)
+                .ok()
+                .and_then(|action| {
+                    try_compact_note_decryption(&OrchardDomain::for_compact_action(&action), &self.prepared_ivk, &action)
+                })
+                .is_some()
+        })
+    }
+
+    /// Decrypt the Orchard actions of `tx` that pay us
+    pub fn decrypt(&self, tx: &Transaction) -> Vec<DecryptedOutput> {
+        let Some(bundle) = tx.orchard_bundle() else {
+            return Vec::new();
+        };
+        bundle
+            .actions()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, action)| {
+                let (note, address, memo) =
+                    try_note_decryption(&OrchardDomain::for_action(action), &self.prepared_ivk, action)?;
+                let diversifier_index = self.ivk.diversifier_index(&address)?;
+                Some(DecryptedOutput {
+                    index,
+                    note: DecryptedNote::Orchard(note),
+                    memo,
+                    commitment: action.cmx().to_bytes(),
+                    diversifier_index: *diversifier_index.to_bytes(),
+                })
+            })
+            .collect()
+    }
+
+    /// Nullifier of one of our notes; unlike Sapling's it does not depend on
+    /// the note's tree position
+    pub fn nullifier(&self, note: &Note) -> [u8; 32] {
+        note.nullifier(&self.fvk).to_bytes()
+    }
+}
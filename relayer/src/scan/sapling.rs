@@ -0,0 +1,81 @@
+use sapling_crypto::keys::NullifierDerivingKey;
+use sapling_crypto::note_encryption::{
+    try_sapling_compact_note_decryption, try_sapling_note_decryption, CompactOutputDescription,
+    PreparedIncomingViewingKey,
+};
+use sapling_crypto::zip32::DiversifiableFullViewingKey;
+use sapling_crypto::Note;
+use zcash_client_backend::proto::compact_formats::CompactTx;
+use zcash_primitives::consensus::{self, BlockHeight};
+use zcash_primitives::transaction::components::sapling::zip212_enforcement;
+use zcash_primitives::transaction::Transaction;
+use zcash_primitives::zip32::Scope;
+
+use super::{DecryptedNote, DecryptedOutput};
+
+/// Trial-decrypts Sapling outputs with the incoming viewing key of the deposit
+/// address and derives the nullifiers of the notes it finds
+///
+/// The incoming viewing key alone can detect and decrypt deposits; the
+/// nullifier deriving key in the full viewing key is what lets the relayer
+/// notice when a deposited note is spent.
+pub struct SaplingScanner {
+    network: consensus::Network,
+    dfvk: DiversifiableFullViewingKey,
+    ivk: PreparedIncomingViewingKey,
+    nk: NullifierDerivingKey,
+}
+
+impl SaplingScanner {
+    pub fn new(network: consensus::Network, dfvk: &DiversifiableFullViewingKey) -> Self {
+        Self {
+            network,
+            dfvk: dfvk.clone(),
+            ivk: PreparedIncomingViewingKey::new(&dfvk.to_ivk(Scope::External)),
+            nk: dfvk.fvk().vk.nk,
+        }
+    }
+
+    /// Whether any Sapling output of a compact transaction at `height` is ours
+    ///
+    /// Compact outputs carry only the note plaintext's first 52 bytes, enough
+    /// to recognise a note but not to read its memo.
+    pub fn compact_match(&self, tx: &CompactTx, height: u32) -> bool {
+        let zip212 = zip212_enforcement(&self.network, BlockHeight::from_u32(height));
+        tx.outputs.iter().any(|output| {
+            CompactOutputDescription::try_from(output.clone())
+                .ok()
+                .and_then(|output| try_sapling_compact_note_decryption(&self.ivk, &output, zip212))
+                .is_some()
+        })
+    }
+
+    /// Decrypt the Sapling outputs of `tx`, mined at `height`, that pay us
+    pub fn decrypt(&self, tx: &Transaction, height: BlockHeight) -> Vec<DecryptedOutput> {
+        let Some(bundle) = tx.sapling_bundle() else {
+            return Vec::new();
+        };
+        let zip212 = zip212_enforcement(&self.network, height);
+        bundle
+            .shielded_outputs()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, output)| {
+                let (note, address, memo) = try_sapling_note_decryption(&self.ivk, output, zip212)?;
+                let (diversifier_index, _) = self.dfvk.decrypt_diversifier(&address)?;
+                Some(DecryptedOutput {
+                    index,
+                    note: DecryptedNote::Sapling(note),
+                    memo,
+                    commitment: output.cmu().to_bytes(),
+                    diversifier_index: *diversifier_index.as_bytes(),
+                })
+            })
+            .collect()
+    }
+
+    /// Nullifier of one of our notes at `position` in the note commitment tree
+    pub fn nullifier(&self, note: &Note, position: u64) -> [u8; 32] {
+        note.nf(&self.nk, position).0
+    }
+}
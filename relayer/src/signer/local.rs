@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+
+use super::BridgeSigner;
+
+/// A Solana keypair file on the relayer host
+pub struct LocalSigner {
+    keypair: Keypair,
+    public_key: [u8; 32],
+}
+
+impl LocalSigner {
+    pub fn open(path: &Path) -> Result<Self> {
+        let keypair = read_keypair_file(path).map_err(|error| anyhow!("reading keypair {}: {error}", path.display()))?;
+        Ok(Self {
+            public_key: keypair.pubkey().to_bytes(),
+            keypair,
+        })
+    }
+}
+
+#[async_trait]
+impl BridgeSigner for LocalSigner {
+    fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.keypair.sign_message(message).as_ref().to_vec())
+    }
+}
@@ -0,0 +1,74 @@
+mod local;
+mod pkcs11;
+mod remote;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+
+use crate::config::SignerConfig;
+
+use self::local::LocalSigner;
+use self::pkcs11::Pkcs11Signer;
+use self::remote::RemoteSigner;
+
+/// An Ed25519 key the bridge signs with, wherever it is kept
+///
+/// Backends only ever hand out signatures, so a key held by an HSM or a remote
+/// signer never reaches the relayer host.
+#[async_trait]
+pub trait BridgeSigner: Send + Sync {
+    /// Raw 32-byte public key
+    fn public_key(&self) -> &[u8];
+
+    /// Raw 64-byte signature over `message`
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Open the signer backend `config` describes
+pub async fn open(config: &SignerConfig) -> Result<Box<dyn BridgeSigner>> {
+    Ok(match config {
+        SignerConfig::Local { keypair } => Box::new(LocalSigner::open(keypair)?),
+        SignerConfig::Pkcs11 {
+            module,
+            slot,
+            key_label,
+            pin_env,
+        } => Box::new(Pkcs11Signer::open(module, *slot, key_label, pin_env).await?),
+        SignerConfig::Remote {
+            url,
+            key_id,
+            tls_ca,
+            tls_cert,
+            tls_key,
+        } => Box::new(RemoteSigner::connect(url, key_id, tls_ca.as_deref(), tls_cert.as_deref(), tls_key.as_deref()).await?),
+    })
+}
+
+/// Solana address of `signer`'s key
+pub fn solana_pubkey(signer: &dyn BridgeSigner) -> Result<Pubkey> {
+    Pubkey::try_from(signer.public_key()).map_err(|_| anyhow!("signer key is not a 32-byte Ed25519 public key"))
+}
+
+/// Transaction running `instructions`, paid for and signed by `signer` alone
+///
+/// The signature is checked before the transaction is returned, so a signer
+/// holding the wrong key fails here rather than at the cluster.
+pub async fn sign_transaction(signer: &dyn BridgeSigner, instructions: &[Instruction], blockhash: Hash) -> Result<Transaction> {
+    let payer = solana_pubkey(signer)?;
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer));
+    transaction.message.recent_blockhash = blockhash;
+    let message = transaction.message_data();
+    let signature = Signature::try_from(signer.sign(&message).await?.as_slice())
+        .map_err(|_| anyhow!("signer returned a signature that is not 64 bytes"))?;
+    anyhow::ensure!(
+        signature.verify(payer.as_ref(), &message),
+        "signer returned a signature that doesn't verify against {payer}"
+    );
+    transaction.signatures = vec![signature];
+    Ok(transaction)
+}
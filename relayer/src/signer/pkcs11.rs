@@ -0,0 +1,92 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+
+use super::BridgeSigner;
+
+/// An Ed25519 key held by a PKCS#11 token, such as a YubiHSM 2 through
+/// `yubihsm_pkcs11.so`
+///
+/// The token signs with `CKM_EDDSA`; the private key never leaves it.
+pub struct Pkcs11Signer {
+    session: Arc<Mutex<Session>>,
+    key: ObjectHandle,
+    public_key: Vec<u8>,
+}
+
+impl Pkcs11Signer {
+    /// Log in to the token in `slot` with the PIN from the `pin_env`
+    /// environment variable and find the key pair labelled `key_label`
+    pub async fn open(module: &Path, slot: u64, key_label: &str, pin_env: &str) -> Result<Self> {
+        let pin = std::env::var(pin_env).with_context(|| format!("reading the PKCS#11 PIN from ${pin_env}"))?;
+        let (module, key_label) = (module.to_path_buf(), key_label.to_string());
+        tokio::task::spawn_blocking(move || {
+            let pkcs11 = Pkcs11::new(&module).with_context(|| format!("loading PKCS#11 module {}", module.display()))?;
+            pkcs11.initialize(CInitializeArgs::OsThreads)?;
+            let slot = pkcs11
+                .get_slots_with_token()?
+                .into_iter()
+                .find(|candidate| candidate.id() == slot)
+                .with_context(|| format!("no PKCS#11 token in slot {slot}"))?;
+            let session = pkcs11.open_ro_session(slot)?;
+            session
+                .login(UserType::User, Some(&AuthPin::new(pin)))
+                .context("logging in to the PKCS#11 token")?;
+
+            let find = |class: ObjectClass, what: &str| -> Result<ObjectHandle> {
+                session
+                    .find_objects(&[Attribute::Class(class), Attribute::Label(key_label.as_bytes().to_vec())])?
+                    .into_iter()
+                    .next()
+                    .with_context(|| format!("no {what} labelled {key_label:?} on the PKCS#11 token"))
+            };
+            let key = find(ObjectClass::PRIVATE_KEY, "private key")?;
+            let public = find(ObjectClass::PUBLIC_KEY, "public key")?;
+            let point = match session.get_attributes(public, &[AttributeType::EcPoint])?.into_iter().next() {
+                Some(Attribute::EcPoint(point)) => point,
+                _ => return Err(anyhow!("public key {key_label:?} has no EC point")),
+            };
+            Ok(Self {
+                session: Arc::new(Mutex::new(session)),
+                key,
+                public_key: ec_point_key(&point)?,
+            })
+        })
+        .await?
+    }
+}
+
+/// `CKA_EC_POINT` of an Ed25519 key: the raw key, usually wrapped in a DER
+/// OCTET STRING
+fn ec_point_key(point: &[u8]) -> Result<Vec<u8>> {
+    match point {
+        [0x04, 0x20, key @ ..] if key.len() == 32 => Ok(key.to_vec()),
+        key if key.len() == 32 => Ok(key.to_vec()),
+        _ => Err(anyhow!("EC point of {} bytes is not an Ed25519 public key", point.len())),
+    }
+}
+
+#[async_trait]
+impl BridgeSigner for Pkcs11Signer {
+    fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let (session, key, message) = (self.session.clone(), self.key, message.to_vec());
+        tokio::task::spawn_blocking(move || {
+            let session = session.lock().map_err(|_| anyhow!("PKCS#11 session lock poisoned"))?;
+            session
+                .sign(&Mechanism::Eddsa, key, &message)
+                .context("signing with the PKCS#11 token")
+        })
+        .await?
+    }
+}
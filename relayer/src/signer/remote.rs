@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
+
+use super::BridgeSigner;
+
+/// A key held by a signing service speaking `solz.signer.v1.Signer`, as
+/// defined in `proto/signer.proto`
+pub struct RemoteSigner {
+    channel: Channel,
+    key_id: String,
+    public_key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct GetPublicKeyRequest {
+    #[prost(string, tag = "1")]
+    key_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct GetPublicKeyResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    public_key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct SignRequest {
+    #[prost(string, tag = "1")]
+    key_id: String,
+    #[prost(bytes = "vec", tag = "2")]
+    message: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct SignResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    signature: Vec<u8>,
+}
+
+impl RemoteSigner {
+    /// Connect to the signer at `url` and fetch the public key of `key_id`
+    ///
+    /// Over `https`, `tls_ca` replaces the web PKI roots, and `tls_cert` and
+    /// `tls_key` authenticate the relayer to the signer.
+    pub async fn connect(
+        url: &str,
+        key_id: &str,
+        tls_ca: Option<&Path>,
+        tls_cert: Option<&Path>,
+        tls_key: Option<&Path>,
+    ) -> Result<Self> {
+        let mut endpoint = Channel::from_shared(url.to_string()).context("invalid remote signer url")?;
+        if url.starts_with("https://") {
+            let mut tls = ClientTlsConfig::new();
+            if let Some(ca) = tls_ca {
+                tls = tls.ca_certificate(Certificate::from_pem(read(ca)?));
+            }
+            if let (Some(cert), Some(key)) = (tls_cert, tls_key) {
+                tls = tls.identity(Identity::from_pem(read(cert)?, read(key)?));
+            }
+            endpoint = endpoint.tls_config(tls)?;
+        }
+        let channel = endpoint
+            .connect()
+            .await
+            .with_context(|| format!("connecting to remote signer at {url}"))?;
+
+        let mut signer = Self {
+            channel,
+            key_id: key_id.to_string(),
+            public_key: Vec::new(),
+        };
+        let response: GetPublicKeyResponse = signer
+            .call(
+                "/solz.signer.v1.Signer/GetPublicKey",
+                GetPublicKeyRequest {
+                    key_id: key_id.to_string(),
+                },
+            )
+            .await
+            .with_context(|| format!("fetching public key {key_id:?} from the remote signer"))?;
+        signer.public_key = response.public_key;
+        Ok(signer)
+    }
+
+    async fn call<Request, Response>(&self, path: &'static str, request: Request) -> Result<Response>
+    where
+        Request: prost::Message + Send + Sync + 'static,
+        Response: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut grpc = tonic::client::Grpc::new(self.channel.clone());
+        grpc.ready().await.context("remote signer not ready")?;
+        let response = grpc
+            .unary(
+                tonic::Request::new(request),
+                PathAndQuery::from_static(path),
+                ProstCodec::default(),
+            )
+            .await?;
+        Ok(response.into_inner())
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("reading {}", path.display()))
+}
+
+#[async_trait]
+impl BridgeSigner for RemoteSigner {
+    fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let response: SignResponse = self
+            .call(
+                "/solz.signer.v1.Signer/Sign",
+                SignRequest {
+                    key_id: self.key_id.clone(),
+                    message: message.to_vec(),
+                },
+            )
+            .await
+            .with_context(|| format!("signing with remote key {:?}", self.key_id))?;
+        Ok(response.signature)
+    }
+}
@@ -0,0 +1,220 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{Context, Result};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::routing::get;
+use axum::{Json, Router};
+use futures::stream::{self, Stream};
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::feed::{DepositFeed, SeenDeposit};
+use crate::metrics::SharedMetrics;
+use crate::payout::Payout;
+use crate::store::Store;
+
+/// What the relayer last saw, published after every poll
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Snapshot {
+    pub relayer: String,
+    pub tip: Option<u32>,  // Zcash chain tip
+    pub scanned_height: Option<u32>,  // Highest block scanned at the confirmation depth
+    pub halted: bool,  // Stopped minting after a deep reorg
+    pub leader: bool,  // Holds the leader lease, or runs alone
+    pub hot_low: bool,  // Hot balance below `reserve.hot_min`
+    pub last_poll: Option<i64>,  // Unix time the last poll finished
+    pub last_error: Option<String>,  // Why the last poll failed, if it did
+}
+
+/// Live status shared between the relayer and the status API
+pub type SharedSnapshot = Arc<RwLock<Snapshot>>;
+
+#[derive(Clone)]
+struct ApiState {
+    store: Store,
+    snapshot: SharedSnapshot,
+    metrics: SharedMetrics,
+    feed: DepositFeed,
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    #[serde(flatten)]
+    snapshot: Snapshot,
+    pending_deposits: usize,
+    hot_balance: u64,  // Unspent zatoshis at the deposit address
+    open_payouts: usize,
+    expired_payouts: usize,  // Open payouts sent again after a transaction expired
+}
+
+/// Where one deposit has got to, as `GET /deposits/:txid` reports it
+#[derive(Serialize)]
+struct DepositStatus {
+    txid: String,
+    status: String,  // seen, pending, minted, quarantined, refunding or refunded
+    amount: u64,  // Zatoshis
+    recipient: Option<String>,
+    confirmations: u32,  // 0 while seen in the mempool
+    signature: Option<String>,  // Of the mint, once minted
+    reason: Option<String>,  // Why it was quarantined
+    refund_txid: Option<String>,  // Once a quarantined deposit is refunded
+}
+
+/// Serve the read-only status API on `listen` until the relayer stops
+///
+/// * `GET /status`: the latest snapshot and deposit and payout counts
+/// * `GET /payouts`: every payout not yet finalized, with its transaction,
+///   expiry height and rebroadcast count
+/// * `GET /metrics`: Prometheus metrics
+/// * `GET /mempool`: deposits seen in the mempool that haven't reached the
+///   confirmation depth
+/// * `GET /deposits/:txid`: where one deposit has got to, from seen in the
+///   mempool to minted
+/// * `GET /events`: server-sent events for each deposit seen, confirmed,
+///   minted or quarantined from now on
+pub async fn serve(
+    listen: SocketAddr,
+    store: Store,
+    snapshot: SharedSnapshot,
+    metrics: SharedMetrics,
+    feed: DepositFeed,
+) -> Result<()> {
+    let app = Router::new()
+        .route("/status", get(status))
+        .route("/payouts", get(payouts))
+        .route("/metrics", get(metrics_text))
+        .route("/mempool", get(mempool))
+        .route("/deposits/:txid", get(deposit))
+        .route("/events", get(events))
+        .with_state(ApiState {
+            store,
+            snapshot,
+            metrics,
+            feed,
+        });
+    axum::Server::try_bind(&listen)
+        .with_context(|| format!("binding status API to {listen}"))?
+        .serve(app.into_make_service())
+        .await
+        .context("serving status API")
+}
+
+async fn status(State(state): State<ApiState>) -> Result<Json<StatusResponse>, StatusCode> {
+    let snapshot = state
+        .snapshot
+        .read()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .clone();
+    let pending_deposits = state
+        .store
+        .pending_deposits()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .len();
+    let hot_balance = state
+        .store
+        .unspent_balance()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let payouts = state
+        .store
+        .open_payouts()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(StatusResponse {
+        snapshot,
+        pending_deposits,
+        hot_balance,
+        open_payouts: payouts.len(),
+        expired_payouts: payouts.iter().filter(|payout| payout.rebroadcasts > 0).count(),
+    }))
+}
+
+async fn payouts(State(state): State<ApiState>) -> Result<Json<Vec<Payout>>, StatusCode> {
+    state
+        .store
+        .open_payouts()
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn metrics_text(State(state): State<ApiState>) -> Result<String, StatusCode> {
+    state.metrics.encode().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn mempool(State(state): State<ApiState>) -> Json<Vec<SeenDeposit>> {
+    Json(state.feed.seen())
+}
+
+async fn deposit(State(state): State<ApiState>, Path(txid): Path<String>) -> Result<Json<DepositStatus>, StatusCode> {
+    let tip = state.snapshot.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?.tip;
+    let confirmations = |height: u32| tip.map_or(0, |tip| (tip + 1).saturating_sub(height));
+    let recorded = state
+        .store
+        .deposit_state(&txid)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(deposit) = recorded {
+        return Ok(Json(DepositStatus {
+            status: deposit.status,
+            amount: deposit.amount as u64,
+            recipient: Some(deposit.recipient),
+            confirmations: confirmations(deposit.height),
+            signature: deposit.signature,
+            reason: None,
+            refund_txid: None,
+            txid,
+        }));
+    }
+    let quarantined = state
+        .store
+        .quarantined(&txid)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(quarantined) = quarantined {
+        return Ok(Json(DepositStatus {
+            status: quarantined.status,
+            amount: quarantined.amount as u64,
+            recipient: None,
+            confirmations: confirmations(quarantined.height),
+            signature: None,
+            reason: Some(quarantined.reason),
+            refund_txid: quarantined.refund_txid,
+            txid,
+        }));
+    }
+    let seen = state.feed.get(&txid).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(DepositStatus {
+        txid,
+        status: "seen".to_string(),
+        amount: seen.amount,
+        recipient: seen.recipient,
+        confirmations: 0,
+        signature: None,
+        reason: None,
+        refund_txid: None,
+    }))
+}
+
+async fn events(State(state): State<ApiState>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = stream::unfold(state.feed.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Ok(sse) = Event::default().event(event.name()).json_data(&event) {
+                        return Some((Ok(sse), receiver));
+                    }
+                }
+                // A client that fell behind misses those events; `GET /deposits/:txid` catches it up
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,1305 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+use sqlx::any::AnyPoolOptions;
+use sqlx::migrate::Migrator;
+use sqlx::{Any, AnyPool};
+
+use crate::audit::AuditEntry;
+use crate::config::DatabaseConfig;
+use crate::deposit::{txid_bytes, Deposit, Pool, ReceivedNote, Spend};
+use crate::keys::DepositAddress;
+use crate::payout::{Payout, Withdrawal};
+use crate::quarantine::Quarantined;
+use crate::scan::TreeState;
+
+/// Checkpoints kept below the latest, for rolling back reorgs
+const CHECKPOINT_HISTORY: u32 = 1_000;
+
+/// Migrations for each database, in their own dialects; both directories
+/// hold the same migrations under the same versions
+static SQLITE_MIGRATIONS: Migrator = sqlx::migrate!("./migrations/sqlite");
+static POSTGRES_MIGRATIONS: Migrator = sqlx::migrate!("./migrations/postgres");
+
+const AUDIT_COLUMNS: &str = "seq, at, action, detail, prev_hash, hash, signer, signature";
+
+const QUARANTINE_COLUMNS: &str = "txid, pool, amount, height, memo, reason, status, recipient, refund_address, \
+     refund_operation_id, refund_txid, resolved_by, quarantined_at";
+
+const PAYOUT_COLUMNS: &str = "id, zec_address, status, amount, fee, operation_id, zcash_txid, output_index, \
+     height, expiry_height, rebroadcasts, created_at";
+
+/// Where a recorded deposit has got to
+#[derive(Debug, sqlx::FromRow)]
+pub struct DepositState {
+    pub status: String,  // pending or minted
+    pub recipient: String,
+    pub amount: i64,  // Zatoshis
+    #[sqlx(try_from = "i64")]
+    pub height: u32,
+    pub signature: Option<String>,  // Of the mint, once minted
+}
+
+/// A `payouts` row as the database stores it
+#[derive(sqlx::FromRow)]
+struct PayoutRow {
+    id: String,
+    zec_address: String,
+    status: String,
+    amount: i64,
+    fee: i64,
+    operation_id: Option<String>,
+    zcash_txid: Option<String>,
+    output_index: Option<i64>,
+    height: Option<i64>,
+    expiry_height: Option<i64>,
+    rebroadcasts: i64,
+    created_at: i64,
+}
+
+impl PayoutRow {
+    fn into_payout(self) -> Payout {
+        Payout {
+            id: self.id,
+            zec_address: self.zec_address,
+            status: self.status,
+            amount: self.amount as u64,
+            fee: self.fee as u64,
+            operation_id: self.operation_id,
+            zcash_txid: self.zcash_txid,
+            output_index: self.output_index.map(|index| index as u32),
+            height: self.height.map(|height| height as u32),
+            expiry_height: self.expiry_height.map(|height| height as u32),
+            rebroadcasts: self.rebroadcasts as u32,
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// The relayer's database, shared by the relayer and its status API, and by
+/// every instance when several run in high availability
+///
+/// Every note the deposit address receives is recorded with its commitment,
+/// tree position and nullifier, so spends of bridge funds can be matched to
+/// the notes they consume. Deposits, attestations, Solana submissions and
+/// payouts are keyed by idempotency IDs, so a restarted relayer can tell what
+/// it already did.
+#[derive(Clone)]
+pub struct Store {
+    pool: AnyPool,
+}
+
+impl Store {
+    /// Open the configured database, creating it and applying migrations as
+    /// needed: the Postgres database at the URL in `url_env` if set, the
+    /// SQLite file at `path` otherwise
+    pub async fn open(config: &DatabaseConfig) -> Result<Self> {
+        sqlx::any::install_default_drivers();
+        let (url, migrator, name) = match &config.url_env {
+            Some(url_env) => {
+                let url = std::env::var(url_env)
+                    .with_context(|| format!("reading the database URL from ${url_env}"))?;
+                (url, &POSTGRES_MIGRATIONS, format!("the Postgres database in ${url_env}"))
+            }
+            None => {
+                let path = config.path.display();
+                (format!("sqlite://{path}?mode=rwc"), &SQLITE_MIGRATIONS, format!("database {path}"))
+            }
+        };
+        let pool = AnyPoolOptions::new()
+            .connect(&url)
+            .await
+            .with_context(|| format!("opening {name}"))?;
+        migrator.run(&pool).await.context("migrating database")?;
+        Ok(Self { pool })
+    }
+
+    /// Record a received note; notes already recorded are left as they are
+    pub async fn insert_note(&self, note: &ReceivedNote) -> Result<()> {
+        let insert = match note.pool {
+            Pool::Sapling => {
+                "INSERT INTO sapling_notes (txid, output_index, height, position, value, cmu, nullifier)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT DO NOTHING"
+            }
+            Pool::Orchard => {
+                "INSERT INTO orchard_notes (txid, action_index, height, position, value, cmx, nullifier)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT DO NOTHING"
+            }
+        };
+        sqlx::query(insert)
+        .bind(&note.txid)
+        .bind(i64::from(note.output_index))
+        .bind(i64::from(note.height))
+        .bind(note.position as i64)
+        .bind(note.amount_zat as i64)
+        .bind(note.commitment.as_slice())
+        .bind(note.nullifier.as_slice())
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("recording {:?} note {}:{}", note.pool, note.txid, note.output_index))?;
+        Ok(())
+    }
+
+    /// Mark the note with `spend`'s nullifier as spent, returning whether it was one of ours
+    pub async fn mark_spent(&self, spend: &Spend) -> Result<bool> {
+        let update = match spend.pool {
+            Pool::Sapling => {
+                "UPDATE sapling_notes SET spent_txid = $1, spent_height = $2 WHERE nullifier = $3 AND spent_txid IS NULL"
+            }
+            Pool::Orchard => {
+                "UPDATE orchard_notes SET spent_txid = $1, spent_height = $2 WHERE nullifier = $3 AND spent_txid IS NULL"
+            }
+        };
+        let result = sqlx::query(update)
+        .bind(&spend.txid)
+        .bind(i64::from(spend.height))
+        .bind(spend.nullifier.as_slice())
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("recording spend in {}", spend.txid))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether any of `nullifiers` spends one of our notes
+    pub async fn spends_ours(&self, nullifiers: &[(Pool, [u8; 32])]) -> Result<bool> {
+        for (pool, nullifier) in nullifiers {
+            let query = match pool {
+                Pool::Sapling => "SELECT 1 FROM sapling_notes WHERE nullifier = $1",
+                Pool::Orchard => "SELECT 1 FROM orchard_notes WHERE nullifier = $1",
+            };
+            if sqlx::query(query)
+                .bind(nullifier.as_slice())
+                .fetch_optional(&self.pool)
+                .await?
+                .is_some()
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Transactions among `spends` that spent one of our notes, in this scan
+    /// or, when rescanning, an earlier one
+    ///
+    /// Every shielded transaction reveals nullifiers, so a transaction only
+    /// counts as the bridge's own if one of them is a nullifier of ours.
+    pub async fn own_transactions(&self, spends: &[Spend]) -> Result<HashSet<String>> {
+        let mut own = HashSet::new();
+        for spend in spends {
+            if !own.contains(&spend.txid) && self.spends_ours(&[(spend.pool, spend.nullifier)]).await? {
+                own.insert(spend.txid.clone());
+            }
+        }
+        Ok(own)
+    }
+
+    /// Hold a deposit that can't be minted as it is, for an operator to
+    /// attribute or refund: every note its transaction paid the bridge, as
+    /// one deposit of their sum
+    pub async fn quarantine(&self, notes: &[ReceivedNote], reason: &str) -> Result<()> {
+        let note = notes.first().context("quarantining a deposit without notes")?;
+        let amount: u64 = notes.iter().map(|note| note.amount_zat).sum();
+        let diversifier_index = notes
+            .iter()
+            .all(|other| other.diversifier_index == note.diversifier_index)
+            .then_some(note.diversifier_index.as_slice());
+        sqlx::query(
+            "INSERT INTO quarantined_deposits (txid, pool, amount, height, memo, reason, diversifier_index)
+             VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT DO NOTHING",
+        )
+        .bind(&note.txid)
+        .bind(note.pool.as_str())
+        .bind(amount as i64)
+        .bind(i64::from(note.height))
+        .bind(note.memo.as_slice())
+        .bind(reason)
+        .bind(diversifier_index)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("quarantining deposit {}", note.txid))?;
+        Ok(())
+    }
+
+    /// Record the deposit address issued to `recipient`
+    pub async fn insert_deposit_address(&self, recipient: &Pubkey, address: &DepositAddress) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO deposit_addresses (recipient, diversifier_index, address) VALUES ($1, $2, $3)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(recipient.to_string())
+        .bind(address.diversifier_index.as_slice())
+        .bind(&address.address)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("recording deposit address for {recipient}"))?;
+        Ok(())
+    }
+
+    /// Quarantined deposits not yet attributed or refunded, oldest first
+    pub async fn quarantined_deposits(&self) -> Result<Vec<Quarantined>> {
+        let query = format!(
+            "SELECT {QUARANTINE_COLUMNS} FROM quarantined_deposits
+             WHERE status IN ('quarantined', 'refunding') ORDER BY height, txid"
+        );
+        Ok(sqlx::query_as(&query).fetch_all(&self.pool).await?)
+    }
+
+    /// The quarantined deposit `txid`, however it was resolved
+    pub async fn quarantined(&self, txid: &str) -> Result<Option<Quarantined>> {
+        let query = format!("SELECT {QUARANTINE_COLUMNS} FROM quarantined_deposits WHERE txid = $1");
+        Ok(sqlx::query_as(&query).bind(txid).fetch_optional(&self.pool).await?)
+    }
+
+    /// Diversifier index of the address every note of quarantined deposit
+    /// `txid` paid, if they paid one and it is still waiting for an operator
+    pub async fn quarantined_diversifier_index(&self, txid: &str) -> Result<Option<[u8; 11]>> {
+        let index: Option<(Option<Vec<u8>>,)> = sqlx::query_as(
+            "SELECT diversifier_index FROM quarantined_deposits WHERE txid = $1 AND status = 'quarantined'",
+        )
+        .bind(txid)
+        .fetch_optional(&self.pool)
+        .await?;
+        index
+            .and_then(|(index,)| index)
+            .map(|index| index.as_slice().try_into().context("stored diversifier index is not 11 bytes"))
+            .transpose()
+    }
+
+    /// Release a quarantined deposit to be minted to `deposit.recipient`,
+    /// returning whether it was still quarantined
+    pub async fn attribute_quarantined(&self, deposit: &Deposit, operator: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            "UPDATE quarantined_deposits
+             SET status = 'attributed', recipient = $1, resolved_by = $2, resolved_at = unixepoch()
+             WHERE txid = $3 AND status = 'quarantined'",
+        )
+        .bind(deposit.recipient.to_string())
+        .bind(operator)
+        .bind(&deposit.txid)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query(
+            "INSERT INTO deposits (id, txid, recipient, amount, height, status)
+             VALUES ($1, $2, $3, $4, $5, 'pending') ON CONFLICT DO NOTHING",
+        )
+        .bind(deposit.idempotency_id())
+        .bind(&deposit.txid)
+        .bind(deposit.recipient.to_string())
+        .bind(deposit.amount as i64)
+        .bind(i64::from(deposit.block_height))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Deposits operators attributed out of quarantine that aren't minted yet
+    pub async fn attributed_deposits(&self) -> Result<Vec<Deposit>> {
+        let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(
+            "SELECT d.txid, d.recipient, d.amount, d.height FROM deposits d
+             JOIN quarantined_deposits q ON q.txid = d.txid
+             WHERE q.status = 'attributed' AND d.status = 'pending' ORDER BY d.height",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(deposit_from_row).collect()
+    }
+
+    /// Claim a quarantined deposit for a refund to `address`, returning
+    /// whether it was still quarantined
+    pub async fn start_refund(&self, txid: &str, address: &str, operator: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE quarantined_deposits
+             SET status = 'refunding', refund_address = $1, resolved_by = $2, resolved_at = unixepoch()
+             WHERE txid = $3 AND status = 'quarantined'",
+        )
+        .bind(address)
+        .bind(operator)
+        .bind(txid)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record the wallet operation sending a refund
+    pub async fn record_refund_operation(&self, txid: &str, operation_id: &str) -> Result<()> {
+        sqlx::query("UPDATE quarantined_deposits SET refund_operation_id = $1 WHERE txid = $2")
+            .bind(operation_id)
+            .bind(txid)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Mark a refund sent as `refund_txid`
+    pub async fn finish_refund(&self, txid: &str, refund_txid: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE quarantined_deposits SET status = 'refunded', refund_txid = $1, resolved_at = unixepoch()
+             WHERE txid = $2",
+        )
+        .bind(refund_txid)
+        .bind(txid)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Put a deposit whose refund was never broadcast back in quarantine
+    pub async fn cancel_refund(&self, txid: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE quarantined_deposits
+             SET status = 'quarantined', refund_address = NULL, refund_operation_id = NULL,
+                 resolved_by = NULL, resolved_at = NULL
+             WHERE txid = $1 AND status = 'refunding'",
+        )
+        .bind(txid)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Refunds handed to the wallet whose outcome isn't known yet, as
+    /// quarantined txid and wallet operation ID
+    pub async fn refunds_in_flight(&self) -> Result<Vec<(String, String)>> {
+        Ok(sqlx::query_as(
+            "SELECT txid, refund_operation_id FROM quarantined_deposits
+             WHERE status = 'refunding' AND refund_operation_id IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Recipient the address at `diversifier_index` was issued to, if any
+    /// and not retired
+    pub async fn deposit_recipient(&self, diversifier_index: &[u8; 11]) -> Result<Option<Pubkey>> {
+        let recipient: Option<(String,)> = sqlx::query_as(
+            "SELECT recipient FROM deposit_addresses WHERE diversifier_index = $1 AND retired_at IS NULL",
+        )
+        .bind(diversifier_index.as_slice())
+        .fetch_optional(&self.pool)
+        .await?;
+        recipient
+            .map(|(recipient,)| recipient.parse().context("stored deposit recipient is not a public key"))
+            .transpose()
+    }
+
+    /// Recipient a retired address at `diversifier_index` was issued to, if any
+    pub async fn retired_recipient(&self, diversifier_index: &[u8; 11]) -> Result<Option<String>> {
+        let recipient: Option<(String,)> = sqlx::query_as(
+            "SELECT recipient FROM deposit_addresses WHERE diversifier_index = $1 AND retired_at IS NOT NULL",
+        )
+        .bind(diversifier_index.as_slice())
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(recipient.map(|(recipient,)| recipient))
+    }
+
+    /// Stop crediting the deposit address issued to `recipient`, returning
+    /// whether it was issued and still active
+    pub async fn retire_deposit_address(&self, recipient: &Pubkey) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE deposit_addresses SET retired_at = unixepoch() WHERE recipient = $1 AND retired_at IS NULL",
+        )
+        .bind(recipient.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record the hashes of newly scanned blocks
+    pub async fn insert_blocks(&self, blocks: &[(u32, String)]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for (height, hash) in blocks {
+            sqlx::query(
+                "INSERT INTO scanned_blocks (height, hash) VALUES ($1, $2)
+                 ON CONFLICT (height) DO UPDATE SET hash = excluded.hash",
+            )
+            .bind(i64::from(*height))
+                .bind(hash)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await.context("recording scanned blocks")
+    }
+
+    /// Height and hash of the highest scanned block
+    pub async fn latest_block(&self) -> Result<Option<(u32, String)>> {
+        let block: Option<(i64, String)> =
+            sqlx::query_as("SELECT height, hash FROM scanned_blocks ORDER BY height DESC LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await?;
+        block.map(|(height, hash)| Ok((u32::try_from(height)?, hash))).transpose()
+    }
+
+    /// Hash recorded for the scanned block at `height`
+    pub async fn block_hash(&self, height: u32) -> Result<Option<String>> {
+        let hash: Option<(String,)> = sqlx::query_as("SELECT hash FROM scanned_blocks WHERE height = $1")
+            .bind(i64::from(height))
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(hash.map(|(hash,)| hash))
+    }
+
+    /// Record that scanning reached the block `height` with `hash`
+    pub async fn save_checkpoint(&self, height: u32, hash: &str, trees: &TreeState) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO scan_checkpoints (height, hash, sapling_tree, orchard_tree) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (height) DO UPDATE SET
+                 hash = excluded.hash, sapling_tree = excluded.sapling_tree, orchard_tree = excluded.orchard_tree",
+        )
+        .bind(i64::from(height))
+        .bind(hash)
+        .bind(&trees.sapling)
+        .bind(&trees.orchard)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM scan_checkpoints WHERE height < $1")
+            .bind(i64::from(height.saturating_sub(CHECKPOINT_HISTORY)))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await.with_context(|| format!("saving checkpoint at height {height}"))
+    }
+
+    /// Height of the last checkpoint, where a restarted relayer resumes scanning
+    pub async fn checkpoint_height(&self) -> Result<Option<u32>> {
+        let height: Option<(i64,)> =
+            sqlx::query_as("SELECT height FROM scan_checkpoints ORDER BY height DESC LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(height.map(|(height,)| u32::try_from(height)).transpose()?)
+    }
+
+    /// Forget everything scanned above `height`: the notes, spends,
+    /// quarantined deposits, block hashes and payout confirmations of orphaned blocks
+    pub async fn roll_back(&self, height: u32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for statement in [
+            "DELETE FROM sapling_notes WHERE height > $1",
+            "DELETE FROM orchard_notes WHERE height > $1",
+            "UPDATE sapling_notes SET spent_txid = NULL, spent_height = NULL WHERE spent_height > $1",
+            "UPDATE orchard_notes SET spent_txid = NULL, spent_height = NULL WHERE spent_height > $1",
+            "DELETE FROM quarantined_deposits WHERE height > $1 AND status = 'quarantined'",
+            "DELETE FROM deposits WHERE height > $1 AND status = 'pending'",
+            "DELETE FROM scanned_blocks WHERE height > $1",
+            "DELETE FROM scan_checkpoints WHERE height > $1",
+            "UPDATE payouts SET status = 'sent', height = NULL WHERE status = 'confirmed' AND height > $1",
+        ] {
+            sqlx::query(statement).bind(i64::from(height)).execute(&mut *tx).await?;
+        }
+        tx.commit().await.with_context(|| format!("rolling back to height {height}"))
+    }
+
+    /// Record an attributed deposit as pending, unless it is already known
+    pub async fn record_deposit(&self, deposit: &Deposit) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO deposits (id, txid, recipient, amount, height, status)
+             VALUES ($1, $2, $3, $4, $5, 'pending') ON CONFLICT DO NOTHING",
+        )
+        .bind(deposit.idempotency_id())
+        .bind(&deposit.txid)
+        .bind(deposit.recipient.to_string())
+        .bind(deposit.amount as i64)
+        .bind(i64::from(deposit.block_height))
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("recording deposit {}", deposit.txid))?;
+        Ok(())
+    }
+
+    pub async fn is_minted(&self, deposit: &Deposit) -> Result<bool> {
+        let status: Option<(String,)> = sqlx::query_as("SELECT status FROM deposits WHERE id = $1")
+            .bind(deposit.idempotency_id())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(status.is_some_and(|(status,)| status == "minted"))
+    }
+
+    /// The deposit recorded for `txid`, if scanning has found it
+    pub async fn deposit_state(&self, txid: &str) -> Result<Option<DepositState>> {
+        let state = sqlx::query_as("SELECT status, recipient, amount, height, signature FROM deposits WHERE txid = $1")
+            .bind(txid)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(state)
+    }
+
+    /// Deposits attributed but not yet minted, as of the last run
+    pub async fn pending_deposits(&self) -> Result<Vec<Deposit>> {
+        let rows: Vec<(String, String, i64, i64)> =
+            sqlx::query_as("SELECT txid, recipient, amount, height FROM deposits WHERE status = 'pending' ORDER BY height")
+                .fetch_all(&self.pool)
+                .await?;
+        rows.into_iter().map(deposit_from_row).collect()
+    }
+
+    /// Pending deposits first recorded more than `min_age_secs` ago
+    pub async fn stuck_deposits(&self, min_age_secs: u64) -> Result<Vec<Deposit>> {
+        let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(
+            "SELECT txid, recipient, amount, height FROM deposits
+             WHERE status = 'pending' AND created_at < unixepoch() - $1 ORDER BY height",
+        )
+        .bind(min_age_secs as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(deposit_from_row).collect()
+    }
+
+    /// Deposit attributed from transaction `txid`, minted or not
+    pub async fn deposit(&self, txid: &str) -> Result<Option<Deposit>> {
+        let row: Option<(String, String, i64, i64)> =
+            sqlx::query_as("SELECT txid, recipient, amount, height FROM deposits WHERE txid = $1")
+                .bind(txid)
+                .fetch_optional(&self.pool)
+                .await?;
+        row.map(deposit_from_row).transpose()
+    }
+
+    /// Mark a deposit minted, by the mint transaction `signature` when known
+    pub async fn mark_minted(&self, idempotency_id: &str, signature: Option<&Signature>) -> Result<()> {
+        sqlx::query(
+            "UPDATE deposits SET status = 'minted', signature = COALESCE(CAST($1 AS TEXT), signature),
+                 updated_at = unixepoch()
+             WHERE id = $2",
+        )
+        .bind(signature.map(Signature::to_string))
+        .bind(idempotency_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record that `guardian` attested to the operation `idempotency_id`
+    pub async fn record_attestation(&self, idempotency_id: &str, guardian: &Pubkey) -> Result<()> {
+        sqlx::query("INSERT INTO attestations (idempotency_id, guardian) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(idempotency_id)
+            .bind(guardian.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record a signed transaction before it is sent, so a crash mid-flight
+    /// leaves a signature to look up and the transaction to send again
+    pub async fn record_submission(&self, idempotency_id: &str, transaction: &Transaction) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO submissions (signature, idempotency_id, status, transaction) VALUES ($1, $2, 'submitted', $3)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(transaction.signatures[0].to_string())
+        .bind(idempotency_id)
+        .bind(bincode::serialize(transaction)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Settle a submission as `confirmed`, `failed` or `expired`
+    pub async fn finish_submission(&self, signature: &Signature, status: &str, error: Option<&str>) -> Result<()> {
+        sqlx::query(
+            "UPDATE submissions SET status = $1, error = CAST($2 AS TEXT), updated_at = unixepoch() WHERE signature = $3",
+        )
+        .bind(status)
+        .bind(error)
+        .bind(signature.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Submissions sent but never settled, as (idempotency ID, signature,
+    /// transaction when it was kept)
+    pub async fn unsettled_submissions(&self) -> Result<Vec<(String, Signature, Option<Transaction>)>> {
+        let rows: Vec<(String, String, Option<Vec<u8>>)> =
+            sqlx::query_as("SELECT idempotency_id, signature, transaction FROM submissions WHERE status = 'submitted'")
+                .fetch_all(&self.pool)
+                .await?;
+        rows.into_iter()
+            .map(|(id, signature, transaction)| {
+                let transaction = transaction
+                    .map(|bytes| bincode::deserialize(&bytes))
+                    .transpose()
+                    .context("stored transaction is malformed")?;
+                Ok((id, signature.parse().context("stored signature is malformed")?, transaction))
+            })
+            .collect()
+    }
+
+    /// Record a withdrawal's payout as `status`, unless it is already known; the
+    /// amount is what the withdrawal is owed until its fee share is known
+    pub async fn record_payout(&self, withdrawal: &Withdrawal, status: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO payouts (id, withdrawal_request, zec_address, amount, status)
+             VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING",
+        )
+        .bind(withdrawal.idempotency_id())
+        .bind(withdrawal.request.to_string())
+        .bind(&withdrawal.zec_address)
+        .bind(withdrawal.net_amount as i64)
+        .bind(status)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("recording payout of withdrawal {}", withdrawal.nonce))?;
+        Ok(())
+    }
+
+    pub async fn payout(&self, idempotency_id: &str) -> Result<Option<Payout>> {
+        let row: Option<PayoutRow> = sqlx::query_as(&format!("SELECT {PAYOUT_COLUMNS} FROM payouts WHERE id = $1"))
+            .bind(idempotency_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(PayoutRow::into_payout))
+    }
+
+    /// Every payout not yet finalized or given up on, oldest first
+    pub async fn open_payouts(&self) -> Result<Vec<Payout>> {
+        let rows: Vec<PayoutRow> = sqlx::query_as(&format!(
+            "SELECT {PAYOUT_COLUMNS} FROM payouts WHERE status NOT IN ('finalized', 'invalid', 'rejected') ORDER BY created_at"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(PayoutRow::into_payout).collect())
+    }
+
+    /// Payouts waiting for operators' approval, oldest first, with who has
+    /// approved each so far
+    pub async fn payouts_awaiting_approval(&self) -> Result<Vec<(Payout, Vec<String>)>> {
+        let rows: Vec<PayoutRow> = sqlx::query_as(&format!(
+            "SELECT {PAYOUT_COLUMNS} FROM payouts WHERE status = 'needs_approval' ORDER BY created_at"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        let mut payouts = Vec::new();
+        for payout in rows.into_iter().map(PayoutRow::into_payout) {
+            let approvers: Vec<(String,)> = sqlx::query_as(
+                "SELECT operator FROM payout_approvals
+                 WHERE idempotency_id = $1 AND decision = 'approved' ORDER BY decided_at",
+            )
+            .bind(&payout.id)
+            .fetch_all(&self.pool)
+            .await?;
+            payouts.push((payout, approvers.into_iter().map(|(operator,)| operator).collect()));
+        }
+        Ok(payouts)
+    }
+
+    /// Record `operator`'s approval of a payout in `needs_approval`, releasing
+    /// it to `pending` once `required` distinct operators have approved;
+    /// returns how many have
+    pub async fn approve_payout(&self, idempotency_id: &str, operator: &str, required: usize) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        ensure_awaiting_approval(&mut tx, idempotency_id).await?;
+        sqlx::query(
+            "INSERT INTO payout_approvals (idempotency_id, operator, decision) VALUES ($1, $2, 'approved')
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(idempotency_id)
+        .bind(operator)
+        .execute(&mut *tx)
+        .await?;
+        let (approvals,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM payout_approvals WHERE idempotency_id = $1 AND decision = 'approved'",
+        )
+        .bind(idempotency_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if approvals as usize >= required {
+            sqlx::query("UPDATE payouts SET status = 'pending', updated_at = unixepoch() WHERE id = $1")
+                .bind(idempotency_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(approvals as usize)
+    }
+
+    /// Mark a payout in `needs_approval` `rejected`, so it is never paid
+    pub async fn reject_payout(&self, idempotency_id: &str, operator: &str, reason: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        ensure_awaiting_approval(&mut tx, idempotency_id).await?;
+        sqlx::query(
+            "INSERT INTO payout_approvals (idempotency_id, operator, decision, reason)
+             VALUES ($1, $2, 'rejected', $3)
+             ON CONFLICT (idempotency_id, operator) DO UPDATE SET
+                 decision = excluded.decision, reason = excluded.reason, decided_at = unixepoch()",
+        )
+        .bind(idempotency_id)
+        .bind(operator)
+        .bind(reason)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE payouts SET status = 'rejected', updated_at = unixepoch() WHERE id = $1")
+            .bind(idempotency_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Mark a payout `sending` for `amount` after its `fee` share, before it is
+    /// handed to the wallet
+    pub async fn start_payout(&self, idempotency_id: &str, amount: u64, fee: u64) -> Result<()> {
+        sqlx::query(
+            "UPDATE payouts SET status = 'sending', amount = $1, fee = $2, operation_id = NULL, updated_at = unixepoch()
+             WHERE id = $3",
+        )
+        .bind(amount as i64)
+        .bind(fee as i64)
+        .bind(idempotency_id)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("starting payout {idempotency_id}"))?;
+        Ok(())
+    }
+
+    pub async fn set_expiry_height(&self, idempotency_id: &str, expiry_height: u32) -> Result<()> {
+        sqlx::query("UPDATE payouts SET expiry_height = $1, updated_at = unixepoch() WHERE id = $2")
+            .bind(i64::from(expiry_height))
+            .bind(idempotency_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record that the payout transaction `txid` expired unmined, and put the
+    /// payout back to `pending` to be sent again
+    pub async fn expire_payout(&self, idempotency_id: &str, txid: &str, expiry_height: u32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO expired_payouts (zcash_txid, payout_id, expiry_height) VALUES ($1, $2, $3)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(txid)
+        .bind(idempotency_id)
+        .bind(i64::from(expiry_height))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE payouts SET status = 'pending', operation_id = NULL, zcash_txid = NULL, output_index = NULL,
+                 expiry_height = NULL, rebroadcasts = rebroadcasts + 1, updated_at = unixepoch()
+             WHERE id = $1 AND zcash_txid = $2",
+        )
+        .bind(idempotency_id)
+        .bind(txid)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await.with_context(|| format!("expiring payout {idempotency_id}"))
+    }
+
+    pub async fn set_output_index(&self, idempotency_id: &str, output_index: u32) -> Result<()> {
+        sqlx::query("UPDATE payouts SET output_index = $1, updated_at = unixepoch() WHERE id = $2")
+            .bind(i64::from(output_index))
+            .bind(idempotency_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Outputs of the payout transaction `txid` already matched to a withdrawal
+    pub async fn payout_outputs(&self, txid: &str) -> Result<Vec<u32>> {
+        let rows: Vec<(i64,)> =
+            sqlx::query_as("SELECT output_index FROM payouts WHERE zcash_txid = $1 AND output_index IS NOT NULL")
+                .bind(txid)
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(rows.into_iter().map(|(index,)| u32::try_from(index)).collect::<Result<_, _>>()?)
+    }
+
+    /// Move a payout to `status`, keeping any other fields already recorded
+    pub async fn update_payout(
+        &self,
+        idempotency_id: &str,
+        status: &str,
+        operation_id: Option<&str>,
+        zcash_txid: Option<&str>,
+        height: Option<u32>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE payouts SET status = $1, operation_id = COALESCE(CAST($2 AS TEXT), operation_id),
+                 zcash_txid = COALESCE(CAST($3 AS TEXT), zcash_txid), height = COALESCE(CAST($4 AS BIGINT), height),
+                 updated_at = unixepoch()
+             WHERE id = $5",
+        )
+        .bind(status)
+        .bind(operation_id)
+        .bind(zcash_txid)
+        .bind(height.map(i64::from))
+        .bind(idempotency_id)
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("updating payout {idempotency_id}"))?;
+        Ok(())
+    }
+
+    /// Mark a payout finalized on Solana, by `signature` when known
+    pub async fn mark_finalized(&self, idempotency_id: &str, signature: Option<&Signature>) -> Result<()> {
+        sqlx::query(
+            "UPDATE payouts SET status = 'finalized', signature = COALESCE(CAST($1 AS TEXT), signature),
+                 updated_at = unixepoch()
+             WHERE id = $2",
+        )
+        .bind(signature.map(Signature::to_string))
+        .bind(idempotency_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Height at which the transaction `txid` was seen spending a bridge note
+    pub async fn spend_height(&self, txid: &str) -> Result<Option<u32>> {
+        let height: Option<(i64,)> = sqlx::query_as(
+            "SELECT spent_height FROM sapling_notes WHERE spent_txid = $1
+             UNION ALL
+             SELECT spent_height FROM orchard_notes WHERE spent_txid = $1
+             LIMIT 1",
+        )
+        .bind(txid)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(height.map(|(height,)| u32::try_from(height)).transpose()?)
+    }
+
+    /// Pool and value of every received note not yet seen spent
+    pub async fn unspent_notes(&self) -> Result<Vec<(Pool, u64)>> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT 'sapling', value FROM sapling_notes WHERE spent_txid IS NULL
+             UNION ALL
+             SELECT 'orchard', value FROM orchard_notes WHERE spent_txid IS NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(pool, value)| {
+                let pool = if pool == "orchard" { Pool::Orchard } else { Pool::Sapling };
+                (pool, value as u64)
+            })
+            .collect())
+    }
+
+    /// Height of the most recent note received, in either pool
+    pub async fn latest_note_height(&self) -> Result<Option<u32>> {
+        let height: Option<(i64,)> = sqlx::query_as(
+            "SELECT height FROM sapling_notes UNION ALL SELECT height FROM orchard_notes ORDER BY height DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(height.map(|(height,)| u32::try_from(height)).transpose()?)
+    }
+
+    /// Take or renew the leader lease for `holder` until `lease_secs` from now,
+    /// returning whether `holder` is the leader
+    ///
+    /// The lease passes to another holder only once it has expired.
+    pub async fn acquire_lease(&self, holder: &str, lease_secs: u64) -> Result<bool> {
+        let result = sqlx::query(
+            "INSERT INTO leader_lease (id, holder, expires_at, renewed_at) VALUES (1, $1, unixepoch() + $2, unixepoch())
+             ON CONFLICT (id) DO UPDATE SET
+                 holder = excluded.holder, expires_at = excluded.expires_at, renewed_at = excluded.renewed_at
+             WHERE leader_lease.holder = excluded.holder OR leader_lease.expires_at < unixepoch()",
+        )
+        .bind(holder)
+        .bind(lease_secs as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Give up the leader lease if `holder` has it, so another instance takes
+    /// over without waiting for it to expire
+    pub async fn release_lease(&self, holder: &str) -> Result<()> {
+        sqlx::query("DELETE FROM leader_lease WHERE holder = $1")
+            .bind(holder)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Total value of received notes not yet seen spent, across both pools
+    pub async fn unspent_balance(&self) -> Result<u64> {
+        let (balance,): (i64,) = sqlx::query_as(
+            "SELECT CAST(COALESCE(SUM(value), 0) AS BIGINT) FROM (
+                 SELECT value FROM sapling_notes WHERE spent_txid IS NULL
+                 UNION ALL
+                 SELECT value FROM orchard_notes WHERE spent_txid IS NULL
+             ) unspent",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(balance as u64)
+    }
+
+    /// Last bridge transaction the burn listener handled
+    pub async fn event_cursor(&self) -> Result<Option<Signature>> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT signature FROM event_cursor WHERE id = 1")
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|(signature,)| Ok(signature.parse()?)).transpose()
+    }
+
+    pub async fn save_event_cursor(&self, signature: &str, slot: u64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO event_cursor (id, signature, slot) VALUES (1, $1, $2)
+             ON CONFLICT (id) DO UPDATE SET signature = excluded.signature, slot = excluded.slot",
+        )
+        .bind(signature)
+        .bind(slot as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn last_audit_entry(&self) -> Result<Option<AuditEntry>> {
+        Ok(sqlx::query_as(&format!("SELECT {AUDIT_COLUMNS} FROM audit_log ORDER BY seq DESC LIMIT 1"))
+            .fetch_optional(&self.pool)
+            .await?)
+    }
+
+    /// Append `entry`, unless another entry already took its `seq`; returns
+    /// whether it was appended
+    pub async fn append_audit_entry(&self, entry: &AuditEntry) -> Result<bool> {
+        let result = sqlx::query(&format!(
+            "INSERT INTO audit_log ({AUDIT_COLUMNS}) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT DO NOTHING"
+        ))
+        .bind(entry.seq)
+        .bind(entry.at)
+        .bind(&entry.action)
+        .bind(&entry.detail)
+        .bind(&entry.prev_hash)
+        .bind(&entry.hash)
+        .bind(&entry.signer)
+        .bind(&entry.signature)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// The whole audit log, in order
+    pub async fn audit_entries(&self) -> Result<Vec<AuditEntry>> {
+        Ok(sqlx::query_as(&format!("SELECT {AUDIT_COLUMNS} FROM audit_log ORDER BY seq"))
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    /// Detail of the latest audit entry for `action`
+    pub async fn last_audit_detail(&self, action: &str) -> Result<Option<String>> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT detail FROM audit_log WHERE action = $1 ORDER BY seq DESC LIMIT 1")
+                .bind(action)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row.map(|(detail,)| detail))
+    }
+}
+
+fn deposit_from_row((txid, recipient, amount, height): (String, String, i64, i64)) -> Result<Deposit> {
+    Ok(Deposit {
+        zcash_txid: txid_bytes(&txid)?,
+        recipient: recipient.parse().context("stored deposit recipient is not a public key")?,
+        amount: amount as u64,
+        block_height: u32::try_from(height)?,
+        confirmations: 0,  // Not tracked once stored
+        txid,
+    })
+}
+
+async fn ensure_awaiting_approval(tx: &mut sqlx::Transaction<'_, Any>, idempotency_id: &str) -> Result<()> {
+    let status: Option<(String,)> = sqlx::query_as("SELECT status FROM payouts WHERE id = $1")
+        .bind(idempotency_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    match status {
+        Some((status,)) if status == "needs_approval" => Ok(()),
+        Some((status,)) => anyhow::bail!("payout {idempotency_id} is {status}, not awaiting approval"),
+        None => anyhow::bail!("no payout {idempotency_id}"),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::deposit::tests::{note, txid};
+
+    /// A fresh database in the temp directory, one per test
+    pub(crate) async fn open_temp(name: &str) -> Store {
+        let path = std::env::temp_dir().join(format!("solz-relayer-{name}-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Store::open(&DatabaseConfig { path, url_env: None }).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn only_spends_of_our_notes_are_own_transactions() {
+        let store = open_temp("own-transactions").await;
+        let ours = note(1, 0, 50_000);
+        store.insert_note(&ours).await.unwrap();
+
+        // A deposit that spends the sender's own notes, and a payout spending ours
+        let spends = [
+            Spend { pool: Pool::Sapling, nullifier: [0x55; 32], txid: txid(2), height: 101 },
+            Spend { pool: Pool::Orchard, nullifier: [0x66; 32], txid: txid(2), height: 101 },
+            Spend { pool: Pool::Sapling, nullifier: ours.nullifier, txid: txid(3), height: 101 },
+        ];
+        for spend in &spends {
+            store.mark_spent(spend).await.unwrap();
+        }
+        let own = store.own_transactions(&spends).await.unwrap();
+        assert_eq!(own, HashSet::from([txid(3)]));
+
+        // Rescanning finds the note already spent and still knows the payout as ours
+        assert!(!store.mark_spent(&spends[2]).await.unwrap());
+        assert_eq!(store.own_transactions(&spends).await.unwrap(), HashSet::from([txid(3)]));
+    }
+
+    fn withdrawal(nonce: u64) -> Withdrawal {
+        Withdrawal {
+            request: Pubkey::new_unique(),
+            nonce,
+            net_amount: 40_000,
+            zec_address: "zs1test".into(),
+            memo: Vec::new(),
+            unlock_slot: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn roll_back_forgets_orphaned_blocks_only() {
+        let store = open_temp("roll-back").await;
+        let kept = note(1, 0, 50_000);
+        let orphaned = ReceivedNote { height: 105, ..note(2, 0, 60_000) };
+        store.insert_note(&kept).await.unwrap();
+        store.insert_note(&orphaned).await.unwrap();
+        let unattributed = ReceivedNote { height: 105, ..note(3, 0, 70_000) };
+        store.quarantine(&[unattributed], "no recipient").await.unwrap();
+        let blocks: Vec<_> = (100..=106).map(|height| (height, format!("{height:064x}"))).collect();
+        store.insert_blocks(&blocks).await.unwrap();
+        let trees = TreeState { sapling: "00".into(), orchard: "00".into() };
+        store.save_checkpoint(100, &blocks[0].1, &trees).await.unwrap();
+        store.save_checkpoint(106, &blocks[6].1, &trees).await.unwrap();
+
+        // Our note spent in an orphaned block, a pending and a minted deposit above the fork
+        let spend = Spend { pool: Pool::Sapling, nullifier: kept.nullifier, txid: txid(4), height: 106 };
+        assert!(store.mark_spent(&spend).await.unwrap());
+        let recipient = Pubkey::new_unique();
+        let pending = Deposit::from_notes(&[orphaned.clone()], &[Some(recipient)]).unwrap();
+        let minted = Deposit::from_notes(&[ReceivedNote { height: 105, ..note(5, 0, 80_000) }], &[Some(recipient)])
+            .unwrap();
+        store.record_deposit(&pending).await.unwrap();
+        store.record_deposit(&minted).await.unwrap();
+        store.mark_minted(&minted.idempotency_id(), None).await.unwrap();
+
+        // Payouts confirmed below and above the fork
+        for (nonce, height) in [(1, 100), (2, 106)] {
+            let id = withdrawal(nonce).idempotency_id();
+            store.record_payout(&withdrawal(nonce), "pending").await.unwrap();
+            let sent_in = txid(10 + nonce as u8);
+            store.update_payout(&id, "confirmed", Some("op"), Some(sent_in.as_str()), Some(height)).await.unwrap();
+        }
+
+        store.roll_back(102).await.unwrap();
+
+        assert_eq!(store.unspent_notes().await.unwrap(), vec![(Pool::Sapling, 50_000)]);
+        assert_eq!(store.spend_height(&txid(4)).await.unwrap(), None);
+        assert_eq!(store.latest_note_height().await.unwrap(), Some(100));
+        assert!(store.quarantined(&txid(3)).await.unwrap().is_none());
+        assert!(store.deposit(&pending.txid).await.unwrap().is_none());
+        assert!(store.is_minted(&minted).await.unwrap());
+        assert_eq!(store.latest_block().await.unwrap().map(|(height, _)| height), Some(102));
+        assert_eq!(store.checkpoint_height().await.unwrap(), Some(100));
+        let below = store.payout(&withdrawal(1).idempotency_id()).await.unwrap().unwrap();
+        assert_eq!((below.status.as_str(), below.height), ("confirmed", Some(100)));
+        let above = store.payout(&withdrawal(2).idempotency_id()).await.unwrap().unwrap();
+        assert_eq!((above.status.as_str(), above.height), ("sent", None));
+        assert_eq!(above.zcash_txid, Some(txid(12)));
+    }
+
+    /// Let the leader lease lapse, as if its holder had stopped renewing it
+    async fn expire_lease(store: &Store) {
+        sqlx::query("UPDATE leader_lease SET expires_at = unixepoch() - 1")
+            .execute(&store.pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn lease_is_held_by_one_instance_at_a_time() {
+        let store = open_temp("lease-held").await;
+
+        assert!(store.acquire_lease("relayer-a", 30).await.unwrap());
+        assert!(!store.acquire_lease("relayer-b", 30).await.unwrap());
+        // Renewing keeps it with its holder
+        assert!(store.acquire_lease("relayer-a", 30).await.unwrap());
+        assert!(!store.acquire_lease("relayer-b", 30).await.unwrap());
+
+        // Releasing by anyone else leaves it held
+        store.release_lease("relayer-b").await.unwrap();
+        assert!(!store.acquire_lease("relayer-b", 30).await.unwrap());
+        store.release_lease("relayer-a").await.unwrap();
+        assert!(store.acquire_lease("relayer-b", 30).await.unwrap());
+        assert!(!store.acquire_lease("relayer-a", 30).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn expired_lease_is_taken_over() {
+        let store = open_temp("lease-expired").await;
+        assert!(store.acquire_lease("relayer-a", 30).await.unwrap());
+
+        expire_lease(&store).await;
+        assert!(store.acquire_lease("relayer-b", 30).await.unwrap());
+        // The old leader finds it lost the lease when it next renews
+        assert!(!store.acquire_lease("relayer-a", 30).await.unwrap());
+        assert!(store.acquire_lease("relayer-b", 30).await.unwrap());
+
+        // A holder whose lease lapsed can renew it while no one else took it
+        expire_lease(&store).await;
+        assert!(store.acquire_lease("relayer-b", 30).await.unwrap());
+        assert!(!store.acquire_lease("relayer-a", 30).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn unexpired_lease_is_not_taken_over() {
+        let store = open_temp("lease-unexpired").await;
+        assert!(store.acquire_lease("relayer-a", 30).await.unwrap());
+
+        let (holder, expires_at, renewed_at): (String, i64, i64) =
+            sqlx::query_as("SELECT holder, expires_at, renewed_at FROM leader_lease")
+                .fetch_one(&store.pool)
+                .await
+                .unwrap();
+        assert_eq!((holder.as_str(), expires_at - renewed_at), ("relayer-a", 30));
+
+        // A failed takeover doesn't touch the lease
+        assert!(!store.acquire_lease("relayer-b", 300).await.unwrap());
+        let (holder, after): (String, i64) = sqlx::query_as("SELECT holder, expires_at FROM leader_lease")
+            .fetch_one(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!((holder.as_str(), after), ("relayer-a", expires_at));
+    }
+
+    /// Withdrawal `nonce`'s payout, recorded `pending`
+    async fn pending_payout(store: &Store, nonce: u64) -> Withdrawal {
+        let withdrawal = Withdrawal {
+            request: Pubkey::new_unique(),
+            nonce,
+            net_amount: 1_000_000,
+            zec_address: "t1SmxPaFcF5kYFn5ctgCt7QiaXzpTZyF4kG".to_string(),
+            memo: Vec::new(),
+            unlock_slot: 0,
+        };
+        store.record_payout(&withdrawal, "pending").await.unwrap();
+        withdrawal
+    }
+
+    /// Hand a payout to the wallet and record its broadcast as `zcash_txid`
+    async fn send(store: &Store, id: &str, zcash_txid: &str, expiry_height: u32) {
+        store.start_payout(id, 990_000, 10_000).await.unwrap();
+        store.update_payout(id, "sending", Some("opid-1"), None, None).await.unwrap();
+        store.update_payout(id, "sent", None, Some(zcash_txid), None).await.unwrap();
+        store.set_expiry_height(id, expiry_height).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn expired_payout_goes_back_to_pending() {
+        let store = open_temp("payout-expired").await;
+        let id = pending_payout(&store, 1).await.idempotency_id();
+        send(&store, &id, &txid(1), 2_000_040).await;
+
+        store.expire_payout(&id, &txid(1), 2_000_040).await.unwrap();
+        let payout = store.payout(&id).await.unwrap().unwrap();
+        assert_eq!(payout.status, "pending");
+        assert_eq!(payout.rebroadcasts, 1);
+        assert_eq!((payout.operation_id, payout.zcash_txid), (None, None));
+        assert_eq!((payout.output_index, payout.expiry_height), (None, None));
+        let (expired,): (String,) = sqlx::query_as("SELECT zcash_txid FROM expired_payouts WHERE payout_id = $1")
+            .bind(&id)
+            .fetch_one(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!(expired, txid(1));
+    }
+
+    #[tokio::test]
+    async fn rebroadcast_payout_is_not_expired_by_its_old_transaction() {
+        let store = open_temp("payout-rebroadcast").await;
+        let id = pending_payout(&store, 2).await.idempotency_id();
+        send(&store, &id, &txid(1), 2_000_040).await;
+        store.expire_payout(&id, &txid(1), 2_000_040).await.unwrap();
+
+        // Sent again as a new transaction
+        send(&store, &id, &txid(2), 2_000_080).await;
+        // Expiring the first transaction again, say after a restart, leaves the new one alone
+        store.expire_payout(&id, &txid(1), 2_000_040).await.unwrap();
+        let payout = store.payout(&id).await.unwrap().unwrap();
+        assert_eq!(payout.status, "sent");
+        assert_eq!(payout.zcash_txid, Some(txid(2)));
+        assert_eq!(payout.rebroadcasts, 1);
+
+        // Once the new transaction is mined the payout is confirmed, never pending again
+        store.update_payout(&id, "confirmed", None, None, Some(2_000_050)).await.unwrap();
+        store.expire_payout(&id, &txid(1), 2_000_040).await.unwrap();
+        assert_eq!(store.payout(&id).await.unwrap().unwrap().status, "confirmed");
+        assert_eq!(store.open_payouts().await.unwrap().len(), 1);
+    }
+
+    /// Outputs of `txid` matched to a withdrawal, in order
+    async fn matched_outputs(store: &Store, txid: &str) -> Vec<u32> {
+        let mut outputs = store.payout_outputs(txid).await.unwrap();
+        outputs.sort();
+        outputs
+    }
+
+    #[tokio::test]
+    async fn expired_payout_frees_the_output_it_was_matched_to() {
+        let store = open_temp("payout-expired-output").await;
+        let first = pending_payout(&store, 1).await.idempotency_id();
+        let second = pending_payout(&store, 2).await.idempotency_id();
+        send(&store, &first, &txid(1), 2_000_040).await;
+        send(&store, &second, &txid(1), 2_000_040).await;
+        store.set_output_index(&first, 0).await.unwrap();
+        store.set_output_index(&second, 1).await.unwrap();
+        assert_eq!(matched_outputs(&store, &txid(1)).await, [0, 1]);
+
+        store.expire_payout(&first, &txid(1), 2_000_040).await.unwrap();
+        store.expire_payout(&second, &txid(1), 2_000_040).await.unwrap();
+        assert!(matched_outputs(&store, &txid(1)).await.is_empty());
+        // Paid again by the next batch, matched afresh to its outputs
+        send(&store, &second, &txid(2), 2_000_080).await;
+        store.set_output_index(&second, 0).await.unwrap();
+        assert_eq!(matched_outputs(&store, &txid(2)).await, [0]);
+        assert_eq!(store.payout(&first).await.unwrap().unwrap().status, "pending");
+    }
+
+    #[tokio::test]
+    async fn payout_seen_again_is_not_paid_again() {
+        let store = open_temp("payout-seen-again").await;
+        let withdrawal = pending_payout(&store, 3).await;
+        let id = withdrawal.idempotency_id();
+        send(&store, &id, &txid(1), 2_000_040).await;
+
+        // Every poll sees the withdrawal until it is finalized, and records its payout again
+        store.record_payout(&withdrawal, "pending").await.unwrap();
+        let payout = store.payout(&id).await.unwrap().unwrap();
+        assert_eq!(payout.status, "sent");
+        assert_eq!(payout.zcash_txid, Some(txid(1)));
+        assert_eq!((payout.amount, payout.fee), (990_000, 10_000));
+    }
+
+    #[tokio::test]
+    async fn payout_the_wallet_refused_goes_back_to_pending() {
+        let store = open_temp("payout-refused").await;
+        let id = pending_payout(&store, 4).await.idempotency_id();
+        store.start_payout(&id, 990_000, 10_000).await.unwrap();
+        store.update_payout(&id, "pending", None, None, None).await.unwrap();
+        let payout = store.payout(&id).await.unwrap().unwrap();
+        assert_eq!((payout.status.as_str(), payout.operation_id), ("pending", None));
+        assert_eq!(payout.rebroadcasts, 0);
+    }
+}
@@ -0,0 +1,46 @@
+use anyhow::{Context, Result};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::TelemetryConfig;
+
+/// Log to stderr and, with `[telemetry]`, export spans over OTLP
+///
+/// `RUST_LOG` filters both, so spans below the log level aren't exported
+/// either.
+pub fn init(config: Option<&TelemetryConfig>) -> Result<()> {
+    let otlp = match config {
+        Some(config) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(&config.otlp_endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                    "service.name",
+                    config.service_name.clone(),
+                )])))
+                .install_batch(runtime::Tokio)
+                .with_context(|| format!("starting the OTLP exporter to {}", config.otlp_endpoint))?;
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(tracing_subscriber::fmt::layer())
+        .with(otlp)
+        .init();
+    Ok(())
+}
+
+/// Flush spans still waiting to be exported
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
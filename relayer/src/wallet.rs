@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::PayoutConfig;
+use crate::rpc::JsonRpcClient;
+
+/// A zcashd-compatible wallet that holds the deposit address's spending key
+///
+/// The relayer only has viewing keys, so payouts are built and signed by the
+/// wallet with `z_sendmany`. Whatever the wallet sends is seen by the scanner
+/// as a spend of bridge notes, which is how payouts are confirmed.
+pub struct Wallet {
+    rpc: JsonRpcClient,
+}
+
+/// One output of a payout
+pub struct Recipient<'a> {
+    pub address: &'a str,
+    pub amount: u64,  // Zatoshis
+    pub memo: Option<&'a [u8]>,  // Shielded recipients only
+}
+
+/// An output of a transaction the wallet sent
+#[derive(Debug)]
+pub struct SentOutput {
+    pub address: String,
+    pub amount: u64,  // Zatoshis
+    pub index: u32,  // Sapling output, Orchard action or transparent output index
+}
+
+#[derive(Deserialize)]
+struct MergeResult {
+    #[serde(rename = "mergingNotes")]
+    merging_notes: u64,
+    #[serde(rename = "opid")]
+    operation_id: String,
+}
+
+/// Outcome of an asynchronous wallet operation
+#[derive(Debug, Clone)]
+pub enum Operation {
+    /// Still queued or building
+    Running,
+    /// Broadcast as the display-order txid
+    Succeeded(String),
+    /// Failed or cancelled; nothing was broadcast
+    Failed(String),
+    /// The wallet has no record of it, for instance after a restart
+    Unknown,
+}
+
+#[derive(Deserialize)]
+struct OperationStatus {
+    status: String,
+    #[serde(default)]
+    result: Option<OperationResult>,
+    #[serde(default)]
+    error: Option<OperationError>,
+}
+
+#[derive(Deserialize)]
+struct OperationResult {
+    txid: String,
+}
+
+#[derive(Deserialize)]
+struct OperationError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct ViewedTransaction {
+    outputs: Vec<ViewedOutput>,
+}
+
+#[derive(Deserialize)]
+struct ViewedOutput {
+    #[serde(default)]
+    output: Option<u32>,  // Sapling
+    #[serde(default)]
+    action: Option<u32>,  // Orchard
+    #[serde(default)]
+    address: Option<String>,
+    outgoing: bool,
+    #[serde(rename = "valueZat")]
+    value_zat: u64,
+}
+
+#[derive(Deserialize)]
+struct DecodedTransaction {
+    #[serde(rename = "expiryheight", default)]
+    expiry_height: u32,
+    vout: Vec<TransparentOutput>,
+}
+
+#[derive(Deserialize)]
+struct TransparentOutput {
+    n: u32,
+    #[serde(rename = "valueZat")]
+    value_zat: u64,
+    #[serde(rename = "scriptPubKey")]
+    script_pub_key: ScriptPubKey,
+}
+
+#[derive(Deserialize)]
+struct ScriptPubKey {
+    #[serde(default)]
+    addresses: Vec<String>,
+}
+
+/// `z_sendmany` takes amounts in ZEC
+fn zec(zatoshis: u64) -> f64 {
+    zatoshis as f64 / 100_000_000.0
+}
+
+impl Wallet {
+    pub fn new(config: &PayoutConfig) -> Self {
+        Self {
+            rpc: JsonRpcClient::new(
+                &config.rpc_url,
+                Some((config.rpc_user.clone(), config.rpc_password.clone())),
+                "1.0",
+            ),
+        }
+    }
+
+    /// Start paying `recipients` from `from` in one transaction, returning the
+    /// wallet's operation ID
+    ///
+    /// Transparent recipients need `reveal_recipients`.
+    pub async fn send(&self, from: &str, recipients: &[Recipient<'_>], fee: u64, reveal_recipients: bool) -> Result<String> {
+        let amounts: Vec<Value> = recipients
+            .iter()
+            .map(|recipient| {
+                let mut amount = json!({ "address": recipient.address, "amount": zec(recipient.amount) });
+                if let Some(memo) = recipient.memo {
+                    amount["memo"] = Value::String(hex::encode(memo));
+                }
+                amount
+            })
+            .collect();
+        let policy = if reveal_recipients {
+            "AllowRevealedRecipients"
+        } else {
+            "AllowRevealedAmounts"
+        };
+        self.rpc
+            .call("z_sendmany", json!([from, amounts, 1, zec(fee), policy]))
+            .await
+    }
+
+    /// Start merging up to `limit` notes received by `address` into one note to
+    /// the same address, returning the operation ID and the number of notes merged
+    ///
+    /// zcashd needs `-experimentalfeatures -zmergetoaddress` for this, and
+    /// merges transparent and Sapling funds only.
+    pub async fn merge(&self, address: &str, limit: usize, fee: u64) -> Result<(String, u64)> {
+        let merge: MergeResult = self
+            .rpc
+            .call("z_mergetoaddress", json!([[address], address, zec(fee), 0, limit]))
+            .await?;
+        Ok((merge.operation_id, merge.merging_notes))
+    }
+
+    /// Outputs of `txid` paying others: shielded outputs as the wallet
+    /// recorded them, and transparent outputs as decoded from the transaction
+    pub async fn outputs(&self, txid: &str) -> Result<Vec<SentOutput>> {
+        let viewed: ViewedTransaction = self.rpc.call("z_viewtransaction", json!([txid])).await?;
+        let mut outputs: Vec<SentOutput> = viewed
+            .outputs
+            .into_iter()
+            .filter(|output| output.outgoing)
+            .filter_map(|output| {
+                Some(SentOutput {
+                    address: output.address?,
+                    amount: output.value_zat,
+                    index: output.output.or(output.action)?,
+                })
+            })
+            .collect();
+
+        let decoded: DecodedTransaction = self.rpc.call("getrawtransaction", json!([txid, 1])).await?;
+        outputs.extend(decoded.vout.into_iter().filter_map(|output| {
+            Some(SentOutput {
+                address: output.script_pub_key.addresses.into_iter().next()?,
+                amount: output.value_zat,
+                index: output.n,
+            })
+        }));
+        Ok(outputs)
+    }
+
+    /// Height after which `txid` can no longer be mined, 0 if it never expires
+    pub async fn expiry_height(&self, txid: &str) -> Result<u32> {
+        let decoded: DecodedTransaction = self.rpc.call("getrawtransaction", json!([txid, 1])).await?;
+        Ok(decoded.expiry_height)
+    }
+
+    pub async fn operation(&self, operation_id: &str) -> Result<Operation> {
+        let statuses: Vec<OperationStatus> = self.rpc.call("z_getoperationstatus", json!([[operation_id]])).await?;
+        let Some(status) = statuses.into_iter().next() else {
+            return Ok(Operation::Unknown);
+        };
+        Ok(match status.status.as_str() {
+            "success" => match status.result {
+                Some(result) => Operation::Succeeded(result.txid),
+                None => Operation::Failed("operation succeeded without a txid".to_string()),
+            },
+            "failed" => Operation::Failed(status.error.map_or_else(|| "failed".to_string(), |error| error.message)),
+            "cancelled" => Operation::Failed("cancelled".to_string()),
+            _ => Operation::Running,
+        })
+    }
+
+    /// Poll `operation_id` until it finishes or `timeout` passes
+    pub async fn wait(&self, operation_id: &str, timeout: Duration) -> Result<Operation> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let operation = self.operation(operation_id).await?;
+            if !matches!(operation, Operation::Running) || tokio::time::Instant::now() >= deadline {
+                return Ok(operation);
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::cmp::Reverse;
+
+use zcash_addr::Receiver;
+
+use crate::deposit::Pool;
+
+/// Zatoshis charged per logical action
+pub const MARGINAL_FEE: u64 = 5_000;
+
+/// Logical actions every transaction pays for, however small
+pub const GRACE_ACTIONS: u64 = 2;
+
+/// Fee of the smallest possible transaction
+pub const MINIMUM_FEE: u64 = MARGINAL_FEE * GRACE_ACTIONS;
+
+const P2PKH_STANDARD_INPUT_SIZE: u64 = 150;
+const P2PKH_STANDARD_OUTPUT_SIZE: u64 = 34;
+const P2SH_OUTPUT_SIZE: u64 = 32;
+
+/// What a transaction contains, as far as its ZIP-317 fee goes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TxShape {
+    pub transparent_input_bytes: u64,
+    pub transparent_output_bytes: u64,
+    pub sapling_spends: u64,
+    pub sapling_outputs: u64,
+    pub orchard_actions: u64,
+}
+
+impl TxShape {
+    pub fn logical_actions(&self) -> u64 {
+        let transparent = self
+            .transparent_input_bytes
+            .div_ceil(P2PKH_STANDARD_INPUT_SIZE)
+            .max(self.transparent_output_bytes.div_ceil(P2PKH_STANDARD_OUTPUT_SIZE));
+        transparent + self.sapling_spends.max(self.sapling_outputs) + self.orchard_actions
+    }
+
+    /// ZIP-317 conventional fee
+    pub fn conventional_fee(&self) -> u64 {
+        MARGINAL_FEE * self.logical_actions().max(GRACE_ACTIONS)
+    }
+}
+
+/// Expected shape of a payout of `total` zatoshis to `receivers`, funded by
+/// the bridge's unspent `notes`
+///
+/// The wallet picks the notes itself, so this assumes it spends the largest
+/// first and returns change to the pool of the largest it spent. Shielded
+/// bundles are padded to two outputs or actions, as the builder does.
+pub fn payout_shape(notes: &[(Pool, u64)], total: u64, receivers: &[Receiver]) -> TxShape {
+    let mut notes = notes.to_vec();
+    notes.sort_by_key(|&(_, value)| Reverse(value));
+
+    let mut shape = TxShape::default();
+    let (mut orchard_spends, mut orchard_outputs) = (0, 0);
+    let mut spent = 0;
+    let mut change_pool = None;
+    for (pool, value) in notes {
+        if spent >= total {
+            break;
+        }
+        spent += value;
+        change_pool.get_or_insert(pool);
+        match pool {
+            Pool::Sapling => shape.sapling_spends += 1,
+            Pool::Orchard => orchard_spends += 1,
+        }
+    }
+
+    for receiver in receivers {
+        match receiver {
+            Receiver::Orchard(_) => orchard_outputs += 1,
+            Receiver::Sapling(_) => shape.sapling_outputs += 1,
+            Receiver::P2pkh(_) => shape.transparent_output_bytes += P2PKH_STANDARD_OUTPUT_SIZE,
+            Receiver::P2sh(_) => shape.transparent_output_bytes += P2SH_OUTPUT_SIZE,
+        }
+    }
+    if spent > total {
+        match change_pool {
+            Some(Pool::Orchard) => orchard_outputs += 1,
+            Some(Pool::Sapling) | None => shape.sapling_outputs += 1,
+        }
+    }
+
+    if shape.sapling_spends + shape.sapling_outputs > 0 {
+        shape.sapling_outputs = shape.sapling_outputs.max(2);
+    }
+    let orchard_actions: u64 = orchard_spends.max(orchard_outputs);
+    if orchard_actions > 0 {
+        shape.orchard_actions = orchard_actions.max(2);
+    }
+    shape
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_transactions_pay_for_the_grace_actions() {
+        assert_eq!(TxShape::default().conventional_fee(), MINIMUM_FEE);
+        let one_action = TxShape { orchard_actions: 1, ..TxShape::default() };
+        assert_eq!(one_action.logical_actions(), 1);
+        assert_eq!(one_action.conventional_fee(), 10_000);
+    }
+
+    #[test]
+    fn fee_counts_each_kind_of_action() {
+        let shape = TxShape {
+            transparent_input_bytes: 3 * P2PKH_STANDARD_INPUT_SIZE,
+            transparent_output_bytes: P2PKH_STANDARD_OUTPUT_SIZE + P2SH_OUTPUT_SIZE,
+            sapling_spends: 1,
+            sapling_outputs: 2,
+            orchard_actions: 2,
+        };
+        // max(3 inputs, 2 outputs) + max(1 spend, 2 outputs) + 2 actions
+        assert_eq!(shape.logical_actions(), 7);
+        assert_eq!(shape.conventional_fee(), 35_000);
+    }
+
+    #[test]
+    fn payout_spends_the_largest_note_and_pads_its_bundle() {
+        let notes = [(Pool::Sapling, 30_000), (Pool::Orchard, 100_000)];
+        let shape = payout_shape(&notes, 50_000, &[Receiver::Orchard([0; 43])]);
+        // One Orchard spend, the payout and change in Orchard, no Sapling bundle
+        assert_eq!(shape, TxShape { orchard_actions: 2, ..TxShape::default() });
+        assert_eq!(shape.conventional_fee(), 10_000);
+    }
+
+    #[test]
+    fn exact_payout_has_no_change() {
+        let notes = [(Pool::Sapling, 10_000), (Pool::Sapling, 10_000)];
+        let shape = payout_shape(&notes, 20_000, &[Receiver::P2pkh([0; 20])]);
+        assert_eq!(
+            shape,
+            TxShape {
+                transparent_output_bytes: P2PKH_STANDARD_OUTPUT_SIZE,
+                sapling_spends: 2,
+                sapling_outputs: 2,  // Padding only
+                ..TxShape::default()
+            }
+        );
+        assert_eq!(shape.conventional_fee(), 15_000);
+    }
+
+    #[test]
+    fn change_goes_to_the_pool_of_the_largest_note_spent() {
+        let notes = [(Pool::Orchard, 5_000), (Pool::Sapling, 40_000)];
+        let shape = payout_shape(&notes, 20_000, &[Receiver::Orchard([0; 43])]);
+        // Sapling spend and change padded to two outputs; one Orchard output padded to two actions
+        assert_eq!(shape.sapling_spends, 1);
+        assert_eq!(shape.sapling_outputs, 2);
+        assert_eq!(shape.orchard_actions, 2);
+        assert_eq!(shape.conventional_fee(), 20_000);
+    }
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+use crate::BridgeError;
+
+/// Size of the header preceding the per-signature offsets
+const HEADER_LEN: usize = 2;
+/// Size of one `Ed25519SignatureOffsets` entry
+const OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+/// Instruction index meaning "this instruction" in the offsets entry
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Return the signers of `message` verified by the ed25519 program instruction
+/// immediately preceding the current one.
+///
+/// The ed25519 program has already checked the signatures by the time this runs;
+/// we only need to confirm that each verified message is ours. Entries pointing at
+/// data in other instructions are rejected so a signature over unrelated data can't
+/// be passed off as an attestation.
+pub fn verified_signers(instructions: &AccountInfo, message: &[u8]) -> Result<Vec<Pubkey>> {
+    let current_index = load_current_index_checked(instructions)?;
+    if current_index == 0 {
+        return Ok(Vec::new());
+    }
+
+    let ix = load_instruction_at_checked(current_index as usize - 1, instructions)?;
+    if ix.program_id != ed25519_program::ID {
+        return Ok(Vec::new());
+    }
+
+    let data = &ix.data;
+    require!(data.len() >= HEADER_LEN, BridgeError::InvalidSignatureInstruction);
+
+    let count = data[0] as usize;
+    require!(
+        data.len() >= HEADER_LEN + count * OFFSETS_LEN,
+        BridgeError::InvalidSignatureInstruction
+    );
+
+    let mut signers = Vec::with_capacity(count);
+    for i in 0..count {
+        let offsets = &data[HEADER_LEN + i * OFFSETS_LEN..HEADER_LEN + (i + 1) * OFFSETS_LEN];
+        let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+        let signature_offset = read_u16(0) as usize;
+        let signature_ix = read_u16(2);
+        let pubkey_offset = read_u16(4) as usize;
+        let pubkey_ix = read_u16(6);
+        let message_offset = read_u16(8) as usize;
+        let message_len = read_u16(10) as usize;
+        let message_ix = read_u16(12);
+
+        require!(
+            signature_ix == CURRENT_INSTRUCTION
+                && pubkey_ix == CURRENT_INSTRUCTION
+                && message_ix == CURRENT_INSTRUCTION,
+            BridgeError::InvalidSignatureInstruction
+        );
+        require!(
+            signature_offset + SIGNATURE_LEN <= data.len()
+                && pubkey_offset + PUBKEY_LEN <= data.len()
+                && message_offset + message_len <= data.len(),
+            BridgeError::InvalidSignatureInstruction
+        );
+
+        if &data[message_offset..message_offset + message_len] != message {
+            continue;
+        }
+
+        let pubkey = Pubkey::try_from(&data[pubkey_offset..pubkey_offset + PUBKEY_LEN])
+            .map_err(|_| error!(BridgeError::InvalidSignatureInstruction))?;
+        if !signers.contains(&pubkey) {
+            signers.push(pubkey);
+        }
+    }
+
+    Ok(signers)
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::alt_bn128::prelude::{
+    alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing,
+};
+
+use crate::BridgeError;
+
+/// Public inputs of the shielded deposit circuit: block hash (2), txid (2),
+/// amount (1) and Solana recipient (2), with 32-byte values split into 128-bit halves
+pub const NUM_PUBLIC_INPUTS: usize = 7;
+
+/// BN254 base field modulus, big-endian
+const FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// Groth16 proof over BN254, points encoded big-endian as the alt_bn128 syscalls expect
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Groth16Proof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+/// Verifying key of the shielded deposit circuit, which has the bridge's
+/// viewing key baked in
+#[account]
+pub struct VerifyingKey {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    pub ic: Vec<[u8; 64]>,  // One point per public input, plus the constant term
+    pub bump: u8,
+}
+
+impl VerifyingKey {
+    pub const LEN: usize = 64 + 128 * 3 + 4 + 64 * (NUM_PUBLIC_INPUTS + 1) + 1;
+
+    /// Check `proof` against `public_inputs`, each a big-endian scalar
+    pub fn verify(&self, proof: &Groth16Proof, public_inputs: &[[u8; 32]; NUM_PUBLIC_INPUTS]) -> Result<()> {
+        require!(
+            self.ic.len() == NUM_PUBLIC_INPUTS + 1,
+            BridgeError::InvalidVerifyingKey
+        );
+
+        // vk_x = ic[0] + sum(input_i * ic[i + 1])
+        let mut vk_x = self.ic[0];
+        for (input, point) in public_inputs.iter().zip(&self.ic[1..]) {
+            let product = alt_bn128_multiplication(&[point.as_slice(), input.as_slice()].concat())
+                .map_err(|_| error!(BridgeError::InvalidProof))?;
+            let sum = alt_bn128_addition(&[vk_x.as_slice(), product.as_slice()].concat())
+                .map_err(|_| error!(BridgeError::InvalidProof))?;
+            vk_x.copy_from_slice(&sum);
+        }
+
+        // e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+        let pairing_input = [
+            negate_g1(&proof.a).as_slice(),
+            proof.b.as_slice(),
+            self.alpha_g1.as_slice(),
+            self.beta_g2.as_slice(),
+            vk_x.as_slice(),
+            self.gamma_g2.as_slice(),
+            proof.c.as_slice(),
+            self.delta_g2.as_slice(),
+        ]
+        .concat();
+        let result = alt_bn128_pairing(&pairing_input).map_err(|_| error!(BridgeError::InvalidProof))?;
+        require!(
+            result.len() == 32 && result[31] == 1 && result[..31].iter().all(|byte| *byte == 0),
+            BridgeError::InvalidProof
+        );
+
+        Ok(())
+    }
+}
+
+/// Split a 32-byte value into two big-endian scalars holding its high and low 16 bytes
+pub fn split_u256(value: &[u8; 32]) -> [[u8; 32]; 2] {
+    let mut high = [0u8; 32];
+    let mut low = [0u8; 32];
+    high[16..].copy_from_slice(&value[..16]);
+    low[16..].copy_from_slice(&value[16..]);
+    [high, low]
+}
+
+/// Big-endian scalar holding `value`
+pub fn u64_scalar(value: u64) -> [u8; 32] {
+    let mut scalar = [0u8; 32];
+    scalar[24..].copy_from_slice(&value.to_be_bytes());
+    scalar
+}
+
+/// Negate a G1 point by replacing y with p - y
+fn negate_g1(point: &[u8; 64]) -> [u8; 64] {
+    let mut negated = *point;
+    if point.iter().all(|byte| *byte == 0) {
+        return negated;
+    }
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let diff = FIELD_MODULUS[i] as i16 - point[32 + i] as i16 - borrow;
+        borrow = (diff < 0) as i16;
+        negated[32 + i] = diff.rem_euclid(256) as u8;
+    }
+    negated
+}
@@ -0,0 +1,350 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+use crate::{BridgeState, DepositReceipt};
+
+/// Bridge state PDA of the bridge for `mint`
+pub fn bridge_state_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bridge_state", mint.as_ref()], &crate::ID).0
+}
+
+/// Bridge stats PDA of `bridge_state`
+pub fn bridge_stats_address(bridge_state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bridge_stats", bridge_state.as_ref()], &crate::ID).0
+}
+
+/// Guardian set PDA of `bridge_state`
+pub fn guardian_set_address(bridge_state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"guardian_set", bridge_state.as_ref()], &crate::ID).0
+}
+
+/// PDA holding the mint authority of `mint`
+pub fn mint_authority_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"mint_authority", mint.as_ref()], &crate::ID).0
+}
+
+/// Deposit receipt PDA for `zcash_txid`, in internal byte order
+pub fn deposit_receipt_address(bridge_state: &Pubkey, zcash_txid: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"deposit", bridge_state.as_ref(), zcash_txid.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Withdrawal request PDA for `nonce`
+pub fn withdrawal_request_address(bridge_state: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"withdrawal", bridge_state.as_ref(), &nonce.to_le_bytes()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Relayer registry PDA of `bridge_state`
+pub fn relayer_registry_address(bridge_state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"relayer_registry", bridge_state.as_ref()], &crate::ID).0
+}
+
+/// `RelayerStake` PDA of `relayer`
+pub fn relayer_stake_address(bridge_state: &Pubkey, relayer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"relayer_stake", bridge_state.as_ref(), relayer.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// `RelayerStats` PDA of `relayer`
+pub fn relayer_stats_address(bridge_state: &Pubkey, relayer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"relayer_stats", bridge_state.as_ref(), relayer.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// `UserLimits` PDA of `user`, whether or not it has been created
+pub fn user_limits_address(bridge_state: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user_limits", bridge_state.as_ref(), user.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// `DenyListEntry` PDA of `user`, whether or not it has been created
+pub fn deny_list_address(bridge_state: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"deny_list", bridge_state.as_ref(), user.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// `ReserveAttestation` ring slot holding snapshot number `sequence`, until
+/// the ring wraps around to it
+pub fn reserve_attestation_address(bridge_state: &Pubkey, sequence: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"reserve_attestation",
+            bridge_state.as_ref(),
+            &(sequence % crate::ReserveAttestation::RING_SIZE).to_le_bytes(),
+        ],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Accounts of a `mint_wzec` call crediting `recipient_token_account`
+///
+/// `recipient` is the token account's owner, whose limits and deny list entry
+/// are checked. The memo program is always passed, so the mint carries the
+/// deposit's provenance memo.
+pub fn mint_accounts(
+    bridge_state: &BridgeState,
+    recipient: &Pubkey,
+    recipient_token_account: &Pubkey,
+    authority: &Pubkey,
+    token_program: &Pubkey,
+    zcash_txid: &[u8; 32],
+) -> crate::accounts::MintWZEC {
+    let bridge_state_key = bridge_state_address(&bridge_state.mint);
+    crate::accounts::MintWZEC {
+        bridge_state: bridge_state_key,
+        bridge_stats: bridge_stats_address(&bridge_state_key),
+        guardian_set: guardian_set_address(&bridge_state_key),
+        deposit_receipt: deposit_receipt_address(&bridge_state_key, zcash_txid),
+        legacy_deposit_receipt: DepositReceipt::legacy_address(
+            &bridge_state_key,
+            bridge_state.singleton_receipts,
+            zcash_txid,
+        ),
+        mint: bridge_state.mint,
+        recipient_token_account: *recipient_token_account,
+        user_limits: user_limits_address(&bridge_state_key, recipient),
+        deny_list_entry: deny_list_address(&bridge_state_key, recipient),
+        fee_vault: get_associated_token_address_with_program_id(
+            &bridge_state_key,
+            &bridge_state.mint,
+            token_program,
+        ),
+        mint_authority: mint_authority_address(&bridge_state.mint),
+        relayer_registry: None,
+        relayer_stake: None,
+        relayer_stats: None,
+        authority: *authority,
+        token_program: *token_program,
+        system_program: anchor_lang::system_program::ID,
+        instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+        memo_program: Some(anchor_spl::memo::ID),
+    }
+}
+
+/// Ed25519 program instruction carrying guardian `signatures` over `message`,
+/// to be placed immediately before the instruction they attest
+///
+/// Every entry points into this instruction, as the bridge requires, and the
+/// message is stored once after the keys and signatures.
+pub fn guardian_attestation_instruction(signatures: &[(Pubkey, [u8; 64])], message: &[u8]) -> Instruction {
+    const HEADER_LEN: usize = 2;
+    const OFFSETS_LEN: usize = 14;
+    const ENTRY_LEN: usize = 32 + 64;
+    const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+    let entries_start = HEADER_LEN + signatures.len() * OFFSETS_LEN;
+    let message_offset = entries_start + signatures.len() * ENTRY_LEN;
+    let mut data = Vec::with_capacity(message_offset + message.len());
+    data.extend_from_slice(&[signatures.len() as u8, 0]);
+    for index in 0..signatures.len() {
+        let pubkey_offset = entries_start + index * ENTRY_LEN;
+        for field in [
+            (pubkey_offset + 32) as u16,
+            CURRENT_INSTRUCTION,
+            pubkey_offset as u16,
+            CURRENT_INSTRUCTION,
+            message_offset as u16,
+            message.len() as u16,
+            CURRENT_INSTRUCTION,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+    }
+    for (guardian, signature) in signatures {
+        data.extend_from_slice(guardian.as_ref());
+        data.extend_from_slice(signature);
+    }
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: anchor_lang::solana_program::ed25519_program::ID,
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// `mint_wzec` instruction for `accounts`
+///
+/// `remaining_accounts` carries the attesting guardians as signers, the
+/// recipient's `FeeExemption` and an archived guardian set when they apply.
+pub fn mint_wzec_instruction(
+    accounts: crate::accounts::MintWZEC,
+    remaining_accounts: Vec<AccountMeta>,
+    amount: u64,
+    zcash_txid: [u8; 32],
+    zcash_block_height: Option<u32>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+    Instruction {
+        program_id: crate::ID,
+        accounts: metas,
+        data: crate::instruction::MintWzec {
+            amount,
+            zcash_txid,
+            zcash_block_height,
+        }
+        .data(),
+    }
+}
+
+/// Accounts of a `burn_wzec` call, derived from the bridge's current state
+///
+/// The withdrawal request is seeded by the bridge's next withdrawal nonce, so the
+/// accounts have to be derived from a fresh read of the bridge state. Under CPI,
+/// `user` is the signer whose tokens are burned and who pays the request's rent;
+/// a calling program burning from its own PDA signs for it with
+/// `CpiContext::new_with_signer`.
+pub fn burn_accounts(
+    bridge_state: &BridgeState,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    token_program: &Pubkey,
+) -> crate::accounts::BurnWZEC {
+    let bridge_state_key = bridge_state_address(&bridge_state.mint);
+    crate::accounts::BurnWZEC {
+        bridge_state: bridge_state_key,
+        bridge_stats: bridge_stats_address(&bridge_state_key),
+        withdrawal_request: withdrawal_request_address(&bridge_state_key, bridge_state.withdrawal_nonce),
+        mint: bridge_state.mint,
+        user_token_account: *user_token_account,
+        fee_vault: get_associated_token_address_with_program_id(
+            &bridge_state_key,
+            &bridge_state.mint,
+            token_program,
+        ),
+        insurance_vault: (bridge_state.insurance_vault != Pubkey::default())
+            .then_some(bridge_state.insurance_vault),
+        user_limits: user_limits_address(&bridge_state_key, user),
+        deny_list_entry: deny_list_address(&bridge_state_key, user),
+        user: *user,
+        token_program: *token_program,
+        system_program: anchor_lang::system_program::ID,
+    }
+}
+
+/// `burn_wzec` instruction for `accounts`, for callers that build instructions
+/// rather than going through `cpi::burn_wzec`
+///
+/// `remaining_accounts` carries the user's `FeeExemption` and the Pyth price
+/// update when the bridge needs them.
+pub fn burn_wzec_instruction(
+    accounts: crate::accounts::BurnWZEC,
+    remaining_accounts: Vec<AccountMeta>,
+    amount: u64,
+    zec_address: String,
+    memo: Option<Vec<u8>>,
+    max_fee_bps: Option<u16>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+    Instruction {
+        program_id: crate::ID,
+        accounts: metas,
+        data: crate::instruction::BurnWzec {
+            amount,
+            zec_address,
+            memo,
+            max_fee_bps,
+        }
+        .data(),
+    }
+}
+
+/// `guardian_pause` instruction: `guardian`, a member of the current guardian
+/// set, halts mints on the bridge for `mint`
+pub fn guardian_pause_instruction(mint: &Pubkey, guardian: &Pubkey) -> Instruction {
+    let bridge_state = bridge_state_address(mint);
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::GuardianPause {
+            bridge_state,
+            guardian_set: guardian_set_address(&bridge_state),
+            guardian: *guardian,
+        }
+        .to_account_metas(None),
+        data: crate::instruction::GuardianPause {}.data(),
+    }
+}
+
+/// `set_pause_flags` instruction: `authority`, the bridge authority, pauses
+/// or resumes mints and burns on the bridge for `mint`
+pub fn set_pause_flags_instruction(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    mints_paused: bool,
+    burns_paused: bool,
+) -> Instruction {
+    let bridge_state = bridge_state_address(mint);
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SetPauseFlags {
+            bridge_state,
+            bridge_stats: bridge_stats_address(&bridge_state),
+            authority: *authority,
+        }
+        .to_account_metas(None),
+        data: crate::instruction::SetPauseFlags {
+            mints_paused,
+            burns_paused,
+        }
+        .data(),
+    }
+}
+
+/// `finalize_withdrawal` instruction recording `payout_txid`, in internal byte
+/// order, as the payout of withdrawal `nonce`, paid by its output `payout_output`
+/// when the payout is batched
+///
+/// A bonded relayer passes `bonded` so its stake and stats are credited with
+/// the finalization; the bridge authority finalizes without them.
+pub fn finalize_withdrawal_instruction(
+    mint: &Pubkey,
+    nonce: u64,
+    authority: &Pubkey,
+    bonded: bool,
+    payout_txid: [u8; 32],
+    payout_height: u32,
+    payout_output: Option<u16>,
+) -> Instruction {
+    let bridge_state = bridge_state_address(mint);
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::FinalizeWithdrawal {
+            bridge_state,
+            withdrawal_request: withdrawal_request_address(&bridge_state, nonce),
+            relayer_registry: bonded.then(|| relayer_registry_address(&bridge_state)),
+            relayer_stake: bonded.then(|| relayer_stake_address(&bridge_state, authority)),
+            relayer_stats: bonded.then(|| relayer_stats_address(&bridge_state, authority)),
+            authority: *authority,
+        }
+        .to_account_metas(None),
+        data: crate::instruction::FinalizeWithdrawal {
+            payout_txid,
+            payout_height,
+            payout_output,
+        }
+        .data(),
+    }
+}
@@ -0,0 +1,371 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    BridgeState, BridgeStats, Roles, VolumeWindow, WithdrawalRequest, WithdrawalStatus,
+    ZcashNetwork, MAX_FEE_BPS,
+};
+
+/// `BridgeState` as originally deployed: no version byte, so the authority
+/// directly follows the discriminator, and one fee charged on burns
+#[derive(AnchorDeserialize)]
+struct BaselineBridgeState {
+    authority: Pubkey,
+    mint: Pubkey,
+    fee_percentage: u16,
+    paused: bool,
+    total_minted: u64,
+    total_burned: u64,
+    fee_collected: u64,
+}
+
+impl BaselineBridgeState {
+    const LEN: usize = 32 + 32 + 2 + 1 + 8 + 8 + 8;
+}
+
+/// `BridgeState` as first versioned, before the volume windows and totals moved
+/// into `BridgeStats`. It shares version byte 1 with the layout that replaced it,
+/// so the two are told apart by account length.
+#[derive(AnchorDeserialize)]
+struct WideBridgeStateV1 {
+    version: u8,
+    authority: Pubkey,
+    pending_authority: Pubkey,
+    mint: Pubkey,
+    mint_fee_bps: u16,
+    burn_fee_bps: u16,
+    cancellation_fee_bps: u16,
+    treasury: Pubkey,
+    max_mint_amount: u64,
+    max_burn_amount: u64,
+    daily_mint_limit: u64,
+    mint_volume: VolumeWindow,
+    withdrawal_nonce: u64,
+    large_withdrawal_threshold: u64,
+    large_withdrawal_delay_slots: u64,
+    mints_paused: bool,
+    burns_paused: bool,
+    circuit_breaker_threshold: u64,
+    circuit_breaker_window_hours: u8,
+    circuit_breaker_tripped: bool,
+    breaker_volume: VolumeWindow,
+    last_heartbeat_slot: u64,
+    max_heartbeat_gap: u64,
+    total_minted: u64,
+    total_burned: u64,
+    fee_collected: u64,
+    bump: u8,
+    mint_authority_bump: u8,
+}
+
+impl WideBridgeStateV1 {
+    const LEN: usize = 1 + 32 + 32 + 32 + 2 + 2 + 2 + 32 + 8 + 8 + 8 + VolumeWindow::LEN + 8 + 8 + 8 + 1 + 1 + 8 + 1 + 1 + VolumeWindow::LEN + 8 + 8 + 8 + 8 + 8 + 1 + 1;
+}
+
+/// Totals and volume windows held in the state account by layouts that predate `BridgeStats`
+#[derive(AnchorDeserialize)]
+pub struct LegacyStats {
+    pub total_minted: u64,
+    pub total_burned: u64,
+    pub fee_collected: u64,
+    pub mint_volume: VolumeWindow,
+    pub breaker_volume: VolumeWindow,
+}
+
+impl LegacyStats {
+    /// Read a `BridgeStats` account the program no longer holds a typed handle to.
+    /// Its fields are all 8-byte aligned, so the zero-copy layout matches Borsh.
+    pub fn from_stats_account(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= 8 + BridgeStats::LEN && data[..8] == BridgeStats::DISCRIMINATOR,
+            ErrorCode::AccountNotInitialized
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+}
+
+/// `WithdrawalRequest` as stored before burns carried a memo: it ends at the bump
+#[derive(AnchorDeserialize)]
+struct PreMemoWithdrawalRequest {
+    user: Pubkey,
+    nonce: u64,
+    amount: u64,
+    fee: u64,
+    net_amount: u64,
+    zec_address: String,
+    status: WithdrawalStatus,
+    created_slot: u64,
+    payout_txid: [u8; 32],
+    payout_height: u32,
+    unlock_slot: u64,
+    vetoed_by: Pubkey,
+    veto_reason: u8,
+    bump: u8,
+}
+
+/// `WithdrawalRequest` as stored before it recorded its rent payer: it ends at the memo
+#[derive(AnchorDeserialize)]
+struct PrePayerWithdrawalRequest {
+    user: Pubkey,
+    nonce: u64,
+    amount: u64,
+    fee: u64,
+    net_amount: u64,
+    zec_address: String,
+    status: WithdrawalStatus,
+    created_slot: u64,
+    payout_txid: [u8; 32],
+    payout_height: u32,
+    unlock_slot: u64,
+    vetoed_by: Pubkey,
+    veto_reason: u8,
+    bump: u8,
+    memo: Vec<u8>,
+}
+
+/// Decode a withdrawal request stored before memos or rent payers, giving it an
+/// empty memo if it had none and its user as the payer
+pub fn decode_legacy_withdrawal(data: &[u8]) -> Result<WithdrawalRequest> {
+    require!(
+        data.len() >= 8 && data[..8] == WithdrawalRequest::DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    if let Ok(request) = PrePayerWithdrawalRequest::deserialize(&mut &data[8..]) {
+        return Ok(WithdrawalRequest {
+            user: request.user,
+            nonce: request.nonce,
+            amount: request.amount,
+            fee: request.fee,
+            net_amount: request.net_amount,
+            zec_address: request.zec_address,
+            status: request.status,
+            created_slot: request.created_slot,
+            payout_txid: request.payout_txid,
+            payout_height: request.payout_height,
+            unlock_slot: request.unlock_slot,
+            vetoed_by: request.vetoed_by,
+            veto_reason: request.veto_reason,
+            bump: request.bump,
+            memo: request.memo,
+            payer: request.user,
+        });
+    }
+    let request = PreMemoWithdrawalRequest::deserialize(&mut &data[8..])?;
+    Ok(WithdrawalRequest {
+        user: request.user,
+        nonce: request.nonce,
+        amount: request.amount,
+        fee: request.fee,
+        net_amount: request.net_amount,
+        zec_address: request.zec_address,
+        status: request.status,
+        created_slot: request.created_slot,
+        payout_txid: request.payout_txid,
+        payout_height: request.payout_height,
+        unlock_slot: request.unlock_slot,
+        vetoed_by: request.vetoed_by,
+        veto_reason: request.veto_reason,
+        bump: request.bump,
+        memo: Vec::new(),
+        payer: request.user,
+    })
+}
+
+/// Layout of a stored bridge state account
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StoredLayout {
+    /// The unversioned original layout, identified by its length
+    Baseline,
+    /// Version 1 with the volume windows and totals still inline
+    WideV1,
+    /// A prefix of the current layout at the given version; later fields are appended
+    Versioned(u8),
+}
+
+impl StoredLayout {
+    /// Identify the layout of the raw account data, discriminator included
+    pub fn detect(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= 8 && data[..8] == BridgeState::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        if data.len() == 8 + BaselineBridgeState::LEN {
+            return Ok(StoredLayout::Baseline);
+        }
+        require!(
+            data.len() > BridgeState::VERSION_OFFSET,
+            ErrorCode::AccountDidNotDeserialize
+        );
+        let version = data[BridgeState::VERSION_OFFSET];
+        if version == 1 && data.len() == 8 + WideBridgeStateV1::LEN {
+            return Ok(StoredLayout::WideV1);
+        }
+        Ok(StoredLayout::Versioned(version))
+    }
+
+    /// Version the layout was written at
+    pub fn version(&self) -> u8 {
+        match self {
+            StoredLayout::Baseline => 0,
+            StoredLayout::WideV1 => 1,
+            StoredLayout::Versioned(version) => *version,
+        }
+    }
+
+    /// Authority recorded in the raw account data
+    pub fn authority(&self, data: &[u8]) -> Result<Pubkey> {
+        let offset = match self {
+            StoredLayout::Baseline => 8,
+            _ => BridgeState::AUTHORITY_OFFSET,
+        };
+        let bytes = data
+            .get(offset..offset + 32)
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        Ok(Pubkey::try_from(bytes).unwrap())
+    }
+
+    /// Decode the account into the current struct, leaving fields the layout lacks
+    /// zeroed for `BridgeState::migrate` to fill, along with any totals and volume
+    /// windows the layout kept inline
+    pub fn decode(&self, data: &[u8]) -> Result<(BridgeState, Option<LegacyStats>)> {
+        match self {
+            StoredLayout::Baseline => {
+                let state = BaselineBridgeState::deserialize(&mut &data[8..])?;
+                let stats = LegacyStats {
+                    total_minted: state.total_minted,
+                    total_burned: state.total_burned,
+                    fee_collected: state.fee_collected,
+                    mint_volume: VolumeWindow::default(),
+                    breaker_volume: VolumeWindow::default(),
+                };
+                // The single fee applied to burns only, and one flag paused both
+                // directions; limits and safeguards it never had start disabled
+                let bridge_state = BridgeState {
+                    version: 0,
+                    authority: state.authority,
+                    pending_authority: Pubkey::default(),
+                    mint: state.mint,
+                    mint_fee_bps: 0,
+                    burn_fee_bps: state.fee_percentage.min(MAX_FEE_BPS),
+                    cancellation_fee_bps: 0,
+                    treasury: Pubkey::default(),
+                    max_mint_amount: u64::MAX,
+                    max_burn_amount: u64::MAX,
+                    daily_mint_limit: u64::MAX,
+                    withdrawal_nonce: 0,
+                    large_withdrawal_threshold: u64::MAX,
+                    large_withdrawal_delay_slots: 0,
+                    mints_paused: state.paused,
+                    burns_paused: state.paused,
+                    circuit_breaker_threshold: u64::MAX,
+                    circuit_breaker_window_hours: VolumeWindow::BUCKETS as u8,
+                    circuit_breaker_tripped: false,
+                    last_heartbeat_slot: 0,
+                    max_heartbeat_gap: 0,
+                    bump: 0,
+                    mint_authority_bump: 0,
+                    stats_bump: 0,
+                    decommissioned_slot: 0,
+                    decommission_grace_slots: 0,
+                    zcash_deposit_pubkey_hash: [0; 20],
+                    network: ZcashNetwork::Testnet,
+                    min_burn_amount: 0,
+                    singleton_receipts: false,
+                    unclaimed_relayer_rewards: 0,
+                    kyc_threshold: u64::MAX,
+                    compliance_attester: Pubkey::default(),
+                    roles: Roles::default(),
+                    timelock_slots: 0,
+                    next_change_id: 0,
+                    insurance_vault: Pubkey::default(),
+                    insurance_share_bps: 0,
+                    insurance_total_shares: 0,
+                    insurance_unstake_slots: 0,
+                    retention_slots: 0,
+                    finalize_window: 0,
+                    settled_floor: 0,
+                    settled_bitmap: 0,
+                    reserve_attestation_count: 0,
+                    price_feed_id: [0; 32],
+                    max_price_age_secs: 0,
+                    kyc_threshold_usd: u64::MAX,
+                    large_withdrawal_threshold_usd: u64::MAX,
+                    guardian_paused_by: Pubkey::default(),
+                    open_withdrawals: 0,
+                };
+                Ok((bridge_state, Some(stats)))
+            }
+            StoredLayout::WideV1 => {
+                let state = WideBridgeStateV1::deserialize(&mut &data[8..])?;
+                let stats = LegacyStats {
+                    total_minted: state.total_minted,
+                    total_burned: state.total_burned,
+                    fee_collected: state.fee_collected,
+                    mint_volume: state.mint_volume,
+                    breaker_volume: state.breaker_volume,
+                };
+                let bridge_state = BridgeState {
+                    version: state.version,
+                    authority: state.authority,
+                    pending_authority: state.pending_authority,
+                    mint: state.mint,
+                    mint_fee_bps: state.mint_fee_bps,
+                    burn_fee_bps: state.burn_fee_bps,
+                    cancellation_fee_bps: state.cancellation_fee_bps,
+                    treasury: state.treasury,
+                    max_mint_amount: state.max_mint_amount,
+                    max_burn_amount: state.max_burn_amount,
+                    daily_mint_limit: state.daily_mint_limit,
+                    withdrawal_nonce: state.withdrawal_nonce,
+                    large_withdrawal_threshold: state.large_withdrawal_threshold,
+                    large_withdrawal_delay_slots: state.large_withdrawal_delay_slots,
+                    mints_paused: state.mints_paused,
+                    burns_paused: state.burns_paused,
+                    circuit_breaker_threshold: state.circuit_breaker_threshold,
+                    circuit_breaker_window_hours: state.circuit_breaker_window_hours,
+                    circuit_breaker_tripped: state.circuit_breaker_tripped,
+                    last_heartbeat_slot: state.last_heartbeat_slot,
+                    max_heartbeat_gap: state.max_heartbeat_gap,
+                    bump: state.bump,
+                    mint_authority_bump: state.mint_authority_bump,
+                    stats_bump: 0,
+                    decommissioned_slot: 0,
+                    decommission_grace_slots: 0,
+                    zcash_deposit_pubkey_hash: [0; 20],
+                    network: ZcashNetwork::Testnet,
+                    min_burn_amount: 0,
+                    singleton_receipts: false,
+                    unclaimed_relayer_rewards: 0,
+                    kyc_threshold: u64::MAX,
+                    compliance_attester: Pubkey::default(),
+                    roles: Roles::default(),
+                    timelock_slots: 0,
+                    next_change_id: 0,
+                    insurance_vault: Pubkey::default(),
+                    insurance_share_bps: 0,
+                    insurance_total_shares: 0,
+                    insurance_unstake_slots: 0,
+                    retention_slots: 0,
+                    finalize_window: 0,
+                    settled_floor: 0,
+                    settled_bitmap: 0,
+                    reserve_attestation_count: 0,
+                    price_feed_id: [0; 32],
+                    max_price_age_secs: 0,
+                    kyc_threshold_usd: u64::MAX,
+                    large_withdrawal_threshold_usd: u64::MAX,
+                    guardian_paused_by: Pubkey::default(),
+                    open_withdrawals: 0,
+                };
+                Ok((bridge_state, Some(stats)))
+            }
+            StoredLayout::Versioned(_) => {
+                // Older versions are shorter; the fields they lack decode as zeroes
+                let mut padded = data.to_vec();
+                if padded.len() < 8 + BridgeState::LEN {
+                    padded.resize(8 + BridgeState::LEN, 0);
+                }
+                Ok((BridgeState::try_deserialize(&mut &padded[..])?, None))
+            }
+        }
+    }
+}
@@ -1,5 +1,42 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount};
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::memo::{self, BuildMemo, Memo};
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{self, CreateMetadataAccountsV3, Metadata};
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::instruction::AuthorityType;
+use anchor_spl::token_2022::spl_token_2022::{self, state::Mint as MintState};
+use anchor_spl::token_interface::{
+    self, Burn, CloseAccount, Mint, MintTo, SetAuthority, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+
+mod ed25519;
+pub mod groth16;
+pub mod interface;
+mod legacy_state;
+mod oracle;
+mod zcash_address;
+pub mod zcash_light_client;
+pub mod zcash_spv;
+
+pub use groth16::{Groth16Proof, VerifyingKey};
+pub use zcash_light_client::*;
+
+/// Upper bound for any bridge fee, in basis points (5%)
+pub const MAX_FEE_BPS: u16 = 500;
+
+/// wZEC decimals, matching zatoshi precision
+pub const WZEC_DECIMALS: u8 = 8;
+
+/// Most deposits a single `mint_wzec_batch` call may mint
+pub const MAX_BATCH_MINTS: usize = 8;
 
 declare_id!("8vZ9qKQZc8kqGmvXZ8VqKDxP8vZ9qKQZc8kqGmvXZ8Vq");
 
@@ -7,290 +44,8046 @@ declare_id!("8vZ9qKQZc8kqGmvXZ8VqKDxP8vZ9qKQZc8kqGmvXZ8Vq");
 pub mod wzec_bridge {
     use super::*;
 
-    /// Initialize the bridge with token mint and authority
-    pub fn initialize(ctx: Context<Initialize>, fee_percentage: u16) -> Result<()> {
+    /// Initialize a bridge instance for a wrapped-asset mint
+    ///
+    /// Every PDA is keyed by the mint (or by the instance's state account), so one
+    /// deployment can run independent bridges for wZEC, wTAZ and future assets.
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        mint_fee_bps: u16,
+        burn_fee_bps: u16,
+        network: ZcashNetwork,
+    ) -> Result<()> {
+        require!(
+            mint_fee_bps <= MAX_FEE_BPS && burn_fee_bps <= MAX_FEE_BPS,
+            BridgeError::FeeTooHigh
+        );
+        check_mint_extensions(&ctx.accounts.mint.to_account_info())?;
+
         let bridge_state = &mut ctx.accounts.bridge_state;
+        bridge_state.version = BridgeState::VERSION;
         bridge_state.authority = ctx.accounts.authority.key();
+        bridge_state.pending_authority = Pubkey::default();
         bridge_state.mint = ctx.accounts.mint.key();
-        bridge_state.fee_percentage = fee_percentage;
-        bridge_state.paused = false;
-        bridge_state.total_minted = 0;
-        bridge_state.total_burned = 0;
-        bridge_state.fee_collected = 0;
+        bridge_state.mint_fee_bps = mint_fee_bps;
+        bridge_state.burn_fee_bps = burn_fee_bps;
+        bridge_state.treasury = Pubkey::default();
+        bridge_state.cancellation_fee_bps = 0;
+        bridge_state.max_mint_amount = u64::MAX;
+        bridge_state.max_burn_amount = u64::MAX;
+        bridge_state.min_burn_amount = 0;
+        bridge_state.daily_mint_limit = u64::MAX;
+        bridge_state.withdrawal_nonce = 0;
+        bridge_state.large_withdrawal_threshold = u64::MAX;
+        bridge_state.large_withdrawal_delay_slots = 0;
+        bridge_state.mints_paused = false;
+        bridge_state.burns_paused = false;
+        bridge_state.circuit_breaker_threshold = u64::MAX;
+        bridge_state.circuit_breaker_window_hours = VolumeWindow::BUCKETS as u8;
+        bridge_state.circuit_breaker_tripped = false;
+        bridge_state.last_heartbeat_slot = Clock::get()?.slot;
+        bridge_state.max_heartbeat_gap = 0;
+        bridge_state.decommissioned_slot = 0;
+        bridge_state.decommission_grace_slots = 0;
+        bridge_state.zcash_deposit_pubkey_hash = [0; 20];
+        bridge_state.network = network;
+        bridge_state.singleton_receipts = false;
+        bridge_state.unclaimed_relayer_rewards = 0;
+        bridge_state.kyc_threshold = u64::MAX;
+        bridge_state.compliance_attester = Pubkey::default();
+        bridge_state.roles = Roles::default();
+        bridge_state.timelock_slots = 0;
+        bridge_state.next_change_id = 0;
+        bridge_state.insurance_vault = Pubkey::default();
+        bridge_state.insurance_share_bps = 0;
+        bridge_state.insurance_total_shares = 0;
+        bridge_state.insurance_unstake_slots = 0;
+        bridge_state.retention_slots = 0;
+        bridge_state.finalize_window = 0;
+        bridge_state.settled_floor = 0;
+        bridge_state.settled_bitmap = 0;
+        bridge_state.reserve_attestation_count = 0;
+        bridge_state.price_feed_id = [0; 32];
+        bridge_state.max_price_age_secs = 0;
+        bridge_state.kyc_threshold_usd = u64::MAX;
+        bridge_state.large_withdrawal_threshold_usd = u64::MAX;
+        bridge_state.guardian_paused_by = Pubkey::default();
+        bridge_state.open_withdrawals = 0;
+        bridge_state.bump = ctx.bumps.bridge_state;
+        bridge_state.mint_authority_bump = ctx.bumps.mint_authority;
+        bridge_state.stats_bump = ctx.bumps.bridge_stats;
+
+        // Counters and volume windows start zeroed
+        ctx.accounts.bridge_stats.load_init()?;
+
+        emit!(BridgeInitializedEvent {
+            authority: bridge_state.authority,
+            mint: bridge_state.mint,
+            mint_fee_bps,
+            burn_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Create the Metaplex metadata account for the wZEC mint (admin only)
+    ///
+    /// The bridge state PDA becomes the metadata update authority, so the display
+    /// fields stay under program control across authority transfers.
+    pub fn create_metadata(
+        ctx: Context<CreateMetadata>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
 
-        msg!("Bridge initialized with authority: {}", bridge_state.authority);
-        msg!("Mint address: {}", bridge_state.mint);
-        msg!("Fee percentage: {}%", fee_percentage as f64 / 100.0);
+        let mint_key = ctx.accounts.mint.key();
+        let state_bump = [ctx.accounts.bridge_state.bump];
+        let mint_authority_bump = [ctx.accounts.bridge_state.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[b"bridge_state", mint_key.as_ref(), &state_bump],
+            &[b"mint_authority", mint_key.as_ref(), &mint_authority_bump],
+        ];
+        let cpi_accounts = CreateMetadataAccountsV3 {
+            metadata: ctx.accounts.metadata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            mint_authority: ctx.accounts.mint_authority.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            update_authority: ctx.accounts.bridge_state.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_metadata_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        metadata::create_metadata_accounts_v3(
+            cpi_ctx,
+            DataV2 {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                uri: uri.clone(),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            true,
+            true,
+            None,
+        )?;
+
+        emit!(MetadataCreatedEvent {
+            mint: ctx.accounts.mint.key(),
+            metadata: ctx.accounts.metadata.key(),
+            name,
+            symbol,
+            uri,
+        });
 
         Ok(())
     }
 
-    /// Mint wZEC tokens (bridge authority only)
+    /// Mint wZEC tokens (guardian quorum only)
+    ///
+    /// Guardians attest to the deposit either as signer remaining accounts or by
+    /// signing `DepositReceipt::attestation_message` off-chain, verified through an
+    /// ed25519 program instruction placed immediately before this one. The
+    /// recipient's `FeeExemption`, if any, may also be passed as a remaining account.
+    ///
+    /// `zcash_txid` is in internal byte order, the reverse of the hex block explorers show.
     pub fn mint_wzec(
         ctx: Context<MintWZEC>,
         amount: u64,
-        zcash_txid: String,
+        zcash_txid: [u8; 32],
+        zcash_block_height: Option<u32>,
     ) -> Result<()> {
         let bridge_state = &mut ctx.accounts.bridge_state;
 
-        // Check if bridge is paused
-        require!(!bridge_state.paused, BridgeError::BridgePaused);
-
-        // Verify authority
+        // Verify the minter, authority or a bonded relayer
         require!(
-            ctx.accounts.authority.key() == bridge_state.authority,
+            bridge_state.has_role(Role::Minter, &ctx.accounts.authority.key())
+                || RelayerStake::is_bonded(&ctx.accounts.relayer_registry, &ctx.accounts.relayer_stake),
             BridgeError::Unauthorized
         );
+        RelayerStats::require_with_stake(&ctx.accounts.relayer_stake, &ctx.accounts.relayer_stats)?;
+
+        // Minting ends for good once the bridge is decommissioned
+        require!(
+            bridge_state.decommissioned_slot == 0,
+            BridgeError::BridgeDecommissioned
+        );
+
+        // Check if minting is paused
+        require!(
+            !bridge_state.mints_paused && !bridge_state.guardian_paused(),
+            BridgeError::BridgePaused
+        );
+
+        // Verify guardian quorum
+        let message = DepositReceipt::attestation_message(
+            bridge_state.network,
+            &ctx.accounts.recipient_token_account.key(),
+            amount,
+            &zcash_txid,
+        );
+        let attested = ed25519::verified_signers(&ctx.accounts.instructions, &message)?;
+        require!(
+            ctx.accounts
+                .guardian_set
+                .has_quorum(&bridge_state.key(), ctx.remaining_accounts, &attested)?,
+            BridgeError::InsufficientGuardianSignatures
+        );
 
         // Validate amount
         require!(amount > 0, BridgeError::InvalidAmount);
+        require!(
+            amount <= bridge_state.max_mint_amount,
+            BridgeError::MintAmountExceedsLimit
+        );
+
+        // Enforce the rolling 24-hour mint limit
+        let now = Clock::get()?.unix_timestamp;
+        let mut stats = ctx.accounts.bridge_stats.load_mut()?;
+        stats
+            .mint_volume
+            .record(now, amount, bridge_state.daily_mint_limit)?;
+
+        // Enforce the recipient's own limits, if any
+        UserLimits::record_mint(&ctx.accounts.user_limits, now, amount)?;
+
+        // Refuse recipients on the deny list
+        DenyListEntry::check(&ctx.accounts.deny_list_entry)?;
+
+        // Large transfers need a compliance credential, if the bridge requires one
+        bridge_state.check_compliance(ctx.remaining_accounts, &ctx.accounts.recipient_token_account.owner, amount)?;
+
+        // Reject deposits that have already been minted
+        let deposit_receipt = &mut ctx.accounts.deposit_receipt;
+        require!(
+            deposit_receipt.amount == 0,
+            BridgeError::DepositAlreadyProcessed
+        );
+
+        deposit_receipt.amount = amount;
+        deposit_receipt.recipient = ctx.accounts.recipient_token_account.key();
+        deposit_receipt.slot = Clock::get()?.slot;
+        deposit_receipt.bump = ctx.bumps.deposit_receipt;
+        deposit_receipt.payer = ctx.accounts.authority.key();
+
+        // Calculate fee, at the recipient's exempt rate if they have one
+        let fee_bps = FeeExemption::mint_fee_bps(
+            ctx.remaining_accounts,
+            &bridge_state.key(),
+            &ctx.accounts.recipient_token_account.owner,
+            bridge_state.mint_fee_bps,
+        )?;
+        let fee = calculate_fee(amount, fee_bps)?;
+        let amount_after_fee = amount
+            .checked_sub(fee)
+            .ok_or(BridgeError::Overflow)?;
 
-        // Mint tokens to recipient
+        // Mint tokens to recipient, signed by the mint authority PDA
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [bridge_state.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"mint_authority", mint_key.as_ref(), &bump]];
         let cpi_accounts = MintTo {
             mint: ctx.accounts.mint.to_account_info(),
             to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: ctx.accounts.authority.to_account_info(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::mint_to(cpi_ctx, amount)?;
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::mint_to(cpi_ctx, amount_after_fee)?;
+
+        // Mint the fee share into the fee vault
+        if fee > 0 {
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.fee_vault.to_account_info(),
+                authority: ctx.accounts.mint_authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::mint_to(cpi_ctx, fee)?;
+        }
 
-        // Update state
-        bridge_state.total_minted = bridge_state
+        // Update stats
+        stats.total_minted = stats
             .total_minted
             .checked_add(amount)
             .ok_or(BridgeError::Overflow)?;
 
-        msg!("Minted {} wZEC to {}", amount, ctx.accounts.recipient_token_account.key());
-        msg!("Zcash TXID: {}", zcash_txid);
+        stats.fee_collected = stats
+            .fee_collected
+            .checked_add(fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        emit!(MintEvent {
+            recipient: ctx.accounts.recipient_token_account.key(),
+            amount,
+            fee,
+            amount_after_fee,
+            zcash_txid,
+            total_minted: stats.total_minted,
+        });
+
+        // Trip the circuit breaker once volume in its window exceeds the threshold;
+        // this mint still lands, but every later one is refused until a reset
+        stats.breaker_volume.record(now, amount, u64::MAX)?;
+        let window_volume = stats
+            .breaker_volume
+            .total_within(bridge_state.circuit_breaker_window_hours);
+        if window_volume > bridge_state.circuit_breaker_threshold {
+            bridge_state.mints_paused = true;
+            bridge_state.circuit_breaker_tripped = true;
+
+            emit!(CircuitBreakerTrippedEvent {
+                window_volume,
+                threshold: bridge_state.circuit_breaker_threshold,
+                window_hours: bridge_state.circuit_breaker_window_hours,
+            });
+        }
+
+        if let Some(relayer_stats) = ctx.accounts.relayer_stats.as_mut() {
+            relayer_stats.record_mint(amount, Clock::get()?.slot)?;
+        }
+
+        // Leave a human-readable provenance note on the transaction; the block
+        // height is as reported by the minter and is not checked against any header
+        if let Some(memo_program) = ctx.accounts.memo_program.as_ref() {
+            let mut note = format!("wZEC mint for Zcash txid {}", zcash_spv::txid_hex(&zcash_txid));
+            if let Some(height) = zcash_block_height {
+                note.push_str(&format!(" at height {}", height));
+            }
+            memo::build_memo(
+                CpiContext::new(memo_program.to_account_info(), BuildMemo {}),
+                note.as_bytes(),
+            )?;
+        }
 
         Ok(())
     }
 
-    /// Burn wZEC tokens and emit withdrawal event
-    pub fn burn_wzec(
-        ctx: Context<BurnWZEC>,
+    /// Mint several attested deposits in one transaction
+    ///
+    /// Remaining accounts hold one `[recipient_token_account, deposit_receipt,
+    /// legacy_deposit_receipt, user_limits, deny_list_entry]` group per deposit, in order, followed by
+    /// any guardian signers, compliance credentials and recipients' `FeeExemption`s. Guardians attest
+    /// the whole batch with a single signature over `DepositReceipt::batch_attestation_message`.
+    pub fn mint_wzec_batch(
+        ctx: Context<MintWZECBatch>,
+        deposits: Vec<BatchDeposit>,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify the minter, authority or a bonded relayer
+        require!(
+            bridge_state.has_role(Role::Minter, &ctx.accounts.authority.key())
+                || RelayerStake::is_bonded(&ctx.accounts.relayer_registry, &ctx.accounts.relayer_stake),
+            BridgeError::Unauthorized
+        );
+        RelayerStats::require_with_stake(&ctx.accounts.relayer_stake, &ctx.accounts.relayer_stats)?;
+
+        require!(
+            bridge_state.decommissioned_slot == 0,
+            BridgeError::BridgeDecommissioned
+        );
+        require!(
+            !bridge_state.mints_paused && !bridge_state.guardian_paused(),
+            BridgeError::BridgePaused
+        );
+
+        require!(
+            !deposits.is_empty() && deposits.len() <= MAX_BATCH_MINTS,
+            BridgeError::InvalidBatchSize
+        );
+        let entry_accounts = deposits.len() * 5;
+        require!(
+            ctx.remaining_accounts.len() >= entry_accounts,
+            BridgeError::InvalidBatchAccounts
+        );
+        let (entries, guardian_signers) = ctx.remaining_accounts.split_at(entry_accounts);
+
+        // Verify guardian quorum once for the whole batch
+        let attested_deposits: Vec<(Pubkey, u64, [u8; 32])> = deposits
+            .iter()
+            .zip(entries.chunks(5))
+            .map(|(deposit, accounts)| (accounts[0].key(), deposit.amount, deposit.zcash_txid))
+            .collect();
+        let message = DepositReceipt::batch_attestation_message(bridge_state.network, &attested_deposits);
+        let attested = ed25519::verified_signers(&ctx.accounts.instructions, &message)?;
+        require!(
+            ctx.accounts
+                .guardian_set
+                .has_quorum(&bridge_state.key(), guardian_signers, &attested)?,
+            BridgeError::InsufficientGuardianSignatures
+        );
+
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let bridge_key = bridge_state.key();
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [bridge_state.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"mint_authority", mint_key.as_ref(), &bump]];
+        let receipt_rent = Rent::get()?.minimum_balance(8 + DepositReceipt::LEN);
+        let mut stats = ctx.accounts.bridge_stats.load_mut()?;
+        let mut batch_total: u64 = 0;
+
+        for (deposit, accounts) in deposits.into_iter().zip(entries.chunks(5)) {
+            let (recipient_info, receipt_info, legacy_receipt_info, user_limits_info, deny_list_info) =
+                (&accounts[0], &accounts[1], &accounts[2], &accounts[3], &accounts[4]);
+            let amount = deposit.amount;
+
+            require!(amount > 0, BridgeError::InvalidAmount);
+            require!(
+                amount <= bridge_state.max_mint_amount,
+                BridgeError::MintAmountExceedsLimit
+            );
+
+            let recipient_token_account = InterfaceAccount::<TokenAccount>::try_from(recipient_info)?;
+            require!(
+                recipient_token_account.mint == mint_key,
+                BridgeError::InvalidTokenMint
+            );
+
+            // Enforce the rolling 24-hour mint limit and the recipient's own limits
+            stats
+                .mint_volume
+                .record(now, amount, bridge_state.daily_mint_limit)?;
+
+            let (user_limits_key, _) = Pubkey::find_program_address(
+                &[b"user_limits", bridge_key.as_ref(), recipient_token_account.owner.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(
+                user_limits_info.key(),
+                user_limits_key,
+                BridgeError::InvalidBatchAccounts
+            );
+            UserLimits::record_mint(user_limits_info, now, amount)?;
+
+            // Refuse recipients on the deny list
+            let (deny_list_key, _) = Pubkey::find_program_address(
+                &[b"deny_list", bridge_key.as_ref(), recipient_token_account.owner.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(
+                deny_list_info.key(),
+                deny_list_key,
+                BridgeError::InvalidBatchAccounts
+            );
+            DenyListEntry::check(deny_list_info)?;
+
+            // Large transfers need a compliance credential, if the bridge requires one
+            bridge_state.check_compliance(guardian_signers, &recipient_token_account.owner, amount)?;
+
+            // Create the deposit receipt; an existing one means the txid was already minted
+            let (receipt_key, receipt_bump) = Pubkey::find_program_address(
+                &[b"deposit", bridge_key.as_ref(), &deposit.zcash_txid],
+                &crate::ID,
+            );
+            require_keys_eq!(
+                receipt_info.key(),
+                receipt_key,
+                BridgeError::InvalidBatchAccounts
+            );
+            require!(
+                receipt_info.data_is_empty(),
+                BridgeError::DepositAlreadyProcessed
+            );
+            require_keys_eq!(
+                legacy_receipt_info.key(),
+                DepositReceipt::legacy_address(
+                    &bridge_key,
+                    bridge_state.singleton_receipts,
+                    &deposit.zcash_txid,
+                ),
+                BridgeError::InvalidBatchAccounts
+            );
+            require!(
+                legacy_receipt_info.data_is_empty(),
+                BridgeError::DepositAlreadyProcessed
+            );
+
+            let receipt_bump_seed = [receipt_bump];
+            let receipt_seeds: &[&[&[u8]]] =
+                &[&[b"deposit", bridge_key.as_ref(), &deposit.zcash_txid, &receipt_bump_seed]];
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: receipt_info.clone(),
+                    },
+                    receipt_seeds,
+                ),
+                receipt_rent,
+                (8 + DepositReceipt::LEN) as u64,
+                &crate::ID,
+            )?;
+            let receipt = DepositReceipt {
+                amount,
+                recipient: recipient_info.key(),
+                slot: clock.slot,
+                bump: receipt_bump,
+                payer: ctx.accounts.authority.key(),
+            };
+            receipt.try_serialize(&mut &mut receipt_info.try_borrow_mut_data()?[..])?;
+
+            // Calculate fee, at the recipient's exempt rate if they have one
+            let fee_bps = FeeExemption::mint_fee_bps(
+                guardian_signers,
+                &bridge_key,
+                &recipient_token_account.owner,
+                bridge_state.mint_fee_bps,
+            )?;
+            let fee = calculate_fee(amount, fee_bps)?;
+            let amount_after_fee = amount
+                .checked_sub(fee)
+                .ok_or(BridgeError::Overflow)?;
+
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: recipient_info.clone(),
+                authority: ctx.accounts.mint_authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::mint_to(cpi_ctx, amount_after_fee)?;
+
+            if fee > 0 {
+                let cpi_accounts = MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.fee_vault.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+                token_interface::mint_to(cpi_ctx, fee)?;
+            }
+
+            stats.total_minted = stats
+                .total_minted
+                .checked_add(amount)
+                .ok_or(BridgeError::Overflow)?;
+
+            stats.fee_collected = stats
+                .fee_collected
+                .checked_add(fee)
+                .ok_or(BridgeError::Overflow)?;
+
+            batch_total = batch_total
+                .checked_add(amount)
+                .ok_or(BridgeError::Overflow)?;
+
+            if let Some(relayer_stats) = ctx.accounts.relayer_stats.as_mut() {
+                relayer_stats.record_mint(amount, clock.slot)?;
+            }
+
+            emit!(MintEvent {
+                recipient: recipient_info.key(),
+                amount,
+                fee,
+                amount_after_fee,
+                zcash_txid: deposit.zcash_txid,
+                total_minted: stats.total_minted,
+            });
+        }
+
+        // The circuit breaker sees the batch as a single mint of its total
+        stats.breaker_volume.record(now, batch_total, u64::MAX)?;
+        let window_volume = stats
+            .breaker_volume
+            .total_within(bridge_state.circuit_breaker_window_hours);
+        if window_volume > bridge_state.circuit_breaker_threshold {
+            bridge_state.mints_paused = true;
+            bridge_state.circuit_breaker_tripped = true;
+
+            emit!(CircuitBreakerTrippedEvent {
+                window_volume,
+                threshold: bridge_state.circuit_breaker_threshold,
+                window_hours: bridge_state.circuit_breaker_window_hours,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Post a guardian-attested Merkle root of confirmed deposits for recipients to claim
+    pub fn post_mint_root(
+        ctx: Context<PostMintRoot>,
+        root: [u8; 32],
+        total_amount: u64,
+    ) -> Result<()> {
+        let bridge_state = &ctx.accounts.bridge_state;
+
+        // Verify the minter or authority
+        require!(
+            bridge_state.has_role(Role::Minter, &ctx.accounts.authority.key()),
+            BridgeError::Unauthorized
+        );
+
+        require!(
+            bridge_state.decommissioned_slot == 0,
+            BridgeError::BridgeDecommissioned
+        );
+        require!(total_amount > 0, BridgeError::InvalidAmount);
+
+        // Verify guardian quorum over the root and its total
+        let message = MintRoot::attestation_message(
+            bridge_state.network,
+            &bridge_state.key(),
+            &root,
+            total_amount,
+        );
+        let attested = ed25519::verified_signers(&ctx.accounts.instructions, &message)?;
+        require!(
+            ctx.accounts
+                .guardian_set
+                .has_quorum(&bridge_state.key(), ctx.remaining_accounts, &attested)?,
+            BridgeError::InsufficientGuardianSignatures
+        );
+
+        let mint_root = &mut ctx.accounts.mint_root;
+        mint_root.root = root;
+        mint_root.total_amount = total_amount;
+        mint_root.claimed_amount = 0;
+        mint_root.posted_slot = Clock::get()?.slot;
+        mint_root.bump = ctx.bumps.mint_root;
+
+        emit!(MintRootPostedEvent {
+            root,
+            total_amount,
+            slot: mint_root.posted_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Publish a guardian-attested snapshot of the Zcash reserves (anyone)
+    ///
+    /// Guardians sign the Zcash height, the ZEC they hold, the wZEC they count as
+    /// circulating and a Merkle root over the reserve's notes and UTXOs. Snapshots
+    /// go into a ring of `ReserveAttestation::RING_SIZE` accounts, overwriting the
+    /// oldest, and record the mint's actual supply when posted alongside the
+    /// guardians' figure. The sequence is part of the signed message, so a snapshot
+    /// can't be posted twice.
+    pub fn post_reserve_attestation(
+        ctx: Context<PostReserveAttestation>,
+        zcash_height: u32,
+        reserve_zatoshis: u64,
+        circulating_wzec: u64,
+        reserve_root: [u8; 32],
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+        let sequence = bridge_state.reserve_attestation_count;
+
+        // Verify guardian quorum over the snapshot at this sequence
+        let message = ReserveAttestation::attestation_message(
+            bridge_state.network,
+            &bridge_state.key(),
+            sequence,
+            zcash_height,
+            reserve_zatoshis,
+            circulating_wzec,
+            &reserve_root,
+        );
+        let attested = ed25519::verified_signers(&ctx.accounts.instructions, &message)?;
+        require!(
+            ctx.accounts
+                .guardian_set
+                .has_quorum(&bridge_state.key(), ctx.remaining_accounts, &attested)?,
+            BridgeError::InsufficientGuardianSignatures
+        );
+
+        bridge_state.reserve_attestation_count = sequence
+            .checked_add(1)
+            .ok_or(BridgeError::Overflow)?;
+
+        let attestation = &mut ctx.accounts.reserve_attestation;
+        attestation.sequence = sequence;
+        attestation.zcash_height = zcash_height;
+        attestation.reserve_zatoshis = reserve_zatoshis;
+        attestation.circulating_wzec = circulating_wzec;
+        attestation.mint_supply = ctx.accounts.mint.supply;
+        attestation.reserve_root = reserve_root;
+        attestation.posted_slot = Clock::get()?.slot;
+        attestation.bump = ctx.bumps.reserve_attestation;
+
+        emit!(ReserveAttestationPostedEvent {
+            sequence,
+            zcash_height,
+            reserve_zatoshis,
+            circulating_wzec,
+            mint_supply: attestation.mint_supply,
+            reserve_root,
+        });
+
+        Ok(())
+    }
+
+    /// Claim a deposit from a posted Merkle root; the recipient signs and pays the receipt rent
+    ///
+    /// The claimant's `FeeExemption`, if any, may be passed as a remaining account.
+    pub fn claim_mint(
+        ctx: Context<ClaimMint>,
         amount: u64,
-        zec_address: String,
+        zcash_txid: [u8; 32],
+        proof: Vec<[u8; 32]>,
     ) -> Result<()> {
         let bridge_state = &mut ctx.accounts.bridge_state;
 
-        // Check if bridge is paused
-        require!(!bridge_state.paused, BridgeError::BridgePaused);
+        require!(
+            bridge_state.decommissioned_slot == 0,
+            BridgeError::BridgeDecommissioned
+        );
+        require!(
+            !bridge_state.mints_paused && !bridge_state.guardian_paused(),
+            BridgeError::BridgePaused
+        );
+
+        // Verify the deposit is in the posted root
+        let mint_root = &mut ctx.accounts.mint_root;
+        let leaf = MintRoot::leaf(&ctx.accounts.claimant.key(), amount, &zcash_txid);
+        require!(
+            mint_root.verify(&proof, leaf),
+            BridgeError::InvalidMerkleProof
+        );
+        mint_root.claimed_amount = mint_root
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+        require!(
+            mint_root.claimed_amount <= mint_root.total_amount,
+            BridgeError::MintRootExhausted
+        );
 
         // Validate amount
         require!(amount > 0, BridgeError::InvalidAmount);
+        require!(
+            amount <= bridge_state.max_mint_amount,
+            BridgeError::MintAmountExceedsLimit
+        );
 
-        // Validate ZEC address format (basic check for testnet shielded address)
+        // Enforce the rolling 24-hour mint limit
+        let now = Clock::get()?.unix_timestamp;
+        let mut stats = ctx.accounts.bridge_stats.load_mut()?;
+        stats
+            .mint_volume
+            .record(now, amount, bridge_state.daily_mint_limit)?;
+
+        // Enforce the recipient's own limits, if any
+        UserLimits::record_mint(&ctx.accounts.user_limits, now, amount)?;
+
+        // Refuse recipients on the deny list
+        DenyListEntry::check(&ctx.accounts.deny_list_entry)?;
+
+        // Large transfers need a compliance credential, if the bridge requires one
+        bridge_state.check_compliance(ctx.remaining_accounts, &ctx.accounts.claimant.key(), amount)?;
+
+        // Reject deposits that have already been minted or claimed
+        let deposit_receipt = &mut ctx.accounts.deposit_receipt;
         require!(
-            zec_address.starts_with("ztestsapling1") && zec_address.len() >= 78,
-            BridgeError::InvalidZecAddress
+            deposit_receipt.amount == 0,
+            BridgeError::DepositAlreadyProcessed
         );
 
-        // Calculate fee
-        let fee = amount
-            .checked_mul(bridge_state.fee_percentage as u64)
-            .ok_or(BridgeError::Overflow)?
-            .checked_div(10000)
-            .ok_or(BridgeError::Overflow)?;
+        deposit_receipt.amount = amount;
+        deposit_receipt.recipient = ctx.accounts.recipient_token_account.key();
+        deposit_receipt.slot = Clock::get()?.slot;
+        deposit_receipt.bump = ctx.bumps.deposit_receipt;
 
+        // Calculate fee, at the claimant's exempt rate if they have one
+        let fee_bps = FeeExemption::mint_fee_bps(
+            ctx.remaining_accounts,
+            &bridge_state.key(),
+            &ctx.accounts.claimant.key(),
+            bridge_state.mint_fee_bps,
+        )?;
+        let fee = calculate_fee(amount, fee_bps)?;
         let amount_after_fee = amount
             .checked_sub(fee)
             .ok_or(BridgeError::Overflow)?;
 
-        // Burn tokens from user
-        let cpi_accounts = Burn {
+        // Mint tokens to the claimant, signed by the mint authority PDA
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [bridge_state.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"mint_authority", mint_key.as_ref(), &bump]];
+        let cpi_accounts = MintTo {
             mint: ctx.accounts.mint.to_account_info(),
-            from: ctx.accounts.user_token_account.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::burn(cpi_ctx, amount)?;
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::mint_to(cpi_ctx, amount_after_fee)?;
 
-        // Update state
-        bridge_state.total_burned = bridge_state
-            .total_burned
+        // Mint the fee share into the fee vault
+        if fee > 0 {
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.fee_vault.to_account_info(),
+                authority: ctx.accounts.mint_authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::mint_to(cpi_ctx, fee)?;
+        }
+
+        // Update stats
+        stats.total_minted = stats
+            .total_minted
             .checked_add(amount)
             .ok_or(BridgeError::Overflow)?;
 
-        bridge_state.fee_collected = bridge_state
+        stats.fee_collected = stats
             .fee_collected
             .checked_add(fee)
             .ok_or(BridgeError::Overflow)?;
 
-        msg!("Burned {} wZEC from {}", amount, ctx.accounts.user.key());
-        msg!("ZEC destination: {}", zec_address);
-        msg!("Amount after fee: {}", amount_after_fee);
-        msg!("Fee collected: {}", fee);
+        emit!(MintEvent {
+            recipient: ctx.accounts.recipient_token_account.key(),
+            amount,
+            fee,
+            amount_after_fee,
+            zcash_txid,
+            total_minted: stats.total_minted,
+        });
+
+        // Claims count towards the circuit breaker like any other mint
+        stats.breaker_volume.record(now, amount, u64::MAX)?;
+        let window_volume = stats
+            .breaker_volume
+            .total_within(bridge_state.circuit_breaker_window_hours);
+        if window_volume > bridge_state.circuit_breaker_threshold {
+            bridge_state.mints_paused = true;
+            bridge_state.circuit_breaker_tripped = true;
+
+            emit!(CircuitBreakerTrippedEvent {
+                window_volume,
+                threshold: bridge_state.circuit_breaker_threshold,
+                window_hours: bridge_state.circuit_breaker_window_hours,
+            });
+        }
 
         Ok(())
     }
 
-    /// Update bridge authority (admin only)
-    pub fn update_authority(ctx: Context<UpdateAuthority>, new_authority: Pubkey) -> Result<()> {
+    /// Mint a transparent Zcash deposit proven against the light client (permissionless)
+    ///
+    /// `raw_tx` must pay the bridge's deposit address and name the Solana recipient
+    /// in an `OP_RETURN` output; its block must have reached the confirmation depth.
+    /// The recipient's `FeeExemption`, if any, may be passed as a remaining account.
+    pub fn mint_with_spv_proof(
+        ctx: Context<MintWithSpvProof>,
+        zcash_txid: [u8; 32],
+        raw_tx: Vec<u8>,
+        block_height: u32,
+        tx_index: u32,
+        merkle_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
         let bridge_state = &mut ctx.accounts.bridge_state;
 
-        // Verify current authority
         require!(
-            ctx.accounts.authority.key() == bridge_state.authority,
-            BridgeError::Unauthorized
+            bridge_state.decommissioned_slot == 0,
+            BridgeError::BridgeDecommissioned
+        );
+        require!(
+            !bridge_state.mints_paused && !bridge_state.guardian_paused(),
+            BridgeError::BridgePaused
+        );
+        require!(
+            bridge_state.zcash_deposit_pubkey_hash != [0; 20],
+            BridgeError::DepositAddressNotSet
         );
 
-        let old_authority = bridge_state.authority;
-        bridge_state.authority = new_authority;
+        // Prove the transaction is in a confirmed block
+        let transaction = zcash_spv::ZcashTransaction::parse(&raw_tx)?;
+        require!(
+            transaction.txid == zcash_txid,
+            BridgeError::InvalidZcashTransaction
+        );
+        {
+            let light_client = ctx.accounts.light_client.load()?;
+            let header = light_client
+                .confirmed_header(block_height)
+                .ok_or(BridgeError::HeaderNotConfirmed)?;
+            require!(
+                header.merkle_root != [0; 32]
+                    && zcash_spv::verify_merkle_proof(
+                        transaction.txid,
+                        tx_index,
+                        &merkle_proof,
+                        &header.merkle_root,
+                    ),
+                BridgeError::InvalidMerkleProof
+            );
+        }
 
-        msg!("Authority updated from {} to {}", old_authority, new_authority);
+        // The deposit pays the bridge and names its recipient
+        let (amount, recipient) =
+            transaction.deposit_to(&bridge_state.zcash_deposit_pubkey_hash)?;
+        require!(
+            ctx.accounts.recipient_token_account.owner == recipient,
+            BridgeError::InvalidTokenAccount
+        );
 
-        Ok(())
-    }
+        // Validate amount
+        require!(amount > 0, BridgeError::InvalidAmount);
+        require!(
+            amount <= bridge_state.max_mint_amount,
+            BridgeError::MintAmountExceedsLimit
+        );
 
-    /// Pause bridge operations (admin only)
-    pub fn pause_bridge(ctx: Context<PauseBridge>) -> Result<()> {
-        let bridge_state = &mut ctx.accounts.bridge_state;
+        // Enforce the rolling 24-hour mint limit
+        let now = Clock::get()?.unix_timestamp;
+        let mut stats = ctx.accounts.bridge_stats.load_mut()?;
+        stats
+            .mint_volume
+            .record(now, amount, bridge_state.daily_mint_limit)?;
 
-        // Verify authority
+        // Enforce the recipient's own limits, if any
+        UserLimits::record_mint(&ctx.accounts.user_limits, now, amount)?;
+
+        // Refuse recipients on the deny list
+        DenyListEntry::check(&ctx.accounts.deny_list_entry)?;
+
+        // Large transfers need a compliance credential, if the bridge requires one
+        bridge_state.check_compliance(ctx.remaining_accounts, &ctx.accounts.recipient_token_account.owner, amount)?;
+
+        // Reject deposits that have already been minted
+        let deposit_receipt = &mut ctx.accounts.deposit_receipt;
         require!(
-            ctx.accounts.authority.key() == bridge_state.authority,
-            BridgeError::Unauthorized
+            deposit_receipt.amount == 0,
+            BridgeError::DepositAlreadyProcessed
         );
 
-        bridge_state.paused = true;
+        deposit_receipt.amount = amount;
+        deposit_receipt.recipient = ctx.accounts.recipient_token_account.key();
+        deposit_receipt.slot = Clock::get()?.slot;
+        deposit_receipt.bump = ctx.bumps.deposit_receipt;
+
+        // Calculate fee, at the recipient's exempt rate if they have one
+        let fee_bps = FeeExemption::mint_fee_bps(
+            ctx.remaining_accounts,
+            &bridge_state.key(),
+            &ctx.accounts.recipient_token_account.owner,
+            bridge_state.mint_fee_bps,
+        )?;
+        let fee = calculate_fee(amount, fee_bps)?;
+        let amount_after_fee = amount
+            .checked_sub(fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        // Mint tokens to recipient, signed by the mint authority PDA
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [bridge_state.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"mint_authority", mint_key.as_ref(), &bump]];
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::mint_to(cpi_ctx, amount_after_fee)?;
+
+        // Mint the fee share into the fee vault
+        if fee > 0 {
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.fee_vault.to_account_info(),
+                authority: ctx.accounts.mint_authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::mint_to(cpi_ctx, fee)?;
+        }
+
+        // Update stats
+        stats.total_minted = stats
+            .total_minted
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+
+        stats.fee_collected = stats
+            .fee_collected
+            .checked_add(fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        emit!(MintEvent {
+            recipient: ctx.accounts.recipient_token_account.key(),
+            amount,
+            fee,
+            amount_after_fee,
+            zcash_txid,
+            total_minted: stats.total_minted,
+        });
 
-        msg!("Bridge paused by {}", ctx.accounts.authority.key());
+        // Proven deposits still count towards the circuit breaker
+        stats.breaker_volume.record(now, amount, u64::MAX)?;
+        let window_volume = stats
+            .breaker_volume
+            .total_within(bridge_state.circuit_breaker_window_hours);
+        if window_volume > bridge_state.circuit_breaker_threshold {
+            bridge_state.mints_paused = true;
+            bridge_state.circuit_breaker_tripped = true;
+
+            emit!(CircuitBreakerTrippedEvent {
+                window_volume,
+                threshold: bridge_state.circuit_breaker_threshold,
+                window_hours: bridge_state.circuit_breaker_window_hours,
+            });
+        }
 
         Ok(())
     }
 
-    /// Resume bridge operations (admin only)
-    pub fn resume_bridge(ctx: Context<ResumeBridge>) -> Result<()> {
-        let bridge_state = &mut ctx.accounts.bridge_state;
-
+    /// Install or rotate the shielded deposit circuit's verifying key (admin only)
+    pub fn set_verifying_key(
+        ctx: Context<SetVerifyingKey>,
+        alpha_g1: [u8; 64],
+        beta_g2: [u8; 128],
+        gamma_g2: [u8; 128],
+        delta_g2: [u8; 128],
+        ic: Vec<[u8; 64]>,
+    ) -> Result<()> {
         // Verify authority
         require!(
-            ctx.accounts.authority.key() == bridge_state.authority,
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
             BridgeError::Unauthorized
         );
+        require!(
+            ic.len() == groth16::NUM_PUBLIC_INPUTS + 1,
+            BridgeError::InvalidVerifyingKey
+        );
 
-        bridge_state.paused = false;
+        let verifying_key = &mut ctx.accounts.verifying_key;
+        verifying_key.alpha_g1 = alpha_g1;
+        verifying_key.beta_g2 = beta_g2;
+        verifying_key.gamma_g2 = gamma_g2;
+        verifying_key.delta_g2 = delta_g2;
+        verifying_key.ic = ic;
+        verifying_key.bump = ctx.bumps.verifying_key;
 
-        msg!("Bridge resumed by {}", ctx.accounts.authority.key());
+        emit!(VerifyingKeySetEvent {
+            authority: ctx.accounts.authority.key(),
+            verifying_key: verifying_key.key(),
+        });
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + BridgeState::LEN,
-        seeds = [b"bridge_state"],
-        bump
-    )]
-    pub bridge_state: Account<'info, BridgeState>,
-    
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    /// Mint a shielded Zcash deposit proven with a Groth16 proof (permissionless)
+    ///
+    /// The proof shows that `zcash_txid`, in the confirmed block at `block_height`,
+    /// pays `amount` to the bridge's shielded address with the recipient's wallet
+    /// in the note memo. The recipient's `FeeExemption`, if any, may be passed as a
+    /// remaining account.
+    pub fn mint_with_shielded_proof(
+        ctx: Context<MintWithShieldedProof>,
+        zcash_txid: [u8; 32],
+        block_height: u32,
+        amount: u64,
+        proof: Groth16Proof,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
 
-#[derive(Accounts)]
-pub struct MintWZEC<'info> {
-    #[account(
-        mut,
-        seeds = [b"bridge_state"],
-        bump
-    )]
-    pub bridge_state: Account<'info, BridgeState>,
-    
+        require!(
+            bridge_state.decommissioned_slot == 0,
+            BridgeError::BridgeDecommissioned
+        );
+        require!(
+            !bridge_state.mints_paused && !bridge_state.guardian_paused(),
+            BridgeError::BridgePaused
+        );
+
+        // Bind the proof to a confirmed block, the txid, the amount and the recipient
+        let block_hash = {
+            let light_client = ctx.accounts.light_client.load()?;
+            light_client
+                .confirmed_header(block_height)
+                .filter(|header| header.hash != [0; 32])
+                .ok_or(BridgeError::HeaderNotConfirmed)?
+                .hash
+        };
+        let [block_hash_high, block_hash_low] = groth16::split_u256(&block_hash);
+        let [txid_high, txid_low] = groth16::split_u256(&zcash_txid);
+        let [recipient_high, recipient_low] =
+            groth16::split_u256(&ctx.accounts.recipient_token_account.owner.to_bytes());
+        let public_inputs = [
+            block_hash_high,
+            block_hash_low,
+            txid_high,
+            txid_low,
+            groth16::u64_scalar(amount),
+            recipient_high,
+            recipient_low,
+        ];
+        ctx.accounts.verifying_key.verify(&proof, &public_inputs)?;
+
+        // Validate amount
+        require!(amount > 0, BridgeError::InvalidAmount);
+        require!(
+            amount <= bridge_state.max_mint_amount,
+            BridgeError::MintAmountExceedsLimit
+        );
+
+        // Enforce the rolling 24-hour mint limit
+        let now = Clock::get()?.unix_timestamp;
+        let mut stats = ctx.accounts.bridge_stats.load_mut()?;
+        stats
+            .mint_volume
+            .record(now, amount, bridge_state.daily_mint_limit)?;
+
+        // Enforce the recipient's own limits, if any
+        UserLimits::record_mint(&ctx.accounts.user_limits, now, amount)?;
+
+        // Refuse recipients on the deny list
+        DenyListEntry::check(&ctx.accounts.deny_list_entry)?;
+
+        // Large transfers need a compliance credential, if the bridge requires one
+        bridge_state.check_compliance(ctx.remaining_accounts, &ctx.accounts.recipient_token_account.owner, amount)?;
+
+        // Reject deposits that have already been minted
+        let deposit_receipt = &mut ctx.accounts.deposit_receipt;
+        require!(
+            deposit_receipt.amount == 0,
+            BridgeError::DepositAlreadyProcessed
+        );
+
+        deposit_receipt.amount = amount;
+        deposit_receipt.recipient = ctx.accounts.recipient_token_account.key();
+        deposit_receipt.slot = Clock::get()?.slot;
+        deposit_receipt.bump = ctx.bumps.deposit_receipt;
+
+        // Calculate fee, at the recipient's exempt rate if they have one
+        let fee_bps = FeeExemption::mint_fee_bps(
+            ctx.remaining_accounts,
+            &bridge_state.key(),
+            &ctx.accounts.recipient_token_account.owner,
+            bridge_state.mint_fee_bps,
+        )?;
+        let fee = calculate_fee(amount, fee_bps)?;
+        let amount_after_fee = amount
+            .checked_sub(fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        // Mint tokens to recipient, signed by the mint authority PDA
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [bridge_state.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"mint_authority", mint_key.as_ref(), &bump]];
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::mint_to(cpi_ctx, amount_after_fee)?;
+
+        // Mint the fee share into the fee vault
+        if fee > 0 {
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.fee_vault.to_account_info(),
+                authority: ctx.accounts.mint_authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::mint_to(cpi_ctx, fee)?;
+        }
+
+        // Update stats
+        stats.total_minted = stats
+            .total_minted
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+
+        stats.fee_collected = stats
+            .fee_collected
+            .checked_add(fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        emit!(MintEvent {
+            recipient: ctx.accounts.recipient_token_account.key(),
+            amount,
+            fee,
+            amount_after_fee,
+            zcash_txid,
+            total_minted: stats.total_minted,
+        });
+
+        // Proven deposits still count towards the circuit breaker
+        stats.breaker_volume.record(now, amount, u64::MAX)?;
+        let window_volume = stats
+            .breaker_volume
+            .total_within(bridge_state.circuit_breaker_window_hours);
+        if window_volume > bridge_state.circuit_breaker_threshold {
+            bridge_state.mints_paused = true;
+            bridge_state.circuit_breaker_tripped = true;
+
+            emit!(CircuitBreakerTrippedEvent {
+                window_volume,
+                threshold: bridge_state.circuit_breaker_threshold,
+                window_hours: bridge_state.circuit_breaker_window_hours,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Burn wZEC tokens and emit withdrawal event
+    ///
+    /// `memo`, if given, is carried into the memo field of the shielded payout.
+    /// `max_fee_bps`, if given, fails the burn when the bridge's burn fee has been
+    /// raised above it since the caller quoted. The user's `FeeExemption`, if any,
+    /// is passed as a remaining account.
+    pub fn burn_wzec(
+        ctx: Context<BurnWZEC>,
+        amount: u64,
+        zec_address: String,
+        memo: Option<Vec<u8>>,
+        max_fee_bps: Option<u16>,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Check if burning is paused
+        require!(!bridge_state.burns_paused, BridgeError::BridgePaused);
+
+        // After decommissioning, burns stay open only through the grace period
+        if bridge_state.decommissioned_slot != 0 {
+            let exit_deadline = bridge_state
+                .decommissioned_slot
+                .checked_add(bridge_state.decommission_grace_slots)
+                .ok_or(BridgeError::Overflow)?;
+            require!(
+                Clock::get()?.slot <= exit_deadline,
+                BridgeError::BridgeDecommissioned
+            );
+        }
+
+        // Validate amount
+        require!(amount > 0, BridgeError::InvalidAmount);
+        require!(
+            amount <= bridge_state.max_burn_amount,
+            BridgeError::BurnAmountExceedsLimit
+        );
+        require!(
+            amount >= bridge_state.min_burn_amount,
+            BridgeError::BurnAmountBelowMinimum
+        );
+
+        // Enforce the user's own limits, if any
+        let now = Clock::get()?.unix_timestamp;
+        UserLimits::record_burn(&ctx.accounts.user_limits, now, amount)?;
+
+        // Refuse users on the deny list
+        DenyListEntry::check(&ctx.accounts.deny_list_entry)?;
+
+        // Large transfers need a compliance credential, if the bridge requires one
+        bridge_state.check_compliance(ctx.remaining_accounts, &ctx.accounts.user.key(), amount)?;
+
+        // Validate the ZEC address against the bridge's network
+        require!(
+            zec_address.len() <= WithdrawalRequest::MAX_ZEC_ADDRESS_LEN
+                && zcash_address::is_valid(&zec_address, bridge_state.network),
+            BridgeError::InvalidZecAddress
+        );
+        let memo = memo.unwrap_or_default();
+        require!(
+            memo.len() <= WithdrawalRequest::MAX_MEMO_LEN,
+            BridgeError::MemoTooLong
+        );
+
+        // Calculate fee, at the user's exempt rate if they have one and within the
+        // caller's slippage bound
+        let fee_bps = FeeExemption::burn_fee_bps(
+            ctx.remaining_accounts,
+            &bridge_state.key(),
+            &ctx.accounts.user.key(),
+            bridge_state.burn_fee_bps,
+        )?;
+        require!(
+            max_fee_bps.map_or(true, |max_fee_bps| fee_bps <= max_fee_bps),
+            BridgeError::FeeExceedsMaximum
+        );
+        let fee = calculate_fee(amount, fee_bps)?;
+
+        let amount_after_fee = amount
+            .checked_sub(fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        // Send the insurance fund its share of the fee
+        let insurance_share = calculate_fee(fee, bridge_state.insurance_share_bps)?;
+        if insurance_share > 0 {
+            let insurance_vault = ctx
+                .accounts
+                .insurance_vault
+                .as_ref()
+                .ok_or(BridgeError::InsuranceVaultRequired)?;
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: insurance_vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, insurance_share, ctx.accounts.mint.decimals)?;
+        }
+
+        // Move the rest of the fee into the fee vault
+        let vault_fee = fee
+            .checked_sub(insurance_share)
+            .ok_or(BridgeError::Overflow)?;
+        if vault_fee > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.fee_vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, vault_fee, ctx.accounts.mint.decimals)?;
+        }
+
+        // Burn the remainder from user
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.user_token_account.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token_interface::burn(cpi_ctx, amount_after_fee)?;
+
+        // Update stats
+        let mut stats = ctx.accounts.bridge_stats.load_mut()?;
+        stats.total_burned = stats
+            .total_burned
+            .checked_add(amount_after_fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        stats.fee_collected = stats
+            .fee_collected
+            .checked_add(fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        // Record the withdrawal for the relayer to pay out
+        let nonce = bridge_state.withdrawal_nonce;
+        bridge_state.withdrawal_nonce = nonce
+            .checked_add(1)
+            .ok_or(BridgeError::Overflow)?;
+        bridge_state.open_withdrawals = bridge_state
+            .open_withdrawals
+            .checked_add(1)
+            .ok_or(BridgeError::Overflow)?;
+
+        let withdrawal_request = &mut ctx.accounts.withdrawal_request;
+        withdrawal_request.user = ctx.accounts.user.key();
+        withdrawal_request.nonce = nonce;
+        withdrawal_request.bump = ctx.bumps.withdrawal_request;
+        withdrawal_request.amount = amount;
+        withdrawal_request.fee = fee;
+        withdrawal_request.net_amount = amount_after_fee;
+        withdrawal_request.zec_address = zec_address.clone();
+        withdrawal_request.status = WithdrawalStatus::Pending;
+        withdrawal_request.created_slot = Clock::get()?.slot;
+        withdrawal_request.payout_txid = [0; 32];
+        withdrawal_request.payout_height = 0;
+        withdrawal_request.vetoed_by = Pubkey::default();
+        withdrawal_request.veto_reason = 0;
+        withdrawal_request.memo = memo.clone();
+        withdrawal_request.payer = ctx.accounts.user.key();
+
+        // Large withdrawals only become payable after the timelock delay
+        let large_withdrawal_threshold = bridge_state.large_withdrawal_threshold_now(ctx.remaining_accounts)?;
+        withdrawal_request.unlock_slot = if amount > large_withdrawal_threshold {
+            withdrawal_request
+                .created_slot
+                .checked_add(bridge_state.large_withdrawal_delay_slots)
+                .ok_or(BridgeError::Overflow)?
+        } else {
+            withdrawal_request.created_slot
+        };
+
+        emit!(BurnEvent {
+            user: ctx.accounts.user.key(),
+            nonce,
+            amount,
+            fee,
+            amount_after_fee,
+            zec_address,
+            memo,
+            unlock_slot: withdrawal_request.unlock_slot,
+            total_burned: stats.total_burned,
+        });
+
+        Ok(())
+    }
+
+    /// Burn enough wZEC that exactly `net_zec_amount` is paid out after the burn fee
+    ///
+    /// The gross amount is worked out from the current fee and burned through
+    /// `burn_wzec`, whose `memo` and `max_fee_bps` are passed along; limits and the
+    /// minimum apply to the gross amount.
+    pub fn burn_wzec_exact_out(
+        ctx: Context<BurnWZEC>,
+        net_zec_amount: u64,
+        zec_address: String,
+        memo: Option<Vec<u8>>,
+        max_fee_bps: Option<u16>,
+    ) -> Result<()> {
+        let fee_bps = FeeExemption::burn_fee_bps(
+            ctx.remaining_accounts,
+            &ctx.accounts.bridge_state.key(),
+            &ctx.accounts.user.key(),
+            ctx.accounts.bridge_state.burn_fee_bps,
+        )?;
+        let amount = gross_for_net(net_zec_amount, fee_bps)?;
+        burn_wzec(ctx, amount, zec_address, memo, max_fee_bps)
+    }
+
+    /// Burn wZEC on behalf of an owner who signed a permit off-chain (named relayer)
+    ///
+    /// The owner signs `PermitNonce::message` for this exact amount, address, relayer,
+    /// relayer fee, nonce and expiry, verified through the ed25519 instruction
+    /// preceding this one, and must have approved the bridge state PDA as delegate of
+    /// their token account for `amount + relayer_fee`. The relayer pays the
+    /// transaction fee and rent and is paid `relayer_fee` in wZEC, so owners without
+    /// SOL can exit. Naming the relayer keeps others from front-running its
+    /// submission for the fee; nonces are used in order.
+    pub fn burn_wzec_with_permit(
+        ctx: Context<BurnWZECWithPermit>,
+        amount: u64,
+        zec_address: String,
+        relayer_fee: u64,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [ctx.accounts.bridge_state.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"bridge_state", mint_key.as_ref(), &bump]];
+        let bridge_state = &mut ctx.accounts.bridge_state;
+        let owner = ctx.accounts.owner.key();
+
+        // Verify the owner signed this permit and that it is still usable
+        require!(
+            Clock::get()?.unix_timestamp <= expiry,
+            BridgeError::PermitExpired
+        );
+        let permit_nonce = &mut ctx.accounts.permit_nonce;
+        require!(
+            nonce == permit_nonce.next_nonce,
+            BridgeError::InvalidPermitNonce
+        );
+        let message = PermitNonce::message(
+            bridge_state.network,
+            &bridge_state.key(),
+            &owner,
+            &ctx.accounts.relayer.key(),
+            amount,
+            relayer_fee,
+            nonce,
+            expiry,
+            &zec_address,
+        );
+        let signers = ed25519::verified_signers(&ctx.accounts.instructions, &message)?;
+        require!(signers.contains(&owner), BridgeError::InvalidPermitSignature);
+
+        permit_nonce.owner = owner;
+        permit_nonce.bump = ctx.bumps.permit_nonce;
+        permit_nonce.next_nonce = nonce.checked_add(1).ok_or(BridgeError::Overflow)?;
+        permit_nonce.relayer_fees_paid = permit_nonce
+            .relayer_fees_paid
+            .checked_add(relayer_fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        // Check if burning is paused
+        require!(!bridge_state.burns_paused, BridgeError::BridgePaused);
+
+        // After decommissioning, burns stay open only through the grace period
+        if bridge_state.decommissioned_slot != 0 {
+            let exit_deadline = bridge_state
+                .decommissioned_slot
+                .checked_add(bridge_state.decommission_grace_slots)
+                .ok_or(BridgeError::Overflow)?;
+            require!(
+                Clock::get()?.slot <= exit_deadline,
+                BridgeError::BridgeDecommissioned
+            );
+        }
+
+        // Validate amount
+        require!(amount > 0, BridgeError::InvalidAmount);
+        require!(
+            amount <= bridge_state.max_burn_amount,
+            BridgeError::BurnAmountExceedsLimit
+        );
+        require!(
+            amount >= bridge_state.min_burn_amount,
+            BridgeError::BurnAmountBelowMinimum
+        );
+
+        // Enforce the owner's own limits, if any
+        let now = Clock::get()?.unix_timestamp;
+        UserLimits::record_burn(&ctx.accounts.user_limits, now, amount)?;
+
+        // Refuse owners on the deny list
+        DenyListEntry::check(&ctx.accounts.deny_list_entry)?;
+
+        // Large transfers need a compliance credential, if the bridge requires one
+        bridge_state.check_compliance(ctx.remaining_accounts, &owner, amount)?;
+
+        // Validate the ZEC address against the bridge's network
+        require!(
+            zec_address.len() <= WithdrawalRequest::MAX_ZEC_ADDRESS_LEN
+                && zcash_address::is_valid(&zec_address, bridge_state.network),
+            BridgeError::InvalidZecAddress
+        );
+
+        // Calculate fee, at the owner's exempt rate if they have one
+        let fee_bps = FeeExemption::burn_fee_bps(
+            ctx.remaining_accounts,
+            &bridge_state.key(),
+            &owner,
+            bridge_state.burn_fee_bps,
+        )?;
+        let fee = calculate_fee(amount, fee_bps)?;
+
+        let amount_after_fee = amount
+            .checked_sub(fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        // Compensate the relayer for the Solana fees it paid, on top of the burn
+        if relayer_fee > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.relayer_token_account.to_account_info(),
+                authority: bridge_state.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::transfer_checked(cpi_ctx, relayer_fee, ctx.accounts.mint.decimals)?;
+        }
+
+        // Send the insurance fund its share of the fee, as the owner's delegate
+        let insurance_share = calculate_fee(fee, bridge_state.insurance_share_bps)?;
+        if insurance_share > 0 {
+            let insurance_vault = ctx
+                .accounts
+                .insurance_vault
+                .as_ref()
+                .ok_or(BridgeError::InsuranceVaultRequired)?;
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: insurance_vault.to_account_info(),
+                authority: bridge_state.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::transfer_checked(cpi_ctx, insurance_share, ctx.accounts.mint.decimals)?;
+        }
+
+        // Move the rest of the fee into the fee vault
+        let vault_fee = fee
+            .checked_sub(insurance_share)
+            .ok_or(BridgeError::Overflow)?;
+        if vault_fee > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.fee_vault.to_account_info(),
+                authority: bridge_state.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::transfer_checked(cpi_ctx, vault_fee, ctx.accounts.mint.decimals)?;
+        }
+
+        // Burn the remainder from the owner
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.user_token_account.to_account_info(),
+            authority: bridge_state.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::burn(cpi_ctx, amount_after_fee)?;
+
+        // Update stats
+        let mut stats = ctx.accounts.bridge_stats.load_mut()?;
+        stats.total_burned = stats
+            .total_burned
+            .checked_add(amount_after_fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        stats.fee_collected = stats
+            .fee_collected
+            .checked_add(fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        // Record the withdrawal for the relayer to pay out
+        let withdrawal_nonce = bridge_state.withdrawal_nonce;
+        bridge_state.withdrawal_nonce = withdrawal_nonce
+            .checked_add(1)
+            .ok_or(BridgeError::Overflow)?;
+        bridge_state.open_withdrawals = bridge_state
+            .open_withdrawals
+            .checked_add(1)
+            .ok_or(BridgeError::Overflow)?;
+
+        let withdrawal_request = &mut ctx.accounts.withdrawal_request;
+        withdrawal_request.user = owner;
+        withdrawal_request.nonce = withdrawal_nonce;
+        withdrawal_request.bump = ctx.bumps.withdrawal_request;
+        withdrawal_request.amount = amount;
+        withdrawal_request.fee = fee;
+        withdrawal_request.net_amount = amount_after_fee;
+        withdrawal_request.zec_address = zec_address.clone();
+        withdrawal_request.status = WithdrawalStatus::Pending;
+        withdrawal_request.created_slot = Clock::get()?.slot;
+        withdrawal_request.payout_txid = [0; 32];
+        withdrawal_request.payout_height = 0;
+        withdrawal_request.vetoed_by = Pubkey::default();
+        withdrawal_request.veto_reason = 0;
+        withdrawal_request.memo = Vec::new();
+        withdrawal_request.payer = ctx.accounts.relayer.key();
+
+        // Large withdrawals only become payable after the timelock delay
+        let large_withdrawal_threshold = bridge_state.large_withdrawal_threshold_now(ctx.remaining_accounts)?;
+        withdrawal_request.unlock_slot = if amount > large_withdrawal_threshold {
+            withdrawal_request
+                .created_slot
+                .checked_add(bridge_state.large_withdrawal_delay_slots)
+                .ok_or(BridgeError::Overflow)?
+        } else {
+            withdrawal_request.created_slot
+        };
+
+        emit!(BurnEvent {
+            user: owner,
+            nonce: withdrawal_nonce,
+            amount,
+            fee,
+            amount_after_fee,
+            zec_address,
+            memo: Vec::new(),
+            unlock_slot: withdrawal_request.unlock_slot,
+            total_burned: stats.total_burned,
+        });
+
+        emit!(BurnPermitUsedEvent {
+            owner,
+            relayer: ctx.accounts.relayer.key(),
+            relayer_fee,
+            permit_nonce: nonce,
+            withdrawal_nonce,
+        });
+
+        Ok(())
+    }
+
+    /// Invalidate every unused burn permit below `next_nonce` (owner only)
+    ///
+    /// Lets an owner withdraw permits handed to a relayer that has not submitted
+    /// them; another key may pay the transaction fee.
+    pub fn cancel_permits(ctx: Context<CancelPermits>, next_nonce: u64) -> Result<()> {
+        let permit_nonce = &mut ctx.accounts.permit_nonce;
+        require!(
+            next_nonce > permit_nonce.next_nonce,
+            BridgeError::InvalidPermitNonce
+        );
+
+        permit_nonce.owner = ctx.accounts.owner.key();
+        permit_nonce.bump = ctx.bumps.permit_nonce;
+        permit_nonce.next_nonce = next_nonce;
+
+        emit!(PermitsCancelledEvent {
+            owner: permit_nonce.owner,
+            next_nonce,
+        });
+
+        Ok(())
+    }
+
+    /// Mark a withdrawal as paid, recording the Zcash payout txid (admin only)
+    ///
+    /// `payout_output` locates the withdrawal's output when one payout pays
+    /// several withdrawals: its Sapling output, Orchard action or transparent
+    /// output index, by the pool of the withdrawal address. It is only emitted.
+    pub fn finalize_withdrawal(
+        ctx: Context<FinalizeWithdrawal>,
+        payout_txid: [u8; 32],
+        payout_height: u32,
+        payout_output: Option<u16>,
+    ) -> Result<()> {
+        // Verify authority or a bonded relayer
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority
+                || RelayerStake::is_bonded(&ctx.accounts.relayer_registry, &ctx.accounts.relayer_stake),
+            BridgeError::Unauthorized
+        );
+        RelayerStats::require_with_stake(&ctx.accounts.relayer_stake, &ctx.accounts.relayer_stats)?;
+
+        let withdrawal_request = &mut ctx.accounts.withdrawal_request;
+        require!(
+            withdrawal_request.status == WithdrawalStatus::Pending,
+            BridgeError::WithdrawalNotPending
+        );
+        require!(
+            Clock::get()?.slot >= withdrawal_request.unlock_slot,
+            BridgeError::WithdrawalTimelocked
+        );
+        ctx.accounts.bridge_state.record_settled(withdrawal_request.nonce)?;
+        ctx.accounts.bridge_state.record_closed_withdrawal();
+
+        withdrawal_request.status = WithdrawalStatus::Completed;
+        withdrawal_request.payout_txid = payout_txid;
+        withdrawal_request.payout_height = payout_height;
+
+        if let Some(relayer_stats) = ctx.accounts.relayer_stats.as_mut() {
+            relayer_stats.record_finalize(withdrawal_request.net_amount, Clock::get()?.slot)?;
+        }
+
+        // Credit a finalizing relayer with its share of the burn fee
+        if let (Some(registry), Some(relayer_stake)) = (
+            ctx.accounts.relayer_registry.as_ref(),
+            ctx.accounts.relayer_stake.as_mut(),
+        ) {
+            let reward = calculate_fee(withdrawal_request.fee, registry.reward_share_bps)?;
+            relayer_stake.finalized_withdrawals = relayer_stake
+                .finalized_withdrawals
+                .checked_add(1)
+                .ok_or(BridgeError::Overflow)?;
+            relayer_stake.pending_rewards = relayer_stake
+                .pending_rewards
+                .checked_add(reward)
+                .ok_or(BridgeError::Overflow)?;
+            let bridge_state = &mut ctx.accounts.bridge_state;
+            bridge_state.unclaimed_relayer_rewards = bridge_state
+                .unclaimed_relayer_rewards
+                .checked_add(reward)
+                .ok_or(BridgeError::Overflow)?;
+        }
+
+        emit!(WithdrawalFinalizedEvent {
+            user: withdrawal_request.user,
+            nonce: withdrawal_request.nonce,
+            net_amount: withdrawal_request.net_amount,
+            payout_txid,
+            payout_height,
+            payout_output,
+        });
+
+        Ok(())
+    }
+
+    /// Create the relayer registry with its bonding requirements (admin only)
+    pub fn initialize_relayer_registry(
+        ctx: Context<InitializeRelayerRegistry>,
+        min_stake: u64,
+        unbonding_slots: u64,
+    ) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+        require!(min_stake > 0, BridgeError::InvalidAmount);
+
+        let registry = &mut ctx.accounts.relayer_registry;
+        registry.min_stake = min_stake;
+        registry.unbonding_slots = unbonding_slots;
+        registry.total_staked = 0;
+        registry.slashed_lamports = 0;
+        registry.reward_share_bps = 0;
+        registry.bump = ctx.bumps.relayer_registry;
+
+        emit!(RelayerRegistryUpdatedEvent {
+            min_stake,
+            unbonding_slots,
+        });
+
+        Ok(())
+    }
+
+    /// Change the relayer bonding requirements (admin only)
+    pub fn update_relayer_registry(
+        ctx: Context<UpdateRelayerRegistry>,
+        min_stake: u64,
+        unbonding_slots: u64,
+    ) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+        require!(min_stake > 0, BridgeError::InvalidAmount);
+
+        let registry = &mut ctx.accounts.relayer_registry;
+        registry.min_stake = min_stake;
+        registry.unbonding_slots = unbonding_slots;
+
+        emit!(RelayerRegistryUpdatedEvent {
+            min_stake,
+            unbonding_slots,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of each withdrawal's burn fee paid to the relayer that finalizes it (admin only)
+    pub fn update_relayer_reward_share(
+        ctx: Context<UpdateRelayerRegistry>,
+        reward_share_bps: u16,
+    ) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+        require!(reward_share_bps <= 10000, BridgeError::InvalidRewardShare);
+
+        let registry = &mut ctx.accounts.relayer_registry;
+        let old_share_bps = registry.reward_share_bps;
+        registry.reward_share_bps = reward_share_bps;
+
+        emit!(RelayerRewardShareUpdatedEvent {
+            old_share_bps,
+            new_share_bps: reward_share_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Pay a relayer the burn fee share it has earned from the fee vault
+    pub fn claim_relayer_rewards(ctx: Context<ClaimRelayerRewards>) -> Result<()> {
+        let amount = ctx.accounts.relayer_stake.pending_rewards;
+        require!(amount > 0, BridgeError::InvalidAmount);
+
+        ctx.accounts.relayer_stake.pending_rewards = 0;
+        let bridge_state = &mut ctx.accounts.bridge_state;
+        bridge_state.unclaimed_relayer_rewards =
+            bridge_state.unclaimed_relayer_rewards.saturating_sub(amount);
+
+        // Transfer from the vault, signed by the bridge state PDA
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [ctx.accounts.bridge_state.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"bridge_state", mint_key.as_ref(), &bump]];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.fee_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.relayer_token_account.to_account_info(),
+            authority: ctx.accounts.bridge_state.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        emit!(RelayerRewardsClaimedEvent {
+            relayer: ctx.accounts.relayer.key(),
+            amount,
+            finalized_withdrawals: ctx.accounts.relayer_stake.finalized_withdrawals,
+        });
+
+        Ok(())
+    }
+
+    /// Create the activity counters of a relayer, or any key that mints or
+    /// finalizes withdrawals, so its work can be watched on-chain
+    pub fn initialize_relayer_stats(ctx: Context<InitializeRelayerStats>) -> Result<()> {
+        let relayer_stats = &mut ctx.accounts.relayer_stats;
+        relayer_stats.relayer = ctx.accounts.relayer.key();
+        relayer_stats.mints_submitted = 0;
+        relayer_stats.minted_volume = 0;
+        relayer_stats.withdrawals_finalized = 0;
+        relayer_stats.finalized_volume = 0;
+        relayer_stats.last_activity_slot = 0;
+        relayer_stats.bump = ctx.bumps.relayer_stats;
+        Ok(())
+    }
+
+    /// Bond SOL as a relayer; the lamports are held in the relayer's stake PDA
+    pub fn stake_relayer(ctx: Context<StakeRelayer>, amount: u64) -> Result<()> {
+        require!(amount > 0, BridgeError::InvalidAmount);
+
+        let cpi_accounts = system_program::Transfer {
+            from: ctx.accounts.relayer.to_account_info(),
+            to: ctx.accounts.relayer_stake.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        system_program::transfer(cpi_ctx, amount)?;
+
+        let relayer_stake = &mut ctx.accounts.relayer_stake;
+        relayer_stake.relayer = ctx.accounts.relayer.key();
+        relayer_stake.bump = ctx.bumps.relayer_stake;
+        relayer_stake.staked = relayer_stake
+            .staked
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+
+        let registry = &mut ctx.accounts.relayer_registry;
+        registry.total_staked = registry
+            .total_staked
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+
+        emit!(RelayerStakedEvent {
+            relayer: relayer_stake.relayer,
+            amount,
+            staked: relayer_stake.staked,
+        });
+
+        Ok(())
+    }
+
+    /// Start unbonding part of a relayer's stake; it stays slashable until withdrawn
+    pub fn unstake_relayer(ctx: Context<UnstakeRelayer>, amount: u64) -> Result<()> {
+        require!(amount > 0, BridgeError::InvalidAmount);
+
+        let registry = &mut ctx.accounts.relayer_registry;
+        let relayer_stake = &mut ctx.accounts.relayer_stake;
+        require!(
+            amount <= relayer_stake.staked,
+            BridgeError::InsufficientStake
+        );
+
+        relayer_stake.staked -= amount;
+        relayer_stake.unbonding = relayer_stake
+            .unbonding
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+        relayer_stake.unbonding_slot = Clock::get()?
+            .slot
+            .checked_add(registry.unbonding_slots)
+            .ok_or(BridgeError::Overflow)?;
+        registry.total_staked = registry.total_staked.saturating_sub(amount);
+
+        emit!(RelayerUnstakedEvent {
+            relayer: relayer_stake.relayer,
+            amount,
+            unbonding_slot: relayer_stake.unbonding_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Return unbonded stake to the relayer once the unbonding period has passed
+    pub fn withdraw_relayer_stake(ctx: Context<WithdrawRelayerStake>) -> Result<()> {
+        let relayer_stake = &mut ctx.accounts.relayer_stake;
+        let amount = relayer_stake.unbonding;
+        require!(amount > 0, BridgeError::InvalidAmount);
+        require!(
+            Clock::get()?.slot >= relayer_stake.unbonding_slot,
+            BridgeError::StakeStillUnbonding
+        );
+
+        relayer_stake.unbonding = 0;
+        relayer_stake.sub_lamports(amount)?;
+        ctx.accounts.relayer.add_lamports(amount)?;
+
+        emit!(RelayerStakeWithdrawnEvent {
+            relayer: relayer_stake.relayer,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Confiscate a relayer's stake for provable misbehavior (guardian quorum)
+    ///
+    /// Bonded stake is slashed before unbonding stake. `evidence` references the
+    /// misbehavior, e.g. the hash of the offending attestation. Guardians approving
+    /// the slash are passed as signer remaining accounts.
+    pub fn slash_relayer(
+        ctx: Context<SlashRelayer>,
+        amount: u64,
+        evidence: [u8; 32],
+    ) -> Result<()> {
+        let signatures = ctx
+            .accounts
+            .guardian_set
+            .count_signers(ctx.remaining_accounts, &[]);
+        require!(
+            signatures >= ctx.accounts.guardian_set.threshold as usize,
+            BridgeError::InsufficientGuardianSignatures
+        );
+
+        let registry = &mut ctx.accounts.relayer_registry;
+        let relayer_stake = &mut ctx.accounts.relayer_stake;
+        require!(amount > 0, BridgeError::InvalidAmount);
+        require!(
+            amount <= relayer_stake.staked.saturating_add(relayer_stake.unbonding),
+            BridgeError::InsufficientStake
+        );
+
+        let from_staked = amount.min(relayer_stake.staked);
+        relayer_stake.staked -= from_staked;
+        relayer_stake.unbonding -= amount - from_staked;
+        relayer_stake.slashed = relayer_stake
+            .slashed
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+        registry.total_staked = registry.total_staked.saturating_sub(from_staked);
+        registry.slashed_lamports = registry
+            .slashed_lamports
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+
+        // Confiscated lamports move into the registry
+        relayer_stake.sub_lamports(amount)?;
+        registry.add_lamports(amount)?;
+
+        emit!(RelayerSlashedEvent {
+            relayer: relayer_stake.relayer,
+            amount,
+            evidence,
+            signatures: signatures as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Move confiscated relayer stake out of the registry to the authority (admin only)
+    pub fn sweep_slashed_lamports(ctx: Context<SweepSlashedLamports>) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        let registry = &mut ctx.accounts.relayer_registry;
+        let amount = registry.slashed_lamports;
+        require!(amount > 0, BridgeError::InvalidAmount);
+
+        registry.slashed_lamports = 0;
+        registry.sub_lamports(amount)?;
+        ctx.accounts.authority.add_lamports(amount)?;
+
+        emit!(SlashedLamportsSweptEvent {
+            authority: ctx.accounts.authority.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Freeze a pending withdrawal during its timelock window (any guardian)
+    pub fn veto_withdrawal(ctx: Context<VetoWithdrawal>, reason_code: u8) -> Result<()> {
+        require!(
+            ctx.accounts
+                .guardian_set
+                .guardians
+                .contains(&ctx.accounts.guardian.key()),
+            BridgeError::NotAGuardian
+        );
+
+        let withdrawal_request = &mut ctx.accounts.withdrawal_request;
+        require!(
+            withdrawal_request.status == WithdrawalStatus::Pending,
+            BridgeError::WithdrawalNotPending
+        );
+        require!(
+            Clock::get()?.slot < withdrawal_request.unlock_slot,
+            BridgeError::VetoWindowClosed
+        );
+
+        withdrawal_request.status = WithdrawalStatus::Vetoed;
+        withdrawal_request.vetoed_by = ctx.accounts.guardian.key();
+        withdrawal_request.veto_reason = reason_code;
+
+        emit!(WithdrawalVetoedEvent {
+            nonce: withdrawal_request.nonce,
+            guardian: withdrawal_request.vetoed_by,
+            reason_code,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a veto and return the withdrawal to Pending (guardian quorum only)
+    ///
+    /// Guardians approving the release are passed as signer remaining accounts.
+    pub fn unveto_withdrawal(ctx: Context<UnvetoWithdrawal>) -> Result<()> {
+        let signatures = ctx
+            .accounts
+            .guardian_set
+            .count_signers(ctx.remaining_accounts, &[]);
+        require!(
+            signatures >= ctx.accounts.guardian_set.threshold as usize,
+            BridgeError::InsufficientGuardianSignatures
+        );
+
+        let withdrawal_request = &mut ctx.accounts.withdrawal_request;
+        require!(
+            withdrawal_request.status == WithdrawalStatus::Vetoed,
+            BridgeError::WithdrawalNotVetoed
+        );
+
+        withdrawal_request.status = WithdrawalStatus::Pending;
+        withdrawal_request.vetoed_by = Pubkey::default();
+        withdrawal_request.veto_reason = 0;
+
+        emit!(WithdrawalUnvetoedEvent {
+            nonce: withdrawal_request.nonce,
+            signatures: signatures as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel an unpayable withdrawal and re-mint its net amount to the user,
+    /// less the cancellation fee (admin only)
+    ///
+    /// Vetoed withdrawals can be cancelled too, so a veto that is never lifted
+    /// doesn't strand the burned amount.
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        let withdrawal_request = &mut ctx.accounts.withdrawal_request;
+        require!(
+            matches!(
+                withdrawal_request.status,
+                WithdrawalStatus::Pending | WithdrawalStatus::Vetoed
+            ),
+            BridgeError::WithdrawalNotPending
+        );
+
+        let cancellation_fee = calculate_fee(
+            withdrawal_request.net_amount,
+            bridge_state.cancellation_fee_bps,
+        )?;
+        let refund_amount = withdrawal_request
+            .net_amount
+            .checked_sub(cancellation_fee)
+            .ok_or(BridgeError::Overflow)?;
+        bridge_state.record_settled(withdrawal_request.nonce)?;
+        bridge_state.record_closed_withdrawal();
+
+        withdrawal_request.status = WithdrawalStatus::Cancelled;
+
+        // Re-mint the refund to the user, signed by the mint authority PDA
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [bridge_state.mint_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"mint_authority", mint_key.as_ref(), &bump]];
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::mint_to(cpi_ctx, refund_amount)?;
+
+        // Mint the cancellation fee into the fee vault
+        if cancellation_fee > 0 {
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.fee_vault.to_account_info(),
+                authority: ctx.accounts.mint_authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::mint_to(cpi_ctx, cancellation_fee)?;
+        }
+
+        // Update stats
+        let mut stats = ctx.accounts.bridge_stats.load_mut()?;
+        stats.total_minted = stats
+            .total_minted
+            .checked_add(withdrawal_request.net_amount)
+            .ok_or(BridgeError::Overflow)?;
+
+        stats.fee_collected = stats
+            .fee_collected
+            .checked_add(cancellation_fee)
+            .ok_or(BridgeError::Overflow)?;
+
+        emit!(WithdrawalCancelledEvent {
+            user: withdrawal_request.user,
+            nonce: withdrawal_request.nonce,
+            refund_amount,
+            cancellation_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Update the fee charged on cancelled withdrawals, in basis points (fee manager or admin)
+    pub fn update_cancellation_fee(ctx: Context<UpdateFee>, new_fee_bps: u16) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify the fee manager or authority
+        require!(
+            bridge_state.has_role(Role::FeeManager, &ctx.accounts.authority.key()),
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::Fee {
+            kind: FeeKind::Cancellation,
+            fee_bps: new_fee_bps,
+        })
+    }
+
+    /// Update the mint fee in basis points (fee manager or admin)
+    pub fn update_mint_fee(ctx: Context<UpdateFee>, new_fee_bps: u16) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify the fee manager or authority
+        require!(
+            bridge_state.has_role(Role::FeeManager, &ctx.accounts.authority.key()),
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::Fee {
+            kind: FeeKind::Mint,
+            fee_bps: new_fee_bps,
+        })
+    }
+
+    /// Update the burn fee in basis points (fee manager or admin)
+    pub fn update_burn_fee(ctx: Context<UpdateFee>, new_fee_bps: u16) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify the fee manager or authority
+        require!(
+            bridge_state.has_role(Role::FeeManager, &ctx.accounts.authority.key()),
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::Fee {
+            kind: FeeKind::Burn,
+            fee_bps: new_fee_bps,
+        })
+    }
+
+    /// Update the per-transaction mint and burn caps (admin only)
+    pub fn update_transaction_limits(
+        ctx: Context<UpdateLimits>,
+        max_mint_amount: u64,
+        max_burn_amount: u64,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::TransactionLimits {
+            max_mint_amount,
+            max_burn_amount,
+        })
+    }
+
+    /// Set the smallest burn accepted, in zatoshis; 0 disables the check (admin only)
+    ///
+    /// Burns below the dust threshold would cost more in Zcash fees than they pay out.
+    pub fn update_min_burn_amount(ctx: Context<UpdateLimits>, min_burn_amount: u64) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::MinBurnAmount { min_burn_amount })
+    }
+
+    /// Require a compliance credential for mints and burns above `kyc_threshold` (admin only)
+    ///
+    /// Credentials are accounts owned by the `compliance_attester` program; see
+    /// `BridgeState::check_compliance`. A threshold of u64::MAX disables the check.
+    pub fn update_compliance(
+        ctx: Context<UpdateLimits>,
+        kyc_threshold: u64,
+        compliance_attester: Pubkey,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::Compliance {
+            kyc_threshold,
+            compliance_attester,
+        })
+    }
+
+    /// Configure the delay applied to withdrawals above a size threshold (admin only)
+    pub fn update_withdrawal_timelock(
+        ctx: Context<UpdateLimits>,
+        large_withdrawal_threshold: u64,
+        large_withdrawal_delay_slots: u64,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::WithdrawalTimelock {
+            large_withdrawal_threshold,
+            large_withdrawal_delay_slots,
+        })
+    }
+
+    /// Set the KYC and withdrawal timelock thresholds in US cents (admin only)
+    ///
+    /// USD thresholds are converted to zatoshis with the Pyth `price_feed_id` feed
+    /// at instruction time and tighten the ZEC ones rather than replace them, so
+    /// policy holds as the ZEC price moves. While one is set, instructions it
+    /// applies to must pass a fully verified price update no older than
+    /// `max_price_age_secs` among their remaining accounts. u64::MAX leaves a
+    /// threshold in ZEC only.
+    pub fn update_usd_limits(
+        ctx: Context<UpdateLimits>,
+        price_feed_id: [u8; 32],
+        max_price_age_secs: u32,
+        kyc_threshold_usd: u64,
+        large_withdrawal_threshold_usd: u64,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::UsdLimits {
+            price_feed_id,
+            max_price_age_secs,
+            kyc_threshold_usd,
+            large_withdrawal_threshold_usd,
+        })
+    }
+
+    /// Configure the mint volume that trips the circuit breaker (admin only)
+    pub fn update_circuit_breaker(
+        ctx: Context<UpdateLimits>,
+        threshold: u64,
+        window_hours: u8,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::CircuitBreaker {
+            threshold,
+            window_hours,
+        })
+    }
+
+    /// Clear a tripped circuit breaker and resume mints (authority or guardian quorum)
+    ///
+    /// When the caller is not the authority, guardians approving the reset are
+    /// passed as signer remaining accounts.
+    pub fn reset_circuit_breaker(ctx: Context<ResetCircuitBreaker>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        if ctx.accounts.caller.key() != bridge_state.authority {
+            let signatures = ctx
+                .accounts
+                .guardian_set
+                .count_signers(ctx.remaining_accounts, &[]);
+            require!(
+                signatures >= ctx.accounts.guardian_set.threshold as usize,
+                BridgeError::Unauthorized
+            );
+        }
+
+        require!(
+            bridge_state.circuit_breaker_tripped,
+            BridgeError::CircuitBreakerNotTripped
+        );
+
+        bridge_state.circuit_breaker_tripped = false;
+        bridge_state.mints_paused = false;
+        ctx.accounts.bridge_stats.load_mut()?.breaker_volume = VolumeWindow::default();
+
+        emit!(CircuitBreakerResetEvent {
+            reset_by: ctx.accounts.caller.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Update the cap on cumulative mints over the last 24 hours (admin only)
+    pub fn update_daily_mint_limit(ctx: Context<UpdateLimits>, daily_mint_limit: u64) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::DailyMintLimit { daily_mint_limit })
+    }
+
+    /// Set the delay queued parameter changes wait before they can execute (admin only)
+    ///
+    /// While it is non-zero, fee, limit and guardian set updates must go through
+    /// `queue_change`, including changes to the delay itself.
+    pub fn update_timelock_slots(ctx: Context<UpdateLimits>, timelock_slots: u64) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.require_no_timelock()?;
+        bridge_state.apply_change(&ParameterChange::TimelockSlots { timelock_slots })
+    }
+
+    /// Queue a parameter change that becomes executable after `timelock_slots`
+    ///
+    /// The proposer needs the same role as the change's direct update instruction
+    /// and gets the `PendingChange` rent back when it is executed or cancelled.
+    pub fn queue_change(ctx: Context<QueueChange>, change: ParameterChange) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify the proposer may make this change directly
+        require!(
+            change.authorized(bridge_state, &ctx.accounts.proposer.key()),
+            BridgeError::Unauthorized
+        );
+
+        // Reject malformed guardian sets now rather than after the delay
+        if let ParameterChange::GuardianSet { guardians, threshold } = &change {
+            GuardianSet::validate(guardians, *threshold)?;
+        }
+
+        let queued_slot = Clock::get()?.slot;
+        let executable_slot = queued_slot
+            .checked_add(bridge_state.timelock_slots)
+            .ok_or(BridgeError::Overflow)?;
+
+        let pending_change = &mut ctx.accounts.pending_change;
+        pending_change.id = bridge_state.next_change_id;
+        pending_change.proposer = ctx.accounts.proposer.key();
+        pending_change.change = change.clone();
+        pending_change.queued_slot = queued_slot;
+        pending_change.executable_slot = executable_slot;
+        pending_change.bump = ctx.bumps.pending_change;
+
+        bridge_state.next_change_id = bridge_state
+            .next_change_id
+            .checked_add(1)
+            .ok_or(BridgeError::Overflow)?;
+
+        emit!(ChangeQueuedEvent {
+            id: pending_change.id,
+            proposer: pending_change.proposer,
+            change,
+            executable_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Apply a queued parameter change once its timelock has elapsed (anyone)
+    ///
+    /// Guardian set changes need the guardian set account.
+    pub fn execute_change(ctx: Context<ExecuteChange>, _id: u64) -> Result<()> {
+        let pending_change = &ctx.accounts.pending_change;
+
+        require!(
+            Clock::get()?.slot >= pending_change.executable_slot,
+            BridgeError::ChangeNotReady
+        );
+
+        match pending_change.change.clone() {
+            ParameterChange::GuardianSet { guardians, threshold } => {
+                ctx.accounts
+                    .guardian_set
+                    .as_mut()
+                    .ok_or(BridgeError::GuardianSetRequired)?
+                    .rotate(guardians, threshold)?;
+            }
+            change => ctx.accounts.bridge_state.apply_change(&change)?,
+        }
+
+        emit!(ChangeExecutedEvent {
+            id: ctx.accounts.pending_change.id,
+        });
+
+        Ok(())
+    }
+
+    /// Drop a queued parameter change before it executes (admin or its proposer)
+    pub fn cancel_pending_change(ctx: Context<CancelPendingChange>, _id: u64) -> Result<()> {
+        let caller = ctx.accounts.caller.key();
+
+        // Verify authority or proposer
+        require!(
+            caller == ctx.accounts.bridge_state.authority
+                || caller == ctx.accounts.pending_change.proposer,
+            BridgeError::Unauthorized
+        );
+
+        emit!(ChangeCancelledEvent {
+            id: ctx.accounts.pending_change.id,
+            cancelled_by: caller,
+        });
+
+        Ok(())
+    }
+
+    /// Create or update rolling 24-hour caps for a single user (admin only)
+    pub fn set_user_limits(
+        ctx: Context<SetUserLimits>,
+        user: Pubkey,
+        mint_limit: u64,
+        burn_limit: u64,
+    ) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        require!(mint_limit > 0 && burn_limit > 0, BridgeError::InvalidAmount);
+
+        let user_limits = &mut ctx.accounts.user_limits;
+        user_limits.user = user;
+        user_limits.mint_limit = mint_limit;
+        user_limits.burn_limit = burn_limit;
+        user_limits.bump = ctx.bumps.user_limits;
+
+        emit!(UserLimitsUpdatedEvent {
+            user,
+            mint_limit,
+            burn_limit,
+        });
+
+        Ok(())
+    }
+
+    /// Remove a user's caps, returning rent to the authority (admin only)
+    pub fn remove_user_limits(ctx: Context<RemoveUserLimits>, user: Pubkey) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        emit!(UserLimitsRemovedEvent { user });
+
+        Ok(())
+    }
+
+    /// Create or update a user's reduced mint and burn fees (fee manager or admin)
+    pub fn set_fee_exemption(
+        ctx: Context<SetFeeExemption>,
+        user: Pubkey,
+        mint_fee_bps: u16,
+        burn_fee_bps: u16,
+    ) -> Result<()> {
+        // Verify the fee manager or authority
+        require!(
+            ctx.accounts.bridge_state.has_role(Role::FeeManager, &ctx.accounts.authority.key()),
+            BridgeError::Unauthorized
+        );
+
+        require!(
+            mint_fee_bps <= MAX_FEE_BPS && burn_fee_bps <= MAX_FEE_BPS,
+            BridgeError::FeeTooHigh
+        );
+
+        let fee_exemption = &mut ctx.accounts.fee_exemption;
+        fee_exemption.user = user;
+        fee_exemption.mint_fee_bps = mint_fee_bps;
+        fee_exemption.burn_fee_bps = burn_fee_bps;
+        fee_exemption.bump = ctx.bumps.fee_exemption;
+
+        emit!(FeeExemptionUpdatedEvent {
+            user,
+            mint_fee_bps,
+            burn_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Remove a user's fee exemption, returning rent to the authority (fee manager or admin)
+    pub fn remove_fee_exemption(ctx: Context<RemoveFeeExemption>, user: Pubkey) -> Result<()> {
+        // Verify the fee manager or authority
+        require!(
+            ctx.accounts.bridge_state.has_role(Role::FeeManager, &ctx.accounts.authority.key()),
+            BridgeError::Unauthorized
+        );
+
+        emit!(FeeExemptionRemovedEvent { user });
+
+        Ok(())
+    }
+
+    /// Refuse mints to and burns by a sanctioned pubkey (admin only)
+    pub fn add_to_deny_list(ctx: Context<AddToDenyList>, user: Pubkey) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        let slot = Clock::get()?.slot;
+        let deny_list_entry = &mut ctx.accounts.deny_list_entry;
+        deny_list_entry.user = user;
+        deny_list_entry.added_slot = slot;
+        deny_list_entry.bump = ctx.bumps.deny_list_entry;
+
+        emit!(DenyListEntryAddedEvent {
+            user,
+            authority: ctx.accounts.authority.key(),
+            slot,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a pubkey's deny list entry, returning rent to the authority (admin only)
+    pub fn remove_from_deny_list(ctx: Context<RemoveFromDenyList>, user: Pubkey) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        emit!(DenyListEntryRemovedEvent {
+            user,
+            authority: ctx.accounts.authority.key(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Set the token account that receives withdrawn fees (admin only)
+    pub fn update_treasury(ctx: Context<UpdateTreasury>, treasury: Pubkey) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        let old_treasury = bridge_state.treasury;
+        bridge_state.treasury = treasury;
+
+        emit!(TreasuryUpdatedEvent {
+            old_treasury,
+            new_treasury: treasury,
+        });
+
+        Ok(())
+    }
+
+    /// Set the transparent Zcash address SPV deposits must pay (admin only)
+    pub fn update_deposit_address(
+        ctx: Context<UpdateTreasury>,
+        pubkey_hash: [u8; 20],
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        let old_pubkey_hash = bridge_state.zcash_deposit_pubkey_hash;
+        bridge_state.zcash_deposit_pubkey_hash = pubkey_hash;
+
+        emit!(DepositAddressUpdatedEvent {
+            old_pubkey_hash,
+            new_pubkey_hash: pubkey_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Sweep accumulated wZEC fees from the fee vault to the treasury (admin only)
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        let bridge_state = &ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        require!(
+            bridge_state.treasury != Pubkey::default(),
+            BridgeError::TreasuryNotConfigured
+        );
+        require!(
+            amount > 0 && amount <= ctx.accounts.fee_vault.amount,
+            BridgeError::InvalidAmount
+        );
+
+        // Leave enough in the vault to pay what relayers have earned
+        require!(
+            ctx.accounts.fee_vault.amount - amount >= bridge_state.unclaimed_relayer_rewards,
+            BridgeError::InvalidAmount
+        );
+
+        // Transfer from the vault, signed by the bridge state PDA
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [ctx.accounts.bridge_state.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"bridge_state", mint_key.as_ref(), &bump]];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.fee_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.bridge_state.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        emit!(FeesWithdrawnEvent {
+            treasury: ctx.accounts.treasury_token_account.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Create the insurance vault, owned by the bridge's insurance PDA (admin only)
+    pub fn initialize_insurance_fund(ctx: Context<InitializeInsuranceFund>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.insurance_vault = ctx.accounts.insurance_vault.key();
+
+        emit!(InsuranceFundInitializedEvent {
+            insurance_vault: bridge_state.insurance_vault,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of every burn fee paid into the insurance fund, in basis
+    /// points of the fee (fee manager or admin)
+    pub fn update_insurance_share(ctx: Context<UpdateFee>, share_bps: u16) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify the fee manager or authority
+        require!(
+            bridge_state.has_role(Role::FeeManager, &ctx.accounts.authority.key()),
+            BridgeError::Unauthorized
+        );
+
+        require!(share_bps <= 10000, BridgeError::InvalidInsuranceShare);
+        require!(
+            share_bps == 0 || bridge_state.insurance_vault != Pubkey::default(),
+            BridgeError::InsuranceFundNotInitialized
+        );
+
+        let old_share_bps = bridge_state.insurance_share_bps;
+        bridge_state.insurance_share_bps = share_bps;
+
+        emit!(InsuranceShareUpdatedEvent {
+            old_share_bps,
+            new_share_bps: share_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Pay from the insurance fund to cover a relayer failure or reserve
+    /// shortfall (admin only, normally executed by a governance proposal)
+    pub fn pay_insurance_claim(
+        ctx: Context<PayInsuranceClaim>,
+        amount: u64,
+        reason: InsuranceClaimReason,
+    ) -> Result<()> {
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        require!(
+            amount > 0 && amount <= ctx.accounts.insurance_vault.amount,
+            BridgeError::InvalidAmount
+        );
+
+        // Transfer from the vault, signed by the insurance PDA
+        let bridge_state_key = ctx.accounts.bridge_state.key();
+        let bump = [ctx.bumps.insurance_authority];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"insurance", bridge_state_key.as_ref(), &bump]];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.insurance_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.insurance_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        emit!(InsuranceClaimPaidEvent {
+            recipient: ctx.accounts.recipient_token_account.key(),
+            amount,
+            reason,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long unstaked insurance shares keep backing the fund before they
+    /// can be claimed (admin only)
+    pub fn update_insurance_unstake_slots(
+        ctx: Context<UpdateLimits>,
+        insurance_unstake_slots: u64,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.insurance_unstake_slots = insurance_unstake_slots;
+
+        emit!(InsuranceUnstakeSlotsUpdatedEvent {
+            insurance_unstake_slots,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long deposit receipts and settled withdrawal requests are kept
+    /// before their rent can be reclaimed; 0 disables reclaiming
+    pub fn update_retention_slots(ctx: Context<UpdateLimits>, retention_slots: u64) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.retention_slots = retention_slots;
+
+        emit!(RetentionSlotsUpdatedEvent { retention_slots });
+
+        Ok(())
+    }
+
+    /// Bound how far payouts may run ahead of the oldest unsettled withdrawal (admin only)
+    ///
+    /// Withdrawal nonces are assigned in burn order, and with a window set a request
+    /// can only be finalized or cancelled while fewer than `finalize_window` nonces
+    /// from the oldest unsettled one, so a relayer can't skip a withdrawal and keep
+    /// paying later ones. Requests held back by the large-withdrawal timelock or a
+    /// veto hold the window too. `settled_floor` is the nonce ordering starts from:
+    /// everything below it is treated as settled. A window of 0 lifts the ordering.
+    pub fn update_finalize_window(
+        ctx: Context<UpdateLimits>,
+        finalize_window: u8,
+        settled_floor: u64,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+        require!(
+            finalize_window as u32 <= u64::BITS,
+            BridgeError::InvalidFinalizeWindow
+        );
+        require!(
+            settled_floor <= bridge_state.withdrawal_nonce,
+            BridgeError::InvalidFinalizeWindow
+        );
+
+        bridge_state.finalize_window = finalize_window;
+        bridge_state.settled_floor = settled_floor;
+        bridge_state.settled_bitmap = 0;
+
+        emit!(FinalizeWindowUpdatedEvent {
+            finalize_window,
+            settled_floor,
+        });
+
+        Ok(())
+    }
+
+    /// Stake wZEC into the insurance fund in exchange for shares of the vault
+    ///
+    /// Shares grow with the fund's cut of burn fees and shrink when claims are paid
+    /// out, so stakers earn fees in return for taking losses first.
+    pub fn stake_insurance(ctx: Context<StakeInsurance>, amount: u64) -> Result<()> {
+        require!(amount > 0, BridgeError::InvalidAmount);
+
+        let bridge_state = &mut ctx.accounts.bridge_state;
+        let balance = ctx.accounts.insurance_vault.amount;
+
+        // Balance paid in before anyone staked is backed by shares nobody owns, so it
+        // keeps covering shortfalls instead of going to the first staker
+        if bridge_state.insurance_total_shares == 0 {
+            bridge_state.insurance_total_shares = balance;
+        }
+        let shares = if bridge_state.insurance_total_shares == 0 {
+            amount
+        } else {
+            require!(balance > 0, BridgeError::InsuranceFundDepleted);
+            u64::try_from(
+                amount as u128 * bridge_state.insurance_total_shares as u128 / balance as u128,
+            )
+            .map_err(|_| BridgeError::Overflow)?
+        };
+        require!(shares > 0, BridgeError::InvalidAmount);
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.staker_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.insurance_vault.to_account_info(),
+            authority: ctx.accounts.staker.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        bridge_state.insurance_total_shares = bridge_state
+            .insurance_total_shares
+            .checked_add(shares)
+            .ok_or(BridgeError::Overflow)?;
+
+        let insurance_stake = &mut ctx.accounts.insurance_stake;
+        insurance_stake.staker = ctx.accounts.staker.key();
+        insurance_stake.bump = ctx.bumps.insurance_stake;
+        insurance_stake.shares = insurance_stake
+            .shares
+            .checked_add(shares)
+            .ok_or(BridgeError::Overflow)?;
+
+        emit!(InsuranceStakedEvent {
+            staker: insurance_stake.staker,
+            amount,
+            shares,
+        });
+
+        Ok(())
+    }
+
+    /// Start the cooldown on insurance shares; they keep sharing fees and losses
+    /// until claimed
+    pub fn unstake_insurance(ctx: Context<UnstakeInsurance>, shares: u64) -> Result<()> {
+        require!(shares > 0, BridgeError::InvalidAmount);
+
+        let insurance_stake = &mut ctx.accounts.insurance_stake;
+        require!(
+            shares <= insurance_stake.shares,
+            BridgeError::InsufficientInsuranceShares
+        );
+
+        insurance_stake.shares -= shares;
+        insurance_stake.unstaking_shares = insurance_stake
+            .unstaking_shares
+            .checked_add(shares)
+            .ok_or(BridgeError::Overflow)?;
+        insurance_stake.unlock_slot = Clock::get()?
+            .slot
+            .checked_add(ctx.accounts.bridge_state.insurance_unstake_slots)
+            .ok_or(BridgeError::Overflow)?;
+
+        emit!(InsuranceUnstakedEvent {
+            staker: insurance_stake.staker,
+            shares,
+            unlock_slot: insurance_stake.unlock_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Pay out unstaked insurance shares at the vault's current value once their
+    /// cooldown has passed
+    pub fn claim_insurance_stake(ctx: Context<ClaimInsuranceStake>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+        let insurance_stake = &mut ctx.accounts.insurance_stake;
+        let shares = insurance_stake.unstaking_shares;
+        require!(shares > 0, BridgeError::InvalidAmount);
+        require!(
+            Clock::get()?.slot >= insurance_stake.unlock_slot,
+            BridgeError::InsuranceStakeLocked
+        );
+
+        let amount = u64::try_from(
+            shares as u128 * ctx.accounts.insurance_vault.amount as u128
+                / bridge_state.insurance_total_shares as u128,
+        )
+        .map_err(|_| BridgeError::Overflow)?;
+
+        insurance_stake.unstaking_shares = 0;
+        bridge_state.insurance_total_shares -= shares;
+
+        if amount > 0 {
+            // Transfer from the vault, signed by the insurance PDA
+            let bridge_state_key = bridge_state.key();
+            let bump = [ctx.bumps.insurance_authority];
+            let signer_seeds: &[&[&[u8]]] = &[&[b"insurance", bridge_state_key.as_ref(), &bump]];
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.insurance_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.staker_token_account.to_account_info(),
+                authority: ctx.accounts.insurance_authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        }
+
+        emit!(InsuranceStakeClaimedEvent {
+            staker: insurance_stake.staker,
+            shares,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Create the guardian set that attests to Zcash deposits (guardian manager or admin)
+    pub fn initialize_guardian_set(
+        ctx: Context<InitializeGuardianSet>,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        // Verify the guardian manager or authority
+        require!(
+            ctx.accounts.bridge_state.has_role(Role::GuardianManager, &ctx.accounts.authority.key()),
+            BridgeError::Unauthorized
+        );
+
+        GuardianSet::validate(&guardians, threshold)?;
+
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        guardian_set.index = 0;
+        guardian_set.guardians = guardians;
+        guardian_set.threshold = threshold;
+        guardian_set.bump = ctx.bumps.guardian_set;
+
+        emit!(GuardianSetUpdatedEvent {
+            index: guardian_set.index,
+            guardians: guardian_set.guardians.clone(),
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the guardian set members and threshold (guardian manager, admin, or
+    /// a quorum of the outgoing set)
+    ///
+    /// Without the guardian manager role, a threshold of the current guardians must
+    /// sign the rotation through preceding ed25519 instructions or as signer
+    /// remaining accounts. The outgoing set is archived under its index and keeps
+    /// attesting for `GuardianSet::GRACE_SLOTS`, so attestations signed before the
+    /// rotation can still land.
+    pub fn rotate_guardian_set(
+        ctx: Context<RotateGuardianSet>,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        if ctx.accounts.bridge_state.has_role(Role::GuardianManager, &ctx.accounts.caller.key()) {
+            ctx.accounts.bridge_state.require_no_timelock()?;
+        } else {
+            // Only the current set may hand over; archived sets can't rotate
+            let message = GuardianSet::rotation_message(
+                ctx.accounts.bridge_state.network,
+                ctx.accounts.guardian_set.index,
+                &guardians,
+                threshold,
+            );
+            let attested = ed25519::verified_signers(&ctx.accounts.instructions, &message)?;
+            let signatures = ctx
+                .accounts
+                .guardian_set
+                .count_signers(ctx.remaining_accounts, &attested);
+            require!(
+                signatures >= ctx.accounts.guardian_set.threshold as usize,
+                BridgeError::InsufficientGuardianSignatures
+            );
+        }
+
+        let expires_slot = Clock::get()?
+            .slot
+            .checked_add(GuardianSet::GRACE_SLOTS)
+            .ok_or(BridgeError::Overflow)?;
+
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        let archived = &mut ctx.accounts.archived_guardian_set;
+        archived.index = guardian_set.index;
+        archived.guardians = guardian_set.guardians.clone();
+        archived.threshold = guardian_set.threshold;
+        archived.expires_slot = expires_slot;
+        archived.bump = ctx.bumps.archived_guardian_set;
+
+        emit!(GuardianSetArchivedEvent {
+            index: archived.index,
+            expires_slot,
+        });
+
+        guardian_set.rotate(guardians, threshold)
+    }
+
+    /// Propose a new bridge authority (admin only)
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify current authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.pending_authority = new_authority;
+
+        emit!(AuthorityProposedEvent {
+            current_authority: bridge_state.authority,
+            proposed_authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Accept a pending authority proposal (proposed authority only)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify there is a proposal and that it names the signer
+        require!(
+            bridge_state.pending_authority != Pubkey::default(),
+            BridgeError::NoPendingAuthority
+        );
+        require!(
+            ctx.accounts.new_authority.key() == bridge_state.pending_authority,
+            BridgeError::Unauthorized
+        );
+
+        let old_authority = bridge_state.authority;
+        bridge_state.authority = bridge_state.pending_authority;
+        bridge_state.pending_authority = Pubkey::default();
+
+        emit!(AuthorityChangedEvent {
+            old_authority,
+            new_authority: bridge_state.authority,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a pending authority proposal (admin only)
+    pub fn cancel_authority_proposal(ctx: Context<CancelAuthorityProposal>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify current authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+        require!(
+            bridge_state.pending_authority != Pubkey::default(),
+            BridgeError::NoPendingAuthority
+        );
+
+        let cancelled_authority = bridge_state.pending_authority;
+        bridge_state.pending_authority = Pubkey::default();
+
+        emit!(AuthorityProposalCancelledEvent {
+            authority: bridge_state.authority,
+            cancelled_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Delegate a role to `holder`, or revoke it with Pubkey::default() (admin only)
+    ///
+    /// The authority keeps every role's powers; see `BridgeState::has_role`.
+    pub fn set_role(ctx: Context<SetRole>, role: Role, holder: Pubkey) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        let slot = bridge_state.roles.holder_mut(role);
+        let old_holder = *slot;
+        *slot = holder;
+
+        emit!(RoleUpdatedEvent {
+            role,
+            old_holder,
+            new_holder: holder,
+        });
+
+        Ok(())
+    }
+
+    /// Pause bridge operations (pauser or admin)
+    pub fn pause_bridge(ctx: Context<PauseBridge>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify the pauser or authority
+        require!(
+            bridge_state.has_role(Role::Pauser, &ctx.accounts.authority.key()),
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.mints_paused = true;
+        bridge_state.burns_paused = true;
+
+        emit!(PauseEvent {
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Resume bridge operations (admin only)
+    ///
+    /// Resuming mints also clears a tripped circuit breaker and restarts its window.
+    pub fn resume_bridge(ctx: Context<ResumeBridge>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.mints_paused = false;
+        bridge_state.burns_paused = false;
+        if bridge_state.circuit_breaker_tripped {
+            bridge_state.circuit_breaker_tripped = false;
+            ctx.accounts.bridge_stats.load_mut()?.breaker_volume = VolumeWindow::default();
+        }
+
+        emit!(ResumeEvent {
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Record that the operator is alive (admin only)
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.last_heartbeat_slot = Clock::get()?.slot;
+
+        emit!(HeartbeatEvent {
+            slot: bridge_state.last_heartbeat_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Configure how many slots may pass without a heartbeat; 0 disables the switch (admin only)
+    pub fn update_heartbeat_gap(ctx: Context<Heartbeat>, max_heartbeat_gap: u64) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.max_heartbeat_gap = max_heartbeat_gap;
+        bridge_state.last_heartbeat_slot = Clock::get()?.slot;
+
+        emit!(HeartbeatGapUpdatedEvent { max_heartbeat_gap });
+
+        Ok(())
+    }
+
+    /// Pause the bridge once the authority has missed its heartbeat (anyone)
+    pub fn trigger_dead_mans_switch(ctx: Context<TriggerDeadMansSwitch>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+        let slot = Clock::get()?.slot;
+
+        require!(
+            bridge_state.max_heartbeat_gap > 0
+                && slot.saturating_sub(bridge_state.last_heartbeat_slot)
+                    > bridge_state.max_heartbeat_gap,
+            BridgeError::HeartbeatNotExpired
+        );
+
+        bridge_state.mints_paused = true;
+        bridge_state.burns_paused = true;
+
+        emit!(DeadMansSwitchTriggeredEvent {
+            caller: ctx.accounts.caller.key(),
+            last_heartbeat_slot: bridge_state.last_heartbeat_slot,
+            slot,
+        });
+
+        Ok(())
+    }
+
+    /// Pause or resume mints and burns independently (admin only)
+    ///
+    /// Resuming mints also clears a tripped circuit breaker and restarts its window.
+    pub fn set_pause_flags(
+        ctx: Context<SetPauseFlags>,
+        mints_paused: bool,
+        burns_paused: bool,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+
+        bridge_state.mints_paused = mints_paused;
+        bridge_state.burns_paused = burns_paused;
+        if !mints_paused && bridge_state.circuit_breaker_tripped {
+            bridge_state.circuit_breaker_tripped = false;
+            ctx.accounts.bridge_stats.load_mut()?.breaker_volume = VolumeWindow::default();
+        }
+
+        emit!(PauseFlagsUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            mints_paused,
+            burns_paused,
+        });
+
+        Ok(())
+    }
+
+    /// Halt mints immediately (any single guardian)
+    ///
+    /// The pause is held apart from `mints_paused`, so neither the authority's
+    /// pause flags nor a circuit breaker reset lift it; only `guardian_resume` does.
+    pub fn guardian_pause(ctx: Context<GuardianPause>) -> Result<()> {
+        require!(
+            ctx.accounts
+                .guardian_set
+                .guardians
+                .contains(&ctx.accounts.guardian.key()),
+            BridgeError::NotAGuardian
+        );
+
+        let bridge_state = &mut ctx.accounts.bridge_state;
+        require!(!bridge_state.guardian_paused(), BridgeError::GuardianPauseActive);
+        bridge_state.guardian_paused_by = ctx.accounts.guardian.key();
+
+        emit!(GuardianPauseEvent {
+            guardian: bridge_state.guardian_paused_by,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a guardian's emergency pause (authority or guardian quorum)
+    ///
+    /// When the caller is not the authority, guardians approving the resume are
+    /// passed as signer remaining accounts.
+    pub fn guardian_resume(ctx: Context<GuardianResume>) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        if ctx.accounts.caller.key() != bridge_state.authority {
+            let signatures = ctx
+                .accounts
+                .guardian_set
+                .count_signers(ctx.remaining_accounts, &[]);
+            require!(
+                signatures >= ctx.accounts.guardian_set.threshold as usize,
+                BridgeError::Unauthorized
+            );
+        }
+
+        require!(bridge_state.guardian_paused(), BridgeError::GuardianPauseNotActive);
+
+        let paused_by = bridge_state.guardian_paused_by;
+        bridge_state.guardian_paused_by = Pubkey::default();
+
+        emit!(GuardianResumeEvent {
+            paused_by,
+            resumed_by: ctx.accounts.caller.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Create the `BridgeStats` account for a bridge whose state predates it (admin only)
+    ///
+    /// Totals and volume windows the stored layout kept inline are copied over. Run
+    /// it before `migrate_state`, which drops them from the state account.
+    pub fn init_bridge_stats(ctx: Context<InitBridgeStats>) -> Result<()> {
+        let info = ctx.accounts.bridge_state.to_account_info();
+
+        // Verify authority against the raw layout, which may predate this program
+        let legacy_stats = {
+            let data = info.try_borrow_data()?;
+            let layout = legacy_state::StoredLayout::detect(&data)?;
+            require!(
+                ctx.accounts.authority.key() == layout.authority(&data)?,
+                BridgeError::Unauthorized
+            );
+            layout.decode(&data)?.1
+        };
+
+        let mut stats = ctx.accounts.bridge_stats.load_init()?;
+        if let Some(legacy_stats) = legacy_stats {
+            stats.total_minted = legacy_stats.total_minted;
+            stats.total_burned = legacy_stats.total_burned;
+            stats.fee_collected = legacy_stats.fee_collected;
+            stats.mint_volume = legacy_stats.mint_volume;
+            stats.breaker_volume = legacy_stats.breaker_volume;
+        }
+
+        emit!(BridgeStatsInitializedEvent {
+            bridge_state: info.key(),
+            total_minted: stats.total_minted,
+            total_burned: stats.total_burned,
+            fee_collected: stats.fee_collected,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade the bridge state account to the current layout (admin only)
+    ///
+    /// Resizes the account to `BridgeState::LEN`, with the authority topping up rent,
+    /// rewrites layouts that can't be read as a prefix of the current one, and fills
+    /// fields added since the stored version with their defaults. The bridge's
+    /// `BridgeStats` must exist; see `init_bridge_stats`.
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let info = ctx.accounts.bridge_state.to_account_info();
+
+        // Verify authority against the raw layout, which may predate this program
+        let (mut bridge_state, from_version) = {
+            let data = info.try_borrow_data()?;
+            let layout = legacy_state::StoredLayout::detect(&data)?;
+            require!(
+                ctx.accounts.authority.key() == layout.authority(&data)?,
+                BridgeError::Unauthorized
+            );
+            require!(
+                layout.version() < BridgeState::VERSION,
+                BridgeError::AlreadyMigrated
+            );
+            (layout.decode(&data)?.0, layout.version())
+        };
+
+        // Resize the account, topping up rent from the authority
+        let new_len = 8 + BridgeState::LEN;
+        if info.data_len() < new_len {
+            let shortfall = Rent::get()?
+                .minimum_balance(new_len)
+                .saturating_sub(info.lamports());
+            if shortfall > 0 {
+                let cpi_accounts = system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                };
+                let cpi_program = ctx.accounts.system_program.to_account_info();
+                system_program::transfer(CpiContext::new(cpi_program, cpi_accounts), shortfall)?;
+            }
+        }
+        if info.data_len() != new_len {
+            info.realloc(new_len, true)?;
+        }
+
+        bridge_state.migrate(from_version);
+        bridge_state.bump = ctx.bumps.bridge_state;
+        bridge_state.mint_authority_bump = Pubkey::find_program_address(
+            &[b"mint_authority", ctx.accounts.mint.key().as_ref()],
+            &crate::ID,
+        )
+        .1;
+        bridge_state.stats_bump = ctx.bumps.bridge_stats;
+        bridge_state.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit!(StateMigratedEvent {
+            from_version,
+            to_version: BridgeState::VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Move a bridge deployed before instances were keyed by mint onto the per-mint PDAs (admin only)
+    ///
+    /// Copies the singleton state and stats into the instance accounts for `mint`,
+    /// hands the mint authority to the per-mint PDA, sweeps the singleton fee vault
+    /// and closes the singleton accounts. Deposit receipts stay where they are and
+    /// keep blocking re-mints through `DepositReceipt::legacy_address`. Withdrawals
+    /// pending under the singleton seeds can't be finalized afterwards, so drain them
+    /// first; the guardian set and any user limits are created again for the instance.
+    pub fn migrate_legacy_bridge(ctx: Context<MigrateLegacyBridge>) -> Result<()> {
+        let legacy_info = ctx.accounts.legacy_bridge_state.to_account_info();
+        let mint_key = ctx.accounts.mint.key();
+
+        // Verify authority against the raw layout, which may predate this program
+        let (mut bridge_state, legacy_stats, from_version) = {
+            let data = legacy_info.try_borrow_data()?;
+            let layout = legacy_state::StoredLayout::detect(&data)?;
+            require!(
+                ctx.accounts.authority.key() == layout.authority(&data)?,
+                BridgeError::Unauthorized
+            );
+            let (bridge_state, legacy_stats) = layout.decode(&data)?;
+            (bridge_state, legacy_stats, layout.version())
+        };
+        require!(bridge_state.mint == mint_key, BridgeError::InvalidMint);
+        check_mint_extensions(&ctx.accounts.mint.to_account_info())?;
+
+        // Totals live inline in the oldest layouts and in the singleton stats account otherwise
+        let stats_info = ctx.accounts.legacy_bridge_stats.to_account_info();
+        let legacy_stats = match legacy_stats {
+            Some(legacy_stats) => legacy_stats,
+            None => {
+                require_keys_eq!(*stats_info.owner, crate::ID, ErrorCode::AccountNotInitialized);
+                legacy_state::LegacyStats::from_stats_account(&stats_info.try_borrow_data()?)?
+            }
+        };
+        {
+            let mut stats = ctx.accounts.bridge_stats.load_init()?;
+            stats.total_minted = legacy_stats.total_minted;
+            stats.total_burned = legacy_stats.total_burned;
+            stats.fee_collected = legacy_stats.fee_collected;
+            stats.mint_volume = legacy_stats.mint_volume;
+            stats.breaker_volume = legacy_stats.breaker_volume;
+        }
+
+        bridge_state.migrate(from_version);
+        bridge_state.bump = ctx.bumps.bridge_state;
+        bridge_state.mint_authority_bump = ctx.bumps.mint_authority;
+        bridge_state.stats_bump = ctx.bumps.bridge_stats;
+        bridge_state.singleton_receipts = true;
+        ctx.accounts.bridge_state.set_inner(bridge_state);
+
+        // Hand the mint to the per-mint authority PDA. The singleton PDA signs via its
+        // seeds; the original deployment left the admin wallet as mint authority.
+        let legacy_mint_authority = ctx.accounts.legacy_mint_authority.key();
+        let legacy_mint_authority_bump = [ctx.bumps.legacy_mint_authority];
+        let legacy_mint_authority_seeds: &[&[&[u8]]] =
+            &[&[b"mint_authority", &legacy_mint_authority_bump]];
+        let new_authority = ctx.accounts.mint_authority.key();
+        let mint_authorities = [
+            (AuthorityType::MintTokens, ctx.accounts.mint.mint_authority),
+            (AuthorityType::FreezeAccount, ctx.accounts.mint.freeze_authority),
+        ];
+        for (authority_type, current) in mint_authorities {
+            let current_authority = match current {
+                COption::None if authority_type == AuthorityType::FreezeAccount => continue,
+                COption::Some(key) if key == legacy_mint_authority => {
+                    ctx.accounts.legacy_mint_authority.to_account_info()
+                }
+                COption::Some(key) if key == ctx.accounts.authority.key() => {
+                    ctx.accounts.authority.to_account_info()
+                }
+                _ if authority_type == AuthorityType::FreezeAccount => {
+                    return err!(BridgeError::InvalidFreezeAuthority)
+                }
+                _ => return err!(BridgeError::InvalidMintAuthority),
+            };
+            let cpi_accounts = SetAuthority {
+                current_authority,
+                account_or_mint: ctx.accounts.mint.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(
+                cpi_program,
+                cpi_accounts,
+                legacy_mint_authority_seeds,
+            );
+            token_interface::set_authority(cpi_ctx, authority_type, Some(new_authority))?;
+        }
+
+        // Sweep the singleton fee vault into the instance's and close it
+        let mut swept = 0;
+        if let Some(legacy_fee_vault) = &ctx.accounts.legacy_fee_vault {
+            let legacy_bump = [ctx.bumps.legacy_bridge_state];
+            let legacy_seeds: &[&[&[u8]]] = &[&[b"bridge_state", &legacy_bump]];
+
+            swept = legacy_fee_vault.amount;
+            if swept > 0 {
+                let cpi_accounts = TransferChecked {
+                    from: legacy_fee_vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.fee_vault.to_account_info(),
+                    authority: legacy_info.clone(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, legacy_seeds);
+                token_interface::transfer_checked(cpi_ctx, swept, ctx.accounts.mint.decimals)?;
+            }
+
+            let cpi_accounts = CloseAccount {
+                account: legacy_fee_vault.to_account_info(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: legacy_info.clone(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, legacy_seeds);
+            token_interface::close_account(cpi_ctx)?;
+        }
+
+        // Close the singleton accounts so the move can't be replayed
+        let destination = ctx.accounts.authority.to_account_info();
+        close_legacy_account(&legacy_info, &destination)?;
+        if !stats_info.data_is_empty() {
+            close_legacy_account(&stats_info, &destination)?;
+        }
+
+        emit!(LegacyBridgeMigratedEvent {
+            legacy_bridge_state: legacy_info.key(),
+            bridge_state: ctx.accounts.bridge_state.key(),
+            from_version,
+            swept,
+        });
+
+        Ok(())
+    }
+
+    /// Rewrite a withdrawal request stored before burns carried a memo or recorded
+    /// their rent payer (anyone)
+    ///
+    /// Pre-memo requests end at their bump; most still decode because the address
+    /// reservation left zeroes behind it, but one whose address filled it does not.
+    /// The request is re-serialized, with an empty memo where it had none and the
+    /// user as its rent payer, and resized to fit, with the payer topping up rent.
+    pub fn migrate_withdrawal_request(
+        ctx: Context<MigrateWithdrawalRequest>,
+        _nonce: u64,
+    ) -> Result<()> {
+        let info = ctx.accounts.withdrawal_request.to_account_info();
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+
+        let withdrawal_request = {
+            let data = info.try_borrow_data()?;
+            require!(
+                WithdrawalRequest::try_deserialize(&mut &data[..]).is_err(),
+                BridgeError::WithdrawalRequestCurrent
+            );
+            legacy_state::decode_legacy_withdrawal(&data)?
+        };
+
+        // Resize the account, topping up rent from the payer
+        let new_len = 8 + WithdrawalRequest::space(&withdrawal_request.zec_address, Some(&withdrawal_request.memo));
+        let shortfall = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(info.lamports());
+        if shortfall > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: info.clone(),
+            };
+            let cpi_program = ctx.accounts.system_program.to_account_info();
+            system_program::transfer(CpiContext::new(cpi_program, cpi_accounts), shortfall)?;
+        }
+        if info.data_len() != new_len {
+            info.realloc(new_len, true)?;
+        }
+        withdrawal_request.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit!(WithdrawalRequestMigratedEvent {
+            withdrawal_request: info.key(),
+            nonce: withdrawal_request.nonce,
+        });
+
+        Ok(())
+    }
+
+    /// Reclaim the rent of a deposit receipt past the retention period (anyone)
+    ///
+    /// The receipt is what stops a deposit from being minted twice, so it isn't
+    /// deleted: it shrinks to its discriminator, which no mint path can decode, and
+    /// the freed rent goes back to whoever paid for it. Its contents are folded into
+    /// the closed-account log first.
+    pub fn close_receipt(ctx: Context<CloseReceipt>, _zcash_txid: [u8; 32]) -> Result<()> {
+        let info = ctx.accounts.deposit_receipt.to_account_info();
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+
+        let deposit_receipt = DepositReceipt::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require_keys_eq!(
+            ctx.accounts.payer.key(),
+            deposit_receipt.payer,
+            BridgeError::InvalidRentPayer
+        );
+        ctx.accounts.bridge_state.check_retention(deposit_receipt.slot)?;
+
+        let (commitment, log_head, closed_count) =
+            ctx.accounts.closed_account_log.record(&info, ctx.bumps.closed_account_log)?;
+
+        // Shrink to the discriminator and refund the rent it no longer needs
+        info.realloc(8, false)?;
+        let refunded = info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(8));
+        **info.try_borrow_mut_lamports()? -= refunded;
+        **ctx.accounts.payer.try_borrow_mut_lamports()? = ctx
+            .accounts
+            .payer
+            .lamports()
+            .checked_add(refunded)
+            .ok_or(BridgeError::Overflow)?;
+
+        emit!(AccountClosedEvent {
+            account: info.key(),
+            kind: ClosedAccountKind::DepositReceipt,
+            payer: ctx.accounts.payer.key(),
+            refunded,
+            commitment,
+            log_head,
+            closed_count,
+        });
+
+        Ok(())
+    }
+
+    /// Close a completed, cancelled or expired withdrawal request past the
+    /// retention period (anyone)
+    ///
+    /// Its rent goes back to whoever paid for it and its contents are folded into
+    /// the closed-account log. Nonces are never reused, so closing it can't let the
+    /// withdrawal be replayed.
+    pub fn close_withdrawal(ctx: Context<CloseWithdrawal>, _nonce: u64) -> Result<()> {
+        let withdrawal_request = &ctx.accounts.withdrawal_request;
+        require!(
+            matches!(
+                withdrawal_request.status,
+                WithdrawalStatus::Completed | WithdrawalStatus::Cancelled | WithdrawalStatus::Expired
+            ),
+            BridgeError::WithdrawalNotSettled
+        );
+        ctx.accounts
+            .bridge_state
+            .check_retention(withdrawal_request.created_slot)?;
+
+        let info = withdrawal_request.to_account_info();
+        let (commitment, log_head, closed_count) =
+            ctx.accounts.closed_account_log.record(&info, ctx.bumps.closed_account_log)?;
+
+        emit!(AccountClosedEvent {
+            account: info.key(),
+            kind: ClosedAccountKind::WithdrawalRequest,
+            payer: ctx.accounts.payer.key(),
+            refunded: info.lamports(),
+            commitment,
+            log_head,
+            closed_count,
+        });
+
+        Ok(())
+    }
+
+    /// Permanently stop minting and open the exit window (admin only)
+    ///
+    /// Holders may still burn for `grace_period_slots`, after which `close_bridge`
+    /// can wind the bridge down.
+    pub fn decommission_bridge(
+        ctx: Context<DecommissionBridge>,
+        grace_period_slots: u64,
+    ) -> Result<()> {
+        let bridge_state = &mut ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+        require!(
+            bridge_state.decommissioned_slot == 0,
+            BridgeError::BridgeDecommissioned
+        );
+
+        let slot = Clock::get()?.slot;
+        bridge_state.decommissioned_slot = slot;
+        bridge_state.decommission_grace_slots = grace_period_slots;
+        bridge_state.mints_paused = true;
+
+        emit!(BridgeDecommissionedEvent {
+            authority: ctx.accounts.authority.key(),
+            slot,
+            grace_period_slots,
+        });
+
+        Ok(())
+    }
+
+    /// Sweep the fee vault to the treasury and close the bridge accounts (admin only)
+    ///
+    /// Only allowed once the decommission grace period has passed, every withdrawal
+    /// has been finalized or cancelled, relayers have claimed their rewards, slashed
+    /// stake has been swept and the insurance fund has been paid out. Relayers can
+    /// still unbond and withdraw their stake afterwards.
+    pub fn close_bridge(ctx: Context<CloseBridge>) -> Result<()> {
+        let bridge_state = &ctx.accounts.bridge_state;
+
+        // Verify authority
+        require!(
+            ctx.accounts.authority.key() == bridge_state.authority,
+            BridgeError::Unauthorized
+        );
+        require!(
+            bridge_state.decommissioned_slot != 0,
+            BridgeError::BridgeNotDecommissioned
+        );
+        let exit_deadline = bridge_state
+            .decommissioned_slot
+            .checked_add(bridge_state.decommission_grace_slots)
+            .ok_or(BridgeError::Overflow)?;
+        require!(
+            Clock::get()?.slot > exit_deadline,
+            BridgeError::DecommissionGracePeriodActive
+        );
+        // Finalizing or cancelling a withdrawal needs the bridge state
+        require!(
+            bridge_state.open_withdrawals == 0,
+            BridgeError::WithdrawalsOutstanding
+        );
+        // Insurance stakers claim through the bridge state too
+        require!(
+            bridge_state.insurance_total_shares == 0,
+            BridgeError::InsuranceFundOutstanding
+        );
+        if bridge_state.insurance_vault != Pubkey::default() {
+            let insurance_vault = ctx
+                .accounts
+                .insurance_vault
+                .as_ref()
+                .ok_or(BridgeError::InvalidInsuranceVault)?;
+            require!(
+                insurance_vault.amount == 0,
+                BridgeError::InsuranceFundOutstanding
+            );
+        }
+        // Sweeping the vault would take what relayers are still owed
+        require!(
+            bridge_state.unclaimed_relayer_rewards == 0,
+            BridgeError::RelayerRewardsOutstanding
+        );
+        // Confiscated stake can only be swept while the bridge exists
+        let registry_info = ctx.accounts.relayer_registry.to_account_info();
+        if !registry_info.data_is_empty() {
+            let registry = RelayerRegistry::try_deserialize(&mut &registry_info.try_borrow_data()?[..])?;
+            require!(
+                registry.slashed_lamports == 0,
+                BridgeError::SlashedLamportsUnswept
+            );
+        }
+
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [bridge_state.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"bridge_state", mint_key.as_ref(), &bump]];
+
+        // Sweep whatever fees remain to the treasury
+        let swept = ctx.accounts.fee_vault.amount;
+        if swept > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.fee_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.bridge_state.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token_interface::transfer_checked(cpi_ctx, swept, ctx.accounts.mint.decimals)?;
+        }
+
+        // Close the now-empty fee vault; the state accounts close on exit
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.fee_vault.to_account_info(),
+            destination: ctx.accounts.authority.to_account_info(),
+            authority: ctx.accounts.bridge_state.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token_interface::close_account(cpi_ctx)?;
+
+        emit!(BridgeClosedEvent {
+            authority: ctx.accounts.authority.key(),
+            swept,
+        });
+
+        Ok(())
+    }
+
+    /// Create the Zcash light client from a trusted post-Blossom checkpoint (admin only)
+    ///
+    /// `times` and `bits` cover the `DIFFICULTY_HEADERS` blocks ending at the checkpoint,
+    /// oldest first.
+    pub fn initialize_light_client(
+        ctx: Context<InitializeLightClient>,
+        checkpoint_height: u32,
+        checkpoint_hash: [u8; 32],
+        times: Vec<u32>,
+        bits: Vec<u32>,
+        min_confirmations: Option<u32>,
+    ) -> Result<()> {
+        zcash_light_client::initialize(
+            ctx,
+            checkpoint_height,
+            checkpoint_hash,
+            times,
+            bits,
+            min_confirmations,
+        )
+    }
+
+    /// Append part of a serialized Zcash header to the submitter's header buffer
+    pub fn write_header_chunk(
+        ctx: Context<WriteHeaderChunk>,
+        offset: u32,
+        bytes: Vec<u8>,
+    ) -> Result<()> {
+        zcash_light_client::write_header_chunk(ctx, offset, bytes)
+    }
+
+    /// Verify the buffered header and extend the light client's chain with it
+    ///
+    /// Equihash verification is expensive; submit with the maximum compute budget.
+    pub fn submit_zcash_header(ctx: Context<SubmitHeader>) -> Result<()> {
+        zcash_light_client::submit_header(ctx)
+    }
+
+    /// Rewind the light client tip to a stored header after a reorg (admin only)
+    pub fn rollback_light_client(ctx: Context<RollbackLightClient>, height: u32) -> Result<()> {
+        zcash_light_client::rollback(ctx, height)
+    }
+}
+
+/// Fee owed on `amount` at `fee_bps` basis points, rounded down
+pub fn calculate_fee(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = amount
+        .checked_mul(fee_bps as u64)
+        .ok_or(BridgeError::Overflow)?
+        .checked_div(10000)
+        .ok_or(BridgeError::Overflow)?;
+
+    Ok(fee)
+}
+
+/// Smallest amount that leaves exactly `net` once `calculate_fee` is taken out
+pub fn gross_for_net(net: u64, fee_bps: u16) -> Result<u64> {
+    require!(net > 0, BridgeError::InvalidAmount);
+    require!(fee_bps < 10000, BridgeError::FeeTooHigh);
+
+    // `amount - fee` rounds up and never steps by more than one, so this hits `net`
+    let gross = (net as u128 - 1) * 10000 / (10000 - fee_bps as u128) + 1;
+    u64::try_from(gross).map_err(|_| error!(BridgeError::Overflow))
+}
+
+/// Token-2022 extensions the bridge can carry without breaking its accounting.
+/// Anything that skims transfers, hooks them or lets a third party move funds is refused.
+const ALLOWED_MINT_EXTENSIONS: [ExtensionType; 2] =
+    [ExtensionType::MetadataPointer, ExtensionType::TokenMetadata];
+
+/// Reject Token-2022 mints carrying extensions outside `ALLOWED_MINT_EXTENSIONS`
+pub fn check_mint_extensions(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        require!(
+            ALLOWED_MINT_EXTENSIONS.contains(&extension),
+            BridgeError::UnsupportedMintExtension
+        );
+    }
+
+    Ok(())
+}
+
+/// Close a program-owned account that no longer deserializes as any current type
+fn close_legacy_account<'info>(info: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = info.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(BridgeError::Overflow)?;
+    **info.try_borrow_mut_lamports()? = 0;
+    info.assign(&system_program::ID);
+    info.realloc(0, false)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    /// CHECK: PDA holding the wZEC mint authority; signs via seeds
+    #[account(
+        seeds = [b"mint_authority", mint.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    /// Fresh wrapped-asset mint, already handed to the mint authority PDA
+    #[account(
+        mut,
+        constraint = mint.decimals == WZEC_DECIMALS @ BridgeError::InvalidMintDecimals,
+        constraint = mint.supply == 0 @ BridgeError::MintSupplyNotZero,
+        constraint = mint.mint_authority == COption::Some(mint_authority.key())
+            @ BridgeError::InvalidMintAuthority,
+        constraint = mint.freeze_authority.is_none()
+            || mint.freeze_authority == COption::Some(mint_authority.key())
+            @ BridgeError::InvalidFreezeAuthority
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BridgeState::LEN,
+        seeds = [b"bridge_state", mint.key().as_ref()],
+        bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BridgeStats::LEN,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMetadata<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(address = bridge_state.mint @ BridgeError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: Metadata PDA of the mint; created by the Token Metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    
+    /// CHECK: PDA holding the wZEC mint authority; signs via seeds
+    #[account(
+        seeds = [b"mint_authority", mint.key().as_ref()],
+        bump = bridge_state.mint_authority_bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    /// Bridge authority; also pays for the metadata account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_metadata_program: Program<'info, Metadata>,
+    
+    pub system_program: Program<'info, System>,
+    
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, zcash_txid: [u8; 32])]
+pub struct MintWZEC<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + DepositReceipt::LEN,
+        seeds = [b"deposit", bridge_state.key().as_ref(), zcash_txid.as_ref()],
+        bump
+    )]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+    
+    /// CHECK: Receipt address the txid had before receipts were seeded by its raw bytes;
+    /// must be empty so deposits minted under the old seeds can't be minted again
+    #[account(
+        address = DepositReceipt::legacy_address(&bridge_state.key(), bridge_state.singleton_receipts, &zcash_txid),
+        constraint = legacy_deposit_receipt.data_is_empty() @ BridgeError::DepositAlreadyProcessed
+    )]
+    pub legacy_deposit_receipt: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = bridge_state.mint @ BridgeError::InvalidMint
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Recipient's `UserLimits` PDA; enforced only if it has been created
+    #[account(
+        mut,
+        seeds = [b"user_limits", bridge_state.key().as_ref(), recipient_token_account.owner.as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+    
+    /// CHECK: Recipient's `DenyListEntry` PDA; the instruction is refused if it exists
+    #[account(
+        seeds = [b"deny_list", bridge_state.key().as_ref(), recipient_token_account.owner.as_ref()],
+        bump
+    )]
+    pub deny_list_entry: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA holding the wZEC mint authority; signs via seeds
+    #[account(
+        seeds = [b"mint_authority", mint.key().as_ref()],
+        bump = bridge_state.mint_authority_bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    /// Registry and stake of a bonded relayer submitting in place of the authority
+    #[account(
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Option<Account<'info, RelayerRegistry>>,
+    
+    #[account(
+        seeds = [b"relayer_stake", bridge_state.key().as_ref(), authority.key().as_ref()],
+        bump = relayer_stake.bump
+    )]
+    pub relayer_stake: Option<Account<'info, RelayerStake>>,
+    
+    /// Activity counters of the signer; required alongside a relayer stake
+    #[account(
+        mut,
+        seeds = [b"relayer_stats", bridge_state.key().as_ref(), authority.key().as_ref()],
+        bump = relayer_stats.bump
+    )]
+    pub relayer_stats: Option<Account<'info, RelayerStats>>,
+    
+    /// Bridge authority or a bonded relayer; also pays for the deposit receipt
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the preceding ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    /// Records the deposit's provenance in a memo on the transaction when passed
+    pub memo_program: Option<Program<'info, Memo>>,
+}
+
+#[derive(Accounts)]
+pub struct MintWZECBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    #[account(
+        mut,
+        address = bridge_state.mint @ BridgeError::InvalidMint
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA holding the wZEC mint authority; signs via seeds
+    #[account(
+        seeds = [b"mint_authority", mint.key().as_ref()],
+        bump = bridge_state.mint_authority_bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    /// Registry and stake of a bonded relayer submitting in place of the authority
+    #[account(
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Option<Account<'info, RelayerRegistry>>,
+    
+    #[account(
+        seeds = [b"relayer_stake", bridge_state.key().as_ref(), authority.key().as_ref()],
+        bump = relayer_stake.bump
+    )]
+    pub relayer_stake: Option<Account<'info, RelayerStake>>,
+    
+    /// Activity counters of the signer; required alongside a relayer stake
+    #[account(
+        mut,
+        seeds = [b"relayer_stats", bridge_state.key().as_ref(), authority.key().as_ref()],
+        bump = relayer_stats.bump
+    )]
+    pub relayer_stats: Option<Account<'info, RelayerStats>>,
+    
+    /// Bridge authority or a bonded relayer; also pays for the deposit receipts
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the preceding ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32])]
+pub struct PostMintRoot<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MintRoot::LEN,
+        seeds = [b"mint_root", bridge_state.key().as_ref(), root.as_ref()],
+        bump
+    )]
+    pub mint_root: Account<'info, MintRoot>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the preceding ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PostReserveAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    #[account(address = bridge_state.mint @ BridgeError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// Ring slot the next attestation overwrites
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ReserveAttestation::LEN,
+        seeds = [
+            b"reserve_attestation",
+            bridge_state.key().as_ref(),
+            &(bridge_state.reserve_attestation_count % ReserveAttestation::RING_SIZE).to_le_bytes()
+        ],
+        bump
+    )]
+    pub reserve_attestation: Account<'info, ReserveAttestation>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the preceding ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, zcash_txid: [u8; 32])]
+pub struct ClaimMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        mut,
+        seeds = [b"mint_root", bridge_state.key().as_ref(), mint_root.root.as_ref()],
+        bump = mint_root.bump
+    )]
+    pub mint_root: Account<'info, MintRoot>,
+    
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        space = 8 + DepositReceipt::LEN,
+        seeds = [b"deposit", bridge_state.key().as_ref(), zcash_txid.as_ref()],
+        bump
+    )]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+    
+    /// CHECK: Receipt address the txid had before receipts were seeded by its raw bytes;
+    /// must be empty so deposits minted under the old seeds can't be minted again
+    #[account(
+        address = DepositReceipt::legacy_address(&bridge_state.key(), bridge_state.singleton_receipts, &zcash_txid),
+        constraint = legacy_deposit_receipt.data_is_empty() @ BridgeError::DepositAlreadyProcessed
+    )]
+    pub legacy_deposit_receipt: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = bridge_state.mint @ BridgeError::InvalidMint
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint,
+        constraint = recipient_token_account.owner == claimant.key() @ BridgeError::InvalidTokenAccount
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Claimant's `UserLimits` PDA; enforced only if it has been created
+    #[account(
+        mut,
+        seeds = [b"user_limits", bridge_state.key().as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+    
+    /// CHECK: Claimant's `DenyListEntry` PDA; the instruction is refused if it exists
+    #[account(
+        seeds = [b"deny_list", bridge_state.key().as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub deny_list_entry: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA holding the wZEC mint authority; signs via seeds
+    #[account(
+        seeds = [b"mint_authority", mint.key().as_ref()],
+        bump = bridge_state.mint_authority_bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    /// Deposit recipient named in the Merkle leaf; pays for the deposit receipt
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(zcash_txid: [u8; 32])]
+pub struct MintWithSpvProof<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        seeds = [b"light_client", bridge_state.key().as_ref()],
+        bump = light_client.load()?.bump
+    )]
+    pub light_client: AccountLoader<'info, LightClient>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + DepositReceipt::LEN,
+        seeds = [b"deposit", bridge_state.key().as_ref(), zcash_txid.as_ref()],
+        bump
+    )]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+    
+    /// CHECK: Receipt address the txid had before receipts were seeded by its raw bytes;
+    /// must be empty so deposits minted under the old seeds can't be minted again
+    #[account(
+        address = DepositReceipt::legacy_address(&bridge_state.key(), bridge_state.singleton_receipts, &zcash_txid),
+        constraint = legacy_deposit_receipt.data_is_empty() @ BridgeError::DepositAlreadyProcessed
+    )]
+    pub legacy_deposit_receipt: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = bridge_state.mint @ BridgeError::InvalidMint
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Recipient's `UserLimits` PDA; enforced only if it has been created
+    #[account(
+        mut,
+        seeds = [b"user_limits", bridge_state.key().as_ref(), recipient_token_account.owner.as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+    
+    /// CHECK: Recipient's `DenyListEntry` PDA; the instruction is refused if it exists
+    #[account(
+        seeds = [b"deny_list", bridge_state.key().as_ref(), recipient_token_account.owner.as_ref()],
+        bump
+    )]
+    pub deny_list_entry: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA holding the wZEC mint authority; signs via seeds
+    #[account(
+        seeds = [b"mint_authority", mint.key().as_ref()],
+        bump = bridge_state.mint_authority_bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    /// Anyone may submit a proof; pays for the deposit receipt
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetVerifyingKey<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + VerifyingKey::LEN,
+        seeds = [b"verifying_key", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(zcash_txid: [u8; 32])]
+pub struct MintWithShieldedProof<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        seeds = [b"light_client", bridge_state.key().as_ref()],
+        bump = light_client.load()?.bump
+    )]
+    pub light_client: AccountLoader<'info, LightClient>,
+    
+    #[account(
+        seeds = [b"verifying_key", bridge_state.key().as_ref()],
+        bump = verifying_key.bump
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + DepositReceipt::LEN,
+        seeds = [b"deposit", bridge_state.key().as_ref(), zcash_txid.as_ref()],
+        bump
+    )]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+    
+    /// CHECK: Receipt address the txid had before receipts were seeded by its raw bytes;
+    /// must be empty so deposits minted under the old seeds can't be minted again
+    #[account(
+        address = DepositReceipt::legacy_address(&bridge_state.key(), bridge_state.singleton_receipts, &zcash_txid),
+        constraint = legacy_deposit_receipt.data_is_empty() @ BridgeError::DepositAlreadyProcessed
+    )]
+    pub legacy_deposit_receipt: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = bridge_state.mint @ BridgeError::InvalidMint
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Recipient's `UserLimits` PDA; enforced only if it has been created
+    #[account(
+        mut,
+        seeds = [b"user_limits", bridge_state.key().as_ref(), recipient_token_account.owner.as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+    
+    /// CHECK: Recipient's `DenyListEntry` PDA; the instruction is refused if it exists
+    #[account(
+        seeds = [b"deny_list", bridge_state.key().as_ref(), recipient_token_account.owner.as_ref()],
+        bump
+    )]
+    pub deny_list_entry: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA holding the wZEC mint authority; signs via seeds
+    #[account(
+        seeds = [b"mint_authority", mint.key().as_ref()],
+        bump = bridge_state.mint_authority_bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    /// Anyone may submit a proof; pays for the deposit receipt
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, zec_address: String, memo: Option<Vec<u8>>)]
+pub struct BurnWZEC<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = 8 + WithdrawalRequest::space(&zec_address, memo.as_ref()),
+        seeds = [b"withdrawal", bridge_state.key().as_ref(), &bridge_state.withdrawal_nonce.to_le_bytes()],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    
+    #[account(
+        mut,
+        address = bridge_state.mint @ BridgeError::InvalidMint
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint,
+        constraint = user_token_account.owner == user.key() @ BridgeError::InvalidTokenAccount
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Required while burn fees are shared with the insurance fund
+    #[account(
+        mut,
+        address = bridge_state.insurance_vault @ BridgeError::InvalidInsuranceVault
+    )]
+    pub insurance_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: User's `UserLimits` PDA; enforced only if it has been created
+    #[account(
+        mut,
+        seeds = [b"user_limits", bridge_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+    
+    /// CHECK: User's `DenyListEntry` PDA; the instruction is refused if it exists
+    #[account(
+        seeds = [b"deny_list", bridge_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub deny_list_entry: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, zec_address: String)]
+pub struct BurnWZECWithPermit<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + WithdrawalRequest::space(&zec_address, None),
+        seeds = [b"withdrawal", bridge_state.key().as_ref(), &bridge_state.withdrawal_nonce.to_le_bytes()],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    
+    #[account(
+        mut,
+        address = bridge_state.mint @ BridgeError::InvalidMint
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint,
+        constraint = user_token_account.owner == owner.key() @ BridgeError::InvalidTokenAccount
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Required while burn fees are shared with the insurance fund
+    #[account(
+        mut,
+        address = bridge_state.insurance_vault @ BridgeError::InvalidInsuranceVault
+    )]
+    pub insurance_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: Owner's `UserLimits` PDA; enforced only if it has been created
+    #[account(
+        mut,
+        seeds = [b"user_limits", bridge_state.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+    
+    /// CHECK: Owner's `DenyListEntry` PDA; the instruction is refused if it exists
+    #[account(
+        seeds = [b"deny_list", bridge_state.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub deny_list_entry: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + PermitNonce::LEN,
+        seeds = [b"permit_nonce", bridge_state.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub permit_nonce: Account<'info, PermitNonce>,
+    
+    /// CHECK: Token owner who signed the permit; verified through the ed25519 instruction
+    pub owner: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = relayer_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint,
+        constraint = relayer_token_account.owner == relayer.key() @ BridgeError::InvalidTokenAccount
+    )]
+    pub relayer_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the preceding ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelPermits<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PermitNonce::LEN,
+        seeds = [b"permit_nonce", bridge_state.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub permit_nonce: Account<'info, PermitNonce>,
+    
+    pub owner: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"withdrawal", bridge_state.key().as_ref(), &withdrawal_request.nonce.to_le_bytes()],
+        bump = withdrawal_request.bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    
+    /// Registry and stake of a bonded relayer finalizing in place of the authority;
+    /// the relayer is credited its share of the withdrawal's burn fee
+    #[account(
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Option<Account<'info, RelayerRegistry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_stake", bridge_state.key().as_ref(), authority.key().as_ref()],
+        bump = relayer_stake.bump
+    )]
+    pub relayer_stake: Option<Account<'info, RelayerStake>>,
+    
+    /// Activity counters of the signer; required alongside a relayer stake
+    #[account(
+        mut,
+        seeds = [b"relayer_stats", bridge_state.key().as_ref(), authority.key().as_ref()],
+        bump = relayer_stats.bump
+    )]
+    pub relayer_stats: Option<Account<'info, RelayerStats>>,
+    
+    /// Bridge authority or a bonded relayer
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRelayerRegistry<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RelayerRegistry::LEN,
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRelayerRegistry<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRelayerRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_stake", bridge_state.key().as_ref(), relayer.key().as_ref()],
+        bump = relayer_stake.bump
+    )]
+    pub relayer_stake: Account<'info, RelayerStake>,
+    
+    #[account(address = bridge_state.mint @ BridgeError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = relayer_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint
+    )]
+    pub relayer_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub relayer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRelayerStats<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + RelayerStats::LEN,
+        seeds = [b"relayer_stats", bridge_state.key().as_ref(), relayer.key().as_ref()],
+        bump
+    )]
+    pub relayer_stats: Account<'info, RelayerStats>,
+    
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StakeRelayer<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + RelayerStake::LEN,
+        seeds = [b"relayer_stake", bridge_state.key().as_ref(), relayer.key().as_ref()],
+        bump
+    )]
+    pub relayer_stake: Account<'info, RelayerStake>,
+    
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeRelayer<'info> {
+    /// CHECK: Bridge state the stake is bonded to; only its key is used, so stake
+    /// can still be unbonded after the bridge is closed
+    pub bridge_state: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_stake", bridge_state.key().as_ref(), relayer.key().as_ref()],
+        bump = relayer_stake.bump
+    )]
+    pub relayer_stake: Account<'info, RelayerStake>,
+    
+    pub relayer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRelayerStake<'info> {
+    /// CHECK: Bridge state the stake is bonded to; only its key is used, so stake
+    /// can still be withdrawn after the bridge is closed
+    pub bridge_state: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_stake", bridge_state.key().as_ref(), relayer.key().as_ref()],
+        bump = relayer_stake.bump
+    )]
+    pub relayer_stake: Account<'info, RelayerStake>,
+    
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashRelayer<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_stake", bridge_state.key().as_ref(), relayer_stake.relayer.as_ref()],
+        bump = relayer_stake.bump
+    )]
+    pub relayer_stake: Account<'info, RelayerStake>,
+}
+
+#[derive(Accounts)]
+pub struct SweepSlashedLamports<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump = relayer_registry.bump
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VetoWithdrawal<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    #[account(
+        mut,
+        seeds = [b"withdrawal", bridge_state.key().as_ref(), &withdrawal_request.nonce.to_le_bytes()],
+        bump = withdrawal_request.bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnvetoWithdrawal<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    #[account(
+        mut,
+        seeds = [b"withdrawal", bridge_state.key().as_ref(), &withdrawal_request.nonce.to_le_bytes()],
+        bump = withdrawal_request.bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        mut,
+        seeds = [b"withdrawal", bridge_state.key().as_ref(), &withdrawal_request.nonce.to_le_bytes()],
+        bump = withdrawal_request.bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    
+    #[account(
+        mut,
+        address = bridge_state.mint @ BridgeError::InvalidMint
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint,
+        constraint = user_token_account.owner == withdrawal_request.user @ BridgeError::InvalidTokenAccount
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA holding the wZEC mint authority; signs via seeds
+    #[account(
+        seeds = [b"mint_authority", mint.key().as_ref()],
+        bump = bridge_state.mint_authority_bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + PendingChange::LEN,
+        seeds = [b"pending_change", bridge_state.key().as_ref(), &bridge_state.next_change_id.to_le_bytes()],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct ExecuteChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"pending_change", bridge_state.key().as_ref(), &id.to_le_bytes()],
+        bump = pending_change.bump,
+        close = proposer
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    
+    /// CHECK: receives the rent of the executed change
+    #[account(mut, address = pending_change.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Option<Account<'info, GuardianSet>>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CancelPendingChange<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"pending_change", bridge_state.key().as_ref(), &id.to_le_bytes()],
+        bump = pending_change.bump,
+        close = proposer
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    
+    /// CHECK: receives the rent of the cancelled change
+    #[account(mut, address = pending_change.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct SetUserLimits<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + UserLimits::LEN,
+        seeds = [b"user_limits", bridge_state.key().as_ref(), user.as_ref()],
+        bump
+    )]
+    pub user_limits: Account<'info, UserLimits>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct RemoveUserLimits<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"user_limits", bridge_state.key().as_ref(), user.as_ref()],
+        bump = user_limits.bump
+    )]
+    pub user_limits: Account<'info, UserLimits>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct SetFeeExemption<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + FeeExemption::LEN,
+        seeds = [b"fee_exemption", bridge_state.key().as_ref(), user.as_ref()],
+        bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct RemoveFeeExemption<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"fee_exemption", bridge_state.key().as_ref(), user.as_ref()],
+        bump = fee_exemption.bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct AddToDenyList<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + DenyListEntry::LEN,
+        seeds = [b"deny_list", bridge_state.key().as_ref(), user.as_ref()],
+        bump
+    )]
+    pub deny_list_entry: Account<'info, DenyListEntry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct RemoveFromDenyList<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"deny_list", bridge_state.key().as_ref(), user.as_ref()],
+        bump = deny_list_entry.bump
+    )]
+    pub deny_list_entry: Account<'info, DenyListEntry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(address = bridge_state.mint @ BridgeError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = bridge_state.treasury @ BridgeError::InvalidTreasury,
+        constraint = treasury_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(address = bridge_state.mint @ BridgeError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA owning the insurance vault; it holds no data
+    #[account(
+        seeds = [b"insurance", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub insurance_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = insurance_authority,
+        associated_token::token_program = token_program
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PayInsuranceClaim<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(address = bridge_state.mint @ BridgeError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA owning the insurance vault; it holds no data
+    #[account(
+        seeds = [b"insurance", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub insurance_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = bridge_state.insurance_vault @ BridgeError::InvalidInsuranceVault
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct StakeInsurance<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(address = bridge_state.mint @ BridgeError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        address = bridge_state.insurance_vault @ BridgeError::InvalidInsuranceVault
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = 8 + InsuranceStake::LEN,
+        seeds = [b"insurance_stake", bridge_state.key().as_ref(), staker.key().as_ref()],
+        bump
+    )]
+    pub insurance_stake: Account<'info, InsuranceStake>,
+    
+    #[account(
+        mut,
+        constraint = staker_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint,
+        constraint = staker_token_account.owner == staker.key() @ BridgeError::InvalidTokenAccount
+    )]
+    pub staker_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeInsurance<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"insurance_stake", bridge_state.key().as_ref(), staker.key().as_ref()],
+        bump = insurance_stake.bump
+    )]
+    pub insurance_stake: Account<'info, InsuranceStake>,
+    
+    pub staker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimInsuranceStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(address = bridge_state.mint @ BridgeError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA owning the insurance vault; it holds no data
+    #[account(
+        seeds = [b"insurance", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub insurance_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = bridge_state.insurance_vault @ BridgeError::InvalidInsuranceVault
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"insurance_stake", bridge_state.key().as_ref(), staker.key().as_ref()],
+        bump = insurance_stake.bump
+    )]
+    pub insurance_stake: Account<'info, InsuranceStake>,
+    
+    #[account(
+        mut,
+        constraint = staker_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint
+    )]
+    pub staker_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub staker: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGuardianSet<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GuardianSet::LEN,
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RotateGuardianSet<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    /// Copy of the outgoing set, valid through the grace period
+    #[account(
+        init,
+        payer = caller,
+        space = 8 + ArchivedGuardianSet::LEN,
+        seeds = [
+            b"guardian_set",
+            bridge_state.key().as_ref(),
+            &guardian_set.index.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub archived_guardian_set: Account<'info, ArchivedGuardianSet>,
+    
+    /// Guardian manager or authority, or whoever submits the outgoing quorum's signatures
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the preceding ed25519 instructions
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAuthorityProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRole<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PauseBridge<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResumeBridge<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TriggerDeadMansSwitch<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianResume<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: Bridge state PDA in a possibly older layout; verified in the handler
+    #[account(
+        mut,
+        seeds = [b"bridge_state", mint.key().as_ref()],
+        bump
+    )]
+    pub bridge_state: UncheckedAccount<'info>,
+    
+    /// CHECK: Mint of the bridge instance; only used to derive its state PDA
+    pub mint: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitBridgeStats<'info> {
+    /// CHECK: Bridge state PDA in a possibly older layout; verified in the handler
+    #[account(
+        seeds = [b"bridge_state", mint.key().as_ref()],
+        bump
+    )]
+    pub bridge_state: UncheckedAccount<'info>,
+    
+    /// CHECK: Mint of the bridge instance; only used to derive its state PDA
+    pub mint: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BridgeStats::LEN,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLegacyBridge<'info> {
+    /// CHECK: Singleton bridge state from before instances were keyed by mint, in any
+    /// stored layout; verified in the handler
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump
+    )]
+    pub legacy_bridge_state: UncheckedAccount<'info>,
+    
+    /// CHECK: Singleton stats account; empty when the stored layout keeps totals inline
+    #[account(
+        mut,
+        seeds = [b"bridge_stats"],
+        bump
+    )]
+    pub legacy_bridge_stats: UncheckedAccount<'info>,
+    
+    /// CHECK: Singleton mint authority PDA; signs via seeds when it still holds the mint
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    pub legacy_mint_authority: UncheckedAccount<'info>,
+    
+    /// Fee vault owned by the singleton state, absent if it was never created
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = legacy_bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub legacy_fee_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: PDA taking over the mint authority
+    #[account(
+        seeds = [b"mint_authority", mint.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BridgeState::LEN,
+        seeds = [b"bridge_state", mint.key().as_ref()],
+        bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BridgeStats::LEN,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct MigrateWithdrawalRequest<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    /// CHECK: Withdrawal request stored before memos, which `Account` can't always
+    /// decode; verified in the handler
+    #[account(
+        mut,
+        seeds = [b"withdrawal", bridge_state.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub withdrawal_request: UncheckedAccount<'info>,
+    
     #[account(mut)]
-    pub mint: Account<'info, Mint>,
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(zcash_txid: [u8; 32])]
+pub struct CloseReceipt<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    /// CHECK: Deposit receipt; decoded in the handler, since it is left as a
+    /// tombstone `Account` couldn't write back
+    #[account(
+        mut,
+        seeds = [b"deposit", bridge_state.key().as_ref(), zcash_txid.as_ref()],
+        bump
+    )]
+    pub deposit_receipt: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = closer,
+        space = 8 + ClosedAccountLog::LEN,
+        seeds = [b"closed_accounts", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub closed_account_log: Account<'info, ClosedAccountLog>,
+    
+    /// CHECK: Receives the refunded rent; checked against the receipt's payer
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    
+    /// Anyone may close an aged receipt; pays for the log the first time
+    #[account(mut)]
+    pub closer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CloseWithdrawal<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"withdrawal", bridge_state.key().as_ref(), &nonce.to_le_bytes()],
+        bump = withdrawal_request.bump,
+        close = payer
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    
+    #[account(
+        init_if_needed,
+        payer = closer,
+        space = 8 + ClosedAccountLog::LEN,
+        seeds = [b"closed_accounts", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub closed_account_log: Account<'info, ClosedAccountLog>,
+    
+    /// CHECK: Receives the request's rent; must be its original payer
+    #[account(
+        mut,
+        address = withdrawal_request.rent_payer() @ BridgeError::InvalidRentPayer
+    )]
+    pub payer: UncheckedAccount<'info>,
     
+    /// Anyone may close a settled request; pays for the log the first time
     #[account(mut)]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub closer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DecommissionBridge<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBridge<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump,
+        close = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_stats", bridge_state.key().as_ref()],
+        bump = bridge_state.stats_bump,
+        close = authority
+    )]
+    pub bridge_stats: AccountLoader<'info, BridgeStats>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian_set", bridge_state.key().as_ref()],
+        bump = guardian_set.bump,
+        close = authority
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    /// CHECK: Relayer registry PDA, empty if the bridge never had one; checked in the handler
+    #[account(
+        seeds = [b"relayer_registry", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub relayer_registry: UncheckedAccount<'info>,
+    
+    /// Required once the insurance fund exists
+    #[account(address = bridge_state.insurance_vault @ BridgeError::InvalidInsuranceVault)]
+    pub insurance_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(address = bridge_state.mint @ BridgeError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_state,
+        associated_token::token_program = token_program
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = bridge_state.treasury @ BridgeError::InvalidTreasury,
+        constraint = treasury_token_account.mint == mint.key() @ BridgeError::InvalidTokenMint
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[account]
+pub struct BridgeState {
+    pub version: u8,  // Layout version, see `BridgeState::VERSION`
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,  // Pubkey::default() when no proposal is open
+    pub mint: Pubkey,
+    pub mint_fee_bps: u16,  // Basis points (10 = 0.1%)
+    pub burn_fee_bps: u16,  // Basis points (10 = 0.1%)
+    pub cancellation_fee_bps: u16,  // Charged on the net amount of cancelled withdrawals
+    pub treasury: Pubkey,  // Token account receiving withdrawn fees
+    pub max_mint_amount: u64,  // Per-transaction cap, u64::MAX when uncapped
+    pub max_burn_amount: u64,  // Per-transaction cap, u64::MAX when uncapped
+    pub daily_mint_limit: u64,  // Rolling 24-hour cap, u64::MAX when uncapped
+    pub withdrawal_nonce: u64,  // Nonce assigned to the next withdrawal request
+    pub large_withdrawal_threshold: u64,  // Burns above this amount are timelocked
+    pub large_withdrawal_delay_slots: u64,
+    pub mints_paused: bool,
+    pub burns_paused: bool,
+    pub circuit_breaker_threshold: u64,  // Mint volume that auto-pauses mints, u64::MAX when disabled
+    pub circuit_breaker_window_hours: u8,
+    pub circuit_breaker_tripped: bool,
+    pub last_heartbeat_slot: u64,
+    pub max_heartbeat_gap: u64,  // Slots without a heartbeat before anyone may pause, 0 when disabled
+    pub bump: u8,  // Canonical bump of this PDA
+    pub mint_authority_bump: u8,  // Canonical bump of the mint authority PDA
+    pub stats_bump: u8,  // Canonical bump of the `BridgeStats` PDA
+    pub decommissioned_slot: u64,  // 0 while the bridge is live
+    pub decommission_grace_slots: u64,  // Burn window after decommissioning
+    pub zcash_deposit_pubkey_hash: [u8; 20],  // P2PKH address SPV deposits pay, zero until set
+    pub network: ZcashNetwork,  // Zcash network withdrawal addresses must belong to
+    pub min_burn_amount: u64,  // Dust threshold in zatoshis, 0 when disabled
+    pub singleton_receipts: bool,  // Receipts predating the move to per-mint PDAs use the singleton seeds
+    pub unclaimed_relayer_rewards: u64,  // wZEC owed to relayers, reserved in the fee vault
+    pub kyc_threshold: u64,  // Mints and burns above this need a compliance credential, u64::MAX when disabled
+    pub compliance_attester: Pubkey,  // Program that issues compliance credentials
+    pub roles: Roles,  // Keys delegated part of the authority's powers
+    pub timelock_slots: u64,  // Delay before queued parameter changes execute, 0 when updates apply directly
+    pub next_change_id: u64,  // Id assigned to the next `PendingChange`
+    pub insurance_vault: Pubkey,  // Token account of the insurance fund, Pubkey::default() until created
+    pub insurance_share_bps: u16,  // Share of each burn fee paid into the insurance fund
+    pub insurance_total_shares: u64,  // Shares of the insurance vault, including unstaking ones
+    pub insurance_unstake_slots: u64,  // Cooldown before unstaked insurance shares can be claimed
+    pub retention_slots: u64,  // Age after which receipts and settled withdrawals can be closed, 0 when never
+    pub finalize_window: u8,  // How far past the oldest unsettled withdrawal nonce payouts may run, 0 when unordered
+    pub settled_floor: u64,  // Every withdrawal nonce below this is settled
+    pub settled_bitmap: u64,  // Bit i marks nonce `settled_floor + i` as settled
+    pub reserve_attestation_count: u64,  // Reserve attestations posted; the next one's sequence
+    pub price_feed_id: [u8; 32],  // Pyth ZEC/USD feed USD limits are converted with
+    pub max_price_age_secs: u32,  // Oldest price update USD limits accept
+    pub kyc_threshold_usd: u64,  // KYC threshold in US cents, u64::MAX when only `kyc_threshold` applies
+    pub large_withdrawal_threshold_usd: u64,  // Withdrawal timelock threshold in US cents, u64::MAX when unset
+    pub guardian_paused_by: Pubkey,  // Guardian holding an emergency mint pause, default when none
+    pub open_withdrawals: u64,  // Withdrawal requests still pending or vetoed
+}
+
+impl BridgeState {
+    /// Current layout version. Bump it whenever fields are appended and teach
+    /// `migrate` their defaults. Version 6 appends nothing: it marks accounts written
+    /// after both version 1 layouts, which share a version byte, were rewritten.
+    pub const VERSION: u8 = 19;
+    /// Offsets in versioned layouts; the unversioned original stores the authority at 8
+    pub const VERSION_OFFSET: usize = 8;
+    pub const AUTHORITY_OFFSET: usize = 9;
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 2 + 2 + 2 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 8 + 1 + 1 + 8 + 8 + 1 + 1 + 1 + 8 + 8 + 20 + 1 + 8 + 1 + 8 + 8 + 32 + Roles::LEN + 8 + 8 + 32 + 2 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 32 + 4 + 8 + 8 + 32 + 8;
+
+    /// Fill fields appended after `from_version` with their defaults
+    pub fn migrate(&mut self, from_version: u8) {
+        if from_version < 2 {
+            self.decommissioned_slot = 0;
+            self.decommission_grace_slots = 0;
+        }
+        if from_version < 3 {
+            self.zcash_deposit_pubkey_hash = [0; 20];
+        }
+        if from_version < 4 {
+            // Earlier layouts only accepted testnet Sapling addresses
+            self.network = ZcashNetwork::Testnet;
+        }
+        if from_version < 5 {
+            self.min_burn_amount = 0;
+        }
+        if from_version < 7 {
+            // Only `migrate_legacy_bridge` carries singleton receipts over
+            self.singleton_receipts = false;
+        }
+        if from_version < 8 {
+            self.unclaimed_relayer_rewards = 0;
+        }
+        if from_version < 9 {
+            self.kyc_threshold = u64::MAX;
+            self.compliance_attester = Pubkey::default();
+        }
+        if from_version < 10 {
+            self.roles = Roles::default();
+        }
+        if from_version < 11 {
+            self.timelock_slots = 0;
+            self.next_change_id = 0;
+        }
+        if from_version < 12 {
+            self.insurance_vault = Pubkey::default();
+            self.insurance_share_bps = 0;
+        }
+        if from_version < 13 {
+            self.insurance_total_shares = 0;
+            self.insurance_unstake_slots = 0;
+        }
+        if from_version < 14 {
+            self.retention_slots = 0;
+        }
+        if from_version < 15 {
+            self.finalize_window = 0;
+            self.settled_floor = 0;
+            self.settled_bitmap = 0;
+        }
+        if from_version < 16 {
+            self.reserve_attestation_count = 0;
+        }
+        if from_version < 17 {
+            self.price_feed_id = [0; 32];
+            self.max_price_age_secs = 0;
+            self.kyc_threshold_usd = u64::MAX;
+            self.large_withdrawal_threshold_usd = u64::MAX;
+        }
+        if from_version < 18 {
+            self.guardian_paused_by = Pubkey::default();
+        }
+        if from_version < 19 {
+            // Withdrawals opened before the count existed aren't in it
+            self.open_withdrawals = 0;
+        }
+        self.version = Self::VERSION;
+    }
+
+    /// Whether a guardian's emergency pause is holding mints
+    pub fn guardian_paused(&self) -> bool {
+        self.guardian_paused_by != Pubkey::default()
+    }
+
+    /// Whether `key` may act as `role`: its delegated holder or the authority
+    pub fn has_role(&self, role: Role, key: &Pubkey) -> bool {
+        let holder = self.roles.holder(role);
+        *key == self.authority || (holder != Pubkey::default() && *key == holder)
+    }
+
+    /// Require an account created at `created_slot` to have outlived the retention period
+    pub fn check_retention(&self, created_slot: u64) -> Result<()> {
+        require!(self.retention_slots > 0, BridgeError::RetentionNotElapsed);
+        let closable_slot = created_slot
+            .checked_add(self.retention_slots)
+            .ok_or(BridgeError::Overflow)?;
+        require!(
+            Clock::get()?.slot >= closable_slot,
+            BridgeError::RetentionNotElapsed
+        );
+        Ok(())
+    }
+
+    /// Record withdrawal `nonce` as settled, refusing it if it is a full window or
+    /// more past the oldest unsettled nonce, then advance the floor past every
+    /// settled nonce directly above it
+    pub fn record_settled(&mut self, nonce: u64) -> Result<()> {
+        if self.finalize_window == 0 || nonce < self.settled_floor {
+            return Ok(());
+        }
+        let offset = nonce - self.settled_floor;
+        require!(
+            offset < self.finalize_window as u64,
+            BridgeError::WithdrawalOutOfOrder
+        );
+        self.settled_bitmap |= 1 << offset;
+
+        let advance = self.settled_bitmap.trailing_ones();
+        self.settled_bitmap = self.settled_bitmap.checked_shr(advance).unwrap_or(0);
+        self.settled_floor = self
+            .settled_floor
+            .checked_add(advance as u64)
+            .ok_or(BridgeError::Overflow)?;
+        Ok(())
+    }
+
+    /// Drop a completed or cancelled withdrawal from the open count. Saturates, since
+    /// withdrawals opened before the count was added aren't in it.
+    pub fn record_closed_withdrawal(&mut self) {
+        self.open_withdrawals = self.open_withdrawals.saturating_sub(1);
+    }
+
+    /// Zatoshis above which mints and burns need a compliance credential: the lower
+    /// of `kyc_threshold` and the USD threshold at the price among `accounts`
+    pub fn kyc_threshold_now(&self, accounts: &[AccountInfo]) -> Result<u64> {
+        self.with_usd_limit(self.kyc_threshold, self.kyc_threshold_usd, accounts)
+    }
+
+    /// Zatoshis above which burns are timelocked: the lower of
+    /// `large_withdrawal_threshold` and the USD threshold at the price among `accounts`
+    pub fn large_withdrawal_threshold_now(&self, accounts: &[AccountInfo]) -> Result<u64> {
+        self.with_usd_limit(
+            self.large_withdrawal_threshold,
+            self.large_withdrawal_threshold_usd,
+            accounts,
+        )
+    }
+
+    /// Tighten a limit in zatoshis by one in US cents, converted at the Pyth price
+    /// passed among `accounts`, which is required whenever the USD limit is set
+    fn with_usd_limit(&self, zatoshis: u64, usd_cents: u64, accounts: &[AccountInfo]) -> Result<u64> {
+        if usd_cents == u64::MAX {
+            return Ok(zatoshis);
+        }
+        let price = oracle::zec_usd_price(accounts, &self.price_feed_id, self.max_price_age_secs)?;
+        Ok(zatoshis.min(price.usd_cents_to_zatoshis(usd_cents)?))
+    }
+
+    /// Direct parameter updates are only allowed while no timelock is set;
+    /// otherwise changes wait in a `PendingChange`
+    pub fn require_no_timelock(&self) -> Result<()> {
+        require!(self.timelock_slots == 0, BridgeError::ChangeTimelocked);
+        Ok(())
+    }
+
+    /// Validate and apply a parameter change, emitting the same event as its
+    /// direct update. Guardian set changes are applied by `GuardianSet::rotate`.
+    pub fn apply_change(&mut self, change: &ParameterChange) -> Result<()> {
+        match *change {
+            ParameterChange::Fee { kind, fee_bps } => {
+                require!(fee_bps <= MAX_FEE_BPS, BridgeError::FeeTooHigh);
+
+                let fee = match kind {
+                    FeeKind::Mint => &mut self.mint_fee_bps,
+                    FeeKind::Burn => &mut self.burn_fee_bps,
+                    FeeKind::Cancellation => &mut self.cancellation_fee_bps,
+                };
+                let old_fee_bps = std::mem::replace(fee, fee_bps);
+
+                emit!(FeeUpdatedEvent {
+                    kind,
+                    old_fee_bps,
+                    new_fee_bps: fee_bps,
+                });
+            }
+            ParameterChange::TransactionLimits { max_mint_amount, max_burn_amount } => {
+                require!(
+                    max_mint_amount > 0
+                        && max_burn_amount > 0
+                        && max_burn_amount >= self.min_burn_amount,
+                    BridgeError::InvalidAmount
+                );
+
+                self.max_mint_amount = max_mint_amount;
+                self.max_burn_amount = max_burn_amount;
+
+                emit!(TransactionLimitsUpdatedEvent {
+                    max_mint_amount,
+                    max_burn_amount,
+                });
+            }
+            ParameterChange::DailyMintLimit { daily_mint_limit } => {
+                require!(daily_mint_limit > 0, BridgeError::InvalidAmount);
+
+                self.daily_mint_limit = daily_mint_limit;
+
+                emit!(DailyMintLimitUpdatedEvent { daily_mint_limit });
+            }
+            ParameterChange::MinBurnAmount { min_burn_amount } => {
+                require!(
+                    min_burn_amount <= self.max_burn_amount,
+                    BridgeError::InvalidAmount
+                );
+
+                let old_min_burn_amount = self.min_burn_amount;
+                self.min_burn_amount = min_burn_amount;
+
+                emit!(MinBurnAmountUpdatedEvent {
+                    old_min_burn_amount,
+                    new_min_burn_amount: min_burn_amount,
+                });
+            }
+            ParameterChange::Compliance { kyc_threshold, compliance_attester } => {
+                require!(
+                    (kyc_threshold == u64::MAX && self.kyc_threshold_usd == u64::MAX)
+                        || compliance_attester != Pubkey::default(),
+                    BridgeError::InvalidComplianceAttester
+                );
+
+                self.kyc_threshold = kyc_threshold;
+                self.compliance_attester = compliance_attester;
+
+                emit!(ComplianceUpdatedEvent {
+                    kyc_threshold,
+                    compliance_attester,
+                });
+            }
+            ParameterChange::WithdrawalTimelock {
+                large_withdrawal_threshold,
+                large_withdrawal_delay_slots,
+            } => {
+                self.large_withdrawal_threshold = large_withdrawal_threshold;
+                self.large_withdrawal_delay_slots = large_withdrawal_delay_slots;
+
+                emit!(WithdrawalTimelockUpdatedEvent {
+                    large_withdrawal_threshold,
+                    large_withdrawal_delay_slots,
+                });
+            }
+            ParameterChange::CircuitBreaker { threshold, window_hours } => {
+                require!(threshold > 0, BridgeError::InvalidAmount);
+                require!(
+                    window_hours > 0 && window_hours as usize <= VolumeWindow::BUCKETS,
+                    BridgeError::InvalidCircuitBreakerWindow
+                );
+
+                self.circuit_breaker_threshold = threshold;
+                self.circuit_breaker_window_hours = window_hours;
+
+                emit!(CircuitBreakerUpdatedEvent {
+                    threshold,
+                    window_hours,
+                });
+            }
+            ParameterChange::UsdLimits {
+                price_feed_id,
+                max_price_age_secs,
+                kyc_threshold_usd,
+                large_withdrawal_threshold_usd,
+            } => {
+                let usd_limits_set =
+                    kyc_threshold_usd != u64::MAX || large_withdrawal_threshold_usd != u64::MAX;
+                require!(
+                    !usd_limits_set || price_feed_id != [0; 32],
+                    BridgeError::InvalidPriceFeed
+                );
+                require!(
+                    kyc_threshold_usd == u64::MAX || self.compliance_attester != Pubkey::default(),
+                    BridgeError::InvalidComplianceAttester
+                );
+
+                self.price_feed_id = price_feed_id;
+                self.max_price_age_secs = max_price_age_secs;
+                self.kyc_threshold_usd = kyc_threshold_usd;
+                self.large_withdrawal_threshold_usd = large_withdrawal_threshold_usd;
+
+                emit!(UsdLimitsUpdatedEvent {
+                    price_feed_id,
+                    max_price_age_secs,
+                    kyc_threshold_usd,
+                    large_withdrawal_threshold_usd,
+                });
+            }
+            ParameterChange::TimelockSlots { timelock_slots } => {
+                let old_timelock_slots = self.timelock_slots;
+                self.timelock_slots = timelock_slots;
+
+                emit!(TimelockSlotsUpdatedEvent {
+                    old_timelock_slots,
+                    new_timelock_slots: timelock_slots,
+                });
+            }
+            ParameterChange::GuardianSet { .. } => return err!(BridgeError::GuardianSetRequired),
+        }
+        Ok(())
+    }
+
+    /// Require a compliance credential for `user` among `accounts` when `amount`
+    /// exceeds the KYC threshold. A credential is any account the attester program
+    /// owns at its `[b"credential", user]` PDA; attesters revoke by closing it.
+    pub fn check_compliance(&self, accounts: &[AccountInfo], user: &Pubkey, amount: u64) -> Result<()> {
+        if amount <= self.kyc_threshold_now(accounts)? {
+            return Ok(());
+        }
+        let (credential, _) = Pubkey::find_program_address(
+            &[b"credential", user.as_ref()],
+            &self.compliance_attester,
+        );
+        require!(
+            accounts.iter().any(|account| {
+                account.key == &credential
+                    && account.owner == &self.compliance_attester
+                    && !account.data_is_empty()
+            }),
+            BridgeError::ComplianceAttestationRequired
+        );
+        Ok(())
+    }
+}
+
+/// Parameter update that can be queued behind the change timelock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ParameterChange {
+    Fee { kind: FeeKind, fee_bps: u16 },
+    TransactionLimits { max_mint_amount: u64, max_burn_amount: u64 },
+    DailyMintLimit { daily_mint_limit: u64 },
+    MinBurnAmount { min_burn_amount: u64 },
+    Compliance { kyc_threshold: u64, compliance_attester: Pubkey },
+    WithdrawalTimelock { large_withdrawal_threshold: u64, large_withdrawal_delay_slots: u64 },
+    CircuitBreaker { threshold: u64, window_hours: u8 },
+    GuardianSet { guardians: Vec<Pubkey>, threshold: u8 },
+    TimelockSlots { timelock_slots: u64 },
+    UsdLimits {
+        price_feed_id: [u8; 32],
+        max_price_age_secs: u32,
+        kyc_threshold_usd: u64,
+        large_withdrawal_threshold_usd: u64,
+    },
+}
+
+impl ParameterChange {
+    /// Largest variant: a full guardian set
+    pub const MAX_LEN: usize = 1 + (4 + 32 * GuardianSet::MAX_GUARDIANS) + 1;
+
+    /// Whether `key` may queue this change: whoever may make it directly
+    pub fn authorized(&self, bridge_state: &BridgeState, key: &Pubkey) -> bool {
+        match self {
+            Self::Fee { .. } => bridge_state.has_role(Role::FeeManager, key),
+            Self::GuardianSet { .. } => bridge_state.has_role(Role::GuardianManager, key),
+            _ => *key == bridge_state.authority,
+        }
+    }
+}
+
+/// Parameter change waiting out the timelock, closed when executed or cancelled
+#[account]
+pub struct PendingChange {
+    pub id: u64,
+    pub proposer: Pubkey,  // Refunded the rent on close
+    pub change: ParameterChange,
+    pub queued_slot: u64,
+    pub executable_slot: u64,
+    pub bump: u8,
+}
+
+impl PendingChange {
+    pub const LEN: usize = 8 + 32 + ParameterChange::MAX_LEN + 8 + 8 + 1;
+}
+
+/// Powers the authority can hand to separate keys or multisigs
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Pauser,  // pause_bridge
+    FeeManager,  // Fee updates and fee exemptions
+    Minter,  // mint_wzec, mint_wzec_batch and post_mint_root
+    GuardianManager,  // Guardian set creation and rotation
+}
+
+/// Holder of each `Role`, Pubkey::default() when only the authority holds it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Roles {
+    pub pauser: Pubkey,
+    pub fee_manager: Pubkey,
+    pub minter: Pubkey,
+    pub guardian_manager: Pubkey,
+}
+
+impl Roles {
+    pub const LEN: usize = 32 * 4;
+
+    pub fn holder(&self, role: Role) -> Pubkey {
+        match role {
+            Role::Pauser => self.pauser,
+            Role::FeeManager => self.fee_manager,
+            Role::Minter => self.minter,
+            Role::GuardianManager => self.guardian_manager,
+        }
+    }
+
+    fn holder_mut(&mut self, role: Role) -> &mut Pubkey {
+        match role {
+            Role::Pauser => &mut self.pauser,
+            Role::FeeManager => &mut self.fee_manager,
+            Role::Minter => &mut self.minter,
+            Role::GuardianManager => &mut self.guardian_manager,
+        }
+    }
+}
+
+/// Hot counters written on every mint and burn, kept apart from the config
+/// in `BridgeState` so they can be updated in place without re-serializing it
+#[account(zero_copy)]
+pub struct BridgeStats {
+    pub total_minted: u64,
+    pub total_burned: u64,
+    pub fee_collected: u64,
+    pub mint_volume: VolumeWindow,
+    pub breaker_volume: VolumeWindow,  // Mint volume since the last breaker reset
+}
+
+impl BridgeStats {
+    pub const LEN: usize = 8 + 8 + 8 + VolumeWindow::LEN + VolumeWindow::LEN;
+}
+
+/// Volume tracked in hourly buckets covering the last 24 hours
+#[zero_copy]
+#[derive(AnchorSerialize, AnchorDeserialize, Default)]
+pub struct VolumeWindow {
+    pub buckets: [u64; 24],
+    pub last_hour: i64,  // Hour index (unix time / 3600) of the newest bucket
+}
+
+impl VolumeWindow {
+    pub const BUCKETS: usize = 24;
+    pub const BUCKET_SECONDS: i64 = 3600;
+    pub const LEN: usize = 8 * Self::BUCKETS + 8;
+
+    /// Zero out buckets that have fallen out of the window ending at `now`
+    fn roll(&mut self, now: i64) {
+        let hour = now / Self::BUCKET_SECONDS;
+        if hour <= self.last_hour {
+            return;
+        }
+
+        if hour - self.last_hour >= Self::BUCKETS as i64 {
+            self.buckets = [0; 24];
+        } else {
+            for h in (self.last_hour + 1)..=hour {
+                self.buckets[h.rem_euclid(Self::BUCKETS as i64) as usize] = 0;
+            }
+        }
+        self.last_hour = hour;
+    }
+
+    /// Total volume recorded within the window
+    pub fn total(&self) -> u64 {
+        self.buckets.iter().fold(0u64, |acc, v| acc.saturating_add(*v))
+    }
+
+    /// Total volume recorded in the newest `hours` buckets, as of the last update
+    pub fn total_within(&self, hours: u8) -> u64 {
+        let hours = (hours as usize).min(Self::BUCKETS) as i64;
+        (0..hours).fold(0u64, |acc, offset| {
+            let bucket = (self.last_hour - offset).rem_euclid(Self::BUCKETS as i64) as usize;
+            acc.saturating_add(self.buckets[bucket])
+        })
+    }
+
+    /// Add `amount` to the current bucket, failing if the window total would exceed `limit`
+    pub fn record(&mut self, now: i64, amount: u64, limit: u64) -> Result<()> {
+        self.roll(now);
+
+        let total = self
+            .total()
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+        require!(total <= limit, BridgeError::RateLimitExceeded);
+
+        let bucket = self.last_hour.rem_euclid(Self::BUCKETS as i64) as usize;
+        self.buckets[bucket] = self.buckets[bucket]
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+
+        Ok(())
+    }
+}
+
+/// Per-user rolling 24-hour caps, layered on top of the bridge-wide limits
+#[account]
+pub struct UserLimits {
+    pub user: Pubkey,
+    pub mint_limit: u64,
+    pub burn_limit: u64,
+    pub mint_volume: VolumeWindow,
+    pub burn_volume: VolumeWindow,
+    pub bump: u8,
+}
+
+impl UserLimits {
+    pub const LEN: usize = 32 + 8 + 8 + VolumeWindow::LEN + VolumeWindow::LEN + 1;
+
+    /// Load the limits stored at `info`, or `None` if the PDA was never created
+    fn load<'info>(info: &AccountInfo<'info>) -> Result<Option<Account<'info, UserLimits>>> {
+        if info.data_is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Account::try_from(info)?))
+    }
+
+    /// Count a mint towards the user's rolling mint cap
+    pub fn record_mint(info: &AccountInfo, now: i64, amount: u64) -> Result<()> {
+        if let Some(mut limits) = Self::load(info)? {
+            let limit = limits.mint_limit;
+            limits.mint_volume.record(now, amount, limit)?;
+            limits.exit(&crate::ID)?;
+        }
+        Ok(())
+    }
+
+    /// Count a burn towards the user's rolling burn cap
+    pub fn record_burn(info: &AccountInfo, now: i64, amount: u64) -> Result<()> {
+        if let Some(mut limits) = Self::load(info)? {
+            let limit = limits.burn_limit;
+            limits.burn_volume.record(now, amount, limit)?;
+            limits.exit(&crate::ID)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reduced fees for a market maker, the treasury or a partner protocol. Honoured by
+/// `mint_wzec` and `burn_wzec` when passed as a remaining account; a rate above the
+/// bridge's current fee has no effect.
+#[account]
+pub struct FeeExemption {
+    pub user: Pubkey,
+    pub mint_fee_bps: u16,
+    pub burn_fee_bps: u16,
+    pub bump: u8,
+}
+
+impl FeeExemption {
+    pub const LEN: usize = 32 + 2 + 2 + 1;
+
+    /// Load `user`'s exemption if it is among `accounts`
+    fn find<'info>(
+        accounts: &[AccountInfo<'info>],
+        bridge_state: &Pubkey,
+        user: &Pubkey,
+    ) -> Result<Option<Account<'info, FeeExemption>>> {
+        let (address, _) = Pubkey::find_program_address(
+            &[b"fee_exemption", bridge_state.as_ref(), user.as_ref()],
+            &crate::ID,
+        );
+        match accounts.iter().find(|account| account.key == &address) {
+            Some(info) => Ok(Some(Account::try_from(info)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Mint fee to charge `user`, given the bridge's own rate
+    pub fn mint_fee_bps(
+        accounts: &[AccountInfo],
+        bridge_state: &Pubkey,
+        user: &Pubkey,
+        fee_bps: u16,
+    ) -> Result<u16> {
+        Ok(Self::find(accounts, bridge_state, user)?
+            .map_or(fee_bps, |exemption| exemption.mint_fee_bps.min(fee_bps)))
+    }
+
+    /// Burn fee to charge `user`, given the bridge's own rate
+    pub fn burn_fee_bps(
+        accounts: &[AccountInfo],
+        bridge_state: &Pubkey,
+        user: &Pubkey,
+        fee_bps: u16,
+    ) -> Result<u16> {
+        Ok(Self::find(accounts, bridge_state, user)?
+            .map_or(fee_bps, |exemption| exemption.burn_fee_bps.min(fee_bps)))
+    }
+}
+
+/// A sanctioned pubkey: wZEC is not minted to it and it can't burn
+#[account]
+pub struct DenyListEntry {
+    pub user: Pubkey,
+    pub added_slot: u64,
+    pub bump: u8,
+}
+
+impl DenyListEntry {
+    pub const LEN: usize = 32 + 8 + 1;
+
+    /// Fail if the entry PDA at `info` has been created
+    pub fn check(info: &AccountInfo) -> Result<()> {
+        require!(info.data_is_empty(), BridgeError::AddressDenied);
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ZcashNetwork {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl ZcashNetwork {
+    /// Suffix appended to every guardian attestation domain, so a signature made
+    /// for one network's bridge can't be replayed against another's
+    pub fn domain_suffix(&self) -> &'static [u8] {
+        match self {
+            ZcashNetwork::Mainnet => b"-mainnet",
+            ZcashNetwork::Testnet => b"-testnet",
+            ZcashNetwork::Regtest => b"-regtest",
+        }
+    }
+
+    /// Light client confirmation depth used when none is given
+    pub fn default_min_confirmations(&self) -> u32 {
+        match self {
+            ZcashNetwork::Mainnet => 24,
+            ZcashNetwork::Testnet => 10,
+            ZcashNetwork::Regtest => 1,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WithdrawalStatus {
+    Pending,
+    Completed,
+    Cancelled,
+    Expired,
+    Vetoed,
+}
+
+/// A burn awaiting its ZEC payout, seeded by its withdrawal nonce
+#[account]
+pub struct WithdrawalRequest {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub net_amount: u64,  // ZEC owed to `zec_address`, in zatoshis
+    pub zec_address: String,
+    pub status: WithdrawalStatus,
+    pub created_slot: u64,
+    pub payout_txid: [u8; 32],  // Zcash payout txid, set once Completed
+    pub payout_height: u32,  // Zcash block height of the payout
+    pub unlock_slot: u64,  // Earliest slot at which the payout may be finalized
+    pub vetoed_by: Pubkey,  // Guardian that froze the request, if Vetoed
+    pub veto_reason: u8,
+    pub bump: u8,
+    pub memo: Vec<u8>,  // Memo for the shielded payout, empty when none
+    pub payer: Pubkey,  // Paid the request's rent, refunded when it is closed
+}
+
+impl WithdrawalRequest {
+    pub const MAX_ZEC_ADDRESS_LEN: usize = 256;
+    /// Size of a Zcash shielded note memo
+    pub const MAX_MEMO_LEN: usize = 512;
+    /// Size with an empty address and memo
+    pub const BASE_LEN: usize = 32 + 8 + 8 + 8 + 8 + 4 + 1 + 8 + 32 + 4 + 8 + 32 + 1 + 1 + 4 + 32;
+
+    /// Size of a request holding `zec_address` and `memo`, so burns without a memo
+    /// don't pay rent for one
+    pub fn space(zec_address: &str, memo: Option<&Vec<u8>>) -> usize {
+        Self::BASE_LEN + zec_address.len() + memo.map_or(0, Vec::len)
+    }
+
+    /// Who gets the rent back when the request is closed. Pre-memo requests that
+    /// still decode read a zero payer from their padding; their user paid.
+    pub fn rent_payer(&self) -> Pubkey {
+        if self.payer == Pubkey::default() {
+            self.user
+        } else {
+            self.payer
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ClosedAccountKind {
+    DepositReceipt,
+    WithdrawalRequest,
+}
+
+/// Rolling hash commitment to every receipt and withdrawal request whose rent
+/// was reclaimed, so their contents can still be proven against the chain
+#[account]
+pub struct ClosedAccountLog {
+    pub head: [u8; 32],  // Hash of the previous head and the latest commitment
+    pub closed_count: u64,
+    pub bump: u8,
+}
+
+impl ClosedAccountLog {
+    pub const LEN: usize = 32 + 8 + 1;
+
+    /// Fold a closing account's key and data into the log, returning its
+    /// commitment, the new head and the number of accounts closed so far
+    pub fn record(&mut self, info: &AccountInfo, bump: u8) -> Result<([u8; 32], [u8; 32], u64)> {
+        let commitment = hashv(&[info.key.as_ref(), &info.try_borrow_data()?]).to_bytes();
+        self.head = hashv(&[&self.head, &commitment]).to_bytes();
+        self.closed_count = self
+            .closed_count
+            .checked_add(1)
+            .ok_or(BridgeError::Overflow)?;
+        self.bump = bump;
+        Ok((commitment, self.head, self.closed_count))
+    }
+}
+
+/// Guardians whose quorum attests to Zcash deposits before wZEC is minted
+#[account]
+pub struct GuardianSet {
+    pub index: u32,  // Incremented on every rotation
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub bump: u8,
+}
+
+impl GuardianSet {
+    pub const MAX_GUARDIANS: usize = 19;
+    pub const LEN: usize = 4 + (4 + 32 * Self::MAX_GUARDIANS) + 1 + 1;
+
+    /// Slots an outgoing set keeps attesting after a rotation (~1 day)
+    pub const GRACE_SLOTS: u64 = 216_000;
+
+    /// Domain separator for the outgoing quorum's approval of a rotation
+    pub const ROTATION_DOMAIN: &'static [u8] = b"solz-guardian-rotation";
+
+    /// Message the set at `index` signs to hand over to `guardians`
+    pub fn rotation_message(network: ZcashNetwork, index: u32, guardians: &[Pubkey], threshold: u8) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::ROTATION_DOMAIN.len() + 8 + 4 + 32 * guardians.len() + 1);
+        message.extend_from_slice(Self::ROTATION_DOMAIN);
+        message.extend_from_slice(network.domain_suffix());
+        message.extend_from_slice(&index.to_le_bytes());
+        for guardian in guardians {
+            message.extend_from_slice(guardian.as_ref());
+        }
+        message.push(threshold);
+        message
+    }
+
+    /// Check membership size, uniqueness and threshold bounds
+    pub fn validate(guardians: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !guardians.is_empty() && guardians.len() <= Self::MAX_GUARDIANS,
+            BridgeError::InvalidGuardianSet
+        );
+
+        for (i, guardian) in guardians.iter().enumerate() {
+            require!(
+                !guardians[..i].contains(guardian),
+                BridgeError::InvalidGuardianSet
+            );
+        }
+
+        require!(
+            threshold > 0 && threshold as usize <= guardians.len(),
+            BridgeError::InvalidGuardianThreshold
+        );
+
+        Ok(())
+    }
+
+    /// Replace the members and threshold, bumping the set index
+    pub fn rotate(&mut self, guardians: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        Self::validate(&guardians, threshold)?;
+
+        self.index = self.index.checked_add(1).ok_or(BridgeError::Overflow)?;
+        self.guardians = guardians;
+        self.threshold = threshold;
+
+        emit!(GuardianSetUpdatedEvent {
+            index: self.index,
+            guardians: self.guardians.clone(),
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Count distinct guardians that signed among the given accounts or
+    /// attested through a verified ed25519 signature
+    pub fn count_signers(&self, accounts: &[AccountInfo], attested: &[Pubkey]) -> usize {
+        count_guardian_signers(&self.guardians, accounts, attested)
+    }
+
+    /// Whether a quorum of this set, or of an archived set still in its grace
+    /// period passed among `accounts`, attested
+    pub fn has_quorum(&self, bridge_state: &Pubkey, accounts: &[AccountInfo], attested: &[Pubkey]) -> Result<bool> {
+        if self.count_signers(accounts, attested) >= self.threshold as usize {
+            return Ok(true);
+        }
+
+        let slot = Clock::get()?.slot;
+        for account in accounts.iter().filter(|account| account.owner == &crate::ID) {
+            let data = account.try_borrow_data()?;
+            let Ok(archived) = ArchivedGuardianSet::try_deserialize(&mut &data[..]) else {
+                continue;
+            };
+            let expected = Pubkey::create_program_address(
+                &[
+                    b"guardian_set",
+                    bridge_state.as_ref(),
+                    &archived.index.to_le_bytes(),
+                    &[archived.bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| BridgeError::InvalidGuardianSet)?;
+            if expected != *account.key || slot >= archived.expires_slot {
+                continue;
+            }
+
+            if count_guardian_signers(&archived.guardians, accounts, attested) >= archived.threshold as usize {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// A guardian set replaced by a rotation, kept so in-flight attestations still verify
+#[account]
+pub struct ArchivedGuardianSet {
+    pub index: u32,
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub expires_slot: u64,  // Attestations from this set are rejected from this slot on
+    pub bump: u8,
+}
+
+impl ArchivedGuardianSet {
+    pub const LEN: usize = 4 + (4 + 32 * GuardianSet::MAX_GUARDIANS) + 1 + 8 + 1;
+}
+
+fn count_guardian_signers(guardians: &[Pubkey], accounts: &[AccountInfo], attested: &[Pubkey]) -> usize {
+    guardians
+        .iter()
+        .filter(|guardian| {
+            attested.contains(guardian)
+                || accounts
+                    .iter()
+                    .any(|account| account.is_signer && account.key == *guardian)
+        })
+        .count()
+}
+
+/// Bonding requirements for relayers allowed to submit attestations and finalizations
+#[account]
+pub struct RelayerRegistry {
+    pub min_stake: u64,  // Lamports a relayer must keep bonded
+    pub unbonding_slots: u64,
+    pub total_staked: u64,
+    pub slashed_lamports: u64,  // Confiscated stake, held in this account
+    pub reward_share_bps: u16,  // Share of a withdrawal's burn fee paid to its finalizing relayer
+    pub bump: u8,
+}
+
+impl RelayerRegistry {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 2 + 1;
+}
+
+/// A relayer's bonded SOL, held as lamports in this PDA on top of its rent
+#[account]
+pub struct RelayerStake {
+    pub relayer: Pubkey,
+    pub staked: u64,
+    pub unbonding: u64,
+    pub unbonding_slot: u64,  // Slot from which `unbonding` can be withdrawn
+    pub slashed: u64,
+    pub finalized_withdrawals: u64,
+    pub pending_rewards: u64,  // Earned wZEC not yet claimed
+    pub bump: u8,
+}
+
+impl RelayerStake {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    /// Whether the signer's stake, if passed, meets the registry minimum
+    pub fn is_bonded(
+        registry: &Option<Account<RelayerRegistry>>,
+        stake: &Option<Account<RelayerStake>>,
+    ) -> bool {
+        match (registry, stake) {
+            (Some(registry), Some(stake)) => stake.staked >= registry.min_stake,
+            _ => false,
+        }
+    }
+}
+
+/// Guardian-attested snapshot of the Zcash reserves, kept in a ring by sequence
+#[account]
+pub struct ReserveAttestation {
+    pub sequence: u64,
+    pub zcash_height: u32,  // Zcash block height the snapshot was taken at
+    pub reserve_zatoshis: u64,  // ZEC held by the bridge
+    pub circulating_wzec: u64,  // wZEC outstanding as counted by the guardians
+    pub mint_supply: u64,  // wZEC supply when the snapshot was posted
+    pub reserve_root: [u8; 32],  // Merkle root over the reserve's notes and UTXOs
+    pub posted_slot: u64,
+    pub bump: u8,
+}
+
+impl ReserveAttestation {
+    pub const LEN: usize = 8 + 4 + 8 + 8 + 8 + 32 + 8 + 1;
+
+    /// Number of ring slots; the newest snapshot overwrites the one this many before it
+    pub const RING_SIZE: u64 = 32;
+
+    /// Domain separator for guardian attestations over reserve snapshots
+    pub const ATTESTATION_DOMAIN: &'static [u8] = b"solz-reserve-attestation";
+
+    /// Message guardians sign to attest reserve snapshot number `sequence`. The
+    /// bridge state key keeps a snapshot of one bridge's reserves from being posted
+    /// for another.
+    pub fn attestation_message(
+        network: ZcashNetwork,
+        bridge_state: &Pubkey,
+        sequence: u64,
+        zcash_height: u32,
+        reserve_zatoshis: u64,
+        circulating_wzec: u64,
+        reserve_root: &[u8; 32],
+    ) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::ATTESTATION_DOMAIN.len() + 8 + 32 + 8 + 4 + 8 + 8 + 32);
+        message.extend_from_slice(Self::ATTESTATION_DOMAIN);
+        message.extend_from_slice(network.domain_suffix());
+        message.extend_from_slice(bridge_state.as_ref());
+        message.extend_from_slice(&sequence.to_le_bytes());
+        message.extend_from_slice(&zcash_height.to_le_bytes());
+        message.extend_from_slice(&reserve_zatoshis.to_le_bytes());
+        message.extend_from_slice(&circulating_wzec.to_le_bytes());
+        message.extend_from_slice(reserve_root);
+        message
+    }
+}
+
+/// What a relayer has submitted, so stalled or misbehaving relayers show up on-chain
+#[account]
+pub struct RelayerStats {
+    pub relayer: Pubkey,
+    pub mints_submitted: u64,
+    pub minted_volume: u64,  // Deposit amounts minted, before fees
+    pub withdrawals_finalized: u64,
+    pub finalized_volume: u64,  // Net ZEC paid out on finalized withdrawals
+    pub last_activity_slot: u64,
+    pub bump: u8,
+}
+
+impl RelayerStats {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    /// Bonded relayers must pass their counters so their work is always recorded
+    pub fn require_with_stake(
+        stake: &Option<Account<RelayerStake>>,
+        stats: &Option<Account<RelayerStats>>,
+    ) -> Result<()> {
+        require!(
+            stake.is_none() || stats.is_some(),
+            BridgeError::RelayerStatsRequired
+        );
+        Ok(())
+    }
+
+    pub fn record_mint(&mut self, amount: u64, slot: u64) -> Result<()> {
+        self.mints_submitted = self
+            .mints_submitted
+            .checked_add(1)
+            .ok_or(BridgeError::Overflow)?;
+        self.minted_volume = self
+            .minted_volume
+            .checked_add(amount)
+            .ok_or(BridgeError::Overflow)?;
+        self.last_activity_slot = slot;
+        Ok(())
+    }
+
+    pub fn record_finalize(&mut self, net_amount: u64, slot: u64) -> Result<()> {
+        self.withdrawals_finalized = self
+            .withdrawals_finalized
+            .checked_add(1)
+            .ok_or(BridgeError::Overflow)?;
+        self.finalized_volume = self
+            .finalized_volume
+            .checked_add(net_amount)
+            .ok_or(BridgeError::Overflow)?;
+        self.last_activity_slot = slot;
+        Ok(())
+    }
+}
+
+/// Next burn permit nonce of a token owner
+#[account]
+pub struct PermitNonce {
+    pub owner: Pubkey,
+    pub next_nonce: u64,  // Permits must be used in nonce order
+    pub relayer_fees_paid: u64,  // wZEC paid to relayers for submitting this owner's permits
+    pub bump: u8,
+}
+
+impl PermitNonce {
+    pub const LEN: usize = 32 + 8 + 8 + 1;
+
+    /// Domain separator for burn permits signed by token owners
+    pub const DOMAIN: &'static [u8] = b"solz-burn-permit";
+
+    /// Message an owner signs to let `relayer` burn `amount` of their wZEC to
+    /// `zec_address` for `relayer_fee` until `expiry`. The bridge state key keeps a
+    /// permit for one asset's bridge from being used on another's.
+    #[allow(clippy::too_many_arguments)]
+    pub fn message(
+        network: ZcashNetwork,
+        bridge_state: &Pubkey,
+        owner: &Pubkey,
+        relayer: &Pubkey,
+        amount: u64,
+        relayer_fee: u64,
+        nonce: u64,
+        expiry: i64,
+        zec_address: &str,
+    ) -> Vec<u8> {
+        let mut message = Vec::with_capacity(
+            Self::DOMAIN.len() + 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + zec_address.len(),
+        );
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(network.domain_suffix());
+        message.extend_from_slice(bridge_state.as_ref());
+        message.extend_from_slice(owner.as_ref());
+        message.extend_from_slice(relayer.as_ref());
+        message.extend_from_slice(&amount.to_le_bytes());
+        message.extend_from_slice(&relayer_fee.to_le_bytes());
+        message.extend_from_slice(&nonce.to_le_bytes());
+        message.extend_from_slice(&expiry.to_le_bytes());
+        message.extend_from_slice(zec_address.as_bytes());
+        message
+    }
+}
+
+/// A staker's shares of the insurance vault
+#[account]
+pub struct InsuranceStake {
+    pub staker: Pubkey,
+    pub shares: u64,
+    pub unstaking_shares: u64,  // Still exposed to fees and losses until claimed
+    pub unlock_slot: u64,  // Slot from which `unstaking_shares` can be claimed
+    pub bump: u8,
+}
+
+impl InsuranceStake {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 1;
+}
+
+/// Record of a processed Zcash deposit, seeded by its txid
+#[account]
+pub struct DepositReceipt {
+    pub amount: u64,
+    pub recipient: Pubkey,
+    pub slot: u64,
+    pub bump: u8,
+    pub payer: Pubkey,  // Paid the receipt's rent, refunded when it is closed
+}
+
+impl DepositReceipt {
+    pub const LEN: usize = 8 + 32 + 8 + 1 + 32;
+
+    /// Address of the receipt for `zcash_txid` under the seeds used before receipts were
+    /// keyed by the raw txid: the SHA-256 of its display-order hex string, without the
+    /// state key for bridges moved off the singleton PDAs
+    pub fn legacy_address(bridge_state: &Pubkey, singleton_receipts: bool, zcash_txid: &[u8; 32]) -> Pubkey {
+        let txid_hash = hash(zcash_spv::txid_hex(zcash_txid).as_bytes()).to_bytes();
+        if singleton_receipts {
+            Pubkey::find_program_address(&[b"deposit", &txid_hash], &crate::ID).0
+        } else {
+            Pubkey::find_program_address(&[b"deposit", bridge_state.as_ref(), &txid_hash], &crate::ID).0
+        }
+    }
+
+    /// Domain separator for off-chain guardian deposit attestations
+    pub const ATTESTATION_DOMAIN: &'static [u8] = b"solz-deposit-attestation";
+
+    /// Message guardians sign to attest that `zcash_txid` paid `amount` to `recipient`
+    pub fn attestation_message(
+        network: ZcashNetwork,
+        recipient: &Pubkey,
+        amount: u64,
+        zcash_txid: &[u8; 32],
+    ) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::ATTESTATION_DOMAIN.len() + 8 + 32 + 8 + 32);
+        message.extend_from_slice(Self::ATTESTATION_DOMAIN);
+        message.extend_from_slice(network.domain_suffix());
+        message.extend_from_slice(recipient.as_ref());
+        message.extend_from_slice(&amount.to_le_bytes());
+        message.extend_from_slice(zcash_txid);
+        message
+    }
+
+    /// Domain separator for guardian attestations over a whole batch of deposits
+    pub const BATCH_ATTESTATION_DOMAIN: &'static [u8] = b"solz-deposit-batch-attestation";
+
+    /// Message guardians sign to attest every `(recipient, amount, zcash_txid)` in a batch, in order
+    pub fn batch_attestation_message(network: ZcashNetwork, deposits: &[(Pubkey, u64, [u8; 32])]) -> Vec<u8> {
+        let mut message = Vec::with_capacity(
+            Self::BATCH_ATTESTATION_DOMAIN.len() + 8 + deposits.len() * (32 + 8 + 32),
+        );
+        message.extend_from_slice(Self::BATCH_ATTESTATION_DOMAIN);
+        message.extend_from_slice(network.domain_suffix());
+        for (recipient, amount, zcash_txid) in deposits {
+            message.extend_from_slice(recipient.as_ref());
+            message.extend_from_slice(&amount.to_le_bytes());
+            message.extend_from_slice(zcash_txid);
+        }
+        message
+    }
+}
+
+/// One deposit in a `mint_wzec_batch` call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchDeposit {
+    pub amount: u64,
+    pub zcash_txid: [u8; 32],
+}
+
+/// Merkle root of confirmed deposits that recipients claim individually
+#[account]
+pub struct MintRoot {
+    pub root: [u8; 32],
+    pub total_amount: u64,    // Sum of every leaf amount under the root
+    pub claimed_amount: u64,
+    pub posted_slot: u64,
+    pub bump: u8,
+}
+
+impl MintRoot {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 1;
+
+    /// Domain separator for guardian attestations over a mint root
+    pub const ATTESTATION_DOMAIN: &'static [u8] = b"solz-mint-root-attestation";
+
+    /// Message guardians sign to attest a root covering `total_amount` of deposits.
+    /// The bridge state key keeps a root attested for one bridge from being posted
+    /// on another sharing its guardians.
+    pub fn attestation_message(
+        network: ZcashNetwork,
+        bridge_state: &Pubkey,
+        root: &[u8; 32],
+        total_amount: u64,
+    ) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::ATTESTATION_DOMAIN.len() + 8 + 32 + 32 + 8);
+        message.extend_from_slice(Self::ATTESTATION_DOMAIN);
+        message.extend_from_slice(network.domain_suffix());
+        message.extend_from_slice(bridge_state.as_ref());
+        message.extend_from_slice(root);
+        message.extend_from_slice(&total_amount.to_le_bytes());
+        message
+    }
+
+    /// Leaf for a deposit of `amount` to the wallet `recipient`
+    ///
+    /// Leaves and inner nodes carry distinct prefixes so an inner node can't be
+    /// presented as a leaf.
+    pub fn leaf(recipient: &Pubkey, amount: u64, zcash_txid: &[u8; 32]) -> [u8; 32] {
+        hashv(&[
+            &[0u8],
+            recipient.as_ref(),
+            &amount.to_le_bytes(),
+            zcash_txid,
+        ])
+        .to_bytes()
+    }
+
+    /// Check `proof` links `leaf` to the root, hashing each pair in sorted order
+    pub fn verify(&self, proof: &[[u8; 32]], leaf: [u8; 32]) -> bool {
+        let computed = proof.iter().fold(leaf, |node, sibling| {
+            let (left, right) = if node <= *sibling {
+                (node, *sibling)
+            } else {
+                (*sibling, node)
+            };
+            hashv(&[&[1u8], &left, &right]).to_bytes()
+        });
+        computed == self.root
+    }
+}
+
+#[event]
+pub struct BridgeInitializedEvent {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub mint_fee_bps: u16,
+    pub burn_fee_bps: u16,
+}
+
+#[event]
+pub struct MetadataCreatedEvent {
+    pub mint: Pubkey,
+    pub metadata: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+#[event]
+pub struct MintEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub amount_after_fee: u64,
+    pub zcash_txid: [u8; 32],
+    pub total_minted: u64,
+}
+
+#[event]
+pub struct ReserveAttestationPostedEvent {
+    pub sequence: u64,
+    pub zcash_height: u32,
+    pub reserve_zatoshis: u64,
+    pub circulating_wzec: u64,
+    pub mint_supply: u64,
+    pub reserve_root: [u8; 32],
+}
+
+#[event]
+pub struct MintRootPostedEvent {
+    pub root: [u8; 32],
+    pub total_amount: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BurnEvent {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub amount_after_fee: u64,
+    pub zec_address: String,
+    pub memo: Vec<u8>,
+    pub unlock_slot: u64,
+    pub total_burned: u64,
+}
+
+#[event]
+pub struct WithdrawalFinalizedEvent {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub net_amount: u64,
+    pub payout_txid: [u8; 32],
+    pub payout_height: u32,
+    pub payout_output: Option<u16>,  // Output of the withdrawal in a batched payout
+}
+
+#[event]
+pub struct WithdrawalVetoedEvent {
+    pub nonce: u64,
+    pub guardian: Pubkey,
+    pub reason_code: u8,
+}
+
+#[event]
+pub struct WithdrawalUnvetoedEvent {
+    pub nonce: u64,
+    pub signatures: u8,
+}
+
+#[event]
+pub struct WithdrawalCancelledEvent {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub refund_amount: u64,
+    pub cancellation_fee: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum InsuranceClaimReason {
+    RelayerFailure,
+    ReserveShortfall,
+}
+
+#[event]
+pub struct InsuranceFundInitializedEvent {
+    pub insurance_vault: Pubkey,
+}
+
+#[event]
+pub struct InsuranceShareUpdatedEvent {
+    pub old_share_bps: u16,
+    pub new_share_bps: u16,
+}
+
+#[event]
+pub struct InsuranceClaimPaidEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub reason: InsuranceClaimReason,
+}
+
+#[event]
+pub struct BurnPermitUsedEvent {
+    pub owner: Pubkey,
+    pub relayer: Pubkey,
+    pub relayer_fee: u64,
+    pub permit_nonce: u64,
+    pub withdrawal_nonce: u64,
+}
+
+#[event]
+pub struct PermitsCancelledEvent {
+    pub owner: Pubkey,
+    pub next_nonce: u64,
 }
 
-#[derive(Accounts)]
-pub struct BurnWZEC<'info> {
-    #[account(
-        mut,
-        seeds = [b"bridge_state"],
-        bump
-    )]
-    pub bridge_state: Account<'info, BridgeState>,
-    
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
-    
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct InsuranceUnstakeSlotsUpdatedEvent {
+    pub insurance_unstake_slots: u64,
 }
 
-#[derive(Accounts)]
-pub struct UpdateAuthority<'info> {
-    #[account(
-        mut,
-        seeds = [b"bridge_state"],
-        bump
-    )]
-    pub bridge_state: Account<'info, BridgeState>,
-    
-    pub authority: Signer<'info>,
+#[event]
+pub struct RetentionSlotsUpdatedEvent {
+    pub retention_slots: u64,
 }
 
-#[derive(Accounts)]
-pub struct PauseBridge<'info> {
-    #[account(
-        mut,
-        seeds = [b"bridge_state"],
-        bump
-    )]
-    pub bridge_state: Account<'info, BridgeState>,
-    
-    pub authority: Signer<'info>,
+#[event]
+pub struct FinalizeWindowUpdatedEvent {
+    pub finalize_window: u8,
+    pub settled_floor: u64,
 }
 
-#[derive(Accounts)]
-pub struct ResumeBridge<'info> {
-    #[account(
-        mut,
-        seeds = [b"bridge_state"],
-        bump
-    )]
-    pub bridge_state: Account<'info, BridgeState>,
-    
-    pub authority: Signer<'info>,
+#[event]
+pub struct AccountClosedEvent {
+    pub account: Pubkey,
+    pub kind: ClosedAccountKind,
+    pub payer: Pubkey,
+    pub refunded: u64,
+    pub commitment: [u8; 32],  // Hash of the account's key and data
+    pub log_head: [u8; 32],
+    pub closed_count: u64,
 }
 
-#[account]
-pub struct BridgeState {
+#[event]
+pub struct InsuranceStakedEvent {
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+}
+
+#[event]
+pub struct InsuranceUnstakedEvent {
+    pub staker: Pubkey,
+    pub shares: u64,
+    pub unlock_slot: u64,
+}
+
+#[event]
+pub struct InsuranceStakeClaimedEvent {
+    pub staker: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FeeKind {
+    Mint,
+    Burn,
+    Cancellation,
+}
+
+#[event]
+pub struct FeeUpdatedEvent {
+    pub kind: FeeKind,
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+}
+
+#[event]
+pub struct TransactionLimitsUpdatedEvent {
+    pub max_mint_amount: u64,
+    pub max_burn_amount: u64,
+}
+
+#[event]
+pub struct MinBurnAmountUpdatedEvent {
+    pub old_min_burn_amount: u64,
+    pub new_min_burn_amount: u64,
+}
+
+#[event]
+pub struct UsdLimitsUpdatedEvent {
+    pub price_feed_id: [u8; 32],
+    pub max_price_age_secs: u32,
+    pub kyc_threshold_usd: u64,
+    pub large_withdrawal_threshold_usd: u64,
+}
+
+#[event]
+pub struct ComplianceUpdatedEvent {
+    pub kyc_threshold: u64,
+    pub compliance_attester: Pubkey,
+}
+
+#[event]
+pub struct WithdrawalTimelockUpdatedEvent {
+    pub large_withdrawal_threshold: u64,
+    pub large_withdrawal_delay_slots: u64,
+}
+
+#[event]
+pub struct TimelockSlotsUpdatedEvent {
+    pub old_timelock_slots: u64,
+    pub new_timelock_slots: u64,
+}
+
+#[event]
+pub struct ChangeQueuedEvent {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub change: ParameterChange,
+    pub executable_slot: u64,
+}
+
+#[event]
+pub struct ChangeExecutedEvent {
+    pub id: u64,
+}
+
+#[event]
+pub struct ChangeCancelledEvent {
+    pub id: u64,
+    pub cancelled_by: Pubkey,
+}
+
+#[event]
+pub struct CircuitBreakerUpdatedEvent {
+    pub threshold: u64,
+    pub window_hours: u8,
+}
+
+#[event]
+pub struct CircuitBreakerTrippedEvent {
+    pub window_volume: u64,
+    pub threshold: u64,
+    pub window_hours: u8,
+}
+
+#[event]
+pub struct CircuitBreakerResetEvent {
+    pub reset_by: Pubkey,
+}
+
+#[event]
+pub struct DailyMintLimitUpdatedEvent {
+    pub daily_mint_limit: u64,
+}
+
+#[event]
+pub struct UserLimitsUpdatedEvent {
+    pub user: Pubkey,
+    pub mint_limit: u64,
+    pub burn_limit: u64,
+}
+
+#[event]
+pub struct UserLimitsRemovedEvent {
+    pub user: Pubkey,
+}
+
+#[event]
+pub struct FeeExemptionUpdatedEvent {
+    pub user: Pubkey,
+    pub mint_fee_bps: u16,
+    pub burn_fee_bps: u16,
+}
+
+#[event]
+pub struct FeeExemptionRemovedEvent {
+    pub user: Pubkey,
+}
+
+#[event]
+pub struct DenyListEntryAddedEvent {
+    pub user: Pubkey,
     pub authority: Pubkey,
-    pub mint: Pubkey,
-    pub fee_percentage: u16,  // Basis points (10 = 0.1%)
-    pub paused: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DenyListEntryRemovedEvent {
+    pub user: Pubkey,
+    pub authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct TreasuryUpdatedEvent {
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+}
+
+#[event]
+pub struct RelayerRegistryUpdatedEvent {
+    pub min_stake: u64,
+    pub unbonding_slots: u64,
+}
+
+#[event]
+pub struct RelayerRewardShareUpdatedEvent {
+    pub old_share_bps: u16,
+    pub new_share_bps: u16,
+}
+
+#[event]
+pub struct RelayerRewardsClaimedEvent {
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub finalized_withdrawals: u64,
+}
+
+#[event]
+pub struct RelayerStakedEvent {
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub staked: u64,
+}
+
+#[event]
+pub struct RelayerUnstakedEvent {
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub unbonding_slot: u64,
+}
+
+#[event]
+pub struct RelayerStakeWithdrawnEvent {
+    pub relayer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RelayerSlashedEvent {
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub evidence: [u8; 32],
+    pub signatures: u8,
+}
+
+#[event]
+pub struct SlashedLamportsSweptEvent {
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VerifyingKeySetEvent {
+    pub authority: Pubkey,
+    pub verifying_key: Pubkey,
+}
+
+#[event]
+pub struct DepositAddressUpdatedEvent {
+    pub old_pubkey_hash: [u8; 20],
+    pub new_pubkey_hash: [u8; 20],
+}
+
+#[event]
+pub struct FeesWithdrawnEvent {
+    pub treasury: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct GuardianSetUpdatedEvent {
+    pub index: u32,
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct GuardianSetArchivedEvent {
+    pub index: u32,
+    pub expires_slot: u64,
+}
+
+#[event]
+pub struct AuthorityProposedEvent {
+    pub current_authority: Pubkey,
+    pub proposed_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityProposalCancelledEvent {
+    pub authority: Pubkey,
+    pub cancelled_authority: Pubkey,
+}
+
+#[event]
+pub struct RoleUpdatedEvent {
+    pub role: Role,
+    pub old_holder: Pubkey,
+    pub new_holder: Pubkey,
+}
+
+#[event]
+pub struct AuthorityChangedEvent {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct PauseEvent {
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct ResumeEvent {
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct HeartbeatEvent {
+    pub slot: u64,
+}
+
+#[event]
+pub struct HeartbeatGapUpdatedEvent {
+    pub max_heartbeat_gap: u64,
+}
+
+#[event]
+pub struct DeadMansSwitchTriggeredEvent {
+    pub caller: Pubkey,
+    pub last_heartbeat_slot: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PauseFlagsUpdatedEvent {
+    pub authority: Pubkey,
+    pub mints_paused: bool,
+    pub burns_paused: bool,
+}
+
+#[event]
+pub struct GuardianPauseEvent {
+    pub guardian: Pubkey,
+}
+
+#[event]
+pub struct GuardianResumeEvent {
+    pub paused_by: Pubkey,
+    pub resumed_by: Pubkey,
+}
+
+#[event]
+pub struct StateMigratedEvent {
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct BridgeStatsInitializedEvent {
+    pub bridge_state: Pubkey,
     pub total_minted: u64,
     pub total_burned: u64,
     pub fee_collected: u64,
 }
 
-impl BridgeState {
-    pub const LEN: usize = 32 + 32 + 2 + 1 + 8 + 8 + 8;
+#[event]
+pub struct LegacyBridgeMigratedEvent {
+    pub legacy_bridge_state: Pubkey,
+    pub bridge_state: Pubkey,
+    pub from_version: u8,
+    pub swept: u64,
+}
+
+#[event]
+pub struct WithdrawalRequestMigratedEvent {
+    pub withdrawal_request: Pubkey,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct BridgeDecommissionedEvent {
+    pub authority: Pubkey,
+    pub slot: u64,
+    pub grace_period_slots: u64,
+}
+
+#[event]
+pub struct BridgeClosedEvent {
+    pub authority: Pubkey,
+    pub swept: u64,
 }
 
 #[error_code]
@@ -309,5 +8102,272 @@ pub enum BridgeError {
     
     #[msg("Arithmetic overflow")]
     Overflow,
+    
+    #[msg("Deposit has already been processed")]
+    DepositAlreadyProcessed,
+    
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+    
+    #[msg("Guardian set must contain between 1 and 19 unique guardians")]
+    InvalidGuardianSet,
+    
+    #[msg("Guardian threshold must be between 1 and the number of guardians")]
+    InvalidGuardianThreshold,
+    
+    #[msg("Not enough guardian signatures to reach quorum")]
+    InsufficientGuardianSignatures,
+    
+    #[msg("Malformed ed25519 signature verification instruction")]
+    InvalidSignatureInstruction,
+    
+    #[msg("Treasury account has not been configured")]
+    TreasuryNotConfigured,
+    
+    #[msg("Treasury token account does not match the configured treasury")]
+    InvalidTreasury,
+    
+    #[msg("Fee exceeds the maximum allowed basis points")]
+    FeeTooHigh,
+    
+    #[msg("Mint amount exceeds the per-transaction limit")]
+    MintAmountExceedsLimit,
+    
+    #[msg("Burn amount exceeds the per-transaction limit")]
+    BurnAmountExceedsLimit,
+    
+    #[msg("Rolling 24-hour volume limit exceeded; retry after the window rolls")]
+    RateLimitExceeded,
+    
+    #[msg("Withdrawal request is not pending")]
+    WithdrawalNotPending,
+    
+    #[msg("Token account does not belong to the expected owner")]
+    InvalidTokenAccount,
+    
+    #[msg("Withdrawal is still within its timelock delay")]
+    WithdrawalTimelocked,
+    
+    #[msg("Signer is not a member of the guardian set")]
+    NotAGuardian,
+    
+    #[msg("Withdrawal can only be vetoed during its timelock window")]
+    VetoWindowClosed,
+    
+    #[msg("Withdrawal request is not vetoed")]
+    WithdrawalNotVetoed,
+    
+    #[msg("Circuit breaker window must be between 1 and 24 hours")]
+    InvalidCircuitBreakerWindow,
+    
+    #[msg("Circuit breaker has not been tripped")]
+    CircuitBreakerNotTripped,
+    
+    #[msg("Authority heartbeat has not expired")]
+    HeartbeatNotExpired,
+    
+    #[msg("Mint carries a Token-2022 extension the bridge does not support")]
+    UnsupportedMintExtension,
+    
+    #[msg("Mint authority must be the bridge mint authority PDA")]
+    InvalidMintAuthority,
+    
+    #[msg("Mint must have 8 decimals")]
+    InvalidMintDecimals,
+    
+    #[msg("Mint must have zero supply")]
+    MintSupplyNotZero,
+    
+    #[msg("Mint freeze authority must be unset or the bridge mint authority PDA")]
+    InvalidFreezeAuthority,
+    
+    #[msg("Mint does not match the bridge mint")]
+    InvalidMint,
+    
+    #[msg("Token account is not for the bridge mint")]
+    InvalidTokenMint,
+    
+    #[msg("Bridge state is already at the current version")]
+    AlreadyMigrated,
+    
+    #[msg("Bridge has been decommissioned")]
+    BridgeDecommissioned,
+    
+    #[msg("Bridge has not been decommissioned")]
+    BridgeNotDecommissioned,
+    
+    #[msg("Decommission grace period has not ended")]
+    DecommissionGracePeriodActive,
+    
+    #[msg("Batch must contain between one and MAX_BATCH_MINTS deposits")]
+    InvalidBatchSize,
+    
+    #[msg("Remaining accounts do not match the batch")]
+    InvalidBatchAccounts,
+    
+    #[msg("Merkle proof does not match the mint root")]
+    InvalidMerkleProof,
+    
+    #[msg("Claims exceed the mint root's total amount")]
+    MintRootExhausted,
+    
+    #[msg("Malformed Zcash block header")]
+    InvalidZcashHeader,
+    
+    #[msg("Header does not extend the light client tip")]
+    HeaderDoesNotExtendTip,
+    
+    #[msg("Header difficulty does not match the expected target")]
+    InvalidDifficulty,
+    
+    #[msg("Invalid Equihash solution")]
+    InvalidEquihashSolution,
+    
+    #[msg("Header hash does not meet its target")]
+    InsufficientProofOfWork,
+    
+    #[msg("Header timestamp is out of range")]
+    InvalidHeaderTimestamp,
+    
+    #[msg("Header is not in the light client window")]
+    HeaderNotInWindow,
+    
+    #[msg("Invalid light client checkpoint")]
+    InvalidCheckpoint,
+    
+    #[msg("Malformed or unsupported Zcash transaction")]
+    InvalidZcashTransaction,
+    
+    #[msg("Deposit does not name a Solana recipient")]
+    MissingDepositRecipient,
+    
+    #[msg("Block has not reached the confirmation depth")]
+    HeaderNotConfirmed,
+    
+    #[msg("Zcash deposit address has not been set")]
+    DepositAddressNotSet,
+    
+    #[msg("Invalid verifying key")]
+    InvalidVerifyingKey,
+    
+    #[msg("Invalid zero-knowledge proof")]
+    InvalidProof,
+    
+    #[msg("Relayer stake is too small for this operation")]
+    InsufficientStake,
+    
+    #[msg("Relayer stake is still unbonding")]
+    StakeStillUnbonding,
+    
+    #[msg("Reward share exceeds 100%")]
+    InvalidRewardShare,
+    
+    #[msg("Memo exceeds the 512-byte Zcash memo size")]
+    MemoTooLong,
+    
+    #[msg("Burn amount is below the minimum withdrawal")]
+    BurnAmountBelowMinimum,
+    
+    #[msg("Relayer rewards are still unclaimed")]
+    RelayerRewardsOutstanding,
+    
+    #[msg("Slashed relayer stake has not been swept")]
+    SlashedLamportsUnswept,
+    
+    #[msg("Withdrawal request already uses the current layout")]
+    WithdrawalRequestCurrent,
+    
+    #[msg("Bridge fee exceeds the caller's maximum")]
+    FeeExceedsMaximum,
+    
+    #[msg("Address is on the bridge's deny list")]
+    AddressDenied,
+    
+    #[msg("A compliance attester is required to enforce a KYC threshold")]
+    InvalidComplianceAttester,
+    
+    #[msg("Transfer above the KYC threshold needs a compliance credential")]
+    ComplianceAttestationRequired,
+    
+    #[msg("Parameter changes must be queued while a timelock is set")]
+    ChangeTimelocked,
+    
+    #[msg("Queued change is still within its timelock")]
+    ChangeNotReady,
+    
+    #[msg("Guardian set changes need the guardian set account")]
+    GuardianSetRequired,
+    
+    #[msg("Insurance share exceeds 100%")]
+    InvalidInsuranceShare,
+    
+    #[msg("Insurance fund has not been initialized")]
+    InsuranceFundNotInitialized,
+    
+    #[msg("Insurance vault does not match the bridge's insurance fund")]
+    InvalidInsuranceVault,
+    
+    #[msg("Burn fees are shared with the insurance fund, whose vault is required")]
+    InsuranceVaultRequired,
+    
+    #[msg("Insurance fund is depleted and cannot take new stake")]
+    InsuranceFundDepleted,
+    
+    #[msg("Not enough staked insurance shares")]
+    InsufficientInsuranceShares,
+    
+    #[msg("Unstaked insurance shares are still cooling down")]
+    InsuranceStakeLocked,
+    
+    #[msg("Burn permit has expired")]
+    PermitExpired,
+    
+    #[msg("Burn permit nonce is not the owner's next nonce")]
+    InvalidPermitNonce,
+    
+    #[msg("Burn permit is not signed by the token owner")]
+    InvalidPermitSignature,
+    
+    #[msg("Account is still within the retention period")]
+    RetentionNotElapsed,
+    
+    #[msg("Withdrawal request is still pending or vetoed")]
+    WithdrawalNotSettled,
+    
+    #[msg("Rent refunds go to the account's original payer")]
+    InvalidRentPayer,
+    
+    #[msg("Finalize window must be at most 64 nonces, starting no later than the next withdrawal")]
+    InvalidFinalizeWindow,
+    
+    #[msg("Withdrawal is too far past the oldest unsettled withdrawal to settle yet")]
+    WithdrawalOutOfOrder,
+    
+    #[msg("Bonded relayers must pass their relayer stats account")]
+    RelayerStatsRequired,
+    
+    #[msg("USD limits need a Pyth price feed")]
+    InvalidPriceFeed,
+    
+    #[msg("USD limits are set, so a ZEC/USD price update must be passed")]
+    PriceUpdateRequired,
+    
+    #[msg("Price update is malformed, unverified or non-positive")]
+    InvalidPriceUpdate,
+    
+    #[msg("Price update is older than the bridge accepts")]
+    StalePrice,
+    
+    #[msg("A guardian has already paused mints")]
+    GuardianPauseActive,
+    
+    #[msg("Mints are not under a guardian pause")]
+    GuardianPauseNotActive,
+    
+    #[msg("Withdrawals are still pending or vetoed")]
+    WithdrawalsOutstanding,
+    
+    #[msg("Insurance shares or vault balance remain")]
+    InsuranceFundOutstanding,
 }
 
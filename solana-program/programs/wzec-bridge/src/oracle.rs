@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+
+use crate::BridgeError;
+
+/// Pyth Solana receiver program, which owns posted price updates
+pub const PYTH_RECEIVER_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Anchor discriminator of the receiver's `PriceUpdateV2` account
+const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// How many Wormhole guardian signatures the receiver checked
+#[derive(AnchorDeserialize)]
+enum VerificationLevel {
+    Partial { _num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorDeserialize)]
+struct PriceFeedMessage {
+    feed_id: [u8; 32],
+    price: i64,
+    _conf: u64,
+    exponent: i32,
+    publish_time: i64,
+    _prev_publish_time: i64,
+    _ema_price: i64,
+    _ema_conf: u64,
+}
+
+/// `PriceUpdateV2` as stored by the receiver, after its discriminator
+#[derive(AnchorDeserialize)]
+struct PriceUpdateV2 {
+    _write_authority: Pubkey,
+    verification_level: VerificationLevel,
+    price_message: PriceFeedMessage,
+    _posted_slot: u64,
+}
+
+/// ZEC/USD price: `price * 10^exponent` dollars per ZEC
+pub struct Price {
+    pub price: u64,
+    pub exponent: i32,
+}
+
+impl Price {
+    /// Zatoshis worth `usd_cents` at this price, rounded down and capped at u64::MAX
+    pub fn usd_cents_to_zatoshis(&self, usd_cents: u64) -> Result<u64> {
+        // cents / 100 dollars, at price * 10^exponent dollars per 10^8 zatoshis
+        let scaled = (usd_cents as u128)
+            .checked_mul(1_000_000)
+            .ok_or(BridgeError::Overflow)?;
+        let scale = 10u128
+            .checked_pow(self.exponent.unsigned_abs())
+            .ok_or(BridgeError::Overflow)?;
+        let zatoshis = if self.exponent <= 0 {
+            scaled.checked_mul(scale).ok_or(BridgeError::Overflow)? / self.price as u128
+        } else {
+            scaled / (self.price as u128).checked_mul(scale).ok_or(BridgeError::Overflow)?
+        };
+        Ok(u64::try_from(zatoshis).unwrap_or(u64::MAX))
+    }
+}
+
+/// Find the Pyth price update for `feed_id` among `accounts` and return its price
+///
+/// Only updates the receiver fully verified against the Wormhole guardian set are
+/// accepted, and only if published within `max_age_secs`.
+pub fn zec_usd_price(accounts: &[AccountInfo], feed_id: &[u8; 32], max_age_secs: u32) -> Result<Price> {
+    let now = Clock::get()?.unix_timestamp;
+    for account in accounts.iter().filter(|account| account.owner == &PYTH_RECEIVER_ID) {
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR,
+            BridgeError::InvalidPriceUpdate
+        );
+        let update = PriceUpdateV2::deserialize(&mut &data[8..])?;
+        let message = update.price_message;
+        if &message.feed_id != feed_id {
+            continue;
+        }
+
+        require!(
+            matches!(update.verification_level, VerificationLevel::Full) && message.price > 0,
+            BridgeError::InvalidPriceUpdate
+        );
+        require!(
+            now.saturating_sub(message.publish_time) <= max_age_secs as i64,
+            BridgeError::StalePrice
+        );
+        return Ok(Price {
+            price: message.price as u64,
+            exponent: message.exponent,
+        });
+    }
+    err!(BridgeError::PriceUpdateRequired)
+}
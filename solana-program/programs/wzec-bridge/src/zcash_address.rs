@@ -0,0 +1,284 @@
+use anchor_lang::solana_program::hash::hash;
+use blake2b_simd::Params;
+
+use crate::ZcashNetwork;
+
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+const BECH32_GENERATOR: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Bytes in a Sapling payment address: 11-byte diversifier and 32-byte pk_d
+const SAPLING_ADDRESS_LEN: usize = 43;
+/// Shortest unified address payload: one receiver plus the 16-byte padding
+const MIN_UNIFIED_ADDRESS_LEN: usize = 48;
+/// Longest input F4Jumble is defined for
+const MAX_UNIFIED_ADDRESS_LEN: usize = 4_194_368;
+/// Trailing bytes of a unified address payload: the HRP, zero-padded
+const UNIFIED_PADDING_LEN: usize = 16;
+/// Transparent address payload: 2-byte prefix and 20-byte hash
+const TRANSPARENT_ADDRESS_LEN: usize = 22;
+
+/// Unified address receiver typecodes (ZIP 316)
+const TYPECODE_P2PKH: u64 = 0x00;
+const TYPECODE_P2SH: u64 = 0x01;
+const TYPECODE_SAPLING: u64 = 0x02;
+const TYPECODE_ORCHARD: u64 = 0x03;
+
+/// Address encodings a network accepts
+struct Prefixes {
+    sapling_hrp: &'static str,
+    unified_hrp: &'static str,
+    p2pkh: [u8; 2],
+    p2sh: [u8; 2],
+}
+
+impl ZcashNetwork {
+    fn prefixes(&self) -> Prefixes {
+        match self {
+            ZcashNetwork::Mainnet => Prefixes {
+                sapling_hrp: "zs",
+                unified_hrp: "u",
+                p2pkh: [0x1c, 0xb8],
+                p2sh: [0x1c, 0xbd],
+            },
+            ZcashNetwork::Testnet => Prefixes {
+                sapling_hrp: "ztestsapling",
+                unified_hrp: "utest",
+                p2pkh: [0x1d, 0x25],
+                p2sh: [0x1c, 0xba],
+            },
+            ZcashNetwork::Regtest => Prefixes {
+                sapling_hrp: "zregtestsapling",
+                unified_hrp: "uregtest",
+                p2pkh: [0x1d, 0x25],
+                p2sh: [0x1c, 0xba],
+            },
+        }
+    }
+}
+
+/// Whether `address` is a well-formed Sapling, unified or transparent address on `network`
+pub fn is_valid(address: &str, network: ZcashNetwork) -> bool {
+    let prefixes = network.prefixes();
+
+    if let Some((hrp, data)) = bech32_decode(address, BECH32_CONST) {
+        return hrp == prefixes.sapling_hrp && data.len() == SAPLING_ADDRESS_LEN;
+    }
+    if let Some((hrp, data)) = bech32_decode(address, BECH32M_CONST) {
+        return hrp == prefixes.unified_hrp && is_valid_unified(hrp, &data);
+    }
+    match base58check_decode(address) {
+        Some(payload) => {
+            payload.len() == TRANSPARENT_ADDRESS_LEN
+                && (payload[..2] == prefixes.p2pkh || payload[..2] == prefixes.p2sh)
+        }
+        None => false,
+    }
+}
+
+/// Whether a jumbled unified address payload carries well-formed receivers (ZIP 316)
+///
+/// Receivers must be in ascending typecode order without repeats, known receivers
+/// must have their exact length, P2PKH and P2SH may not both appear, and at least
+/// one Sapling or Orchard receiver is required. Unknown typecodes are skipped.
+fn is_valid_unified(hrp: &str, jumbled: &[u8]) -> bool {
+    if jumbled.len() < MIN_UNIFIED_ADDRESS_LEN
+        || jumbled.len() > MAX_UNIFIED_ADDRESS_LEN
+        || hrp.len() > UNIFIED_PADDING_LEN
+    {
+        return false;
+    }
+    let payload = f4jumble_inv(jumbled);
+    let (mut items, padding) = payload.split_at(payload.len() - UNIFIED_PADDING_LEN);
+    let mut expected_padding = [0u8; UNIFIED_PADDING_LEN];
+    expected_padding[..hrp.len()].copy_from_slice(hrp.as_bytes());
+    if padding != expected_padding {
+        return false;
+    }
+
+    let mut previous_typecode = None;
+    let (mut transparent, mut shielded) = (false, false);
+    while !items.is_empty() {
+        let (typecode, length) = match (read_compact_size(&mut items), read_compact_size(&mut items)) {
+            (Some(typecode), Some(length)) => (typecode, length),
+            _ => return false,
+        };
+        if previous_typecode.map_or(false, |previous| typecode <= previous) {
+            return false;
+        }
+        previous_typecode = Some(typecode);
+
+        let expected_length = match typecode {
+            TYPECODE_P2PKH | TYPECODE_P2SH => {
+                if transparent {
+                    return false;
+                }
+                transparent = true;
+                Some(20)
+            }
+            TYPECODE_SAPLING | TYPECODE_ORCHARD => {
+                shielded = true;
+                Some(SAPLING_ADDRESS_LEN as u64)
+            }
+            _ => None,
+        };
+        if expected_length.map_or(false, |expected| length != expected) || length > items.len() as u64 {
+            return false;
+        }
+        items = &items[length as usize..];
+    }
+
+    shielded
+}
+
+/// Read a minimally encoded Bitcoin CompactSize, advancing `data` past it
+fn read_compact_size(data: &mut &[u8]) -> Option<u64> {
+    let (&first, rest) = data.split_first()?;
+    let (width, minimum) = match first {
+        0xfd => (2, 0xfd),
+        0xfe => (4, 0x1_0000),
+        0xff => (8, 0x1_0000_0000),
+        value => {
+            *data = rest;
+            return Some(value as u64);
+        }
+    };
+    let bytes = rest.get(..width)?;
+    let mut buffer = [0u8; 8];
+    buffer[..width].copy_from_slice(bytes);
+    let value = u64::from_le_bytes(buffer);
+    *data = &rest[width..];
+    (value >= minimum).then_some(value)
+}
+
+/// Invert the F4Jumble permutation unified addresses are encoded under
+fn f4jumble_inv(message: &[u8]) -> Vec<u8> {
+    let left_len = (message.len() / 2).min(64);
+    let (mut left, mut right) = (message[..left_len].to_vec(), message[left_len..].to_vec());
+    xor_into(&mut left, &f4jumble_h(1, &right, left_len));
+    xor_into(&mut right, &f4jumble_g(1, &left, right.len()));
+    xor_into(&mut left, &f4jumble_h(0, &right, left_len));
+    xor_into(&mut right, &f4jumble_g(0, &left, right.len()));
+    left.extend_from_slice(&right);
+    left
+}
+
+fn f4jumble_h(round: u8, input: &[u8], len: usize) -> Vec<u8> {
+    let mut personal = *b"UA_F4Jumble_H\0\0\0";
+    personal[13] = round;
+    Params::new()
+        .hash_length(len)
+        .personal(&personal)
+        .hash(input)
+        .as_bytes()
+        .to_vec()
+}
+
+fn f4jumble_g(round: u8, input: &[u8], len: usize) -> Vec<u8> {
+    let mut personal = *b"UA_F4Jumble_G\0\0\0";
+    personal[13] = round;
+    let mut output = Vec::with_capacity(len + 64);
+    let mut block: u16 = 0;
+    while output.len() < len {
+        personal[14..].copy_from_slice(&block.to_le_bytes());
+        output.extend_from_slice(Params::new().personal(&personal).hash(input).as_bytes());
+        block = block.wrapping_add(1);
+    }
+    output.truncate(len);
+    output
+}
+
+fn xor_into(target: &mut [u8], mask: &[u8]) {
+    for (byte, mask_byte) in target.iter_mut().zip(mask) {
+        *byte ^= mask_byte;
+    }
+}
+
+fn bech32_polymod(values: impl Iterator<Item = u8>) -> u32 {
+    let mut checksum: u32 = 1;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x01ff_ffff) << 5) ^ value as u32;
+        for (i, generator) in BECH32_GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+/// Decode a lowercase bech32 (or bech32m, per `constant`) string into its HRP and payload
+///
+/// Zcash unified addresses may exceed BIP 173's 90-character limit, so none is applied.
+fn bech32_decode(address: &str, constant: u32) -> Option<(&str, Vec<u8>)> {
+    if address.bytes().any(|byte| byte.is_ascii_uppercase()) {
+        return None;
+    }
+    let separator = address.rfind('1')?;
+    let (hrp, data) = (&address[..separator], &address[separator + 1..]);
+    if hrp.is_empty() || data.len() < 6 {
+        return None;
+    }
+
+    let values = data
+        .bytes()
+        .map(|byte| BECH32_CHARSET.iter().position(|c| *c == byte).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()?;
+
+    let expanded_hrp = hrp
+        .bytes()
+        .map(|byte| byte >> 5)
+        .chain(std::iter::once(0))
+        .chain(hrp.bytes().map(|byte| byte & 31));
+    if bech32_polymod(expanded_hrp.chain(values.iter().copied())) != constant {
+        return None;
+    }
+
+    // Regroup the 5-bit values, minus the checksum, into bytes
+    let mut payload = Vec::with_capacity(values.len() * 5 / 8);
+    let (mut accumulator, mut bits) = (0u32, 0u32);
+    for value in &values[..values.len() - 6] {
+        accumulator = (accumulator << 5) | *value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            payload.push((accumulator >> bits) as u8);
+        }
+    }
+    if bits >= 5 || (accumulator << (8 - bits)) & 0xff != 0 {
+        return None;
+    }
+
+    Some((hrp, payload))
+}
+
+/// Decode a Base58Check string and return its payload without the checksum
+fn base58check_decode(address: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for character in address.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|c| *c == character)? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    let leading_zeros = address.bytes().take_while(|byte| *byte == b'1').count();
+    let mut decoded = vec![0u8; leading_zeros];
+    decoded.extend_from_slice(&bytes);
+
+    if decoded.len() < 4 {
+        return None;
+    }
+    let (payload, checksum) = decoded.split_at(decoded.len() - 4);
+    let digest = hash(&hash(payload).to_bytes()).to_bytes();
+    (digest[..4] == *checksum).then(|| payload.to_vec())
+}
@@ -0,0 +1,573 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use uint::construct_uint;
+
+use crate::{BridgeError, BridgeState, ZcashNetwork};
+
+construct_uint! {
+    /// 256-bit unsigned integer for proof-of-work targets
+    pub struct U256(4);
+}
+
+/// Serialized size of a mainnet or testnet block header, including its Equihash
+/// solution; regtest headers are shorter
+pub const ZCASH_HEADER_LEN: usize = 140 + 3 + 1344;
+
+/// Number of recent headers kept on chain
+pub const HEADER_WINDOW: usize = 100;
+
+/// Post-Blossom target block spacing, in seconds
+const TARGET_SPACING: i64 = 75;
+/// Blocks averaged by the DigiShield difficulty adjustment
+const AVERAGING_WINDOW: u32 = 17;
+/// Blocks in a median-time-past calculation
+const MEDIAN_TIME_SPAN: u32 = 11;
+/// Headers needed below a new block to check its difficulty
+pub const DIFFICULTY_HEADERS: u32 = AVERAGING_WINDOW + MEDIAN_TIME_SPAN;
+const MAX_ADJUST_DOWN_PERCENT: i64 = 32;
+const MAX_ADJUST_UP_PERCENT: i64 = 16;
+/// How far a block's time may run ahead of the median time past, in seconds
+const MAX_FUTURE_BLOCK_TIME_MTP: u32 = 90 * 60;
+/// Gap after its parent, in target spacings, past which a block may use the minimum difficulty
+const MIN_DIFFICULTY_GAP_SPACINGS: i64 = 6;
+
+/// Proof-of-work rules a network's headers are checked against
+struct ChainParams {
+    equihash_n: u32,
+    equihash_k: u32,
+    /// CompactSize encoding of the solution length, followed by that many bytes
+    solution_len_prefix: &'static [u8],
+    solution_len: usize,
+    /// Easiest target allowed, in compact form
+    pow_limit_bits: u32,
+    /// Parent height from which a block arriving after a long gap may use the minimum difficulty
+    min_difficulty_after_height: Option<u32>,
+    /// Whether difficulty is recomputed each block; regtest keeps it fixed
+    retarget: bool,
+}
+
+impl ZcashNetwork {
+    fn chain_params(&self) -> ChainParams {
+        match self {
+            ZcashNetwork::Mainnet => ChainParams {
+                equihash_n: 200,
+                equihash_k: 9,
+                solution_len_prefix: &[0xfd, 0x40, 0x05],
+                solution_len: 1344,
+                pow_limit_bits: 0x1f07_ffff,
+                min_difficulty_after_height: None,
+                retarget: true,
+            },
+            ZcashNetwork::Testnet => ChainParams {
+                equihash_n: 200,
+                equihash_k: 9,
+                solution_len_prefix: &[0xfd, 0x40, 0x05],
+                solution_len: 1344,
+                pow_limit_bits: 0x2007_ffff,
+                min_difficulty_after_height: Some(299_187),
+                retarget: true,
+            },
+            ZcashNetwork::Regtest => ChainParams {
+                equihash_n: 48,
+                equihash_k: 5,
+                solution_len_prefix: &[0x24],
+                solution_len: 36,
+                pow_limit_bits: 0x200f_0f0f,
+                min_difficulty_after_height: Some(0),
+                retarget: false,
+            },
+        }
+    }
+}
+
+/// Compact record of a stored Zcash header
+#[zero_copy]
+#[derive(Default)]
+pub struct HeaderRecord {
+    pub hash: [u8; 32],
+    pub merkle_root: [u8; 32],  // Zero for checkpointed headers
+    pub height: u32,
+    pub time: u32,
+    pub bits: u32,
+    pub _padding: [u8; 4],
+}
+
+impl HeaderRecord {
+    pub const LEN: usize = 32 + 32 + 4 + 4 + 4 + 4;
+}
+
+/// Zcash light client: a ring buffer of the most recent headers on the best chain
+#[account(zero_copy)]
+pub struct LightClient {
+    pub bridge_state: Pubkey,
+    pub tip_height: u32,
+    pub min_confirmations: u32,  // Depth at which a header counts as confirmed
+    pub bump: u8,
+    pub network: u8,  // `ZcashNetwork` of the bridge; zero, mainnet, in clients created before it was stored
+    pub _padding: [u8; 6],
+    pub headers: [HeaderRecord; HEADER_WINDOW],
+}
+
+impl LightClient {
+    pub const LEN: usize = 32 + 4 + 4 + 1 + 1 + 6 + HeaderRecord::LEN * HEADER_WINDOW;
+
+    /// Network whose consensus rules submitted headers must follow
+    pub fn network(&self) -> ZcashNetwork {
+        match self.network {
+            1 => ZcashNetwork::Testnet,
+            2 => ZcashNetwork::Regtest,
+            _ => ZcashNetwork::Mainnet,
+        }
+    }
+
+    /// Stored header at `height`, if it is on the current chain and still in the window
+    pub fn header(&self, height: u32) -> Option<&HeaderRecord> {
+        if height > self.tip_height {
+            return None;
+        }
+        let record = &self.headers[height as usize % HEADER_WINDOW];
+        (record.height == height && record.bits != 0).then_some(record)
+    }
+
+    /// Highest header buried under at least `min_confirmations` blocks
+    pub fn confirmed_height(&self) -> Option<u32> {
+        self.tip_height.checked_sub(self.min_confirmations)
+    }
+
+    /// Stored header at `height`, if it has reached the confirmation depth
+    pub fn confirmed_header(&self, height: u32) -> Option<&HeaderRecord> {
+        if height > self.confirmed_height()? {
+            return None;
+        }
+        self.header(height)
+    }
+
+    fn tip(&self) -> &HeaderRecord {
+        &self.headers[self.tip_height as usize % HEADER_WINDOW]
+    }
+
+    fn push(&mut self, record: HeaderRecord) {
+        self.tip_height = record.height;
+        self.headers[record.height as usize % HEADER_WINDOW] = record;
+    }
+
+    /// Median of the last `MEDIAN_TIME_SPAN` block times ending at `height`
+    fn median_time_past(&self, height: u32) -> Result<u32> {
+        let mut times = [0u32; MEDIAN_TIME_SPAN as usize];
+        for (i, time) in times.iter_mut().enumerate() {
+            *time = self
+                .header(height - i as u32)
+                .ok_or(BridgeError::HeaderNotInWindow)?
+                .time;
+        }
+        times.sort_unstable();
+        Ok(times[MEDIAN_TIME_SPAN as usize / 2])
+    }
+
+    /// Compact target a block at `time` after the tip must carry (DigiShield v3)
+    fn next_bits(&self, time: u32, params: &ChainParams) -> Result<u32> {
+        let last = self.tip_height;
+
+        // Testnet and regtest fall back to the minimum difficulty after a long gap
+        if let Some(after_height) = params.min_difficulty_after_height {
+            let gap = time as i64 - self.tip().time as i64;
+            if last >= after_height && gap > MIN_DIFFICULTY_GAP_SPACINGS * TARGET_SPACING {
+                return Ok(params.pow_limit_bits);
+            }
+        }
+        if !params.retarget {
+            return Ok(self.tip().bits);
+        }
+
+        require!(
+            last >= DIFFICULTY_HEADERS,
+            BridgeError::HeaderNotInWindow
+        );
+
+        let mut total = U256::zero();
+        for i in 0..AVERAGING_WINDOW {
+            let bits = self
+                .header(last - i)
+                .ok_or(BridgeError::HeaderNotInWindow)?
+                .bits;
+            total += target_from_bits(bits).ok_or(BridgeError::InvalidZcashHeader)?;
+        }
+        let average = total / U256::from(AVERAGING_WINDOW);
+
+        let window_timespan = AVERAGING_WINDOW as i64 * TARGET_SPACING;
+        let min_timespan = window_timespan * (100 - MAX_ADJUST_UP_PERCENT) / 100;
+        let max_timespan = window_timespan * (100 + MAX_ADJUST_DOWN_PERCENT) / 100;
+
+        let actual_timespan = self.median_time_past(last)? as i64
+            - self.median_time_past(last - AVERAGING_WINDOW)? as i64;
+        let actual_timespan = (window_timespan + (actual_timespan - window_timespan) / 4)
+            .clamp(min_timespan, max_timespan);
+
+        let pow_limit = target_from_bits(params.pow_limit_bits).unwrap();
+        let next = average / U256::from(window_timespan) * U256::from(actual_timespan);
+        Ok(bits_from_target(next.min(pow_limit)))
+    }
+}
+
+/// Scratch account a submitter fills with a serialized header across several transactions
+#[account]
+pub struct HeaderBuffer {
+    pub data: Vec<u8>,
+    pub bump: u8,
+}
+
+impl HeaderBuffer {
+    pub const LEN: usize = 4 + ZCASH_HEADER_LEN + 1;
+}
+
+/// Borrowed view over a serialized Zcash block header
+pub struct ZcashHeader<'a> {
+    raw: &'a [u8],
+    network: ZcashNetwork,
+}
+
+impl<'a> ZcashHeader<'a> {
+    pub fn parse(raw: &'a [u8], network: ZcashNetwork) -> Result<Self> {
+        let params = network.chain_params();
+        let prefix_end = 140 + params.solution_len_prefix.len();
+        require!(
+            raw.len() == prefix_end + params.solution_len
+                && raw[140..prefix_end] == *params.solution_len_prefix,
+            BridgeError::InvalidZcashHeader
+        );
+        Ok(Self { raw, network })
+    }
+
+    pub fn prev_hash(&self) -> [u8; 32] {
+        self.raw[4..36].try_into().unwrap()
+    }
+
+    pub fn merkle_root(&self) -> [u8; 32] {
+        self.raw[36..68].try_into().unwrap()
+    }
+
+    pub fn time(&self) -> u32 {
+        u32::from_le_bytes(self.raw[100..104].try_into().unwrap())
+    }
+
+    pub fn bits(&self) -> u32 {
+        u32::from_le_bytes(self.raw[104..108].try_into().unwrap())
+    }
+
+    /// Double SHA-256 of the full header, in internal byte order
+    pub fn hash(&self) -> [u8; 32] {
+        hash(&hash(self.raw).to_bytes()).to_bytes()
+    }
+
+    /// Check the Equihash solution against the header and nonce
+    pub fn verify_equihash(&self) -> Result<()> {
+        let params = self.network.chain_params();
+        equihash::is_valid_solution(
+            params.equihash_n,
+            params.equihash_k,
+            &self.raw[..108],
+            &self.raw[108..140],
+            &self.raw[140 + params.solution_len_prefix.len()..],
+        )
+        .map_err(|_| error!(BridgeError::InvalidEquihashSolution))
+    }
+}
+
+/// Expand a compact target, rejecting negative or overflowing encodings
+pub fn target_from_bits(bits: u32) -> Option<U256> {
+    let exponent = bits >> 24;
+    let mantissa = bits & 0x007f_ffff;
+    if mantissa == 0 || bits & 0x0080_0000 != 0 {
+        return None;
+    }
+    if exponent <= 3 {
+        return Some(U256::from(mantissa >> (8 * (3 - exponent))));
+    }
+    if exponent > 34 || (mantissa > 0xff && exponent > 33) || (mantissa > 0xffff && exponent > 32) {
+        return None;
+    }
+    Some(U256::from(mantissa) << (8 * (exponent - 3) as usize))
+}
+
+/// Encode a target in compact form
+pub fn bits_from_target(target: U256) -> u32 {
+    let mut size = (target.bits() + 7) / 8;
+    let mut compact = if size <= 3 {
+        (target.low_u64() << (8 * (3 - size))) as u32
+    } else {
+        (target >> (8 * (size - 3))).low_u64() as u32
+    };
+    if compact & 0x0080_0000 != 0 {
+        compact >>= 8;
+        size += 1;
+    }
+    compact | ((size as u32) << 24)
+}
+
+pub fn initialize(
+    ctx: Context<InitializeLightClient>,
+    checkpoint_height: u32,
+    checkpoint_hash: [u8; 32],
+    times: Vec<u32>,
+    bits: Vec<u32>,
+    min_confirmations: Option<u32>,
+) -> Result<()> {
+    // Verify authority
+    require!(
+        ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+        BridgeError::Unauthorized
+    );
+
+    let min_confirmations = min_confirmations
+        .unwrap_or_else(|| ctx.accounts.bridge_state.network.default_min_confirmations());
+    let count = DIFFICULTY_HEADERS as usize;
+    require!(
+        times.len() == count && bits.len() == count && checkpoint_height >= DIFFICULTY_HEADERS,
+        BridgeError::InvalidCheckpoint
+    );
+    require!(
+        (min_confirmations as usize) < HEADER_WINDOW,
+        BridgeError::InvalidCheckpoint
+    );
+
+    let mut light_client = ctx.accounts.light_client.load_init()?;
+    light_client.bridge_state = ctx.accounts.bridge_state.key();
+    light_client.min_confirmations = min_confirmations;
+    light_client.bump = ctx.bumps.light_client;
+    light_client.network = ctx.accounts.bridge_state.network as u8;
+
+    // Seed the window with the times and targets the difficulty check needs,
+    // oldest first; only the checkpoint itself is known by hash
+    let first_height = checkpoint_height + 1 - DIFFICULTY_HEADERS;
+    for (i, (time, bits)) in times.into_iter().zip(bits).enumerate() {
+        require!(
+            target_from_bits(bits).is_some(),
+            BridgeError::InvalidCheckpoint
+        );
+        let height = first_height + i as u32;
+        light_client.push(HeaderRecord {
+            hash: if height == checkpoint_height { checkpoint_hash } else { [0; 32] },
+            height,
+            time,
+            bits,
+            ..Default::default()
+        });
+    }
+
+    emit!(LightClientInitializedEvent {
+        height: checkpoint_height,
+        hash: checkpoint_hash,
+        min_confirmations,
+    });
+
+    Ok(())
+}
+
+pub fn write_header_chunk(ctx: Context<WriteHeaderChunk>, offset: u32, bytes: Vec<u8>) -> Result<()> {
+    let buffer = &mut ctx.accounts.header_buffer;
+    buffer.bump = ctx.bumps.header_buffer;
+
+    // Writing at offset zero starts a new header
+    if offset == 0 {
+        buffer.data.clear();
+    }
+    require!(
+        offset as usize == buffer.data.len()
+            && buffer.data.len() + bytes.len() <= ZCASH_HEADER_LEN,
+        BridgeError::InvalidZcashHeader
+    );
+    buffer.data.extend_from_slice(&bytes);
+
+    Ok(())
+}
+
+pub fn submit_header(ctx: Context<SubmitHeader>) -> Result<()> {
+    let mut light_client = ctx.accounts.light_client.load_mut()?;
+    let network = light_client.network();
+    let header = ZcashHeader::parse(&ctx.accounts.header_buffer.data, network)?;
+
+    // Only extensions of the current tip are accepted
+    let tip = light_client.tip();
+    require!(
+        header.prev_hash() == tip.hash,
+        BridgeError::HeaderDoesNotExtendTip
+    );
+    let height = light_client
+        .tip_height
+        .checked_add(1)
+        .ok_or(BridgeError::Overflow)?;
+
+    let median_time_past = light_client.median_time_past(light_client.tip_height)?;
+    require!(
+        header.time() > median_time_past
+            && header.time() <= median_time_past.saturating_add(MAX_FUTURE_BLOCK_TIME_MTP),
+        BridgeError::InvalidHeaderTimestamp
+    );
+
+    require!(
+        header.bits() == light_client.next_bits(header.time(), &network.chain_params())?,
+        BridgeError::InvalidDifficulty
+    );
+    let target = target_from_bits(header.bits()).ok_or(BridgeError::InvalidDifficulty)?;
+    let block_hash = header.hash();
+    require!(
+        U256::from_little_endian(&block_hash) <= target,
+        BridgeError::InsufficientProofOfWork
+    );
+    header.verify_equihash()?;
+
+    light_client.push(HeaderRecord {
+        hash: block_hash,
+        merkle_root: header.merkle_root(),
+        height,
+        time: header.time(),
+        bits: header.bits(),
+        ..Default::default()
+    });
+
+    emit!(ZcashHeaderSubmittedEvent {
+        height,
+        hash: block_hash,
+        submitter: ctx.accounts.submitter.key(),
+    });
+
+    Ok(())
+}
+
+pub fn rollback(ctx: Context<RollbackLightClient>, height: u32) -> Result<()> {
+    // Verify authority
+    require!(
+        ctx.accounts.authority.key() == ctx.accounts.bridge_state.authority,
+        BridgeError::Unauthorized
+    );
+
+    let mut light_client = ctx.accounts.light_client.load_mut()?;
+    require!(
+        height < light_client.tip_height
+            && light_client.header(height).map_or(false, |record| record.hash != [0; 32]),
+        BridgeError::HeaderNotInWindow
+    );
+
+    // The new tip must keep enough history below it to check the next difficulty
+    for i in 0..DIFFICULTY_HEADERS {
+        require!(
+            height >= i && light_client.header(height - i).is_some(),
+            BridgeError::HeaderNotInWindow
+        );
+    }
+    let previous_tip = light_client.tip_height;
+    light_client.tip_height = height;
+
+    emit!(LightClientRolledBackEvent {
+        previous_tip,
+        tip: height,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeLightClient<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LightClient::LEN,
+        seeds = [b"light_client", bridge_state.key().as_ref()],
+        bump
+    )]
+    pub light_client: AccountLoader<'info, LightClient>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WriteHeaderChunk<'info> {
+    #[account(
+        seeds = [b"light_client", light_client.load()?.bridge_state.as_ref()],
+        bump = light_client.load()?.bump
+    )]
+    pub light_client: AccountLoader<'info, LightClient>,
+    
+    #[account(
+        init_if_needed,
+        payer = submitter,
+        space = 8 + HeaderBuffer::LEN,
+        seeds = [b"header_buffer", light_client.key().as_ref(), submitter.key().as_ref()],
+        bump
+    )]
+    pub header_buffer: Account<'info, HeaderBuffer>,
+    
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitHeader<'info> {
+    #[account(
+        mut,
+        seeds = [b"light_client", light_client.load()?.bridge_state.as_ref()],
+        bump = light_client.load()?.bump
+    )]
+    pub light_client: AccountLoader<'info, LightClient>,
+    
+    /// Consumed by the submission; rent goes back to the submitter
+    #[account(
+        mut,
+        seeds = [b"header_buffer", light_client.key().as_ref(), submitter.key().as_ref()],
+        bump = header_buffer.bump,
+        close = submitter
+    )]
+    pub header_buffer: Account<'info, HeaderBuffer>,
+    
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RollbackLightClient<'info> {
+    #[account(
+        seeds = [b"bridge_state", bridge_state.mint.as_ref()],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"light_client", bridge_state.key().as_ref()],
+        bump = light_client.load()?.bump
+    )]
+    pub light_client: AccountLoader<'info, LightClient>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct LightClientInitializedEvent {
+    pub height: u32,
+    pub hash: [u8; 32],
+    pub min_confirmations: u32,
+}
+
+#[event]
+pub struct ZcashHeaderSubmittedEvent {
+    pub height: u32,
+    pub hash: [u8; 32],
+    pub submitter: Pubkey,
+}
+
+#[event]
+pub struct LightClientRolledBackEvent {
+    pub previous_tip: u32,
+    pub tip: u32,
+}
@@ -0,0 +1,256 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use blake2b_simd::Params;
+
+use crate::BridgeError;
+
+const OVERWINTERED_FLAG: u32 = 1 << 31;
+const SAPLING_VERSION_GROUP_ID: u32 = 0x892f_2085;
+const NU5_VERSION_GROUP_ID: u32 = 0x26a7_270a;
+
+/// `OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG`
+const P2PKH_PREFIX: [u8; 3] = [0x76, 0xa9, 0x14];
+const P2PKH_SUFFIX: [u8; 2] = [0x88, 0xac];
+/// `OP_RETURN <32 bytes>`, carrying the Solana recipient
+const RECIPIENT_MEMO_PREFIX: [u8; 2] = [0x6a, 0x20];
+
+/// Transparent output of a Zcash transaction
+pub struct TxOut<'a> {
+    pub value: u64,
+    pub script: &'a [u8],
+}
+
+/// Transparent-only Zcash transaction, as far as a deposit needs it
+pub struct ZcashTransaction<'a> {
+    pub txid: [u8; 32],
+    pub outputs: Vec<TxOut<'a>>,
+}
+
+impl<'a> ZcashTransaction<'a> {
+    /// Parse a v4 (Sapling) or v5 (NU5) transaction with no shielded components
+    /// and compute its txid
+    pub fn parse(raw: &'a [u8]) -> Result<Self> {
+        let mut reader = Reader { data: raw, pos: 0 };
+
+        let header = reader.u32()?;
+        let version_group_id = reader.u32()?;
+        require!(
+            header & OVERWINTERED_FLAG != 0,
+            BridgeError::InvalidZcashTransaction
+        );
+
+        match (header & !OVERWINTERED_FLAG, version_group_id) {
+            (4, SAPLING_VERSION_GROUP_ID) => {
+                reader.skip_inputs()?;
+                let outputs = reader.outputs()?;
+                reader.take(4 + 4)?; // lock time, expiry height
+                require!(reader.u64()? == 0, BridgeError::InvalidZcashTransaction);
+                for _ in 0..3 {
+                    // Sapling spends, Sapling outputs, JoinSplits
+                    require!(
+                        reader.compact_size()? == 0,
+                        BridgeError::InvalidZcashTransaction
+                    );
+                }
+                reader.finish()?;
+
+                Ok(Self {
+                    txid: hash(&hash(raw).to_bytes()).to_bytes(),
+                    outputs,
+                })
+            }
+            (5, NU5_VERSION_GROUP_ID) => {
+                let branch_id = reader.take(4)?;
+                let lock_time_and_expiry = reader.take(4 + 4)?;
+                let inputs_start = reader.pos;
+                reader.skip_inputs()?;
+                let inputs = &raw[inputs_start..reader.pos];
+                let outputs_start = reader.pos;
+                let outputs = reader.outputs()?;
+                let outputs_raw = &raw[outputs_start..reader.pos];
+                for _ in 0..3 {
+                    // Sapling spends, Sapling outputs, Orchard actions
+                    require!(
+                        reader.compact_size()? == 0,
+                        BridgeError::InvalidZcashTransaction
+                    );
+                }
+                reader.finish()?;
+
+                let header_digest = blake2b(
+                    b"ZTxIdHeadersHash",
+                    &[&raw[..8], branch_id, lock_time_and_expiry],
+                );
+                let transparent_digest = transparent_digest(inputs, outputs_raw)?;
+                let mut personal = *b"ZcashTxHash_\0\0\0\0";
+                personal[12..].copy_from_slice(branch_id);
+                let txid = blake2b(
+                    &personal,
+                    &[
+                        &header_digest,
+                        &transparent_digest,
+                        &blake2b(b"ZTxIdSaplingHash", &[]),
+                        &blake2b(b"ZTxIdOrchardHash", &[]),
+                    ],
+                );
+
+                Ok(Self { txid, outputs })
+            }
+            _ => err!(BridgeError::InvalidZcashTransaction),
+        }
+    }
+
+    /// Total paid to the P2PKH address `pubkey_hash` and the Solana recipient named
+    /// in the transaction's `OP_RETURN` memo
+    pub fn deposit_to(&self, pubkey_hash: &[u8; 20]) -> Result<(u64, Pubkey)> {
+        let mut amount: u64 = 0;
+        let mut recipient = None;
+        for output in &self.outputs {
+            let script = output.script;
+            if script.len() == 25
+                && script[..3] == P2PKH_PREFIX
+                && script[3..23] == pubkey_hash[..]
+                && script[23..] == P2PKH_SUFFIX
+            {
+                amount = amount
+                    .checked_add(output.value)
+                    .ok_or(BridgeError::Overflow)?;
+            } else if script.len() == 34 && script[..2] == RECIPIENT_MEMO_PREFIX {
+                require!(recipient.is_none(), BridgeError::InvalidZcashTransaction);
+                recipient = Some(Pubkey::try_from(&script[2..]).unwrap());
+            }
+        }
+        let recipient = recipient.ok_or(BridgeError::MissingDepositRecipient)?;
+        Ok((amount, recipient))
+    }
+}
+
+/// Txid in the byte-reversed hex form block explorers and zcashd display
+pub fn txid_hex(txid: &[u8; 32]) -> String {
+    txid.iter().rev().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Check that `txid` sits at `index` in the block whose transaction Merkle root is `root`
+pub fn verify_merkle_proof(txid: [u8; 32], mut index: u32, proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    let mut node = txid;
+    for sibling in proof {
+        let mut pair = [0u8; 64];
+        if index & 1 == 0 {
+            pair[..32].copy_from_slice(&node);
+            pair[32..].copy_from_slice(sibling);
+        } else {
+            pair[..32].copy_from_slice(sibling);
+            pair[32..].copy_from_slice(&node);
+        }
+        node = hash(&hash(&pair).to_bytes()).to_bytes();
+        index >>= 1;
+    }
+    index == 0 && node == *root
+}
+
+/// ZIP 244 digest of the transparent inputs and outputs
+fn transparent_digest(inputs: &[u8], outputs: &[u8]) -> Result<[u8; 32]> {
+    let mut reader = Reader { data: inputs, pos: 0 };
+    let count = reader.compact_size()?;
+    let mut prevouts = Vec::new();
+    let mut sequences = Vec::new();
+    for _ in 0..count {
+        prevouts.extend_from_slice(reader.take(32 + 4)?);
+        let script_len = reader.compact_size()? as usize;
+        reader.take(script_len)?;
+        sequences.extend_from_slice(reader.take(4)?);
+    }
+
+    // Skip the output count; the digest covers the serialized outputs alone
+    let mut reader = Reader { data: outputs, pos: 0 };
+    reader.compact_size()?;
+    let outputs = &outputs[reader.pos..];
+
+    if count == 0 && outputs.is_empty() {
+        return Ok(blake2b(b"ZTxIdTranspaHash", &[]));
+    }
+    Ok(blake2b(
+        b"ZTxIdTranspaHash",
+        &[
+            &blake2b(b"ZTxIdPrevoutHash", &[&prevouts]),
+            &blake2b(b"ZTxIdSequencHash", &[&sequences]),
+            &blake2b(b"ZTxIdOutputsHash", &[outputs]),
+        ],
+    ))
+}
+
+fn blake2b(personal: &[u8; 16], parts: &[&[u8]]) -> [u8; 32] {
+    let mut state = Params::new().hash_length(32).personal(personal).to_state();
+    for part in parts {
+        state.update(part);
+    }
+    state.finalize().as_bytes().try_into().unwrap()
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(BridgeError::InvalidZcashTransaction)?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn compact_size(&mut self) -> Result<u64> {
+        Ok(match self.take(1)?[0] {
+            0xfd => u16::from_le_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            0xfe => self.u32()? as u64,
+            0xff => self.u64()?,
+            len => len as u64,
+        })
+    }
+
+    fn skip_inputs(&mut self) -> Result<()> {
+        let count = self.compact_size()?;
+        for _ in 0..count {
+            self.take(32 + 4)?; // prevout
+            let script_len = self.compact_size()? as usize;
+            self.take(script_len)?;
+            self.take(4)?; // sequence
+        }
+        Ok(())
+    }
+
+    fn outputs(&mut self) -> Result<Vec<TxOut<'a>>> {
+        let count = self.compact_size()?;
+        let mut outputs = Vec::new();
+        for _ in 0..count {
+            let value = self.u64()?;
+            let script_len = self.compact_size()? as usize;
+            outputs.push(TxOut {
+                value,
+                script: self.take(script_len)?,
+            });
+        }
+        Ok(outputs)
+    }
+
+    fn finish(&self) -> Result<()> {
+        require!(
+            self.pos == self.data.len(),
+            BridgeError::InvalidZcashTransaction
+        );
+        Ok(())
+    }
+}
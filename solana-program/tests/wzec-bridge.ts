@@ -1,8 +1,20 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { WzecBridge } from "../target/types/wzec_bridge";
-import { TOKEN_PROGRAM_ID, createMint, getOrCreateAssociatedTokenAccount } from "@solana/spl-token";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  approve,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+  getOrCreateAssociatedTokenAccount,
+  transfer,
+} from "@solana/spl-token";
 import { expect } from "chai";
+import { createHash } from "crypto";
+import { readFileSync } from "fs";
 
 describe("wzec-bridge", () => {
   // Configure the client to use the local cluster.
@@ -13,24 +25,187 @@ describe("wzec-bridge", () => {
   
   let mint: anchor.web3.PublicKey;
   let bridgeState: anchor.web3.PublicKey;
+  let bridgeStats: anchor.web3.PublicKey;
+  let guardianSet: anchor.web3.PublicKey;
+  let feeVault: anchor.web3.PublicKey;
+  let mintAuthority: anchor.web3.PublicKey;
   let userTokenAccount: anchor.web3.PublicKey;
   
   const authority = provider.wallet.publicKey;
   const user = anchor.web3.Keypair.generate();
+  const guardians = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+  const testnetZecAddress =
+    "ztestsapling1gh7w9vhta6eaq23c0qrv53z0xamgpg099q4cs68epka5gp6evvwj6ugkg2mjdvzyq938c6lwyvu";
+  const mainnetZecAddress =
+    "zs1gh7w9vhta6eaq23c0qrv53z0xamgpg099q4cs68epka5gp6evvwj6ugkg2mjdvzyq938cjgu8tg";
+
+  const TOKEN_METADATA_PROGRAM_ID = new anchor.web3.PublicKey(
+    "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+  );
+
+  const MEMO_PROGRAM_ID = new anchor.web3.PublicKey(
+    "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"
+  );
+
+  // Derive the state, stats and mint authority PDAs of the bridge for a mint
+  const bridgePdasFor = (mintKey: anchor.web3.PublicKey) => {
+    const [state] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("bridge_state"), mintKey.toBuffer()],
+      program.programId
+    );
+    const [stats] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("bridge_stats"), state.toBuffer()],
+      program.programId
+    );
+    const [authorityPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_authority"), mintKey.toBuffer()],
+      program.programId
+    );
+    return { bridgeState: state, bridgeStats: stats, mintAuthority: authorityPda };
+  };
+
+  // Derive the per-user limits PDA
+  const userLimitsFor = (owner: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user_limits"), bridgeState.toBuffer(), owner.toBuffer()],
+      program.programId
+    )[0];
+
+  // Derive the deny list entry PDA for a wallet
+  const denyListEntryFor = (owner: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("deny_list"), bridgeState.toBuffer(), owner.toBuffer()],
+      program.programId
+    )[0];
+
+  // Derive the withdrawal request PDA for a nonce
+  const withdrawalRequestFor = (nonce: anchor.BN) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("withdrawal"), bridgeState.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  // Derive the pending parameter change PDA for an id
+  const pendingChangeFor = (id: anchor.BN) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_change"), bridgeState.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  // Derive the withdrawal request PDA the next burn will create
+  const nextWithdrawalRequest = async () => {
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    return withdrawalRequestFor(state.withdrawalNonce);
+  };
+
+  // Guardian signer accounts passed as remaining accounts to mint_wzec
+  const guardianSigners = (keypairs: anchor.web3.Keypair[]) =>
+    keypairs.map((guardian) => ({
+      pubkey: guardian.publicKey,
+      isSigner: true,
+      isWritable: false,
+    }));
+
+  // Stand-in 32-byte Zcash txid derived from a label
+  const txidFor = (label: string) => [...createHash("sha256").update(label).digest()];
+
+  // Derive the deposit receipt PDA for a Zcash txid
+  const depositReceiptFor = (zcashTxid: number[]) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), bridgeState.toBuffer(), Buffer.from(zcashTxid)],
+      program.programId
+    )[0];
+
+  // Derive the receipt address a Zcash txid had before receipts were seeded by its
+  // raw bytes: the SHA-256 of the txid's display-order hex string
+  const legacyDepositReceiptFor = (zcashTxid: number[]) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("deposit"),
+        bridgeState.toBuffer(),
+        createHash("sha256").update(Buffer.from(zcashTxid).reverse().toString("hex")).digest(),
+      ],
+      program.programId
+    )[0];
+
+  // Load a committed fixture keypair
+  const loadKeypair = (path: string) =>
+    anchor.web3.Keypair.fromSecretKey(Uint8Array.from(JSON.parse(readFileSync(path, "utf8"))));
+
+  // Create a fresh mint and initialize a bridge for it, returning its accounts
+  const initializeBridgeFor = async (
+    network: Parameters<typeof program.methods.initialize>[2],
+    mintFeeBps: number
+  ) => {
+    const mintKeypair = anchor.web3.Keypair.generate();
+    const pdas = bridgePdasFor(mintKeypair.publicKey);
+    const bridgeMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      pdas.mintAuthority,
+      null,
+      8,
+      mintKeypair
+    );
+    const bridgeFeeVault = getAssociatedTokenAddressSync(bridgeMint, pdas.bridgeState, true);
+    await program.methods
+      .initialize(mintFeeBps, 0, network)
+      .accounts({
+        bridgeState: pdas.bridgeState,
+        bridgeStats: pdas.bridgeStats,
+        mint: bridgeMint,
+        mintAuthority: pdas.mintAuthority,
+        feeVault: bridgeFeeVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    return { ...pdas, mint: bridgeMint, feeVault: bridgeFeeVault };
+  };
+
+  // Derive the PDA seeded by a label followed by `seeds`
+  const pdaFor = (label: string, ...seeds: Buffer[]) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from(label), ...seeds], program.programId)[0];
+
+  // Exempt `owner` from a bridge's fees and return the exemption account to pass along
+  const exemptFromFees = async (bridge: anchor.web3.PublicKey, owner: anchor.web3.PublicKey) => {
+    const feeExemption = pdaFor("fee_exemption", bridge.toBuffer(), owner.toBuffer());
+    await program.methods
+      .setFeeExemption(owner, 0, 0)
+      .accounts({
+        bridgeState: bridge,
+        feeExemption,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    return { pubkey: feeExemption, isSigner: false, isWritable: false };
+  };
 
   before(async () => {
-    // Create token mint
+    // Bridge PDAs are keyed by the mint, so its address is fixed; Anchor.toml seeds
+    // fixture accounts at the PDAs it derives
+    const mintKeypair = loadKeypair("tests/fixtures/wzec-mint.json");
+    ({ bridgeState, bridgeStats, mintAuthority } = bridgePdasFor(mintKeypair.publicKey));
+
+    // Create token mint, owned by the bridge from the start
     mint = await createMint(
       provider.connection,
       provider.wallet.payer,
-      authority,
+      mintAuthority,
       null,
-      8 // 8 decimals like ZEC
+      8, // 8 decimals like ZEC
+      mintKeypair
     );
 
-    // Derive bridge state PDA
-    [bridgeState] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("bridge_state")],
+    // Fee vault is the bridge state PDA's associated token account
+    feeVault = getAssociatedTokenAddressSync(mint, bridgeState, true);
+
+    // Derive guardian set PDA
+    [guardianSet] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("guardian_set"), bridgeState.toBuffer()],
       program.programId
     );
 
@@ -51,15 +226,55 @@ describe("wzec-bridge", () => {
     userTokenAccount = userTokenAccountInfo.address;
   });
 
+  it("Rejects a mint with the wrong decimals", async () => {
+    const badMintKeypair = anchor.web3.Keypair.generate();
+    const badPdas = bridgePdasFor(badMintKeypair.publicKey);
+    const badMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      badPdas.mintAuthority,
+      null,
+      6,
+      badMintKeypair
+    );
+
+    try {
+      await program.methods
+        .initialize(0, 10, { testnet: {} })
+        .accounts({
+          bridgeState: badPdas.bridgeState,
+          bridgeStats: badPdas.bridgeStats,
+          mint: badMint,
+          mintAuthority: badPdas.mintAuthority,
+          feeVault: getAssociatedTokenAddressSync(badMint, badPdas.bridgeState, true),
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidMintDecimals");
+    }
+  });
+
   it("Initializes the bridge", async () => {
-    const feePercentage = 10; // 0.1%
+    const mintFeeBps = 0;
+    const burnFeeBps = 10; // 0.1%
 
     const tx = await program.methods
-      .initialize(feePercentage)
+      .initialize(mintFeeBps, burnFeeBps, { testnet: {} })
       .accounts({
         bridgeState,
+        bridgeStats,
         mint,
+        mintAuthority,
+        feeVault,
         authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .rpc();
@@ -69,126 +284,4508 @@ describe("wzec-bridge", () => {
     // Fetch bridge state
     const state = await program.account.bridgeState.fetch(bridgeState);
     
+    expect(state.version).to.equal(18);
+    expect(state.network).to.deep.equal({ testnet: {} });
     expect(state.authority.toString()).to.equal(authority.toString());
     expect(state.mint.toString()).to.equal(mint.toString());
-    expect(state.feePercentage).to.equal(feePercentage);
-    expect(state.paused).to.be.false;
-    expect(state.totalMinted.toNumber()).to.equal(0);
-    expect(state.totalBurned.toNumber()).to.equal(0);
+    expect(state.mintFeeBps).to.equal(mintFeeBps);
+    expect(state.burnFeeBps).to.equal(burnFeeBps);
+    expect(state.mintsPaused).to.be.false;
+    expect(state.burnsPaused).to.be.false;
+
+    const stats = await program.account.bridgeStats.fetch(bridgeStats);
+    expect(stats.totalMinted.toNumber()).to.equal(0);
+    expect(stats.totalBurned.toNumber()).to.equal(0);
+
+    const mintInfo = await getMint(provider.connection, mint);
+    expect(mintInfo.mintAuthority.toString()).to.equal(mintAuthority.toString());
+  });
+
+  it("Initializes an independent bridge for a second asset", async () => {
+    const tazMintKeypair = anchor.web3.Keypair.generate();
+    const tazPdas = bridgePdasFor(tazMintKeypair.publicKey);
+    const tazMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      tazPdas.mintAuthority,
+      null,
+      8,
+      tazMintKeypair
+    );
+
+    await program.methods
+      .initialize(25, 25, { testnet: {} })
+      .accounts({
+        bridgeState: tazPdas.bridgeState,
+        bridgeStats: tazPdas.bridgeStats,
+        mint: tazMint,
+        mintAuthority: tazPdas.mintAuthority,
+        feeVault: getAssociatedTokenAddressSync(tazMint, tazPdas.bridgeState, true),
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const tazState = await program.account.bridgeState.fetch(tazPdas.bridgeState);
+    expect(tazState.mint.toString()).to.equal(tazMint.toString());
+    expect(tazState.mintFeeBps).to.equal(25);
+
+    // The wZEC instance keeps its own configuration
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.mintFeeBps).to.equal(0);
+  });
+
+  it("Creates token metadata for the mint", async () => {
+    const [metadata] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+
+    await program.methods
+      .createMetadata("Wrapped ZEC", "wZEC", "https://solz.network/wzec.json")
+      .accounts({
+        bridgeState,
+        mint,
+        mintAuthority,
+        metadata,
+        authority,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const info = await provider.connection.getAccountInfo(metadata);
+    expect(info.owner.toString()).to.equal(TOKEN_METADATA_PROGRAM_ID.toString());
+  });
+
+  it("Initializes the guardian set", async () => {
+    const threshold = 2;
+
+    await program.methods
+      .initializeGuardianSet(
+        guardians.map((guardian) => guardian.publicKey),
+        threshold
+      )
+      .accounts({
+        bridgeState,
+        guardianSet,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const state = await program.account.guardianSet.fetch(guardianSet);
+    expect(state.guardians.length).to.equal(guardians.length);
+    expect(state.threshold).to.equal(threshold);
+  });
+
+  it("Rejects a mint without guardian quorum", async () => {
+    const amount = new anchor.BN(100_000_000);
+    const zcashTxid = txidFor("test_txid_no_quorum");
+
+    try {
+      await program.methods
+        .mintWzec(amount, zcashTxid, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          guardianSet,
+          depositReceipt: depositReceiptFor(zcashTxid),
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+          mint,
+          mintAuthority,
+          recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          feeVault,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians.slice(0, 1)))
+        .signers(guardians.slice(0, 1))
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientGuardianSignatures");
+    }
   });
 
   it("Mints wZEC tokens", async () => {
     const amount = new anchor.BN(100_000_000); // 1 wZEC
-    const zcashTxid = "test_txid_12345";
+    const zcashTxid = txidFor("test_txid_12345");
 
     const tx = await program.methods
-      .mintWzec(amount, zcashTxid)
+      .mintWzec(amount, zcashTxid, 2_500_000)
       .accounts({
         bridgeState,
+        bridgeStats,
+        guardianSet,
+        depositReceipt: depositReceiptFor(zcashTxid),
+        legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
         mint,
+        mintAuthority,
         recipientTokenAccount: userTokenAccount,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        feeVault,
         authority,
         tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        memoProgram: MEMO_PROGRAM_ID,
       })
+      .remainingAccounts(guardianSigners(guardians))
+      .signers(guardians)
       .rpc();
 
     console.log("Mint transaction:", tx);
 
-    // Fetch updated bridge state
-    const state = await program.account.bridgeState.fetch(bridgeState);
-    expect(state.totalMinted.toString()).to.equal(amount.toString());
+    // The mint carries a memo naming the Zcash deposit in display order
+    const confirmed = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const displayTxid = Buffer.from(zcashTxid).reverse().toString("hex");
+    expect(confirmed.meta.logMessages.join("\n")).to.include(
+      `wZEC mint for Zcash txid ${displayTxid} at height 2500000`
+    );
+
+    // Fetch updated bridge stats
+    const stats = await program.account.bridgeStats.fetch(bridgeStats);
+    expect(stats.totalMinted.toString()).to.equal(amount.toString());
+
+    const receipt = await program.account.depositReceipt.fetch(depositReceiptFor(zcashTxid));
+    expect(receipt.amount.toString()).to.equal(amount.toString());
+    expect(receipt.recipient.toString()).to.equal(userTokenAccount.toString());
   });
 
-  it("Burns wZEC tokens", async () => {
-    const amount = new anchor.BN(50_000_000); // 0.5 wZEC
-    const zecAddress = "ztestsapling1234567890123456789012345678901234567890123456789012345678901234567890";
+  it("Rejects a second mint for the same Zcash txid", async () => {
+    const amount = new anchor.BN(100_000_000);
+    const zcashTxid = txidFor("test_txid_12345");
 
-    const tx = await program.methods
-      .burnWzec(amount, zecAddress)
+    try {
+      await program.methods
+        .mintWzec(amount, zcashTxid, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          guardianSet,
+          depositReceipt: depositReceiptFor(zcashTxid),
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+          mint,
+          mintAuthority,
+          recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          feeVault,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers(guardians)
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("DepositAlreadyProcessed");
+    }
+  });
+
+  it("Refuses mints to and burns by a denied address until it is removed", async () => {
+    const denyListEntry = denyListEntryFor(user.publicKey);
+    await program.methods
+      .addToDenyList(user.publicKey)
       .accounts({
         bridgeState,
-        mint,
-        userTokenAccount,
-        user: user.publicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        denyListEntry,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
       })
-      .signers([user])
       .rpc();
 
-    console.log("Burn transaction:", tx);
+    const entry = await program.account.denyListEntry.fetch(denyListEntry);
+    expect(entry.user.toString()).to.equal(user.publicKey.toString());
 
-    // Fetch updated bridge state
-    const state = await program.account.bridgeState.fetch(bridgeState);
-    expect(state.totalBurned.toNumber()).to.be.greaterThan(0);
-  });
+    const zcashTxid = txidFor("test_txid_denied");
+    try {
+      await program.methods
+        .mintWzec(new anchor.BN(100_000_000), zcashTxid, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          guardianSet,
+          depositReceipt: depositReceiptFor(zcashTxid),
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+          mint,
+          mintAuthority,
+          recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry,
+          feeVault,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers(guardians)
+        .rpc();
 
-  it("Pauses the bridge", async () => {
-    const tx = await program.methods
-      .pauseBridge()
+      expect.fail("Should have refused the mint");
+    } catch (error) {
+      expect(error.toString()).to.include("AddressDenied");
+    }
+
+    try {
+      await program.methods
+        .burnWzec(new anchor.BN(5_000_000), testnetZecAddress, null, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest: await nextWithdrawalRequest(),
+          mint,
+          userTokenAccount,
+          feeVault,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry,
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have refused the burn");
+    } catch (error) {
+      expect(error.toString()).to.include("AddressDenied");
+    }
+
+    await program.methods
+      .removeFromDenyList(user.publicKey)
       .accounts({
         bridgeState,
+        denyListEntry,
         authority,
       })
       .rpc();
 
-    console.log("Pause transaction:", tx);
-
-    const state = await program.account.bridgeState.fetch(bridgeState);
-    expect(state.paused).to.be.true;
+    expect(await provider.connection.getAccountInfo(denyListEntry)).to.be.null;
   });
 
-  it("Prevents minting when paused", async () => {
-    const amount = new anchor.BN(100_000_000);
-    const zcashTxid = "test_txid_paused";
+  it("Refuses a deposit already minted under the legacy receipt seeds", async () => {
+    // Zcash genesis coinbase txid as zcashd and block explorers display it; the
+    // program takes the bytes in internal order, which is the reverse
+    const displayTxid = "c4eaa58879081de3c24a7b117ed2b28300e7ec4c4c1dff1d3f1268b7857a4cf2";
+    const zcashTxid = [...Buffer.from(displayTxid, "hex").reverse()];
+    expect(Buffer.from(zcashTxid).toString("hex")).to.equal(
+      "f24c7a85b768123f1dff1d4c4cece70083b2d27e117b4ac2e31d087988a5eac4"
+    );
+
+    // Anchor.toml seeds a receipt at the address the old seeds give this txid
+    expect(legacyDepositReceiptFor(zcashTxid).toString()).to.equal(
+      "FuG8q5gyEYeu4ZzkB7zNCoN7BAha5jzmUtvtTDW7gT1F"
+    );
 
     try {
       await program.methods
-        .mintWzec(amount, zcashTxid)
+        .mintWzec(new anchor.BN(50_000_000), zcashTxid, null)
         .accounts({
           bridgeState,
+          bridgeStats,
+          guardianSet,
+          depositReceipt: depositReceiptFor(zcashTxid),
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
           mint,
+          mintAuthority,
           recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          feeVault,
           authority,
           tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
         })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers(guardians)
         .rpc();
-      
+
       expect.fail("Should have thrown an error");
     } catch (error) {
-      expect(error.toString()).to.include("BridgePaused");
+      expect(error.toString()).to.include("DepositAlreadyProcessed");
     }
   });
 
-  it("Resumes the bridge", async () => {
-    const tx = await program.methods
-      .resumeBridge()
+  it("Mints wZEC with an off-chain guardian attestation", async () => {
+    const amount = new anchor.BN(25_000_000);
+    const zcashTxid = txidFor("test_txid_attested");
+
+    // Guardian 0 signs off-chain; guardian 1 co-signs the transaction
+    const message = Buffer.concat([
+      Buffer.from("solz-deposit-attestation-testnet"),
+      userTokenAccount.toBuffer(),
+      amount.toArrayLike(Buffer, "le", 8),
+      Buffer.from(zcashTxid),
+    ]);
+    const attestation = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: guardians[0].secretKey,
+      message,
+    });
+
+    await program.methods
+      .mintWzec(amount, zcashTxid, null)
       .accounts({
         bridgeState,
+        bridgeStats,
+        guardianSet,
+        depositReceipt: depositReceiptFor(zcashTxid),
+        legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+        mint,
+        mintAuthority,
+        recipientTokenAccount: userTokenAccount,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        feeVault,
         authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
       })
+      .remainingAccounts(guardianSigners(guardians.slice(1)))
+      .preInstructions([attestation])
+      .signers(guardians.slice(1))
       .rpc();
 
-    console.log("Resume transaction:", tx);
+    const receipt = await program.account.depositReceipt.fetch(depositReceiptFor(zcashTxid));
+    expect(receipt.amount.toString()).to.equal(amount.toString());
+  });
 
-    const state = await program.account.bridgeState.fetch(bridgeState);
-    expect(state.paused).to.be.false;
+  it("Mints a batch of deposits under one guardian attestation", async () => {
+    const recipients = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    const deposits = [
+      { amount: new anchor.BN(5_000_000), zcashTxid: txidFor("test_txid_batch_0") },
+      { amount: new anchor.BN(7_000_000), zcashTxid: txidFor("test_txid_batch_1") },
+    ];
+    const recipientTokenAccounts = [];
+    for (const recipient of recipients) {
+      const account = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        recipient.publicKey
+      );
+      recipientTokenAccounts.push(account.address);
+    }
+
+    // One guardian signature covers every deposit in the batch
+    const message = Buffer.concat([
+      Buffer.from("solz-deposit-batch-attestation-testnet"),
+      ...deposits.map((deposit, i) =>
+        Buffer.concat([
+          recipientTokenAccounts[i].toBuffer(),
+          deposit.amount.toArrayLike(Buffer, "le", 8),
+          Buffer.from(deposit.zcashTxid),
+        ])
+      ),
+    ]);
+    const attestation = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: guardians[0].secretKey,
+      message,
+    });
+
+    const entryAccounts = deposits.flatMap((deposit, i) => [
+      { pubkey: recipientTokenAccounts[i], isSigner: false, isWritable: true },
+      { pubkey: depositReceiptFor(deposit.zcashTxid), isSigner: false, isWritable: true },
+      { pubkey: legacyDepositReceiptFor(deposit.zcashTxid), isSigner: false, isWritable: false },
+      { pubkey: userLimitsFor(recipients[i].publicKey), isSigner: false, isWritable: true },
+      { pubkey: denyListEntryFor(recipients[i].publicKey), isSigner: false, isWritable: false },
+    ]);
+
+    await program.methods
+      .mintWzecBatch(deposits)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        guardianSet,
+        mint,
+        feeVault,
+        mintAuthority,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .remainingAccounts([...entryAccounts, ...guardianSigners(guardians.slice(1))])
+      .preInstructions([attestation])
+      .signers(guardians.slice(1))
+      .rpc();
+
+    for (const [i, deposit] of deposits.entries()) {
+      const receipt = await program.account.depositReceipt.fetch(
+        depositReceiptFor(deposit.zcashTxid)
+      );
+      expect(receipt.amount.toString()).to.equal(deposit.amount.toString());
+      expect(receipt.recipient.toString()).to.equal(recipientTokenAccounts[i].toString());
+    }
+
+    const balance = await provider.connection.getTokenAccountBalance(recipientTokenAccounts[1]);
+    expect(Number(balance.value.amount)).to.be.greaterThan(0);
   });
 
-  it("Updates authority", async () => {
-    const newAuthority = anchor.web3.Keypair.generate().publicKey;
+  it("Lets a recipient claim a deposit from a posted Merkle root", async () => {
+    const other = anchor.web3.Keypair.generate();
+    const claim = { amount: new anchor.BN(3_000_000), zcashTxid: txidFor("test_txid_claim_0") };
+    const sha256 = (...parts: Buffer[]) =>
+      createHash("sha256").update(Buffer.concat(parts)).digest();
+    const leafFor = (owner: anchor.web3.PublicKey, amount: anchor.BN, zcashTxid: number[]) =>
+      sha256(
+        Buffer.from([0]),
+        owner.toBuffer(),
+        amount.toArrayLike(Buffer, "le", 8),
+        Buffer.from(zcashTxid)
+      );
 
-    const tx = await program.methods
-      .updateAuthority(newAuthority)
+    // Two-leaf tree: the user's deposit and one for another wallet
+    const userLeaf = leafFor(user.publicKey, claim.amount, claim.zcashTxid);
+    const otherLeaf = leafFor(other.publicKey, new anchor.BN(4_000_000), txidFor("test_txid_claim_1"));
+    const [left, right] = Buffer.compare(userLeaf, otherLeaf) <= 0
+      ? [userLeaf, otherLeaf]
+      : [otherLeaf, userLeaf];
+    const root = sha256(Buffer.from([1]), left, right);
+    const totalAmount = new anchor.BN(7_000_000);
+
+    const [mintRoot] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_root"), bridgeState.toBuffer(), root],
+      program.programId
+    );
+
+    await program.methods
+      .postMintRoot([...root], totalAmount)
       .accounts({
         bridgeState,
+        guardianSet,
+        mintRoot,
         authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
       })
+      .remainingAccounts(guardianSigners(guardians))
+      .signers(guardians)
       .rpc();
 
-    console.log("Update authority transaction:", tx);
+    const claimAccounts = {
+      bridgeState,
+      bridgeStats,
+      mintRoot,
+      depositReceipt: depositReceiptFor(claim.zcashTxid),
+      legacyDepositReceipt: legacyDepositReceiptFor(claim.zcashTxid),
+      mint,
+      recipientTokenAccount: userTokenAccount,
+      userLimits: userLimitsFor(user.publicKey),
+      denyListEntry: denyListEntryFor(user.publicKey),
+      feeVault,
+      mintAuthority,
+      claimant: user.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
 
-    const state = await program.account.bridgeState.fetch(bridgeState);
-    expect(state.authority.toString()).to.equal(newAuthority.toString());
+    // A proof for the wrong amount doesn't verify
+    try {
+      await program.methods
+        .claimMint(new anchor.BN(4_000_000), claim.zcashTxid, [[...otherLeaf]])
+        .accounts(claimAccounts)
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidMerkleProof");
+    }
+
+    await program.methods
+      .claimMint(claim.amount, claim.zcashTxid, [[...otherLeaf]])
+      .accounts(claimAccounts)
+      .signers([user])
+      .rpc();
+
+    const receipt = await program.account.depositReceipt.fetch(
+      depositReceiptFor(claim.zcashTxid)
+    );
+    expect(receipt.amount.toString()).to.equal(claim.amount.toString());
+
+    const posted = await program.account.mintRoot.fetch(mintRoot);
+    expect(posted.claimedAmount.toString()).to.equal(claim.amount.toString());
   });
-});
 
+  it("Rejects a mint root attested for another bridge", async () => {
+    const root = createHash("sha256").update("cross-bridge mint root").digest();
+    const totalAmount = new anchor.BN(2_000_000);
+    const [mintRoot] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_root"), bridgeState.toBuffer(), root],
+      program.programId
+    );
+    const otherBridgeState = bridgePdasFor(anchor.web3.Keypair.generate().publicKey).bridgeState;
+
+    // One guardian attests off-chain and the other signs, meeting the threshold of two
+    const postAttestedFor = (attestedBridge: anchor.web3.PublicKey) =>
+      program.methods
+        .postMintRoot([...root], totalAmount)
+        .accounts({
+          bridgeState,
+          guardianSet,
+          mintRoot,
+          authority,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians.slice(1)))
+        .preInstructions([
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: guardians[0].secretKey,
+            message: Buffer.concat([
+              Buffer.from("solz-mint-root-attestation-testnet"),
+              attestedBridge.toBuffer(),
+              root,
+              totalAmount.toArrayLike(Buffer, "le", 8),
+            ]),
+          }),
+        ])
+        .signers(guardians.slice(1))
+        .rpc();
+
+    try {
+      await postAttestedFor(otherBridgeState);
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientGuardianSignatures");
+    }
+
+    await postAttestedFor(bridgeState);
+    const posted = await program.account.mintRoot.fetch(mintRoot);
+    expect(posted.totalAmount.toString()).to.equal(totalAmount.toString());
+  });
+
+  it("Lets a bonded relayer submit mints and guardians slash its stake", async () => {
+    const relayer = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      relayer.publicKey,
+      3 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+
+    const [relayerRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("relayer_registry"), bridgeState.toBuffer()],
+      program.programId
+    );
+    const [relayerStake] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("relayer_stake"), bridgeState.toBuffer(), relayer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [relayerStats] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("relayer_stats"), bridgeState.toBuffer(), relayer.publicKey.toBuffer()],
+      program.programId
+    );
+    const minStake = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL);
+
+    await program.methods
+      .initializeRelayerRegistry(minStake, new anchor.BN(0))
+      .accounts({
+        bridgeState,
+        relayerRegistry,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .stakeRelayer(minStake)
+      .accounts({
+        bridgeState,
+        relayerRegistry,
+        relayerStake,
+        relayer: relayer.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([relayer])
+      .rpc();
+
+    await program.methods
+      .initializeRelayerStats()
+      .accounts({
+        bridgeState,
+        relayerStats,
+        relayer: relayer.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([relayer])
+      .rpc();
+
+    // With the minimum bonded, the relayer may submit an attested mint, the relayer may submit an attested mint
+    const zcashTxid = txidFor("test_txid_relayer");
+    await program.methods
+      .mintWzec(new anchor.BN(1_000_000), zcashTxid, null)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        guardianSet,
+        depositReceipt: depositReceiptFor(zcashTxid),
+        legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+        mint,
+        mintAuthority,
+        recipientTokenAccount: userTokenAccount,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        feeVault,
+        relayerRegistry,
+        relayerStake,
+        relayerStats,
+        authority: relayer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .remainingAccounts(guardianSigners(guardians))
+      .signers([relayer, ...guardians])
+      .rpc();
+
+    let relayerActivity = await program.account.relayerStats.fetch(relayerStats);
+    expect(relayerActivity.mintsSubmitted.toNumber()).to.equal(1);
+    expect(relayerActivity.mintedVolume.toNumber()).to.equal(1_000_000);
+    expect(relayerActivity.lastActivitySlot.toNumber()).to.be.greaterThan(0);
+
+    // Guardians slash half the bond, which drops the relayer below the minimum
+    const evidence = createHash("sha256").update("bad attestation").digest();
+    await program.methods
+      .slashRelayer(minStake.divn(2), [...evidence])
+      .accounts({
+        bridgeState,
+        guardianSet,
+        relayerRegistry,
+        relayerStake,
+      })
+      .remainingAccounts(guardianSigners(guardians))
+      .signers(guardians)
+      .rpc();
+
+    const stake = await program.account.relayerStake.fetch(relayerStake);
+    expect(stake.staked.toString()).to.equal(minStake.divn(2).toString());
+    expect(stake.slashed.toString()).to.equal(minStake.divn(2).toString());
+
+    let registry = await program.account.relayerRegistry.fetch(relayerRegistry);
+    expect(registry.slashedLamports.toString()).to.equal(minStake.divn(2).toString());
+
+    // The authority sweeps the confiscated lamports out of the registry
+    const registryBalance = await provider.connection.getBalance(relayerRegistry);
+    await program.methods
+      .sweepSlashedLamports()
+      .accounts({
+        bridgeState,
+        relayerRegistry,
+        authority,
+      })
+      .rpc();
+
+    registry = await program.account.relayerRegistry.fetch(relayerRegistry);
+    expect(registry.slashedLamports.toNumber()).to.equal(0);
+    expect(await provider.connection.getBalance(relayerRegistry)).to.equal(
+      registryBalance - minStake.divn(2).toNumber()
+    );
+
+    try {
+      const zcashTxid = txidFor("test_txid_relayer_slashed");
+      await program.methods
+        .mintWzec(new anchor.BN(1_000_000), zcashTxid, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          guardianSet,
+          depositReceipt: depositReceiptFor(zcashTxid),
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+          mint,
+          mintAuthority,
+          recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          feeVault,
+          relayerRegistry,
+          relayerStake,
+          relayerStats,
+          authority: relayer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers([relayer, ...guardians])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+  });
+
+  it("Rejects a mainnet address on a testnet bridge", async () => {
+    try {
+      await program.methods
+        .burnWzec(new anchor.BN(5_000_000), mainnetZecAddress, null, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest: await nextWithdrawalRequest(),
+          mint,
+          userTokenAccount,
+          feeVault,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidZecAddress");
+    }
+  });
+
+  it("Validates each address kind on a mainnet bridge", async () => {
+    const zecMintKeypair = anchor.web3.Keypair.generate();
+    const zec = bridgePdasFor(zecMintKeypair.publicKey);
+    const zecMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      zec.mintAuthority,
+      null,
+      8,
+      zecMintKeypair
+    );
+    const zecFeeVault = getAssociatedTokenAddressSync(zecMint, zec.bridgeState, true);
+    const zecPda = (...seeds: Buffer[]) =>
+      anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const zecGuardianSet = zecPda(Buffer.from("guardian_set"), zec.bridgeState.toBuffer());
+    const zecUserLimits = zecPda(
+      Buffer.from("user_limits"),
+      zec.bridgeState.toBuffer(),
+      user.publicKey.toBuffer()
+    );
+    const zecDenyListEntry = zecPda(
+      Buffer.from("deny_list"),
+      zec.bridgeState.toBuffer(),
+      user.publicKey.toBuffer()
+    );
+
+    await program.methods
+      .initialize(0, 0, { mainnet: {} })
+      .accounts({
+        bridgeState: zec.bridgeState,
+        bridgeStats: zec.bridgeStats,
+        mint: zecMint,
+        mintAuthority: zec.mintAuthority,
+        feeVault: zecFeeVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .initializeGuardianSet(
+        guardians.map((guardian) => guardian.publicKey),
+        2
+      )
+      .accounts({
+        bridgeState: zec.bridgeState,
+        guardianSet: zecGuardianSet,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const zecTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        zecMint,
+        user.publicKey
+      )
+    ).address;
+    const zcashTxid = txidFor("test_txid_mainnet_addresses");
+    await program.methods
+      .mintWzec(new anchor.BN(100_000_000), zcashTxid, null)
+      .accounts({
+        bridgeState: zec.bridgeState,
+        bridgeStats: zec.bridgeStats,
+        guardianSet: zecGuardianSet,
+        depositReceipt: zecPda(Buffer.from("deposit"), zec.bridgeState.toBuffer(), Buffer.from(zcashTxid)),
+        legacyDepositReceipt: zecPda(
+          Buffer.from("deposit"),
+          zec.bridgeState.toBuffer(),
+          createHash("sha256").update(Buffer.from(zcashTxid).reverse().toString("hex")).digest()
+        ),
+        mint: zecMint,
+        mintAuthority: zec.mintAuthority,
+        recipientTokenAccount: zecTokenAccount,
+        userLimits: zecUserLimits,
+        denyListEntry: zecDenyListEntry,
+        feeVault: zecFeeVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .remainingAccounts(guardianSigners(guardians))
+      .signers(guardians)
+      .rpc();
+
+    const burnTo = async (zecAddress: string) => {
+      const state = await program.account.bridgeState.fetch(zec.bridgeState);
+      await program.methods
+        .burnWzec(new anchor.BN(1_000_000), zecAddress, null, null)
+        .accounts({
+          bridgeState: zec.bridgeState,
+          bridgeStats: zec.bridgeStats,
+          withdrawalRequest: zecPda(
+            Buffer.from("withdrawal"),
+            zec.bridgeState.toBuffer(),
+            state.withdrawalNonce.toArrayLike(Buffer, "le", 8)
+          ),
+          mint: zecMint,
+          userTokenAccount: zecTokenAccount,
+          feeVault: zecFeeVault,
+          userLimits: zecUserLimits,
+          denyListEntry: zecDenyListEntry,
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    };
+
+    // Sapling, unified (P2PKH, Sapling and Orchard receivers), P2PKH and P2SH
+    const accepted = [
+      mainnetZecAddress,
+      "u1xqvav52hqhjsas5vwtncd7vrdnyaw6v968985ds9c8yv6amjezndgqzz7cje6cy8hdlnrflnvy84yln07nmftf5gt2s3medxtmu3twpd908yajfqeaemzdpycpkt3enjlj5ye9z56whzqth4k456ursxdp9smsdsttam5uezmnw9msdsz49p7e4sxphxqqj36ym3t2a5kwy7yjj46t9",
+      "t1SmxPaFcF5kYFn5ctgCt7QiaXzpTZyF4kG",
+      "t3bXfhDDYoFtXJRTuwUn7ttQYDZMCrrS23T",
+    ];
+    for (const zecAddress of accepted) {
+      await burnTo(zecAddress);
+    }
+
+    const rejected = {
+      // Last character altered, breaking the bech32 / Base58Check checksum
+      saplingBadChecksum: mainnetZecAddress.slice(0, -1) + "h",
+      transparentBadChecksum: "t1SmxPaFcF5kYFn5ctgCt7QiaXzpTZyF4kH",
+      // Unified addresses with valid bech32m checksums but malformed contents
+      unifiedTransparentOnly:
+        "u18mwjdutxhuup5jcveh9unvf483u742dsga7ftmjapk9ra0mdn8f5wg92n708uzqttuv9jx4978sq3kkqlue8jq",
+      unifiedWrongPadding:
+        "u1ggptn0n2x3lt2s6u0cuh2h8gyety5e0j3jvpwt35zgfkdu98nwalp34ma95ykl6jk3lyp2htlkp63aek7wtclf5mr0mftn529cflr3jdysc9m9e3dpq7c002vmugdh437antzuswp4x",
+      unifiedUnordered:
+        "u1wryw4wlpafr8kn8e5dwf6uks90y0h5gdpuhl402p6e3k4u54543p5yp0vek3ndfdrtlqec204a529swphctljvaknllwnshju46y4muhauze9m0vy2pgku4pm9ew2kpn2nngv6s5hn0",
+      unifiedShortReceiver:
+        "u1ln0n9q5wlhjs8xf3exmmym3qnkzusnsg3qsnngv066hcv0h6xduzhengcadtnr57lkpp44nqunnwsfped5k47u260jrt30l90xqjc2l4h5vnz6e5zx95utaut8xwdezc9r824tvm3yhy407vl0wjrdsh6ad8hwh2v0ggeay9p53rum",
+      // Well-formed, but for testnet
+      testnetUnified:
+        "utest1gv9f2kn833uj09vqt5t64rpep3ymq5hv4z9zsrphkjam9sn3egevd0737lt6f696zk08jcfqyheddq9nnykqxg077a0tztkyru3zee9stl4eawxwsw2kr2n3p06zms6d4yake2u9tdcqw7ljgudfhhumygttnfu86wvg8ayljq8xvg3h",
+      testnetSapling: testnetZecAddress,
+    };
+    for (const [kind, zecAddress] of Object.entries(rejected)) {
+      try {
+        await burnTo(zecAddress);
+
+        expect.fail(`Should have rejected ${kind}`);
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidZecAddress");
+      }
+    }
+
+    const state = await program.account.bridgeState.fetch(zec.bridgeState);
+    expect(state.withdrawalNonce.toNumber()).to.equal(accepted.length);
+  });
+
+  it("Rejects a memo longer than a Zcash note memo", async () => {
+    try {
+      await program.methods
+        .burnWzec(new anchor.BN(5_000_000), testnetZecAddress, Buffer.alloc(513), null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest: await nextWithdrawalRequest(),
+          mint,
+          userTokenAccount,
+          feeVault,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("MemoTooLong");
+    }
+  });
+
+  it("Refuses a burn when the fee is above the caller's maximum", async () => {
+    // The burn fee is 10 bps
+    try {
+      await program.methods
+        .burnWzec(new anchor.BN(5_000_000), testnetZecAddress, null, 9)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest: await nextWithdrawalRequest(),
+          mint,
+          userTokenAccount,
+          feeVault,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("FeeExceedsMaximum");
+    }
+  });
+
+  it("Burns wZEC tokens", async () => {
+    const amount = new anchor.BN(50_000_000); // 0.5 wZEC
+    const zecAddress = testnetZecAddress;
+    const memo = Buffer.from("invoice 42");
+    const withdrawalRequest = await nextWithdrawalRequest();
+
+    const tx = await program.methods
+      .burnWzec(amount, zecAddress, memo, 10)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        withdrawalRequest,
+        mint,
+        userTokenAccount,
+        feeVault,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    console.log("Burn transaction:", tx);
+
+    // Fetch updated bridge stats
+    const stats = await program.account.bridgeStats.fetch(bridgeStats);
+    expect(stats.totalBurned.toNumber()).to.be.greaterThan(0);
+
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.user.toString()).to.equal(user.publicKey.toString());
+    expect(request.amount.toString()).to.equal(amount.toString());
+    expect(request.netAmount.toString()).to.equal("49950000");
+    expect(request.zecAddress).to.equal(zecAddress);
+    expect(Buffer.from(request.memo).toString()).to.equal("invoice 42");
+    expect(request.status).to.deep.equal({ pending: {} });
+
+    // Sized to the address and memo it holds rather than their maximums
+    const requestAccount = await provider.connection.getAccountInfo(withdrawalRequest);
+    expect(requestAccount.data.length).to.equal(8 + 191 + zecAddress.length + memo.length);
+
+    // 0.1% of the burn is kept in the fee vault
+    const vault = await getAccount(provider.connection, feeVault);
+    expect(vault.amount.toString()).to.equal("50000");
+  });
+
+  it("Finalizes a withdrawal with the Zcash payout txid", async () => {
+    const withdrawalRequest = withdrawalRequestFor(new anchor.BN(0));
+    const payoutTxid = Array.from(Buffer.alloc(32, 7));
+
+    await program.methods
+      .finalizeWithdrawal(payoutTxid, 2_500_000, 0)
+      .accounts({
+        bridgeState,
+        withdrawalRequest,
+        authority,
+      })
+      .rpc();
+
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.status).to.deep.equal({ completed: {} });
+    expect(request.payoutTxid).to.deep.equal(payoutTxid);
+    expect(request.payoutHeight).to.equal(2_500_000);
+  });
+
+  it("Withdraws collected fees to the treasury", async () => {
+    const treasury = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      authority
+    );
+
+    await program.methods
+      .updateTreasury(treasury.address)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    await program.methods
+      .withdrawFees(new anchor.BN(50_000))
+      .accounts({
+        bridgeState,
+        mint,
+        feeVault,
+        treasuryTokenAccount: treasury.address,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const vault = await getAccount(provider.connection, feeVault);
+    expect(vault.amount.toString()).to.equal("0");
+
+    const treasuryAccount = await getAccount(provider.connection, treasury.address);
+    expect(treasuryAccount.amount.toString()).to.equal("50000");
+  });
+
+  it("Cancels a withdrawal and refunds the user", async () => {
+    const withdrawalRequest = await nextWithdrawalRequest();
+
+    await program.methods
+      .burnWzec(
+        new anchor.BN(5_000_000),
+        testnetZecAddress,
+        null,
+        null
+      )
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        withdrawalRequest,
+        mint,
+        userTokenAccount,
+        feeVault,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    const before = await getAccount(provider.connection, userTokenAccount);
+
+    await program.methods
+      .cancelWithdrawal()
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        withdrawalRequest,
+        mint,
+        mintAuthority,
+        userTokenAccount,
+        feeVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.status).to.deep.equal({ cancelled: {} });
+
+    // No cancellation fee is configured, so the full net amount comes back
+    const after = await getAccount(provider.connection, userTokenAccount);
+    expect((after.amount - before.amount).toString()).to.equal(request.netAmount.toString());
+  });
+
+  it("Enforces the per-transaction burn cap", async () => {
+    await program.methods
+      .updateTransactionLimits(new anchor.BN(1_000_000_000), new anchor.BN(10_000_000))
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .burnWzec(
+          new anchor.BN(20_000_000),
+          testnetZecAddress,
+          null,
+          null
+        )
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest: await nextWithdrawalRequest(),
+          mint,
+          userTokenAccount,
+          feeVault,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("BurnAmountExceedsLimit");
+    }
+
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.maxMintAmount.toString()).to.equal("1000000000");
+    expect(state.maxBurnAmount.toString()).to.equal("10000000");
+  });
+
+  it("Rejects burns below the dust threshold", async () => {
+    await program.methods
+      .updateMinBurnAmount(new anchor.BN(10_000))
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .burnWzec(new anchor.BN(5_000), testnetZecAddress, null, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest: await nextWithdrawalRequest(),
+          mint,
+          userTokenAccount,
+          feeVault,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("BurnAmountBelowMinimum");
+    }
+
+    // Later tests make small burns, so lift the threshold again
+    await program.methods
+      .updateMinBurnAmount(new anchor.BN(0))
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+  });
+
+  it("Timelocks large withdrawals", async () => {
+    await program.methods
+      .updateWithdrawalTimelock(new anchor.BN(1_000_000), new anchor.BN(1_000_000))
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    const withdrawalRequest = await nextWithdrawalRequest();
+
+    await program.methods
+      .burnWzec(
+        new anchor.BN(2_000_000),
+        testnetZecAddress,
+        null,
+        null
+      )
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        withdrawalRequest,
+        mint,
+        userTokenAccount,
+        feeVault,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.unlockSlot.sub(request.createdSlot).toNumber()).to.equal(1_000_000);
+
+    try {
+      await program.methods
+        .finalizeWithdrawal(Array.from(Buffer.alloc(32, 9)), 2_500_001, null)
+        .accounts({
+          bridgeState,
+          withdrawalRequest,
+          authority,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("WithdrawalTimelocked");
+    }
+  });
+
+  it("Lets a guardian veto a timelocked withdrawal and a quorum lift it", async () => {
+    // Withdrawal 2 was created by the timelock test above
+    const withdrawalRequest = withdrawalRequestFor(new anchor.BN(2));
+
+    await program.methods
+      .vetoWithdrawal(1)
+      .accounts({
+        bridgeState,
+        guardianSet,
+        withdrawalRequest,
+        guardian: guardians[0].publicKey,
+      })
+      .signers([guardians[0]])
+      .rpc();
+
+    let request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.status).to.deep.equal({ vetoed: {} });
+    expect(request.vetoReason).to.equal(1);
+
+    await program.methods
+      .unvetoWithdrawal()
+      .accounts({
+        bridgeState,
+        guardianSet,
+        withdrawalRequest,
+      })
+      .remainingAccounts(guardianSigners(guardians))
+      .signers(guardians)
+      .rpc();
+
+    request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.status).to.deep.equal({ pending: {} });
+  });
+
+  it("Cancels a vetoed withdrawal and refunds the user", async () => {
+    // Still above the timelock threshold set above, so a guardian can veto it
+    const withdrawalRequest = await nextWithdrawalRequest();
+    await program.methods
+      .burnWzec(new anchor.BN(2_000_000), testnetZecAddress, null, null)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        withdrawalRequest,
+        mint,
+        userTokenAccount,
+        feeVault,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    await program.methods
+      .vetoWithdrawal(2)
+      .accounts({
+        bridgeState,
+        guardianSet,
+        withdrawalRequest,
+        guardian: guardians[0].publicKey,
+      })
+      .signers([guardians[0]])
+      .rpc();
+
+    const before = await getAccount(provider.connection, userTokenAccount);
+    await program.methods
+      .cancelWithdrawal()
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        withdrawalRequest,
+        mint,
+        mintAuthority,
+        userTokenAccount,
+        feeVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.status).to.deep.equal({ cancelled: {} });
+    const after = await getAccount(provider.connection, userTokenAccount);
+    expect((after.amount - before.amount).toString()).to.equal(request.netAmount.toString());
+  });
+
+  it("Pays a relayer its share of the burn fee for each withdrawal it finalizes", async () => {
+    const relayer = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      relayer.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+
+    const [relayerRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("relayer_registry"), bridgeState.toBuffer()],
+      program.programId
+    );
+    const [relayerStake] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("relayer_stake"), bridgeState.toBuffer(), relayer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [relayerStats] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("relayer_stats"), bridgeState.toBuffer(), relayer.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .updateRelayerRewardShare(5_000)
+      .accounts({
+        bridgeState,
+        relayerRegistry,
+        authority,
+      })
+      .rpc();
+
+    await program.methods
+      .stakeRelayer(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({
+        bridgeState,
+        relayerRegistry,
+        relayerStake,
+        relayer: relayer.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([relayer])
+      .rpc();
+
+    // Below the timelock threshold, so it can be finalized right away
+    const withdrawalRequest = await nextWithdrawalRequest();
+    await program.methods
+      .burnWzec(
+        new anchor.BN(500_000),
+        testnetZecAddress,
+        null,
+        null
+      )
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        withdrawalRequest,
+        mint,
+        userTokenAccount,
+        feeVault,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    // Bonded relayers can't finalize without recording it in their stats
+    try {
+      await program.methods
+        .finalizeWithdrawal(Array.from(Buffer.alloc(32, 11)), 2_500_002, null)
+        .accounts({
+          bridgeState,
+          withdrawalRequest,
+          relayerRegistry,
+          relayerStake,
+          authority: relayer.publicKey,
+        })
+        .signers([relayer])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("RelayerStatsRequired");
+    }
+
+    await program.methods
+      .initializeRelayerStats()
+      .accounts({
+        bridgeState,
+        relayerStats,
+        relayer: relayer.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([relayer])
+      .rpc();
+    await program.methods
+      .finalizeWithdrawal(Array.from(Buffer.alloc(32, 11)), 2_500_002, null)
+      .accounts({
+        bridgeState,
+        withdrawalRequest,
+        relayerRegistry,
+        relayerStake,
+        relayerStats,
+        authority: relayer.publicKey,
+      })
+      .signers([relayer])
+      .rpc();
+
+    const relayerActivity = await program.account.relayerStats.fetch(relayerStats);
+    expect(relayerActivity.withdrawalsFinalized.toNumber()).to.equal(1);
+    expect(relayerActivity.finalizedVolume.toNumber()).to.equal(499_500);
+
+    // 0.1% burn fee on 500_000 is 500, half of which goes to the relayer
+    const stake = await program.account.relayerStake.fetch(relayerStake);
+    expect(stake.finalizedWithdrawals.toNumber()).to.equal(1);
+    expect(stake.pendingRewards.toNumber()).to.equal(250);
+
+    // The reward stays reserved in the vault; no registry account is needed to enforce it
+    let state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.unclaimedRelayerRewards.toNumber()).to.equal(250);
+    const vault = await getAccount(provider.connection, feeVault);
+    try {
+      await program.methods
+        .withdrawFees(new anchor.BN(vault.amount.toString()))
+        .accounts({
+          bridgeState,
+          mint,
+          feeVault,
+          treasuryTokenAccount: getAssociatedTokenAddressSync(mint, authority),
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidAmount");
+    }
+
+    const relayerTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      relayer.publicKey
+    );
+    await program.methods
+      .claimRelayerRewards()
+      .accounts({
+        bridgeState,
+        relayerRegistry,
+        relayerStake,
+        mint,
+        feeVault,
+        relayerTokenAccount: relayerTokenAccount.address,
+        relayer: relayer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([relayer])
+      .rpc();
+
+    const paid = await getAccount(provider.connection, relayerTokenAccount.address);
+    expect(paid.amount.toString()).to.equal("250");
+
+    state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.unclaimedRelayerRewards.toNumber()).to.equal(0);
+  });
+
+  it("Enforces the rolling daily mint limit", async () => {
+    // 1.41 wZEC has already been minted within the window
+    await program.methods
+      .updateDailyMintLimit(new anchor.BN(150_000_000))
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    const amount = new anchor.BN(50_000_000);
+    const zcashTxid = txidFor("test_txid_rate_limited");
+
+    try {
+      await program.methods
+        .mintWzec(amount, zcashTxid, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          guardianSet,
+          depositReceipt: depositReceiptFor(zcashTxid),
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+          mint,
+          mintAuthority,
+          recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          feeVault,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers(guardians)
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("RateLimitExceeded");
+    }
+
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.dailyMintLimit.toString()).to.equal("150000000");
+  });
+
+  it("Trips the circuit breaker on abnormal mint volume", async () => {
+    // 1.41 wZEC has been minted since initialization
+    await program.methods
+      .updateCircuitBreaker(new anchor.BN(100_000_000), 24)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    const amount = new anchor.BN(10_000_000);
+    const zcashTxid = txidFor("test_txid_circuit_breaker");
+
+    await program.methods
+      .mintWzec(amount, zcashTxid, null)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        guardianSet,
+        depositReceipt: depositReceiptFor(zcashTxid),
+        legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+        mint,
+        mintAuthority,
+        recipientTokenAccount: userTokenAccount,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        feeVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .remainingAccounts(guardianSigners(guardians))
+      .signers(guardians)
+      .rpc();
+
+    let state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.circuitBreakerTripped).to.be.true;
+    expect(state.mintsPaused).to.be.true;
+
+    await program.methods
+      .resetCircuitBreaker()
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        guardianSet,
+        caller: authority,
+      })
+      .rpc();
+
+    state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.circuitBreakerTripped).to.be.false;
+    expect(state.mintsPaused).to.be.false;
+  });
+
+  it("Clears a tripped circuit breaker when mints are resumed by hand", async () => {
+    const breaker = await initializeBridgeFor({ testnet: {} }, 0);
+    const breakerGuardianSet = pdaFor("guardian_set", breaker.bridgeState.toBuffer());
+    await program.methods
+      .initializeGuardianSet(
+        guardians.map((guardian) => guardian.publicKey),
+        2
+      )
+      .accounts({
+        bridgeState: breaker.bridgeState,
+        guardianSet: breakerGuardianSet,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .updateCircuitBreaker(new anchor.BN(15_000_000), 24)
+      .accounts({
+        bridgeState: breaker.bridgeState,
+        authority,
+      })
+      .rpc();
+
+    const recipientTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        breaker.mint,
+        user.publicKey
+      )
+    ).address;
+    const mintDeposit = (label: string) => {
+      const zcashTxid = txidFor(label);
+      return program.methods
+        .mintWzec(new anchor.BN(10_000_000), zcashTxid, null)
+        .accounts({
+          bridgeState: breaker.bridgeState,
+          bridgeStats: breaker.bridgeStats,
+          guardianSet: breakerGuardianSet,
+          depositReceipt: pdaFor("deposit", breaker.bridgeState.toBuffer(), Buffer.from(zcashTxid)),
+          legacyDepositReceipt: pdaFor(
+            "deposit",
+            breaker.bridgeState.toBuffer(),
+            createHash("sha256").update(Buffer.from(zcashTxid).reverse().toString("hex")).digest()
+          ),
+          mint: breaker.mint,
+          mintAuthority: breaker.mintAuthority,
+          recipientTokenAccount,
+          userLimits: pdaFor("user_limits", breaker.bridgeState.toBuffer(), user.publicKey.toBuffer()),
+          denyListEntry: pdaFor("deny_list", breaker.bridgeState.toBuffer(), user.publicKey.toBuffer()),
+          feeVault: breaker.feeVault,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers(guardians)
+        .rpc();
+    };
+
+    await mintDeposit("test_txid_breaker_manual_0");
+    await mintDeposit("test_txid_breaker_manual_1");
+    let state = await program.account.bridgeState.fetch(breaker.bridgeState);
+    expect(state.circuitBreakerTripped).to.be.true;
+
+    await program.methods
+      .setPauseFlags(false, false)
+      .accounts({
+        bridgeState: breaker.bridgeState,
+        bridgeStats: breaker.bridgeStats,
+        authority,
+      })
+      .rpc();
+
+    state = await program.account.bridgeState.fetch(breaker.bridgeState);
+    expect(state.circuitBreakerTripped).to.be.false;
+    expect(state.mintsPaused).to.be.false;
+
+    // The window restarted, so the next mint stays under the threshold
+    await mintDeposit("test_txid_breaker_manual_2");
+    state = await program.account.bridgeState.fetch(breaker.bridgeState);
+    expect(state.circuitBreakerTripped).to.be.false;
+  });
+
+  it("Enforces per-user limits until they are removed", async () => {
+    const userLimits = userLimitsFor(user.publicKey);
+
+    await program.methods
+      .setUserLimits(user.publicKey, new anchor.BN(1_000_000_000), new anchor.BN(1_000))
+      .accounts({
+        bridgeState,
+        userLimits,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const limits = await program.account.userLimits.fetch(userLimits);
+    expect(limits.burnLimit.toString()).to.equal("1000");
+
+    try {
+      await program.methods
+        .burnWzec(
+          new anchor.BN(5_000),
+          testnetZecAddress,
+          null,
+          null
+        )
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest: await nextWithdrawalRequest(),
+          mint,
+          userTokenAccount,
+          feeVault,
+          userLimits,
+          denyListEntry: denyListEntryFor(user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("RateLimitExceeded");
+    }
+
+    await program.methods
+      .removeUserLimits(user.publicKey)
+      .accounts({
+        bridgeState,
+        userLimits,
+        authority,
+      })
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(userLimits)).to.be.null;
+  });
+
+  it("Rejects a fee above the maximum", async () => {
+    try {
+      await program.methods
+        .updateBurnFee(501)
+        .accounts({
+          bridgeState,
+          authority,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("FeeTooHigh");
+    }
+  });
+
+  it("Updates mint and burn fees independently", async () => {
+    await program.methods
+      .updateMintFee(5)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    await program.methods
+      .updateBurnFee(20)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.mintFeeBps).to.equal(5);
+    expect(state.burnFeeBps).to.equal(20);
+  });
+
+  it("Queues parameter changes behind the change timelock", async () => {
+    await program.methods
+      .updateTimelockSlots(new anchor.BN(16))
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    // Direct updates are refused while a timelock is set
+    try {
+      await program.methods
+        .updateMintFee(7)
+        .accounts({
+          bridgeState,
+          authority,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("ChangeTimelocked");
+    }
+
+    let state = await program.account.bridgeState.fetch(bridgeState);
+    const feeChangeId = state.nextChangeId;
+    const feeChange = pendingChangeFor(feeChangeId);
+    await program.methods
+      .queueChange({ fee: { kind: { mint: {} }, feeBps: 7 } })
+      .accounts({
+        bridgeState,
+        pendingChange: feeChange,
+        proposer: authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const pending = await program.account.pendingChange.fetch(feeChange);
+    expect(pending.executableSlot.sub(pending.queuedSlot).toNumber()).to.equal(16);
+
+    try {
+      await program.methods
+        .executeChange(feeChangeId)
+        .accounts({
+          bridgeState,
+          pendingChange: feeChange,
+          proposer: authority,
+          guardianSet: null,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("ChangeNotReady");
+    }
+
+    // Lowering the timelock has to wait out the current one too
+    const timelockChangeId = feeChangeId.addn(1);
+    const timelockChange = pendingChangeFor(timelockChangeId);
+    await program.methods
+      .queueChange({ timelockSlots: { timelockSlots: new anchor.BN(0) } })
+      .accounts({
+        bridgeState,
+        pendingChange: timelockChange,
+        proposer: authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .cancelPendingChange(feeChangeId)
+      .accounts({
+        bridgeState,
+        pendingChange: feeChange,
+        proposer: authority,
+        caller: authority,
+      })
+      .rpc();
+    expect(await provider.connection.getAccountInfo(feeChange)).to.be.null;
+
+    const { executableSlot } = await program.account.pendingChange.fetch(timelockChange);
+    while ((await provider.connection.getSlot()) < executableSlot.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+
+    await program.methods
+      .executeChange(timelockChangeId)
+      .accounts({
+        bridgeState,
+        pendingChange: timelockChange,
+        proposer: authority,
+        guardianSet: null,
+      })
+      .rpc();
+
+    state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.timelockSlots.toNumber()).to.equal(0);
+    expect(state.mintFeeBps).to.equal(5);
+    expect(await provider.connection.getAccountInfo(timelockChange)).to.be.null;
+  });
+
+  it("Burns the gross amount that pays out an exact ZEC amount", async () => {
+    const withdrawalRequest = await nextWithdrawalRequest();
+
+    await program.methods
+      .burnWzecExactOut(new anchor.BN(1_000_000), testnetZecAddress, null, 20)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        withdrawalRequest,
+        mint,
+        userTokenAccount,
+        feeVault,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    // 20 bps of 1,002,004 rounds down to 2,004, leaving exactly 1,000,000
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.amount.toString()).to.equal("1002004");
+    expect(request.fee.toString()).to.equal("2004");
+    expect(request.netAmount.toString()).to.equal("1000000");
+  });
+
+  it("Burns without a fee for a user with a fee exemption", async () => {
+    const feeExemption = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_exemption"), bridgeState.toBuffer(), user.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+    await program.methods
+      .setFeeExemption(user.publicKey, 0, 0)
+      .accounts({
+        bridgeState,
+        feeExemption,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const withdrawalRequest = await nextWithdrawalRequest();
+    await program.methods
+      .burnWzec(new anchor.BN(1_000_000), testnetZecAddress, null, 0)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        withdrawalRequest,
+        mint,
+        userTokenAccount,
+        feeVault,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([{ pubkey: feeExemption, isSigner: false, isWritable: false }])
+      .signers([user])
+      .rpc();
+
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.fee.toNumber()).to.equal(0);
+    expect(request.netAmount.toNumber()).to.equal(1_000_000);
+
+    await program.methods
+      .removeFeeExemption(user.publicKey)
+      .accounts({
+        bridgeState,
+        feeExemption,
+        authority,
+      })
+      .rpc();
+
+    expect(await program.account.feeExemption.fetchNullable(feeExemption)).to.be.null;
+  });
+
+  it("Mints a batch at each recipient's exempt rate", async () => {
+    const [exempt, charged] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    const feeExemption = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_exemption"), bridgeState.toBuffer(), exempt.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    await program.methods
+      .setFeeExemption(exempt.publicKey, 0, 0)
+      .accounts({
+        bridgeState,
+        feeExemption,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const recipients = [exempt, charged];
+    const deposits = [
+      { amount: new anchor.BN(2_000_000), zcashTxid: txidFor("test_txid_batch_exempt_0") },
+      { amount: new anchor.BN(2_000_000), zcashTxid: txidFor("test_txid_batch_exempt_1") },
+    ];
+    const recipientTokenAccounts = [];
+    for (const recipient of recipients) {
+      const account = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        recipient.publicKey
+      );
+      recipientTokenAccounts.push(account.address);
+    }
+
+    const message = Buffer.concat([
+      Buffer.from("solz-deposit-batch-attestation-testnet"),
+      ...deposits.map((deposit, i) =>
+        Buffer.concat([
+          recipientTokenAccounts[i].toBuffer(),
+          deposit.amount.toArrayLike(Buffer, "le", 8),
+          Buffer.from(deposit.zcashTxid),
+        ])
+      ),
+    ]);
+    const attestation = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: guardians[0].secretKey,
+      message,
+    });
+
+    const entryAccounts = deposits.flatMap((deposit, i) => [
+      { pubkey: recipientTokenAccounts[i], isSigner: false, isWritable: true },
+      { pubkey: depositReceiptFor(deposit.zcashTxid), isSigner: false, isWritable: true },
+      { pubkey: legacyDepositReceiptFor(deposit.zcashTxid), isSigner: false, isWritable: false },
+      { pubkey: userLimitsFor(recipients[i].publicKey), isSigner: false, isWritable: true },
+      { pubkey: denyListEntryFor(recipients[i].publicKey), isSigner: false, isWritable: false },
+    ]);
+
+    await program.methods
+      .mintWzecBatch(deposits)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        guardianSet,
+        mint,
+        feeVault,
+        mintAuthority,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .remainingAccounts([
+        ...entryAccounts,
+        ...guardianSigners(guardians.slice(1)),
+        { pubkey: feeExemption, isSigner: false, isWritable: false },
+      ])
+      .preInstructions([attestation])
+      .signers(guardians.slice(1))
+      .rpc();
+
+    // 5 bps of 2,000,000 is 1,000, charged only to the recipient without an exemption
+    const balances = [];
+    for (const account of recipientTokenAccounts) {
+      balances.push((await provider.connection.getTokenAccountBalance(account)).value.amount);
+    }
+    expect(balances).to.deep.equal(["2000000", "1999000"]);
+  });
+
+  it("Claims a deposit from a Merkle root at the claimant's exempt rate", async () => {
+    const claim = { amount: new anchor.BN(2_000_000), zcashTxid: txidFor("test_txid_claim_exempt") };
+    const sha256 = (...parts: Buffer[]) =>
+      createHash("sha256").update(Buffer.concat(parts)).digest();
+    const leafFor = (owner: anchor.web3.PublicKey, amount: anchor.BN, zcashTxid: number[]) =>
+      sha256(
+        Buffer.from([0]),
+        owner.toBuffer(),
+        amount.toArrayLike(Buffer, "le", 8),
+        Buffer.from(zcashTxid)
+      );
+    const userLeaf = leafFor(user.publicKey, claim.amount, claim.zcashTxid);
+    const otherLeaf = leafFor(
+      anchor.web3.Keypair.generate().publicKey,
+      new anchor.BN(1_000_000),
+      txidFor("test_txid_claim_exempt_other")
+    );
+    const [left, right] = Buffer.compare(userLeaf, otherLeaf) <= 0
+      ? [userLeaf, otherLeaf]
+      : [otherLeaf, userLeaf];
+    const root = sha256(Buffer.from([1]), left, right);
+    const mintRoot = pdaFor("mint_root", bridgeState.toBuffer(), root);
+
+    await program.methods
+      .postMintRoot([...root], new anchor.BN(3_000_000))
+      .accounts({
+        bridgeState,
+        guardianSet,
+        mintRoot,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .remainingAccounts(guardianSigners(guardians))
+      .signers(guardians)
+      .rpc();
+
+    const feeExemption = await exemptFromFees(bridgeState, user.publicKey);
+    const before = (await getAccount(provider.connection, userTokenAccount)).amount;
+    await program.methods
+      .claimMint(claim.amount, claim.zcashTxid, [[...otherLeaf]])
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        mintRoot,
+        depositReceipt: depositReceiptFor(claim.zcashTxid),
+        legacyDepositReceipt: legacyDepositReceiptFor(claim.zcashTxid),
+        mint,
+        recipientTokenAccount: userTokenAccount,
+        userLimits: userLimitsFor(user.publicKey),
+        denyListEntry: denyListEntryFor(user.publicKey),
+        feeVault,
+        mintAuthority,
+        claimant: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([feeExemption])
+      .signers([user])
+      .rpc();
+
+    // The bridge charges 5 bps; the exemption waives it
+    const after = (await getAccount(provider.connection, userTokenAccount)).amount;
+    expect((after - before).toString()).to.equal(claim.amount.toString());
+
+    await program.methods
+      .removeFeeExemption(user.publicKey)
+      .accounts({
+        bridgeState,
+        feeExemption: feeExemption.pubkey,
+        authority,
+      })
+      .rpc();
+  });
+
+  it("Pays a share of burn fees into the insurance fund and claims out of it", async () => {
+    const [insuranceAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("insurance"), bridgeState.toBuffer()],
+      program.programId
+    );
+    const insuranceVault = getAssociatedTokenAddressSync(mint, insuranceAuthority, true);
+
+    await program.methods
+      .initializeInsuranceFund()
+      .accounts({
+        bridgeState,
+        mint,
+        insuranceAuthority,
+        insuranceVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .updateInsuranceShare(5000)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    const burnAccounts = async () => ({
+      bridgeState,
+      bridgeStats,
+      withdrawalRequest: await nextWithdrawalRequest(),
+      mint,
+      userTokenAccount,
+      feeVault,
+      userLimits: userLimitsFor(user.publicKey),
+      denyListEntry: denyListEntryFor(user.publicKey),
+      user: user.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    });
+
+    // The fund's share cannot be skipped by leaving its vault out
+    try {
+      await program.methods
+        .burnWzec(new anchor.BN(1_000_000), testnetZecAddress, null, null)
+        .accounts(await burnAccounts())
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InsuranceVaultRequired");
+    }
+
+    const feeVaultBefore = await getAccount(provider.connection, feeVault);
+    await program.methods
+      .burnWzec(new anchor.BN(1_000_000), testnetZecAddress, null, null)
+      .accounts({ ...(await burnAccounts()), insuranceVault })
+      .signers([user])
+      .rpc();
+
+    // Half of the 2,000 fee goes to the fund and half to the fee vault
+    const feeVaultAfter = await getAccount(provider.connection, feeVault);
+    expect((feeVaultAfter.amount - feeVaultBefore.amount).toString()).to.equal("1000");
+    expect((await getAccount(provider.connection, insuranceVault)).amount.toString()).to.equal("1000");
+
+    const userBalance = (await getAccount(provider.connection, userTokenAccount)).amount;
+    await program.methods
+      .payInsuranceClaim(new anchor.BN(1000), { reserveShortfall: {} })
+      .accounts({
+        bridgeState,
+        mint,
+        insuranceAuthority,
+        insuranceVault,
+        recipientTokenAccount: userTokenAccount,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    expect((await getAccount(provider.connection, insuranceVault)).amount.toString()).to.equal("0");
+    expect(
+      ((await getAccount(provider.connection, userTokenAccount)).amount - userBalance).toString()
+    ).to.equal("1000");
+
+    await program.methods
+      .updateInsuranceShare(0)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+  });
+
+  it("Lets stakers back the insurance fund and absorb a claim first", async () => {
+    const [insuranceAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("insurance"), bridgeState.toBuffer()],
+      program.programId
+    );
+    const insuranceVault = getAssociatedTokenAddressSync(mint, insuranceAuthority, true);
+    const [insuranceStake] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("insurance_stake"), bridgeState.toBuffer(), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const setUnstakeSlots = (slots: number) =>
+      program.methods
+        .updateInsuranceUnstakeSlots(new anchor.BN(slots))
+        .accounts({
+          bridgeState,
+          authority,
+        })
+        .rpc();
+    const unstake = (shares: number) =>
+      program.methods
+        .unstakeInsurance(new anchor.BN(shares))
+        .accounts({
+          bridgeState,
+          insuranceStake,
+          staker: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    const claim = () =>
+      program.methods
+        .claimInsuranceStake()
+        .accounts({
+          bridgeState,
+          mint,
+          insuranceAuthority,
+          insuranceVault,
+          insuranceStake,
+          stakerTokenAccount: userTokenAccount,
+          staker: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    await setUnstakeSlots(1_000_000);
+
+    await program.methods
+      .stakeInsurance(new anchor.BN(1_000_000))
+      .accounts({
+        bridgeState,
+        mint,
+        insuranceVault,
+        insuranceStake,
+        stakerTokenAccount: userTokenAccount,
+        staker: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    let stake = await program.account.insuranceStake.fetch(insuranceStake);
+    expect(stake.shares.toNumber()).to.equal(1_000_000);
+
+    // A shortfall claim comes out of the stakers' capital
+    await program.methods
+      .payInsuranceClaim(new anchor.BN(250_000), { relayerFailure: {} })
+      .accounts({
+        bridgeState,
+        mint,
+        insuranceAuthority,
+        insuranceVault,
+        recipientTokenAccount: feeVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await unstake(500_000);
+    try {
+      await claim();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InsuranceStakeLocked");
+    }
+
+    // Unstaking again restarts the cooldown under the new setting
+    await setUnstakeSlots(0);
+    await unstake(500_000);
+
+    const userBalance = (await getAccount(provider.connection, userTokenAccount)).amount;
+    await claim();
+
+    stake = await program.account.insuranceStake.fetch(insuranceStake);
+    expect(stake.shares.toNumber()).to.equal(0);
+    expect(stake.unstakingShares.toNumber()).to.equal(0);
+    expect(
+      ((await getAccount(provider.connection, userTokenAccount)).amount - userBalance).toString()
+    ).to.equal("750000");
+    expect((await getAccount(provider.connection, insuranceVault)).amount.toString()).to.equal("0");
+
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.insuranceTotalShares.toNumber()).to.equal(0);
+  });
+
+  it("Lets a relayer burn for an owner who signed a permit and pay itself in wZEC", async () => {
+    const [permitNonce] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("permit_nonce"), bridgeState.toBuffer(), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const relayerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, provider.wallet.payer, mint, authority)
+    ).address;
+    const amount = new anchor.BN(500_000);
+    const relayerFee = new anchor.BN(10_000);
+    const nonce = new anchor.BN(0);
+    const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+
+    // The owner delegates to the bridge once, then only signs permits off-chain
+    await approve(provider.connection, user, userTokenAccount, bridgeState, user, 1_000_000);
+
+    const permit = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: user.secretKey,
+      message: Buffer.concat([
+        Buffer.from("solz-burn-permit-testnet"),
+        bridgeState.toBuffer(),
+        user.publicKey.toBuffer(),
+        authority.toBuffer(),
+        amount.toArrayLike(Buffer, "le", 8),
+        relayerFee.toArrayLike(Buffer, "le", 8),
+        nonce.toArrayLike(Buffer, "le", 8),
+        expiry.toArrayLike(Buffer, "le", 8),
+        Buffer.from(testnetZecAddress),
+      ]),
+    });
+    const permitAccounts = async () => ({
+      bridgeState,
+      bridgeStats,
+      withdrawalRequest: await nextWithdrawalRequest(),
+      mint,
+      userTokenAccount,
+      feeVault,
+      userLimits: userLimitsFor(user.publicKey),
+      denyListEntry: denyListEntryFor(user.publicKey),
+      permitNonce,
+      owner: user.publicKey,
+      relayerTokenAccount,
+      relayer: authority,
+      instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    });
+
+    // The relayer signs and pays for the transaction; the owner does not
+    const withdrawalRequest = await nextWithdrawalRequest();
+    const relayerBalance = (await getAccount(provider.connection, relayerTokenAccount)).amount;
+    await program.methods
+      .burnWzecWithPermit(amount, testnetZecAddress, relayerFee, nonce, expiry)
+      .accounts(await permitAccounts())
+      .preInstructions([permit])
+      .rpc();
+
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.user.toString()).to.equal(user.publicKey.toString());
+    expect(request.amount.toNumber()).to.equal(500_000);
+    expect(
+      ((await getAccount(provider.connection, relayerTokenAccount)).amount - relayerBalance).toString()
+    ).to.equal("10000");
+
+    let nonces = await program.account.permitNonce.fetch(permitNonce);
+    expect(nonces.nextNonce.toNumber()).to.equal(1);
+    expect(nonces.relayerFeesPaid.toNumber()).to.equal(10_000);
+
+    // A used permit cannot be replayed
+    try {
+      await program.methods
+        .burnWzecWithPermit(amount, testnetZecAddress, relayerFee, nonce, expiry)
+        .accounts(await permitAccounts())
+        .preInstructions([permit])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidPermitNonce");
+    }
+
+    // Nor can a permit be stretched to a different amount
+    try {
+      await program.methods
+        .burnWzecWithPermit(new anchor.BN(600_000), testnetZecAddress, relayerFee, new anchor.BN(1), expiry)
+        .accounts(await permitAccounts())
+        .preInstructions([permit])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidPermitSignature");
+    }
+
+    // The owner can void permits it handed out but no longer wants used
+    await program.methods
+      .cancelPermits(new anchor.BN(5))
+      .accounts({
+        bridgeState,
+        permitNonce,
+        owner: user.publicKey,
+        payer: authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    nonces = await program.account.permitNonce.fetch(permitNonce);
+    expect(nonces.nextNonce.toNumber()).to.equal(5);
+  });
+
+  it("Reclaims rent from aged receipts and settled withdrawals", async () => {
+    const zcashTxid = txidFor("test_txid_12345");
+    const depositReceipt = depositReceiptFor(zcashTxid);
+    const withdrawalRequest = withdrawalRequestFor(new anchor.BN(0));
+    const [closedAccountLog] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("closed_accounts"), bridgeState.toBuffer()],
+      program.programId
+    );
+    const setRetentionSlots = (slots: number) =>
+      program.methods
+        .updateRetentionSlots(new anchor.BN(slots))
+        .accounts({
+          bridgeState,
+          authority,
+        })
+        .rpc();
+    const closeReceipt = () =>
+      program.methods
+        .closeReceipt(zcashTxid)
+        .accounts({
+          bridgeState,
+          depositReceipt,
+          closedAccountLog,
+          payer: authority,
+          closer: authority,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    // Nothing can be closed while retention is disabled
+    try {
+      await closeReceipt();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("RetentionNotElapsed");
+    }
+
+    await setRetentionSlots(1);
+    const receiptBefore = await provider.connection.getAccountInfo(depositReceipt);
+    await closeReceipt();
+
+    // The receipt stays behind as a tombstone holding only rent for its discriminator
+    const tombstone = await provider.connection.getAccountInfo(depositReceipt);
+    expect(tombstone.owner.toString()).to.equal(program.programId.toString());
+    expect(tombstone.data.length).to.equal(8);
+    expect(tombstone.lamports).to.equal(
+      await provider.connection.getMinimumBalanceForRentExemption(8)
+    );
+    expect(receiptBefore.lamports).to.be.greaterThan(tombstone.lamports);
+
+    // The deposit still can't be minted again
+    try {
+      await program.methods
+        .mintWzec(new anchor.BN(100_000_000), zcashTxid, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          guardianSet,
+          depositReceipt,
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+          mint,
+          mintAuthority,
+          recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          feeVault,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers(guardians)
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Should have thrown");
+    }
+
+    // The completed withdrawal's rent goes back to the user who burned
+    const requestLamports = (await provider.connection.getAccountInfo(withdrawalRequest)).lamports;
+    const userBefore = await provider.connection.getBalance(user.publicKey);
+    try {
+      await program.methods
+        .closeWithdrawal(new anchor.BN(0))
+        .accounts({
+          bridgeState,
+          withdrawalRequest,
+          closedAccountLog,
+          payer: authority,
+          closer: authority,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidRentPayer");
+    }
+    await program.methods
+      .closeWithdrawal(new anchor.BN(0))
+      .accounts({
+        bridgeState,
+        withdrawalRequest,
+        closedAccountLog,
+        payer: user.publicKey,
+        closer: authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(withdrawalRequest)).to.be.null;
+    expect(await provider.connection.getBalance(user.publicKey)).to.equal(
+      userBefore + requestLamports
+    );
+
+    const log = await program.account.closedAccountLog.fetch(closedAccountLog);
+    expect(log.closedCount.toNumber()).to.equal(2);
+    expect(Buffer.from(log.head).equals(Buffer.alloc(32))).to.be.false;
+
+    await setRetentionSlots(0);
+  });
+
+  it("Finalizes withdrawals in nonce order within the finalize window", async () => {
+    const setFinalizeWindow = (window: number, floor: anchor.BN) =>
+      program.methods
+        .updateFinalizeWindow(window, floor)
+        .accounts({
+          bridgeState,
+          authority,
+        })
+        .rpc();
+    const burn = async () =>
+      program.methods
+        .burnWzec(new anchor.BN(1_000_000), testnetZecAddress, null, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest: await nextWithdrawalRequest(),
+          mint,
+          userTokenAccount,
+          feeVault,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    const finalize = (nonce: anchor.BN) =>
+      program.methods
+        .finalizeWithdrawal(Array.from(Buffer.alloc(32, 21)), 2_500_010, null)
+        .accounts({
+          bridgeState,
+          withdrawalRequest: withdrawalRequestFor(nonce),
+          authority,
+        })
+        .rpc();
+
+    try {
+      await setFinalizeWindow(65, new anchor.BN(0));
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidFinalizeWindow");
+    }
+
+    const first = (await program.account.bridgeState.fetch(bridgeState)).withdrawalNonce;
+    const second = first.addn(1);
+    await setFinalizeWindow(1, first);
+    await burn();
+    await burn();
+
+    // The later withdrawal can't be paid while the earlier one is outstanding
+    try {
+      await finalize(second);
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("WithdrawalOutOfOrder");
+    }
+
+    await finalize(first);
+    let state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.settledFloor.toString()).to.equal(second.toString());
+
+    await finalize(second);
+    state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.settledFloor.toString()).to.equal(second.addn(1).toString());
+    expect(state.settledBitmap.toNumber()).to.equal(0);
+
+    await setFinalizeWindow(0, new anchor.BN(0));
+  });
+
+  it("Posts guardian-attested reserve snapshots into a ring", async () => {
+    const ringSlotFor = (sequence: number) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("reserve_attestation"),
+          bridgeState.toBuffer(),
+          new anchor.BN(sequence % 32).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    const reserveRoot = [...createHash("sha256").update("reserve notes").digest()];
+    const post = (signers: anchor.web3.Keypair[], sequence: number) =>
+      program.methods
+        .postReserveAttestation(2_600_000, new anchor.BN(500_000_000), new anchor.BN(400_000_000), reserveRoot)
+        .accounts({
+          bridgeState,
+          guardianSet,
+          mint,
+          reserveAttestation: ringSlotFor(sequence),
+          payer: authority,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(signers))
+        .signers(signers)
+        .rpc();
+
+    const sequence = (await program.account.bridgeState.fetch(bridgeState)).reserveAttestationCount.toNumber();
+    try {
+      await post(guardians.slice(0, 1), sequence);
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientGuardianSignatures");
+    }
+
+    await post(guardians, sequence);
+
+    const attestation = await program.account.reserveAttestation.fetch(ringSlotFor(sequence));
+    expect(attestation.sequence.toNumber()).to.equal(sequence);
+    expect(attestation.zcashHeight).to.equal(2_600_000);
+    expect(attestation.reserveZatoshis.toNumber()).to.equal(500_000_000);
+    expect(attestation.circulatingWzec.toNumber()).to.equal(400_000_000);
+    expect(attestation.reserveRoot).to.deep.equal(reserveRoot);
+    const supply = (await getMint(provider.connection, mint)).supply;
+    expect(attestation.mintSupply.toString()).to.equal(supply.toString());
+
+    // The next snapshot takes the next ring slot
+    await post(guardians, sequence + 1);
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.reserveAttestationCount.toNumber()).to.equal(sequence + 2);
+  });
+
+  it("Rejects a reserve snapshot attested for another bridge", async () => {
+    const sequence = (await program.account.bridgeState.fetch(bridgeState)).reserveAttestationCount;
+    const [ringSlot] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("reserve_attestation"),
+        bridgeState.toBuffer(),
+        new anchor.BN(sequence.toNumber() % 32).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const reserveRoot = createHash("sha256").update("cross-bridge reserve notes").digest();
+    const otherBridgeState = bridgePdasFor(anchor.web3.Keypair.generate().publicKey).bridgeState;
+
+    // One guardian attests off-chain and the other signs, meeting the threshold of two
+    const postAttestedFor = (attestedBridge: anchor.web3.PublicKey) =>
+      program.methods
+        .postReserveAttestation(
+          2_600_002,
+          new anchor.BN(500_000_000),
+          new anchor.BN(400_000_000),
+          [...reserveRoot]
+        )
+        .accounts({
+          bridgeState,
+          guardianSet,
+          mint,
+          reserveAttestation: ringSlot,
+          payer: authority,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians.slice(1)))
+        .preInstructions([
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: guardians[0].secretKey,
+            message: Buffer.concat([
+              Buffer.from("solz-reserve-attestation-testnet"),
+              attestedBridge.toBuffer(),
+              sequence.toArrayLike(Buffer, "le", 8),
+              new anchor.BN(2_600_002).toArrayLike(Buffer, "le", 4),
+              new anchor.BN(500_000_000).toArrayLike(Buffer, "le", 8),
+              new anchor.BN(400_000_000).toArrayLike(Buffer, "le", 8),
+              reserveRoot,
+            ]),
+          }),
+        ])
+        .signers(guardians.slice(1))
+        .rpc();
+
+    try {
+      await postAttestedFor(otherBridgeState);
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientGuardianSignatures");
+    }
+
+    await postAttestedFor(bridgeState);
+    const attestation = await program.account.reserveAttestation.fetch(ringSlot);
+    expect(attestation.sequence.toString()).to.equal(sequence.toString());
+    expect(attestation.zcashHeight).to.equal(2_600_002);
+  });
+
+  it("Converts USD withdrawal limits at the Pyth ZEC/USD price", async () => {
+    // Fixture price update: $40.00 per ZEC, published in October 2025
+    const priceUpdate = new anchor.web3.PublicKey("GNUXzyp7ohdXkQFvxwqg6ev5VGmRrmEVH7x64Kwm7pve");
+    const feedId = [
+      ...Buffer.from("be9b59d178f0d6a97ab4c343bff2aa69caa1eaae3e9048a65788c529b125bb24", "hex"),
+    ];
+    const unset = new anchor.BN("18446744073709551615");
+    const setUsdLimits = (feed: number[], largeWithdrawalThresholdUsd: anchor.BN) =>
+      program.methods
+        .updateUsdLimits(feed, 4_294_967_295, unset, largeWithdrawalThresholdUsd)
+        .accounts({
+          bridgeState,
+          authority,
+        })
+        .rpc();
+    const burn = async (remainingAccounts: anchor.web3.AccountMeta[]) => {
+      const withdrawalRequest = await nextWithdrawalRequest();
+      await program.methods
+        .burnWzec(new anchor.BN(500_000), testnetZecAddress, null, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest,
+          mint,
+          userTokenAccount,
+          feeVault,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([user])
+        .rpc();
+      return withdrawalRequest;
+    };
+
+    try {
+      await setUsdLimits(Array(32).fill(0), new anchor.BN(10));
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidPriceFeed");
+    }
+
+    // $0.10 is 250,000 zatoshis at $40, below the 1,000,000 zatoshi threshold
+    await setUsdLimits(feedId, new anchor.BN(10));
+
+    try {
+      await burn([]);
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("PriceUpdateRequired");
+    }
+
+    const withdrawalRequest = await burn([{ pubkey: priceUpdate, isSigner: false, isWritable: false }]);
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.unlockSlot.sub(request.createdSlot).toNumber()).to.equal(1_000_000);
+
+    await setUsdLimits(Array(32).fill(0), unset);
+  });
+
+  it("Rotates the guardian set with a quorum of the outgoing set", async () => {
+    const newGuardians = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    const archivedGuardianSetFor = (index: number) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("guardian_set"), bridgeState.toBuffer(), new anchor.BN(index).toArrayLike(Buffer, "le", 4)],
+        program.programId
+      )[0];
+    const rotate = (
+      members: anchor.web3.Keypair[],
+      caller: anchor.web3.Keypair | null,
+      approvers: anchor.web3.Keypair[],
+      index: number
+    ) =>
+      program.methods
+        .rotateGuardianSet(members.map((guardian) => guardian.publicKey), 2)
+        .accounts({
+          bridgeState,
+          guardianSet,
+          archivedGuardianSet: archivedGuardianSetFor(index),
+          caller: caller ? caller.publicKey : authority,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(guardianSigners(approvers))
+        .signers(caller ? [caller, ...approvers] : approvers)
+        .rpc();
+
+    const outgoingIndex = (await program.account.guardianSet.fetch(guardianSet)).index;
+    try {
+      await rotate(newGuardians, user, guardians.slice(0, 1), outgoingIndex);
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientGuardianSignatures");
+    }
+
+    await rotate(newGuardians, user, guardians, outgoingIndex);
+
+    const current = await program.account.guardianSet.fetch(guardianSet);
+    expect(current.index).to.equal(outgoingIndex + 1);
+    expect(current.guardians.map((key) => key.toBase58())).to.deep.equal(
+      newGuardians.map((guardian) => guardian.publicKey.toBase58())
+    );
+    const archivedGuardianSet = archivedGuardianSetFor(outgoingIndex);
+    const archived = await program.account.archivedGuardianSet.fetch(archivedGuardianSet);
+    expect(archived.index).to.equal(outgoingIndex);
+    expect(archived.guardians.map((key) => key.toBase58())).to.deep.equal(
+      guardians.map((guardian) => guardian.publicKey.toBase58())
+    );
+    const slot = await provider.connection.getSlot();
+    expect(archived.expiresSlot.toNumber()).to.be.greaterThan(slot);
+
+    // The outgoing set still attests during the grace period, given its archive
+    const sequence = (await program.account.bridgeState.fetch(bridgeState)).reserveAttestationCount.toNumber();
+    const ringSlot = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("reserve_attestation"),
+        bridgeState.toBuffer(),
+        new anchor.BN(sequence % 32).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const post = (remainingAccounts: anchor.web3.AccountMeta[]) =>
+      program.methods
+        .postReserveAttestation(2_600_001, new anchor.BN(500_000_000), new anchor.BN(400_000_000), Array(32).fill(0))
+        .accounts({
+          bridgeState,
+          guardianSet,
+          mint,
+          reserveAttestation: ringSlot,
+          payer: authority,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers(guardians)
+        .rpc();
+
+    try {
+      await post(guardianSigners(guardians));
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientGuardianSignatures");
+    }
+
+    await post([
+      ...guardianSigners(guardians),
+      { pubkey: archivedGuardianSet, isSigner: false, isWritable: false },
+    ]);
+
+    // Archived sets can't rotate; the guardian manager restores the original set
+    try {
+      await rotate(guardians, user, guardians, outgoingIndex + 1);
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientGuardianSignatures");
+    }
+
+    await rotate(guardians, null, [], outgoingIndex + 1);
+    expect((await program.account.guardianSet.fetch(guardianSet)).index).to.equal(outgoingIndex + 2);
+  });
+
+  it("Lets a single guardian pause mints until a quorum resumes them", async () => {
+    const zcashTxid = txidFor("guardian-pause-deposit");
+    const mintDeposit = () =>
+      program.methods
+        .mintWzec(new anchor.BN(10_000_000), zcashTxid, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          guardianSet,
+          depositReceipt: depositReceiptFor(zcashTxid),
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+          mint,
+          mintAuthority,
+          recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          feeVault,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers(guardians)
+        .rpc();
+    const resume = (caller: anchor.web3.Keypair | null, approvers: anchor.web3.Keypair[]) =>
+      program.methods
+        .guardianResume()
+        .accounts({
+          bridgeState,
+          guardianSet,
+          caller: caller ? caller.publicKey : authority,
+        })
+        .remainingAccounts(guardianSigners(approvers))
+        .signers(caller ? [caller, ...approvers] : approvers)
+        .rpc();
+
+    try {
+      await program.methods
+        .guardianPause()
+        .accounts({
+          bridgeState,
+          guardianSet,
+          guardian: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("NotAGuardian");
+    }
+
+    await program.methods
+      .guardianPause()
+      .accounts({
+        bridgeState,
+        guardianSet,
+        guardian: guardians[0].publicKey,
+      })
+      .signers([guardians[0]])
+      .rpc();
+
+    let state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.guardianPausedBy.toString()).to.equal(guardians[0].publicKey.toString());
+
+    // Clearing the authority's pause flags leaves the guardian pause in place
+    await program.methods
+      .setPauseFlags(false, false)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        authority,
+      })
+      .rpc();
+
+    try {
+      await mintDeposit();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("BridgePaused");
+    }
+
+    try {
+      await resume(user, guardians.slice(0, 1));
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    await resume(user, guardians);
+
+    state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.guardianPausedBy.toString()).to.equal(anchor.web3.PublicKey.default.toString());
+
+    await mintDeposit();
+
+    try {
+      await resume(null, []);
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("GuardianPauseNotActive");
+    }
+  });
+
+  it("Requires a compliance credential for burns above the KYC threshold", async () => {
+    // The attester program owns a credential for this wallet only
+    const attester = new anchor.web3.PublicKey("3YFL5i5wrKVnThh6EQshiupXGxc2cN1WxkqjMYXRTPK8");
+    const credential = new anchor.web3.PublicKey("2pxP5XASK8632J8jumpd9SZaAdzWd7Eod3kcdTTJoRL4");
+    const attestedUser = loadKeypair("tests/fixtures/attested-user.json");
+
+    await program.methods
+      .updateCompliance(new anchor.BN(1_000_000), attester)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    const burnAccounts = async (owner: anchor.web3.PublicKey, tokenAccount: anchor.web3.PublicKey) => ({
+      bridgeState,
+      bridgeStats,
+      withdrawalRequest: await nextWithdrawalRequest(),
+      mint,
+      userTokenAccount: tokenAccount,
+      feeVault,
+      userLimits: userLimitsFor(owner),
+      denyListEntry: denyListEntryFor(owner),
+      user: owner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    });
+
+    try {
+      await program.methods
+        .burnWzec(new anchor.BN(2_000_000), testnetZecAddress, null, null)
+        .accounts(await burnAccounts(user.publicKey, userTokenAccount))
+        .remainingAccounts([{ pubkey: credential, isSigner: false, isWritable: false }])
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("ComplianceAttestationRequired");
+    }
+
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(attestedUser.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+    );
+    const attestedTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      attestedUser.publicKey
+    );
+    await transfer(
+      provider.connection,
+      provider.wallet.payer,
+      userTokenAccount,
+      attestedTokenAccount.address,
+      user,
+      2_000_000
+    );
+
+    const withdrawalRequest = await nextWithdrawalRequest();
+    await program.methods
+      .burnWzec(new anchor.BN(2_000_000), testnetZecAddress, null, null)
+      .accounts(await burnAccounts(attestedUser.publicKey, attestedTokenAccount.address))
+      .remainingAccounts([{ pubkey: credential, isSigner: false, isWritable: false }])
+      .signers([attestedUser])
+      .rpc();
+
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.user.toString()).to.equal(attestedUser.publicKey.toString());
+
+    await program.methods
+      .updateCompliance(new anchor.BN("18446744073709551615"), anchor.web3.PublicKey.default)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+  });
+
+  it("Rejects a burn from a token account the signer does not own", async () => {
+    const foreignTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      authority
+    );
+
+    try {
+      await program.methods
+        .burnWzec(
+          new anchor.BN(1_000),
+          testnetZecAddress,
+          null,
+          null
+        )
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          withdrawalRequest: await nextWithdrawalRequest(),
+          mint,
+          userTokenAccount: foreignTokenAccount.address,
+          feeVault,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidTokenAccount");
+    }
+  });
+
+  it("Refuses to close a bridge that has not been decommissioned", async () => {
+    const treasury = getAssociatedTokenAddressSync(mint, authority);
+
+    try {
+      await program.methods
+        .closeBridge()
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          guardianSet,
+          relayerRegistry: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("relayer_registry"), bridgeState.toBuffer()],
+            program.programId
+          )[0],
+          mint,
+          feeVault,
+          treasuryTokenAccount: treasury,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("BridgeNotDecommissioned");
+    }
+  });
+
+  it("Closes a decommissioned bridge only once its withdrawals have settled", async () => {
+    const closingMintKeypair = anchor.web3.Keypair.generate();
+    const closing = bridgePdasFor(closingMintKeypair.publicKey);
+    const closingMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      closing.mintAuthority,
+      null,
+      8,
+      closingMintKeypair
+    );
+    const closingFeeVault = getAssociatedTokenAddressSync(closingMint, closing.bridgeState, true);
+    const closingPda = (...seeds: Buffer[]) =>
+      anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const closingGuardianSet = closingPda(Buffer.from("guardian_set"), closing.bridgeState.toBuffer());
+    const closingUserLimits = closingPda(
+      Buffer.from("user_limits"),
+      closing.bridgeState.toBuffer(),
+      user.publicKey.toBuffer()
+    );
+    const closingDenyListEntry = closingPda(
+      Buffer.from("deny_list"),
+      closing.bridgeState.toBuffer(),
+      user.publicKey.toBuffer()
+    );
+
+    await program.methods
+      .initialize(0, 0, { testnet: {} })
+      .accounts({
+        bridgeState: closing.bridgeState,
+        bridgeStats: closing.bridgeStats,
+        mint: closingMint,
+        mintAuthority: closing.mintAuthority,
+        feeVault: closingFeeVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .initializeGuardianSet(
+        guardians.map((guardian) => guardian.publicKey),
+        2
+      )
+      .accounts({
+        bridgeState: closing.bridgeState,
+        guardianSet: closingGuardianSet,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    const treasury = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        closingMint,
+        authority
+      )
+    ).address;
+    await program.methods
+      .updateTreasury(treasury)
+      .accounts({
+        bridgeState: closing.bridgeState,
+        authority,
+      })
+      .rpc();
+
+    const closingTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        closingMint,
+        user.publicKey
+      )
+    ).address;
+    const zcashTxid = txidFor("test_txid_close_bridge");
+    await program.methods
+      .mintWzec(new anchor.BN(10_000_000), zcashTxid, null)
+      .accounts({
+        bridgeState: closing.bridgeState,
+        bridgeStats: closing.bridgeStats,
+        guardianSet: closingGuardianSet,
+        depositReceipt: closingPda(
+          Buffer.from("deposit"),
+          closing.bridgeState.toBuffer(),
+          Buffer.from(zcashTxid)
+        ),
+        legacyDepositReceipt: closingPda(
+          Buffer.from("deposit"),
+          closing.bridgeState.toBuffer(),
+          createHash("sha256").update(Buffer.from(zcashTxid).reverse().toString("hex")).digest()
+        ),
+        mint: closingMint,
+        mintAuthority: closing.mintAuthority,
+        recipientTokenAccount: closingTokenAccount,
+        userLimits: closingUserLimits,
+        denyListEntry: closingDenyListEntry,
+        feeVault: closingFeeVault,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .remainingAccounts(guardianSigners(guardians))
+      .signers(guardians)
+      .rpc();
+
+    await program.methods
+      .decommissionBridge(new anchor.BN(10))
+      .accounts({
+        bridgeState: closing.bridgeState,
+        authority,
+      })
+      .rpc();
+
+    // Holders may still burn during the grace period
+    const withdrawalRequest = closingPda(
+      Buffer.from("withdrawal"),
+      closing.bridgeState.toBuffer(),
+      new anchor.BN(0).toArrayLike(Buffer, "le", 8)
+    );
+    await program.methods
+      .burnWzec(new anchor.BN(10_000_000), testnetZecAddress, null, null)
+      .accounts({
+        bridgeState: closing.bridgeState,
+        bridgeStats: closing.bridgeStats,
+        withdrawalRequest,
+        mint: closingMint,
+        userTokenAccount: closingTokenAccount,
+        feeVault: closingFeeVault,
+        userLimits: closingUserLimits,
+        denyListEntry: closingDenyListEntry,
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+    expect(
+      (await program.account.bridgeState.fetch(closing.bridgeState)).openWithdrawals.toNumber()
+    ).to.equal(1);
+
+    const { decommissionedSlot, decommissionGraceSlots } =
+      await program.account.bridgeState.fetch(closing.bridgeState);
+    const exitDeadline = decommissionedSlot.add(decommissionGraceSlots).toNumber();
+    while ((await provider.connection.getSlot()) <= exitDeadline) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+
+    const closeBridge = () =>
+      program.methods
+        .closeBridge()
+        .accounts({
+          bridgeState: closing.bridgeState,
+          bridgeStats: closing.bridgeStats,
+          guardianSet: closingGuardianSet,
+          relayerRegistry: closingPda(Buffer.from("relayer_registry"), closing.bridgeState.toBuffer()),
+          insuranceVault: null,
+          mint: closingMint,
+          feeVault: closingFeeVault,
+          treasuryTokenAccount: treasury,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    // The pending burn still needs the bridge state to finalize
+    try {
+      await closeBridge();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("WithdrawalsOutstanding");
+    }
+
+    await program.methods
+      .finalizeWithdrawal(Array.from(Buffer.alloc(32, 21)), 2_500_100, 0)
+      .accounts({
+        bridgeState: closing.bridgeState,
+        withdrawalRequest,
+        authority,
+      })
+      .rpc();
+    expect(
+      (await program.account.bridgeState.fetch(closing.bridgeState)).openWithdrawals.toNumber()
+    ).to.equal(0);
+
+    await closeBridge();
+    expect(await provider.connection.getAccountInfo(closing.bridgeState)).to.be.null;
+    expect(await provider.connection.getAccountInfo(closingFeeVault)).to.be.null;
+  });
+
+  it("Refuses to migrate a state account already at the current version", async () => {
+    try {
+      await program.methods
+        .migrateState()
+        .accounts({
+          bridgeState,
+          mint,
+          bridgeStats,
+          authority,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyMigrated");
+    }
+  });
+
+  it("Migrates a state account in the original unversioned layout", async () => {
+    // Anchor.toml seeds a baseline-sized bridge state: no version byte, so the
+    // authority sits right after the discriminator
+    const legacyAuthority = loadKeypair("tests/fixtures/legacy-authority.json");
+    const legacyMint = new anchor.web3.PublicKey("GY7npGFZo5FiNaLnN8gWEczhMjWGjKU4gwucSS4qpPdh");
+    const legacy = bridgePdasFor(legacyMint);
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(legacyAuthority.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+    );
+    const accounts = {
+      bridgeState: legacy.bridgeState,
+      mint: legacyMint,
+      bridgeStats: legacy.bridgeStats,
+      authority: legacyAuthority.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    // Only the authority stored at offset 8 may migrate it
+    try {
+      await program.methods.initBridgeStats().accounts({ ...accounts, authority }).rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    await program.methods.initBridgeStats().accounts(accounts).signers([legacyAuthority]).rpc();
+    await program.methods.migrateState().accounts(accounts).signers([legacyAuthority]).rpc();
+
+    const state = await program.account.bridgeState.fetch(legacy.bridgeState);
+    expect(state.version).to.equal(18);
+    expect(state.authority.toString()).to.equal(legacyAuthority.publicKey.toString());
+    expect(state.mint.toString()).to.equal(legacyMint.toString());
+    expect(state.mintFeeBps).to.equal(0);
+    expect(state.burnFeeBps).to.equal(25);
+    expect(state.mintsPaused).to.equal(true);
+    expect(state.burnsPaused).to.equal(true);
+    expect(state.maxMintAmount.toString()).to.equal("18446744073709551615");
+
+    const stats = await program.account.bridgeStats.fetch(legacy.bridgeStats);
+    expect(stats.totalMinted.toString()).to.equal("900000000");
+    expect(stats.totalBurned.toString()).to.equal("400000000");
+    expect(stats.feeCollected.toString()).to.equal("1000000");
+  });
+
+  it("Rewrites a state account in the first versioned layout", async () => {
+    // Anchor.toml seeds a bridge state that still carries its totals and volume windows
+    const legacyAuthority = loadKeypair("tests/fixtures/legacy-authority.json");
+    const legacyMint = new anchor.web3.PublicKey("B23nsiSqkaMSdQ48551LD8w6o8Z2soaQJMu3sHpQndAw");
+    const legacy = bridgePdasFor(legacyMint);
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(legacyAuthority.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+    );
+
+    // Without a stats account there is nowhere to move the totals to
+    try {
+      await program.methods
+        .migrateState()
+        .accounts({
+          bridgeState: legacy.bridgeState,
+          mint: legacyMint,
+          bridgeStats: legacy.bridgeStats,
+          authority: legacyAuthority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([legacyAuthority])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("AccountNotInitialized");
+    }
+
+    await program.methods
+      .initBridgeStats()
+      .accounts({
+        bridgeState: legacy.bridgeState,
+        mint: legacyMint,
+        bridgeStats: legacy.bridgeStats,
+        authority: legacyAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([legacyAuthority])
+      .rpc();
+
+    const stats = await program.account.bridgeStats.fetch(legacy.bridgeStats);
+    expect(stats.totalMinted.toString()).to.equal("700000000");
+    expect(stats.totalBurned.toString()).to.equal("200000000");
+    expect(stats.feeCollected.toString()).to.equal("1000000");
+    expect(stats.mintVolume.buckets[5].toString()).to.equal("300000000");
+
+    await program.methods
+      .migrateState()
+      .accounts({
+        bridgeState: legacy.bridgeState,
+        mint: legacyMint,
+        bridgeStats: legacy.bridgeStats,
+        authority: legacyAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([legacyAuthority])
+      .rpc();
+
+    const state = await program.account.bridgeState.fetch(legacy.bridgeState);
+    expect(state.version).to.equal(18);
+    expect(state.authority.toString()).to.equal(legacyAuthority.publicKey.toString());
+    expect(state.mint.toString()).to.equal(legacyMint.toString());
+    expect(state.mintFeeBps).to.equal(5);
+    expect(state.burnFeeBps).to.equal(10);
+    expect(state.withdrawalNonce.toString()).to.equal("3");
+    expect(state.circuitBreakerWindowHours).to.equal(24);
+    expect(state.network).to.deep.equal({ testnet: {} });
+    const info = await provider.connection.getAccountInfo(legacy.bridgeState);
+    expect(info.data.length).to.equal(program.account.bridgeState.size);
+  });
+
+  it("Moves a singleton bridge onto the per-mint PDAs", async () => {
+    // Anchor.toml seeds a bridge deployed before instances were keyed by mint: its
+    // state sits at the unkeyed PDA and the admin wallet still holds the mint
+    const legacyAuthority = loadKeypair("tests/fixtures/legacy-authority.json");
+    const singletonMint = new anchor.web3.PublicKey("FGLt5qctaEYqEXLpaTZtNh4aB411GWXEfgdYrKFZYSVA");
+    const singletonPda = (seed: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync([Buffer.from(seed)], program.programId)[0];
+    const moved = bridgePdasFor(singletonMint);
+    const movedFeeVault = getAssociatedTokenAddressSync(singletonMint, moved.bridgeState, true);
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(legacyAuthority.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+    );
+
+    await program.methods
+      .migrateLegacyBridge()
+      .accounts({
+        legacyBridgeState: singletonPda("bridge_state"),
+        legacyBridgeStats: singletonPda("bridge_stats"),
+        legacyMintAuthority: singletonPda("mint_authority"),
+        legacyFeeVault: null,
+        mintAuthority: moved.mintAuthority,
+        mint: singletonMint,
+        bridgeState: moved.bridgeState,
+        bridgeStats: moved.bridgeStats,
+        feeVault: movedFeeVault,
+        authority: legacyAuthority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([legacyAuthority])
+      .rpc();
+
+    const state = await program.account.bridgeState.fetch(moved.bridgeState);
+    expect(state.version).to.equal(18);
+    expect(state.authority.toString()).to.equal(legacyAuthority.publicKey.toString());
+    expect(state.burnFeeBps).to.equal(25);
+    expect(state.singletonReceipts).to.equal(true);
+
+    const stats = await program.account.bridgeStats.fetch(moved.bridgeStats);
+    expect(stats.totalMinted.toString()).to.equal("900000000");
+    expect(stats.totalBurned.toString()).to.equal("400000000");
+
+    const mintInfo = await getMint(provider.connection, singletonMint);
+    expect(mintInfo.mintAuthority.toString()).to.equal(moved.mintAuthority.toString());
+    expect(await provider.connection.getAccountInfo(singletonPda("bridge_state"))).to.equal(null);
+
+    // Deposits minted by the singleton keep their receipts under its seeds
+    const movedGuardianSet = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("guardian_set"), moved.bridgeState.toBuffer()],
+      program.programId
+    )[0];
+    await program.methods
+      .initializeGuardianSet(
+        guardians.map((guardian) => guardian.publicKey),
+        2
+      )
+      .accounts({
+        bridgeState: moved.bridgeState,
+        guardianSet: movedGuardianSet,
+        authority: legacyAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([legacyAuthority])
+      .rpc();
+
+    const zcashTxid = txidFor("singleton_receipt");
+    const singletonReceipt = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("deposit"),
+        createHash("sha256").update(Buffer.from(zcashTxid).reverse().toString("hex")).digest(),
+      ],
+      program.programId
+    )[0];
+    expect(singletonReceipt.toString()).to.equal("9ocRa9sQPct2kECh2HzgYnedq8jvd95oWDVGkxqKmTAU");
+    const recipient = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      legacyAuthority,
+      singletonMint,
+      user.publicKey
+    );
+
+    try {
+      await program.methods
+        .mintWzec(new anchor.BN(50_000_000), zcashTxid, null)
+        .accounts({
+          bridgeState: moved.bridgeState,
+          bridgeStats: moved.bridgeStats,
+          guardianSet: movedGuardianSet,
+          depositReceipt: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("deposit"), moved.bridgeState.toBuffer(), Buffer.from(zcashTxid)],
+            program.programId
+          )[0],
+          legacyDepositReceipt: singletonReceipt,
+          mint: singletonMint,
+          mintAuthority: moved.mintAuthority,
+          recipientTokenAccount: recipient.address,
+          userLimits: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("user_limits"), moved.bridgeState.toBuffer(), user.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          denyListEntry: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("deny_list"), moved.bridgeState.toBuffer(), user.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          feeVault: movedFeeVault,
+          authority: legacyAuthority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers([legacyAuthority, ...guardians])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("DepositAlreadyProcessed");
+    }
+  });
+
+  it("Rewrites a withdrawal request stored before memos", async () => {
+    // Fixture at nonce 1,000,000 whose 256-character address fills the old
+    // reservation, leaving no zeroes to read as an empty memo
+    const nonce = new anchor.BN(1_000_000);
+    const withdrawalRequest = withdrawalRequestFor(nonce);
+    try {
+      await program.account.withdrawalRequest.fetch(withdrawalRequest);
+
+      expect.fail("Should not decode before migrating");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Should not decode");
+    }
+
+    const migrate = () =>
+      program.methods
+        .migrateWithdrawalRequest(nonce)
+        .accounts({
+          bridgeState,
+          withdrawalRequest,
+          payer: authority,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    await migrate();
+
+    const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+    expect(request.nonce.toNumber()).to.equal(1_000_000);
+    expect(request.netAmount.toNumber()).to.equal(990_000);
+    expect(request.zecAddress).to.have.lengthOf(256);
+    expect(request.status).to.deep.equal({ pending: {} });
+    expect(request.memo).to.have.lengthOf(0);
+    expect(request.payer.toString()).to.equal(request.user.toString());
+
+    const requestAccount = await provider.connection.getAccountInfo(withdrawalRequest);
+    expect(requestAccount.data.length).to.equal(8 + 191 + 256);
+
+    try {
+      await migrate();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("WithdrawalRequestCurrent");
+    }
+  });
+
+  it("Records heartbeats and keeps the dead-man's switch armed", async () => {
+    await program.methods
+      .updateHeartbeatGap(new anchor.BN(1_000))
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    await program.methods
+      .heartbeat()
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.maxHeartbeatGap.toNumber()).to.equal(1_000);
+    expect(state.lastHeartbeatSlot.toNumber()).to.be.greaterThan(0);
+
+    // The authority just checked in, so nobody can pause the bridge yet
+    try {
+      await program.methods
+        .triggerDeadMansSwitch()
+        .accounts({
+          bridgeState,
+          caller: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("HeartbeatNotExpired");
+    }
+  });
+
+  it("Initializes the Zcash light client and rejects a header off its tip", async () => {
+    const [lightClient] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("light_client"), bridgeState.toBuffer()],
+      program.programId
+    );
+    const [headerBuffer] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("header_buffer"), lightClient.toBuffer(), authority.toBuffer()],
+      program.programId
+    );
+
+    // Synthetic checkpoint: 28 blocks at the target spacing and a fixed target
+    const checkpointHeight = 2_000_000;
+    const checkpointHash = createHash("sha256").update("checkpoint").digest();
+    const times = Array.from({ length: 28 }, (_, i) => 1_700_000_000 + i * 75);
+    const bits = Array.from({ length: 28 }, () => 0x1c01_0000);
+
+    await program.methods
+      .initializeLightClient(checkpointHeight, [...checkpointHash], times, bits, 10)
+      .accounts({
+        bridgeState,
+        lightClient,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const client = await program.account.lightClient.fetch(lightClient);
+    expect(client.tipHeight).to.equal(checkpointHeight);
+    expect(client.minConfirmations).to.equal(10);
+
+    // A well-formed header whose parent is not the checkpoint
+    const header = Buffer.alloc(1487);
+    Buffer.from([0xfd, 0x40, 0x05]).copy(header, 140);
+    for (let offset = 0; offset < header.length; offset += 800) {
+      await program.methods
+        .writeHeaderChunk(offset, header.subarray(offset, offset + 800))
+        .accounts({
+          lightClient,
+          headerBuffer,
+          submitter: authority,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    try {
+      await program.methods
+        .submitZcashHeader()
+        .accounts({
+          lightClient,
+          headerBuffer,
+          submitter: authority,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("HeaderDoesNotExtendTip");
+    }
+  });
+
+  it("Accepts a regtest header under regtest proof-of-work rules", async () => {
+    const regtestMintKeypair = anchor.web3.Keypair.generate();
+    const regtest = bridgePdasFor(regtestMintKeypair.publicKey);
+    const regtestMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      regtest.mintAuthority,
+      null,
+      8,
+      regtestMintKeypair
+    );
+    await program.methods
+      .initialize(0, 0, { regtest: {} })
+      .accounts({
+        bridgeState: regtest.bridgeState,
+        bridgeStats: regtest.bridgeStats,
+        mint: regtestMint,
+        mintAuthority: regtest.mintAuthority,
+        feeVault: getAssociatedTokenAddressSync(regtestMint, regtest.bridgeState, true),
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const [lightClient] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("light_client"), regtest.bridgeState.toBuffer()],
+      program.programId
+    );
+    const [headerBuffer] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("header_buffer"), lightClient.toBuffer(), authority.toBuffer()],
+      program.programId
+    );
+
+    // Regtest's easiest target throughout, so the next block keeps it
+    const checkpointHash = createHash("sha256").update("regtest checkpoint").digest();
+    const times = Array.from({ length: 28 }, (_, i) => 1_700_000_000 + i * 75);
+    const bits = Array.from({ length: 28 }, () => 0x200f_0f0f);
+    await program.methods
+      .initializeLightClient(100, [...checkpointHash], times, bits, null)
+      .accounts({
+        bridgeState: regtest.bridgeState,
+        lightClient,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const submit = async (header: Buffer) => {
+      for (let offset = 0; offset < header.length; offset += 800) {
+        await program.methods
+          .writeHeaderChunk(offset, header.subarray(offset, offset + 800))
+          .accounts({
+            lightClient,
+            headerBuffer,
+            submitter: authority,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+      }
+      await program.methods
+        .submitZcashHeader()
+        .accounts({
+          lightClient,
+          headerBuffer,
+          submitter: authority,
+        })
+        .rpc();
+    };
+
+    // A header sized for mainnet's Equihash (200, 9) is malformed on regtest
+    const mainnetSized = Buffer.alloc(1487);
+    Buffer.from([0xfd, 0x40, 0x05]).copy(mainnetSized, 140);
+    try {
+      await submit(mainnetSized);
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidZcashHeader");
+    }
+
+    // Block 101 on the checkpoint, 75 s later, with a valid Equihash (48, 5) solution
+    const header = Buffer.from(
+      "040000004323d45de15d5023129356a153d3fa6bf1d421d4c15d7fa4126bd8816899bfd1" +
+        "604127e1df709bf02f36cb9ef8f1b6b38af9096a6cdbc65eca17aef5d6269f83" +
+        "0000000000000000000000000000000000000000000000000000000000000000" +
+        "34f953650f0f0f20" +
+        "1200000000000000000000000000000000000000000000000000000000000000" +
+        "24023d49d4c09d2d7ba3101aa3ddd8a58283d903285795c0f2249f57071955bf70ecf53b86",
+      "hex"
+    );
+    await submit(header);
+
+    const client = await program.account.lightClient.fetch(lightClient);
+    expect(client.tipHeight).to.equal(101);
+    expect(client.network).to.equal(2);
+    expect(Buffer.from(client.headers[101 % 100].hash).toString("hex")).to.equal(
+      "5320624d5e35417fb96811037e59e5e9306b7736d68882e8db277e941ef4b204"
+    );
+  });
+
+  it("Checks SPV deposit proofs against the light client", async () => {
+    const [lightClient] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("light_client"), bridgeState.toBuffer()],
+      program.programId
+    );
+    const depositPubkeyHash = createHash("sha256").update("bridge t-address").digest().subarray(0, 20);
+
+    await program.methods
+      .updateDepositAddress([...depositPubkeyHash])
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    // Transparent v4 transaction paying the bridge and naming the user in OP_RETURN
+    const value = Buffer.alloc(8);
+    value.writeBigUInt64LE(10_000_000n);
+    const rawTx = Buffer.concat([
+      Buffer.from("04000080", "hex"), // overwintered v4
+      Buffer.from("85202f89", "hex"), // Sapling version group id
+      Buffer.from([1]),
+      Buffer.alloc(36),
+      Buffer.from([0]),
+      Buffer.from("ffffffff", "hex"),
+      Buffer.from([2]),
+      value,
+      Buffer.from([25, 0x76, 0xa9, 0x14]),
+      depositPubkeyHash,
+      Buffer.from([0x88, 0xac]),
+      Buffer.alloc(8),
+      Buffer.from([34, 0x6a, 0x20]),
+      user.publicKey.toBuffer(),
+      Buffer.alloc(4 + 4 + 8), // lock time, expiry, value balance
+      Buffer.from([0, 0, 0]),
+    ]);
+    const txid = createHash("sha256")
+      .update(createHash("sha256").update(rawTx).digest())
+      .digest();
+    const zcashTxid = [...txid];
+
+    const spvAccounts = {
+      bridgeState,
+      bridgeStats,
+      lightClient,
+      depositReceipt: depositReceiptFor(zcashTxid),
+      legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+      mint,
+      recipientTokenAccount: userTokenAccount,
+      userLimits: userLimitsFor(user.publicKey),
+      denyListEntry: denyListEntryFor(user.publicKey),
+      feeVault,
+      mintAuthority,
+      payer: authority,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    // Checkpointed headers carry no Merkle root, so nothing can be proven against them
+    try {
+      await program.methods
+        .mintWithSpvProof(zcashTxid, rawTx, 1_999_980, 0, [])
+        .accounts(spvAccounts)
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidMerkleProof");
+    }
+
+    // The claimed txid must match the transaction
+    try {
+      await program.methods
+        .mintWithSpvProof(Array(32).fill(0), rawTx, 1_999_980, 0, [])
+        .accounts({
+          ...spvAccounts,
+          depositReceipt: depositReceiptFor(Array(32).fill(0)),
+          legacyDepositReceipt: legacyDepositReceiptFor(Array(32).fill(0)),
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidZcashTransaction");
+    }
+  });
+
+  it("Stores the shielded deposit verifying key and requires a confirmed block", async () => {
+    const [lightClient] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("light_client"), bridgeState.toBuffer()],
+      program.programId
+    );
+    const [verifyingKey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("verifying_key"), bridgeState.toBuffer()],
+      program.programId
+    );
+    const g1 = () => Array(64).fill(1);
+    const g2 = () => Array(128).fill(2);
+    const vkAccounts = {
+      bridgeState,
+      verifyingKey,
+      authority,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    // The circuit has seven public inputs, so eight IC points
+    try {
+      await program.methods
+        .setVerifyingKey(g1(), g2(), g2(), g2(), [g1(), g1()])
+        .accounts(vkAccounts)
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidVerifyingKey");
+    }
+
+    await program.methods
+      .setVerifyingKey(g1(), g2(), g2(), g2(), Array.from({ length: 8 }, g1))
+      .accounts(vkAccounts)
+      .rpc();
+
+    const stored = await program.account.verifyingKey.fetch(verifyingKey);
+    expect(stored.ic.length).to.equal(8);
+
+    // The light client tip is not yet buried under ten blocks
+    const zcashTxid = Array(32).fill(0xab);
+    try {
+      await program.methods
+        .mintWithShieldedProof(zcashTxid, 2_000_000, new anchor.BN(1_000_000), {
+          a: Array(64).fill(0),
+          b: Array(128).fill(0),
+          c: Array(64).fill(0),
+        })
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          lightClient,
+          verifyingKey,
+          depositReceipt: depositReceiptFor(zcashTxid),
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+          mint,
+          recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          feeVault,
+          mintAuthority,
+          payer: authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("HeaderNotConfirmed");
+    }
+  });
+
+  it("Mints an SPV-proven deposit at the recipient's exempt rate", async () => {
+    const spv = await initializeBridgeFor({ regtest: {} }, 5);
+    const lightClient = pdaFor("light_client", spv.bridgeState.toBuffer());
+    const headerBuffer = pdaFor("header_buffer", lightClient.toBuffer(), authority.toBuffer());
+    const depositPubkeyHash = createHash("sha256").update("bridge t-address").digest().subarray(0, 20);
+    await program.methods
+      .updateDepositAddress([...depositPubkeyHash])
+      .accounts({
+        bridgeState: spv.bridgeState,
+        authority,
+      })
+      .rpc();
+
+    // No confirmations required, so the first header past the checkpoint is confirmed
+    const checkpointHash = createHash("sha256").update("regtest checkpoint").digest();
+    const times = Array.from({ length: 28 }, (_, i) => 1_700_000_000 + i * 75);
+    const bits = Array.from({ length: 28 }, () => 0x200f_0f0f);
+    await program.methods
+      .initializeLightClient(100, [...checkpointHash], times, bits, 0)
+      .accounts({
+        bridgeState: spv.bridgeState,
+        lightClient,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // A fixed recipient keeps the deposit's txid, and the header mined over it, fixed
+    const recipient = anchor.web3.Keypair.fromSeed(
+      createHash("sha256").update("spv deposit recipient").digest()
+    );
+    const value = Buffer.alloc(8);
+    value.writeBigUInt64LE(10_000_000n);
+    const rawTx = Buffer.concat([
+      Buffer.from("04000080", "hex"), // overwintered v4
+      Buffer.from("85202f89", "hex"), // Sapling version group id
+      Buffer.from([1]),
+      Buffer.alloc(36),
+      Buffer.from([0]),
+      Buffer.from("ffffffff", "hex"),
+      Buffer.from([2]),
+      value,
+      Buffer.from([25, 0x76, 0xa9, 0x14]),
+      depositPubkeyHash,
+      Buffer.from([0x88, 0xac]),
+      Buffer.alloc(8),
+      Buffer.from([34, 0x6a, 0x20]),
+      recipient.publicKey.toBuffer(),
+      Buffer.alloc(4 + 4 + 8), // lock time, expiry, value balance
+      Buffer.from([0, 0, 0]),
+    ]);
+    const zcashTxid = [
+      ...createHash("sha256").update(createHash("sha256").update(rawTx).digest()).digest(),
+    ];
+
+    // Block 101 holding only the deposit, so its Merkle root is the txid, with a
+    // valid Equihash (48, 5) solution
+    const header = Buffer.from(
+      "040000004323d45de15d5023129356a153d3fa6bf1d421d4c15d7fa4126bd8816899bfd1" +
+        "aeab8470337885ac1dfe70c429e936abb938f6118ea8fba4ba99fb7fee3b6692" +
+        "0000000000000000000000000000000000000000000000000000000000000000" +
+        "34f953650f0f0f20" +
+        "1700000000000000000000000000000000000000000000000000000000000000" +
+        "240d596b97f492e9dbfc23a55d74f63355ff29199ed2ea729c0e1b97232f9f37d2ecaa6dc8",
+      "hex"
+    );
+    expect(header.subarray(36, 68)).to.deep.equal(Buffer.from(zcashTxid));
+    await program.methods
+      .writeHeaderChunk(0, header)
+      .accounts({
+        lightClient,
+        headerBuffer,
+        submitter: authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .submitZcashHeader()
+      .accounts({
+        lightClient,
+        headerBuffer,
+        submitter: authority,
+      })
+      .rpc();
+
+    const recipientTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        spv.mint,
+        recipient.publicKey
+      )
+    ).address;
+    const feeExemption = await exemptFromFees(spv.bridgeState, recipient.publicKey);
+    await program.methods
+      .mintWithSpvProof(zcashTxid, rawTx, 101, 0, [])
+      .accounts({
+        bridgeState: spv.bridgeState,
+        bridgeStats: spv.bridgeStats,
+        lightClient,
+        depositReceipt: pdaFor("deposit", spv.bridgeState.toBuffer(), Buffer.from(zcashTxid)),
+        legacyDepositReceipt: pdaFor(
+          "deposit",
+          spv.bridgeState.toBuffer(),
+          createHash("sha256").update(Buffer.from(zcashTxid).reverse().toString("hex")).digest()
+        ),
+        mint: spv.mint,
+        recipientTokenAccount,
+        userLimits: pdaFor("user_limits", spv.bridgeState.toBuffer(), recipient.publicKey.toBuffer()),
+        denyListEntry: pdaFor("deny_list", spv.bridgeState.toBuffer(), recipient.publicKey.toBuffer()),
+        feeVault: spv.feeVault,
+        mintAuthority: spv.mintAuthority,
+        payer: authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([feeExemption])
+      .rpc();
+
+    // The bridge charges 5 bps; the exemption waives it
+    const balance = await provider.connection.getTokenAccountBalance(recipientTokenAccount);
+    expect(balance.value.amount).to.equal("10000000");
+  });
+
+  it("Mints a shielded deposit at the recipient's exempt rate", async () => {
+    const shielded = await initializeBridgeFor({ testnet: {} }, 5);
+    const lightClient = pdaFor("light_client", shielded.bridgeState.toBuffer());
+    const verifyingKey = pdaFor("verifying_key", shielded.bridgeState.toBuffer());
+
+    // No confirmations required, so the checkpoint itself is a confirmed block
+    const checkpointHash = createHash("sha256").update("shielded checkpoint").digest();
+    await program.methods
+      .initializeLightClient(
+        100,
+        [...checkpointHash],
+        Array.from({ length: 28 }, (_, i) => 1_700_000_000 + i * 75),
+        Array.from({ length: 28 }, () => 0x200f_0f0f),
+        0
+      )
+      .accounts({
+        bridgeState: shielded.bridgeState,
+        lightClient,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // A key of points at infinity accepts the all-infinity proof, standing in
+    // for the deposit circuit
+    await program.methods
+      .setVerifyingKey(
+        Array(64).fill(0),
+        Array(128).fill(0),
+        Array(128).fill(0),
+        Array(128).fill(0),
+        Array.from({ length: 8 }, () => Array(64).fill(0))
+      )
+      .accounts({
+        bridgeState: shielded.bridgeState,
+        verifyingKey,
+        authority,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const recipient = anchor.web3.Keypair.generate();
+    const recipientTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        shielded.mint,
+        recipient.publicKey
+      )
+    ).address;
+    const feeExemption = await exemptFromFees(shielded.bridgeState, recipient.publicKey);
+    const zcashTxid = txidFor("test_txid_shielded_exempt");
+    await program.methods
+      .mintWithShieldedProof(zcashTxid, 100, new anchor.BN(3_000_000), {
+        a: Array(64).fill(0),
+        b: Array(128).fill(0),
+        c: Array(64).fill(0),
+      })
+      .accounts({
+        bridgeState: shielded.bridgeState,
+        bridgeStats: shielded.bridgeStats,
+        lightClient,
+        verifyingKey,
+        depositReceipt: pdaFor("deposit", shielded.bridgeState.toBuffer(), Buffer.from(zcashTxid)),
+        legacyDepositReceipt: pdaFor(
+          "deposit",
+          shielded.bridgeState.toBuffer(),
+          createHash("sha256").update(Buffer.from(zcashTxid).reverse().toString("hex")).digest()
+        ),
+        mint: shielded.mint,
+        recipientTokenAccount,
+        userLimits: pdaFor("user_limits", shielded.bridgeState.toBuffer(), recipient.publicKey.toBuffer()),
+        denyListEntry: pdaFor("deny_list", shielded.bridgeState.toBuffer(), recipient.publicKey.toBuffer()),
+        feeVault: shielded.feeVault,
+        mintAuthority: shielded.mintAuthority,
+        payer: authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([feeExemption])
+      .rpc();
+
+    // The bridge charges 5 bps; the exemption waives it
+    const balance = await provider.connection.getTokenAccountBalance(recipientTokenAccount);
+    expect(balance.value.amount).to.equal("3000000");
+  });
+
+  it("Pauses mints independently of burns", async () => {
+    await program.methods
+      .setPauseFlags(true, false)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        authority,
+      })
+      .rpc();
+
+    let state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.mintsPaused).to.be.true;
+    expect(state.burnsPaused).to.be.false;
+
+    await program.methods
+      .setPauseFlags(false, false)
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        authority,
+      })
+      .rpc();
+
+    state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.mintsPaused).to.be.false;
+  });
+
+  it("Delegates pausing and fee updates to separate role holders", async () => {
+    const pauser = anchor.web3.Keypair.generate();
+    const feeManager = anchor.web3.Keypair.generate();
+    await program.methods
+      .setRole({ pauser: {} }, pauser.publicKey)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+    await program.methods
+      .setRole({ feeManager: {} }, feeManager.publicKey)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    let state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.roles.pauser.toString()).to.equal(pauser.publicKey.toString());
+    expect(state.roles.feeManager.toString()).to.equal(feeManager.publicKey.toString());
+
+    await program.methods
+      .updateBurnFee(state.burnFeeBps)
+      .accounts({
+        bridgeState,
+        authority: feeManager.publicKey,
+      })
+      .signers([feeManager])
+      .rpc();
+
+    // Each role only carries its own powers
+    try {
+      await program.methods
+        .updateBurnFee(state.burnFeeBps)
+        .accounts({
+          bridgeState,
+          authority: pauser.publicKey,
+        })
+        .signers([pauser])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    await program.methods
+      .pauseBridge()
+      .accounts({
+        bridgeState,
+        authority: pauser.publicKey,
+      })
+      .signers([pauser])
+      .rpc();
+
+    state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.mintsPaused).to.be.true;
+
+    // Revoking the role leaves the pauser powerless
+    await program.methods
+      .setRole({ pauser: {} }, anchor.web3.PublicKey.default)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .pauseBridge()
+        .accounts({
+          bridgeState,
+          authority: pauser.publicKey,
+        })
+        .signers([pauser])
+        .rpc();
+
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+  });
+
+  it("Pauses the bridge", async () => {
+    const tx = await program.methods
+      .pauseBridge()
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    console.log("Pause transaction:", tx);
+
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.mintsPaused).to.be.true;
+    expect(state.burnsPaused).to.be.true;
+  });
+
+  it("Prevents minting when paused", async () => {
+    const amount = new anchor.BN(100_000_000);
+    const zcashTxid = txidFor("test_txid_paused");
+
+    try {
+      await program.methods
+        .mintWzec(amount, zcashTxid, null)
+        .accounts({
+          bridgeState,
+          bridgeStats,
+          guardianSet,
+          depositReceipt: depositReceiptFor(zcashTxid),
+          legacyDepositReceipt: legacyDepositReceiptFor(zcashTxid),
+          mint,
+          mintAuthority,
+          recipientTokenAccount: userTokenAccount,
+          userLimits: userLimitsFor(user.publicKey),
+          denyListEntry: denyListEntryFor(user.publicKey),
+          feeVault,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(guardianSigners(guardians))
+        .signers(guardians)
+        .rpc();
+      
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.toString()).to.include("BridgePaused");
+    }
+  });
+
+  it("Resumes the bridge", async () => {
+    const tx = await program.methods
+      .resumeBridge()
+      .accounts({
+        bridgeState,
+        bridgeStats,
+        authority,
+      })
+      .rpc();
+
+    console.log("Resume transaction:", tx);
+
+    const state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.mintsPaused).to.be.false;
+    expect(state.burnsPaused).to.be.false;
+  });
+
+  it("Cancels an authority proposal", async () => {
+    const proposed = anchor.web3.Keypair.generate().publicKey;
+
+    await program.methods
+      .proposeAuthority(proposed)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    let state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.pendingAuthority.toString()).to.equal(proposed.toString());
+
+    await program.methods
+      .cancelAuthorityProposal()
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.pendingAuthority.toString()).to.equal(anchor.web3.PublicKey.default.toString());
+    expect(state.authority.toString()).to.equal(authority.toString());
+  });
+
+  it("Transfers authority after the proposed key accepts", async () => {
+    const newAuthority = anchor.web3.Keypair.generate();
+
+    const tx = await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accounts({
+        bridgeState,
+        authority,
+      })
+      .rpc();
+
+    console.log("Propose authority transaction:", tx);
+
+    // Authority is unchanged until the proposal is accepted
+    let state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.authority.toString()).to.equal(authority.toString());
+
+    await program.methods
+      .acceptAuthority()
+      .accounts({
+        bridgeState,
+        newAuthority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+
+    state = await program.account.bridgeState.fetch(bridgeState);
+    expect(state.authority.toString()).to.equal(newAuthority.publicKey.toString());
+    expect(state.pendingAuthority.toString()).to.equal(anchor.web3.PublicKey.default.toString());
+  });
+});
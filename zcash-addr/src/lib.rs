@@ -0,0 +1,405 @@
+//! Zcash address handling shared by the relayer, SDK and CLI
+//!
+//! A thin layer over `zcash_address` that parses and validates unified,
+//! Sapling and transparent addresses against a network, builds them from
+//! receivers, and picks the receiver a payment should go to. The on-chain
+//! program has its own decoder (`wzec_bridge::zcash_address`); everything
+//! off-chain goes through this crate.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use zcash_address::unified::{self, Container, Encoding};
+use zcash_address::{ConversionError, ParseError, ToAddress, TryFromAddress, ZcashAddress};
+
+pub use zcash_address::Network;
+
+/// Where one pool delivers funds within an address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Receiver {
+    Orchard([u8; 43]),
+    Sapling([u8; 43]),  // 11-byte diversifier and 32-byte pk_d
+    P2sh([u8; 20]),
+    P2pkh([u8; 20]),
+}
+
+impl Receiver {
+    /// ZIP 316 typecode; higher typecodes are preferred
+    pub fn typecode(&self) -> u32 {
+        match self {
+            Receiver::P2pkh(_) => 0x00,
+            Receiver::P2sh(_) => 0x01,
+            Receiver::Sapling(_) => 0x02,
+            Receiver::Orchard(_) => 0x03,
+        }
+    }
+
+    pub fn is_shielded(&self) -> bool {
+        matches!(self, Receiver::Orchard(_) | Receiver::Sapling(_))
+    }
+
+    /// This receiver alone, as an address on `network`
+    ///
+    /// Orchard has no standalone encoding, so an Orchard receiver becomes a
+    /// unified address holding only that receiver.
+    pub fn to_address(&self, network: Network) -> Address {
+        match self {
+            Receiver::Orchard(_) => Address::unified(network, vec![self.clone()])
+                .expect("a lone Orchard receiver is a valid unified address"),
+            receiver => Address {
+                network,
+                kind: Kind::Single(receiver.clone()),
+            },
+        }
+    }
+
+    fn to_unified(&self) -> unified::Receiver {
+        match self {
+            Receiver::Orchard(data) => unified::Receiver::Orchard(*data),
+            Receiver::Sapling(data) => unified::Receiver::Sapling(*data),
+            Receiver::P2sh(data) => unified::Receiver::P2sh(*data),
+            Receiver::P2pkh(data) => unified::Receiver::P2pkh(*data),
+        }
+    }
+
+    fn from_unified(receiver: &unified::Receiver) -> Option<Self> {
+        match receiver {
+            unified::Receiver::Orchard(data) => Some(Receiver::Orchard(*data)),
+            unified::Receiver::Sapling(data) => Some(Receiver::Sapling(*data)),
+            unified::Receiver::P2sh(data) => Some(Receiver::P2sh(*data)),
+            unified::Receiver::P2pkh(data) => Some(Receiver::P2pkh(*data)),
+            unified::Receiver::Unknown { .. } => None,
+        }
+    }
+}
+
+/// A Zcash address and the network it is encoded for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    network: Network,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    /// Receivers of typecodes this crate doesn't know are kept in `address`
+    /// so the address re-encodes unchanged, but are never paid to
+    Unified {
+        address: unified::Address,
+        receivers: Vec<Receiver>,
+    },
+    /// A Sapling or transparent address
+    Single(Receiver),
+}
+
+impl Address {
+    /// Parse an address for whichever network its encoding names
+    pub fn parse(encoded: &str) -> Result<Self, Error> {
+        ZcashAddress::try_from_encoded(encoded)
+            .map_err(Error::Parse)?
+            .convert::<Address>()
+            .map_err(|error| Error::Unsupported(error.to_string()))
+    }
+
+    /// Parse an address, rejecting encodings for any network but `network`
+    pub fn parse_for(encoded: &str, network: Network) -> Result<Self, Error> {
+        let address = Self::parse(encoded)?;
+        if address.network != network {
+            return Err(Error::WrongNetwork {
+                expected: network,
+                actual: address.network,
+            });
+        }
+        Ok(address)
+    }
+
+    /// A unified address from `receivers`, which need at least one shielded
+    /// receiver and no two of the same type
+    pub fn unified(network: Network, receivers: Vec<Receiver>) -> Result<Self, Error> {
+        let address = unified::Address::try_from_items(receivers.iter().map(Receiver::to_unified).collect())
+            .map_err(Error::Parse)?;
+        Ok(Self::from_unified(network, address))
+    }
+
+    pub fn sapling(network: Network, data: [u8; 43]) -> Self {
+        Receiver::Sapling(data).to_address(network)
+    }
+
+    pub fn p2pkh(network: Network, data: [u8; 20]) -> Self {
+        Receiver::P2pkh(data).to_address(network)
+    }
+
+    pub fn p2sh(network: Network, data: [u8; 20]) -> Self {
+        Receiver::P2sh(data).to_address(network)
+    }
+
+    fn from_unified(network: Network, address: unified::Address) -> Self {
+        let receivers = address.items().iter().filter_map(Receiver::from_unified).collect();
+        Self {
+            network,
+            kind: Kind::Unified { address, receivers },
+        }
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn is_unified(&self) -> bool {
+        matches!(self.kind, Kind::Unified { .. })
+    }
+
+    /// The receivers this crate understands, in encoding order
+    pub fn receivers(&self) -> &[Receiver] {
+        match &self.kind {
+            Kind::Unified { receivers, .. } => receivers,
+            Kind::Single(receiver) => std::slice::from_ref(receiver),
+        }
+    }
+
+    /// The receiver a payment should use: Orchard, then Sapling, then
+    /// transparent, as ZIP 316 orders them
+    pub fn preferred_receiver(&self) -> Option<&Receiver> {
+        self.receivers().iter().max_by_key(|receiver| receiver.typecode())
+    }
+
+    /// The preferred receiver among those that keep the payment shielded
+    pub fn preferred_shielded_receiver(&self) -> Option<&Receiver> {
+        self.preferred_receiver().filter(|receiver| receiver.is_shielded())
+    }
+
+    /// Whether a payment to this address can carry a memo
+    pub fn can_receive_memo(&self) -> bool {
+        self.receivers().iter().any(Receiver::is_shielded)
+    }
+
+    pub fn encode(&self) -> String {
+        match &self.kind {
+            Kind::Unified { address, .. } => address.encode(&self.network),
+            Kind::Single(Receiver::Sapling(data)) => ZcashAddress::from_sapling(self.network, *data).encode(),
+            Kind::Single(Receiver::P2pkh(data)) => ZcashAddress::from_transparent_p2pkh(self.network, *data).encode(),
+            Kind::Single(Receiver::P2sh(data)) => ZcashAddress::from_transparent_p2sh(self.network, *data).encode(),
+            Kind::Single(Receiver::Orchard(_)) => unreachable!("Orchard receivers are always wrapped in a unified address"),
+        }
+    }
+}
+
+impl TryFromAddress for Address {
+    type Error = Infallible;
+
+    fn try_from_sapling(network: Network, data: [u8; 43]) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(Self::sapling(network, data))
+    }
+
+    fn try_from_unified(network: Network, data: unified::Address) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(Self::from_unified(network, data))
+    }
+
+    fn try_from_transparent_p2pkh(network: Network, data: [u8; 20]) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(Self::p2pkh(network, data))
+    }
+
+    fn try_from_transparent_p2sh(network: Network, data: [u8; 20]) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(Self::p2sh(network, data))
+    }
+}
+
+impl FromStr for Address {
+    type Err = Error;
+
+    fn from_str(encoded: &str) -> Result<Self, Self::Err> {
+        Self::parse(encoded)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+/// Why an address was rejected
+#[derive(Debug)]
+pub enum Error {
+    /// Not a valid encoding of any Zcash address
+    Parse(ParseError),
+    /// A valid address of a kind the bridge doesn't handle, such as Sprout
+    Unsupported(String),
+    WrongNetwork { expected: Network, actual: Network },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(error) => write!(f, "invalid Zcash address: {error}"),
+            Error::Unsupported(reason) => write!(f, "unsupported Zcash address: {reason}"),
+            Error::WrongNetwork { expected, actual } => {
+                write!(f, "address is for {actual:?}, expected {expected:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAPLING: &str = "zs1gh7w9vhta6eaq23c0qrv53z0xamgpg099q4cs68epka5gp6evvwj6ugkg2mjdvzyq938cjgu8tg";
+    const TESTNET_SAPLING: &str =
+        "ztestsapling1gh7w9vhta6eaq23c0qrv53z0xamgpg099q4cs68epka5gp6evvwj6ugkg2mjdvzyq938c6lwyvu";
+    const P2PKH: &str = "t1SmxPaFcF5kYFn5ctgCt7QiaXzpTZyF4kG";
+    const P2SH: &str = "t3bXfhDDYoFtXJRTuwUn7ttQYDZMCrrS23T";
+    // P2PKH, Sapling and Orchard receivers
+    const UNIFIED: &str = "u1xqvav52hqhjsas5vwtncd7vrdnyaw6v968985ds9c8yv6amjezndgqzz7cje6cy8hdlnrflnvy84yln07nmftf5gt2s3medxtmu3twpd908yajfqeaemzdpycpkt3enjlj5ye9z56whzqth4k456ursxdp9smsdsttam5uezmnw9msdsz49p7e4sxphxqqj36ym3t2a5kwy7yjj46t9";
+
+    fn round_trip(address: &Address) -> Address {
+        let parsed = Address::parse(&address.encode()).unwrap();
+        assert_eq!(&parsed, address);
+        parsed
+    }
+
+    #[test]
+    fn each_receiver_type_round_trips() {
+        let sapling = round_trip(&Address::sapling(Network::Main, [7; 43]));
+        assert_eq!(sapling.receivers(), [Receiver::Sapling([7; 43])]);
+        assert!(!sapling.is_unified());
+
+        let p2pkh = round_trip(&Address::p2pkh(Network::Test, [8; 20]));
+        assert_eq!(p2pkh.receivers(), [Receiver::P2pkh([8; 20])]);
+        assert!(p2pkh.encode().starts_with("tm"));
+
+        let p2sh = round_trip(&Address::p2sh(Network::Main, [9; 20]));
+        assert_eq!(p2sh.receivers(), [Receiver::P2sh([9; 20])]);
+        assert!(p2sh.encode().starts_with("t3"));
+
+        // Orchard only exists inside a unified address
+        let orchard = round_trip(&Receiver::Orchard([10; 43]).to_address(Network::Main));
+        assert!(orchard.is_unified());
+        assert!(orchard.encode().starts_with("u1"));
+        assert_eq!(orchard.receivers(), [Receiver::Orchard([10; 43])]);
+
+        let unified = round_trip(
+            &Address::unified(
+                Network::Test,
+                vec![Receiver::P2pkh([1; 20]), Receiver::Sapling([2; 43]), Receiver::Orchard([3; 43])],
+            )
+            .unwrap(),
+        );
+        assert!(unified.encode().starts_with("utest1"));
+        assert_eq!(unified.receivers().len(), 3);
+    }
+
+    #[test]
+    fn known_addresses_parse_and_re_encode() {
+        for (encoded, typecode) in [(SAPLING, 0x02), (P2PKH, 0x00), (P2SH, 0x01)] {
+            let address = Address::parse(encoded).unwrap();
+            assert_eq!(address.network(), Network::Main);
+            assert_eq!(address.receivers().len(), 1);
+            assert_eq!(address.receivers()[0].typecode(), typecode);
+            assert_eq!(address.encode(), encoded);
+        }
+
+        let unified = Address::parse(UNIFIED).unwrap();
+        assert!(unified.is_unified());
+        assert_eq!(unified.encode(), UNIFIED);
+    }
+
+    #[test]
+    fn unified_addresses_prefer_orchard_then_sapling() {
+        let unified = Address::parse(UNIFIED).unwrap();
+        assert!(matches!(unified.preferred_receiver(), Some(Receiver::Orchard(_))));
+        assert!(unified.can_receive_memo());
+
+        let without_orchard = Address::unified(
+            Network::Main,
+            vec![Receiver::Sapling([2; 43]), Receiver::P2pkh([1; 20])],
+        )
+        .unwrap();
+        assert_eq!(without_orchard.preferred_receiver(), Some(&Receiver::Sapling([2; 43])));
+        assert_eq!(
+            without_orchard.preferred_shielded_receiver(),
+            Some(&Receiver::Sapling([2; 43]))
+        );
+
+        let transparent = Address::parse(P2PKH).unwrap();
+        assert!(matches!(transparent.preferred_receiver(), Some(Receiver::P2pkh(_))));
+        assert_eq!(transparent.preferred_shielded_receiver(), None);
+        assert!(!transparent.can_receive_memo());
+    }
+
+    #[test]
+    fn unknown_receivers_are_kept_but_not_paid() {
+        let items = vec![
+            unified::Receiver::Sapling([2; 43]),
+            unified::Receiver::Unknown {
+                typecode: 0x30,
+                data: vec![0xab; 16],
+            },
+        ];
+        let encoded = unified::Address::try_from_items(items).unwrap().encode(&Network::Main);
+
+        let address = Address::parse(&encoded).unwrap();
+        assert_eq!(address.receivers(), [Receiver::Sapling([2; 43])]);
+        assert_eq!(address.preferred_receiver(), Some(&Receiver::Sapling([2; 43])));
+        assert_eq!(address.encode(), encoded);
+    }
+
+    #[test]
+    fn addresses_for_another_network_are_rejected() {
+        assert!(Address::parse_for(SAPLING, Network::Main).is_ok());
+        assert!(matches!(
+            Address::parse_for(SAPLING, Network::Test),
+            Err(Error::WrongNetwork {
+                expected: Network::Test,
+                actual: Network::Main,
+            })
+        ));
+        assert!(matches!(
+            Address::parse_for(TESTNET_SAPLING, Network::Main),
+            Err(Error::WrongNetwork {
+                expected: Network::Main,
+                actual: Network::Test,
+            })
+        ));
+        assert!(matches!(
+            Address::parse_for(UNIFIED, Network::Test),
+            Err(Error::WrongNetwork { .. })
+        ));
+    }
+
+    #[test]
+    fn malformed_addresses_are_rejected() {
+        let altered = format!("{}h", &SAPLING[..SAPLING.len() - 1]);
+        let malformed = [
+            "",
+            "not an address",
+            // Truncated, or with the checksum altered
+            &SAPLING[..SAPLING.len() - 4],
+            &altered,
+            &P2PKH[..P2PKH.len() - 1],
+            &UNIFIED[..UNIFIED.len() - 6],
+            // Valid bech32m, but only transparent receivers or the wrong padding
+            "u18mwjdutxhuup5jcveh9unvf483u742dsga7ftmjapk9ra0mdn8f5wg92n708uzqttuv9jx4978sq3kkqlue8jq",
+            "u1ggptn0n2x3lt2s6u0cuh2h8gyety5e0j3jvpwt35zgfkdu98nwalp34ma95ykl6jk3lyp2htlkp63aek7wtclf5mr0mftn529cflr3jdysc9m9e3dpq7c002vmugdh437antzuswp4x",
+        ];
+        for encoded in malformed {
+            assert!(
+                matches!(Address::parse(encoded), Err(Error::Parse(_))),
+                "{encoded:?} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn unified_addresses_need_a_shielded_receiver() {
+        assert!(Address::unified(Network::Main, vec![Receiver::P2pkh([1; 20])]).is_err());
+        assert!(Address::unified(
+            Network::Main,
+            vec![Receiver::Sapling([2; 43]), Receiver::Sapling([3; 43])]
+        )
+        .is_err());
+    }
+}